@@ -0,0 +1,140 @@
+//! Client half of the session audit log (`POST /sessions/{id}/audit` on Agent A)
+//!
+//! The chat sets the sink once with its session id; from then on every LLM decision and
+//! every tool call through `call_server_tool` is reported, with passenger and payment
+//! details redacted by field name. Reporting is best effort: an unreachable Agent A is
+//! logged and never holds up the conversation. CLI subcommands don't set a sink and
+//! report nothing.
+
+use serde_json::{json, Map, Value};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::llm::LlmTurn;
+use crate::WithToken;
+
+/// Fields whose values never leave the client unredacted (compared case-insensitively,
+/// ignoring `_`)
+const REDACTED_FIELDS: &[&str] = &[
+    "passengername",
+    "passengeremail",
+    "email",
+    "piisalt",
+    "cardnumber",
+    "pan",
+    "cvv",
+    "expiry",
+    "cryptogram",
+];
+
+struct Sink {
+    client: reqwest::Client,
+    url: String,
+    token: Option<String>,
+}
+
+static SINK: OnceLock<Sink> = OnceLock::new();
+
+/// Report this chat's events under `session_id`
+pub fn init(client: reqwest::Client, server_url: &str, token: Option<&str>, session_id: &str) {
+    let url = format!("{}/sessions/{}/audit", server_url, session_id);
+    let _ = SINK.set(Sink { client, url, token: token.map(str::to_string) });
+}
+
+/// 0x-prefixed keccak-256 of what the LLM was sent
+pub fn prompt_hash(system: &str, request: &str) -> String {
+    let mut prompt = Vec::with_capacity(system.len() + request.len() + 1);
+    prompt.extend_from_slice(system.as_bytes());
+    prompt.push(0);
+    prompt.extend_from_slice(request.as_bytes());
+    format!("0x{}", hex::encode(ethers::utils::keccak256(prompt)))
+}
+
+/// `latency` is how long the LLM call took; Agent A's `/metrics` reads it with the usage
+pub async fn llm_decision(provider: &str, prompt_hash: String, tools: Vec<String>, turn: &LlmTurn, latency: Duration) {
+    record(json!({
+        "type": "llm_decision",
+        "prompt_hash": prompt_hash,
+        "provider": provider,
+        "tools": tools,
+        "text": turn.text,
+        "latency_ms": latency.as_millis() as u64,
+        "input_tokens": turn.usage.map(|usage| usage.input_tokens),
+        "output_tokens": turn.usage.map(|usage| usage.output_tokens),
+    }))
+    .await;
+}
+
+pub async fn tool_call(tool: &str, arguments: &Value) {
+    record(json!({ "type": "tool_call", "tool": tool, "arguments": redact(arguments) })).await;
+}
+
+/// `result` is the tool's JSON data as a string, as `call_server_tool` returns it
+pub async fn tool_result(tool: &str, result: &anyhow::Result<String>) {
+    let event = match result {
+        Ok(data) => {
+            let data = serde_json::from_str(data).unwrap_or_else(|_| Value::String(data.clone()));
+            json!({ "type": "tool_result", "tool": tool, "success": true, "result": redact(&data) })
+        }
+        Err(e) => json!({ "type": "tool_result", "tool": tool, "success": false, "error": e.to_string() }),
+    };
+    record(event).await;
+}
+
+async fn record(event: Value) {
+    let Some(sink) = SINK.get() else {
+        return;
+    };
+    if let Err(e) = sink.client.post(&sink.url).with_token(sink.token.as_deref()).json(&event).send().await.and_then(|r| r.error_for_status()) {
+        tracing::warn!("Reporting audit event: {}", e);
+    }
+}
+
+/// `value` with every [`REDACTED_FIELDS`] value replaced, at any depth
+pub fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| {
+                    let normalized = key.replace('_', "").to_ascii_lowercase();
+                    let value = if REDACTED_FIELDS.contains(&normalized.as_str()) {
+                        json!("[redacted]")
+                    } else {
+                        redact(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_passenger_and_card_fields() {
+        let args = json!({
+            "from": "NYC",
+            "passenger_name": "Jane Doe",
+            "passengerEmail": "jane@example.com",
+            "legs": [{"Email": "x@example.com", "to": "LON"}],
+            "credentials": {"cryptogram": "abc", "network": "visa"},
+        });
+        let redacted = redact(&args);
+        assert_eq!(redacted["from"], "NYC");
+        assert_eq!(redacted["passenger_name"], "[redacted]");
+        assert_eq!(redacted["passengerEmail"], "[redacted]");
+        assert_eq!(redacted["legs"][0]["Email"], "[redacted]");
+        assert_eq!(redacted["legs"][0]["to"], "LON");
+        assert_eq!(redacted["credentials"]["cryptogram"], "[redacted]");
+        assert_eq!(redacted["credentials"]["network"], "visa");
+
+        assert_ne!(prompt_hash("system", "a"), prompt_hash("system", "b"));
+        assert_ne!(prompt_hash("ab", "c"), prompt_hash("a", "bc"));
+    }
+}
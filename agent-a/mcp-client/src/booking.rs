@@ -0,0 +1,566 @@
+//! Interactive booking session: cancellation intents and idle timeout
+//!
+//! Every prompt in the booking flow goes through [`BookingSession`]. Typing a cancel
+//! intent ("cancel", "stop", "never mind", ...) at any step, or answering after the
+//! session has been idle longer than `BOOKING_IDLE_TIMEOUT_SECS`, ends the booking
+//! and emits a cancellation record.
+//!
+//! Each session also owns the idempotency key sent with its payment and booking
+//! tool calls, so a retried step can't charge or book twice.
+//!
+//! An [`Itinerary`] holds one leg per `get-ticket-price` call (outbound, return, or
+//! further segments); the legs are paid for together and booked as a group. Each leg
+//! keeps the quote it was priced under so stale quotes can be re-priced before payment.
+//! Legs are totalled only when they were all quoted in the same currency. Agent B prices
+//! by departure day, so a leg the user didn't give a date for is asked about and re-priced.
+//!
+//! A booking can carry several [`Passenger`]s; every leg is booked once per passenger,
+//! and their names and emails are redacted from anything echoed to the terminal log.
+//!
+//! Each milestone is reported to Agent A as a [`BookingProgress`] under the chat session
+//! id, so a resume intent ("continue my booking") after a reconnect or an expired
+//! prompt picks the booking up from its last step, and a refund intent ("cancel my
+//! booking") after it completed cancels the trip and returns what the fare rules allow.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::{BufRead, Write};
+use std::time::{Duration, Instant};
+
+/// Upper bound on passengers per booking, matching typical airline group limits
+pub const MAX_PASSENGERS: usize = 9;
+
+const CANCEL_INTENTS: &[&str] = &["cancel", "stop", "abort", "quit", "exit", "never mind", "nevermind"];
+const RESUME_INTENTS: &[&str] = &[
+    "resume",
+    "continue",
+    "continue my booking",
+    "continue booking",
+    "where was i",
+    "where did i leave off",
+    "pick up where i left off",
+];
+const REFUND_INTENTS: &[&str] = &[
+    "refund",
+    "i want a refund",
+    "refund my booking",
+    "cancel my booking",
+    "cancel booking",
+    "cancel my flight",
+    "cancel my trip",
+];
+
+/// One flight segment
+#[derive(Debug, Clone, Serialize)]
+pub struct Leg {
+    pub from: String,
+    pub to: String,
+    /// Departure day as given by the user/LLM (YYYY-MM-DD), if any
+    pub date: Option<String>,
+    /// In `currency`'s minor units (cents; whole yen for JPY)
+    pub price_cents: Option<u64>,
+    /// ISO 4217 code of `price_cents`
+    pub currency: String,
+    /// Promo code Agent B applied to this leg
+    pub promo_code: Option<String>,
+    /// Amount the promo took off `price_cents`
+    pub discount_cents: u64,
+    /// Agent B quote the price was issued under
+    pub quote_id: Option<String>,
+    /// Unix seconds after which the quote must be re-priced
+    pub expires_at: Option<u64>,
+    /// Day Agent B counted the advance-purchase tier from; a price proof must use the same one
+    pub purchase_date: Option<String>,
+    /// zkVM program (and its ELF hash) Agent B signed into the quote, for proving the price
+    pub program_id: Option<String>,
+    pub elf_hash: Option<String>,
+    /// `get-ticket-price` arguments, kept for re-pricing
+    #[serde(skip)]
+    pub pricing_args: Value,
+}
+
+impl Leg {
+    /// Leg described by `get-ticket-price` arguments; a `date` argument is renamed to the
+    /// tool's `departure_date`
+    pub fn from_pricing_args(args: &mut Value) -> Self {
+        let field = |name: &str| args.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let (from, to) = (field("from"), field("to"));
+        if let Some(date) = args.as_object_mut().and_then(|obj| obj.remove("date")) {
+            args["departure_date"] = date;
+        }
+        let date = args.get("departure_date").and_then(|d| d.as_str()).map(str::to_string);
+        Self {
+            from,
+            to,
+            date,
+            price_cents: None,
+            currency: "USD".to_string(),
+            promo_code: None,
+            discount_cents: 0,
+            quote_id: None,
+            expires_at: None,
+            purchase_date: None,
+            program_id: None,
+            elf_hash: None,
+            pricing_args: args.clone(),
+        }
+    }
+
+    /// Take price and quote from a `get-ticket-price` result
+    pub fn apply_quote(&mut self, result: &Value) {
+        self.price_cents = result.get("price_cents").and_then(|p| p.as_u64());
+        // Agent B builds without currency support only quote USD
+        self.currency = result.get("currency").and_then(|c| c.as_str()).unwrap_or("USD").to_string();
+        self.promo_code = result.get("promo_code").and_then(|p| p.as_str()).map(str::to_string);
+        self.discount_cents = result.get("discount_cents").and_then(|d| d.as_u64()).unwrap_or_default();
+        self.quote_id = result.get("quote_id").and_then(|q| q.as_str()).map(str::to_string);
+        self.expires_at = result.get("expires_at").and_then(|e| e.as_u64());
+        let text = |field: &str| result.get(field).and_then(|v| v.as_str()).map(str::to_string);
+        self.purchase_date = text("purchase_date");
+        self.program_id = text("program_id");
+        self.elf_hash = text("elf_hash");
+    }
+
+    /// `format_zk_input` input proving this leg's quote: the pricing request bound to the
+    /// quote it was issued under
+    pub fn zk_request(&self) -> Value {
+        let mut request = self.pricing_args.clone();
+        request["quote_id"] = Value::from(self.quote_id.clone());
+        request["currency"] = Value::from(self.currency.clone());
+        request["purchase_date"] = Value::from(self.purchase_date.clone());
+        request
+    }
+
+    /// Set the departure day; the leg needs re-pricing afterwards
+    pub fn set_date(&mut self, date: &str) {
+        self.date = Some(date.to_string());
+        self.pricing_args["departure_date"] = Value::from(date);
+    }
+
+    /// Quotes without an expiry never go stale
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    fn label(&self) -> String {
+        match &self.date {
+            Some(date) => format!("{} → {} on {}", self.from, self.to, date),
+            None => format!("{} → {}", self.from, self.to),
+        }
+    }
+}
+
+/// Legs of one booking, in travel order
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Itinerary {
+    pub legs: Vec<Leg>,
+}
+
+impl Itinerary {
+    /// Sum of leg prices; `None` unless every leg is priced in the same currency
+    pub fn total(&self) -> Option<u64> {
+        if self.legs.is_empty() || self.legs.iter().any(|leg| leg.currency != self.currency()) {
+            return None;
+        }
+        self.legs.iter().map(|leg| leg.price_cents).sum()
+    }
+
+    /// Currency of the first leg (USD for an empty itinerary)
+    pub fn currency(&self) -> &str {
+        self.legs.first().map_or("USD", |leg| leg.currency.as_str())
+    }
+
+    /// Minor-unit `amount` in the itinerary's currency, e.g. "$679.90" or "¥101645"
+    pub fn format(&self, amount: u64) -> String {
+        format_price(amount, self.currency())
+    }
+
+    /// "NYC → London → NYC" (legs that don't connect are joined with "; ")
+    pub fn route(&self) -> String {
+        let mut route = String::new();
+        let mut last_to: Option<&str> = None;
+        for leg in &self.legs {
+            match last_to {
+                Some(to) if to.eq_ignore_ascii_case(&leg.from) => {}
+                Some(_) => route.push_str(&format!("; {}", leg.from)),
+                None => route.push_str(&leg.from),
+            }
+            route.push_str(&format!(" → {}", leg.to));
+            last_to = Some(&leg.to);
+        }
+        route
+    }
+
+    /// Per-leg price lines for the confirmation prompt
+    pub fn describe(&self) -> String {
+        self.legs
+            .iter()
+            .enumerate()
+            .map(|(i, leg)| match (leg.price_cents, &leg.promo_code) {
+                (Some(price), Some(code)) => format!(
+                    "  {}. {}: {} ({} saved {})\n",
+                    i + 1,
+                    leg.label(),
+                    format_price(price, &leg.currency),
+                    code,
+                    format_price(leg.discount_cents, &leg.currency)
+                ),
+                (Some(price), None) => format!("  {}. {}: {}\n", i + 1, leg.label(), format_price(price, &leg.currency)),
+                (None, _) => format!("  {}. {}: (no price)\n", i + 1, leg.label()),
+            })
+            .collect()
+    }
+
+    /// Indices of legs whose quote has lapsed at `now` (unix seconds)
+    pub fn expired_legs(&self, now: u64) -> Vec<usize> {
+        (0..self.legs.len()).filter(|&i| self.legs[i].is_expired(now)).collect()
+    }
+
+    pub fn leg_label(&self, i: usize) -> String {
+        self.legs.get(i).map(Leg::label).unwrap_or_default()
+    }
+
+    /// Legs without a departure day
+    pub fn undated_legs(&self) -> Vec<usize> {
+        (0..self.legs.len()).filter(|&i| self.legs[i].date.is_none()).collect()
+    }
+}
+
+/// Milestone of a booking, as stored by Agent A (`POST /session/{id}`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookingStage {
+    Quoted,
+    Confirmed,
+    PassengersEntered,
+    Paid,
+    Booked,
+    Cancelled,
+}
+
+/// Where a booking stands; passenger details stay out of it apart from the count
+#[derive(Debug, Clone, Serialize)]
+pub struct BookingProgress {
+    pub stage: BookingStage,
+    pub route: String,
+    pub total_cents: Option<u64>,
+    pub currency: String,
+    pub passengers: usize,
+    /// `get-ticket-price` arguments per leg, replayed on resume
+    pub pricing_calls: Vec<Value>,
+    pub payment_method: Option<String>,
+    pub confirmation_codes: Vec<String>,
+    /// Agent B booking ids, cancelled on a refund intent
+    pub booking_ids: Vec<String>,
+    /// Card purchase instruction id or crypto transaction hash
+    pub payment_reference: Option<String>,
+}
+
+impl BookingProgress {
+    /// `itinerary` at `stage`, with `total` covering all `passengers`
+    pub fn new(stage: BookingStage, itinerary: &Itinerary, total: Option<u64>, passengers: usize) -> Self {
+        Self {
+            stage,
+            route: itinerary.route(),
+            total_cents: total,
+            currency: itinerary.currency().to_string(),
+            passengers,
+            pricing_calls: itinerary.legs.iter().map(|leg| leg.pricing_args.clone()).collect(),
+            payment_method: None,
+            confirmation_codes: Vec::new(),
+            booking_ids: Vec::new(),
+            payment_reference: None,
+        }
+    }
+}
+
+/// `YYYY-MM-DD` with a plausible month and day; Agent B rejects days that don't exist
+pub fn is_travel_date(input: &str) -> bool {
+    let bytes = input.as_bytes();
+    let digits = |range: std::ops::Range<usize>| bytes[range].iter().all(u8::is_ascii_digit);
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' || !digits(0..4) || !digits(5..7) || !digits(8..10) {
+        return false;
+    }
+    let month: u32 = input[5..7].parse().unwrap_or_default();
+    let day: u32 = input[8..10].parse().unwrap_or_default();
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+/// Minor units as a decimal string: 67990 USD → "679.90", 101645 JPY → "101645"
+pub fn format_amount(price_cents: u64, currency: &str) -> String {
+    if currency.eq_ignore_ascii_case("JPY") {
+        price_cents.to_string()
+    } else {
+        format!("{}.{:02}", price_cents / 100, price_cents % 100)
+    }
+}
+
+/// Amount with its currency symbol; codes without one are appended ("12.50 CHF")
+pub fn format_price(price_cents: u64, currency: &str) -> String {
+    let amount = format_amount(price_cents, currency);
+    match currency.to_ascii_uppercase().as_str() {
+        "USD" => format!("${}", amount),
+        "EUR" => format!("€{}", amount),
+        "GBP" => format!("£{}", amount),
+        "JPY" => format!("¥{}", amount),
+        other => format!("{} {}", amount, other),
+    }
+}
+
+/// Traveller on a booking; both fields are PII
+#[derive(Debug, Clone, Serialize)]
+pub struct Passenger {
+    pub name: String,
+    pub email: String,
+}
+
+/// Replace every passenger's name and email in `text` with a placeholder
+pub fn redact_pii(text: &str, passengers: &[Passenger]) -> String {
+    let mut redacted = text.to_string();
+    for (i, passenger) in passengers.iter().enumerate() {
+        for (value, field) in [(&passenger.email, "email"), (&passenger.name, "name")] {
+            if !value.trim().is_empty() {
+                redacted = redacted.replace(value.as_str(), &format!("[passenger {} {}]", i + 1, field));
+            }
+        }
+    }
+    redacted
+}
+
+/// "continue my booking" and the like: pick up this session's unfinished booking
+pub fn is_resume_intent(input: &str) -> bool {
+    let input = input.trim().trim_end_matches(['.', '!', '?']).to_lowercase();
+    RESUME_INTENTS.contains(&input.as_str())
+}
+
+/// "cancel my booking" and the like: cancel this session's completed booking for a refund
+///
+/// Plain "cancel" is not one; mid-booking it stops the flow without charging.
+pub fn is_refund_intent(input: &str) -> bool {
+    let input = input.trim().trim_end_matches(['.', '!', '?']).to_lowercase();
+    REFUND_INTENTS.contains(&input.as_str())
+}
+
+/// Step of the booking flow a prompt belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookingStep {
+    TravelDate,
+    ConfirmPrice,
+    PassengerCount,
+    PassengerName,
+    PassengerEmail,
+    PaymentMethod,
+    EnrollCard,
+    ConfirmPayment,
+    ApprovePayment,
+    ConfirmRefund,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    UserRequested,
+    IdleTimeout,
+}
+
+/// Emitted when a booking ends without completing
+#[derive(Debug, Clone, Serialize)]
+pub struct CancellationRecord {
+    pub route: String,
+    pub step: BookingStep,
+    pub reason: CancelReason,
+    pub idle_secs: u64,
+}
+
+pub fn is_cancel_intent(input: &str) -> bool {
+    let input = input.trim().to_lowercase();
+    CANCEL_INTENTS
+        .iter()
+        .any(|intent| input == *intent || input.starts_with(&format!("{} ", intent)))
+}
+
+pub struct BookingSession {
+    route: String,
+    idle_timeout: Duration,
+    last_activity: Instant,
+    idempotency_key: String,
+}
+
+impl BookingSession {
+    pub fn new(route: &str, idle_timeout: Duration) -> Self {
+        Self {
+            route: route.to_string(),
+            idle_timeout,
+            last_activity: Instant::now(),
+            idempotency_key: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// Key for this booking attempt's payment/booking calls (`Idempotency-Key`)
+    pub fn idempotency_key(&self) -> &str {
+        &self.idempotency_key
+    }
+
+    /// Free-text answer, or `None` if the booking was cancelled or expired
+    pub fn ask<R: BufRead, W: Write>(
+        &mut self,
+        step: BookingStep,
+        prompt: &str,
+        reader: &mut R,
+        stdout: &mut W,
+    ) -> Result<Option<String>> {
+        write!(stdout, "{}", prompt)?;
+        stdout.flush()?;
+
+        let mut input = String::new();
+        reader.read_line(&mut input)?;
+
+        let idle = self.last_activity.elapsed();
+        if idle > self.idle_timeout {
+            self.cancel(step, CancelReason::IdleTimeout, idle, stdout)?;
+            return Ok(None);
+        }
+        if is_cancel_intent(&input) {
+            self.cancel(step, CancelReason::UserRequested, idle, stdout)?;
+            return Ok(None);
+        }
+        self.last_activity = Instant::now();
+        Ok(Some(input.trim().to_string()))
+    }
+
+    /// y/n answer, or `None` if the booking was cancelled or expired
+    pub fn confirm<R: BufRead, W: Write>(
+        &mut self,
+        step: BookingStep,
+        question: &str,
+        reader: &mut R,
+        stdout: &mut W,
+    ) -> Result<Option<bool>> {
+        loop {
+            let Some(answer) = self.ask(step, &format!("{} [y/n] ", question), reader, stdout)? else {
+                return Ok(None);
+            };
+            match answer.to_lowercase().as_str() {
+                "y" | "yes" => return Ok(Some(true)),
+                "n" | "no" => return Ok(Some(false)),
+                _ => writeln!(stdout, "Please answer 'y' or 'n' (or 'cancel' to stop booking).")?,
+            }
+        }
+    }
+
+    fn cancel<W: Write>(
+        &self,
+        step: BookingStep,
+        reason: CancelReason,
+        idle: Duration,
+        stdout: &mut W,
+    ) -> Result<()> {
+        let record = CancellationRecord {
+            route: self.route.clone(),
+            step,
+            reason,
+            idle_secs: idle.as_secs(),
+        };
+        match reason {
+            CancelReason::UserRequested if step == BookingStep::ConfirmRefund => {
+                writeln!(stdout, "\nAgent A: Okay, your booking stays as it is.\n")?
+            }
+            CancelReason::UserRequested => writeln!(stdout, "\nAgent A: Okay, I've cancelled the booking. Nothing was charged.\n")?,
+            CancelReason::IdleTimeout => writeln!(
+                stdout,
+                "\nAgent A: This booking expired after {}s of inactivity. Say \"continue my booking\" to pick it up again.\n",
+                self.idle_timeout.as_secs()
+            )?,
+        }
+        tracing::info!(record = %serde_json::to_string(&record).unwrap_or_default(), "booking cancelled");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_intent_at_any_step() {
+        let mut session = BookingSession::new("NYC → London", Duration::from_secs(300));
+        let mut out = Vec::new();
+
+        let mut reader = "Jane Doe\n".as_bytes();
+        let name = session.ask(BookingStep::PassengerName, "", &mut reader, &mut out).unwrap();
+        assert_eq!(name.as_deref(), Some("Jane Doe"));
+
+        let mut reader = "cancel\n".as_bytes();
+        let choice = session.ask(BookingStep::PaymentMethod, "", &mut reader, &mut out).unwrap();
+        assert!(choice.is_none());
+
+        assert!(is_cancel_intent("Never mind"));
+        assert!(!is_cancel_intent("Stockholm"));
+
+        assert!(is_resume_intent("Continue my booking!"));
+        assert!(is_resume_intent("where was I?"));
+        assert!(!is_resume_intent("continue to London on Friday"));
+
+        assert!(is_refund_intent("Cancel my booking."));
+        assert!(is_refund_intent("I want a refund"));
+        assert!(!is_refund_intent("cancel"));
+        assert!(!is_resume_intent("cancel my booking"));
+    }
+
+    #[test]
+    fn test_round_trip_itinerary() {
+        let mut args = serde_json::json!({ "from": "NYC", "to": "London", "vip": false, "date": "2026-11-02" });
+        let mut outbound = Leg::from_pricing_args(&mut args);
+        assert!(args.get("date").is_none());
+        assert_eq!(outbound.pricing_args["departure_date"], "2026-11-02");
+        assert_eq!(outbound.date.as_deref(), Some("2026-11-02"));
+        outbound.apply_quote(&serde_json::json!({ "price_cents": 45_000, "quote_id": "q_1", "expires_at": 1_000, "program_id": "price-v1" }));
+        assert_eq!(outbound.program_id.as_deref(), Some("price-v1"));
+        let zk_request = outbound.zk_request();
+        assert_eq!(zk_request["quote_id"], "q_1");
+        assert_eq!(zk_request["currency"], "USD");
+        assert_eq!(zk_request["departure_date"], "2026-11-02");
+        assert!(zk_request["purchase_date"].is_null());
+
+        let mut itinerary = Itinerary { legs: vec![outbound] };
+        assert_eq!(itinerary.total(), Some(45_000));
+
+        let mut args = serde_json::json!({ "from": "London", "to": "NYC", "vip": false });
+        itinerary.legs.push(Leg::from_pricing_args(&mut args));
+        assert_eq!(itinerary.total(), None);
+        assert_eq!(itinerary.undated_legs(), vec![1]);
+        itinerary.legs[1].set_date("2026-11-09");
+        assert_eq!(itinerary.legs[1].pricing_args["departure_date"], "2026-11-09");
+        assert!(itinerary.undated_legs().is_empty());
+        assert!(is_travel_date("2026-11-09"));
+        assert!(!is_travel_date("next friday"));
+        assert!(!is_travel_date("2026-13-09"));
+        itinerary.legs[1].price_cents = Some(40_000);
+        assert_eq!(itinerary.total(), Some(85_000));
+        assert_eq!(itinerary.format(85_000), "$850.00");
+        assert_eq!(itinerary.route(), "NYC → London → NYC");
+        assert_eq!(itinerary.expired_legs(999), Vec::<usize>::new());
+        assert_eq!(itinerary.expired_legs(1_000), vec![0]);
+
+        itinerary.legs[1].apply_quote(&serde_json::json!({ "price_cents": 35_000, "currency": "EUR" }));
+        assert_eq!(itinerary.total(), None);
+
+        itinerary.legs[0].apply_quote(&serde_json::json!({ "price_cents": 40_500, "promo_code": "WELCOME10", "discount_cents": 4_500 }));
+        assert!(itinerary.describe().contains("$405.00 (WELCOME10 saved $45.00)"));
+        assert_eq!(format_price(101_645, "JPY"), "¥101645");
+    }
+
+    #[test]
+    fn test_redacts_every_passengers_pii() {
+        let passengers = [
+            Passenger { name: "Jane Doe".into(), email: "jane@example.com".into() },
+            Passenger { name: "John Doe".into(), email: "john@example.com".into() },
+        ];
+        let args = r#"{"passenger_name":"John Doe","passenger_email":"john@example.com","to":"London"}"#;
+        let redacted = redact_pii(args, &passengers);
+        assert!(!redacted.contains("John") && !redacted.contains("john@"));
+        assert!(redacted.contains("[passenger 2 email]") && redacted.contains("London"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Command-line interface: `chat` (default) plus non-interactive subcommands
+//!
+//! The subcommands call the same tool endpoints the chat flow uses, print JSON on
+//! stdout (progress goes to stderr), and exit non-zero on failure, so they can be
+//! scripted.
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tool_envelope::{parse_tool_response, tool_list};
+
+use crate::{fetch_tool_definitions, AgentConfig, WithToken};
+
+/// How often `attest` polls the attestation job
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Parser)]
+#[command(name = "mcp-client-ai", about = "Agent A client: chat, pricing, attestation, and proof tools")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Interactive chat with the LLM-driven booking flow (default)
+    Chat {
+        /// Pick up this session's booking (the id printed when a chat starts)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Quote a flight from Agent B
+    Price {
+        #[arg(long)]
+        from: String,
+        #[arg(long)]
+        to: String,
+        #[arg(long)]
+        vip: bool,
+    },
+    /// Prove a program run on the attester and wait for the proof
+    Attest {
+        #[arg(long)]
+        program_id: String,
+        /// zkVM input bytes, hex (0x optional)
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        claimed_output: Option<String>,
+        /// Record the proof under this session (see `proofs export`)
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Verify a proof bundle (single or session export) on-chain
+    Verify {
+        #[arg(long)]
+        proof_file: PathBuf,
+    },
+    /// Session proof bundles
+    Proofs {
+        #[command(subcommand)]
+        command: ProofsCommand,
+    },
+    /// Check connectivity to every configured server
+    Doctor,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProofsCommand {
+    /// Download every proof recorded for a session
+    Export {
+        #[arg(long)]
+        session: String,
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Run a non-interactive subcommand (`chat` is handled by the caller)
+pub async fn run(command: Command, config: &AgentConfig) -> Result<()> {
+    let client = reqwest::Client::new();
+    match command {
+        Command::Chat { .. } => Err(anyhow!("chat is interactive; run it without a subcommand")),
+        Command::Price { from, to, vip } => {
+            let quote = post_tool(&client, &config.agent_b_url, None, "get-ticket-price", json!({ "from": from, "to": to, "vip": vip })).await?;
+            print_json(&quote)
+        }
+        Command::Attest { program_id, input, claimed_output, session } => {
+            let job = post_tool(
+                &client,
+                &config.server_url,
+                config.agent_a_token(),
+                "request_attestation",
+                json!({
+                    "program_id": program_id,
+                    "input_hex": input,
+                    "claimed_output": claimed_output,
+                    "session_id": session
+                }),
+            )
+            .await?;
+            let status_url = job
+                .get("status_url")
+                .and_then(|u| u.as_str())
+                .ok_or_else(|| anyhow!("no status_url in response: {}", job))?;
+            print_json(&wait_for_job(&client, &format!("{}{}", config.server_url, status_url), config.agent_a_token()).await?)
+        }
+        Command::Verify { proof_file } => verify(&client, config, &proof_file).await,
+        Command::Proofs { command: ProofsCommand::Export { session, out } } => {
+            let url = format!("{}/sessions/{}/proof-bundle", config.server_url, session);
+            let bundle = unwrap_envelope(client.get(&url).with_token(config.agent_a_token()).send().await?.json().await?)?;
+            match out {
+                Some(path) => {
+                    std::fs::write(&path, serde_json::to_string_pretty(&bundle)?)?;
+                    eprintln!("✓ Wrote {} proofs to {}", bundle["proofs"].as_array().map_or(0, Vec::len), path.display());
+                    Ok(())
+                }
+                None => print_json(&bundle),
+            }
+        }
+        Command::Doctor => doctor(&client, config).await,
+    }
+}
+
+/// POST `/tools/{tool}` on `base_url` and unwrap the response envelope
+async fn post_tool(
+    client: &reqwest::Client,
+    base_url: &str,
+    token: Option<&str>,
+    tool: &str,
+    arguments: Value,
+) -> Result<Value> {
+    let response = client
+        .post(format!("{}/tools/{}", base_url, tool))
+        .with_token(token)
+        .json(&arguments)
+        .send()
+        .await?;
+    unwrap_envelope(response.json().await?)
+}
+
+fn unwrap_envelope(response: Value) -> Result<Value> {
+    Ok(parse_tool_response(&response)?.clone())
+}
+
+fn print_json(value: &Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Poll an attestation job until it is done (returns the status view) or failed
+async fn wait_for_job(client: &reqwest::Client, status_url: &str, token: Option<&str>) -> Result<Value> {
+    loop {
+        let view = unwrap_envelope(client.get(status_url).with_token(token).send().await?.json().await?)?;
+        match view.get("status").and_then(|s| s.as_str()) {
+            Some("done") => return Ok(view),
+            Some("failed") => return Err(anyhow!("attestation failed: {}", view)),
+            status => eprintln!("… {}", status.unwrap_or("unknown")),
+        }
+        tokio::time::sleep(JOB_POLL_INTERVAL).await;
+    }
+}
+
+/// Verify every bundle in the file: a session export (`proofs[].bundle`) or one bundle
+async fn verify(client: &reqwest::Client, config: &AgentConfig, proof_file: &PathBuf) -> Result<()> {
+    let file: Value = serde_json::from_str(&std::fs::read_to_string(proof_file)?)?;
+    let bundles: Vec<(Option<&str>, &Value)> = match file.get("proofs").and_then(|p| p.as_array()) {
+        Some(proofs) => proofs
+            .iter()
+            .map(|p| (p.get("kind").and_then(|k| k.as_str()), &p["bundle"]))
+            .collect(),
+        None => vec![(None, &file)],
+    };
+
+    let mut results = Vec::new();
+    for (kind, bundle) in bundles {
+        let claim_type = match kind {
+            Some("price") => Some("pricing"),
+            Some("booking") => Some("booking"),
+            _ => None,
+        };
+        let result = post_tool(
+            client,
+            &config.server_url,
+            config.agent_a_token(),
+            "verify_on_chain",
+            json!({
+                "proof": bundle["proof"],
+                "public_values": bundle["public_values"],
+                "vk_hash": bundle["vk_hash"],
+                "claim_type": claim_type
+            }),
+        )
+        .await?;
+        results.push(result);
+    }
+
+    let all_verified = results.iter().all(|r| r.get("verified").and_then(|v| v.as_bool()) == Some(true));
+    print_json(&json!(results))?;
+    if all_verified {
+        Ok(())
+    } else {
+        Err(anyhow!("not every proof verified"))
+    }
+}
+
+/// One line per server; fails if any required server is unreachable
+async fn doctor(client: &reqwest::Client, config: &AgentConfig) -> Result<()> {
+    let mut servers = vec![
+        ("Agent A server", config.server_url.as_str(), config.agent_a_token()),
+        ("Agent B MCP server", config.agent_b_url.as_str(), None),
+    ];
+    if let (true, Some(url)) = (config.payment_agent_enabled, config.payment_agent_url.as_deref()) {
+        servers.push(("Payment agent", url, None));
+    }
+
+    let mut failures = 0;
+    for (name, url, token) in servers {
+        match fetch_tool_definitions(client, url, token).await {
+            Ok(response) => {
+                let tools = tool_list(&response).map_or(0, Vec::len);
+                println!("✓ {} ({}): {} tools", name, url, tools);
+            }
+            Err(e) => {
+                println!("✗ {} ({}): {}", name, url, e);
+                failures += 1;
+            }
+        }
+    }
+    println!("✓ LLM provider: {:?}", config.llm_provider);
+
+    if failures > 0 {
+        return Err(anyhow!("{} server(s) unreachable", failures));
+    }
+    Ok(())
+}
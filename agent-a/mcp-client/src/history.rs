@@ -0,0 +1,143 @@
+//! Conversation history with automatic compaction
+//!
+//! Turns are kept verbatim until the estimated size exceeds `HISTORY_TOKEN_BUDGET`;
+//! then everything but the last `HISTORY_KEEP_TURNS` turns is summarized by the LLM
+//! into a compact note. Booking facts (route, passenger, confirmation code) are kept
+//! separately so a summary can never drop them.
+
+use crate::llm::LlmProvider;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+const COMPACT_PROMPT: &str = "Summarize this conversation between a traveller and Agent A, a travel coordinator, \
+in at most five short sentences. Keep routes, prices, names, and anything the traveller still wants done.";
+
+/// Rough token estimate (≈4 characters per token), good enough for budgeting
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+#[derive(Debug, Clone)]
+struct Turn {
+    role: Role,
+    text: String,
+}
+
+pub struct Conversation {
+    turns: Vec<Turn>,
+    summary: Option<String>,
+    facts: BTreeMap<&'static str, String>,
+    token_budget: usize,
+    keep_turns: usize,
+}
+
+impl Conversation {
+    pub fn new(token_budget: usize, keep_turns: usize) -> Self {
+        Self {
+            turns: Vec::new(),
+            summary: None,
+            facts: BTreeMap::new(),
+            token_budget,
+            keep_turns: keep_turns.max(1),
+        }
+    }
+
+    pub fn push(&mut self, role: Role, text: impl Into<String>) {
+        let text = text.into();
+        if !text.trim().is_empty() {
+            self.turns.push(Turn { role, text });
+        }
+    }
+
+    /// Record a booking fact that must survive compaction
+    pub fn remember(&mut self, key: &'static str, value: impl Into<String>) {
+        self.facts.insert(key, value.into());
+    }
+
+    fn estimated_tokens(&self) -> usize {
+        self.summary.as_deref().map(estimate_tokens).unwrap_or(0)
+            + self.turns.iter().map(|t| estimate_tokens(&t.text)).sum::<usize>()
+    }
+
+    /// Summarize older turns if over budget; falls back to dropping them if the LLM fails
+    pub async fn compact_if_needed(&mut self, llm: &dyn LlmProvider) {
+        if self.estimated_tokens() <= self.token_budget || self.turns.len() <= self.keep_turns {
+            return;
+        }
+
+        let older: Vec<Turn> = self.turns.drain(..self.turns.len() - self.keep_turns).collect();
+        let mut transcript = String::new();
+        if let Some(summary) = &self.summary {
+            transcript.push_str(&format!("Earlier summary: {}\n", summary));
+        }
+        transcript.push_str(&render_turns(&older));
+
+        match llm.chat(COMPACT_PROMPT, &transcript, &json!({ "tools": [] })).await {
+            Ok(turn) if !turn.text.trim().is_empty() => self.summary = Some(turn.text.trim().to_string()),
+            Ok(_) => {}
+            Err(e) => tracing::debug!("History compaction failed, dropping {} turns: {}", older.len(), e),
+        }
+    }
+
+    /// Extra system prompt carrying the summary and booking facts, if any
+    pub fn system_note(&self) -> Option<String> {
+        let mut note = String::new();
+        if let Some(summary) = &self.summary {
+            note.push_str(&format!("Earlier in this conversation: {}\n", summary));
+        }
+        if !self.facts.is_empty() {
+            note.push_str("Booking facts:\n");
+            for (key, value) in &self.facts {
+                note.push_str(&format!("- {}: {}\n", key, value));
+            }
+        }
+        (!note.is_empty()).then_some(note)
+    }
+
+    /// User message for the next turn: recent turns verbatim, then the new request
+    pub fn render(&self, request: &str) -> String {
+        if self.turns.is_empty() {
+            return request.to_string();
+        }
+        format!("Conversation so far:\n{}\nCurrent request: {}", render_turns(&self.turns), request)
+    }
+}
+
+fn render_turns(turns: &[Turn]) -> String {
+    turns
+        .iter()
+        .map(|t| match t.role {
+            Role::User => format!("User: {}\n", t.text),
+            Role::Assistant => format!("Agent A: {}\n", t.text),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockProvider;
+
+    #[tokio::test]
+    async fn test_compaction_keeps_recent_turns_and_facts() {
+        let mut conversation = Conversation::new(10, 2);
+        conversation.remember("route", "NYC → London");
+        for i in 0..5 {
+            conversation.push(Role::User, format!("question number {}", i));
+        }
+
+        conversation.compact_if_needed(&MockProvider).await;
+
+        assert_eq!(conversation.turns.len(), 2);
+        assert!(conversation.render("next").contains("question number 4"));
+        let note = conversation.system_note().unwrap();
+        assert!(note.contains("Earlier in this conversation"));
+        assert!(note.contains("route: NYC → London"));
+    }
+}
@@ -0,0 +1,1812 @@
+//! Agent A - AI-powered MCP Client
+//!
+//! This client:
+//! 1. Takes user queries via stdin
+//! 2. Asks the configured LLM (native tool use) to pick a tool and extract its arguments
+//! 3. Invokes the MCP server via HTTP with the appropriate tool
+//! 4. Returns results to the user
+//!
+//! Requires: ANTHROPIC_API_KEY (default provider), or LLM_PROVIDER=openai|mock (see `llm`)
+//! Usage: mcp-client-ai [chat|price|attest|verify|proofs export|doctor] (see `cli`;
+//! loads from .env or env vars)
+//!
+//! The binary only calls [`run`]; the chat itself is a [`Chat`], which the integration
+//! tests in `tests/` drive with scripted input against mock servers.
+
+mod audit;
+mod booking;
+mod cli;
+mod history;
+pub mod llm;
+mod payment_client;
+mod policy;
+mod prompts;
+mod proof_verify;
+mod registry;
+
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use agent_config::{ConfigError, Validate};
+use tool_envelope::{parse_tool_response, tool_list};
+use booking::{
+    format_amount, format_price, is_refund_intent, is_resume_intent, is_travel_date, redact_pii, BookingProgress,
+    BookingSession, BookingStage, BookingStep, Itinerary, Leg, Passenger, MAX_PASSENGERS,
+};
+use history::{Conversation, Role};
+use llm::{LlmProvider, LlmTurn, ProviderKind};
+use payment_client::{PaymentClient, PaymentIds, MERCHANT};
+use policy::{Payment, PaymentPolicy, PolicyRules, PolicyViolation, Verdict, PAYMENT_TOOLS};
+use prompts::{referenced_tools, Prompts};
+use proof_verify::{CryptographicProof, TrustedWitnesses};
+use registry::ToolRegistry;
+
+// Load .env file on startup
+fn init_env() {
+    let _ = dotenv::dotenv();
+}
+
+/// Agent configuration
+///
+/// Loaded from AGENT_A_CLIENT_CONFIG (default `agent-a-client.toml`, optional) with env
+/// overrides (ANTHROPIC_API_KEY, LLM_*, AGENT_A_SERVER_URL, AGENT_A_API_TOKEN, AGENT_B_MCP_URL, PAYMENT_AGENT_*,
+/// BOOKING_IDLE_TIMEOUT_SECS, TOOL_CONCURRENCY, HISTORY_*, PROMPTS_DIR,
+/// REQUIRE_PROOF_BEFORE_PAYMENT, RECLAIM_WITNESSES, and the spending policy: PAYMENT_MAX_PER_TRANSACTION_CENTS,
+/// PAYMENT_MAX_PER_DAY_CENTS, PAYMENT_ALLOWED_MERCHANTS, PAYMENT_APPROVAL_THRESHOLD_CENTS,
+/// PAYMENT_ALLOWED_ROUTES).
+#[derive(Debug, Deserialize)]
+pub struct AgentConfig {
+    #[serde(rename = "anthropic_api_key")]
+    claude_api_key: Option<String>,
+    #[serde(default = "default_llm_provider")]
+    llm_provider: ProviderKind,
+    /// Provider-specific default if unset
+    llm_model: Option<String>,
+    /// API key for the OpenAI-compatible provider (optional for local servers)
+    llm_api_key: Option<String>,
+    /// OpenAI-compatible base URL, e.g. http://localhost:11434/v1
+    llm_base_url: Option<String>,
+    #[serde(rename = "agent_a_server_url", default = "default_server_url")]
+    server_url: String,
+    /// Bearer token for Agent A, when it runs with AGENT_A_API_TOKENS
+    agent_a_api_token: Option<String>,
+    #[serde(rename = "agent_b_mcp_url", default = "default_agent_b_url")]
+    agent_b_url: String,
+    payment_agent_url: Option<String>,
+    #[serde(default = "default_true")]
+    payment_agent_enabled: bool,
+    /// Booking prompts answered after this long idle expire the booking
+    #[serde(default = "default_booking_idle_timeout_secs")]
+    booking_idle_timeout_secs: u64,
+    /// Max independent tool calls in flight at once
+    #[serde(default = "default_tool_concurrency")]
+    tool_concurrency: usize,
+    /// Estimated tokens of history before older turns are summarized
+    #[serde(default = "default_history_token_budget")]
+    history_token_budget: usize,
+    /// Most recent turns always kept verbatim
+    #[serde(default = "default_history_keep_turns")]
+    history_keep_turns: usize,
+    /// Prompt templates overriding the built-in ones (see `prompts`)
+    prompts_dir: Option<String>,
+    /// Prove every quoted price with Agent A's attester, and check the proof, before
+    /// asking for payment
+    #[serde(default)]
+    require_proof_before_payment: bool,
+    /// Comma-separated addresses of the Reclaim attestors trusted to sign zkfetch price
+    /// proofs; a proof signed by anyone else fails local verification
+    reclaim_witnesses: Option<String>,
+    /// Spending policy (see `policy`); unset rules don't apply
+    payment_max_per_transaction_cents: Option<u64>,
+    payment_max_per_day_cents: Option<u64>,
+    /// Comma-separated merchant names
+    payment_allowed_merchants: Option<String>,
+    payment_approval_threshold_cents: Option<u64>,
+    /// Comma-separated "FROM-TO" routes, e.g. "NYC-LON,LON-NYC"
+    payment_allowed_routes: Option<String>,
+}
+
+fn default_llm_provider() -> ProviderKind {
+    ProviderKind::Anthropic
+}
+
+fn default_server_url() -> String {
+    "http://localhost:3001".to_string()
+}
+
+fn default_agent_b_url() -> String {
+    "http://localhost:8001".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_booking_idle_timeout_secs() -> u64 {
+    300
+}
+
+fn default_tool_concurrency() -> usize {
+    4
+}
+
+fn default_history_token_budget() -> usize {
+    8000
+}
+
+fn default_history_keep_turns() -> usize {
+    6
+}
+
+impl AgentConfig {
+    fn load() -> Result<Self> {
+        Ok(agent_config::load("AGENT_A_CLIENT_CONFIG", "agent-a-client.toml")?)
+    }
+
+    /// Payment agent to discover tools on, unless it is disabled
+    fn payment_agent_url(&self) -> Option<&str> {
+        self.payment_agent_url.as_deref().filter(|_| self.payment_agent_enabled)
+    }
+
+    fn agent_a_token(&self) -> Option<&str> {
+        self.agent_a_api_token.as_deref()
+    }
+
+    fn trusted_witnesses(&self) -> TrustedWitnesses {
+        TrustedWitnesses::parse(&policy::parse_list(self.reclaim_witnesses.as_deref())).unwrap_or_default()
+    }
+
+    fn booking_idle_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.booking_idle_timeout_secs)
+    }
+
+    fn payment_policy(&self) -> PaymentPolicy {
+        PaymentPolicy::new(PolicyRules {
+            max_per_transaction_cents: self.payment_max_per_transaction_cents,
+            max_per_day_cents: self.payment_max_per_day_cents,
+            allowed_merchants: policy::parse_list(self.payment_allowed_merchants.as_deref()),
+            approval_threshold_cents: self.payment_approval_threshold_cents,
+            allowed_routes: policy::parse_list(self.payment_allowed_routes.as_deref()),
+        })
+    }
+
+    fn llm(&self, client: reqwest::Client) -> Box<dyn LlmProvider> {
+        let api_key = match self.llm_provider {
+            ProviderKind::Anthropic => self.claude_api_key.clone(),
+            _ => self.llm_api_key.clone(),
+        };
+        llm::from_config(
+            self.llm_provider,
+            client,
+            self.llm_model.clone(),
+            api_key,
+            self.llm_base_url.clone(),
+        )
+    }
+}
+
+impl Validate for AgentConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        match self.llm_provider {
+            ProviderKind::Anthropic => {
+                agent_config::check_present("anthropic_api_key", self.claude_api_key.as_deref())?
+            }
+            ProviderKind::OpenAi => {
+                if let Some(url) = &self.llm_base_url {
+                    agent_config::check_url("llm_base_url", url)?;
+                }
+            }
+            ProviderKind::Mock => {}
+        }
+        agent_config::check_url("agent_a_server_url", &self.server_url)?;
+        if self.tool_concurrency == 0 {
+            return Err(agent_config::invalid("tool_concurrency", "must be at least 1"));
+        }
+        agent_config::check_url("agent_b_mcp_url", &self.agent_b_url)?;
+        TrustedWitnesses::parse(&policy::parse_list(self.reclaim_witnesses.as_deref()))
+            .map_err(|e| agent_config::invalid("reclaim_witnesses", e))?;
+        if let Some(dir) = &self.prompts_dir {
+            if !std::path::Path::new(dir).is_dir() {
+                return Err(agent_config::invalid("prompts_dir", "must be a directory"));
+            }
+        }
+        if self.payment_agent_enabled {
+            if let Some(url) = &self.payment_agent_url {
+                agent_config::check_url("payment_agent_url", url)?;
+            }
+        }
+        for route in policy::parse_list(self.payment_allowed_routes.as_deref()) {
+            if route.split_once('-').is_none_or(|(from, to)| from.trim().is_empty() || to.trim().is_empty()) {
+                return Err(agent_config::invalid("payment_allowed_routes", format!("`{}` is not FROM-TO", route)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fetch tool definitions from a server
+async fn fetch_tool_definitions(
+    client: &reqwest::Client,
+    server_url: &str,
+    token: Option<&str>,
+) -> Result<Value> {
+    let url = format!("{}/tools", server_url);
+    let response = client.get(&url).with_token(token).send().await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await?;
+        return Err(anyhow!("Failed to fetch tools: {}", error_text));
+    }
+
+    let tools: Value = response.json().await?;
+    Ok(tools)
+}
+
+/// Discover tools on Agent A Server, Agent B MCP Server, and the Payment Agent
+async fn fetch_all_tools(
+    client: &reqwest::Client,
+    agent_a_url: &str,
+    agent_a_token: Option<&str>,
+    agent_b_url: &str,
+    payment_agent_url: Option<&str>,
+) -> Result<ToolRegistry> {
+    let mut registry = ToolRegistry::new();
+
+    // Fetch Agent A tools
+    if let Ok(resp) = fetch_tool_definitions(client, agent_a_url, agent_a_token).await {
+        if let Some(tools) = tool_list(&resp) {
+            let added = registry.register("agent_a", agent_a_url, agent_a_token, tools);
+            println!("  [Agent A Server] Loaded {} tools", added);
+        }
+    }
+    
+    // Fetch Agent B MCP Server tools
+    match fetch_tool_definitions(client, agent_b_url, None).await {
+        Ok(response) => {
+            if let Some(tools) = tool_list(&response) {
+                let added = registry.register("agent_b", agent_b_url, None, tools);
+                println!("  [Agent B MCP Server] Loaded {} pricing/booking tools", added);
+            }
+        }
+        Err(e) => {
+            println!("  ⚠️  Agent B MCP Server unavailable: {}", e);
+            println!("     (Continuing with Agent A tools only)");
+        }
+    }
+    
+    // Fetch Payment Agent tools if available
+    if let Some(payment_url) = payment_agent_url {
+        match fetch_tool_definitions(client, payment_url, None).await {
+            Ok(payment_response) => {
+                // Payment Agent returns tools in data.tools
+                if let Some(tools) = tool_list(&payment_response) {
+                    let added = registry.register("payment_agent", payment_url, None, tools);
+                    println!("  [Payment Agent] Loaded {} payment tools", added);
+                }
+            }
+            Err(e) => {
+                println!("  ⚠️  Payment Agent unavailable: {}", e);
+                println!("     (Continuing without payment capabilities)");
+            }
+        }
+    }
+
+    if registry.is_empty() {
+        return Err(anyhow!("No tool server reachable"));
+    }
+    Ok(registry)
+}
+
+/// Stream an LLM reply to stdout as "Agent A: ..."
+async fn print_streamed<W: Write>(
+    llm: &dyn LlmProvider,
+    system: &str,
+    user: &str,
+    stdout: &mut W,
+) -> Result<()> {
+    let mut chunks = llm.stream(system, user).await?;
+    print!("Agent A: ");
+    while let Some(chunk) = chunks.next().await {
+        print!("{}", chunk?);
+        stdout.flush()?;
+    }
+    println!("\n");
+    Ok(())
+}
+
+/// Pull `field` out of a free-text answer ("I'm Jane Doe"), falling back to the trimmed answer
+async fn extract_or_raw(llm: &dyn LlmProvider, answer: &str, field: &str) -> String {
+    match llm.extract_field(answer, field).await {
+        Ok(Some(value)) => value,
+        _ => answer.trim().to_string(),
+    }
+}
+
+/// Call server tool via HTTP, on whichever server advertised it
+async fn call_server_tool(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    tool_name: &str,
+    arguments: Value,
+    idempotency_key: Option<&str>,
+) -> Result<String> {
+    let Some(key) = idempotency_key else {
+        return execute_tool_call(client, registry, tool_name, &arguments, None).await;
+    };
+
+    // A retried payment/booking call with the same key returns the first result, and one
+    // made while the first is in flight waits for it instead of executing again
+    let slot = {
+        let mut calls = keyed_calls().lock().unwrap();
+        calls.retain(|_, (first_made, _)| first_made.elapsed() < KEYED_CALL_TTL);
+        let (_, slot) = calls
+            .entry(format!("{}:{}", tool_name, key))
+            .or_insert_with(|| (std::time::Instant::now(), Arc::default()));
+        slot.clone()
+    };
+    let mut executed = false;
+    let data = slot
+        .get_or_try_init(|| {
+            executed = true;
+            execute_tool_call(client, registry, tool_name, &arguments, Some(key))
+        })
+        .await?;
+    if !executed {
+        println!("  (already executed for this booking attempt, reusing result)");
+    }
+    Ok(data.clone())
+}
+
+/// One tool call, reported to the audit log
+async fn execute_tool_call(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    tool_name: &str,
+    arguments: &Value,
+    idempotency_key: Option<&str>,
+) -> Result<String> {
+    audit::tool_call(tool_name, arguments).await;
+    let result = send_tool_call(client, registry, tool_name, arguments, idempotency_key).await;
+    audit::tool_result(tool_name, &result).await;
+    result
+}
+
+/// POST `/tools/{tool_name}` wherever the registry found it; the tool's data
+async fn send_tool_call(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    tool_name: &str,
+    arguments: &Value,
+    idempotency_key: Option<&str>,
+) -> Result<String> {
+    let origin = registry.origin(tool_name)?;
+
+    let url = format!("{}/tools/{}", origin.url, tool_name);
+
+    let mut request = client.post(&url).with_token(origin.token.as_deref()).json(arguments);
+    if let Some(key) = idempotency_key {
+        request = request.header("Idempotency-Key", key);
+    }
+    let response = request.send().await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await?;
+        return Err(anyhow!("Server error: {}", error_text));
+    }
+
+    let result: Value = response.json().await?;
+    tool_response_data(&result)
+}
+
+/// How long a payment/booking call's result is reused for calls with the same key
+const KEYED_CALL_TTL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Payment/booking calls by `tool:idempotency-key`: when first made, and the result once
+/// one succeeded. Entries older than [`KEYED_CALL_TTL`] are dropped.
+type KeyedCalls = HashMap<String, (std::time::Instant, Arc<tokio::sync::OnceCell<String>>)>;
+
+fn keyed_calls() -> &'static Mutex<KeyedCalls> {
+    static CALLS: OnceLock<Mutex<KeyedCalls>> = OnceLock::new();
+    CALLS.get_or_init(Default::default)
+}
+
+/// Send Agent A's bearer token (AGENT_A_API_TOKEN) on a request, if one is configured
+pub(crate) trait WithToken {
+    fn with_token(self, token: Option<&str>) -> Self;
+}
+
+impl WithToken for reqwest::RequestBuilder {
+    fn with_token(self, token: Option<&str>) -> Self {
+        match token {
+            Some(token) => self.bearer_auth(token),
+            None => self,
+        }
+    }
+}
+
+/// The `data` of a tool server's envelope, as JSON text
+fn tool_response_data(result: &Value) -> Result<String> {
+    Ok(parse_tool_response(result)?.to_string())
+}
+
+/// Run independent tool calls with at most `limit` in flight; results keep the call order
+async fn call_server_tools(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    calls: Vec<(String, Value)>,
+    limit: usize,
+) -> Vec<(String, Result<String>)> {
+    stream::iter(calls)
+        .map(|(tool_name, arguments)| async move {
+            let result =
+                call_server_tool(client, registry, &tool_name, arguments, None).await;
+            (tool_name, result)
+        })
+        .buffered(limit.max(1))
+        .collect()
+        .await
+}
+
+/// Re-price every leg whose quote has lapsed; returns the legs that were re-priced
+async fn reprice_expired_legs(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    witnesses: &TrustedWitnesses,
+    itinerary: &mut Itinerary,
+) -> Result<Vec<usize>> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let expired = itinerary.expired_legs(now);
+
+    for &i in &expired {
+        price_leg(client, registry, witnesses, &mut itinerary.legs[i]).await?;
+    }
+    Ok(expired)
+}
+
+/// Quote `leg` again from its pricing arguments
+async fn price_leg(client: &reqwest::Client, registry: &ToolRegistry, witnesses: &TrustedWitnesses, leg: &mut Leg) -> Result<()> {
+    let result = call_server_tool(client, registry, "get-ticket-price", leg.pricing_args.clone(), None).await?;
+    let parsed: Value = serde_json::from_str(&result)?;
+    if let Some(proof) = CryptographicProof::from_tool_result(&parsed, witnesses) {
+        if !proof.verified {
+            return Err(anyhow!("price proof failed local verification: {}", proof.failure.unwrap_or_default()));
+        }
+    }
+    leg.apply_quote(&parsed);
+    Ok(())
+}
+
+/// Ask for the departure day of every undated leg and re-price it; `false` if the booking was
+/// cancelled or expired. A day Agent B won't price (past, blackout) is asked for again.
+#[allow(clippy::too_many_arguments)]
+async fn date_undated_legs<R: BufRead, W: Write>(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    witnesses: &TrustedWitnesses,
+    llm: &dyn LlmProvider,
+    itinerary: &mut Itinerary,
+    session: &mut BookingSession,
+    reader: &mut R,
+    stdout: &mut W,
+) -> Result<bool> {
+    for i in itinerary.undated_legs() {
+        let prompt = format!("What day does your {} flight leave? (YYYY-MM-DD): ", itinerary.leg_label(i));
+        loop {
+            let Some(answer) = session.ask(BookingStep::TravelDate, &prompt, reader, stdout)? else {
+                return Ok(false);
+            };
+            let date = extract_or_raw(llm, &answer, "date as YYYY-MM-DD").await;
+            if !is_travel_date(&date) {
+                writeln!(stdout, "Please give the date as YYYY-MM-DD (or 'cancel' to stop booking).")?;
+                continue;
+            }
+            let leg = &mut itinerary.legs[i];
+            leg.set_date(&date);
+            match price_leg(client, registry, witnesses, leg).await {
+                Ok(()) => break,
+                Err(e) => writeln!(stdout, "✗ Can't fly on {}: {}", date, e)?,
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Book every leg for every passenger as one group and report the confirmation codes;
+/// the (confirmation code, booking id) of each seat that was booked
+async fn book_itinerary(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    itinerary: &Itinerary,
+    passengers: &[Passenger],
+    session: &BookingSession,
+    conversation: &mut Conversation,
+) -> Vec<(String, String)> {
+    // (leg, passenger, confirmation code, booking id) per booked seat
+    let mut confirmations: Vec<(usize, usize, String, String)> = Vec::new();
+    let seats = itinerary.legs.len() * passengers.len();
+    'legs: for (i, leg) in itinerary.legs.iter().enumerate() {
+        for (j, passenger) in passengers.iter().enumerate() {
+            let mut book_args = json!({
+                "from": leg.from,
+                "to": leg.to,
+                "passenger_name": passenger.name,
+                "passenger_email": passenger.email
+            });
+            // Book the flight, cabin, and fare family the leg was priced for
+            for field in ["departure_date", "cabin", "fare_family"] {
+                if let Some(value) = leg.pricing_args.get(field) {
+                    book_args[field] = value.clone();
+                }
+            }
+
+            println!("→ Invoking: book-flight with args {}", redact_pii(&book_args.to_string(), passengers));
+
+            // One key per leg and passenger so each seat is deduped independently
+            let seat_key = format!("{}:leg{}:pax{}", session.idempotency_key(), i + 1, j + 1);
+            let outcome = call_server_tool(
+                client,
+                registry,
+                "book-flight",
+                book_args,
+                Some(&seat_key),
+            )
+            .await
+            .and_then(|result| {
+                println!("✓ Result: {}\n", redact_pii(&result, passengers));
+                let booking: Value = serde_json::from_str(&result)?;
+                let field = |name: &str| booking.get(name).and_then(|c| c.as_str()).map(str::to_string);
+                let conf_code = field("confirmation_code").ok_or_else(|| anyhow!("no confirmation code in booking response"))?;
+                Ok((conf_code, field("booking_id").unwrap_or_default()))
+            });
+            match outcome {
+                Ok((conf_code, booking_id)) => confirmations.push((i, j, conf_code, booking_id)),
+                Err(e) => {
+                    println!("✗ Error booking {} for passenger {}: {}\n", itinerary.leg_label(i), j + 1, e);
+                    break 'legs;
+                }
+            }
+        }
+    }
+
+    let codes = confirmations.iter().map(|(_, _, code, _)| code.as_str()).collect::<Vec<_>>().join(", ");
+    if confirmations.len() == seats {
+        show_success("Flight booking confirmed!");
+        if let [(_, _, conf_code, _)] = confirmations.as_slice() {
+            println!("Agent A: Your flight booking {} has been confirmed.\n", itinerary.leg_label(0));
+            println!("Agent A: Confirmation code: {}\n", conf_code);
+        } else {
+            println!("Agent A: Your trip ({}) has been confirmed:", itinerary.route());
+            for (i, j, conf_code, _) in &confirmations {
+                println!("  {} — {}: {}", itinerary.leg_label(*i), passengers[*j].name, conf_code);
+            }
+            println!();
+        }
+        conversation.remember("confirmation_code", codes);
+        println!("Agent A: You'll receive a confirmation email shortly with your flight details and receipt.\n");
+    } else if !confirmations.is_empty() {
+        println!(
+            "Agent A: Only {} of {} seats were booked (confirmation: {}). Please contact support about the rest of your trip.\n",
+            confirmations.len(),
+            seats,
+            codes
+        );
+        conversation.remember("confirmation_code", codes);
+    }
+    confirmations.into_iter().map(|(_, _, code, booking_id)| (code, booking_id)).collect()
+}
+
+/// Total for `seats` passengers, re-pricing lapsed quotes first; `None` if the booking was
+/// cancelled, the user turned down a new fare, or a leg couldn't be re-priced. With
+/// `require_proof`, re-priced legs are proven again, so payment is only ever taken for a
+/// proven price.
+#[allow(clippy::too_many_arguments)]
+async fn current_total<R: BufRead, W: Write>(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    witnesses: &TrustedWitnesses,
+    require_proof: bool,
+    itinerary: &mut Itinerary,
+    seats: usize,
+    total: u64,
+    session: &mut BookingSession,
+    reader: &mut R,
+    stdout: &mut W,
+) -> Result<Option<u64>> {
+    match reprice_expired_legs(client, registry, witnesses, itinerary).await {
+        Ok(repriced) if repriced.is_empty() => Ok(Some(total)),
+        Ok(repriced_legs) => {
+            let Some(repriced) = itinerary.total().map(|t| t * seats as u64) else {
+                writeln!(stdout, "Agent A: Your quote expired and I couldn't re-price every leg. Please try again.\n")?;
+                return Ok(None);
+            };
+            if repriced != total {
+                writeln!(stdout, "Agent A: Your quote expired and the fare is now {} (was {}).", itinerary.format(repriced), itinerary.format(total))?;
+                let Some(accept) = session.confirm(BookingStep::ConfirmPrice, "Accept the new price?", reader, stdout)? else {
+                    return Ok(None);
+                };
+                if !accept {
+                    writeln!(stdout, "Agent A: Okay, I've cancelled the booking.\n")?;
+                    return Ok(None);
+                }
+            }
+            if require_proof {
+                if let Err(e) = prove_quoted_prices(client, registry, itinerary, &repriced_legs, session).await {
+                    writeln!(stdout, "✗ Couldn't prove your new price: {}", e)?;
+                    writeln!(stdout, "Agent A: I won't take payment for a price I can't prove, so I've stopped this booking.\n")?;
+                    return Ok(None);
+                }
+            }
+            Ok(Some(repriced))
+        }
+        Err(e) => {
+            writeln!(stdout, "✗ Couldn't refresh your expired quote: {}\n", e)?;
+            Ok(None)
+        }
+    }
+}
+
+/// How often a pending crypto payment is checked
+const CRYPTO_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Request `total` as a crypto payment on Agent A and wait for the transfer; the settling
+/// transaction hash, or `None` if the request expired unpaid
+async fn pay_with_crypto(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    itinerary: &Itinerary,
+    total: u64,
+    session: &BookingSession,
+) -> Result<Option<String>> {
+    let args = json!({
+        "amount_cents": total,
+        "currency": itinerary.currency(),
+        "session_id": session.idempotency_key(),
+    });
+    println!("→ Invoking: create_crypto_payment with args {}", args);
+    let result = call_server_tool(client, registry, "create_crypto_payment", args, Some(session.idempotency_key())).await?;
+    let payment: Value = serde_json::from_str(&result)?;
+    let payment_id = payment
+        .get("id")
+        .and_then(|id| id.as_str())
+        .ok_or_else(|| anyhow!("no payment id in create_crypto_payment response"))?;
+
+    // The amount carries a per-payment salt below a cent; only the exact amount settles it
+    println!(
+        "\nAgent A: Please send exactly {} {} ({}) to {} (chain {}).",
+        payment["amount_formatted"].as_str().unwrap_or_default(),
+        payment["symbol"].as_str().unwrap_or_default(),
+        format_amount(total, "USD"),
+        payment["recipient"].as_str().unwrap_or_default(),
+        payment["chain_id"]
+    );
+    println!("Agent A: Wallet link: {}\n", payment["uri"].as_str().unwrap_or_default());
+    show_status("Waiting for your transfer to be mined...");
+
+    // The server expires the request; stop polling a little after that regardless
+    let ttl = payment["expires_at"].as_u64().unwrap_or_default().saturating_sub(payment["created_at"].as_u64().unwrap_or_default());
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(ttl) + CRYPTO_POLL_INTERVAL * 6;
+    while std::time::Instant::now() < deadline {
+        tokio::time::sleep(CRYPTO_POLL_INTERVAL).await;
+        let status = match call_server_tool(client, registry, "get_crypto_payment", json!({"payment_id": payment_id}), None).await {
+            Ok(result) => serde_json::from_str::<Value>(&result)?,
+            Err(e) => {
+                tracing::warn!("Checking crypto payment {}: {}", payment_id, e);
+                continue;
+            }
+        };
+        match status["status"].as_str() {
+            Some("paid") => return Ok(status["tx_hash"].as_str().map(str::to_string)),
+            Some("expired") => return Ok(None),
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// How often a queued price proof is checked
+const PROOF_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How long a price proof may take before the booking gives up on it
+const PROOF_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20 * 60);
+
+/// Prove the quoted price of each of `legs` on Agent A (`format_zk_input` +
+/// `request_attestation`) and verify the proofs, off-chain when the server offers it,
+/// on-chain otherwise. Any leg that can't be proven fails the whole itinerary.
+async fn prove_quoted_prices(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    itinerary: &Itinerary,
+    legs: &[usize],
+    session: &BookingSession,
+) -> Result<()> {
+    for &i in legs {
+        let leg = &itinerary.legs[i];
+        let label = itinerary.leg_label(i);
+        let program_id = leg
+            .program_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("the quote for {} names no zkVM program", label))?;
+
+        show_status(&format!("Preparing the price proof for {}...", label));
+        let args = json!({"endpoint": "price", "input": leg.zk_request()});
+        let input: Value = serde_json::from_str(&call_server_tool(client, registry, "format_zk_input", args, None).await?)?;
+
+        let args = json!({
+            "program_id": program_id,
+            "input_hex": input["input_hex"],
+            "claimed_output": json!({"price_cents": leg.price_cents, "currency": leg.currency}).to_string(),
+            "elf_hash": leg.elf_hash,
+            "session_id": session.idempotency_key(),
+        });
+        println!("→ Invoking: request_attestation for {}", label);
+        let job: Value = serde_json::from_str(&call_server_tool(client, registry, "request_attestation", args, None).await?)?;
+        let status_url = job["status_url"]
+            .as_str()
+            .ok_or_else(|| anyhow!("no status_url in request_attestation response"))?;
+        let origin = registry.origin("request_attestation")?;
+        let status_url = format!("{}{}", origin.url, status_url);
+
+        let bundle = wait_for_proof(client, &status_url, origin.token.as_deref(), PROOF_POLL_INTERVAL, PROOF_TIMEOUT).await?;
+        let verified = if registry.offers("verify_offchain") {
+            let result = call_server_tool(client, registry, "verify_offchain", json!({"proof_bundle": bundle}), None).await?;
+            serde_json::from_str::<Value>(&result)?
+        } else {
+            let args = json!({
+                "proof": bundle["proof"],
+                "public_values": bundle["public_values"],
+                "vk_hash": bundle["vk_hash"],
+                "claim_type": "pricing",
+            });
+            serde_json::from_str::<Value>(&call_server_tool(client, registry, "verify_on_chain", args, None).await?)?
+        };
+        if verified["verified"].as_bool() != Some(true) {
+            return Err(anyhow!("the price proof for {} did not verify: {}", label, verified));
+        }
+        show_success(&format!("Price for {} proven and verified", label));
+    }
+    Ok(())
+}
+
+/// Poll an attestation job every `interval` until it finishes, showing its progress; the
+/// proof bundle. Gives up after `timeout`, so a stuck or lost job can't hold the chat.
+async fn wait_for_proof(
+    client: &reqwest::Client,
+    status_url: &str,
+    token: Option<&str>,
+    interval: std::time::Duration,
+    timeout: std::time::Duration,
+) -> Result<Value> {
+    let poll = async {
+        let mut last_shown = String::new();
+        loop {
+            let view: Value = serde_json::from_str(&tool_response_data(&client.get(status_url).with_token(token).send().await?.json().await?)?)?;
+            let progress = match view["status"].as_str() {
+                Some("done") => {
+                    return view
+                        .get("proof_bundle")
+                        .cloned()
+                        .ok_or_else(|| anyhow!("attestation finished without a proof bundle"));
+                }
+                Some("failed") => {
+                    return Err(anyhow!("attestation failed: {}", view["message"].as_str().unwrap_or("unknown error")));
+                }
+                Some("proving") => format!("Proving... {}%", view["pct"].as_u64().unwrap_or_default()),
+                Some("executing") => "Executing the pricing program...".to_string(),
+                _ => "Waiting for a prover...".to_string(),
+            };
+            if progress != last_shown {
+                println!("  … {}", progress);
+                last_shown = progress;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    };
+    tokio::time::timeout(timeout, poll)
+        .await
+        .map_err(|_| anyhow!("the price proof wasn't ready after {:?}", timeout))?
+}
+
+/// Report a booking milestone to Agent A under the chat session; best effort, since
+/// resuming is a convenience and shouldn't hold up the booking
+async fn save_progress(
+    client: &reqwest::Client,
+    server_url: &str,
+    token: Option<&str>,
+    session_id: &str,
+    progress: &impl Serialize,
+) {
+    let url = format!("{}/session/{}", server_url, session_id);
+    if let Err(e) = client.post(&url).with_token(token).json(progress).send().await.and_then(|r| r.error_for_status()) {
+        tracing::warn!("Saving booking progress for session {}: {}", session_id, e);
+    }
+}
+
+/// The session's booking as stored by Agent A (with its `summary`), if it has one
+async fn fetch_progress(
+    client: &reqwest::Client,
+    server_url: &str,
+    token: Option<&str>,
+    session_id: &str,
+) -> Result<Option<Value>> {
+    let response = client.get(format!("{}/session/{}", server_url, session_id)).with_token(token).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&tool_response_data(&response.json().await?)?)?))
+}
+
+/// Recap the session's booking; the `get-ticket-price` calls to replay if it can be
+/// picked up (a paid or booked trip can't)
+async fn resume_booking(
+    client: &reqwest::Client,
+    server_url: &str,
+    token: Option<&str>,
+    session_id: &str,
+) -> Option<Vec<(String, Value)>> {
+    let progress = match fetch_progress(client, server_url, token, session_id).await {
+        Ok(Some(progress)) => progress,
+        Ok(None) => {
+            println!("Agent A: I don't have a booking in progress for this session.\n");
+            return None;
+        }
+        Err(e) => {
+            println!("✗ Couldn't load your booking: {}\n", e);
+            return None;
+        }
+    };
+    println!("Agent A: Welcome back! {}\n", progress["summary"].as_str().unwrap_or_default());
+    if progress["resumable"].as_bool() != Some(true) {
+        return None;
+    }
+    let calls = progress["pricing_calls"]
+        .as_array()?
+        .iter()
+        .map(|args| ("get-ticket-price".to_string(), args.clone()))
+        .collect();
+    println!("Agent A: Let's pick up where you left off. I'll re-check the fare first.\n");
+    Some(calls)
+}
+
+/// Cancel the session's completed booking and return what the fare rules allow
+///
+/// Each booking is cancelled through Agent A's attest_refund, which proves the refund,
+/// or through Agent B's cancel-booking when Agent A doesn't offer it. A card payment is
+/// reversed with the payment agent's reverse-purchase-instruction if it has one; anything
+/// else (always crypto) is recorded as a pending manual refund.
+async fn refund_booking<R: BufRead, W: Write>(
+    client: &reqwest::Client,
+    registry: &ToolRegistry,
+    config: &AgentConfig,
+    payment_agent_url: Option<&str>,
+    chat_session: &str,
+    reader: &mut R,
+    stdout: &mut W,
+) -> Result<()> {
+    let mut progress = match fetch_progress(client, &config.server_url, config.agent_a_token(), chat_session).await {
+        Ok(Some(progress)) => progress,
+        Ok(None) => {
+            println!("Agent A: I don't have a booking for this session to cancel.\n");
+            return Ok(());
+        }
+        Err(e) => {
+            println!("✗ Couldn't load your booking: {}\n", e);
+            return Ok(());
+        }
+    };
+    if progress["stage"] != json!(BookingStage::Booked) {
+        println!("Agent A: {} There's no completed booking to cancel.\n", progress["summary"].as_str().unwrap_or_default());
+        return Ok(());
+    }
+    let route = progress["route"].as_str().unwrap_or_default().to_string();
+    let booking_ids: Vec<String> = serde_json::from_value(progress["booking_ids"].clone()).unwrap_or_default();
+    if booking_ids.is_empty() || booking_ids.iter().any(String::is_empty) {
+        println!("Agent A: I can't find the booking references for {}. Please contact support to cancel it.\n", route);
+        return Ok(());
+    }
+
+    let mut session = BookingSession::new(&route, config.booking_idle_timeout());
+    println!(
+        "Agent A: Cancelling {} can't be undone. The refund depends on your fare and how close departure is.\n",
+        route
+    );
+    match session.confirm(BookingStep::ConfirmRefund, "Cancel this booking?", reader, stdout)? {
+        Some(true) => {}
+        Some(false) => {
+            println!("Agent A: Okay, your booking stays as it is.\n");
+            return Ok(());
+        }
+        None => return Ok(()),
+    }
+
+    let proven = registry.offers("attest_refund");
+    if proven {
+        show_status("Cancelling and proving your refund (this can take a while)...");
+    } else {
+        show_status("Cancelling your booking...");
+    }
+    let mut refund_cents = 0;
+    let mut currency = progress["currency"].as_str().unwrap_or("USD").to_string();
+    let mut remaining = Vec::new();
+    for booking_id in &booking_ids {
+        let (tool, args) = if proven {
+            ("attest_refund", json!({ "booking_id": booking_id, "session_id": chat_session }))
+        } else {
+            ("cancel-booking", json!({ "booking_id": booking_id }))
+        };
+        println!("→ Invoking: {} with args {}", tool, args);
+        let key = format!("{}:{}", session.idempotency_key(), booking_id);
+        let outcome = call_server_tool(client, registry, tool, args, Some(&key)).await.and_then(|result| {
+            println!("✓ Result: {}\n", result);
+            let cancellation: Value = serde_json::from_str(&result)?;
+            // attest_refund reports the refund at the top level, cancel-booking under `cancellation`
+            let refund = cancellation
+                .get("refund_cents")
+                .or_else(|| cancellation.pointer("/cancellation/refund_cents"))
+                .and_then(|v| v.as_u64())
+                .ok_or_else(|| anyhow!("no refund in {} response", tool))?;
+            Ok((refund, cancellation["currency"].as_str().map(str::to_string)))
+        });
+        match outcome {
+            Ok((refund, refund_currency)) => {
+                refund_cents += refund;
+                if let Some(refund_currency) = refund_currency {
+                    currency = refund_currency;
+                }
+            }
+            Err(e) => {
+                println!("✗ Error cancelling booking {}: {}\n", booking_id, e);
+                remaining.push(booking_id.clone());
+            }
+        }
+    }
+    if remaining.len() == booking_ids.len() {
+        println!("Agent A: I couldn't cancel {}; it is still booked.\n", route);
+        return Ok(());
+    }
+
+    let paid_by_card = progress["payment_method"] == "card";
+    let instruction_id = progress["payment_reference"].as_str().map(str::to_string);
+    let refund_status = match instruction_id {
+        _ if refund_cents == 0 => {
+            println!("Agent A: Your fare isn't refundable this close to departure, so there's nothing to return.\n");
+            "none"
+        }
+        Some(instruction_id) if paid_by_card && registry.offers("reverse-purchase-instruction") => {
+            let payments = PaymentClient::new(
+                client,
+                registry,
+                payment_agent_url.unwrap_or("http://localhost:3002"),
+                PaymentIds::new(chat_session, session.idempotency_key()),
+            );
+            let amount = format_amount(refund_cents, &currency);
+            match payments.reverse_purchase(&instruction_id, &amount, &currency, session.idempotency_key()).await {
+                Ok(reversal) if reversal.is_reversed() => {
+                    tracing::info!("Reversal {} of instruction {}", reversal.reversal_id.as_deref().unwrap_or("-"), instruction_id);
+                    show_success(&format!("{} is on its way back to your card.", format_price(refund_cents, &currency)));
+                    "reversed"
+                }
+                Ok(reversal) => {
+                    println!("✗ Reversal refused ({}); a refund will be processed manually.\n", reversal.status.unwrap_or_default());
+                    "pending_manual"
+                }
+                Err(e) => {
+                    println!("✗ Error reversing the payment: {}; a refund will be processed manually.\n", e);
+                    "pending_manual"
+                }
+            }
+        }
+        _ => {
+            println!(
+                "Agent A: Your refund of {} has been recorded and will be processed manually.\n",
+                format_price(refund_cents, &currency)
+            );
+            "pending_manual"
+        }
+    };
+
+    if remaining.is_empty() {
+        progress["stage"] = json!(BookingStage::Cancelled);
+        show_success(&format!("{} is cancelled.", route));
+    } else {
+        println!(
+            "Agent A: {} of {} bookings were cancelled. Please contact support about the rest ({}).\n",
+            booking_ids.len() - remaining.len(),
+            booking_ids.len(),
+            remaining.join(", ")
+        );
+    }
+    progress["booking_ids"] = json!(remaining);
+    progress["currency"] = json!(currency);
+    progress["refund_cents"] = json!(refund_cents);
+    progress["refund_status"] = json!(refund_status);
+    save_progress(client, &config.server_url, config.agent_a_token(), chat_session, &progress).await;
+    Ok(())
+}
+
+/// Payment of `total` for every leg of `itinerary` through `tool`
+fn itinerary_payment(tool: &str, itinerary: &Itinerary, total: u64) -> Payment {
+    Payment {
+        tool: tool.to_string(),
+        amount_cents: total,
+        currency: itinerary.currency().to_string(),
+        merchant: MERCHANT.to_string(),
+        legs: Some(itinerary.legs.iter().map(|leg| (leg.from.clone(), leg.to.clone())).collect()),
+    }
+}
+
+/// Run `payment` past the spending policy before its tool is called; false if the policy
+/// refused it or the user didn't approve a payment above the approval threshold
+#[allow(clippy::too_many_arguments)]
+async fn allow_payment<R: BufRead, W: Write>(
+    client: &reqwest::Client,
+    server_url: &str,
+    token: Option<&str>,
+    chat_session: &str,
+    policy: &PaymentPolicy,
+    payment: &Payment,
+    session: &mut BookingSession,
+    reader: &mut R,
+    stdout: &mut W,
+) -> Result<bool> {
+    match policy.check(payment) {
+        Ok(Verdict::Allow) => Ok(true),
+        Ok(Verdict::NeedsApproval) => {
+            println!(
+                "Agent A: {} is above your approval threshold, so I need your explicit go-ahead.\n",
+                format_price(payment.amount_cents, &payment.currency)
+            );
+            let approved = session.confirm(BookingStep::ApprovePayment, "Approve this payment?", reader, stdout)?;
+            if approved == Some(false) {
+                println!("Agent A: Okay, I won't make this payment.\n");
+            }
+            Ok(approved == Some(true))
+        }
+        Err(violation) => {
+            println!("✗ Payment refused by your spending policy: {}", violation);
+            println!("  {}\n", json!({ "error": "policy_violation", "tool": payment.tool, "rule": violation.rule, "message": violation.message }));
+            record_denied_payment(client, server_url, token, chat_session, payment, &violation).await;
+            Ok(false)
+        }
+    }
+}
+
+/// Log a refused payment to the session's proof trace on Agent A; best effort
+async fn record_denied_payment(
+    client: &reqwest::Client,
+    server_url: &str,
+    token: Option<&str>,
+    session_id: &str,
+    payment: &Payment,
+    violation: &PolicyViolation,
+) {
+    let url = format!("{}/sessions/{}/denied-actions", server_url, session_id);
+    let denied = json!({
+        "tool": payment.tool,
+        "rule": violation.rule,
+        "message": violation.message,
+        // An unreadable amount is checked as u64::MAX; don't log it as one
+        "amount_cents": (payment.amount_cents != u64::MAX).then_some(payment.amount_cents),
+        "currency": payment.currency,
+    });
+    if let Err(e) = client.post(&url).with_token(token).json(&denied).send().await.and_then(|r| r.error_for_status()) {
+        tracing::warn!("Logging denied payment for session {}: {}", session_id, e);
+    }
+}
+
+/// Helper: Show status message
+fn show_status(message: &str) {
+    println!("\n⏳ {}", message);
+    io::stdout().flush().ok();
+}
+
+/// Helper: Show success message
+fn show_success(message: &str) {
+    println!("\n✅ {}", message);
+}
+
+/// Helper: Show step indicator
+fn show_step(step: u32, total: u32, message: &str) {
+    println!("\n[Step {}/{}] {}", step, total, message);
+}
+
+/// The mcp-client binary: `chat` unless another subcommand is given
+pub async fn run() -> Result<()> {
+    // Load .env file
+    init_env();
+
+    let cli = cli::Cli::parse();
+    let config = AgentConfig::load()?;
+    match cli.command.unwrap_or(cli::Command::Chat { session: None }) {
+        cli::Command::Chat { session } => run_chat(config, session).await,
+        command => cli::run(command, &config).await,
+    }
+}
+/// Interactive loop: the LLM routes each request to tools, with the guided booking flow
+///
+/// Booking progress is saved under `session` (a new id if unset), so the same booking can
+/// be picked up after a restart with `chat --session <id>`.
+async fn run_chat(config: AgentConfig, session: Option<String>) -> Result<()> {
+    let client = reqwest::Client::new();
+    let llm = config.llm(client.clone());
+
+    println!("\n╔════════════════════════════════════════════════════════════╗");
+    println!("║       Agent A - AI-Powered MCP Client                      ║");
+    println!("║              (Connects to HTTP Server)                     ║");
+    println!("╚════════════════════════════════════════════════════════════╝\n");
+
+    let mut chat = Chat::connect(client.clone(), llm, config, io::stdin().lock(), io::stdout()).await?;
+
+    println!("Examples:");
+    println!("  'Get pricing from NYC to London for VIP'");
+    println!("  'Verify a ZK proof on Sepolia'");
+    println!("  'Request a ZK attestation'\n");
+
+    println!("Type 'exit' or 'quit' to end.\n");
+
+    match session {
+        Some(id) => {
+            match fetch_progress(&client, &chat.config.server_url, chat.config.agent_a_token(), &id).await {
+                Ok(Some(progress)) => {
+                    println!("Agent A: Welcome back! {}", progress["summary"].as_str().unwrap_or_default());
+                    if progress["resumable"].as_bool() == Some(true) {
+                        println!("Agent A: Say \"continue my booking\" to pick it up.");
+                    }
+                    println!();
+                }
+                Ok(None) => println!("No booking in progress for session {}.\n", id),
+                Err(e) => tracing::warn!("Loading booking progress for session {}: {}", id, e),
+            }
+            chat.session_id = id;
+        }
+        None => println!("Session: {} (resume later with `chat --session {}`)\n", chat.session_id, chat.session_id),
+    }
+
+    audit::init(client, &chat.config.server_url, chat.config.agent_a_token(), &chat.session_id);
+
+    loop {
+        print!("\nYou: ");
+        io::stdout().flush()?;
+
+        let mut user_input = String::new();
+        if chat.reader.read_line(&mut user_input)? == 0 {
+            break; // EOF
+        }
+
+        let input = user_input.trim();
+        if input.is_empty() {
+            continue;
+        }
+        if matches!(input.to_lowercase().as_str(), "exit" | "quit") {
+            println!("\nGoodbye!");
+            break;
+        }
+
+        chat.process_user_query(input).await?;
+    }
+
+    Ok(())
+}
+
+/// One chat session: the tools the servers offer, the conversation so far, and where the
+/// booking flow reads its answers from and writes its prompts to
+pub struct Chat<R, W> {
+    client: reqwest::Client,
+    llm: Box<dyn LlmProvider>,
+    config: AgentConfig,
+    registry: ToolRegistry,
+    tool_definitions: Value,
+    system_prompt: String,
+    summary_prompt: String,
+    /// Booking progress, payment ids and audit events are kept under this id
+    session_id: String,
+    policy: PaymentPolicy,
+    conversation: Conversation,
+    reader: R,
+    stdout: W,
+}
+
+impl<R: BufRead, W: Write> Chat<R, W> {
+    /// Discover the tools of every configured server and render the prompts for them,
+    /// under a new session id
+    pub async fn connect(
+        client: reqwest::Client,
+        llm: Box<dyn LlmProvider>,
+        config: AgentConfig,
+        reader: R,
+        stdout: W,
+    ) -> Result<Self> {
+        println!("Fetching tool definitions...");
+        let registry = match fetch_all_tools(&client, &config.server_url, config.agent_a_token(), &config.agent_b_url, config.payment_agent_url()).await {
+            Ok(registry) => {
+                println!("✓ Loaded {} tools from server(s)\n", registry.len());
+                registry
+            }
+            Err(e) => {
+                eprintln!("✗ Failed to fetch tools: {}\n", e);
+                eprintln!("Make sure the MCP server is running on {}\n", config.server_url);
+                return Err(e);
+            }
+        };
+
+        println!("Capabilities:");
+        for (i, tool) in registry.iter().enumerate() {
+            if let Some(name) = tool.get("name").and_then(|n| n.as_str()) {
+                if let Some(desc) = tool.get("description").and_then(|d| d.as_str()) {
+                    println!("  {}. {} - {}", i + 1, name, desc);
+                }
+            }
+        }
+        println!();
+
+        let prompts = Prompts::load(config.prompts_dir.as_deref());
+        let system_prompt = prompts.system(&registry)?;
+        let summary_prompt = prompts.summary()?;
+        // Overridden templates aren't guarded by the registry like the built-in ones
+        for tool in referenced_tools(&system_prompt) {
+            if registry.origin(tool).is_err() {
+                tracing::warn!("System prompt mentions {}, which no connected server offers", tool);
+            }
+        }
+
+        Ok(Self {
+            tool_definitions: registry.definitions(),
+            policy: config.payment_policy(),
+            conversation: Conversation::new(config.history_token_budget, config.history_keep_turns),
+            session_id: uuid::Uuid::new_v4().to_string(),
+            client,
+            llm,
+            config,
+            registry,
+            system_prompt,
+            summary_prompt,
+            reader,
+            stdout,
+        })
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Answer one user request: route it through the LLM to tools, running the guided
+    /// booking flow (quote, passengers, payment, booking) when it prices a flight. Refund
+    /// and resume intents skip the LLM. Every question the flow asks is read from `reader`.
+    pub async fn process_user_query(&mut self, input: &str) -> Result<()> {
+        let Self {
+            client,
+            llm,
+            config,
+            registry,
+            tool_definitions,
+            system_prompt,
+            summary_prompt,
+            session_id: chat_session,
+            policy,
+            conversation,
+            reader,
+            stdout,
+        } = self;
+        let llm: &dyn LlmProvider = &**llm;
+        let payment_agent_url = config.payment_agent_url();
+        let witnesses = config.trusted_witnesses();
+
+        println!("\nAgent A: Processing your request...\n");
+
+        // Ask the LLM which tools to call, with (compacted) history as context
+        conversation.compact_if_needed(llm).await;
+        let system = match conversation.system_note() {
+            Some(note) => format!("{}\n\n{}", system_prompt, note),
+            None => system_prompt.clone(),
+        };
+        let request = conversation.render(input);
+        conversation.push(Role::User, input);
+
+        // "cancel my booking" after a completed booking refunds it
+        if is_refund_intent(input) {
+            refund_booking(client, registry, config, payment_agent_url, chat_session, reader, stdout).await?;
+            return Ok(());
+        }
+
+        // "continue my booking" replays the saved legs instead of asking the LLM
+        let turn = if is_resume_intent(input) {
+            match resume_booking(client, &config.server_url, config.agent_a_token(), chat_session).await {
+                Some(tool_calls) => Ok(LlmTurn { text: "Resuming the booking".to_string(), tool_calls, usage: None }),
+                None => return Ok(()),
+            }
+        } else {
+            let started = std::time::Instant::now();
+            let turn = llm.chat(&system, &request, tool_definitions).await;
+            if let Ok(turn) = &turn {
+                let tools = turn.tool_calls.iter().map(|(name, _)| name.clone()).collect();
+                let prompt_hash = audit::prompt_hash(&system, &request);
+                audit::llm_decision(llm.name(), prompt_hash, tools, turn, started.elapsed()).await;
+            }
+            turn
+        };
+
+        match turn {
+            Ok(turn) => {
+                let tool_calls = turn.tool_calls;
+                let called: Vec<&str> = tool_calls.iter().map(|(name, _)| name.as_str()).collect();
+                conversation.push(
+                    Role::Assistant,
+                    if called.is_empty() {
+                        turn.text.clone()
+                    } else {
+                        format!("{} [called: {}]", turn.text, called.join(", "))
+                    },
+                );
+                if tool_calls.is_empty() {
+                    // No tools needed, just show the LLM's response
+                    println!("Agent A: {}\n", turn.text);
+                } else {
+                    // Track if this is a payment flow (triggered by get-ticket-price tool)
+                    let is_payment_flow = tool_calls.iter()
+                        .any(|(name, _)| name == "get-ticket-price");
+                    
+                    if is_payment_flow {
+                        // Interactive payment workflow
+                        show_step(1, 3, "Processing booking request...");
+                        
+                        let mut payment_confirmed = false;
+                        let mut itinerary = Itinerary::default();
+                        let mut unverified_price = false;
+                        
+                        for (tool_name, arguments) in &tool_calls {
+                            // Non-payment tools
+                            if !tool_name.contains("enroll") && !tool_name.contains("purchase") && !tool_name.contains("retrieve") {
+                                println!("→ Invoking: {} with args {}", tool_name, arguments);
+
+                                // Each get-ticket-price call is one leg of the itinerary
+                                let mut call_args = arguments.clone();
+                                let leg = (tool_name == "get-ticket-price").then(|| Leg::from_pricing_args(&mut call_args));
+
+                                match call_server_tool(
+                                    client,
+                                    registry,
+                                    tool_name,
+                                    call_args,
+                                    None,
+                                )
+                                .await
+                                {
+                                    Ok(result) => {
+                                        println!("✓ Result: {}\n", result);
+                                        
+                                        // Store the leg's price
+                                        if let Some(mut leg) = leg {
+                                            let parsed = serde_json::from_str::<Value>(&result).unwrap_or_default();
+                                            // A zkfetch-proven price must pass our own check before we take payment for it
+                                            if let Some(proof) = CryptographicProof::from_tool_result(&parsed, &witnesses) {
+                                                if proof.verified {
+                                                    show_success(&format!("Price proof verified locally ({})", proof.identifier.unwrap_or_default()));
+                                                } else {
+                                                    println!("✗ Price proof failed local verification: {}", proof.failure.unwrap_or_default());
+                                                    unverified_price = true;
+                                                }
+                                            }
+                                            leg.apply_quote(&parsed);
+                                            itinerary.legs.push(leg);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        println!("✗ Error: {}\n", e);
+                                        itinerary.legs.extend(leg);
+                                    }
+                                }
+                            }
+                        }
+                        
+                        if unverified_price {
+                            println!("Agent A: I can't verify this price, so I won't proceed with payment.\n");
+                            return Ok(());
+                        }
+
+                        // Every prompt from here on accepts "cancel" and expires when idle
+                        let mut session = BookingSession::new(&itinerary.route(), config.booking_idle_timeout());
+
+                        // Agent B prices by departure day, so undated legs need one before we quote
+                        if !date_undated_legs(client, registry, &witnesses, llm, &mut itinerary, &mut session, reader, stdout).await? {
+                            return Ok(());
+                        }
+                        if !itinerary.legs.is_empty() && itinerary.total().is_none() {
+                            println!("Agent A: I couldn't price every leg of this trip ({}). Please try again.\n", itinerary.route());
+                            return Ok(());
+                        }
+                        
+                        // Every leg is priced: present the itinerary and ask for confirmation
+                        if let Some(total) = itinerary.total() {
+                            if itinerary.legs.len() == 1 {
+                                println!("Agent A: Great! I found a flight {} for {}.", itinerary.leg_label(0), itinerary.format(total));
+                            } else {
+                                println!("Agent A: Great! I priced your {}-leg trip ({}):", itinerary.legs.len(), itinerary.route());
+                                print!("{}", itinerary.describe());
+                                println!("Agent A: Total: {}.", itinerary.format(total));
+                            }
+                            conversation.remember("itinerary", format!("{} at {}", itinerary.route(), itinerary.format(total)));
+                            println!("Agent A: This includes all taxes and fees.\n");
+                            save_progress(client, &config.server_url, config.agent_a_token(), chat_session, &BookingProgress::new(BookingStage::Quoted, &itinerary, Some(total), 0)).await;
+
+                            // Ask user if they want to proceed
+                            let Some(proceed) = session.confirm(BookingStep::ConfirmPrice, "Would you like to proceed with this booking?", reader, stdout)? else {
+                                return Ok(());
+                            };
+                            if proceed {
+                                save_progress(client, &config.server_url, config.agent_a_token(), chat_session, &BookingProgress::new(BookingStage::Confirmed, &itinerary, Some(total), 0)).await;
+
+                                // Get passenger details, one name + email per traveller
+                                let Some(count) = session.ask(BookingStep::PassengerCount, "How many passengers? [1]: ", reader, stdout)? else {
+                                    return Ok(());
+                                };
+                                let count = count.parse::<usize>().unwrap_or(1).clamp(1, MAX_PASSENGERS);
+
+                                let mut passengers = Vec::with_capacity(count);
+                                let mut cancelled = false;
+                                for n in 1..=count {
+                                    let who = if count == 1 { "your".to_string() } else { format!("passenger {}'s", n) };
+                                    let Some(name) = session.ask(BookingStep::PassengerName, &format!("Please enter {} full name: ", who), reader, stdout)? else {
+                                        cancelled = true;
+                                        break;
+                                    };
+                                    let name = extract_or_raw(llm, &name, "full name").await;
+
+                                    let Some(email) = session.ask(BookingStep::PassengerEmail, &format!("Please enter {} email address: ", who), reader, stdout)? else {
+                                        cancelled = true;
+                                        break;
+                                    };
+                                    let email = extract_or_raw(llm, &email, "email address").await;
+                                    passengers.push(Passenger { name, email });
+                                }
+                                if cancelled {
+                                    return Ok(());
+                                }
+                                // Names only: emails stay out of the LLM context
+                                conversation.remember("passengers", passengers.iter().map(|p| p.name.as_str()).collect::<Vec<_>>().join(", "));
+
+                                // Leg prices are per seat
+                                let total = total * passengers.len() as u64;
+                                if passengers.len() > 1 {
+                                    println!("\nAgent A: Total for {} passengers: {}.", passengers.len(), itinerary.format(total));
+                                }
+                                save_progress(
+                                    client,
+                                    &config.server_url,
+                                    config.agent_a_token(),
+                                    chat_session,
+                                    &BookingProgress::new(BookingStage::PassengersEntered, &itinerary, Some(total), passengers.len()),
+                                )
+                                .await;
+                                
+                                // Optionally, no payment until every quoted price is proven
+                                if config.require_proof_before_payment {
+                                    if let Err(e) = prove_quoted_prices(client, registry, &itinerary, &(0..itinerary.legs.len()).collect::<Vec<_>>(), &session).await {
+                                        println!("✗ Couldn't prove your quoted price: {}", e);
+                                        println!("Agent A: I won't take payment for a price I can't prove, so I've stopped this booking.\n");
+                                        return Ok(());
+                                    }
+                                }
+
+                                // Ask about payment method
+                                let crypto_available = registry.offers("create_crypto_payment");
+                                println!("\nAgent A: Great! Let's set up your payment.\n");
+                                println!("How would you like to pay?");
+                                println!("  1. Visa Credit Card");
+                                if crypto_available {
+                                    println!("  2. Crypto (stablecoin transfer)\n");
+                                } else {
+                                    println!("  2. Other payment method\n");
+                                }
+                                
+                                let Some(payment_choice) = session.ask(BookingStep::PaymentMethod, "Choose payment method [1-2] (or 'cancel'): ", reader, stdout)? else {
+                                    return Ok(());
+                                };
+                                
+                                let payment_method = match payment_choice.as_str() {
+                                    "1" => "Visa Credit Card",
+                                    "2" if crypto_available => "Crypto",
+                                    "2" => {
+                                        println!("Agent A: Other payment methods are not yet supported. Please choose Visa.\n");
+                                        "Visa Credit Card"
+                                    }
+                                    _ => {
+                                        println!("Agent A: Invalid choice. Using Visa Credit Card.\n");
+                                        "Visa Credit Card"
+                                    }
+                                };
+                                
+                                if payment_method == "Crypto" {
+                                    // Paid on-chain: book only once the transfer has landed
+                                    show_step(2, 3, "Requesting your crypto payment...");
+                                    let Some(total) = current_total(client, registry, &witnesses, config.require_proof_before_payment, &mut itinerary, passengers.len(), total, &mut session, reader, stdout).await? else {
+                                        return Ok(());
+                                    };
+                                    let Some(pay) = session.confirm(BookingStep::ConfirmPayment, &format!("Pay {} in crypto?", itinerary.format(total)), reader, stdout)? else {
+                                        return Ok(());
+                                    };
+                                    if !pay {
+                                        println!("Agent A: Payment cancelled. Your booking has been cancelled.\n");
+                                        return Ok(());
+                                    }
+                                    let payment = itinerary_payment("create_crypto_payment", &itinerary, total);
+                                    if !allow_payment(client, &config.server_url, config.agent_a_token(), chat_session, policy, &payment, &mut session, reader, stdout).await? {
+                                        return Ok(());
+                                    }
+                                    match pay_with_crypto(client, registry, &itinerary, total, &session).await {
+                                        Ok(Some(tx_hash)) => {
+                                            policy.record(&payment);
+                                            show_success(&format!("Payment received in transaction {}", tx_hash));
+                                            let mut progress = BookingProgress::new(BookingStage::Paid, &itinerary, Some(total), passengers.len());
+                                            progress.payment_method = Some("crypto".to_string());
+                                            progress.payment_reference = Some(tx_hash.clone());
+                                            conversation.remember("payment_tx", tx_hash);
+                                            save_progress(client, &config.server_url, config.agent_a_token(), chat_session, &progress).await;
+
+                                            show_step(3, 3, "Completing your flight booking...");
+                                            for (code, booking_id) in
+                                                book_itinerary(client, registry, &itinerary, &passengers, &session, conversation).await
+                                            {
+                                                progress.confirmation_codes.push(code);
+                                                progress.booking_ids.push(booking_id);
+                                            }
+                                            if !progress.confirmation_codes.is_empty() {
+                                                progress.stage = BookingStage::Booked;
+                                                save_progress(client, &config.server_url, config.agent_a_token(), chat_session, &progress).await;
+                                            }
+                                        }
+                                        Ok(None) => println!("Agent A: No payment arrived, so I haven't booked anything.\n"),
+                                        Err(e) => println!("✗ Crypto payment failed: {}\n", e),
+                                    }
+                                    return Ok(());
+                                }
+
+                                println!("Agent A: Perfect! I'll set up your {} for this transaction.\n", payment_method);
+                                
+                                // User confirmed, proceed directly with payment
+                                println!("Agent A: To proceed with the booking, I'll need to set up payment.\n");
+                                
+                                // Enrollment step
+                                show_step(2, 3, "Enrolling your payment card...");
+
+                                let payments = PaymentClient::new(
+                                    client,
+                                    registry,
+                                    payment_agent_url.unwrap_or("http://localhost:3002"),
+                                    PaymentIds::new(chat_session, session.idempotency_key()),
+                                );
+
+                                // Check if card is already enrolled
+                                let mut enrolled_token = match payments.session().await {
+                                    Ok(payment_session) => payment_session.enrolled_token().map(str::to_string),
+                                    // Session check failed, proceed with enrollment
+                                    Err(e) => {
+                                        tracing::debug!("Payment session lookup failed: {}", e);
+                                        None
+                                    }
+                                };
+                                if enrolled_token.is_some() {
+                                    println!("Agent A: I found an existing payment card in your account.\n");
+                                    show_success("Your card is already enrolled with biometric authentication!");
+                                }
+
+                                // If not enrolled, ask user to enroll
+                                if enrolled_token.is_none() {
+                                    println!("Agent A: Let me securely add your card for this transaction.");
+                                    println!("Agent A: You'll authenticate using your device's biometric authentication (Face ID/Fingerprint).\n");
+
+                                    let Some(ready) = session.confirm(BookingStep::EnrollCard, "Ready to add your card?", reader, stdout)? else {
+                                        return Ok(());
+                                    };
+                                    if ready {
+                                        show_status("Adding your card...");
+                                        match payments.enroll_card().await {
+                                            Ok(token_id) => {
+                                                show_success("Your card has been enrolled with biometric authentication!");
+                                                enrolled_token = Some(token_id);
+                                            }
+                                            Err(e) => println!("✗ Enrollment failed: {}\n", e),
+                                        }
+                                    } else {
+                                        println!("Agent A: Card enrollment cancelled. Unable to proceed with payment.\n");
+                                        return Ok(());
+                                    }
+                                }
+
+                                // Payment confirmation step
+                                if let Some(token_id) = enrolled_token {
+                                    show_step(3, 3, "Confirming payment...");
+
+                                    println!("Agent A: Your card is ready. Shall I proceed with the payment?\n");
+
+                                    let Some(pay) = session.confirm(BookingStep::ConfirmPayment, "Proceed with payment?", reader, stdout)? else {
+                                        return Ok(());
+                                    };
+                                    if pay {
+                                        // A quote may have lapsed while the user was entering details; never charge a stale price
+                                        let Some(total) = current_total(client, registry, &witnesses, config.require_proof_before_payment, &mut itinerary, passengers.len(), total, &mut session, reader, stdout).await? else {
+                                            return Ok(());
+                                        };
+
+                                        let payment = itinerary_payment("initiate-purchase-instruction", &itinerary, total);
+                                        if !allow_payment(client, &config.server_url, config.agent_a_token(), chat_session, policy, &payment, &mut session, reader, stdout).await? {
+                                            return Ok(());
+                                        }
+
+                                        show_status("Processing payment...");
+                                        show_status("You'll be asked to authenticate with biometric on your device...");
+
+                                        // Execute purchase, then retrieve credentials for the instruction it created
+                                        let amount = format_amount(total, itinerary.currency());
+                                        let outcome = match payments
+                                            .initiate_purchase(&token_id, &amount, itinerary.currency(), session.idempotency_key())
+                                            .await
+                                        {
+                                            Ok(purchase) => {
+                                                tracing::info!(
+                                                    "Purchase instruction {} is {}",
+                                                    purchase.instruction_id,
+                                                    purchase.status.as_deref().unwrap_or("pending")
+                                                );
+                                                payments
+                                                    .retrieve_credentials(&token_id, &purchase.instruction_id)
+                                                    .await
+                                                    .map(|credentials| (purchase.instruction_id, credentials))
+                                            }
+                                            Err(e) => Err(e),
+                                        };
+                                        let mut instruction_id = None;
+                                        match outcome {
+                                            Ok((id, credentials)) if credentials.is_authorized() => {
+                                                let network = credentials.details.get("network").and_then(Value::as_str);
+                                                tracing::info!("Payment authorized on {}", network.unwrap_or("unknown network"));
+                                                payment_confirmed = true;
+                                                instruction_id = Some(id);
+                                                policy.record(&payment);
+                                            }
+                                            Ok((_, credentials)) => {
+                                                println!("✗ Payment declined ({})\n", credentials.status.unwrap_or_default());
+                                            }
+                                            Err(e) => println!("✗ Error: {}\n", e),
+                                        }
+
+                                        if payment_confirmed {
+                                            show_success("Payment confirmed! Now I am going to complete your booking!");
+                                            let mut progress = BookingProgress::new(BookingStage::Paid, &itinerary, Some(total), passengers.len());
+                                            progress.payment_method = Some("card".to_string());
+                                            progress.payment_reference = instruction_id;
+                                            save_progress(client, &config.server_url, config.agent_a_token(), chat_session, &progress).await;
+
+                                            show_step(3, 3, "Completing your flight booking...");
+                                            for (code, booking_id) in
+                                                book_itinerary(client, registry, &itinerary, &passengers, &session, conversation).await
+                                            {
+                                                progress.confirmation_codes.push(code);
+                                                progress.booking_ids.push(booking_id);
+                                            }
+                                            if !progress.confirmation_codes.is_empty() {
+                                                progress.stage = BookingStage::Booked;
+                                                save_progress(client, &config.server_url, config.agent_a_token(), chat_session, &progress).await;
+                                            }
+                                        }
+                                    } else {
+                                        println!("Agent A: Payment cancelled. Your booking has been cancelled.\n");
+                                    }
+                                }
+                            } else {
+                                println!("Agent A: Okay, I've cancelled the booking. Let me know if you'd like to try different dates or destinations.\n");
+                                return Ok(());
+                            }
+                        }
+                        
+                    } else {
+                        // Non-payment tool flow: calls are independent, so run them concurrently.
+                        // A payment tool the LLM calls directly still has to pass the policy.
+                        let mut calls = Vec::new();
+                        let mut payments = Vec::new();
+                        for (tool_name, arguments) in tool_calls {
+                            if PAYMENT_TOOLS.contains(&tool_name.as_str()) {
+                                let payment = Payment::from_tool_args(&tool_name, &arguments, MERCHANT);
+                                let mut approval = BookingSession::new(&tool_name, config.booking_idle_timeout());
+                                if !allow_payment(client, &config.server_url, config.agent_a_token(), chat_session, policy, &payment, &mut approval, reader, stdout).await? {
+                                    continue;
+                                }
+                                payments.push(payment);
+                            }
+                            println!("→ Invoking: {} with args {}", tool_name, arguments);
+                            calls.push((tool_name, arguments));
+                        }
+                        let outcomes = call_server_tools(
+                            client,
+                            registry,
+                            calls,
+                            config.tool_concurrency,
+                        )
+                        .await;
+
+                        let mut results = Vec::new();
+                        for (tool_name, outcome) in outcomes {
+                            match outcome {
+                                Ok(result) => {
+                                    if let Some(i) = payments.iter().position(|payment| payment.tool == tool_name) {
+                                        policy.record(&payments.remove(i));
+                                    }
+                                    println!("✓ {}: {}\n", tool_name, result);
+                                    results.push(format!("{}: {}", tool_name, result));
+                                }
+                                Err(e) => {
+                                    println!("✗ {}: {}\n", tool_name, e);
+                                }
+                            }
+                        }
+
+                        if results.is_empty() {
+                            // The LLM's accompanying message to the user
+                            if !turn.text.is_empty() {
+                                println!("Agent A: {}\n", turn.text);
+                            }
+                        } else {
+                            let summary_input = format!("Request: {}\n\nTool results:\n{}", input, results.join("\n"));
+                            if let Err(e) = print_streamed(llm, summary_prompt, &summary_input, stdout).await {
+                                println!("Agent A: {}\n", turn.text);
+                                tracing::debug!("Summary stream failed: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("✗ {} API error: {}\n", llm.name(), e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_concurrent_keyed_calls_execute_once() {
+        let executed = Arc::new(AtomicUsize::new(0));
+        let counter = executed.clone();
+        let app = axum::Router::new().route(
+            "/tools/book-flight",
+            axum::routing::post(move || {
+                let counter = counter.clone();
+                async move {
+                    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                    axum::Json(json!({ "success": true, "data": { "booking_id": format!("bk-{}", n) }, "error": null }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let mut registry = ToolRegistry::new();
+        registry.register("agent_b", &url, None, &[json!({ "name": "book-flight" })]);
+        let client = reqwest::Client::new();
+        let key = uuid::Uuid::new_v4().to_string();
+        let call = || call_server_tool(&client, &registry, "book-flight", json!({}), Some(&key));
+
+        let (first, second) = tokio::join!(call(), call());
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert_eq!(executed.load(Ordering::SeqCst), 1);
+
+        // A different attempt is a different call
+        call_server_tool(&client, &registry, "book-flight", json!({}), Some("another-attempt")).await.unwrap();
+        assert_eq!(executed.load(Ordering::SeqCst), 2);
+    }
+}
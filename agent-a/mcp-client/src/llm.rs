@@ -0,0 +1,522 @@
+//! LLM providers for the travel agent
+//!
+//! `LLM_PROVIDER` selects the backend: `anthropic` (default), `openai` (any
+//! OpenAI-compatible `/chat/completions` endpoint, e.g. a local model server), or
+//! `mock` (deterministic, no network; lets the booking flow run without an API key).
+//!
+//! Tool definitions are passed through as fetched from the MCP servers
+//! (`{"tools": [{name, description, inputSchema}]}`) and mapped per provider.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const ANTHROPIC_URL: &str = "https://api.anthropic.com/v1/messages";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const DEFAULT_ANTHROPIC_MODEL: &str = "claude-3-haiku-20240307";
+const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+const MAX_TOKENS: i32 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Anthropic,
+    #[serde(alias = "openai-compatible")]
+    OpenAi,
+    Mock,
+}
+
+/// What the model said and which tools it asked for
+#[derive(Debug, Default)]
+pub struct LlmTurn {
+    pub text: String,
+    pub tool_calls: Vec<(String, Value)>,
+    /// Tokens billed for the turn, when the provider reports them
+    pub usage: Option<TokenUsage>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// One turn: `system` + `user`, with `tools` offered for native tool use
+    async fn chat(&self, system: &str, user: &str, tools: &Value) -> Result<LlmTurn>;
+
+    /// Pull a single value (`field`, e.g. "email address") out of free text, if present
+    async fn extract_field(&self, text: &str, field: &str) -> Result<Option<String>> {
+        let system = format!(
+            "Extract the {} from the user's text. Reply with the value only, or NONE if it is not present.",
+            field
+        );
+        let turn = self.chat(&system, text, &json!({ "tools": [] })).await?;
+        let value = turn.text.trim().trim_matches('"').to_string();
+        Ok((!value.is_empty() && value != "NONE").then_some(value))
+    }
+
+    /// Plain-text reply streamed as it is generated
+    async fn stream(&self, system: &str, user: &str) -> Result<BoxStream<'static, Result<String>>>;
+}
+
+/// Build the configured provider
+pub fn from_config(
+    kind: ProviderKind,
+    client: reqwest::Client,
+    model: Option<String>,
+    api_key: Option<String>,
+    base_url: Option<String>,
+) -> Box<dyn LlmProvider> {
+    match kind {
+        ProviderKind::Anthropic => Box::new(AnthropicProvider {
+            client,
+            api_key: api_key.unwrap_or_default(),
+            model: model.unwrap_or_else(|| DEFAULT_ANTHROPIC_MODEL.to_string()),
+        }),
+        ProviderKind::OpenAi => Box::new(OpenAiProvider {
+            client,
+            base_url: base_url
+                .unwrap_or_else(|| "https://api.openai.com/v1".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            api_key,
+            model: model.unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string()),
+        }),
+        ProviderKind::Mock => Box::new(MockProvider),
+    }
+}
+
+/// `(name, description, input_schema)` for each fetched MCP tool
+fn tool_specs(tools: &Value) -> Vec<(String, String, Value)> {
+    tools
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|tool| {
+            let name = tool.get("name")?.as_str()?.to_string();
+            let description = tool
+                .get("description")
+                .and_then(|d| d.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let schema = tool
+                .get("inputSchema")
+                .or_else(|| tool.get("input_schema"))
+                .cloned()
+                .unwrap_or_else(|| json!({ "type": "object", "properties": {} }));
+            Some((name, description, schema))
+        })
+        .collect()
+}
+
+/// Split an SSE body into `data:` payloads (stops at OpenAI's `[DONE]`)
+fn sse_data(response: reqwest::Response) -> BoxStream<'static, Result<String>> {
+    response
+        .bytes_stream()
+        .map(|chunk| chunk.map_err(anyhow::Error::from))
+        .scan(String::new(), |buf, chunk| {
+            let events = chunk.map(|bytes| {
+                buf.push_str(&String::from_utf8_lossy(&bytes));
+                let mut events = Vec::new();
+                while let Some(pos) = buf.find('\n') {
+                    let line = buf[..pos].trim().to_string();
+                    buf.drain(..=pos);
+                    if let Some(data) = line.strip_prefix("data:") {
+                        events.push(data.trim().to_string());
+                    }
+                }
+                events
+            });
+            futures::future::ready(Some(events))
+        })
+        .flat_map(|events| match events {
+            Ok(events) => stream::iter(events.into_iter().map(Ok).collect::<Vec<_>>()),
+            Err(e) => stream::iter(vec![Err(e)]),
+        })
+        .take_while(|data| futures::future::ready(!matches!(data, Ok(d) if d == "[DONE]")))
+        .boxed()
+}
+
+// ============================================================================
+// Anthropic
+// ============================================================================
+
+pub struct AnthropicProvider {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ClaudeRequest {
+    model: String,
+    max_tokens: i32,
+    system: String,
+    messages: Vec<ClaudeMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ClaudeTool>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ClaudeMessage {
+    role: String,
+    content: String,
+}
+
+/// Tool definition in Anthropic's `tools` format
+#[derive(Debug, Serialize)]
+struct ClaudeTool {
+    name: String,
+    description: String,
+    input_schema: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeResponse {
+    content: Vec<ContentBlock>,
+    #[serde(default)]
+    stop_reason: String,
+    usage: Option<ClaudeUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        name: String,
+        #[serde(default)]
+        input: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl AnthropicProvider {
+    async fn send(&self, request: &ClaudeRequest) -> Result<reqwest::Response> {
+        let response = self
+            .client
+            .post(ANTHROPIC_URL)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("Claude API error: {}", error_text));
+        }
+        Ok(response)
+    }
+
+    fn request(&self, system: &str, user: &str, tools: Vec<ClaudeTool>, stream: bool) -> ClaudeRequest {
+        ClaudeRequest {
+            model: self.model.clone(),
+            max_tokens: MAX_TOKENS,
+            system: system.to_string(),
+            messages: vec![ClaudeMessage {
+                role: "user".to_string(),
+                content: user.to_string(),
+            }],
+            tools,
+            stream,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    async fn chat(&self, system: &str, user: &str, tools: &Value) -> Result<LlmTurn> {
+        let tools = tool_specs(tools)
+            .into_iter()
+            .map(|(name, description, input_schema)| ClaudeTool { name, description, input_schema })
+            .collect();
+        let response: ClaudeResponse = self
+            .send(&self.request(system, user, tools, false))
+            .await?
+            .json()
+            .await?;
+        if response.content.is_empty() {
+            return Err(anyhow!("No response from Claude"));
+        }
+
+        let mut turn = LlmTurn {
+            usage: response.usage.map(|usage| TokenUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+            }),
+            ..LlmTurn::default()
+        };
+        for block in response.content {
+            match block {
+                ContentBlock::Text { text } => {
+                    if !turn.text.is_empty() {
+                        turn.text.push('\n');
+                    }
+                    turn.text.push_str(&text);
+                }
+                ContentBlock::ToolUse { name, input } => turn.tool_calls.push((name, input)),
+                ContentBlock::Other => {}
+            }
+        }
+        tracing::debug!("Claude stop_reason={} tool_calls={}", response.stop_reason, turn.tool_calls.len());
+        Ok(turn)
+    }
+
+    async fn stream(&self, system: &str, user: &str) -> Result<BoxStream<'static, Result<String>>> {
+        let response = self.send(&self.request(system, user, Vec::new(), true)).await?;
+        // Only `content_block_delta` events carry text
+        Ok(sse_data(response)
+            .filter_map(|data| async move {
+                match data {
+                    Ok(data) => serde_json::from_str::<Value>(&data)
+                        .ok()
+                        .filter(|event| event["type"] == "content_block_delta")
+                        .and_then(|event| event["delta"]["text"].as_str().map(|t| Ok(t.to_string()))),
+                    Err(e) => Some(Err(e)),
+                }
+            })
+            .boxed())
+    }
+}
+
+// ============================================================================
+// OpenAI-compatible (OpenAI, vLLM, Ollama, llama.cpp server, ...)
+// ============================================================================
+
+pub struct OpenAiProvider {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl OpenAiProvider {
+    async fn send(&self, body: Value) -> Result<reqwest::Response> {
+        let mut request = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(anyhow!("LLM API error: {}", error_text));
+        }
+        Ok(response)
+    }
+
+    fn messages(system: &str, user: &str) -> Value {
+        json!([
+            { "role": "system", "content": system },
+            { "role": "user", "content": user }
+        ])
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    async fn chat(&self, system: &str, user: &str, tools: &Value) -> Result<LlmTurn> {
+        let tools: Vec<Value> = tool_specs(tools)
+            .into_iter()
+            .map(|(name, description, parameters)| {
+                json!({
+                    "type": "function",
+                    "function": { "name": name, "description": description, "parameters": parameters }
+                })
+            })
+            .collect();
+
+        let mut body = json!({
+            "model": self.model,
+            "max_tokens": MAX_TOKENS,
+            "messages": Self::messages(system, user),
+        });
+        if !tools.is_empty() {
+            body["tools"] = json!(tools);
+        }
+
+        let response: Value = self.send(body).await?.json().await?;
+        let message = response
+            .pointer("/choices/0/message")
+            .ok_or_else(|| anyhow!("No response from LLM"))?;
+
+        let usage = &response["usage"];
+        let mut turn = LlmTurn {
+            text: message["content"].as_str().unwrap_or_default().to_string(),
+            tool_calls: Vec::new(),
+            usage: usage["prompt_tokens"].as_u64().zip(usage["completion_tokens"].as_u64()).map(
+                |(input_tokens, output_tokens)| TokenUsage { input_tokens, output_tokens },
+            ),
+        };
+        for call in message["tool_calls"].as_array().into_iter().flatten() {
+            let Some(name) = call.pointer("/function/name").and_then(|n| n.as_str()) else {
+                continue;
+            };
+            // Arguments arrive as a JSON-encoded string
+            let arguments = call
+                .pointer("/function/arguments")
+                .and_then(|a| a.as_str())
+                .and_then(|a| serde_json::from_str(a).ok())
+                .unwrap_or_else(|| json!({}));
+            turn.tool_calls.push((name.to_string(), arguments));
+        }
+        Ok(turn)
+    }
+
+    async fn stream(&self, system: &str, user: &str) -> Result<BoxStream<'static, Result<String>>> {
+        let body = json!({
+            "model": self.model,
+            "max_tokens": MAX_TOKENS,
+            "messages": Self::messages(system, user),
+            "stream": true,
+        });
+        let response = self.send(body).await?;
+        Ok(sse_data(response)
+            .filter_map(|data| async move {
+                match data {
+                    Ok(data) => serde_json::from_str::<Value>(&data).ok().and_then(|chunk| {
+                        chunk
+                            .pointer("/choices/0/delta/content")
+                            .and_then(|c| c.as_str())
+                            .map(|t| Ok(t.to_string()))
+                    }),
+                    Err(e) => Some(Err(e)),
+                }
+            })
+            .boxed())
+    }
+}
+
+// ============================================================================
+// Mock
+// ============================================================================
+
+/// Deterministic keyword router: "from X to Y" → `get-ticket-price`, no network
+pub struct MockProvider;
+
+impl MockProvider {
+    /// Word following `keyword` (case-insensitive), stripped of punctuation
+    fn word_after(text: &str, keyword: &str) -> Option<String> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        words
+            .iter()
+            .position(|w| w.eq_ignore_ascii_case(keyword))
+            .and_then(|i| words.get(i + 1))
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_string())
+            .filter(|w| !w.is_empty())
+    }
+
+    fn reply(user: &str) -> LlmTurn {
+        let lower = user.to_lowercase();
+        let route = Self::word_after(user, "from").zip(Self::word_after(user, "to"));
+
+        match route {
+            Some((from, to)) if ["book", "price", "pricing", "flight", "ticket"].iter().any(|k| lower.contains(k)) => {
+                let vip = lower.contains("vip");
+                let mut tool_calls = vec![(
+                    "get-ticket-price".to_string(),
+                    json!({ "from": from, "to": to, "vip": vip }),
+                )];
+                // Round trip: a second leg back
+                if lower.contains("round trip") || lower.contains("return") {
+                    tool_calls.push((
+                        "get-ticket-price".to_string(),
+                        json!({ "from": to, "to": from, "vip": vip }),
+                    ));
+                }
+                LlmTurn {
+                    text: format!("Let me check the price from {} to {}.", from, to),
+                    tool_calls,
+                    usage: None,
+                }
+            }
+            _ => LlmTurn {
+                text: "I can price and book flights — try 'Book a flight from NYC to London'.".to_string(),
+                tool_calls: Vec::new(),
+                usage: None,
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn chat(&self, _system: &str, user: &str, _tools: &Value) -> Result<LlmTurn> {
+        Ok(Self::reply(user))
+    }
+
+    /// Emails are the first `@` token; anything else is the trimmed text itself
+    async fn extract_field(&self, text: &str, field: &str) -> Result<Option<String>> {
+        let text = text.trim();
+        if field.contains("email") {
+            return Ok(text
+                .split_whitespace()
+                .find(|w| w.contains('@'))
+                .map(|w| w.trim_matches(|c: char| c == ',' || c == '.' || c == '<' || c == '>').to_string()));
+        }
+        Ok((!text.is_empty()).then(|| text.to_string()))
+    }
+
+    async fn stream(&self, _system: &str, user: &str) -> Result<BoxStream<'static, Result<String>>> {
+        let words: Vec<Result<String>> = Self::reply(user)
+            .text
+            .split_inclusive(' ')
+            .map(|w| Ok(w.to_string()))
+            .collect();
+        Ok(stream::iter(words).boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_routes_booking_to_pricing_tool() {
+        let llm = MockProvider;
+        let turn = llm
+            .chat("", "Book a VIP flight from NYC to London", &json!({ "tools": [] }))
+            .await
+            .unwrap();
+        assert_eq!(turn.tool_calls.len(), 1);
+        assert_eq!(turn.tool_calls[0].0, "get-ticket-price");
+        assert_eq!(turn.tool_calls[0].1, json!({ "from": "NYC", "to": "London", "vip": true }));
+
+        let email = llm.extract_field("it's jane@example.com.", "email address").await.unwrap();
+        assert_eq!(email.as_deref(), Some("jane@example.com"));
+    }
+}
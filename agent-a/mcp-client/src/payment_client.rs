@@ -0,0 +1,328 @@
+//! Typed calls to the payment agent's card tools and session lookup
+//!
+//! The card flow is enroll-card (once per consumer), then initiate-purchase-instruction
+//! and retrieve-payment-credentials per payment; a cancelled booking's refund goes back
+//! through reverse-purchase-instruction, where the payment agent offers it. Every id the payment agent sees comes
+//! from [`PaymentIds`]: the consumer and payment session follow the chat session, so a
+//! card enrolled earlier in the chat (or before a `--session` restart) is found again,
+//! while enrollment and transaction references follow the booking attempt.
+
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::registry::ToolRegistry;
+use crate::{call_server_tool, tool_response_data};
+
+/// Merchant name shown on the payment instruction
+pub const MERCHANT: &str = "ZeroProof Travel";
+
+/// Payment agent identifiers for one booking attempt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentIds {
+    pub session_id: String,
+    pub consumer_id: String,
+    pub enrollment_reference_id: String,
+    pub transaction_reference_id: String,
+}
+
+impl PaymentIds {
+    /// Ids for the booking keyed `booking_key` in chat session `chat_session`
+    pub fn new(chat_session: &str, booking_key: &str) -> Self {
+        Self {
+            session_id: format!("sess_{}", chat_session),
+            consumer_id: format!("consumer_{}", chat_session),
+            enrollment_reference_id: format!("enroll_{}", booking_key),
+            transaction_reference_id: format!("txn_{}", booking_key),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollCardRequest<'a> {
+    pub session_id: &'a str,
+    pub consumer_id: &'a str,
+    pub enrollment_reference_id: &'a str,
+}
+
+/// enroll-card result; payment agents report success either as `success: true` or as
+/// `status: "SUCCESS"`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollCardResponse {
+    pub success: Option<bool>,
+    pub status: Option<String>,
+    pub token_id: Option<String>,
+}
+
+impl EnrollCardResponse {
+    pub fn is_success(&self) -> bool {
+        self.success == Some(true) || self.status.as_deref().is_some_and(|s| s.eq_ignore_ascii_case("SUCCESS"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurchaseRequest<'a> {
+    pub session_id: &'a str,
+    pub consumer_id: &'a str,
+    pub token_id: &'a str,
+    /// Decimal amount, e.g. "679.90"
+    pub amount: &'a str,
+    pub currency: &'a str,
+    pub merchant: &'a str,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurchaseResponse {
+    pub instruction_id: String,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrieveCredentialsRequest<'a> {
+    pub session_id: &'a str,
+    pub consumer_id: &'a str,
+    pub token_id: &'a str,
+    pub instruction_id: &'a str,
+    pub transaction_reference_id: &'a str,
+}
+
+/// retrieve-payment-credentials result; the credential fields vary by network and are
+/// passed through as-is
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentCredentials {
+    pub status: Option<String>,
+    #[serde(flatten)]
+    pub details: Map<String, Value>,
+}
+
+impl PaymentCredentials {
+    /// Anything but an explicit decline or failure counts as authorized
+    pub fn is_authorized(&self) -> bool {
+        !self
+            .status
+            .as_deref()
+            .is_some_and(|s| ["DECLINED", "FAILED", "ERROR"].iter().any(|bad| s.eq_ignore_ascii_case(bad)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReversePurchaseRequest<'a> {
+    pub session_id: &'a str,
+    pub consumer_id: &'a str,
+    /// Purchase instruction being (partly) reversed
+    pub instruction_id: &'a str,
+    /// Decimal amount to return, at most the amount purchased
+    pub amount: &'a str,
+    pub currency: &'a str,
+    pub transaction_reference_id: &'a str,
+}
+
+/// reverse-purchase-instruction result
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReversalResponse {
+    pub reversal_id: Option<String>,
+    pub status: Option<String>,
+}
+
+impl ReversalResponse {
+    /// Same rule as [`PaymentCredentials::is_authorized`]: only an explicit failure counts
+    pub fn is_reversed(&self) -> bool {
+        !self
+            .status
+            .as_deref()
+            .is_some_and(|s| ["DECLINED", "FAILED", "ERROR", "REJECTED"].iter().any(|bad| s.eq_ignore_ascii_case(bad)))
+    }
+}
+
+/// `GET /session/{id}` on the payment agent
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentSession {
+    #[serde(default)]
+    pub enrolled_token_count: u64,
+    #[serde(default)]
+    pub enrolled_token_ids: Vec<String>,
+}
+
+impl PaymentSession {
+    /// First enrolled card, if any
+    pub fn enrolled_token(&self) -> Option<&str> {
+        if self.enrolled_token_count == 0 {
+            return None;
+        }
+        self.enrolled_token_ids.first().map(String::as_str)
+    }
+}
+
+/// The payment agent, as seen from one booking attempt
+pub struct PaymentClient<'a> {
+    client: &'a reqwest::Client,
+    registry: &'a ToolRegistry,
+    /// Payment agent base URL, for the session lookup (tools go wherever the registry says)
+    base_url: &'a str,
+    pub ids: PaymentIds,
+}
+
+impl<'a> PaymentClient<'a> {
+    pub fn new(client: &'a reqwest::Client, registry: &'a ToolRegistry, base_url: &'a str, ids: PaymentIds) -> Self {
+        Self { client, registry, base_url, ids }
+    }
+
+    /// The consumer's payment session (enrolled cards)
+    pub async fn session(&self) -> Result<PaymentSession> {
+        let url = format!("{}/session/{}", self.base_url, self.ids.session_id);
+        parse(&tool_response_data(&self.client.get(&url).send().await?.json().await?)?)
+    }
+
+    /// Enroll a card; its token id, looked up in the session if enroll-card didn't return one
+    pub async fn enroll_card(&self) -> Result<String> {
+        let request = EnrollCardRequest {
+            session_id: &self.ids.session_id,
+            consumer_id: &self.ids.consumer_id,
+            enrollment_reference_id: &self.ids.enrollment_reference_id,
+        };
+        let response: EnrollCardResponse = self.call("enroll-card", &request, None).await?;
+        if !response.is_success() {
+            return Err(anyhow!("enrollment was not accepted (status {})", response.status.as_deref().unwrap_or("unknown")));
+        }
+        match response.token_id {
+            Some(token_id) => Ok(token_id),
+            None => self
+                .session()
+                .await?
+                .enrolled_token()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("card enrolled but no token id was returned")),
+        }
+    }
+
+    /// Start a purchase of `amount` (decimal string) on the card `token_id`
+    pub async fn initiate_purchase(
+        &self,
+        token_id: &str,
+        amount: &str,
+        currency: &str,
+        idempotency_key: &str,
+    ) -> Result<PurchaseResponse> {
+        let request = PurchaseRequest {
+            session_id: &self.ids.session_id,
+            consumer_id: &self.ids.consumer_id,
+            token_id,
+            amount,
+            currency,
+            merchant: MERCHANT,
+        };
+        self.call("initiate-purchase-instruction", &request, Some(idempotency_key)).await
+    }
+
+    /// Network credentials for an initiated purchase (the user authenticates on their device)
+    pub async fn retrieve_credentials(&self, token_id: &str, instruction_id: &str) -> Result<PaymentCredentials> {
+        let request = RetrieveCredentialsRequest {
+            session_id: &self.ids.session_id,
+            consumer_id: &self.ids.consumer_id,
+            token_id,
+            instruction_id,
+            transaction_reference_id: &self.ids.transaction_reference_id,
+        };
+        self.call("retrieve-payment-credentials", &request, None).await
+    }
+
+    /// Return `amount` (decimal string) of the purchase `instruction_id` to the card
+    pub async fn reverse_purchase(
+        &self,
+        instruction_id: &str,
+        amount: &str,
+        currency: &str,
+        idempotency_key: &str,
+    ) -> Result<ReversalResponse> {
+        let request = ReversePurchaseRequest {
+            session_id: &self.ids.session_id,
+            consumer_id: &self.ids.consumer_id,
+            instruction_id,
+            amount,
+            currency,
+            transaction_reference_id: &self.ids.transaction_reference_id,
+        };
+        self.call("reverse-purchase-instruction", &request, Some(idempotency_key)).await
+    }
+
+    async fn call<Req: Serialize, Res: DeserializeOwned>(
+        &self,
+        tool: &str,
+        request: &Req,
+        idempotency_key: Option<&str>,
+    ) -> Result<Res> {
+        let args = serde_json::to_value(request)?;
+        println!("→ Invoking: {} with args {}", tool, args);
+        let result = call_server_tool(self.client, self.registry, tool, args, idempotency_key).await?;
+        println!("✓ Result: {}\n", result);
+        parse(&result).map_err(|e| anyhow!("unexpected {} response: {}", tool, e))
+    }
+}
+
+fn parse<T: DeserializeOwned>(result: &str) -> Result<T> {
+    Ok(serde_json::from_str(result)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_follow_the_chat_and_booking() {
+        let ids = PaymentIds::new("chat-1", "booking-9");
+        assert_eq!(ids.session_id, "sess_chat-1");
+        assert_eq!(ids.consumer_id, "consumer_chat-1");
+        assert_eq!(ids.enrollment_reference_id, "enroll_booking-9");
+        assert_eq!(ids.transaction_reference_id, "txn_booking-9");
+
+        let request = EnrollCardRequest {
+            session_id: &ids.session_id,
+            consumer_id: &ids.consumer_id,
+            enrollment_reference_id: &ids.enrollment_reference_id,
+        };
+        let args = serde_json::to_value(&request).unwrap();
+        assert_eq!(args["enrollmentReferenceId"], "enroll_booking-9");
+    }
+
+    #[test]
+    fn test_parses_response_variants() {
+        let enrolled: EnrollCardResponse = parse(r#"{"success": true, "tokenId": "tok_1"}"#).unwrap();
+        assert!(enrolled.is_success());
+        assert_eq!(enrolled.token_id.as_deref(), Some("tok_1"));
+        let enrolled: EnrollCardResponse = parse(r#"{"status": "SUCCESS"}"#).unwrap();
+        assert!(enrolled.is_success());
+        assert!(enrolled.token_id.is_none());
+        let rejected: EnrollCardResponse = parse(r#"{"success": false, "status": "PENDING"}"#).unwrap();
+        assert!(!rejected.is_success());
+
+        let purchase: PurchaseResponse = parse(r#"{"instructionId": "ins_1", "status": "CREATED"}"#).unwrap();
+        assert_eq!(purchase.instruction_id, "ins_1");
+        assert!(parse::<PurchaseResponse>(r#"{"status": "CREATED"}"#).is_err());
+
+        let credentials: PaymentCredentials = parse(r#"{"cryptogram": "abc", "network": "visa"}"#).unwrap();
+        assert!(credentials.is_authorized());
+        assert_eq!(credentials.details["network"], "visa");
+        let declined: PaymentCredentials = parse(r#"{"status": "declined"}"#).unwrap();
+        assert!(!declined.is_authorized());
+
+        let reversed: ReversalResponse = parse(r#"{"reversalId": "rev_1", "status": "REVERSED"}"#).unwrap();
+        assert!(reversed.is_reversed());
+        assert_eq!(reversed.reversal_id.as_deref(), Some("rev_1"));
+        let rejected: ReversalResponse = parse(r#"{"status": "rejected"}"#).unwrap();
+        assert!(!rejected.is_reversed());
+
+        let session: PaymentSession = parse(r#"{"enrolledTokenCount": 2, "enrolledTokenIds": ["tok_a", "tok_b"]}"#).unwrap();
+        assert_eq!(session.enrolled_token(), Some("tok_a"));
+        let empty: PaymentSession = parse("{}").unwrap();
+        assert_eq!(empty.enrolled_token(), None);
+    }
+}
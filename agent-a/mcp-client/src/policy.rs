@@ -0,0 +1,269 @@
+//! Spending rules checked before any payment tool runs
+//!
+//! The chat client is the one place that sees both the itinerary and every payment rail
+//! (card tools on the payment agent, `create_crypto_payment` on Agent A), so it enforces
+//! the rules itself: a cap per payment and per day, allowed merchants and routes, and an
+//! amount above which the user has to approve the payment explicitly. A refused payment
+//! never reaches its tool; the user is shown the [`PolicyViolation`] and it is logged to
+//! the session's proof trace on Agent A (`POST /sessions/{id}/denied-actions`).
+//!
+//! Amounts are in the payment currency's minor units. The daily total counts payments
+//! made through this client process since midnight UTC, per currency.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Tools that move money and so go through [`PaymentPolicy::check`]
+pub const PAYMENT_TOOLS: &[&str] = &["initiate-purchase-instruction", "create_crypto_payment"];
+
+#[derive(Debug, Clone, Default)]
+pub struct PolicyRules {
+    pub max_per_transaction_cents: Option<u64>,
+    pub max_per_day_cents: Option<u64>,
+    /// Empty: any merchant
+    pub allowed_merchants: Vec<String>,
+    /// Payments above this need the user's explicit approval
+    pub approval_threshold_cents: Option<u64>,
+    /// "FROM-TO" pairs as passed to get-ticket-price, e.g. "NYC-LON"; empty: any route
+    pub allowed_routes: Vec<String>,
+}
+
+/// Entries of a comma-separated setting, trimmed
+pub fn parse_list(list: Option<&str>) -> Vec<String> {
+    list.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// A payment about to be made
+#[derive(Debug, Clone)]
+pub struct Payment {
+    pub tool: String,
+    pub amount_cents: u64,
+    pub currency: String,
+    pub merchant: String,
+    /// (from, to) of every leg paid for; `None` for a payment tool called outside the
+    /// booking flow, which the route rule then refuses
+    pub legs: Option<Vec<(String, String)>>,
+}
+
+impl Payment {
+    /// A payment tool call the LLM issued directly, from its arguments (`amount` as a
+    /// decimal string or `amount_cents`)
+    pub fn from_tool_args(tool: &str, args: &Value, default_merchant: &str) -> Self {
+        let currency = args["currency"].as_str().unwrap_or("USD").to_string();
+        let amount_cents = args["amount_cents"]
+            .as_u64()
+            .or_else(|| args["amount"].as_str().and_then(|amount| parse_amount(amount, &currency)))
+            .or_else(|| args["amount"].as_f64().and_then(|amount| parse_amount(&amount.to_string(), &currency)))
+            // An amount we can't read is checked as unlimited, so any cap refuses it
+            .unwrap_or(u64::MAX);
+        Self {
+            tool: tool.to_string(),
+            amount_cents,
+            currency,
+            merchant: args["merchant"].as_str().unwrap_or(default_merchant).to_string(),
+            legs: None,
+        }
+    }
+}
+
+/// Decimal amount to minor units: "679.90" USD → 67990, "101645" JPY → 101645
+pub fn parse_amount(amount: &str, currency: &str) -> Option<u64> {
+    let digits = if currency.eq_ignore_ascii_case("JPY") { 0 } else { 2 };
+    let (whole, fraction) = amount.trim().split_once('.').unwrap_or((amount.trim(), ""));
+    if fraction.len() > digits || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let whole: u64 = whole.parse().ok()?;
+    let fraction: u64 = if fraction.is_empty() { 0 } else { format!("{:0<width$}", fraction, width = digits).parse().ok()? };
+    whole.checked_mul(10u64.pow(digits as u32))?.checked_add(fraction)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyRule {
+    MaxPerTransaction,
+    MaxPerDay,
+    Merchant,
+    Route,
+}
+
+/// Why a payment was refused
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicyViolation {
+    pub rule: PolicyRule,
+    pub message: String,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// What to do with a payment the rules allow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// Above the approval threshold: ask the user before paying
+    NeedsApproval,
+}
+
+pub struct PaymentPolicy {
+    rules: PolicyRules,
+    /// Spent per (UTC day, currency)
+    spent: Mutex<HashMap<(u64, String), u64>>,
+}
+
+impl PaymentPolicy {
+    pub fn new(rules: PolicyRules) -> Self {
+        Self { rules, spent: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn check(&self, payment: &Payment) -> Result<Verdict, PolicyViolation> {
+        self.check_on(payment, today())
+    }
+
+    /// Count a payment that went through towards today's total
+    pub fn record(&self, payment: &Payment) {
+        self.record_on(payment, today());
+    }
+
+    fn check_on(&self, payment: &Payment, day: u64) -> Result<Verdict, PolicyViolation> {
+        let rules = &self.rules;
+        let deny = |rule, message: String| Err(PolicyViolation { rule, message });
+        let amount = |cents: u64| crate::booking::format_price(cents, &payment.currency);
+
+        if let Some(max) = rules.max_per_transaction_cents {
+            if payment.amount_cents > max {
+                return deny(
+                    PolicyRule::MaxPerTransaction,
+                    format!("{} is over the {} limit per payment", amount(payment.amount_cents), amount(max)),
+                );
+            }
+        }
+        if let Some(max) = rules.max_per_day_cents {
+            let spent = self.spent_on(day, &payment.currency);
+            if spent.saturating_add(payment.amount_cents) > max {
+                return deny(
+                    PolicyRule::MaxPerDay,
+                    format!(
+                        "{} would take today's spending over the {} daily limit ({} already spent)",
+                        amount(payment.amount_cents),
+                        amount(max),
+                        amount(spent)
+                    ),
+                );
+            }
+        }
+        if !rules.allowed_merchants.is_empty()
+            && !rules.allowed_merchants.iter().any(|merchant| merchant.eq_ignore_ascii_case(&payment.merchant))
+        {
+            return deny(PolicyRule::Merchant, format!("payments to {} are not allowed", payment.merchant));
+        }
+        if !rules.allowed_routes.is_empty() {
+            let Some(legs) = &payment.legs else {
+                return deny(PolicyRule::Route, "payments are only allowed for booked routes".to_string());
+            };
+            for (from, to) in legs {
+                let route = format!("{}-{}", from, to);
+                if !rules.allowed_routes.iter().any(|allowed| allowed.eq_ignore_ascii_case(&route)) {
+                    return deny(PolicyRule::Route, format!("flights {} → {} are not allowed", from, to));
+                }
+            }
+        }
+
+        match rules.approval_threshold_cents {
+            Some(threshold) if payment.amount_cents > threshold => Ok(Verdict::NeedsApproval),
+            _ => Ok(Verdict::Allow),
+        }
+    }
+
+    fn record_on(&self, payment: &Payment, day: u64) {
+        let mut spent = self.spent.lock().unwrap();
+        let total = spent.entry((day, payment.currency.to_ascii_uppercase())).or_default();
+        *total = total.saturating_add(payment.amount_cents);
+    }
+
+    fn spent_on(&self, day: u64, currency: &str) -> u64 {
+        self.spent.lock().unwrap().get(&(day, currency.to_ascii_uppercase())).copied().unwrap_or_default()
+    }
+}
+
+/// Days since the Unix epoch (UTC)
+fn today() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payment(amount_cents: u64) -> Payment {
+        Payment {
+            tool: "initiate-purchase-instruction".into(),
+            amount_cents,
+            currency: "USD".into(),
+            merchant: "ZeroProof Travel".into(),
+            legs: Some(vec![("NYC".into(), "LON".into())]),
+        }
+    }
+
+    #[test]
+    fn test_rules_refuse_or_escalate_payments() {
+        let policy = PaymentPolicy::new(PolicyRules {
+            max_per_transaction_cents: Some(100_000),
+            max_per_day_cents: Some(150_000),
+            allowed_merchants: parse_list(Some("ZeroProof Travel, Other Air")),
+            approval_threshold_cents: Some(50_000),
+            allowed_routes: parse_list(Some("NYC-LON,LON-NYC")),
+        });
+
+        assert_eq!(policy.check_on(&payment(40_000), 1), Ok(Verdict::Allow));
+        assert_eq!(policy.check_on(&payment(68_250), 1), Ok(Verdict::NeedsApproval));
+        assert_eq!(policy.check_on(&payment(120_000), 1).unwrap_err().rule, PolicyRule::MaxPerTransaction);
+
+        policy.record_on(&payment(90_000), 1);
+        let over = policy.check_on(&payment(68_250), 1).unwrap_err();
+        assert_eq!(over.rule, PolicyRule::MaxPerDay);
+        assert_eq!(over.message, "$682.50 would take today's spending over the $1500.00 daily limit ($900.00 already spent)");
+        // A new day starts from zero
+        assert!(policy.check_on(&payment(68_250), 2).is_ok());
+
+        let mut elsewhere = payment(10_000);
+        elsewhere.merchant = "Shady Tours".into();
+        assert_eq!(policy.check_on(&elsewhere, 2).unwrap_err().rule, PolicyRule::Merchant);
+
+        let mut paris = payment(10_000);
+        paris.legs = Some(vec![("NYC".into(), "LON".into()), ("LON".into(), "PAR".into())]);
+        assert_eq!(policy.check_on(&paris, 2).unwrap_err().rule, PolicyRule::Route);
+
+        // Called by the LLM outside the booking flow: no route, and the amount is parsed
+        let direct = Payment::from_tool_args("initiate-purchase-instruction", &json!({"amount": "99.90", "currency": "USD"}), "ZeroProof Travel");
+        assert_eq!(direct.amount_cents, 9_990);
+        assert_eq!(policy.check_on(&direct, 2).unwrap_err().rule, PolicyRule::Route);
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("679.90", "USD"), Some(67_990));
+        assert_eq!(parse_amount("679.9", "EUR"), Some(67_990));
+        assert_eq!(parse_amount("679", "USD"), Some(67_900));
+        assert_eq!(parse_amount("101645", "JPY"), Some(101_645));
+        assert_eq!(parse_amount("1.005", "USD"), None);
+        assert_eq!(parse_amount("abc", "USD"), None);
+    }
+}
@@ -0,0 +1,140 @@
+//! LLM prompts rendered from templates (`prompts/*.j2`)
+//!
+//! The system prompt is `system.j2` plus one policy block per tool family
+//! (`travel_tools.j2`, `payment_workflow.j2`, `zk_tools.j2`). Templates see the names of
+//! the tools the connected servers offer as `tools`, and each block only mentions tools
+//! in that list, so the LLM isn't told about tools it can't call. Tool names are written
+//! in backticks, which is how [`referenced_tools`] finds them.
+//!
+//! Built-in templates are compiled in; a file of the same name in PROMPTS_DIR replaces
+//! one per deployment, and further templates there can be included by the overrides.
+
+use anyhow::Result;
+use minijinja::{context, Environment, UndefinedBehavior};
+use std::path::PathBuf;
+
+use crate::registry::ToolRegistry;
+
+const BUILTIN: &[(&str, &str)] = &[
+    ("system.j2", include_str!("../prompts/system.j2")),
+    ("travel_tools.j2", include_str!("../prompts/travel_tools.j2")),
+    ("payment_workflow.j2", include_str!("../prompts/payment_workflow.j2")),
+    ("zk_tools.j2", include_str!("../prompts/zk_tools.j2")),
+    ("summary.j2", include_str!("../prompts/summary.j2")),
+];
+
+pub struct Prompts {
+    env: Environment<'static>,
+}
+
+impl Prompts {
+    /// Built-in templates, with any in `dir` taking precedence
+    pub fn load(dir: Option<&str>) -> Self {
+        let dir = dir.map(PathBuf::from);
+        let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_loader(move |name| {
+            if let Some(path) = dir.as_ref().map(|dir| dir.join(name)).filter(|path| path.is_file()) {
+                return std::fs::read_to_string(&path).map(Some).map_err(|e| {
+                    minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, format!("{}: {}", path.display(), e))
+                });
+            }
+            Ok(BUILTIN.iter().find(|(builtin, _)| *builtin == name).map(|(_, source)| source.to_string()))
+        });
+        Self { env }
+    }
+
+    /// Routing instructions for the tool-selecting turn, covering the tools in `registry`
+    pub fn system(&self, registry: &ToolRegistry) -> Result<String> {
+        let tools: Vec<&str> = registry.names().collect();
+        Ok(self.env.get_template("system.j2")?.render(context! { tools })?)
+    }
+
+    /// Instructions for the plain-language summary of tool results
+    pub fn summary(&self) -> Result<String> {
+        Ok(self.env.get_template("summary.j2")?.render(context! {})?)
+    }
+}
+
+/// Tool names a rendered prompt mentions (backticked identifiers), in order of appearance
+pub fn referenced_tools(prompt: &str) -> Vec<&str> {
+    let mut tools = Vec::new();
+    for name in prompt.split('`').skip(1).step_by(2) {
+        let is_tool_name = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if is_tool_name && !tools.contains(&name) {
+            tools.push(name);
+        }
+    }
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const AGENT_B_TOOLS: &[&str] = &["get-ticket-price", "get-flight-options", "validate-promo", "book-flight"];
+    const AGENT_A_TOOLS: &[&str] = &[
+        "format_zk_input",
+        "request_attestation",
+        "verify_on_chain",
+        "attest_price",
+        "attest_cheapest_option",
+    ];
+    const PAYMENT_TOOLS: &[&str] = &["enroll-card", "initiate-purchase-instruction", "retrieve-payment-credentials"];
+    const BOOKING_ONLY: &[&str] = &["get-ticket-price", "book-flight"];
+
+    fn registry(servers: &[(&'static str, &[&str])]) -> ToolRegistry {
+        let mut registry = ToolRegistry::new();
+        for (server, names) in servers {
+            let tools: Vec<_> = names.iter().map(|name| json!({"name": name})).collect();
+            registry.register(server, "http://localhost", None, &tools);
+        }
+        registry
+    }
+
+    #[test]
+    fn test_system_prompt_only_names_offered_tools() {
+        let prompts = Prompts::load(None);
+        let cases = [
+            registry(&[("agent_a", AGENT_A_TOOLS), ("agent_b", AGENT_B_TOOLS), ("payment_agent", PAYMENT_TOOLS)]),
+            registry(&[("agent_a", AGENT_A_TOOLS), ("agent_b", AGENT_B_TOOLS)]),
+            registry(&[("agent_b", BOOKING_ONLY)]),
+            registry(&[]),
+        ];
+        for registry in &cases {
+            let prompt = prompts.system(registry).unwrap();
+            let offered: Vec<&str> = registry.names().collect();
+            for tool in referenced_tools(&prompt) {
+                assert!(offered.contains(&tool), "prompt names {} but the registry doesn't offer it", tool);
+            }
+        }
+
+        // With every server connected, every tool is covered by a policy block
+        let full = &cases[0];
+        let prompt = prompts.system(full).unwrap();
+        let referenced = referenced_tools(&prompt);
+        for tool in full.names() {
+            assert!(referenced.contains(&tool), "no policy mentions {}", tool);
+        }
+        assert!(prompt.contains("with payment capabilities"));
+        assert!(!prompts.system(&cases[1]).unwrap().contains("PAYMENT TOOLS"));
+    }
+
+    #[test]
+    fn test_prompts_dir_overrides_a_block() {
+        let dir = std::env::temp_dir().join(format!("prompts-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("zk_tools.j2"), "\nZK: {{ tools | length }} tools").unwrap();
+
+        let prompts = Prompts::load(dir.to_str());
+        let prompt = prompts.system(&registry(&[("agent_a", AGENT_A_TOOLS)])).unwrap();
+        assert!(prompt.contains("ZK: 5 tools"));
+        assert!(!prompt.contains("OTHER TOOLS"));
+        assert!(prompts.summary().unwrap().starts_with("You are Agent A"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+//! Local verification of zkfetch (Reclaim zk-TLS) proofs
+//!
+//! The zkfetch-wrapper reports its own `verified` flag; we don't trust it. A proof is
+//! accepted only if, checked here:
+//! - `claimData.identifier` is keccak256(provider \n parameters \n context)
+//! - every signature recovers (EIP-191 over identifier \n owner \n timestampS \n epoch)
+//!   to one of the proof's witnesses, and every witness signed
+//! - every signer is one of the [`TrustedWitnesses`] (RECLAIM_WITNESSES); the proof's own
+//!   witness list only says who signed, and anyone can sign a claim and list themselves
+//! - the epoch is set and the claim timestamp is not in the future or too old
+
+use ethers::types::{Address, Signature};
+use ethers::utils::keccak256;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Tolerated clock skew for claim timestamps
+const MAX_CLOCK_SKEW_SECS: u64 = 300;
+/// Proofs older than this are rejected as stale
+const MAX_PROOF_AGE_SECS: u64 = 24 * 60 * 60;
+
+/// Reclaim attestors whose signatures are accepted; empty accepts no proof at all
+#[derive(Debug, Clone, Default)]
+pub struct TrustedWitnesses(HashSet<Address>);
+
+impl TrustedWitnesses {
+    /// Parse witness addresses, e.g. from a comma-separated RECLAIM_WITNESSES
+    pub fn parse(addresses: &[String]) -> Result<Self, String> {
+        addresses
+            .iter()
+            .map(|a| Address::from_str(a).map_err(|e| format!("invalid witness address {}: {}", a, e)))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReclaimProof {
+    claim_data: ClaimData,
+    signatures: Vec<String>,
+    witnesses: Vec<Witness>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClaimData {
+    provider: String,
+    parameters: String,
+    #[serde(default)]
+    context: String,
+    owner: String,
+    timestamp_s: u64,
+    epoch: u64,
+    identifier: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Witness {
+    id: String,
+}
+
+/// A zkfetch proof attached to a tool result, with our own verification verdict
+#[derive(Debug, Clone)]
+pub struct CryptographicProof {
+    pub identifier: Option<String>,
+    /// Set from [`verify`], never from the wrapper's `verified` field
+    pub verified: bool,
+    pub failure: Option<String>,
+}
+
+impl CryptographicProof {
+    /// Extract and verify the `proof` carried by a zkfetch-style tool result, if any
+    pub fn from_tool_result(result: &Value, trusted: &TrustedWitnesses) -> Option<Self> {
+        let proof = result.get("proof")?;
+        let mock = result.pointer("/metadata/mock").and_then(|m| m.as_bool()).unwrap_or(false);
+
+        let outcome = if mock {
+            Err("mock proof (Reclaim not configured)".to_string())
+        } else {
+            verify(proof, trusted)
+        };
+        Some(Self {
+            identifier: proof
+                .pointer("/claimData/identifier")
+                .and_then(|i| i.as_str())
+                .map(str::to_string),
+            verified: outcome.is_ok(),
+            failure: outcome.err(),
+        })
+    }
+}
+
+/// Verify a Reclaim proof's structure and that `trusted` witnesses signed it
+pub fn verify(proof: &Value, trusted: &TrustedWitnesses) -> Result<(), String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    verify_at(proof, trusted, now)
+}
+
+fn verify_at(proof: &Value, trusted: &TrustedWitnesses, now: u64) -> Result<(), String> {
+    let proof: ReclaimProof =
+        serde_json::from_value(proof.clone()).map_err(|e| format!("malformed proof: {}", e))?;
+    let claim = &proof.claim_data;
+
+    let expected_id = format!(
+        "0x{}",
+        hex::encode(keccak256(format!("{}\n{}\n{}", claim.provider, claim.parameters, claim.context)))
+    );
+    if !claim.identifier.eq_ignore_ascii_case(&expected_id) {
+        return Err(format!("identifier mismatch: claim says {}, data hashes to {}", claim.identifier, expected_id));
+    }
+
+    if claim.epoch == 0 {
+        return Err("epoch is not set".to_string());
+    }
+    if claim.timestamp_s > now + MAX_CLOCK_SKEW_SECS {
+        return Err(format!("claim timestamp {} is in the future", claim.timestamp_s));
+    }
+    if now.saturating_sub(claim.timestamp_s) > MAX_PROOF_AGE_SECS {
+        return Err(format!("claim timestamp {} is older than {}s", claim.timestamp_s, MAX_PROOF_AGE_SECS));
+    }
+
+    let witnesses: HashSet<Address> = proof
+        .witnesses
+        .iter()
+        .map(|w| Address::from_str(&w.id).map_err(|e| format!("invalid witness {}: {}", w.id, e)))
+        .collect::<Result<_, _>>()?;
+    if witnesses.is_empty() || proof.signatures.is_empty() {
+        return Err("proof has no witnesses or signatures".to_string());
+    }
+
+    let sign_data = format!(
+        "{}\n{}\n{}\n{}",
+        claim.identifier.to_lowercase(),
+        claim.owner.to_lowercase(),
+        claim.timestamp_s,
+        claim.epoch
+    );
+    let mut signers = HashSet::new();
+    for signature in &proof.signatures {
+        let signature = Signature::from_str(signature).map_err(|e| format!("invalid signature: {}", e))?;
+        let signer = signature
+            .recover(sign_data.as_str())
+            .map_err(|e| format!("signature recovery failed: {}", e))?;
+        if !witnesses.contains(&signer) {
+            return Err(format!("signature by {:?}, which is not a witness for this claim", signer));
+        }
+        if !trusted.0.contains(&signer) {
+            return Err(format!("signature by {:?}, which is not a trusted Reclaim witness", signer));
+        }
+        signers.insert(signer);
+    }
+    if signers != witnesses {
+        return Err(format!("{} of {} witnesses signed", signers.len(), witnesses.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::{LocalWallet, Signer};
+    use serde_json::json;
+
+    fn signed_proof(wallet: &LocalWallet, timestamp: u64) -> Value {
+        let (provider, parameters, context) = ("http", r#"{"url":"https://api.example.com/price"}"#, "");
+        let identifier = format!("0x{}", hex::encode(keccak256(format!("{}\n{}\n{}", provider, parameters, context))));
+        let owner = "0x0000000000000000000000000000000000000001";
+        let message = format!("{}\n{}\n{}\n{}", identifier, owner, timestamp, 1);
+        let signature = wallet.sign_hash(ethers::utils::hash_message(message)).unwrap();
+
+        json!({
+            "claimData": {
+                "provider": provider, "parameters": parameters, "context": context,
+                "owner": owner, "timestampS": timestamp, "epoch": 1, "identifier": identifier
+            },
+            "signatures": [format!("0x{}", signature)],
+            "witnesses": [{ "id": format!("{:?}", wallet.address()), "url": "wss://witness" }]
+        })
+    }
+
+    fn witness() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".parse().unwrap()
+    }
+
+    fn trusting(wallet: &LocalWallet) -> TrustedWitnesses {
+        TrustedWitnesses::parse(&[format!("{:?}", wallet.address())]).unwrap()
+    }
+
+    #[test]
+    fn test_verify_accepts_witness_signature_and_rejects_tampering() {
+        let wallet = witness();
+        let trusted = trusting(&wallet);
+        let now = 1_700_000_000;
+        let proof = signed_proof(&wallet, now - 60);
+        assert_eq!(verify_at(&proof, &trusted, now), Ok(()));
+
+        let mut tampered = proof.clone();
+        tampered["claimData"]["parameters"] = json!(r#"{"url":"https://evil.example.com"}"#);
+        assert!(verify_at(&tampered, &trusted, now).unwrap_err().contains("identifier mismatch"));
+
+        assert!(verify_at(&proof, &trusted, now + MAX_PROOF_AGE_SECS + 120).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_self_signed_proof() {
+        // Signed by its own listed witness, but not one of the trusted attestors
+        let now = 1_700_000_000;
+        let impostor: LocalWallet = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d".parse().unwrap();
+        let proof = signed_proof(&impostor, now - 60);
+        let error = verify_at(&proof, &trusting(&witness()), now).unwrap_err();
+        assert!(error.contains("not a trusted Reclaim witness"), "{}", error);
+
+        assert!(verify_at(&proof, &TrustedWitnesses::default(), now).is_err());
+        assert!(TrustedWitnesses::parse(&["not-an-address".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Tool → server routing built from `/tools` discovery
+//!
+//! Every tool is tagged with the server that advertised it, so routing is a lookup
+//! rather than a hard-coded name list. Tools no connected server offers are an error.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Server a tool was discovered on
+#[derive(Debug, Clone)]
+pub struct Origin {
+    pub server: &'static str,
+    pub url: String,
+    /// Bearer token the server wants, if any
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ToolRegistry {
+    /// Definitions in discovery order, as offered to the LLM
+    tools: Vec<Value>,
+    origins: HashMap<String, Origin>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `tools` discovered on `server` at `url`, called with `token`; returns how
+    /// many were added
+    ///
+    /// A name already registered by an earlier server keeps its first origin.
+    pub fn register(&mut self, server: &'static str, url: &str, token: Option<&str>, tools: &[Value]) -> usize {
+        let mut added = 0;
+        for tool in tools {
+            let Some(name) = tool.get("name").and_then(|n| n.as_str()) else {
+                continue;
+            };
+            if let Some(existing) = self.origins.get(name) {
+                tracing::warn!("Tool {} offered by both {} and {}; keeping {}", name, existing.server, server, existing.server);
+                continue;
+            }
+            self.origins.insert(
+                name.to_string(),
+                Origin {
+                    server,
+                    url: url.trim_end_matches('/').to_string(),
+                    token: token.map(str::to_string),
+                },
+            );
+            self.tools.push(tool.clone());
+            added += 1;
+        }
+        added
+    }
+
+    pub fn origin(&self, tool_name: &str) -> Result<&Origin> {
+        self.origins
+            .get(tool_name)
+            .ok_or_else(|| anyhow!("Unknown tool '{}': not offered by any connected server", tool_name))
+    }
+
+    /// Whether a connected server offers `tool_name`
+    pub fn offers(&self, tool_name: &str) -> bool {
+        self.origins.contains_key(tool_name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Names of the registered tools, in discovery order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tools.iter().filter_map(|tool| tool.get("name").and_then(|n| n.as_str()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        self.tools.iter()
+    }
+
+    /// `{"tools": [...]}` in the shape the servers advertise
+    pub fn definitions(&self) -> Value {
+        json!({ "tools": self.tools })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_by_origin_and_rejects_unknown_tools() {
+        let mut registry = ToolRegistry::new();
+        registry.register("agent_a", "http://localhost:3001", Some("secret"), &[json!({ "name": "attest_price" })]);
+        registry.register("agent_b", "http://localhost:8001/", None, &[json!({ "name": "get-ticket-price" })]);
+
+        assert_eq!(registry.origin("get-ticket-price").unwrap().url, "http://localhost:8001");
+        assert_eq!(registry.origin("attest_price").unwrap().server, "agent_a");
+        assert_eq!(registry.origin("attest_price").unwrap().token.as_deref(), Some("secret"));
+        assert!(registry.origin("get-ticket-price").unwrap().token.is_none());
+        assert!(registry.origin("enroll-card").is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! End-to-end runs of `Chat::process_user_query` against the mocks in `common`: the guided
+//! booking flow with card and crypto payment, the spending policy, cancelling mid-flow and
+//! after booking, proving the quoted price before payment, and authenticating to Agent A
+
+mod common;
+
+use common::{price_turn, turn, CannedLlm, Mocks, CARD_BOOKING, PRICE_CENTS};
+use serde_json::json;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_card_booking_then_refund() {
+    let harness = Mocks::new().start().await;
+    let mut answers = CARD_BOOKING.to_vec();
+    answers.push("y"); // cancel the booking afterwards
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), json!({}), &answers).await;
+
+    chat.process_user_query("Book a flight from NYC to London on December 1st").await.unwrap();
+
+    let quotes = harness.agent_b.calls.to("get-ticket-price");
+    assert_eq!(quotes, vec![json!({ "from": "NYC", "to": "LON", "departure_date": "2026-12-01" })]);
+    assert_eq!(
+        harness.payments.calls.names(),
+        ["enroll-card", "initiate-purchase-instruction", "retrieve-payment-credentials"]
+    );
+    let purchase = &harness.payments.calls.to("initiate-purchase-instruction")[0];
+    assert_eq!(purchase["amount"], "682.50");
+    assert_eq!(purchase["tokenId"], "tok_1");
+    let booking = &harness.agent_b.calls.to("book-flight")[0];
+    assert_eq!(booking["passenger_name"], "Jane Doe");
+    assert_eq!(booking["departure_date"], "2026-12-01");
+
+    assert_eq!(harness.stages(), ["quoted", "confirmed", "passengers_entered", "paid", "booked"]);
+    let progress = harness.progress(chat.session_id());
+    assert_eq!(progress["payment_method"], "card");
+    assert_eq!(progress["payment_reference"], "ins_1");
+    assert_eq!(progress["confirmation_codes"], json!(["ZP0001"]));
+    assert_eq!(progress["total_cents"], PRICE_CENTS);
+
+    // A refund intent skips the LLM, proves the refund on Agent A, and reverses the card
+    chat.process_user_query("cancel my booking").await.unwrap();
+
+    assert_eq!(harness.agent_a.calls.to("attest_refund")[0]["booking_id"], "bk-1");
+    let reversal = &harness.payments.calls.to("reverse-purchase-instruction")[0];
+    assert_eq!(reversal["instructionId"], "ins_1");
+    assert_eq!(reversal["amount"], "341.25");
+    let progress = harness.progress(chat.session_id());
+    assert_eq!(progress["stage"], "cancelled");
+    assert_eq!(progress["refund_status"], "reversed");
+    assert_eq!(progress["refund_cents"], PRICE_CENTS / 2);
+}
+
+#[tokio::test]
+async fn test_declined_card_books_nothing() {
+    let mut mocks = Mocks::new();
+    mocks.payments = mocks.payments.tool("retrieve-payment-credentials", |_| Ok(json!({ "status": "DECLINED" })));
+    let harness = mocks.start().await;
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), json!({}), CARD_BOOKING).await;
+
+    chat.process_user_query("Book a flight from NYC to London").await.unwrap();
+
+    assert_eq!(harness.payments.calls.to("retrieve-payment-credentials").len(), 1);
+    assert!(harness.agent_b.calls.to("book-flight").is_empty());
+    assert_eq!(harness.progress(chat.session_id())["stage"], "passengers_entered");
+}
+
+#[tokio::test]
+async fn test_cancel_mid_booking_charges_nothing() {
+    let harness = Mocks::new().start().await;
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), json!({}), &["y", "cancel"]).await;
+
+    chat.process_user_query("Book a flight from NYC to London").await.unwrap();
+
+    assert!(harness.payments.calls.names().is_empty());
+    assert!(harness.agent_b.calls.to("book-flight").is_empty());
+    assert_eq!(harness.stages(), ["quoted", "confirmed"]);
+}
+
+#[tokio::test]
+async fn test_policy_refuses_payment_over_limit() {
+    let harness = Mocks::new().start().await;
+    let limit = json!({ "payment_max_per_transaction_cents": 50_000 });
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), limit, CARD_BOOKING).await;
+
+    chat.process_user_query("Book a flight from NYC to London").await.unwrap();
+
+    assert!(harness.payments.calls.to("initiate-purchase-instruction").is_empty());
+    assert!(harness.agent_b.calls.to("book-flight").is_empty());
+    let denied = harness.agent_a.calls.to("denied-actions");
+    assert_eq!(denied.len(), 1);
+    assert_eq!(denied[0]["rule"], "max_per_transaction");
+    assert_eq!(denied[0]["amount_cents"], PRICE_CENTS);
+}
+
+#[tokio::test]
+async fn test_crypto_payment_then_booking() {
+    let harness = Mocks::new().start().await;
+    let answers = ["y", "1", "Jane Doe", "jane@example.com", "2", "y"];
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), json!({}), &answers).await;
+
+    chat.process_user_query("Book a flight from NYC to London").await.unwrap();
+
+    assert!(harness.payments.calls.names().is_empty());
+    assert_eq!(harness.agent_a.calls.to("create_crypto_payment")[0]["amount_cents"], PRICE_CENTS);
+    assert_eq!(harness.agent_b.calls.to("book-flight").len(), 1);
+    let progress = harness.progress(chat.session_id());
+    assert_eq!(progress["stage"], "booked");
+    assert_eq!(progress["payment_method"], "crypto");
+    assert_eq!(progress["payment_reference"], "0xfeed");
+}
+
+#[tokio::test]
+async fn test_price_is_proven_before_payment() {
+    let harness = Mocks::new().start().await;
+    let require_proof = json!({ "require_proof_before_payment": true });
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), require_proof, CARD_BOOKING).await;
+
+    chat.process_user_query("Book a flight from NYC to London").await.unwrap();
+
+    let attestation = &harness.agent_a.calls.to("request_attestation")[0];
+    assert_eq!(attestation["program_id"], "pricing-v1");
+    assert_eq!(attestation["elf_hash"], "0xe1f");
+    assert!(attestation["session_id"].as_str().is_some_and(|key| !key.is_empty()));
+    assert!(attestation["claimed_output"].as_str().unwrap().contains(&PRICE_CENTS.to_string()));
+    let verified = &harness.agent_a.calls.to("verify_offchain")[0];
+    assert_eq!(verified["proof_bundle"]["vk_hash"], "0x00c0");
+    assert_eq!(harness.progress(chat.session_id())["stage"], "booked");
+}
+
+#[tokio::test]
+async fn test_agent_a_token_is_sent_when_auth_is_on() {
+    let mut mocks = Mocks::new();
+    mocks.agent_a = mocks.agent_a.require_token("s3cret");
+    let harness = mocks.start().await;
+    let config = json!({ "require_proof_before_payment": true, "agent_a_api_token": "s3cret" });
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), config, CARD_BOOKING).await;
+
+    chat.process_user_query("Book a flight from NYC to London").await.unwrap();
+
+    // Tool discovery, the proof and its status URL, and the saved progress all got through
+    assert_eq!(harness.agent_a.calls.to("verify_offchain").len(), 1);
+    assert_eq!(harness.progress(chat.session_id())["stage"], "booked");
+}
+
+#[tokio::test]
+async fn test_repriced_quote_is_proven_again_before_payment() {
+    // The first quote has already lapsed when payment starts; the second costs more
+    let quotes = Arc::new(AtomicU64::new(0));
+    let mut mocks = Mocks::new();
+    mocks.agent_b = mocks.agent_b.tool("get-ticket-price", move |args| {
+        let repriced = quotes.fetch_add(1, Ordering::SeqCst) > 0;
+        Ok(json!({
+            "from": args["from"],
+            "to": args["to"],
+            "price_cents": if repriced { PRICE_CENTS + 100 } else { PRICE_CENTS },
+            "currency": "USD",
+            "quote_id": if repriced { "quote-2" } else { "quote-1" },
+            "expires_at": if repriced { u64::MAX } else { 0 },
+            "purchase_date": "2026-10-15",
+            "program_id": "pricing-v1",
+            "elf_hash": "0xe1f",
+        }))
+    });
+    let harness = mocks.start().await;
+    let require_proof = json!({ "require_proof_before_payment": true });
+    let answers = ["y", "1", "Jane Doe", "jane@example.com", "2", "y", "y"];
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), require_proof, &answers).await;
+
+    chat.process_user_query("Book a flight from NYC to London").await.unwrap();
+
+    let attestations = harness.agent_a.calls.to("request_attestation");
+    assert_eq!(attestations.len(), 2);
+    assert!(attestations[1]["claimed_output"].as_str().unwrap().contains(&(PRICE_CENTS + 100).to_string()));
+    assert_eq!(harness.agent_a.calls.to("create_crypto_payment")[0]["amount_cents"], PRICE_CENTS + 100);
+}
+
+#[tokio::test]
+async fn test_unproven_price_stops_before_payment() {
+    let mut mocks = Mocks::new();
+    mocks.agent_a = mocks.agent_a.tool("verify_offchain", |_| Ok(json!({ "verified": false, "error": "vk mismatch" })));
+    let harness = mocks.start().await;
+    let require_proof = json!({ "require_proof_before_payment": true });
+    let answers = ["y", "1", "Jane Doe", "jane@example.com"];
+    let mut chat = harness.chat(CannedLlm::new(vec![price_turn("NYC", "LON")]), require_proof, &answers).await;
+
+    chat.process_user_query("Book a flight from NYC to London").await.unwrap();
+
+    assert_eq!(harness.agent_a.calls.to("verify_offchain").len(), 1);
+    assert!(harness.payments.calls.names().is_empty());
+    assert!(harness.agent_b.calls.to("book-flight").is_empty());
+}
+
+#[tokio::test]
+async fn test_direct_tool_calls_still_pass_the_policy() {
+    let harness = Mocks::new().start().await;
+    let llm = CannedLlm::new(vec![
+        turn(
+            "Checking the proof on-chain.",
+            vec![("verify_on_chain", json!({ "proof": "0x01", "public_values": "0x0b", "vk_hash": "0x00c0" }))],
+        ),
+        turn("Requesting the payment.", vec![("create_crypto_payment", json!({ "amount_cents": 90_000, "currency": "USD" }))]),
+    ]);
+    let routes = json!({ "payment_allowed_routes": "NYC-LON" });
+    let mut chat = harness.chat(llm, routes, &[]).await;
+
+    chat.process_user_query("Verify my proof on-chain").await.unwrap();
+    assert_eq!(harness.agent_a.calls.to("verify_on_chain")[0]["vk_hash"], "0x00c0");
+
+    // Outside the booking flow there is no route, so the route rule refuses it
+    chat.process_user_query("Send me a crypto payment request for $900").await.unwrap();
+    assert!(harness.agent_a.calls.to("create_crypto_payment").is_empty());
+    assert_eq!(harness.agent_a.calls.to("denied-actions")[0]["rule"], "route");
+}
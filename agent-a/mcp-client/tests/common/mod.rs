@@ -0,0 +1,398 @@
+//! In-process stand-ins for everything the chat talks to
+//!
+//! Each mock is an axum router on a random local port that advertises its tools on
+//! `GET /tools`, records every `POST /tools/{name}` body, and answers it with a canned
+//! handler in the `{success, data, error}` envelope the real servers use:
+//! - Agent A: the attestation tools (its attester behind `request_attestation` and the job
+//!   status URL), crypto payments, refunds, and the per-session booking progress store
+//! - Agent B: pricing, booking, and cancellation
+//! - the payment agent: card enrollment, purchase, credentials, and reversal
+//!
+//! Any of them can demand a bearer token with [`ToolServer::require_token`].
+//!
+//! [`CannedLlm`] replays scripted routing turns, and [`Script`] feeds the answers to the
+//! booking prompts, so a test drives [`Chat::process_user_query`] end to end.
+
+#![allow(dead_code)]
+
+use agent_a_client::llm::{LlmProvider, LlmTurn, MockProvider};
+use agent_a_client::{AgentConfig, Chat};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use axum::extract::{Path, Request, State};
+use axum::http::{header::AUTHORIZATION, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures::stream::{self, BoxStream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, Cursor, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Price every mock quote carries, in cents
+pub const PRICE_CENTS: u64 = 68_250;
+
+/// Answers to a full card booking: proceed, one passenger, name, email, Visa, add the
+/// card, pay
+pub const CARD_BOOKING: &[&str] = &["y", "1", "Jane Doe", "jane@example.com", "1", "y", "y"];
+
+/// `data` of a tool reply, or its `error`
+pub type Reply = Result<Value, String>;
+type Handler = Arc<dyn Fn(&Value) -> Reply + Send + Sync>;
+
+/// Requests a mock received, in order: tool name (or route) and JSON body
+#[derive(Clone, Default)]
+pub struct Calls(Arc<Mutex<Vec<(String, Value)>>>);
+
+impl Calls {
+    fn push(&self, name: &str, body: Value) {
+        self.0.lock().unwrap().push((name.to_string(), body));
+    }
+
+    /// Bodies sent to `name`
+    pub fn to(&self, name: &str) -> Vec<Value> {
+        self.0.lock().unwrap().iter().filter(|(n, _)| n == name).map(|(_, body)| body.clone()).collect()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.lock().unwrap().iter().map(|(name, _)| name.clone()).collect()
+    }
+}
+
+fn envelope(reply: Reply) -> Json<Value> {
+    Json(match reply {
+        Ok(data) => json!({ "success": true, "data": data, "error": null }),
+        Err(error) => json!({ "success": false, "data": null, "error": error }),
+    })
+}
+
+/// A tool server under construction
+pub struct ToolServer {
+    tools: Vec<(String, Handler)>,
+    routes: Router,
+    calls: Calls,
+    /// Bearer token every request must carry, if any
+    token: Option<String>,
+}
+
+impl ToolServer {
+    pub fn new() -> Self {
+        Self { tools: Vec::new(), routes: Router::new(), calls: Calls::default(), token: None }
+    }
+
+    /// Answer 401 to requests without `Authorization: Bearer <token>`
+    pub fn require_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Offer `name`, answered by `handler` (replacing any earlier handler for it)
+    pub fn tool(mut self, name: &str, handler: impl Fn(&Value) -> Reply + Send + Sync + 'static) -> Self {
+        self.tools.retain(|(tool, _)| tool != name);
+        self.tools.push((name.to_string(), Arc::new(handler)));
+        self
+    }
+
+    /// Stop offering `name`
+    pub fn without(mut self, name: &str) -> Self {
+        self.tools.retain(|(tool, _)| tool != name);
+        self
+    }
+
+    async fn serve(self) -> MockServer {
+        let listing = json!({
+            "tools": self.tools.iter().map(|(name, _)| json!({
+                "name": name,
+                "description": format!("mock {}", name),
+                "inputSchema": { "type": "object", "properties": {} },
+            })).collect::<Vec<_>>(),
+        });
+        let handlers: Arc<HashMap<String, Handler>> = Arc::new(self.tools.into_iter().collect());
+        let calls = self.calls.clone();
+        let router = Router::new()
+            .route("/tools", get(move || async move { Json(listing) }))
+            .route(
+                "/tools/:name",
+                post(move |Path(name): Path<String>, Json(args): Json<Value>| async move {
+                    calls.push(&name, args.clone());
+                    envelope(match handlers.get(&name) {
+                        Some(handler) => handler(&args),
+                        None => Err(format!("unknown tool {}", name)),
+                    })
+                }),
+            )
+            .merge(self.routes);
+        let router = match self.token {
+            Some(token) => router.layer(middleware::from_fn_with_state(Arc::<str>::from(token), check_token)),
+            None => router,
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        MockServer { url, calls: self.calls }
+    }
+}
+
+async fn check_token(State(token): State<Arc<str>>, req: Request, next: Next) -> Response {
+    let bearer = req.headers().get(AUTHORIZATION).and_then(|v| v.to_str().ok()).and_then(|v| v.strip_prefix("Bearer "));
+    if bearer == Some(&*token) {
+        next.run(req).await
+    } else {
+        StatusCode::UNAUTHORIZED.into_response()
+    }
+}
+
+pub struct MockServer {
+    pub url: String,
+    pub calls: Calls,
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// The three servers with default handlers; adjust them with [`ToolServer::tool`] before
+/// [`Mocks::start`]
+pub struct Mocks {
+    pub agent_a: ToolServer,
+    pub agent_b: ToolServer,
+    pub payments: ToolServer,
+}
+
+impl Mocks {
+    pub fn new() -> Self {
+        let bookings = Arc::new(AtomicU64::new(0));
+        let agent_b = ToolServer::new()
+            .tool("get-ticket-price", |args| {
+                Ok(json!({
+                    "from": args["from"],
+                    "to": args["to"],
+                    "price_cents": PRICE_CENTS,
+                    "currency": "USD",
+                    "quote_id": "quote-1",
+                    "expires_at": now() + 3600,
+                    "purchase_date": "2026-10-15",
+                    "program_id": "pricing-v1",
+                    "elf_hash": "0xe1f",
+                }))
+            })
+            .tool("book-flight", move |_| {
+                let n = bookings.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(json!({ "confirmation_code": format!("ZP{:04}", n), "booking_id": format!("bk-{}", n) }))
+            })
+            .tool("cancel-booking", |_| Ok(json!({ "cancellation": { "refund_cents": PRICE_CENTS / 2 }, "currency": "USD" })));
+
+        let agent_a = ToolServer::new()
+            .tool("format_zk_input", |_| Ok(json!({ "input_hex": "0x0102" })))
+            .tool("request_attestation", |_| Ok(json!({ "job_id": "job-1", "status_url": "/attest/jobs/job-1" })))
+            .tool("verify_offchain", |_| Ok(json!({ "verified": true })))
+            .tool("verify_on_chain", |_| Ok(json!({ "verified": true, "details": "mock verifier" })))
+            .tool("attest_refund", |args| {
+                Ok(json!({ "booking_id": args["booking_id"], "refund_cents": PRICE_CENTS / 2, "currency": "USD" }))
+            })
+            .tool("create_crypto_payment", |args| {
+                Ok(json!({
+                    "id": "pay-1",
+                    "amount_cents": args["amount_cents"],
+                    "symbol": "USDC",
+                    "recipient": "0x00000000000000000000000000000000000000a1",
+                    "chain_id": 31337,
+                    "uri": "ethereum:0x00000000000000000000000000000000000000a1@31337",
+                    "created_at": 0,
+                    "expires_at": 60,
+                }))
+            })
+            .tool("get_crypto_payment", |_| Ok(json!({ "id": "pay-1", "status": "paid", "tx_hash": "0xfeed" })));
+
+        let payments = ToolServer::new()
+            .tool("enroll-card", |_| Ok(json!({ "success": true, "tokenId": "tok_1" })))
+            .tool("initiate-purchase-instruction", |_| Ok(json!({ "instructionId": "ins_1", "status": "CREATED" })))
+            .tool("retrieve-payment-credentials", |_| Ok(json!({ "status": "APPROVED", "network": "visa" })))
+            .tool("reverse-purchase-instruction", |_| Ok(json!({ "reversalId": "rev_1", "status": "REVERSED" })));
+
+        Self { agent_a, agent_b, payments }
+    }
+
+    pub async fn start(self) -> Harness {
+        // Agent A keeps booking progress per chat session, and serves the attester's
+        // finished proofs at the job status URL
+        let progress: Arc<Mutex<HashMap<String, Value>>> = Arc::default();
+        let mut agent_a = self.agent_a;
+        let (store, calls) = (progress.clone(), agent_a.calls.clone());
+        let lookup = progress.clone();
+        let denied = agent_a.calls.clone();
+        agent_a.routes = Router::new()
+            .route(
+                "/session/:id",
+                get(move |Path(id): Path<String>| async move {
+                    let mut progress = lookup.lock().unwrap().get(&id).cloned().ok_or(StatusCode::NOT_FOUND)?;
+                    let stage = progress["stage"].as_str().unwrap_or_default().to_string();
+                    let summary = format!("Your {} booking is {}.", progress["route"].as_str().unwrap_or_default(), stage);
+                    progress["summary"] = json!(summary);
+                    progress["resumable"] = json!(matches!(stage.as_str(), "quoted" | "confirmed" | "passengers_entered"));
+                    Ok::<_, StatusCode>(envelope(Ok(progress)))
+                })
+                .post(move |Path(id): Path<String>, Json(progress): Json<Value>| async move {
+                    calls.push("session", progress.clone());
+                    store.lock().unwrap().insert(id, progress);
+                    envelope(Ok(json!({})))
+                }),
+            )
+            .route(
+                "/sessions/:id/denied-actions",
+                post(move |Json(action): Json<Value>| async move {
+                    denied.push("denied-actions", action);
+                    envelope(Ok(json!({})))
+                }),
+            )
+            .route(
+                "/attest/jobs/:id",
+                get(|Path(id): Path<String>| async move {
+                    envelope(Ok(json!({
+                        "job_id": id,
+                        "status": "done",
+                        "proof_bundle": {
+                            "proof": "",
+                            "public_values": "0x0b",
+                            "vk_hash": "0x00c0",
+                            "mode": "mock",
+                        },
+                    })))
+                }),
+            );
+
+        // No card enrolled yet
+        let mut payments = self.payments;
+        payments.routes = Router::new().route("/session/:id", get(|| async { envelope(Ok(json!({}))) }));
+
+        Harness {
+            agent_a: agent_a.serve().await,
+            agent_b: self.agent_b.serve().await,
+            payments: payments.serve().await,
+            progress,
+        }
+    }
+}
+
+/// The running mocks
+pub struct Harness {
+    pub agent_a: MockServer,
+    pub agent_b: MockServer,
+    pub payments: MockServer,
+    progress: Arc<Mutex<HashMap<String, Value>>>,
+}
+
+impl Harness {
+    /// Client configuration pointing at the mocks, with `overrides` (config keys as in
+    /// agent-a-client.toml) applied on top
+    pub fn config(&self, overrides: Value) -> AgentConfig {
+        let mut config = json!({
+            "llm_provider": "mock",
+            "agent_a_server_url": self.agent_a.url,
+            "agent_b_mcp_url": self.agent_b.url,
+            "payment_agent_url": self.payments.url,
+        });
+        for (key, value) in overrides.as_object().cloned().unwrap_or_default() {
+            config[key] = value;
+        }
+        serde_json::from_value(config).unwrap()
+    }
+
+    /// A chat connected to the mocks that routes with `llm` and answers the booking
+    /// prompts from `answers`
+    pub async fn chat(&self, llm: CannedLlm, overrides: Value, answers: &[&str]) -> Chat<Script, io::Sink> {
+        Chat::connect(reqwest::Client::new(), Box::new(llm), self.config(overrides), Script::new(answers), io::sink())
+            .await
+            .unwrap()
+    }
+
+    /// Booking progress Agent A last stored for `session_id`
+    pub fn progress(&self, session_id: &str) -> Value {
+        self.progress.lock().unwrap().get(session_id).cloned().unwrap_or(Value::Null)
+    }
+
+    /// Stages the booking went through, in order
+    pub fn stages(&self) -> Vec<String> {
+        self.agent_a.calls.to("session").iter().map(|p| p["stage"].as_str().unwrap_or_default().to_string()).collect()
+    }
+}
+
+/// Scripted answers, one per line; running out fails the test instead of leaving a y/n
+/// prompt asking forever
+pub struct Script(Cursor<Vec<u8>>);
+
+impl Script {
+    pub fn new(answers: &[&str]) -> Self {
+        Self(Cursor::new(answers.iter().map(|answer| format!("{}\n", answer)).collect::<String>().into_bytes()))
+    }
+}
+
+impl Read for Script {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl BufRead for Script {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let buf = self.0.fill_buf()?;
+        assert!(!buf.is_empty(), "the chat asked for more answers than the test scripted");
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.0.consume(amount)
+    }
+}
+
+/// LLM that answers each routing turn with the next scripted one; field extraction is the
+/// mock provider's and summaries are a fixed line
+pub struct CannedLlm {
+    turns: Mutex<VecDeque<LlmTurn>>,
+}
+
+impl CannedLlm {
+    pub fn new(turns: Vec<LlmTurn>) -> Self {
+        Self { turns: Mutex::new(turns.into()) }
+    }
+}
+
+/// A routing turn calling `tools`
+pub fn turn(text: &str, tools: Vec<(&str, Value)>) -> LlmTurn {
+    LlmTurn {
+        text: text.to_string(),
+        tool_calls: tools.into_iter().map(|(name, args)| (name.to_string(), args)).collect(),
+        usage: None,
+    }
+}
+
+/// The LLM pricing one dated leg
+pub fn price_turn(from: &str, to: &str) -> LlmTurn {
+    turn(
+        &format!("Let me check the price from {} to {}.", from, to),
+        vec![("get-ticket-price", json!({ "from": from, "to": to, "date": "2026-12-01" }))],
+    )
+}
+
+#[async_trait]
+impl LlmProvider for CannedLlm {
+    fn name(&self) -> &'static str {
+        "canned"
+    }
+
+    async fn chat(&self, _system: &str, _user: &str, _tools: &Value) -> Result<LlmTurn> {
+        self.turns.lock().unwrap().pop_front().ok_or_else(|| anyhow!("no scripted turn left"))
+    }
+
+    async fn extract_field(&self, text: &str, field: &str) -> Result<Option<String>> {
+        MockProvider.extract_field(text, field).await
+    }
+
+    async fn stream(&self, _system: &str, _user: &str) -> Result<BoxStream<'static, Result<String>>> {
+        Ok(stream::iter(vec![Ok("Done.".to_string())]).boxed())
+    }
+}
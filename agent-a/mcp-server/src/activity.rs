@@ -0,0 +1,136 @@
+//! Live activity events per session (`GET /sessions/{id}/events`)
+//!
+//! Handlers publish typed [`ActivityEvent`]s as a tool call progresses, so a frontend
+//! can render a timeline instead of a spinner. Events are only delivered to currently
+//! connected subscribers; nothing is buffered for sessions nobody is watching.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+use crate::ClaimMismatch;
+
+/// Events a subscriber can fall behind by before older ones are dropped
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActivityEvent {
+    ToolStarted {
+        tool: String,
+    },
+    ToolResult {
+        tool: String,
+        success: bool,
+        /// Error message when `success` is false
+        error: Option<String>,
+    },
+    ProofCollected {
+        /// "price", "booking", "refund", "attestation", or "mismatch"
+        kind: String,
+        vk_hash: String,
+        verified: Option<bool>,
+    },
+    /// A recorded proof was checked on-chain in the background
+    ProofVerified {
+        proof_id: String,
+        verified: bool,
+        tx_hash: Option<String>,
+    },
+    /// Crypto payment requested or settled (`create_crypto_payment`, `get_crypto_payment`);
+    /// orchestrators running other payment rails can publish it too
+    PaymentStep {
+        step: String,
+        detail: Value,
+    },
+    BookingConfirmed {
+        booking_id: String,
+        confirmation_code: String,
+    },
+    /// A proof committed something other than what the user was shown; the flow stopped
+    ClaimMismatch {
+        tool: String,
+        mismatches: Vec<ClaimMismatch>,
+    },
+    /// The client's spending policy refused a payment before any payment tool ran
+    PaymentDenied {
+        tool: String,
+        rule: String,
+        message: String,
+    },
+}
+
+impl ActivityEvent {
+    /// SSE event name, same as the `type` tag
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ToolStarted { .. } => "tool_started",
+            Self::ToolResult { .. } => "tool_result",
+            Self::ProofCollected { .. } => "proof_collected",
+            Self::ProofVerified { .. } => "proof_verified",
+            Self::PaymentStep { .. } => "payment_step",
+            Self::BookingConfirmed { .. } => "booking_confirmed",
+            Self::ClaimMismatch { .. } => "claim_mismatch",
+            Self::PaymentDenied { .. } => "payment_denied",
+        }
+    }
+}
+
+/// Broadcast channel per watched session
+#[derive(Default)]
+pub struct ActivityFeed {
+    channels: Mutex<HashMap<String, broadcast::Sender<ActivityEvent>>>,
+}
+
+impl ActivityFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, session_id: &str) -> broadcast::Receiver<ActivityEvent> {
+        self.channels
+            .lock()
+            .unwrap()
+            .entry(session_id.to_string())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Deliver `event` to the session's subscribers; channels nobody listens to are dropped
+    pub fn publish(&self, session_id: Option<&str>, event: ActivityEvent) {
+        let Some(session_id) = session_id else {
+            return;
+        };
+        let mut channels = self.channels.lock().unwrap();
+        if let Some(sender) = channels.get(session_id) {
+            if sender.send(event).is_err() {
+                channels.remove(session_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_typed_events() {
+        let feed = ActivityFeed::new();
+        feed.publish(Some("s1"), ActivityEvent::ToolStarted { tool: "ignored".into() });
+
+        let mut events = feed.subscribe("s1");
+        feed.publish(Some("s1"), ActivityEvent::ToolStarted { tool: "attest_price".into() });
+        feed.publish(Some("s2"), ActivityEvent::ToolStarted { tool: "other".into() });
+
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.name(), "tool_started");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "type": "tool_started", "tool": "attest_price" })
+        );
+        assert!(events.try_recv().is_err());
+    }
+}
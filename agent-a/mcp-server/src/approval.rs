@@ -0,0 +1,86 @@
+//! Approval sub-protocol for side-effecting HTTP calls
+//!
+//! Instead of executing, a guarded call is held and the response carries a
+//! [`PendingAction`]. The caller confirms or denies it by id (`POST /chat/approve`)
+//! before `expires_at`; unresolved actions are dropped after the TTL.
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// An action waiting for the caller's confirm/deny
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PendingAction {
+    pub id: String,
+    /// Human-readable summary to show in the confirm dialog
+    pub description: String,
+    /// Unix seconds after which the action can no longer be approved
+    pub expires_at: u64,
+}
+
+/// Held actions of type `T`, keyed by pending-action id
+pub struct Approvals<T> {
+    ttl: Duration,
+    pending: Mutex<HashMap<String, (PendingAction, Instant, T)>>,
+}
+
+impl<T> Approvals<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hold `action` until it is resolved or expires
+    pub fn propose(&self, description: impl Into<String>, action: T) -> PendingAction {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let pending_action = PendingAction {
+            id: uuid::Uuid::new_v4().to_string(),
+            description: description.into(),
+            expires_at: now + self.ttl.as_secs(),
+        };
+
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, (_, created, _)| created.elapsed() < self.ttl);
+        pending.insert(pending_action.id.clone(), (pending_action.clone(), Instant::now(), action));
+        pending_action
+    }
+
+    /// Remove and return the held action; each id resolves at most once
+    pub fn take(&self, id: &str) -> Result<T> {
+        let (_, created, action) = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| anyhow!("No pending action {}", id))?;
+        if created.elapsed() >= self.ttl {
+            return Err(anyhow!("Pending action {} expired", id));
+        }
+        Ok(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_actions_resolve_once_and_expire() {
+        let approvals = Approvals::new(Duration::from_secs(60));
+        let action = approvals.propose("Book NYC → London", 42);
+        assert_eq!(approvals.take(&action.id).unwrap(), 42);
+        assert!(approvals.take(&action.id).is_err());
+
+        let approvals = Approvals::new(Duration::ZERO);
+        let action = approvals.propose("Book NYC → London", 42);
+        assert!(approvals.take(&action.id).unwrap_err().to_string().contains("expired"));
+    }
+}
@@ -0,0 +1,229 @@
+//! Per-session audit log of agent decisions and tool calls (`GET /sessions/{id}/audit`)
+//!
+//! Proofs show what was computed; the audit log shows why the agent did it. The chat
+//! client reports each LLM decision (a hash of the prompt, the tools chosen and the
+//! text) and every tool call with its result (`POST /sessions/{id}/audit`, arguments and
+//! results redacted by the client). Agent A adds its own side: every activity event it
+//! publishes (tools run, proofs collected, payments, bookings) and each booking state
+//! transition. Together they let an operator reconstruct a session even when no proof
+//! was collected.
+//!
+//! Events are numbered per session in arrival order. With AGENT_A_PROOF_DB set they live
+//! in the same SQLite file as the proofs and survive restarts.
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEvent {
+    /// Position in the session's log, from 1; set by the store
+    #[serde(default)]
+    pub seq: u64,
+    /// Unix seconds; set by the server
+    #[serde(default)]
+    pub at: u64,
+    /// "client" for events the chat client reported, "agent_a" for the server's own
+    #[serde(default)]
+    pub actor: String,
+    #[serde(flatten)]
+    pub kind: AuditKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditKind {
+    /// The LLM routed a user request
+    LlmDecision {
+        /// 0x-prefixed hash of the system prompt and rendered request
+        prompt_hash: String,
+        /// LLM provider, e.g. "anthropic"
+        provider: Option<String>,
+        /// Tools it chose, in order (empty for a plain reply)
+        tools: Vec<String>,
+        text: String,
+        /// How long the LLM call took
+        latency_ms: Option<u64>,
+        /// Token usage, when the provider reports it
+        input_tokens: Option<u64>,
+        output_tokens: Option<u64>,
+    },
+    ToolCall {
+        tool: String,
+        /// As sent, with passenger and payment details redacted
+        arguments: Value,
+    },
+    ToolResult {
+        tool: String,
+        success: bool,
+        /// Redacted like the arguments
+        result: Option<Value>,
+        error: Option<String>,
+    },
+    /// The session's booking moved to a new stage (`POST /session/{id}`)
+    StateTransition {
+        from: Option<String>,
+        to: String,
+    },
+    /// An activity event Agent A published (see [`crate::activity::ActivityEvent`])
+    Activity { event: Value },
+}
+
+impl AuditEvent {
+    pub fn new(actor: &str, kind: AuditKind) -> Self {
+        Self { seq: 0, at: 0, actor: actor.to_string(), kind }
+    }
+}
+
+/// One event per line, for export
+pub fn to_jsonl(events: &[AuditEvent]) -> Result<String> {
+    let mut out = String::new();
+    for event in events {
+        out.push_str(&serde_json::to_string(event)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Append-only audit log per session
+pub trait AuditStore: Send + Sync {
+    /// Number and store `event`; returns it as stored
+    fn append(&self, session_id: &str, event: AuditEvent) -> Result<AuditEvent>;
+
+    /// The session's events in order (empty if it has none)
+    fn session(&self, session_id: &str) -> Result<Vec<AuditEvent>>;
+}
+
+#[derive(Default)]
+pub struct MemoryAuditStore {
+    sessions: Mutex<HashMap<String, Vec<AuditEvent>>>,
+}
+
+impl MemoryAuditStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AuditStore for MemoryAuditStore {
+    fn append(&self, session_id: &str, mut event: AuditEvent) -> Result<AuditEvent> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let events = sessions.entry(session_id.to_string()).or_default();
+        event.seq = events.len() as u64 + 1;
+        events.push(event.clone());
+        Ok(event)
+    }
+
+    fn session(&self, session_id: &str) -> Result<Vec<AuditEvent>> {
+        Ok(self.sessions.lock().unwrap().get(session_id).cloned().unwrap_or_default())
+    }
+}
+
+/// Audit events persisted in a SQLite database
+pub struct SqliteAuditStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteAuditStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS audit_events (
+                session_id TEXT NOT NULL,
+                seq        INTEGER NOT NULL,
+                at         INTEGER NOT NULL,
+                event      TEXT NOT NULL,
+                PRIMARY KEY (session_id, seq)
+            );",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+}
+
+impl AuditStore for SqliteAuditStore {
+    fn append(&self, session_id: &str, mut event: AuditEvent) -> Result<AuditEvent> {
+        let conn = self.conn.lock().unwrap();
+        let last: i64 = conn.query_row(
+            "SELECT COALESCE(MAX(seq), 0) FROM audit_events WHERE session_id = ?1",
+            params![session_id],
+            |row| row.get(0),
+        )?;
+        event.seq = last as u64 + 1;
+        conn.execute(
+            "INSERT INTO audit_events (session_id, seq, at, event) VALUES (?1, ?2, ?3, ?4)",
+            params![session_id, event.seq as i64, event.at as i64, serde_json::to_string(&event)?],
+        )?;
+        Ok(event)
+    }
+
+    fn session(&self, session_id: &str) -> Result<Vec<AuditEvent>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT event FROM audit_events WHERE session_id = ?1 ORDER BY seq")?;
+        let events = stmt
+            .query_map(params![session_id], |row| row.get::<_, String>(0))?
+            .map(|event| Ok(serde_json::from_str(&event?)?))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(events)
+    }
+}
+
+/// SQLite at `path` if set, else memory
+pub fn open(path: Option<&str>) -> Result<Box<dyn AuditStore>> {
+    Ok(match path {
+        Some(path) => Box::new(SqliteAuditStore::open(path)?),
+        None => Box::new(MemoryAuditStore::new()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_backends_number_events_per_session() {
+        let stores: [Box<dyn AuditStore>; 2] =
+            [Box::new(MemoryAuditStore::new()), Box::new(SqliteAuditStore::open_in_memory().unwrap())];
+        for store in stores {
+            let decision: AuditEvent = serde_json::from_value(json!({
+                "type": "llm_decision",
+                "prompt_hash": "0xabc",
+                "provider": "mock",
+                "tools": ["get-ticket-price"],
+                "text": "Pricing NYC → LON",
+            }))
+            .unwrap();
+            assert_eq!(store.append("s1", decision).unwrap().seq, 1);
+            let call = AuditEvent::new(
+                "client",
+                AuditKind::ToolCall { tool: "get-ticket-price".into(), arguments: json!({"from": "NYC", "to": "LON"}) },
+            );
+            assert_eq!(store.append("s1", call).unwrap().seq, 2);
+            let transition = AuditEvent::new("agent_a", AuditKind::StateTransition { from: None, to: "quoted".into() });
+            assert_eq!(store.append("s2", transition).unwrap().seq, 1);
+
+            let events = store.session("s1").unwrap();
+            assert_eq!(events.len(), 2);
+            assert!(matches!(&events[0].kind, AuditKind::LlmDecision { tools, .. } if tools == &["get-ticket-price"]));
+            assert!(store.session("s3").unwrap().is_empty());
+
+            let jsonl = to_jsonl(&events).unwrap();
+            let lines: Vec<Value> = jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+            assert_eq!(lines.len(), 2);
+            assert_eq!(lines[1]["type"], "tool_call");
+            assert_eq!(lines[1]["seq"], 2);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Bearer-token auth for the HTTP API
+//!
+//! Tokens come from `agent_a_api_tokens` (AGENT_A_API_TOKENS) as comma-separated `principal:token` pairs
+//! (a bare token maps to principal "default"). With no tokens configured, auth is off.
+
+use axum::{
+    extract::{Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::Arc;
+use tool_envelope::{codes, ToolResponse};
+use tracing::Instrument;
+
+/// Routes reachable without a token
+const PUBLIC_PATHS: &[&str] = &["/health", "/openapi.json", "/docs", "/.well-known/tap-jwks.json"];
+
+/// Authenticated caller, available to handlers as `Extension<Principal>`
+#[derive(Debug, Clone)]
+pub struct Principal(pub String);
+
+tokio::task_local! {
+    static PRINCIPAL: String;
+}
+
+/// Principal of the request being handled, if auth is on and it passed
+pub fn current() -> Option<String> {
+    PRINCIPAL.try_with(|p| p.clone()).ok()
+}
+
+#[derive(Debug, Default)]
+pub struct AuthConfig {
+    /// (principal, token)
+    tokens: Vec<(String, String)>,
+}
+
+impl AuthConfig {
+    pub fn parse(list: &str) -> Self {
+        let tokens = list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once(':') {
+                Some((principal, token)) => (principal.to_string(), token.to_string()),
+                None => ("default".to_string(), entry.to_string()),
+            })
+            .collect();
+        Self { tokens }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    fn principal_for(&self, token: &str) -> Option<&str> {
+        self.tokens
+            .iter()
+            .find(|(_, expected)| constant_time_eq(expected.as_bytes(), token.as_bytes()))
+            .map(|(principal, _)| principal.as_str())
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Reject requests without a valid `Authorization: Bearer <token>` (outside `PUBLIC_PATHS`)
+///
+/// The principal is attached to the request extensions, to the tracing span the rest
+/// of the request runs in, and to [`current`] for code that has no access to the request.
+pub async fn require_bearer(
+    State(auth): State<Arc<AuthConfig>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path().to_string();
+    if !auth.is_enabled() || PUBLIC_PATHS.contains(&path.as_str()) {
+        return next.run(req).await;
+    }
+
+    let principal = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .and_then(|token| auth.principal_for(token.trim()))
+        .map(str::to_string);
+
+    match principal {
+        Some(principal) => {
+            let span = tracing::info_span!("http", principal = %principal, path = %path);
+            req.extensions_mut().insert(Principal(principal.clone()));
+            PRINCIPAL.scope(principal, next.run(req).instrument(span)).await
+        }
+        None => {
+            tracing::warn!("✗ Rejected unauthenticated request to {}", path);
+            let body = ToolResponse::<()>::err("Missing or invalid bearer token").with_code(codes::UNAUTHORIZED);
+            (StatusCode::UNAUTHORIZED, Json(body)).into_response()
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Where a chat session's booking stands (`GET /session/{id}`), kept so a user who
+//! reconnects can pick the booking up instead of starting over
+//!
+//! The client drives the booking flow and reports each milestone with
+//! `POST /session/{id}`; the latest [`BookingState`] replaces the previous one. The
+//! `get-ticket-price` arguments of every leg are kept so a resumed booking can be
+//! re-priced (the old quote has usually expired by then). Passenger names and emails are
+//! never stored, only how many travellers there are.
+//!
+//! With AGENT_A_PROOF_DB set, states live in the same SQLite file as the proofs and
+//! survive restarts.
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use zk_protocol::abi::format_price;
+
+/// Milestones of the booking flow, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BookingStage {
+    /// Every leg priced and shown to the user
+    Quoted,
+    /// The user accepted the price
+    Confirmed,
+    /// Passenger details taken; payment is next
+    PassengersEntered,
+    Paid,
+    Booked,
+    /// Cancelled after booking; see `refund_status`
+    Cancelled,
+}
+
+impl BookingStage {
+    /// What the user still has to do, as shown in a resume summary
+    pub fn next_step(&self) -> &'static str {
+        match self {
+            BookingStage::Quoted => "confirming the price",
+            BookingStage::Confirmed => "passenger details and payment",
+            BookingStage::PassengersEntered => "payment",
+            BookingStage::Paid => "issuing your tickets",
+            BookingStage::Booked => "nothing, you're all set",
+            BookingStage::Cancelled => "nothing, the booking is cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BookingState {
+    /// Set from the path
+    #[serde(default)]
+    pub session_id: String,
+    pub stage: BookingStage,
+    /// e.g. "NYC → London → NYC"
+    pub route: String,
+    /// Price for every passenger, in `currency`'s minor units
+    pub total_cents: Option<u64>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    /// Number of travellers (0 until entered)
+    #[serde(default)]
+    pub passengers: usize,
+    /// `get-ticket-price` arguments of each leg, for re-pricing on resume
+    #[serde(default)]
+    pub pricing_calls: Vec<Value>,
+    /// "card" or "crypto", once chosen
+    pub payment_method: Option<String>,
+    #[serde(default)]
+    pub confirmation_codes: Vec<String>,
+    /// Agent B booking ids, for cancelling
+    #[serde(default)]
+    pub booking_ids: Vec<String>,
+    /// Card purchase instruction id or crypto transaction hash
+    pub payment_reference: Option<String>,
+    /// Proven refund, once cancelled
+    pub refund_cents: Option<u64>,
+    /// "reversed" or "pending_manual"
+    pub refund_status: Option<String>,
+    /// Unix seconds, set by the server
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+impl BookingState {
+    /// Whether the flow can be picked up again; once paid, replaying it would charge twice
+    pub fn is_resumable(&self) -> bool {
+        self.stage < BookingStage::Paid && !self.pricing_calls.is_empty()
+    }
+
+    /// One-line recap, e.g. "You confirmed NYC → London at 682.50 USD; next step is payment."
+    pub fn summary(&self) -> String {
+        let price = self
+            .total_cents
+            .map(|total| format!(" at {}", format_price(total, &self.currency)))
+            .unwrap_or_default();
+        let done = match self.stage {
+            BookingStage::Quoted => format!("I quoted {}{}", self.route, price),
+            BookingStage::Confirmed => format!("You confirmed {}{}", self.route, price),
+            BookingStage::PassengersEntered => {
+                let travellers = if self.passengers == 1 { "1 passenger".to_string() } else { format!("{} passengers", self.passengers) };
+                format!("You confirmed {}{} for {}", self.route, price, travellers)
+            }
+            BookingStage::Paid => format!("You paid for {}", self.route),
+            BookingStage::Booked => {
+                return format!("Your trip {} is booked (confirmation: {}).", self.route, self.confirmation_codes.join(", "));
+            }
+            BookingStage::Cancelled => {
+                let refund = self.refund_cents.map(|refund| format_price(refund, &self.currency)).unwrap_or_else(|| "no refund".to_string());
+                let status = match self.refund_status.as_deref() {
+                    Some("reversed") => ", returned to your card",
+                    Some("pending_manual") => ", pending manual processing",
+                    _ => "",
+                };
+                return format!("Your trip {} is cancelled ({}{}).", self.route, refund, status);
+            }
+        };
+        format!("{}; next step is {}.", done, self.stage.next_step())
+    }
+}
+
+/// Latest booking state per session
+pub trait BookingStateStore: Send + Sync {
+    fn get(&self, session_id: &str) -> Result<Option<BookingState>>;
+
+    /// Replace the session's state
+    fn put(&self, state: &BookingState) -> Result<()>;
+}
+
+#[derive(Default)]
+pub struct MemoryBookingStateStore {
+    states: Mutex<HashMap<String, BookingState>>,
+}
+
+impl MemoryBookingStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BookingStateStore for MemoryBookingStateStore {
+    fn get(&self, session_id: &str) -> Result<Option<BookingState>> {
+        Ok(self.states.lock().unwrap().get(session_id).cloned())
+    }
+
+    fn put(&self, state: &BookingState) -> Result<()> {
+        self.states.lock().unwrap().insert(state.session_id.clone(), state.clone());
+        Ok(())
+    }
+}
+
+/// Booking states persisted in a SQLite database
+pub struct SqliteBookingStateStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteBookingStateStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS booking_states (
+                session_id TEXT PRIMARY KEY,
+                updated_at INTEGER NOT NULL,
+                state      TEXT NOT NULL
+            );",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+}
+
+impl BookingStateStore for SqliteBookingStateStore {
+    fn get(&self, session_id: &str) -> Result<Option<BookingState>> {
+        let state: Option<String> = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT state FROM booking_states WHERE session_id = ?1", params![session_id], |row| row.get(0))
+            .optional()?;
+        Ok(state.map(|state| serde_json::from_str(&state)).transpose()?)
+    }
+
+    fn put(&self, state: &BookingState) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO booking_states (session_id, updated_at, state) VALUES (?1, ?2, ?3)
+             ON CONFLICT (session_id) DO UPDATE SET updated_at = excluded.updated_at, state = excluded.state",
+            params![state.session_id, state.updated_at as i64, serde_json::to_string(state)?],
+        )?;
+        Ok(())
+    }
+}
+
+/// SQLite at `path` if set, else memory
+pub fn open(path: Option<&str>) -> Result<Box<dyn BookingStateStore>> {
+    Ok(match path {
+        Some(path) => Box::new(SqliteBookingStateStore::open(path)?),
+        None => Box::new(MemoryBookingStateStore::new()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_backends_keep_the_latest_state() {
+        let stores: [Box<dyn BookingStateStore>; 2] =
+            [Box::new(MemoryBookingStateStore::new()), Box::new(SqliteBookingStateStore::open_in_memory().unwrap())];
+        for store in stores {
+            let mut state: BookingState = serde_json::from_value(json!({
+                "stage": "confirmed",
+                "route": "NYC → LON",
+                "total_cents": 68_250,
+                "pricing_calls": [{"from": "NYC", "to": "LON", "departure_date": "2026-11-02"}],
+            }))
+            .unwrap();
+            state.session_id = "s1".into();
+            store.put(&state).unwrap();
+            assert!(store.get("s2").unwrap().is_none());
+
+            let saved = store.get("s1").unwrap().unwrap();
+            assert!(saved.is_resumable());
+            assert_eq!(saved.summary(), "You confirmed NYC → LON at 682.50 USD; next step is passenger details and payment.");
+
+            state.stage = BookingStage::Booked;
+            state.confirmation_codes = vec!["ABC123".into()];
+            store.put(&state).unwrap();
+            let booked = store.get("s1").unwrap().unwrap();
+            assert!(!booked.is_resumable());
+            assert_eq!(booked.summary(), "Your trip NYC → LON is booked (confirmation: ABC123).");
+
+            state.stage = BookingStage::Cancelled;
+            state.refund_cents = Some(58_250);
+            state.refund_status = Some("pending_manual".into());
+            store.put(&state).unwrap();
+            let cancelled = store.get("s1").unwrap().unwrap();
+            assert!(!cancelled.is_resumable());
+            assert_eq!(cancelled.summary(), "Your trip NYC → LON is cancelled (582.50 USD, pending manual processing).");
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Circuit breakers for Agent A's downstream services
+//!
+//! After `threshold` consecutive failures a breaker opens and calls fail fast until
+//! `cooldown` has passed; the next call (or a successful health probe) is let through
+//! as a trial and closes the breaker again if it succeeds.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+/// Snapshot reported on Agent A's `/health`
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerStatus {
+    pub name: &'static str,
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    last_error: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: &'static str,
+    threshold: u32,
+    cooldown: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name,
+            threshold: threshold.max(1),
+            cooldown,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn state(&self) -> BreakerState {
+        let inner = self.inner.lock().unwrap();
+        match inner.opened_at {
+            None => BreakerState::Closed,
+            Some(at) if at.elapsed() < self.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    /// Fail fast while open
+    pub fn check(&self) -> Result<()> {
+        if self.state() != BreakerState::Open {
+            return Ok(());
+        }
+        let inner = self.inner.lock().unwrap();
+        let retry_in = inner
+            .opened_at
+            .map(|at| self.cooldown.saturating_sub(at.elapsed()).as_secs())
+            .unwrap_or(0);
+        Err(anyhow::anyhow!(
+            "{} unavailable: circuit open after {} consecutive failures (last error: {}), retrying in {}s",
+            self.name,
+            inner.consecutive_failures,
+            inner.last_error.as_deref().unwrap_or("unknown"),
+            retry_in
+        ))
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.opened_at.is_some() {
+            tracing::info!("✓ {} recovered, closing circuit", self.name);
+        }
+        *inner = Inner::default();
+    }
+
+    pub fn record_failure(&self, error: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        inner.last_error = Some(error.to_string());
+        // Trips at the threshold, and re-arms the cooldown if a half-open trial fails
+        if inner.consecutive_failures >= self.threshold {
+            if inner.opened_at.is_none() {
+                tracing::warn!(
+                    "⚠ {} circuit opened after {} consecutive failures: {}",
+                    self.name,
+                    inner.consecutive_failures,
+                    error
+                );
+            }
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// Run `call` through the breaker
+    pub async fn call<T, F>(&self, call: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        self.check()?;
+        let result = call.await;
+        match &result {
+            Ok(_) => self.record_success(),
+            Err(e) => self.record_failure(&e.to_string()),
+        }
+        result
+    }
+
+    pub fn status(&self) -> BreakerStatus {
+        let state = self.state();
+        let inner = self.inner.lock().unwrap();
+        BreakerStatus {
+            name: self.name,
+            state,
+            consecutive_failures: inner.consecutive_failures,
+            last_error: inner.last_error.clone(),
+        }
+    }
+}
+
+/// One breaker per downstream subsystem
+#[derive(Debug)]
+pub struct Breakers {
+    pub agent_b: CircuitBreaker,
+    pub attester: CircuitBreaker,
+    pub rpc: CircuitBreaker,
+}
+
+impl Breakers {
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            agent_b: CircuitBreaker::new("agent_b", threshold, cooldown),
+            attester: CircuitBreaker::new("attester", threshold, cooldown),
+            rpc: CircuitBreaker::new("rpc", threshold, cooldown),
+        }
+    }
+
+    pub fn all(&self) -> [&CircuitBreaker; 3] {
+        [&self.agent_b, &self.attester, &self.rpc]
+    }
+
+    pub fn status(&self) -> Vec<BreakerStatus> {
+        self.all().iter().map(|b| b.status()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold_and_half_opens_after_cooldown() {
+        let breaker = CircuitBreaker::new("agent_b", 2, Duration::from_millis(20));
+
+        breaker.record_failure("connection refused");
+        assert!(breaker.check().is_ok());
+        breaker.record_failure("connection refused");
+        assert_eq!(breaker.state(), BreakerState::Open);
+        assert!(breaker.check().is_err());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert!(breaker.check().is_ok());
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+}
@@ -0,0 +1,126 @@
+//! Local chain for running the on-chain path without Sepolia (`CHAIN_MODE=local`)
+//!
+//! [`start`] connects to an Anvil node (spawning `anvil` when no URL is given), deploys a
+//! verifier that accepts every proof, and deploys ZeroProof against it from the Foundry
+//! artifact (`forge build` in `contracts/`). The same verifier is registered for
+//! `sp1-plonk`, so PLONK proofs take the on-chain path too. Transactions are signed with Anvil's first
+//! dev account, which the node funds, so no keys or faucet ETH are needed.
+//!
+//! The mock verifier is embedded as bytecode: a contract whose code is a single `STOP`,
+//! so `verifyProof` calls succeed with no return data, as the real SP1 verifier's do.
+
+use anyhow::{anyhow, Context, Result};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Bytes, TransactionRequest};
+use ethers::utils::{Anvil, AnvilInstance};
+use std::sync::Arc;
+use zk_protocol::ProofSystem;
+
+/// Private key of Anvil's first default account
+pub const ANVIL_DEV_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+/// Default location of the ZeroProof artifact, relative to the repository root
+pub const DEFAULT_ARTIFACT: &str = "contracts/out/ZeroProof.sol/ZeroProof.json";
+
+/// Init code returning the one-byte runtime `00` (STOP):
+/// `PUSH1 1 PUSH1 12 PUSH1 0 CODECOPY PUSH1 1 PUSH1 0 RETURN STOP`
+const ACCEPT_ALL_VERIFIER: [u8; 13] = [
+    0x60, 0x01, 0x60, 0x0c, 0x60, 0x00, 0x39, 0x60, 0x01, 0x60, 0x00, 0xf3, 0x00,
+];
+
+/// A running local chain with the contracts deployed; a spawned node stops on drop
+pub struct LocalChain {
+    pub rpc_url: String,
+    pub chain_id: u64,
+    pub zeroproof_addr: String,
+    pub verifier_addr: String,
+    /// Funded dev account that deployed the contracts
+    pub private_key: String,
+    _anvil: Option<AnvilInstance>,
+}
+
+/// Start (or attach to) Anvil and deploy the mock verifier and ZeroProof
+pub async fn start(anvil_url: Option<&str>, artifact_path: &str) -> Result<LocalChain> {
+    let artifact: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(artifact_path)
+            .with_context(|| format!("Reading {} (run `forge build` in contracts/)", artifact_path))?,
+    )?;
+    let abi: ethers::abi::Abi = serde_json::from_value(artifact["abi"].clone())?;
+    let bytecode: Bytes = artifact["bytecode"]["object"]
+        .as_str()
+        .ok_or_else(|| anyhow!("{} has no bytecode.object", artifact_path))?
+        .parse()?;
+
+    let (rpc_url, anvil) = match anvil_url {
+        Some(url) => (url.to_string(), None),
+        None => {
+            let anvil = spawn_anvil()?;
+            (anvil.endpoint(), Some(anvil))
+        }
+    };
+
+    let provider = Provider::<Http>::try_from(rpc_url.as_str())?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let wallet = ANVIL_DEV_KEY.trim_start_matches("0x").parse::<LocalWallet>()?.with_chain_id(chain_id);
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let verifier = deploy_raw(&client, ACCEPT_ALL_VERIFIER.to_vec()).await?;
+    let zeroproof = ethers::contract::ContractFactory::new(abi, bytecode, client.clone())
+        .deploy((verifier, verifier))?
+        .send()
+        .await?;
+    zeroproof
+        .method::<_, ()>("registerVerifier", (ProofSystem::Sp1Plonk.as_bytes32(), verifier))?
+        .send()
+        .await?
+        .await?;
+    let zeroproof = zeroproof.address();
+
+    tracing::info!(
+        "✓ Local chain {} at {}: ZeroProof {:#x}, accept-all verifier {:#x}",
+        chain_id,
+        rpc_url,
+        zeroproof,
+        verifier
+    );
+    Ok(LocalChain {
+        rpc_url,
+        chain_id,
+        zeroproof_addr: format!("{:#x}", zeroproof),
+        verifier_addr: format!("{:#x}", verifier),
+        private_key: ANVIL_DEV_KEY.to_string(),
+        _anvil: anvil,
+    })
+}
+
+/// Spawn `anvil` from PATH. ethers' `Anvil::spawn` panics when the binary is missing or the
+/// node doesn't come up, so check for it first and turn a panic into an error.
+fn spawn_anvil() -> Result<AnvilInstance> {
+    std::process::Command::new("anvil")
+        .arg("--version")
+        .output()
+        .context("Running anvil (is Foundry installed? set ANVIL_URL to use a running node)")?;
+    std::panic::catch_unwind(|| Anvil::new().spawn()).map_err(|panic| {
+        let reason = panic
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| panic.downcast_ref::<&str>().copied())
+            .unwrap_or("unknown error");
+        anyhow!("Spawning anvil: {}", reason)
+    })
+}
+
+async fn deploy_raw<M: Middleware + 'static>(client: &M, init_code: Vec<u8>) -> Result<Address> {
+    let pending = client
+        .send_transaction(TransactionRequest::new().data(init_code), None)
+        .await
+        .map_err(|e| anyhow!("Deploying mock verifier: {}", e))?;
+    let receipt = pending
+        .await?
+        .ok_or_else(|| anyhow!("Mock verifier deployment dropped"))?;
+    receipt
+        .contract_address
+        .ok_or_else(|| anyhow!("Mock verifier deployment has no contract address"))
+}
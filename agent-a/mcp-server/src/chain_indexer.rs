@@ -0,0 +1,403 @@
+//! Local index of ZeroProof contract events, for browsing on-chain claims
+//!
+//! [`run`] subscribes to the contract's `ProofVerified` and `ClaimRecorded` logs over a
+//! WebSocket RPC. On every (re)connect it first backfills from the block after the last
+//! one indexed, so gaps while disconnected are filled. Events land in a [`ClaimIndex`]
+//! (SQLite, on disk or in memory) that `GET /claims` queries by agent and claim type.
+//!
+//! Logs are keyed by (transaction hash, log index), so replaying a range adds nothing
+//! twice, and logs a reorg removes are deleted again.
+
+use anyhow::{anyhow, Result};
+use ethers::types::{Address, Filter, Log, H256};
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::ClaimType;
+
+/// Blocks per eth_getLogs request while backfilling (public RPCs cap the range)
+const BACKFILL_RANGE: u64 = 5_000;
+
+/// Wait before reconnecting after the subscription fails
+const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(10);
+
+const PROOF_VERIFIED: &str = "ProofVerified(bytes32,bytes32,address,bytes32,uint256)";
+const CLAIM_RECORDED: &str = "ClaimRecorded(bytes32,address,bytes32,bytes32,bytes32,uint256)";
+
+fn topic(signature: &str) -> H256 {
+    H256(ethers::core::utils::keccak256(signature.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// One indexed contract event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedClaim {
+    /// "proof_verified" or "claim_recorded"
+    pub event: String,
+    pub agent: String,
+    /// bytes32 claim type; `claim_type_label` names the known ones
+    pub claim_type: String,
+    pub claim_type_label: Option<String>,
+    /// `claimHash` of a ProofVerified, `dataHash` of a ClaimRecorded
+    pub data_hash: String,
+    /// ClaimRecorded only
+    pub claim_id: Option<String>,
+    /// ProofVerified only
+    pub proof_type: Option<String>,
+    /// ClaimRecorded only
+    pub vk_hash: Option<String>,
+    /// Block time from the event (unix seconds)
+    pub timestamp: u64,
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub log_index: u64,
+}
+
+impl IndexedClaim {
+    /// Decode a ZeroProof log; `None` for other events or malformed logs
+    pub fn from_log(log: &Log) -> Option<Self> {
+        let topics = &log.topics;
+        let word = |i: usize| log.data.get(i * 32..(i + 1) * 32);
+        let uint = |i: usize| word(i).map(|w| ethers::types::U256::from_big_endian(w).low_u64());
+        let address = |t: &H256| format!("{:#x}", Address::from(*t));
+        let (event, agent, claim_type, data_hash, claim_id, proof_type, vk_hash, timestamp) =
+            match topics.first() {
+                Some(t) if *t == topic(PROOF_VERIFIED) && topics.len() == 4 => (
+                    "proof_verified",
+                    address(&topics[3]),
+                    topics[2],
+                    to_hex(word(0)?),
+                    None,
+                    Some(to_hex(topics[1].as_bytes())),
+                    None,
+                    uint(1)?,
+                ),
+                Some(t) if *t == topic(CLAIM_RECORDED) && topics.len() == 4 => (
+                    "claim_recorded",
+                    address(&topics[2]),
+                    topics[3],
+                    to_hex(word(0)?),
+                    Some(to_hex(topics[1].as_bytes())),
+                    None,
+                    Some(to_hex(word(1)?)),
+                    uint(2)?,
+                ),
+                _ => return None,
+            };
+
+        Some(Self {
+            event: event.to_string(),
+            agent,
+            claim_type: to_hex(claim_type.as_bytes()),
+            claim_type_label: claim_type_label(&claim_type.0).map(str::to_string),
+            data_hash,
+            claim_id,
+            proof_type,
+            vk_hash,
+            timestamp,
+            block_number: log.block_number?.as_u64(),
+            tx_hash: format!("{:#x}", log.transaction_hash?),
+            log_index: log.log_index?.as_u64(),
+        })
+    }
+}
+
+fn claim_type_label(claim_type: &[u8; 32]) -> Option<&'static str> {
+    [ClaimType::Pricing, ClaimType::Booking, ClaimType::Payment]
+        .into_iter()
+        .find(|known| known.as_bytes32() == *claim_type)
+        .map(|known| known.label())
+}
+
+/// `claimType` query value: a label ("booking") or a 0x bytes32
+pub fn parse_claim_type(value: &str) -> Result<String> {
+    Ok(match value {
+        "pricing" => to_hex(&ClaimType::Pricing.as_bytes32()),
+        "booking" => to_hex(&ClaimType::Booking.as_bytes32()),
+        "payment" => to_hex(&ClaimType::Payment.as_bytes32()),
+        raw => {
+            let bytes = hex::decode(raw.trim_start_matches("0x"))?;
+            if bytes.len() != 32 {
+                return Err(anyhow!("claimType must be pricing, booking, payment, or a 0x bytes32"));
+            }
+            to_hex(&bytes)
+        }
+    })
+}
+
+/// `GET /claims` filters; they combine with AND
+#[derive(Debug, Clone, Default)]
+pub struct ClaimFilter {
+    pub agent: Option<String>,
+    /// 0x bytes32 (see [`parse_claim_type`])
+    pub claim_type: Option<String>,
+    /// Only events from these transactions (the ones a session sent)
+    pub tx_hashes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaimPage {
+    pub claims: Vec<IndexedClaim>,
+    /// Pass as `cursor` to get the next page; `None` on the last page
+    pub next_cursor: Option<u64>,
+    /// Highest block fully indexed
+    pub indexed_through: Option<u64>,
+}
+
+pub struct ClaimIndex {
+    conn: Mutex<Connection>,
+}
+
+impl ClaimIndex {
+    /// SQLite file at `path`, else in memory (rebuilt by backfilling after a restart)
+    pub fn open(path: Option<&str>) -> Result<Self> {
+        let conn = match path {
+            Some(path) => Connection::open(path)?,
+            None => Connection::open_in_memory()?,
+        };
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS claim_events (
+                seq          INTEGER PRIMARY KEY AUTOINCREMENT,
+                tx_hash      TEXT NOT NULL,
+                log_index    INTEGER NOT NULL,
+                agent        TEXT NOT NULL,
+                claim_type   TEXT NOT NULL,
+                block_number INTEGER NOT NULL,
+                event        TEXT NOT NULL,
+                UNIQUE (tx_hash, log_index)
+            );
+            CREATE INDEX IF NOT EXISTS claim_events_by_agent ON claim_events (agent, seq);
+            CREATE TABLE IF NOT EXISTS indexer_state (
+                id              INTEGER PRIMARY KEY CHECK (id = 0),
+                indexed_through INTEGER NOT NULL
+            );",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Store an event; false if it was already indexed
+    pub fn insert(&self, claim: &IndexedClaim) -> Result<bool> {
+        let added = self.conn.lock().unwrap().execute(
+            "INSERT OR IGNORE INTO claim_events (tx_hash, log_index, agent, claim_type, block_number, event)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                claim.tx_hash,
+                claim.log_index as i64,
+                claim.agent,
+                claim.claim_type,
+                claim.block_number as i64,
+                serde_json::to_string(claim)?,
+            ],
+        )?;
+        Ok(added == 1)
+    }
+
+    /// Drop an event a reorg removed
+    pub fn remove(&self, tx_hash: &str, log_index: u64) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "DELETE FROM claim_events WHERE tx_hash = ?1 AND log_index = ?2",
+            params![tx_hash, log_index as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Index or unindex a subscription log
+    pub fn apply(&self, log: &Log) -> Result<()> {
+        if log.removed == Some(true) {
+            if let (Some(tx_hash), Some(log_index)) = (log.transaction_hash, log.log_index) {
+                self.remove(&format!("{:#x}", tx_hash), log_index.as_u64())?;
+            }
+            return Ok(());
+        }
+        match IndexedClaim::from_log(log) {
+            Some(claim) => {
+                self.insert(&claim)?;
+            }
+            None => tracing::warn!("Skipping undecodable ZeroProof log in tx {:?}", log.transaction_hash),
+        }
+        Ok(())
+    }
+
+    pub fn indexed_through(&self) -> Result<Option<u64>> {
+        Ok(self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT indexed_through FROM indexer_state WHERE id = 0", [], |row| row.get::<_, i64>(0))
+            .optional()?
+            .map(|block| block as u64))
+    }
+
+    pub fn set_indexed_through(&self, block: u64) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO indexer_state (id, indexed_through) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET indexed_through = excluded.indexed_through",
+            params![block as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Up to `limit` matching events indexed after `cursor`, in indexing order
+    pub fn query(&self, filter: &ClaimFilter, cursor: Option<u64>, limit: usize) -> Result<ClaimPage> {
+        let mut sql = String::from("SELECT seq, event FROM claim_events WHERE seq > ?");
+        let mut args: Vec<Box<dyn ToSql>> = vec![Box::new(cursor.unwrap_or(0) as i64)];
+        if let Some(agent) = &filter.agent {
+            sql.push_str(" AND agent = ?");
+            args.push(Box::new(agent.to_lowercase()));
+        }
+        if let Some(claim_type) = &filter.claim_type {
+            sql.push_str(" AND claim_type = ?");
+            args.push(Box::new(claim_type.to_lowercase()));
+        }
+        if let Some(tx_hashes) = &filter.tx_hashes {
+            sql.push_str(&format!(" AND tx_hash IN ({})", vec!["?"; tx_hashes.len()].join(", ")));
+            args.extend(tx_hashes.iter().map(|h| Box::new(h.to_lowercase()) as Box<dyn ToSql>));
+        }
+        // One extra row tells us whether there is a next page
+        sql.push_str(" ORDER BY seq LIMIT ?");
+        args.push(Box::new(limit as i64 + 1));
+
+        let rows = {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt
+                .query_map(rusqlite::params_from_iter(args.iter()), |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            rows
+        };
+
+        let has_more = rows.len() > limit;
+        let mut claims = Vec::with_capacity(limit);
+        let mut last_seq = None;
+        for (seq, event) in rows.into_iter().take(limit) {
+            claims.push(serde_json::from_str(&event)?);
+            last_seq = Some(seq as u64);
+        }
+        Ok(ClaimPage {
+            claims,
+            next_cursor: if has_more { last_seq } else { None },
+            indexed_through: self.indexed_through()?,
+        })
+    }
+}
+
+/// Follow `contract`'s events over `ws_url` forever, starting at `from_block` on an
+/// empty index
+pub async fn run(ws_url: String, contract: String, index: std::sync::Arc<ClaimIndex>, from_block: u64) {
+    loop {
+        if let Err(e) = follow(&ws_url, &contract, &index, from_block).await {
+            tracing::warn!("Chain indexer disconnected: {}; reconnecting in {:?}", e, RECONNECT_DELAY);
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn follow(ws_url: &str, contract: &str, index: &ClaimIndex, from_block: u64) -> Result<()> {
+    use ethers::providers::{Middleware, Provider, StreamExt, Ws};
+
+    let provider = Provider::<Ws>::connect(ws_url).await?;
+    let filter = Filter::new()
+        .address(contract.parse::<Address>()?)
+        .topic0(vec![topic(PROOF_VERIFIED), topic(CLAIM_RECORDED)]);
+
+    // Subscribe before backfilling so nothing falls between the two
+    let mut stream = provider.subscribe_logs(&filter).await?;
+
+    let latest = provider.get_block_number().await?.as_u64();
+    let mut start = index.indexed_through()?.map(|b| b + 1).unwrap_or(from_block);
+    if start <= latest {
+        tracing::info!("Chain indexer backfilling blocks {}..={}", start, latest);
+    }
+    while start <= latest {
+        let end = (start + BACKFILL_RANGE - 1).min(latest);
+        for log in provider.get_logs(&filter.clone().from_block(start).to_block(end)).await? {
+            index.apply(&log)?;
+        }
+        index.set_indexed_through(end)?;
+        start = end + 1;
+    }
+    tracing::info!("✓ Chain indexer following {} from block {}", contract, start);
+
+    while let Some(log) = stream.next().await {
+        index.apply(&log)?;
+        // More logs of the same block may follow, so only the one before counts as done
+        if let Some(block) = log.block_number {
+            let done = block.as_u64().saturating_sub(1);
+            if index.indexed_through()?.is_none_or(|through| through < done) {
+                index.set_indexed_through(done)?;
+            }
+        }
+    }
+    Err(anyhow!("subscription closed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Bytes, U256, U64};
+
+    fn claim_recorded_log(tx: u8, log_index: u64, claim_type: ClaimType) -> Log {
+        let mut data = vec![0xaa; 32];
+        data.extend_from_slice(&[0xbb; 32]);
+        let mut timestamp = [0u8; 32];
+        U256::from(1_700_000_000u64).to_big_endian(&mut timestamp);
+        data.extend_from_slice(&timestamp);
+        Log {
+            topics: vec![
+                topic(CLAIM_RECORDED),
+                H256([0x11; 32]),
+                H256::from(Address::repeat_byte(0x22)),
+                H256(claim_type.as_bytes32()),
+            ],
+            data: Bytes::from(data),
+            block_number: Some(U64::from(42)),
+            transaction_hash: Some(H256([tx; 32])),
+            log_index: Some(U256::from(log_index)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_index_decodes_dedupes_and_filters_events() {
+        let claim = IndexedClaim::from_log(&claim_recorded_log(1, 0, ClaimType::Booking)).unwrap();
+        assert_eq!(claim.event, "claim_recorded");
+        assert_eq!(claim.agent, format!("{:#x}", Address::repeat_byte(0x22)));
+        assert_eq!(claim.claim_type_label.as_deref(), Some("booking"));
+        assert_eq!(claim.vk_hash, Some(to_hex(&[0xbb; 32])));
+        assert_eq!(claim.timestamp, 1_700_000_000);
+
+        let index = ClaimIndex::open(None).unwrap();
+        assert!(index.insert(&claim).unwrap());
+        assert!(!index.insert(&claim).unwrap());
+        index.apply(&claim_recorded_log(2, 0, ClaimType::Pricing)).unwrap();
+
+        let booking = ClaimFilter {
+            agent: Some(claim.agent.to_uppercase().replace("0X", "0x")),
+            claim_type: Some(parse_claim_type("booking").unwrap()),
+            ..Default::default()
+        };
+        let page = index.query(&booking, None, 10).unwrap();
+        assert_eq!(page.claims, vec![claim.clone()]);
+
+        let all = index.query(&ClaimFilter::default(), None, 1).unwrap();
+        assert!(all.next_cursor.is_some());
+        let by_tx = ClaimFilter { tx_hashes: Some(vec![format!("{:#x}", H256([2; 32]))]), ..Default::default() };
+        assert_eq!(index.query(&by_tx, None, 10).unwrap().claims.len(), 1);
+
+        let mut removed = claim_recorded_log(1, 0, ClaimType::Booking);
+        removed.removed = Some(true);
+        index.apply(&removed).unwrap();
+        assert!(index.query(&booking, None, 10).unwrap().claims.is_empty());
+
+        index.set_indexed_through(41).unwrap();
+        index.set_indexed_through(42).unwrap();
+        assert_eq!(index.indexed_through().unwrap(), Some(42));
+        assert!(parse_claim_type("0x1234").is_err());
+    }
+}
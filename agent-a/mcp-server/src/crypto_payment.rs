@@ -0,0 +1,363 @@
+//! Crypto settlement of a booking: an EIP-681 request for a stablecoin transfer, then
+//! watching the chain for it (the "pay with crypto" alternative to the card flow)
+//!
+//! [`PaymentLedger::create`] prices a USD amount in the configured ERC-20 token and
+//! returns a [`CryptoPayment`] with an `ethereum:` URI a wallet can open. Every payment
+//! adds its own sub-cent salt to the token amount, so two sessions owing the same price
+//! ask for different amounts. Checking a pending payment ([`find_transfer`]) asks the RPC
+//! pool for `Transfer` logs of the token to the recipient since the request was created;
+//! the first transfer of exactly the amount settles it, and past `expires_at` only one
+//! mined by then does. A transaction settles at most one payment.
+//!
+//! Settled payments are kept per `session_id` and exported with the session's proof
+//! bundle, next to the proofs of the booking they paid for.
+
+use anyhow::{anyhow, Result};
+use ethers::types::{Address, U256};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use crate::{RpcPool, RpcReply};
+
+const TRANSFER: &str = "Transfer(address,address,uint256)";
+
+/// Where and in what crypto payments are made
+#[derive(Debug, Clone)]
+pub struct PaymentTerms {
+    pub chain_id: u64,
+    /// ERC-20 token contract (a USD stablecoin)
+    pub token: Address,
+    pub symbol: String,
+    pub decimals: u32,
+    pub recipient: Address,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CryptoPaymentStatus {
+    Pending,
+    Paid,
+    /// Not paid before `expires_at`
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CryptoPayment {
+    pub id: String,
+    pub session_id: Option<String>,
+    pub chain_id: u64,
+    pub token: String,
+    pub symbol: String,
+    pub recipient: String,
+    /// Price being paid, in cents
+    pub amount_cents: u64,
+    /// Token amount in base units (decimal string): the price plus this payment's salt
+    pub amount: String,
+    /// `amount` in whole tokens, e.g. "679.900001"; the exact amount to send
+    pub amount_formatted: String,
+    /// EIP-681 transfer request, e.g. for a wallet QR code
+    pub uri: String,
+    /// Transfers from this block on count
+    pub from_block: u64,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub status: CryptoPaymentStatus,
+    /// Settling transfer, once paid
+    pub tx_hash: Option<String>,
+    pub block_number: Option<u64>,
+    pub payer: Option<String>,
+}
+
+/// A token transfer found on-chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub tx_hash: String,
+    pub block_number: u64,
+    pub from: String,
+    pub value: U256,
+}
+
+/// Cents as token base units: 1 USD = 10^decimals
+pub fn token_amount(amount_cents: u64, decimals: u32) -> Result<U256> {
+    if decimals < 2 {
+        return Err(anyhow!("Token with {} decimals can't represent cents", decimals));
+    }
+    Ok(U256::from(amount_cents) * U256::exp10(decimals as usize - 2))
+}
+
+/// EIP-681 URI calling `transfer(recipient, amount)` on `token`
+pub fn eip681_uri(token: Address, chain_id: u64, recipient: Address, amount: U256) -> String {
+    format!("ethereum:{:#x}@{}/transfer?address={:#x}&uint256={}", token, chain_id, recipient, amount)
+}
+
+/// Payments created by this server, by id
+#[derive(Default)]
+pub struct PaymentLedger {
+    payments: Mutex<HashMap<String, CryptoPayment>>,
+    /// Transactions that already settled a payment
+    settled_txs: Mutex<HashSet<String>>,
+    /// Next sub-cent salt to try; rolls over so recent amounts aren't reused
+    next_salt: Mutex<u64>,
+}
+
+impl PaymentLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New pending payment of `amount_cents` (USD), payable from `from_block` for `ttl_secs`
+    pub fn create(
+        &self,
+        terms: &PaymentTerms,
+        amount_cents: u64,
+        session_id: Option<&str>,
+        from_block: u64,
+        now: u64,
+        ttl_secs: u64,
+    ) -> Result<CryptoPayment> {
+        let price = token_amount(amount_cents, terms.decimals)?;
+        let mut payments = self.payments.lock().unwrap();
+        let amount = self.salted_amount(&payments, terms, price)?;
+        let payment = CryptoPayment {
+            id: format!("pay_{}", uuid::Uuid::new_v4().simple()),
+            session_id: session_id.map(str::to_string),
+            chain_id: terms.chain_id,
+            token: format!("{:#x}", terms.token),
+            symbol: terms.symbol.clone(),
+            recipient: format!("{:#x}", terms.recipient),
+            amount_cents,
+            amount: amount.to_string(),
+            amount_formatted: ethers::utils::format_units(amount, terms.decimals)?,
+            uri: eip681_uri(terms.token, terms.chain_id, terms.recipient, amount),
+            from_block,
+            created_at: now,
+            expires_at: now + ttl_secs,
+            status: CryptoPaymentStatus::Pending,
+            tx_hash: None,
+            block_number: None,
+            payer: None,
+        };
+        payments.insert(payment.id.clone(), payment.clone());
+        Ok(payment)
+    }
+
+    /// `price` plus a salt of 1 base unit up to just under a cent that no pending
+    /// payment in the same token uses
+    fn salted_amount(&self, payments: &HashMap<String, CryptoPayment>, terms: &PaymentTerms, price: U256) -> Result<U256> {
+        let salts = U256::exp10(terms.decimals as usize - 2).saturating_sub(U256::one()).low_u64();
+        let token = format!("{:#x}", terms.token);
+        let in_use: HashSet<&str> = payments
+            .values()
+            .filter(|p| p.status == CryptoPaymentStatus::Pending && p.token == token)
+            .map(|p| p.amount.as_str())
+            .collect();
+
+        let mut next_salt = self.next_salt.lock().unwrap();
+        for _ in 0..salts.min(in_use.len() as u64 + 1) {
+            let salt = *next_salt % salts + 1;
+            *next_salt = salt;
+            let amount = price + salt;
+            if !in_use.contains(amount.to_string().as_str()) {
+                return Ok(amount);
+            }
+        }
+        Err(anyhow!("No free amount to tell a new payment apart from {} pending ones", in_use.len()))
+    }
+
+    pub fn get(&self, id: &str) -> Option<CryptoPayment> {
+        self.payments.lock().unwrap().get(id).cloned()
+    }
+
+    /// Transactions that can't settle another payment
+    pub fn settled_txs(&self) -> HashSet<String> {
+        self.settled_txs.lock().unwrap().clone()
+    }
+
+    /// Mark a pending payment paid by `transfer`; `None` if it isn't pending or the
+    /// transfer already settled another payment
+    pub fn settle(&self, id: &str, transfer: &Transfer) -> Option<CryptoPayment> {
+        let mut payments = self.payments.lock().unwrap();
+        let payment = payments.get_mut(id).filter(|p| p.status == CryptoPaymentStatus::Pending)?;
+        if !self.settled_txs.lock().unwrap().insert(transfer.tx_hash.clone()) {
+            return None;
+        }
+        payment.status = CryptoPaymentStatus::Paid;
+        payment.tx_hash = Some(transfer.tx_hash.clone());
+        payment.block_number = Some(transfer.block_number);
+        payment.payer = Some(transfer.from.clone());
+        Some(payment.clone())
+    }
+
+    /// Expire a pending payment past its deadline; returns the current view
+    pub fn expire_if_due(&self, id: &str, now: u64) -> Option<CryptoPayment> {
+        let mut payments = self.payments.lock().unwrap();
+        let payment = payments.get_mut(id)?;
+        if payment.status == CryptoPaymentStatus::Pending && now >= payment.expires_at {
+            payment.status = CryptoPaymentStatus::Expired;
+        }
+        Some(payment.clone())
+    }
+
+    /// Paid payments of a session, oldest first
+    pub fn settled_for_session(&self, session_id: &str) -> Vec<CryptoPayment> {
+        let mut paid: Vec<_> = self
+            .payments
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|p| p.status == CryptoPaymentStatus::Paid && p.session_id.as_deref() == Some(session_id))
+            .cloned()
+            .collect();
+        paid.sort_by_key(|p| p.created_at);
+        paid
+    }
+}
+
+/// Current block number, where a new payment starts watching
+pub async fn latest_block(client: &reqwest::Client, rpc: &RpcPool) -> Result<u64> {
+    let RpcReply { response, .. } = rpc.call(client, "eth_blockNumber", serde_json::json!([])).await?;
+    let block = response
+        .get("result")
+        .and_then(|r| r.as_str())
+        .ok_or_else(|| anyhow!("Unexpected eth_blockNumber response: {}", response))?;
+    Ok(u64::from_str_radix(block.trim_start_matches("0x"), 16)?)
+}
+
+/// Timestamp of block `number`
+pub async fn block_timestamp(client: &reqwest::Client, rpc: &RpcPool, number: u64) -> Result<u64> {
+    let params = serde_json::json!([format!("{:#x}", number), false]);
+    let RpcReply { response, .. } = rpc.call(client, "eth_getBlockByNumber", params).await?;
+    let timestamp = response
+        .pointer("/result/timestamp")
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| anyhow!("Unexpected eth_getBlockByNumber response: {}", response))?;
+    Ok(u64::from_str_radix(timestamp.trim_start_matches("0x"), 16)?)
+}
+
+/// First transfer settling `payment` that isn't in `exclude`, if one has been mined; with
+/// `mined_by`, only one in a block no later than that timestamp
+pub async fn find_transfer(
+    client: &reqwest::Client,
+    rpc: &RpcPool,
+    payment: &CryptoPayment,
+    exclude: &HashSet<String>,
+    mined_by: Option<u64>,
+) -> Result<Option<Transfer>> {
+    let recipient: Address = payment.recipient.parse()?;
+    let params = serde_json::json!([{
+        "address": payment.token,
+        "fromBlock": format!("{:#x}", payment.from_block),
+        "toBlock": "latest",
+        "topics": [
+            format!("{:#x}", ethers::types::H256(ethers::core::utils::keccak256(TRANSFER.as_bytes()))),
+            Value::Null,
+            format!("{:#x}", ethers::types::H256::from(recipient)),
+        ],
+    }]);
+    let RpcReply { response, .. } = rpc.call(client, "eth_getLogs", params).await?;
+    if let Some(error) = response.get("error") {
+        return Err(anyhow!("eth_getLogs failed: {}", error));
+    }
+    let logs = response
+        .get("result")
+        .and_then(|r| r.as_array())
+        .ok_or_else(|| anyhow!("Unexpected eth_getLogs response: {}", response))?;
+
+    let amount = U256::from_dec_str(&payment.amount)?;
+    let candidates = logs
+        .iter()
+        .filter_map(parse_transfer)
+        .filter(|t| t.value == amount && !exclude.contains(&t.tx_hash));
+    for transfer in candidates {
+        match mined_by {
+            Some(deadline) if block_timestamp(client, rpc, transfer.block_number).await? > deadline => {}
+            _ => return Ok(Some(transfer)),
+        }
+    }
+    Ok(None)
+}
+
+/// `Transfer` log as returned by eth_getLogs; `None` for removed or malformed logs
+pub fn parse_transfer(log: &Value) -> Option<Transfer> {
+    if log.get("removed").and_then(|r| r.as_bool()) == Some(true) {
+        return None;
+    }
+    let topics = log.get("topics")?.as_array()?;
+    let from = topics.get(1)?.as_str()?.trim_start_matches("0x");
+    let quantity = |field: &str| u64::from_str_radix(log.get(field)?.as_str()?.trim_start_matches("0x"), 16).ok();
+    Some(Transfer {
+        tx_hash: log.get("transactionHash")?.as_str()?.to_lowercase(),
+        block_number: quantity("blockNumber")?,
+        from: format!("0x{}", from.get(24..)?),
+        value: U256::from_str_radix(log.get("data")?.as_str()?.trim_start_matches("0x"), 16).ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn terms() -> PaymentTerms {
+        PaymentTerms {
+            chain_id: 11155111,
+            token: "0x1c7d4b196cb0c7b01d743fbc6116a902379c7238".parse().unwrap(),
+            symbol: "USDC".into(),
+            decimals: 6,
+            recipient: "0x00000000000000000000000000000000000000aa".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_payment_request_and_settlement() {
+        let ledger = PaymentLedger::new();
+        let payment = ledger.create(&terms(), 67_990, Some("s1"), 100, 1_000, 900).unwrap();
+        assert_eq!(payment.amount, "679900001");
+        assert_eq!(payment.amount_formatted, "679.900001");
+        assert_eq!(
+            payment.uri,
+            "ethereum:0x1c7d4b196cb0c7b01d743fbc6116a902379c7238@11155111/transfer?address=0x00000000000000000000000000000000000000aa&uint256=679900001"
+        );
+
+        let amount = U256::from(679_900_001u64);
+        let log = json!({
+            "transactionHash": "0xABC",
+            "blockNumber": "0x70",
+            "topics": [
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                "0x00000000000000000000000000000000000000000000000000000000000000bb",
+                "0x00000000000000000000000000000000000000000000000000000000000000aa"
+            ],
+            "data": format!("0x{}", hex::encode(ethers::abi::encode(&[ethers::abi::Token::Uint(amount)]))),
+        });
+        let transfer = parse_transfer(&log).unwrap();
+        assert_eq!(transfer.from, "0x00000000000000000000000000000000000000bb");
+        assert_eq!(transfer.value, amount);
+
+        let paid = ledger.settle(&payment.id, &transfer).unwrap();
+        assert_eq!(paid.status, CryptoPaymentStatus::Paid);
+        assert_eq!(paid.tx_hash.as_deref(), Some("0xabc"));
+        assert_eq!(ledger.settled_for_session("s1").len(), 1);
+
+        // The same transfer can't pay for a second booking
+        let second = ledger.create(&terms(), 67_990, Some("s1"), 100, 1_000, 900).unwrap();
+        assert!(ledger.settle(&second.id, &transfer).is_none());
+        assert_eq!(ledger.expire_if_due(&second.id, 1_900).unwrap().status, CryptoPaymentStatus::Expired);
+    }
+
+    #[test]
+    fn test_pending_payments_of_one_price_ask_for_different_amounts() {
+        let ledger = PaymentLedger::new();
+        let first = ledger.create(&terms(), 67_990, Some("s1"), 100, 1_000, 900).unwrap();
+        let second = ledger.create(&terms(), 67_990, Some("s2"), 100, 1_000, 900).unwrap();
+        assert_ne!(first.amount, second.amount);
+        assert_eq!(first.amount_cents, second.amount_cents);
+
+        // A token without sub-cent units can't tell them apart
+        let cents_only = PaymentTerms { decimals: 2, ..terms() };
+        assert!(ledger.create(&cents_only, 67_990, Some("s3"), 100, 1_000, 900).is_err());
+    }
+}
@@ -0,0 +1,270 @@
+//! Gas and EIP-1559 fee estimates for ZeroProof transactions
+//!
+//! [`estimate`] runs `eth_estimateGas` on the calldata and reads recent `eth_feeHistory`,
+//! then prices the transaction with a [`FeeStrategy`]: the priority fee is the 10th, 50th,
+//! or 90th percentile tip of the last blocks (slow, normal, fast), and the max fee leaves
+//! room for the base fee to double. A configured cap lowers the max fee; a cap below the
+//! current base fee is an error, since the transaction could not be included. A call
+//! that reverts fails with [`EstimateReverted`], carrying the revert data to decode.
+//!
+//! The same [`CostEstimate`] is shown to the user before sending (eth_call mode of
+//! `verify_on_chain`) and applied to the transaction when it is sent.
+
+use anyhow::{anyhow, Result};
+use ethers::types::{Eip1559TransactionRequest, U256};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{RpcPool, RpcReply};
+
+/// Blocks of fee history the tip is taken from
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// Headroom over `eth_estimateGas`, in percent
+const GAS_LIMIT_MARGIN: u64 = 20;
+
+/// Tip when the node reports no rewards (empty blocks)
+const DEFAULT_PRIORITY_FEE_WEI: u64 = 1_500_000_000;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeSpeed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+}
+
+impl FeeSpeed {
+    /// Reward percentile asked of eth_feeHistory
+    fn percentile(self) -> u64 {
+        match self {
+            FeeSpeed::Slow => 10,
+            FeeSpeed::Normal => 50,
+            FeeSpeed::Fast => 90,
+        }
+    }
+
+    fn column(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeeStrategy {
+    pub speed: FeeSpeed,
+    /// Upper bound on `maxFeePerGas`, in gwei
+    pub max_fee_gwei: Option<u64>,
+}
+
+impl FeeStrategy {
+    pub fn with_speed(self, speed: Option<FeeSpeed>) -> Self {
+        Self { speed: speed.unwrap_or(self.speed), ..self }
+    }
+}
+
+/// Error of [`estimate`] when `eth_estimateGas` reverts: the transaction would revert if sent
+#[derive(Debug, Clone)]
+pub struct EstimateReverted {
+    /// The node's error message
+    pub message: String,
+    /// 0x-prefixed revert data, when the node returns it
+    pub data: Option<String>,
+}
+
+impl EstimateReverted {
+    /// The revert in a JSON-RPC `error`, if it is one (some nodes nest `data` one level deeper)
+    pub fn from_rpc_error(error: &Value) -> Option<Self> {
+        let message = error.get("message").and_then(Value::as_str).unwrap_or_default().to_string();
+        let data = error
+            .get("data")
+            .and_then(|data| data.as_str().or_else(|| data.get("data").and_then(Value::as_str)))
+            .map(str::to_string);
+        (data.is_some() || message.contains("revert")).then_some(Self { message, data })
+    }
+}
+
+impl std::fmt::Display for EstimateReverted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "eth_estimateGas reverted: {}", self.message)
+    }
+}
+
+impl std::error::Error for EstimateReverted {}
+
+/// Expected and worst-case cost of a transaction; wei amounts are decimal strings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CostEstimate {
+    pub speed: FeeSpeed,
+    /// From eth_estimateGas
+    pub gas_estimate: u64,
+    /// Sent as the gas limit (estimate plus margin)
+    pub gas_limit: u64,
+    /// Base fee of the next block
+    pub base_fee_per_gas: String,
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    /// gas_estimate × (base fee + tip), in ETH
+    pub expected_cost_eth: String,
+    /// gas_limit × max fee: the most the transaction can cost, in ETH
+    pub max_cost_eth: String,
+    /// The max fee was lowered to the configured cap
+    pub capped: bool,
+}
+
+impl CostEstimate {
+    /// Set gas limit and EIP-1559 fees on `tx`
+    pub fn apply(&self, tx: Eip1559TransactionRequest) -> Result<Eip1559TransactionRequest> {
+        Ok(tx
+            .gas(self.gas_limit)
+            .max_fee_per_gas(U256::from_dec_str(&self.max_fee_per_gas)?)
+            .max_priority_fee_per_gas(U256::from_dec_str(&self.max_priority_fee_per_gas)?))
+    }
+}
+
+/// Estimate gas for calling `to` with `call_data` from `from` and price it with `strategy`
+pub async fn estimate(
+    client: &reqwest::Client,
+    rpc: &RpcPool,
+    from: Option<&str>,
+    to: &str,
+    call_data: &[u8],
+    strategy: &FeeStrategy,
+) -> Result<CostEstimate> {
+    let mut call = serde_json::json!({
+        "to": to,
+        "data": format!("0x{}", hex::encode(call_data)),
+    });
+    if let Some(from) = from {
+        call["from"] = Value::from(from);
+    }
+    let RpcReply { response, .. } = rpc.call(client, "eth_estimateGas", serde_json::json!([call])).await?;
+    if let Some(reverted) = response.get("error").and_then(EstimateReverted::from_rpc_error) {
+        return Err(reverted.into());
+    }
+    let gas_estimate = quantity(result(&response, "eth_estimateGas")?)?.as_u64();
+
+    let params = serde_json::json!([
+        format!("{:#x}", FEE_HISTORY_BLOCKS),
+        "latest",
+        [FeeSpeed::Slow.percentile(), FeeSpeed::Normal.percentile(), FeeSpeed::Fast.percentile()],
+    ]);
+    let RpcReply { response, .. } = rpc.call(client, "eth_feeHistory", params).await?;
+
+    price(strategy, gas_estimate, result(&response, "eth_feeHistory")?)
+}
+
+/// Price `gas_estimate` from an eth_feeHistory result
+pub fn price(strategy: &FeeStrategy, gas_estimate: u64, fee_history: &Value) -> Result<CostEstimate> {
+    // The last entry is the base fee of the next block
+    let base_fee = fee_history["baseFeePerGas"]
+        .as_array()
+        .and_then(|fees| fees.last())
+        .ok_or_else(|| anyhow!("eth_feeHistory returned no baseFeePerGas"))
+        .and_then(quantity)?;
+
+    let mut tips = fee_history["reward"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|block| block.get(strategy.speed.column()))
+        .map(quantity)
+        .collect::<Result<Vec<_>>>()?;
+    tips.sort();
+    let mut tip = tips.get(tips.len() / 2).copied().unwrap_or(U256::from(DEFAULT_PRIORITY_FEE_WEI));
+
+    let mut max_fee = base_fee * 2 + tip;
+    let mut capped = false;
+    if let Some(cap_gwei) = strategy.max_fee_gwei {
+        let cap = U256::from(cap_gwei) * U256::exp10(9);
+        if cap < base_fee {
+            return Err(anyhow!(
+                "Max fee cap of {} gwei is below the current base fee ({} gwei)",
+                cap_gwei,
+                ethers::utils::format_units(base_fee, "gwei")?
+            ));
+        }
+        if max_fee > cap {
+            max_fee = cap;
+            tip = tip.min(cap - base_fee);
+            capped = true;
+        }
+    }
+
+    let gas_limit = gas_estimate + gas_estimate * GAS_LIMIT_MARGIN / 100;
+    Ok(CostEstimate {
+        speed: strategy.speed,
+        gas_estimate,
+        gas_limit,
+        base_fee_per_gas: base_fee.to_string(),
+        max_fee_per_gas: max_fee.to_string(),
+        max_priority_fee_per_gas: tip.to_string(),
+        expected_cost_eth: ethers::utils::format_ether(U256::from(gas_estimate) * (base_fee + tip)),
+        max_cost_eth: ethers::utils::format_ether(U256::from(gas_limit) * max_fee),
+        capped,
+    })
+}
+
+fn result<'a>(response: &'a Value, method: &str) -> Result<&'a Value> {
+    if let Some(error) = response.get("error") {
+        return Err(anyhow!("{} failed: {}", method, error));
+    }
+    response
+        .get("result")
+        .ok_or_else(|| anyhow!("Unexpected {} response: {}", method, response))
+}
+
+/// Hex JSON-RPC quantity
+fn quantity(value: &Value) -> Result<U256> {
+    let hex = value.as_str().ok_or_else(|| anyhow!("Expected a hex quantity, got {}", value))?;
+    Ok(U256::from_str_radix(hex.trim_start_matches("0x"), 16)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_price_follows_speed_and_cap() {
+        // 10 gwei next base fee; tips of 1/2/3 gwei at the 10th/50th/90th percentiles
+        let history = json!({
+            "baseFeePerGas": ["0x1", "0x2540be400"],
+            "reward": [["0x3b9aca00", "0x77359400", "0xb2d05e00"]],
+        });
+        let normal = price(&FeeStrategy::default(), 100_000, &history).unwrap();
+        assert_eq!(normal.gas_limit, 120_000);
+        assert_eq!(normal.max_priority_fee_per_gas, "2000000000");
+        assert_eq!(normal.max_fee_per_gas, "22000000000");
+        assert_eq!(normal.expected_cost_eth, "0.001200000000000000");
+        assert!(!normal.capped);
+
+        let fast = FeeStrategy { speed: FeeSpeed::Fast, max_fee_gwei: Some(12) };
+        let capped = price(&fast, 100_000, &history).unwrap();
+        assert_eq!(capped.max_fee_per_gas, "12000000000");
+        assert_eq!(capped.max_priority_fee_per_gas, "2000000000");
+        assert!(capped.capped);
+
+        let below_base = FeeStrategy { max_fee_gwei: Some(5), ..FeeStrategy::default() };
+        assert!(price(&below_base, 100_000, &history).is_err());
+
+        let tx = normal.apply(Eip1559TransactionRequest::new()).unwrap();
+        assert_eq!(tx.gas, Some(U256::from(120_000)));
+    }
+
+    #[test]
+    fn test_estimate_reverted_reads_rpc_errors() {
+        let anvil = json!({ "code": 3, "message": "execution reverted", "data": "0x09bde339" });
+        assert_eq!(EstimateReverted::from_rpc_error(&anvil).unwrap().data.as_deref(), Some("0x09bde339"));
+
+        let nested = json!({ "code": -32000, "message": "execution reverted", "data": { "data": "0x09bde339" } });
+        assert_eq!(EstimateReverted::from_rpc_error(&nested).unwrap().data.as_deref(), Some("0x09bde339"));
+
+        let bare = json!({ "code": -32000, "message": "execution reverted" });
+        assert!(EstimateReverted::from_rpc_error(&bare).unwrap().data.is_none());
+
+        let funds = json!({ "code": -32000, "message": "insufficient funds for gas * price + value" });
+        assert!(EstimateReverted::from_rpc_error(&funds).is_none());
+    }
+}
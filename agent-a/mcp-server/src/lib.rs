@@ -1,17 +1,46 @@
-/// Agent A MCP Server Library
-/// 
-/// Exposes ZK proof operations as MCP tools:
-/// - verify_on_chain: Verify proofs on Sepolia testnet
-/// - request_attestation: Request attestation from attester service
-/// - format_zk_input: Format input for zkVM
-/// - call_agent_b: Call Agent B pricing/booking endpoints
+//! Agent A MCP Server Library
+//! 
+//! Exposes ZK proof operations as MCP tools:
+//! - verify_on_chain: Verify proofs on Sepolia testnet
+//! - request_attestation: Request attestation from attester service
+//! - format_zk_input: Format input for zkVM
+//! - get_ticket_price / book_flight: Call Agent B pricing/booking endpoints
+//! - attest_price: Price → zk-input → attestation → claim check → on-chain check in one call
+//! - attest_booking: Booking → zk-input → attestation → proof bundle in one call
+//! - attest_refund: Cancellation → zk-input → attestation → refund check in one call
 
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
 use anyhow::Result;
 
 // Re-export from zk-protocol
-pub use zk_protocol::{AttestRequest, AttestResponse, AgentResponse};
+pub use zk_protocol::{
+    AttestJobAccepted, AttestJobId, AttestJobStatus, AttestRequest, AttestResponse, AgentResponse,
+    AgentIdentity, ClaimType, Codec, Envelope, ProofBundle, ProofMode, ProofSystem, ProtocolVersion, ProverMode,
+    PiiCommitment, SignedAgentResponse, ZkProtocolError,
+};
+use zk_protocol::abi::{format_price, BookingClaim, CancellationClaim, PricingClaim, PublicValues, ValuesLayout};
+use zk_protocol::hex_to_bytes;
+
+pub mod activity;
+pub mod approval;
+pub mod audit;
+pub mod booking_state;
+pub mod breaker;
+pub mod chain_fixtures;
+pub mod chain_indexer;
+pub mod crypto_payment;
+pub mod fees;
+pub mod metrics;
+pub mod proof_chain;
+pub mod proof_store;
+pub mod session_bundle;
+pub mod session_guard;
+pub mod tap;
+pub mod tx_queue;
+use breaker::Breakers;
+use telemetry::correlation::WithRequestId;
+use tap::WithTapSignature;
 
 /// Pricing input for Agent B
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
@@ -22,17 +51,120 @@ pub struct PricingInput {
     pub to: String,
     /// VIP status
     pub vip: bool,
+    /// Outbound day, `YYYY-MM-DD`; sets season, weekend, and advance-purchase pricing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub departure_date: Option<String>,
+    /// Return day for a round trip priced in one quote, `YYYY-MM-DD`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_date: Option<String>,
+    /// ISO 4217 code to quote in (USD if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// economy, premium, business, or first (economy if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cabin: Option<String>,
+    /// non_refundable or refundable (non_refundable if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fare_family: Option<String>,
+    /// Agent B promo code; pricing fails if it doesn't apply
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub promo_code: Option<String>,
 }
 
 /// Response from pricing service
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PricingResponse {
-    /// Calculated price
-    pub price: f64,
+    /// Calculated price in `currency`'s minor units (cents; whole yen for JPY)
+    pub price_cents: u64,
+    /// ISO 4217 code of `price_cents`
+    pub currency: String,
+    /// Promo code Agent B applied
+    #[serde(default)]
+    pub promo_code: Option<String>,
+    /// Amount the promo took off `price_cents`
+    #[serde(default)]
+    pub discount_cents: u64,
+    /// Day Agent B priced on (`YYYY-MM-DD`), which sets the advance-purchase tier
+    #[serde(default)]
+    pub purchase_date: Option<String>,
+    /// Quote the price was issued under (None for Agent B builds without quotes)
+    pub quote_id: Option<String>,
+    /// Unix seconds after which the quote must be re-priced
+    pub expires_at: Option<u64>,
+    /// Program ID for attestation
+    pub program_id: String,
+    /// ELF hash for verification
+    pub elf_hash: String,
+    /// did:key of Agent B if the response was signed
+    pub signer: Option<String>,
+}
+
+/// Booking input for Agent B
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct BookingInput {
+    /// Source location
+    pub from: String,
+    /// Destination location
+    pub to: String,
+    /// Passenger full name
+    pub passenger_name: String,
+    /// Passenger email
+    pub passenger_email: String,
+    /// economy, premium, business, or first (economy if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cabin: Option<String>,
+    /// non_refundable or refundable (non_refundable if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fare_family: Option<String>,
+    /// 32-byte hex salt for the passenger commitment (Agent B picks one if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pii_salt: Option<String>,
+}
+
+/// Response from booking service
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BookingResponse {
+    /// Booking reference
+    pub booking_id: String,
+    /// Booking status (e.g. "confirmed")
+    pub status: String,
+    /// Airline confirmation code
+    pub confirmation_code: String,
+    /// Opens `passenger_commitment`; only the passenger should see it
+    pub pii_salt: Option<String>,
+    /// Salted hash of the passenger's name and email, all a booking proof reveals about them
+    pub passenger_commitment: Option<String>,
+    /// Program ID for attestation
+    pub program_id: String,
+    /// ELF hash for verification
+    pub elf_hash: String,
+    /// did:key of Agent B if the response was signed
+    pub signer: Option<String>,
+}
+
+/// Agent B's answer to cancelling a booking
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CancellationResponse {
+    pub booking_id: String,
+    /// "cancelled"
+    pub status: String,
+    /// Fare paid, in `currency`'s minor units
+    pub fare_cents: u64,
+    /// Cancellation fee under pricing-core's rules
+    pub fee_cents: u64,
+    /// `fare_cents - fee_cents`
+    pub refund_cents: u64,
+    pub currency: String,
+    /// YYYY-MM-DD the fee was computed for
+    pub cancelled_on: String,
+    /// `/zk-input` request (`endpoint: "cancel"`) that re-executes the fee calculation
+    pub zk_input: serde_json::Value,
     /// Program ID for attestation
     pub program_id: String,
     /// ELF hash for verification
     pub elf_hash: String,
+    /// did:key of Agent B if the response was signed
+    pub signer: Option<String>,
 }
 
 /// On-chain verification result
@@ -44,6 +176,133 @@ pub struct VerificationResult {
     pub error: Option<String>,
     /// Details from contract call
     pub details: Option<String>,
+    /// Transaction hash (submit mode only)
+    pub tx_hash: Option<String>,
+    /// Block the transaction was included in (submit mode only)
+    pub block_number: Option<u64>,
+    /// Gas consumed by the transaction (submit mode only)
+    pub gas_used: Option<u64>,
+    /// RPC endpoint that served the call
+    pub rpc_endpoint: Option<String>,
+    /// Cost of the verifyProof transaction: what was paid for in submit mode, what a
+    /// submit would cost otherwise
+    pub cost_estimate: Option<fees::CostEstimate>,
+}
+
+// Custom errors of contracts/src/ZeroProof.sol and the SP1 verifier it calls; the
+// selectors RevertReason matches come from these declarations
+ethers::contract::abigen!(
+    ZeroProofContract,
+    r#"[
+        error UnsupportedProofType(bytes32 proofType)
+        error ProofAlreadyVerified(bytes32 proofHash)
+        error VerificationFailed()
+        error InvalidVerifierAddress()
+        error ClaimAlreadyRecorded(bytes32 claimId)
+        error ClaimNotFound(bytes32 claimId)
+        error NotClaimAgent(address agent, address sender)
+    ]"#;
+
+    SP1VerifierContract,
+    r#"[
+        error WrongVerifierSelector(bytes4 received, bytes4 expected)
+        error InvalidProof()
+    ]"#
+);
+
+/// Selector of the standard `Error(string)` revert
+const ERROR_STRING_SELECTOR: &str = "08c379a0";
+
+/// Classified revert from the ZeroProof / SP1 verifier contracts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevertReason {
+    /// Proof's VERIFIER_HASH doesn't match the deployed verifier
+    WrongVerifierSelector,
+    /// Proof failed cryptographic verification
+    InvalidProof,
+    /// No verifier is registered on this chain's ZeroProof for the proof type
+    UnsupportedProofType,
+    /// `verifyProof` already accepted this exact (proof type, proof, claim)
+    ProofAlreadyVerified,
+    /// The verifier rejected the proof without a reason of its own
+    VerificationFailed,
+    /// A verifier was registered at the zero address
+    InvalidVerifierAddress,
+    /// `recordClaim` already stored this claim
+    ClaimAlreadyRecorded,
+    /// `getClaim` found nothing under the claim id
+    ClaimNotFound,
+    /// `recordClaim` was sent by someone other than the claim's agent
+    NotClaimAgent,
+    /// Any other revert: `Error(string)` message or raw selector
+    Custom(String),
+}
+
+impl RevertReason {
+    /// Classify hex-encoded revert data by its 4-byte selector
+    pub fn from_revert_data(data: &str) -> Self {
+        use ethers::contract::EthError;
+
+        fn selector_of<E: EthError>() -> String {
+            hex::encode(E::selector())
+        }
+
+        let data = data.trim_start_matches("0x").to_ascii_lowercase();
+        let selector = data.get(..8).unwrap_or(&data);
+
+        match selector {
+            s if s == selector_of::<WrongVerifierSelector>() => Self::WrongVerifierSelector,
+            s if s == selector_of::<InvalidProof>() => Self::InvalidProof,
+            s if s == selector_of::<UnsupportedProofType>() => Self::UnsupportedProofType,
+            s if s == selector_of::<ProofAlreadyVerified>() => Self::ProofAlreadyVerified,
+            s if s == selector_of::<VerificationFailed>() => Self::VerificationFailed,
+            s if s == selector_of::<InvalidVerifierAddress>() => Self::InvalidVerifierAddress,
+            s if s == selector_of::<ClaimAlreadyRecorded>() => Self::ClaimAlreadyRecorded,
+            s if s == selector_of::<ClaimNotFound>() => Self::ClaimNotFound,
+            s if s == selector_of::<NotClaimAgent>() => Self::NotClaimAgent,
+            ERROR_STRING_SELECTOR => Self::Custom(
+                decode_error_string(&data[8..]).unwrap_or_else(|| format!("0x{}", data)),
+            ),
+            "" => Self::Custom("empty revert data".to_string()),
+            s => Self::Custom(format!("unrecognized selector 0x{}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongVerifierSelector => {
+                write!(f, "WrongVerifierSelector: proof's VERIFIER_HASH doesn't match contract")
+            }
+            Self::InvalidProof => write!(f, "InvalidProof: proof verification failed"),
+            Self::UnsupportedProofType => write!(
+                f,
+                "UnsupportedProofType: no verifier registered for this proof type (e.g. sp1-plonk) on this chain"
+            ),
+            Self::ProofAlreadyVerified => {
+                write!(f, "ProofAlreadyVerified: this proof and claim were already verified on-chain")
+            }
+            Self::VerificationFailed => write!(f, "VerificationFailed: the verifier rejected the proof"),
+            Self::InvalidVerifierAddress => write!(f, "InvalidVerifierAddress: verifier address is zero"),
+            Self::ClaimAlreadyRecorded => write!(f, "ClaimAlreadyRecorded: this claim is already recorded"),
+            Self::ClaimNotFound => write!(f, "ClaimNotFound: no claim recorded under this id"),
+            Self::NotClaimAgent => {
+                write!(f, "NotClaimAgent: only the claim's agent can record it")
+            }
+            Self::Custom(reason) => write!(f, "Reverted: {}", reason),
+        }
+    }
+}
+
+/// Decode the message of an `Error(string)` payload (hex, selector stripped)
+fn decode_error_string(payload_hex: &str) -> Option<String> {
+    let bytes = hex::decode(payload_hex).ok()?;
+    let decoded = zk_protocol::abi::decode(&bytes, &[zk_protocol::abi::AbiType::String]).ok()?;
+    match decoded.into_iter().next()? {
+        zk_protocol::abi::AbiValue::String(message) => Some(message),
+        _ => None,
+    }
 }
 
 /// Attestation request parameters
@@ -59,6 +318,168 @@ pub struct AttestationParams {
     pub verify_locally: bool,
 }
 
+/// Consolidated result of the attest_price pipeline
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AttestPriceResult {
+    /// Price quoted by Agent B, in `currency`'s minor units
+    pub price_cents: u64,
+    /// ISO 4217 code of `price_cents`
+    pub currency: String,
+    /// Quote the proof is bound to
+    pub quote_id: Option<String>,
+    /// Unix seconds after which the quote must be re-priced
+    pub expires_at: Option<u64>,
+    /// Program ID the proof was generated for
+    pub program_id: String,
+    /// ELF hash advertised by Agent B
+    pub elf_hash: String,
+    /// Hex-encoded proof
+    pub proof: String,
+    /// Hex-encoded public values
+    pub public_values: String,
+    /// VK hash for on-chain verification
+    pub vk_hash: String,
+    /// SNARK wrapper of `proof`
+    #[serde(default)]
+    #[schemars(with = "String")]
+    pub proof_mode: ProofMode,
+    /// On-chain verification result (None if not requested)
+    pub verified: Option<bool>,
+}
+
+impl AttestPriceResult {
+    /// Portable bundle of this result's proof
+    pub fn proof_bundle(&self) -> Result<ProofBundle> {
+        Ok(ProofBundle {
+            proof: hex_to_bytes(&self.proof)?,
+            public_values: hex_to_bytes(&self.public_values)?,
+            vk_hash: self.vk_hash.clone(),
+            program_id: self.program_id.clone(),
+            elf_hash: self.elf_hash.clone(),
+            proof_mode: self.proof_mode,
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Consolidated result of the attest_booking pipeline
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AttestBookingResult {
+    /// Booking reference from Agent B
+    pub booking_id: String,
+    /// Booking status from Agent B
+    pub status: String,
+    /// Airline confirmation code from Agent B
+    pub confirmation_code: String,
+    /// With the passenger's name and email, opens `passenger_commitment`; keep it private
+    pub pii_salt: String,
+    /// Passenger commitment in the proven claim
+    pub passenger_commitment: String,
+    /// Proof, public values, and VK hash for the booking
+    #[schemars(with = "serde_json::Value")]
+    pub proof_bundle: ProofBundle,
+    /// On-chain verification result (None if not requested)
+    pub verified: Option<bool>,
+}
+
+/// Consolidated result of the attest_refund pipeline
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AttestRefundResult {
+    pub booking_id: String,
+    pub fare_cents: u64,
+    pub fee_cents: u64,
+    /// Amount owed back to the payer, as proven
+    pub refund_cents: u64,
+    pub currency: String,
+    pub cancelled_on: String,
+    /// Proof, public values, and VK hash for the cancellation
+    #[schemars(with = "serde_json::Value")]
+    pub proof_bundle: ProofBundle,
+    /// On-chain verification result (None if not requested)
+    pub verified: Option<bool>,
+}
+
+/// Options for Agent B to price side by side
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct FlightOptionsInput {
+    /// Each priced as by [`get_ticket_price`]; all in the same currency
+    pub options: Vec<PricingInput>,
+}
+
+/// One row of Agent B's comparison
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct FlightOption {
+    pub from: String,
+    pub to: String,
+    pub departure_date: Option<String>,
+    pub return_date: Option<String>,
+    /// None if Agent B couldn't price this option (see `error`)
+    pub price_cents: Option<u64>,
+    pub promo_code: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Response from Agent B's /price/batch
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FlightOptionsResponse {
+    /// In request order
+    pub options: Vec<FlightOption>,
+    /// ISO 4217 code of every price in `options`
+    pub currency: String,
+    /// Index into `options` of the lowest price
+    pub cheapest: usize,
+    /// Day Agent B priced on (`YYYY-MM-DD`)
+    #[serde(default)]
+    pub purchase_date: Option<String>,
+    /// `{endpoint, input}` to send to /zk-input to prove the comparison
+    #[serde(default)]
+    pub zk_input: serde_json::Value,
+    /// Program ID for attestation
+    pub program_id: String,
+    /// ELF hash for verification
+    pub elf_hash: String,
+    /// did:key of Agent B if the response was signed
+    #[serde(default)]
+    pub signer: Option<String>,
+}
+
+impl FlightOptionsResponse {
+    pub fn cheapest_option(&self) -> Option<&FlightOption> {
+        self.options.get(self.cheapest)
+    }
+}
+
+/// Consolidated result of the attest_cheapest_option pipeline
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AttestCheapestResult {
+    /// Every option Agent B compared, in request order
+    pub options: Vec<FlightOption>,
+    /// ISO 4217 code of every price in `options`
+    pub currency: String,
+    /// Index into `options` of the proven cheapest
+    pub cheapest: usize,
+    /// Proof, public values, and VK hash for the whole comparison
+    #[schemars(with = "serde_json::Value")]
+    pub proof_bundle: ProofBundle,
+    /// On-chain verification result (None if not requested)
+    pub verified: Option<bool>,
+}
+
+/// Service endpoints used by multi-step pipelines
+pub struct PipelineEndpoints<'a> {
+    pub agent_b_url: &'a str,
+    pub attester_url: &'a str,
+    pub zeroproof_addr: &'a str,
+    pub rpc: &'a RpcPool,
+    /// Expected Agent B signer (did:key), if pinned
+    pub agent_b_did: Option<&'a str>,
+    /// Per-subsystem circuit breakers
+    pub breakers: &'a Breakers,
+}
+
 /// ZK input formatting parameters
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ZkInputParams {
@@ -75,30 +496,331 @@ pub struct ZkInputResult {
     pub input_bytes: String,
     /// Input as array of u8 for verification
     pub input_array: Vec<u8>,
+    /// Agent B program that proves this input, and its program_id (unset from older Agent B
+    /// servers); the pipelines prove against the program_id signed into Agent B's response
+    #[serde(default)]
+    pub program: Option<String>,
+    #[serde(default)]
+    pub program_id: Option<String>,
 }
 
-/// Verifies proof on-chain with Sepolia ZeroProof contract
-pub async fn verify_on_chain(
-    zeroproof_addr: &str,
-    rpc_url: &str,
+/// Pool of JSON-RPC endpoints with round-robin selection and retry/backoff
+///
+/// Transient failures (network errors, HTTP 429/5xx, rate-limit JSON-RPC codes) move on
+/// to the next endpoint after an exponentially growing delay. Contract reverts are
+/// returned as-is - retrying them elsewhere would not change the answer.
+pub struct RpcPool {
+    urls: Vec<String>,
+    next: std::sync::atomic::AtomicUsize,
+    max_attempts: u32,
+    base_backoff: std::time::Duration,
+}
+
+/// JSON-RPC response plus the endpoint that produced it
+#[derive(Debug)]
+pub struct RpcReply {
+    pub endpoint: String,
+    pub response: serde_json::Value,
+}
+
+impl RpcPool {
+    pub fn new(urls: Vec<String>) -> Result<Self> {
+        if urls.is_empty() {
+            return Err(anyhow::anyhow!("RpcPool needs at least one RPC URL"));
+        }
+        Ok(Self {
+            max_attempts: (urls.len() as u32).max(3),
+            urls,
+            next: std::sync::atomic::AtomicUsize::new(0),
+            base_backoff: std::time::Duration::from_millis(250),
+        })
+    }
+
+    /// Parse a comma-separated list of URLs (e.g. RPC_URLS)
+    pub fn from_list(list: &str) -> Result<Self> {
+        Self::new(
+            list.split(',')
+                .map(|u| u.trim().to_string())
+                .filter(|u| !u.is_empty())
+                .collect(),
+        )
+    }
+
+    pub fn with_retry(mut self, max_attempts: u32, base_backoff: std::time::Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.base_backoff = base_backoff;
+        self
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Next endpoint in round-robin order
+    pub fn next_url(&self) -> &str {
+        let i = self.next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        &self.urls[i % self.urls.len()]
+    }
+
+    /// Send a JSON-RPC request, failing over between endpoints on transient errors
+    pub async fn call(
+        &self,
+        client: &reqwest::Client,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<RpcReply> {
+        let payload = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1,
+        });
+
+        let mut last_error = None;
+        for attempt in 0..self.max_attempts {
+            if attempt > 0 {
+                let delay = self.base_backoff * 2u32.saturating_pow(attempt - 1);
+                tokio::time::sleep(delay).await;
+            }
+
+            let endpoint = self.next_url().to_string();
+            match client.post(&endpoint).json(&payload).send().await {
+                Ok(resp) if resp.status().as_u16() == 429 || resp.status().is_server_error() => {
+                    tracing::warn!("⚠ RPC {} returned HTTP {} (attempt {})", endpoint, resp.status(), attempt + 1);
+                    last_error = Some(anyhow::anyhow!("{} returned HTTP {}", endpoint, resp.status()));
+                }
+                Ok(resp) => match resp.json::<serde_json::Value>().await {
+                    Ok(response) => {
+                        if let Some(error) = response.get("error").filter(|e| is_transient_rpc_error(e)) {
+                            tracing::warn!("⚠ RPC {} transient error {} (attempt {})", endpoint, error, attempt + 1);
+                            last_error = Some(anyhow::anyhow!("{}: {}", endpoint, error));
+                            continue;
+                        }
+                        tracing::debug!("RPC {} served {}", endpoint, method);
+                        return Ok(RpcReply { endpoint, response });
+                    }
+                    Err(e) => {
+                        tracing::warn!("⚠ RPC {} sent invalid JSON: {} (attempt {})", endpoint, e, attempt + 1);
+                        last_error = Some(e.into());
+                    }
+                },
+                Err(e) => {
+                    tracing::warn!("⚠ RPC {} unreachable: {} (attempt {})", endpoint, e, attempt + 1);
+                    last_error = Some(e.into());
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No RPC endpoints configured")))
+    }
+}
+
+/// JSON-RPC errors worth retrying on another endpoint (rate limits, node unavailable)
+fn is_transient_rpc_error(error: &serde_json::Value) -> bool {
+    let code = error.get("code").and_then(|c| c.as_i64()).unwrap_or(0);
+    let message = error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .unwrap_or("")
+        .to_lowercase();
+    matches!(code, -32005 | -32002 | -32603) && !message.contains("revert")
+}
+
+/// Builder for the `Claim` tuple passed to `ZeroProof.verifyProof`
+///
+/// Defaults to the zero agent address and the `pricing` claim type; public data
+/// falls back to the proof's public values and the data hash to keccak256(public data).
+#[derive(Debug, Clone)]
+pub struct ClaimBuilder {
+    agent: ethers::types::Address,
+    claim_type: [u8; 32],
+    public_data: Option<Vec<u8>>,
+    data_hash: Option<[u8; 32]>,
+}
+
+impl Default for ClaimBuilder {
+    fn default() -> Self {
+        Self {
+            agent: ethers::types::Address::zero(),
+            claim_type: ClaimType::Pricing.as_bytes32(),
+            public_data: None,
+            data_hash: None,
+        }
+    }
+}
+
+impl ClaimBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn agent(mut self, agent: ethers::types::Address) -> Self {
+        self.agent = agent;
+        self
+    }
+
+    /// `agent`, unless one was set
+    pub fn default_agent(mut self, agent: ethers::types::Address) -> Self {
+        if self.agent.is_zero() {
+            self.agent = agent;
+        }
+        self
+    }
+
+    pub fn claim_type(mut self, claim_type: ClaimType) -> Self {
+        self.claim_type = claim_type.as_bytes32();
+        self
+    }
+
+    /// Raw `claimType` for types not covered by [`ClaimType`]
+    pub fn claim_type_raw(mut self, claim_type: [u8; 32]) -> Self {
+        self.claim_type = claim_type;
+        self
+    }
+
+    pub fn public_data(mut self, public_data: Vec<u8>) -> Self {
+        self.public_data = Some(public_data);
+        self
+    }
+
+    pub fn data_hash(mut self, data_hash: [u8; 32]) -> Self {
+        self.data_hash = Some(data_hash);
+        self
+    }
+
+    /// Public data and data hash, with the defaults applied
+    fn resolve(&self, public_values: &[u8]) -> (Vec<u8>, [u8; 32]) {
+        let public_data = self.public_data.clone().unwrap_or_else(|| public_values.to_vec());
+        let data_hash = self
+            .data_hash
+            .unwrap_or_else(|| ethers::core::utils::keccak256(&public_data));
+        (public_data, data_hash)
+    }
+
+    /// ABI token `(address agent, bytes32 claimType, bytes publicData, bytes32 dataHash)`
+    pub fn build(&self, public_values: &[u8]) -> ethers::abi::Token {
+        use ethers::abi::Token;
+
+        let (public_data, data_hash) = self.resolve(public_values);
+
+        Token::Tuple(vec![
+            Token::Address(self.agent),
+            Token::FixedBytes(self.claim_type.to_vec()),
+            Token::Bytes(public_data),
+            Token::FixedBytes(data_hash.to_vec()),
+        ])
+    }
+
+    /// `ZeroProof.claimIdOf(agent, claimType, dataHash)`: where `recordClaim` stores this claim
+    pub fn claim_id(&self, public_values: &[u8]) -> [u8; 32] {
+        use ethers::abi::Token;
+
+        let (_, data_hash) = self.resolve(public_values);
+        ethers::core::utils::keccak256(ethers::abi::encode(&[
+            Token::Address(self.agent),
+            Token::FixedBytes(self.claim_type.to_vec()),
+            Token::FixedBytes(data_hash.to_vec()),
+        ]))
+    }
+}
+
+/// Optional claim overrides accepted by the `verify_on_chain` tool
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ClaimArgs {
+    /// Agent address (0x-prefixed; defaults to the zero address, or the signer when recording)
+    #[serde(default)]
+    pub agent_address: Option<String>,
+    /// "pricing", "booking", "payment", or a 0x-prefixed bytes32
+    #[serde(default)]
+    pub claim_type: Option<String>,
+    /// Hex public data (defaults to the proof's public values)
+    #[serde(default)]
+    pub public_data: Option<String>,
+    /// Hex bytes32 data hash (defaults to keccak256(public data))
+    #[serde(default)]
+    pub data_hash: Option<String>,
+}
+
+impl ClaimArgs {
+    pub fn into_builder(self) -> Result<ClaimBuilder> {
+        let mut builder = ClaimBuilder::new();
+
+        if let Some(agent) = self.agent_address {
+            builder = builder.agent(agent.parse()?);
+        }
+        if let Some(claim_type) = self.claim_type {
+            builder = match claim_type.as_str() {
+                "pricing" => builder.claim_type(ClaimType::Pricing),
+                "booking" => builder.claim_type(ClaimType::Booking),
+                "payment" => builder.claim_type(ClaimType::Payment),
+                raw => builder.claim_type_raw(bytes32_from_hex(raw, "claim_type")?),
+            };
+        }
+        if let Some(public_data) = self.public_data {
+            builder = builder.public_data(hex_to_bytes(&public_data)?);
+        }
+        if let Some(data_hash) = self.data_hash {
+            builder = builder.data_hash(bytes32_from_hex(&data_hash, "data_hash")?);
+        }
+
+        Ok(builder)
+    }
+}
+
+fn bytes32_from_hex(value: &str, field: &str) -> Result<[u8; 32]> {
+    let bytes = hex_to_bytes(value)?;
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| anyhow::anyhow!("{} must be 32 bytes, got {}", field, b.len()))
+}
+
+/// Encode calldata for ZeroProof.verifyProof(bytes32,bytes,(address,bytes32,bytes,bytes32))
+pub fn encode_verify_proof_call(
     proof_hex: &str,
     public_values_hex: &str,
     vk_hash: &str,
-) -> Result<bool> {
-    tracing::info!("→ Verifying proof on-chain with ZeroProof at {}", zeroproof_addr);
-    
+    claim: &ClaimBuilder,
+) -> Result<Vec<u8>> {
+    encode_proof_call("verifyProof", proof_hex, public_values_hex, vk_hash, claim)
+}
+
+/// Encode calldata for ZeroProof.recordClaim, which takes the same arguments as verifyProof
+pub fn encode_record_claim_call(
+    proof_hex: &str,
+    public_values_hex: &str,
+    vk_hash: &str,
+    claim: &ClaimBuilder,
+) -> Result<Vec<u8>> {
+    encode_proof_call("recordClaim", proof_hex, public_values_hex, vk_hash, claim)
+}
+
+/// Calldata for `function(bytes32,bytes,(address,bytes32,bytes,bytes32))` with an SP1 proof
+///
+/// The proof type follows the proof's verifier-hash prefix: PLONK proofs go to the
+/// `sp1-plonk` verifier registered on ZeroProof, everything else to `sp1-zkvm` (Groth16).
+fn encode_proof_call(
+    function: &str,
+    proof_hex: &str,
+    public_values_hex: &str,
+    vk_hash: &str,
+    claim: &ClaimBuilder,
+) -> Result<Vec<u8>> {
     // Decode proof, public values, and VK hash
-    let proof_bytes = hex::decode(proof_hex.strip_prefix("0x").unwrap_or(proof_hex))?;
-    let public_values_bytes = hex::decode(public_values_hex.strip_prefix("0x").unwrap_or(public_values_hex))?;
-    let vk_hash_bytes = hex::decode(vk_hash.strip_prefix("0x").unwrap_or(vk_hash))?;
-    
+    let proof_bytes = hex_to_bytes(proof_hex)?;
+    let public_values_bytes = hex_to_bytes(public_values_hex)?;
+    let vk_hash_bytes = hex_to_bytes(vk_hash)?;
+
+    if proof_bytes.is_empty() {
+        return Err(anyhow::anyhow!("{}", MOCK_PROOF_ERROR));
+    }
     if vk_hash_bytes.len() != 32 {
         return Err(anyhow::anyhow!("VK hash must be 32 bytes, got {}", vk_hash_bytes.len()));
     }
     
     // Build ZeroProof.verifyProof(bytes32 proofType, bytes calldata proof, Claim calldata claim)
-    // For SP1 proofs: proofType = keccak256("sp1-zkvm")
-    let proof_type = ethers::core::utils::keccak256(b"sp1-zkvm");
+    // For SP1 proofs: proofType = keccak256("sp1-zkvm"), or keccak256("sp1-plonk") for PLONK
+    let proof_system = ProofMode::from_proof(&proof_bytes).unwrap_or_default().proof_system();
+    let proof_type = proof_system.as_bytes32();
     
     // SP1 proof format: encode(vkey, publicValues, proofBytes)
     let sp1_proof = {
@@ -109,151 +831,907 @@ pub async fn verify_on_chain(
     };
     
     // Claim structure: (address agent, bytes32 claimType, bytes publicData, bytes32 dataHash)
-    let claim = {
-        let agent = ethers::abi::Token::Address(ethers::types::Address::zero());
-        let claim_type = ethers::abi::Token::FixedBytes(ethers::core::utils::keccak256(b"pricing").to_vec());
-        let public_data = ethers::abi::Token::Bytes(public_values_bytes.clone());
-        let data_hash = ethers::abi::Token::FixedBytes(ethers::core::utils::keccak256(&public_values_bytes).to_vec());
-        ethers::abi::Token::Tuple(vec![agent, claim_type, public_data, data_hash])
-    };
+    let claim = claim.build(&public_values_bytes);
     
-    // Encode function call: verifyProof(bytes32,bytes,(address,bytes32,bytes,bytes32))
+    // Encode function call: <function>(bytes32,bytes,(address,bytes32,bytes,bytes32))
     let proof_type_token = ethers::abi::Token::FixedBytes(proof_type.to_vec());
     let proof_token = ethers::abi::Token::Bytes(sp1_proof);
     let encoded = ethers::abi::encode(&[proof_type_token, proof_token, claim]);
 
-    // Function selector for verifyProof(bytes32,bytes,(address,bytes32,bytes,bytes32))
-    let fn_selector = &ethers::core::utils::keccak256(b"verifyProof(bytes32,bytes,(address,bytes32,bytes,bytes32))")[..4];
+    let signature = format!("{}(bytes32,bytes,(address,bytes32,bytes,bytes32))", function);
+    let fn_selector = &ethers::core::utils::keccak256(signature.as_bytes())[..4];
     let mut call_data = fn_selector.to_vec();
     call_data.extend(encoded);
-    let call_data_hex = format!("0x{}", hex::encode(&call_data));
 
-    tracing::debug!("Proof Type: sp1-zkvm ({})", hex::encode(&proof_type));
+    tracing::debug!("Proof Type: {} ({})", proof_system, hex::encode(proof_type));
     tracing::debug!("VK Hash: {}", vk_hash);
     tracing::debug!("Public Values ({} bytes)", public_values_hex.len() / 2);
 
+    Ok(call_data)
+}
+
+/// Verifies proof on-chain with Sepolia ZeroProof contract (eth_call, nothing is recorded)
+pub async fn verify_on_chain(
+    client: &reqwest::Client,
+    zeroproof_addr: &str,
+    rpc: &RpcPool,
+    proof_hex: &str,
+    public_values_hex: &str,
+    vk_hash: &str,
+    claim: &ClaimBuilder,
+) -> Result<VerificationResult> {
+    tracing::info!("→ Verifying proof on-chain with ZeroProof at {}", zeroproof_addr);
+
+    let call_data = encode_verify_proof_call(proof_hex, public_values_hex, vk_hash, claim)?;
+    let call_data_hex = format!("0x{}", hex::encode(&call_data));
+
     // Use JSON-RPC eth_call to ZeroProof contract
-    let payload = serde_json::json!({
-        "jsonrpc": "2.0",
-        "method": "eth_call",
-        "params": [
-            {
-                "to": zeroproof_addr,
-                "data": call_data_hex,
-            },
-            "latest"
-        ],
-        "id": 1,
-    });
-
-    let client = reqwest::Client::new();
-    let response: serde_json::Value = client
-        .post(rpc_url)
-        .json(&payload)
-        .send()
-        .await?
-        .json()
-        .await?;
+    let params = serde_json::json!([
+        {
+            "to": zeroproof_addr,
+            "data": call_data_hex,
+        },
+        "latest"
+    ]);
+
+    let RpcReply { endpoint, response } = rpc.call(client, "eth_call", params).await?;
+
+    let mut result = VerificationResult {
+        verified: false,
+        error: None,
+        details: None,
+        tx_hash: None,
+        block_number: None,
+        gas_used: None,
+        rpc_endpoint: Some(endpoint),
+        cost_estimate: None,
+    };
 
     if let Some(error) = response.get("error") {
         tracing::error!("✗ On-chain verification FAILED (contract reverted): {}", error);
-        Ok(false)
+        result.error = Some(error.to_string());
+
+        // Try to decode the revert reason
+        if let Some(data) = error.get("data").and_then(|v| v.as_str()) {
+            let reason = RevertReason::from_revert_data(data);
+            tracing::error!("  → {}", reason);
+            result.details = Some(reason.to_string());
+        }
     } else if response.get("result").and_then(|v| v.as_str()).is_some() {
         // If eth_call succeeds, verifyProof() didn't revert = proof is valid
         tracing::info!("✓ On-chain verification result: valid");
-        Ok(true)
+        result.verified = true;
     } else {
         tracing::warn!("⚠ Unexpected JSON-RPC response: {}", response);
-        Ok(false)
+        result.error = Some(format!("Unexpected JSON-RPC response: {}", response));
     }
+
+    Ok(result)
 }
 
-/// Call Agent B to get pricing and program info
-pub async fn get_ticket_price(
-    agent_b_url: &str,
-    input: &PricingInput,
-) -> Result<PricingResponse> {
-    tracing::info!("→ Calling Agent B at {}", agent_b_url);
-    
-    let client = reqwest::Client::new();
-    let response_json = client
-        .post(&format!("{}/price", agent_b_url))
-        .json(&serde_json::json!({
-            "from": input.from,
-            "to": input.to,
-            "vip": input.vip
-        }))
-        .send()
-        .await?
-        .json::<serde_json::Value>()
-        .await?;
+/// Verifies proof on-chain by sending a real transaction (recorded on-chain)
+///
+/// Sent through `queue` (which owns the signing wallet's nonce) under `label`, priced
+/// with `fees` (see [`fees::estimate`]). Waits for the receipt and reports tx hash, block
+/// number, gas used, and the estimate it was sent with. A proof that reverts in the gas
+/// estimate isn't sent; it comes back `verified: false` with the decoded [`RevertReason`].
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_on_chain(
+    client: &reqwest::Client,
+    zeroproof_addr: &str,
+    rpc: &RpcPool,
+    queue: &tx_queue::TxQueue,
+    label: Option<&str>,
+    fees: &fees::FeeStrategy,
+    proof_hex: &str,
+    public_values_hex: &str,
+    vk_hash: &str,
+    claim: &ClaimBuilder,
+) -> Result<VerificationResult> {
+    use ethers::types::{Address, Eip1559TransactionRequest};
 
-    // Extract fields directly from response JSON
-    let price = response_json
-        .get("price")
-        .and_then(|p| p.as_f64())
-        .unwrap_or(0.0);
-    
-    let program_id = response_json
-        .get("program_id")
-        .and_then(|p| p.as_str())
-        .unwrap_or("")
-        .to_string();
-    
-    let elf_hash = response_json
-        .get("elf_hash")
-        .and_then(|e| e.as_str())
-        .unwrap_or("")
-        .to_string();
+    tracing::info!("→ Submitting verifyProof transaction to ZeroProof at {}", zeroproof_addr);
 
-    tracing::info!("✓ Agent B response: price={}, program_id={}", price, program_id);
+    let call_data = encode_verify_proof_call(proof_hex, public_values_hex, vk_hash, claim)?;
 
-    Ok(PricingResponse {
-        price,
-        program_id,
-        elf_hash,
+    let from = format!("{:#x}", queue.address());
+    let cost = match fees::estimate(client, rpc, Some(&from), zeroproof_addr, &call_data, fees).await {
+        Ok(cost) => cost,
+        // The proof would revert on-chain: report it like a failed eth_call, without sending
+        Err(e) => {
+            let reverted = e.downcast::<fees::EstimateReverted>()?;
+            tracing::error!("✗ verifyProof would revert, not sending: {}", reverted.message);
+            let reason = reverted.data.as_deref().map(RevertReason::from_revert_data);
+            if let Some(reason) = &reason {
+                tracing::error!("  → {}", reason);
+            }
+            return Ok(VerificationResult {
+                verified: false,
+                error: Some(reverted.to_string()),
+                details: reason.map(|r| r.to_string()),
+                tx_hash: None,
+                block_number: None,
+                gas_used: None,
+                rpc_endpoint: None,
+                cost_estimate: None,
+            });
+        }
+    };
+    tracing::info!("  Estimated gas {} at up to {} ETH ({:?})", cost.gas_estimate, cost.max_cost_eth, cost.speed);
+
+    let to: Address = zeroproof_addr.parse()?;
+    let tx = cost.apply(Eip1559TransactionRequest::new().to(to).data(call_data))?;
+
+    let receipt = queue.send(label, tx).await?;
+    let tx_hash = format!("{:#x}", receipt.transaction_hash);
+
+    let verified = receipt.status.map(|s| s.as_u64() == 1).unwrap_or(false);
+    let block_number = receipt.block_number.map(|b| b.as_u64());
+    let gas_used = receipt.gas_used.map(|g| g.as_u64());
+
+    if verified {
+        tracing::info!("✓ Proof verified on-chain in block {:?} (gas used: {:?})", block_number, gas_used);
+    } else {
+        tracing::error!("✗ verifyProof transaction reverted: {}", tx_hash);
+    }
+
+    Ok(VerificationResult {
+        verified,
+        error: if verified { None } else { Some("Transaction reverted".to_string()) },
+        details: None,
+        tx_hash: Some(tx_hash),
+        block_number,
+        gas_used,
+        rpc_endpoint: Some(queue.rpc_url().to_string()),
+        cost_estimate: Some(cost),
     })
 }
 
-/// Get ZK input formatting from Agent B
-pub async fn format_zk_input(
-    agent_b_url: &str,
-    endpoint: &str,
-    input: &serde_json::Value,
-) -> Result<ZkInputResult> {
-    tracing::info!("→ Getting ZK input format from Agent B");
-    
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&format!("{}/zk-input", agent_b_url))
-        .json(&serde_json::json!({
-            "endpoint": endpoint,
-            "input": input
-        }))
-        .send()
-        .await?
-        .json::<serde_json::Value>()
-        .await?;
-    
-    let input_array: Vec<u8> = response["input_bytes"]
-        .as_array()
-        .ok_or_else(|| anyhow::anyhow!("Missing input_bytes in response"))?
-        .iter()
-        .filter_map(|v| v.as_u64().map(|n| n as u8))
-        .collect();
+/// Result of [`verify_offchain`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OffchainVerification {
+    pub verified: bool,
+    /// "groth16" or "plonk", read from the proof's verifier-hash prefix
+    pub proof_mode: String,
+    pub vk_hash: String,
+    /// Why the proof was rejected
+    pub error: Option<String>,
+}
 
-    let input_hex = format!("0x{}", hex::encode(&input_array));
-    
-    tracing::info!("✓ ZK input formatted: {} bytes", input_array.len());
+/// Check a proof with the pure-Rust SP1 verifier, without any RPC endpoint
+///
+/// Runs the same Groth16 / PLONK pairing checks as the on-chain verifier against the
+/// bundle's own VK hash, so an exported bundle can be audited offline and a proof can
+/// be checked before gas is paid for it. CPU-bound (tens of milliseconds); call it from
+/// a blocking task. The mode follows the proof's prefix over the bundle's `proof_mode`.
+pub fn verify_offchain(bundle: &ProofBundle) -> OffchainVerification {
+    let mut bundle = bundle.clone();
+    bundle.proof_mode = ProofMode::from_proof(&bundle.proof).unwrap_or(bundle.proof_mode);
+    let proof_mode = match bundle.proof_mode {
+        ProofMode::Groth16 => "groth16",
+        ProofMode::Plonk => "plonk",
+    };
 
-    Ok(ZkInputResult {
-        input_bytes: input_hex,
-        input_array,
-    })
+    let error = if bundle.proof.is_empty() {
+        Some("Bundle has no proof bytes (mock proof)".to_string())
+    } else if bundle.proof.len() < 4 {
+        Some("Proof is shorter than its 4-byte verifier-hash prefix".to_string())
+    } else {
+        bundle
+            .verify_locally(zk_protocol::ElfOrVk::VkHash(&bundle.vk_hash))
+            .err()
+            .map(|e| e.to_string())
+    };
+    match &error {
+        None => tracing::info!("✓ Off-chain verification passed ({})", proof_mode),
+        Some(e) => tracing::warn!("✗ Off-chain verification failed ({}): {}", proof_mode, e),
+    }
+
+    OffchainVerification {
+        verified: error.is_none(),
+        proof_mode: proof_mode.to_string(),
+        vk_hash: bundle.vk_hash,
+        error,
+    }
 }
 
-/// Request attestation from attester service
+/// [`verify_offchain`] for hex proof, public values, and VK hash as taken by `verify_on_chain`
+pub fn verify_offchain_hex(proof_hex: &str, public_values_hex: &str, vk_hash: &str) -> Result<OffchainVerification> {
+    let proof = hex_to_bytes(proof_hex)?;
+    let bundle = ProofBundle {
+        proof_mode: ProofMode::from_proof(&proof).unwrap_or_default(),
+        proof,
+        public_values: hex_to_bytes(public_values_hex)?,
+        vk_hash: vk_hash.to_string(),
+        program_id: String::new(),
+        elf_hash: String::new(),
+        created_at: 0,
+    };
+    Ok(verify_offchain(&bundle))
+}
+
+/// Multicall3, deployed at this address on Sepolia, mainnet, and most other chains
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// One proof of a batch for [`verify_batch_on_chain`] / [`submit_batch_on_chain`]
+#[derive(Debug, Clone)]
+pub struct BatchProof {
+    /// Caller's id for the proof, echoed in its result
+    pub id: String,
+    pub proof_hex: String,
+    pub public_values_hex: String,
+    pub vk_hash: String,
+    pub claim: ClaimBuilder,
+}
+
+/// Result for one proof of a batch
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct BatchVerification {
+    pub id: String,
+    #[serde(flatten)]
+    pub result: VerificationResult,
+}
+
+/// Every proof of a session checked in one batch
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SessionVerification {
+    pub session_id: String,
+    /// Proofs that passed
+    pub verified: usize,
+    /// Proofs that reverted or could not be encoded
+    pub failed: usize,
+    /// Ids of proofs the contract can't check (mock proofs without proof bytes)
+    pub skipped: Vec<String>,
+    pub results: Vec<BatchVerification>,
+}
+
+/// Verify several proofs with one eth_call through Multicall3 `aggregate3`
+///
+/// Each `verifyProof` call may fail on its own, so one bad proof doesn't hide the
+/// results of the others. Results come back in the order of `proofs`. On a chain
+/// without Multicall3 at `multicall_addr` (a bare Anvil node), the proofs are
+/// verified one call at a time instead.
+pub async fn verify_batch_on_chain(
+    client: &reqwest::Client,
+    zeroproof_addr: &str,
+    multicall_addr: &str,
+    rpc: &RpcPool,
+    proofs: &[BatchProof],
+) -> Result<Vec<BatchVerification>> {
+    tracing::info!("→ Verifying {} proofs on-chain via Multicall3 at {}", proofs.len(), multicall_addr);
+
+    let mut results = Vec::with_capacity(proofs.len());
+    let mut calls = Vec::new();
+    for proof in proofs {
+        match encode_verify_proof_call(&proof.proof_hex, &proof.public_values_hex, &proof.vk_hash, &proof.claim) {
+            Ok(call_data) => calls.push((results.len(), call_data)),
+            Err(e) => tracing::warn!("  Proof {} not encodable: {}", proof.id, e),
+        }
+        results.push(BatchVerification {
+            id: proof.id.clone(),
+            result: unverified(Some("Could not encode verifyProof call".to_string()), None),
+        });
+    }
+    if calls.is_empty() {
+        return Ok(results);
+    }
+
+    if !has_code(client, rpc, multicall_addr).await? {
+        tracing::warn!("⚠ No Multicall3 at {}; verifying proofs one by one", multicall_addr);
+        for (index, _) in &calls {
+            let proof = &proofs[*index];
+            results[*index].result = verify_on_chain(
+                client,
+                zeroproof_addr,
+                rpc,
+                &proof.proof_hex,
+                &proof.public_values_hex,
+                &proof.vk_hash,
+                &proof.claim,
+            )
+            .await?;
+        }
+        return Ok(results);
+    }
+
+    let zeroproof: ethers::types::Address = zeroproof_addr.parse()?;
+    let call_data = encode_aggregate3(zeroproof, calls.iter().map(|(_, data)| data.as_slice()), true);
+    let params = serde_json::json!([
+        {
+            "to": multicall_addr,
+            "data": format!("0x{}", hex::encode(&call_data)),
+        },
+        "latest"
+    ]);
+    let RpcReply { endpoint, response } = rpc.call(client, "eth_call", params).await?;
+    if let Some(error) = response.get("error") {
+        return Err(anyhow::anyhow!("Multicall3 aggregate3 failed: {}", error));
+    }
+    let returned = response
+        .get("result")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Unexpected JSON-RPC response: {}", response))?;
+    let outcomes = decode_aggregate3(&hex_to_bytes(returned)?)?;
+    if outcomes.len() != calls.len() {
+        return Err(anyhow::anyhow!("Multicall3 returned {} results for {} calls", outcomes.len(), calls.len()));
+    }
+
+    for ((index, _), (success, return_data)) in calls.iter().zip(outcomes) {
+        let result = &mut results[*index].result;
+        result.rpc_endpoint = Some(endpoint.clone());
+        if success {
+            result.verified = true;
+            result.error = None;
+        } else {
+            let reason = RevertReason::from_revert_data(&hex::encode(&return_data));
+            tracing::error!("  ✗ Proof {} reverted: {}", proofs[*index].id, reason);
+            result.error = Some("verifyProof reverted".to_string());
+            result.details = Some(reason.to_string());
+        }
+    }
+    let verified = results.iter().filter(|r| r.result.verified).count();
+    tracing::info!("✓ Batch verification: {} of {} proofs valid", verified, proofs.len());
+
+    Ok(results)
+}
+
+/// Verify several proofs with one transaction through Multicall3
+///
+/// The batch is checked with [`verify_batch_on_chain`] first and only the proofs that
+/// pass are sent, atomically, so a single bad proof can't revert the transaction. Every
+/// sent proof reports the shared tx hash, block, gas used, and cost estimate.
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_batch_on_chain(
+    client: &reqwest::Client,
+    zeroproof_addr: &str,
+    multicall_addr: &str,
+    rpc: &RpcPool,
+    queue: &tx_queue::TxQueue,
+    label: Option<&str>,
+    fees: &fees::FeeStrategy,
+    proofs: &[BatchProof],
+) -> Result<Vec<BatchVerification>> {
+    use ethers::types::{Address, Eip1559TransactionRequest};
+
+    let mut results = verify_batch_on_chain(client, zeroproof_addr, multicall_addr, rpc, proofs).await?;
+    let passing: Vec<usize> = (0..results.len()).filter(|&i| results[i].result.verified).collect();
+    if passing.is_empty() {
+        return Ok(results);
+    }
+
+    if !has_code(client, rpc, multicall_addr).await? {
+        for index in passing {
+            let proof = &proofs[index];
+            results[index].result = submit_on_chain(
+                client,
+                zeroproof_addr,
+                rpc,
+                queue,
+                label,
+                fees,
+                &proof.proof_hex,
+                &proof.public_values_hex,
+                &proof.vk_hash,
+                &proof.claim,
+            )
+            .await?;
+        }
+        return Ok(results);
+    }
+
+    tracing::info!("→ Submitting {} verifyProof calls in one Multicall3 transaction", passing.len());
+    let calls = passing
+        .iter()
+        .map(|&i| {
+            let proof = &proofs[i];
+            encode_verify_proof_call(&proof.proof_hex, &proof.public_values_hex, &proof.vk_hash, &proof.claim)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let call_data = encode_aggregate3(zeroproof_addr.parse()?, calls.iter().map(Vec::as_slice), false);
+
+    let from = format!("{:#x}", queue.address());
+    let cost = fees::estimate(client, rpc, Some(&from), multicall_addr, &call_data, fees).await?;
+    tracing::info!("  Estimated gas {} at up to {} ETH ({:?})", cost.gas_estimate, cost.max_cost_eth, cost.speed);
+
+    let to: Address = multicall_addr.parse()?;
+    let tx = cost.apply(Eip1559TransactionRequest::new().to(to).data(call_data))?;
+    let receipt = queue.send(label, tx).await?;
+    let tx_hash = format!("{:#x}", receipt.transaction_hash);
+    let verified = receipt.status.map(|s| s.as_u64() == 1).unwrap_or(false);
+    if !verified {
+        tracing::error!("✗ Batch verifyProof transaction reverted: {}", tx_hash);
+    }
+
+    for index in passing {
+        let result = &mut results[index].result;
+        result.verified = verified;
+        result.error = if verified { None } else { Some("Transaction reverted".to_string()) };
+        result.tx_hash = Some(tx_hash.clone());
+        result.block_number = receipt.block_number.map(|b| b.as_u64());
+        result.gas_used = receipt.gas_used.map(|g| g.as_u64());
+        result.rpc_endpoint = Some(queue.rpc_url().to_string());
+        result.cost_estimate = Some(cost.clone());
+    }
+    Ok(results)
+}
+
+fn unverified(error: Option<String>, details: Option<String>) -> VerificationResult {
+    VerificationResult {
+        verified: false,
+        error,
+        details,
+        tx_hash: None,
+        block_number: None,
+        gas_used: None,
+        rpc_endpoint: None,
+        cost_estimate: None,
+    }
+}
+
+/// Whether a contract is deployed at `address`
+async fn has_code(client: &reqwest::Client, rpc: &RpcPool, address: &str) -> Result<bool> {
+    let RpcReply { response, .. } = rpc
+        .call(client, "eth_getCode", serde_json::json!([address, "latest"]))
+        .await?;
+    let code = response
+        .get("result")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Unexpected eth_getCode response: {}", response))?;
+    Ok(code.trim_start_matches("0x").chars().any(|c| c != '0'))
+}
+
+/// Calldata for Multicall3 `aggregate3((address,bool,bytes)[])`, calling `target` with each of `calls`
+fn encode_aggregate3<'a>(
+    target: ethers::types::Address,
+    calls: impl Iterator<Item = &'a [u8]>,
+    allow_failure: bool,
+) -> Vec<u8> {
+    use ethers::abi::Token;
+
+    let calls = calls
+        .map(|data| Token::Tuple(vec![Token::Address(target), Token::Bool(allow_failure), Token::Bytes(data.to_vec())]))
+        .collect();
+    let mut call_data = ethers::core::utils::keccak256(b"aggregate3((address,bool,bytes)[])")[..4].to_vec();
+    call_data.extend(ethers::abi::encode(&[Token::Array(calls)]));
+    call_data
+}
+
+/// `(bool success, bytes returnData)[]` returned by `aggregate3`
+fn decode_aggregate3(data: &[u8]) -> Result<Vec<(bool, Vec<u8>)>> {
+    use ethers::abi::{ParamType, Token};
+
+    let result_type = ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes])));
+    let Some(Token::Array(entries)) = ethers::abi::decode(&[result_type], data)?.into_iter().next() else {
+        return Err(anyhow::anyhow!("aggregate3 returned no result array"));
+    };
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            Token::Tuple(fields) => match fields.as_slice() {
+                [Token::Bool(success), Token::Bytes(data)] => Ok((*success, data.clone())),
+                _ => Err(anyhow::anyhow!("Unexpected aggregate3 result entry")),
+            },
+            _ => Err(anyhow::anyhow!("Unexpected aggregate3 result entry")),
+        })
+        .collect()
+}
+
+/// Result of [`record_claim`]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RecordClaimResult {
+    /// `claimIdOf(agent, claimType, dataHash)`; look the record up with [`get_claim`]
+    pub claim_id: String,
+    pub tx_hash: String,
+    pub block_number: Option<u64>,
+    pub gas_used: Option<u64>,
+    /// Fees the transaction was sent with
+    pub cost_estimate: fees::CostEstimate,
+}
+
+/// A claim stored by `ZeroProof.recordClaim`
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClaimRecord {
+    pub claim_id: String,
+    /// Agent address from the claim
+    pub agent: String,
+    pub claim_type: String,
+    pub data_hash: String,
+    /// Program verifying key the proof was checked against (zero for non-SP1 proofs)
+    pub vk_hash: String,
+    /// Block time it was recorded at (unix seconds)
+    pub timestamp: u64,
+}
+
+/// Record a verified claim in the ZeroProof registry as (agent, claimType, dataHash,
+/// vkHash, timestamp), so a booking has an on-chain anchor that outlives the eth_call
+///
+/// The contract verifies the proof again unless a `verifyProof` transaction already
+/// accepted it, and refuses a claim recorded before. Only the claim's agent may record it,
+/// so the agent defaults to the signer. Sent and priced like [`submit_on_chain`]; a
+/// reverted transaction is an error.
+#[allow(clippy::too_many_arguments)]
+pub async fn record_claim(
+    client: &reqwest::Client,
+    zeroproof_addr: &str,
+    rpc: &RpcPool,
+    queue: &tx_queue::TxQueue,
+    fees: &fees::FeeStrategy,
+    proof_hex: &str,
+    public_values_hex: &str,
+    vk_hash: &str,
+    claim: &ClaimBuilder,
+) -> Result<RecordClaimResult> {
+    use ethers::types::{Address, Eip1559TransactionRequest};
+
+    let claim = &claim.clone().default_agent(queue.address());
+    let call_data = encode_record_claim_call(proof_hex, public_values_hex, vk_hash, claim)?;
+    let claim_id = format!("0x{}", hex::encode(claim.claim_id(&hex_to_bytes(public_values_hex)?)));
+    tracing::info!("→ Recording claim {} in ZeroProof at {}", claim_id, zeroproof_addr);
+
+    let from = format!("{:#x}", queue.address());
+    let cost = fees::estimate(client, rpc, Some(&from), zeroproof_addr, &call_data, fees).await?;
+    let to: Address = zeroproof_addr.parse()?;
+    let tx = cost.apply(Eip1559TransactionRequest::new().to(to).data(call_data))?;
+
+    let receipt = queue.send(Some(&claim_id), tx).await?;
+    let tx_hash = format!("{:#x}", receipt.transaction_hash);
+    if receipt.status.map(|s| s.as_u64()) != Some(1) {
+        return Err(anyhow::anyhow!("recordClaim transaction {} reverted", tx_hash));
+    }
+    tracing::info!("✓ Claim {} recorded in block {:?}", claim_id, receipt.block_number);
+
+    Ok(RecordClaimResult {
+        claim_id,
+        tx_hash,
+        block_number: receipt.block_number.map(|b| b.as_u64()),
+        gas_used: receipt.gas_used.map(|g| g.as_u64()),
+        cost_estimate: cost,
+    })
+}
+
+/// Read a claim recorded with [`record_claim`] (None if nothing is recorded under `claim_id`)
+pub async fn get_claim(
+    client: &reqwest::Client,
+    zeroproof_addr: &str,
+    rpc: &RpcPool,
+    claim_id: &str,
+) -> Result<Option<ClaimRecord>> {
+    use ethers::abi::{ParamType, Token};
+
+    let id = bytes32_from_hex(claim_id, "claim_id")?;
+    // The public getter for claimRecords(bytes32), which returns zeros instead of reverting
+    let mut call_data = ethers::core::utils::keccak256(b"claimRecords(bytes32)")[..4].to_vec();
+    call_data.extend_from_slice(&id);
+
+    let params = serde_json::json!([
+        {
+            "to": zeroproof_addr,
+            "data": format!("0x{}", hex::encode(&call_data)),
+        },
+        "latest"
+    ]);
+    let RpcReply { response, .. } = rpc.call(client, "eth_call", params).await?;
+    if let Some(error) = response.get("error") {
+        return Err(anyhow::anyhow!("claimRecords call failed: {}", error));
+    }
+    let result = response
+        .get("result")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Unexpected JSON-RPC response: {}", response))?;
+
+    let types = [
+        ParamType::Address,
+        ParamType::FixedBytes(32),
+        ParamType::FixedBytes(32),
+        ParamType::FixedBytes(32),
+        ParamType::Uint(64),
+    ];
+    let bytes32 = |token: &Token| match token {
+        Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        _ => String::new(),
+    };
+    match ethers::abi::decode(&types, &hex_to_bytes(result)?)?.as_slice() {
+        [Token::Address(agent), claim_type, data_hash, vk_hash, Token::Uint(timestamp)] => {
+            if timestamp.is_zero() {
+                return Ok(None);
+            }
+            Ok(Some(ClaimRecord {
+                claim_id: format!("0x{}", hex::encode(id)),
+                agent: format!("{:#x}", agent),
+                claim_type: bytes32(claim_type),
+                data_hash: bytes32(data_hash),
+                vk_hash: bytes32(vk_hash),
+                timestamp: timestamp.as_u64(),
+            }))
+        }
+        _ => Err(anyhow::anyhow!("Unexpected claimRecords return data: {}", result)),
+    }
+}
+
+/// Anchor a 32-byte root on-chain as the calldata of a zero-value self-transfer
+///
+/// Returns the tx hash and block number once mined. Anyone holding the root can later
+/// look the transaction up and compare it with a fresh export.
+pub async fn anchor_on_chain(
+    queue: &tx_queue::TxQueue,
+    label: Option<&str>,
+    root: &[u8; 32],
+) -> Result<(String, Option<u64>)> {
+    use ethers::types::TransactionRequest;
+
+    tracing::info!("→ Anchoring root 0x{}", hex::encode(root));
+    let tx = TransactionRequest::new().to(queue.address()).value(0).data(root.to_vec());
+    let receipt = queue.send(label, tx).await?;
+    let tx_hash = format!("{:#x}", receipt.transaction_hash);
+    if receipt.status.map(|s| s.as_u64()) != Some(1) {
+        return Err(anyhow::anyhow!("Anchor transaction {} reverted", tx_hash));
+    }
+    Ok((tx_hash, receipt.block_number.map(|b| b.as_u64())))
+}
+
+/// Call Agent B to get pricing and program info
+///
+/// If Agent B signed the response, the signature is checked before anything is returned.
+/// With `expected_signer` set (Agent B's did:key), unsigned or foreign-signed responses are rejected.
+pub async fn get_ticket_price(
+    client: &reqwest::Client,
+    agent_b_url: &str,
+    input: &PricingInput,
+    expected_signer: Option<&str>,
+) -> Result<PricingResponse> {
+    tracing::info!("→ Calling Agent B at {}", agent_b_url);
+    
+    let url = format!("{}/price", agent_b_url);
+    let response_json = client
+        .post(&url)
+        .with_request_id()
+        .tap_json("POST", &url, input)
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+
+    let signer = check_agent_b_signature(&response_json, expected_signer)?;
+
+    // Extract fields directly from response JSON
+    let price_cents = response_json
+        .get("price_cents")
+        .and_then(|p| p.as_u64())
+        .ok_or_else(|| anyhow::anyhow!("Agent B response has no price_cents"))?;
+    // Agent B builds without currency support only quote USD
+    let currency = response_json
+        .get("currency")
+        .and_then(|c| c.as_str())
+        .unwrap_or("USD")
+        .to_string();
+    let promo_code = response_json
+        .get("promo_code")
+        .and_then(|c| c.as_str())
+        .map(str::to_string);
+    let discount_cents = response_json.get("discount_cents").and_then(|d| d.as_u64()).unwrap_or(0);
+    // Day the advance-purchase tier was counted from; the proof must use the same one
+    let purchase_date = response_json
+        .get("purchase_date")
+        .and_then(|d| d.as_str())
+        .map(str::to_string);
+
+    let quote_id = response_json
+        .get("quote_id")
+        .and_then(|q| q.as_str())
+        .map(str::to_string);
+    let expires_at = response_json.get("expires_at").and_then(|e| e.as_u64());
+    
+    let program_id = response_json
+        .get("program_id")
+        .and_then(|p| p.as_str())
+        .unwrap_or("")
+        .to_string();
+    
+    let elf_hash = response_json
+        .get("elf_hash")
+        .and_then(|e| e.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    tracing::info!("✓ Agent B response: price={}, program_id={}", format_price(price_cents, &currency), program_id);
+
+    Ok(PricingResponse {
+        price_cents,
+        currency,
+        promo_code,
+        discount_cents,
+        purchase_date,
+        quote_id,
+        expires_at,
+        program_id,
+        elf_hash,
+        signer,
+    })
+}
+
+/// Call Agent B to book a flight
+///
+/// Signature handling matches [`get_ticket_price`].
+pub async fn book_flight(
+    client: &reqwest::Client,
+    agent_b_url: &str,
+    input: &BookingInput,
+    expected_signer: Option<&str>,
+) -> Result<BookingResponse> {
+    tracing::info!("→ Booking via Agent B at {}", agent_b_url);
+
+    let url = format!("{}/book", agent_b_url);
+    let response_json = client
+        .post(&url)
+        .with_request_id()
+        .tap_json("POST", &url, input)
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+
+    let signer = check_agent_b_signature(&response_json, expected_signer)?;
+
+    let optional = |name: &str| response_json.get(name).and_then(|v| v.as_str()).map(String::from);
+    let field = |name: &str| optional(name).unwrap_or_default();
+
+    let booking = BookingResponse {
+        booking_id: field("booking_id"),
+        status: field("status"),
+        confirmation_code: field("confirmation_code"),
+        pii_salt: optional("pii_salt"),
+        passenger_commitment: optional("passenger_commitment"),
+        program_id: field("program_id"),
+        elf_hash: field("elf_hash"),
+        signer,
+    };
+
+    check_passenger_commitment(&booking, input)?;
+
+    tracing::info!("✓ Agent B booking: id={}, status={}", booking.booking_id, booking.status);
+
+    Ok(booking)
+}
+
+/// Cancel a booking through Agent B as of today
+///
+/// The fee follows pricing-core's cancellation rules; signature handling matches
+/// [`get_ticket_price`].
+pub async fn cancel_booking(
+    client: &reqwest::Client,
+    agent_b_url: &str,
+    booking_id: &str,
+    expected_signer: Option<&str>,
+) -> Result<CancellationResponse> {
+    tracing::info!("→ Cancelling booking {} via Agent B", booking_id);
+
+    let url = format!("{}/booking/{}/cancel", agent_b_url, booking_id);
+    let response = client
+        .post(&url)
+        .with_request_id()
+        .tap_json("POST", &url, &serde_json::json!({}))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(anyhow::anyhow!("Agent B refused to cancel {}: {} {}", booking_id, status, response.text().await?));
+    }
+    let response_json = response.json::<serde_json::Value>().await?;
+
+    let signer = check_agent_b_signature(&response_json, expected_signer)?;
+
+    let field = |name: &str| response_json.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let cancellation = response_json
+        .get("cancellation")
+        .ok_or_else(|| anyhow::anyhow!("Agent B response for {} has no cancellation", booking_id))?;
+    let cents = |name: &str| {
+        cancellation
+            .get(name)
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow::anyhow!("Agent B cancellation has no {}", name))
+    };
+
+    let response = CancellationResponse {
+        booking_id: field("booking_id"),
+        status: field("status"),
+        fare_cents: response_json.get("price_cents").and_then(|v| v.as_u64()).unwrap_or_default(),
+        fee_cents: cents("fee_cents")?,
+        refund_cents: cents("refund_cents")?,
+        currency: field("currency"),
+        cancelled_on: cancellation.get("cancelled_on").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        zk_input: response_json.get("zk_input").cloned().unwrap_or_default(),
+        program_id: field("program_id"),
+        elf_hash: field("elf_hash"),
+        signer,
+    };
+
+    tracing::info!(
+        "✓ Agent B cancelled {}: refund {} (fee {})",
+        response.booking_id,
+        format_price(response.refund_cents, &response.currency),
+        format_price(response.fee_cents, &response.currency)
+    );
+
+    Ok(response)
+}
+
+/// Check that the booking's passenger commitment opens to the passenger we booked, so a
+/// proof of it can later be claimed by them
+pub fn check_passenger_commitment(booking: &BookingResponse, input: &BookingInput) -> Result<()> {
+    let (Some(salt), Some(commitment)) = (&booking.pii_salt, &booking.passenger_commitment) else {
+        tracing::warn!("⚠ Agent B booking has no passenger commitment");
+        return Ok(());
+    };
+    let salt = PiiCommitment::parse_salt(salt)?;
+    if !PiiCommitment::from_hex(commitment)?.opens_to(&salt, &input.passenger_name, &input.passenger_email) {
+        return Err(anyhow::anyhow!(
+            "Agent B's passenger commitment for booking {} doesn't match the passenger",
+            booking.booking_id
+        ));
+    }
+    Ok(())
+}
+
+/// Check Agent B's signature over (data, program_id, elf_hash), returning the signer
+fn check_agent_b_signature(
+    response_json: &serde_json::Value,
+    expected_signer: Option<&str>,
+) -> Result<Option<String>> {
+    if response_json.get("signature").is_some() {
+        let signed: SignedAgentResponse = serde_json::from_value(response_json.clone())?;
+        signed.verify(expected_signer)?;
+        tracing::info!("✓ Agent B response signature valid (signer={})", signed.signer);
+        Ok(Some(signed.signer))
+    } else if expected_signer.is_some() {
+        Err(anyhow::anyhow!("Agent B response is unsigned but AGENT_B_DID is configured"))
+    } else {
+        tracing::warn!("⚠ Agent B response is unsigned");
+        Ok(None)
+    }
+}
+
+/// Get ZK input formatting from Agent B
+pub async fn format_zk_input(
+    client: &reqwest::Client,
+    agent_b_url: &str,
+    endpoint: &str,
+    input: &serde_json::Value,
+) -> Result<ZkInputResult> {
+    tracing::info!("→ Getting ZK input format from Agent B");
+    
+    let url = format!("{}/zk-input", agent_b_url);
+    let response = client
+        .post(&url)
+        .with_request_id()
+        .tap_json("POST", &url, &serde_json::json!({
+            "endpoint": endpoint,
+            "input": input
+        }))
+        .send()
+        .await?
+        .json::<serde_json::Value>()
+        .await?;
+    
+    let input_array = zk_protocol::json_array_to_bytes(&response["input_bytes"])
+        .map_err(|e| anyhow::anyhow!("Missing input_bytes in response: {}", e))?;
+
+    let input_hex = format!("0x{}", hex::encode(&input_array));
+    
+    tracing::info!("✓ ZK input formatted: {} bytes", input_array.len());
+
+    Ok(ZkInputResult {
+        input_bytes: input_hex,
+        input_array,
+        program: response["program"].as_str().map(String::from),
+        program_id: response["program_id"].as_str().map(String::from),
+    })
+}
+
+/// Request attestation from attester service
+///
+/// Proving takes minutes: `client` should be built with a long timeout.
 pub async fn request_attestation(
+    client: &reqwest::Client,
     attester_url: &str,
     program_id: &str,
     input_bytes: Vec<u8>,
@@ -261,29 +1739,626 @@ pub async fn request_attestation(
     verify_locally: bool,
 ) -> Result<AttestResponse> {
     tracing::info!("→ Requesting attestation from {}", attester_url);
-    
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(7200))
-        .build()?;
 
-    let request = AttestRequest {
+    let request = new_attest_request(program_id, input_bytes, claimed_output, verify_locally);
+    let input_bytes = request.input_bytes.clone();
+
+    let envelope = client
+        .post(format!("{}/attest", attester_url))
+        .with_request_id()
+        .json(&Envelope::new(request))
+        .send()
+        .await?
+        .json::<Envelope<AttestResponse>>()
+        .await?;
+
+    let (response, check) = envelope.migrate_legacy().open()?;
+    if let zk_protocol::VersionCheck::MinorSkew { local, peer } = check {
+        tracing::warn!("⚠ Attester protocol minor version skew: local={}, attester={}", local, peer);
+    }
+
+    tracing::info!("✓ Attestation response: verified_output={}", response.verified_output);
+    check_input_binding(&response, &input_bytes)?;
+
+    Ok(response)
+}
+
+/// Fail if the proof's public values commit another input than `input_bytes`, so an
+/// attester can't answer with a proof of a different (say, cheaper) request. Agent B's
+/// guests all commit `PublicValues`, so values that don't decode as such fail too.
+pub fn check_input_binding(response: &AttestResponse, input_bytes: &[u8]) -> Result<()> {
+    let public_values = hex_to_bytes(&response.public_values)?;
+    PublicValues::check_input(ValuesLayout::Bound, &public_values, input_bytes)?;
+    Ok(())
+}
+
+/// A field where a proof's committed claim differs from what the user was shown
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ClaimMismatch {
+    pub field: String,
+    /// Value shown to the user
+    pub quoted: String,
+    /// Value the proof committed
+    pub proven: String,
+}
+
+/// Error of [`attest_price`] when the proof doesn't commit the quoted price or route
+///
+/// Carries the proof so the caller can record the mismatch.
+#[derive(Debug, Clone)]
+pub struct PriceMismatch {
+    pub mismatches: Vec<ClaimMismatch>,
+    pub proof_bundle: ProofBundle,
+}
+
+impl std::fmt::Display for PriceMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Proof doesn't match the quote shown:")?;
+        for m in &self.mismatches {
+            write!(f, " {} quoted {}, proven {};", m.field, m.quoted, m.proven)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PriceMismatch {}
+
+/// Fields of a proven pricing claim that differ from Agent B's quote for `input`
+pub fn pricing_mismatches(claim: &PricingClaim, input: &PricingInput, quote: &PricingResponse) -> Vec<ClaimMismatch> {
+    let mut mismatches = Vec::new();
+    let mut check = |field: &str, quoted: String, proven: String, same: bool| {
+        if !same {
+            mismatches.push(ClaimMismatch { field: field.to_string(), quoted, proven });
+        }
+    };
+    check("from", input.from.clone(), claim.from.clone(), input.from.eq_ignore_ascii_case(&claim.from));
+    check("to", input.to.clone(), claim.to.clone(), input.to.eq_ignore_ascii_case(&claim.to));
+    check("price_cents", quote.price_cents.to_string(), claim.price_cents.to_string(), quote.price_cents == claim.price_cents);
+    check("currency", quote.currency.clone(), claim.currency.clone(), quote.currency.eq_ignore_ascii_case(&claim.currency));
+    check("vip", input.vip.to_string(), claim.vip.to_string(), input.vip == claim.vip);
+    if let Some(quote_id) = &quote.quote_id {
+        check("quote_id", quote_id.clone(), claim.quote_id.clone(), *quote_id == claim.quote_id);
+    }
+    if let Some(date) = &input.departure_date {
+        check("departure_date", date.clone(), claim.departure_date.clone(), *date == claim.departure_date);
+    }
+    if let Some(date) = &input.return_date {
+        check("return_date", date.clone(), claim.return_date.clone(), *date == claim.return_date);
+    }
+    mismatches
+}
+
+/// Mode of an attester proof, read from its verifier-hash prefix (Groth16 if unrecognized)
+/// Why a mock proof (attester in PROVER_MODE=mock) never goes on-chain
+pub const MOCK_PROOF_ERROR: &str = "Mock proof (attester PROVER_MODE=mock): it has no proof bytes and can't be verified on-chain";
+
+/// Whether to run a requested on-chain check; never for a mock proof
+fn wants_onchain(verify_onchain: bool, attestation: &AttestResponse) -> bool {
+    if verify_onchain && attestation.mode == ProverMode::Mock {
+        tracing::warn!("⊘ Skipping the on-chain check: {}", MOCK_PROOF_ERROR);
+        return false;
+    }
+    verify_onchain
+}
+
+pub fn proof_mode_of(response: &AttestResponse) -> ProofMode {
+    hex_to_bytes(&response.proof)
+        .ok()
+        .and_then(|proof| ProofMode::from_proof(&proof))
+        .unwrap_or_default()
+}
+
+/// Bincode-encoded attestation request with a fresh replay nonce
+pub fn new_attest_request(
+    program_id: &str,
+    input_bytes: Vec<u8>,
+    claimed_output: Option<serde_json::Value>,
+    verify_locally: bool,
+) -> AttestRequest {
+    AttestRequest {
         program_id: program_id.to_string(),
         input_bytes,
+        codec: Codec::Bincode,
         claimed_output,
         verify_locally,
+        nonce: Some(uuid::Uuid::new_v4().to_string()),
+        expires_at: None,
+        proof_mode: ProofMode::Groth16,
+    }
+}
+
+/// Full pricing attestation pipeline in one call:
+/// Agent B /price → Agent B /zk-input → attester /attest → claim check → (optional) on-chain verification
+///
+/// A proof committing another price or route than the quote fails with [`PriceMismatch`].
+pub async fn attest_price(
+    http: &reqwest::Client,
+    attester_http: &reqwest::Client,
+    endpoints: &PipelineEndpoints<'_>,
+    input: &PricingInput,
+    verify_onchain: bool,
+) -> Result<AttestPriceResult> {
+    let breakers = endpoints.breakers;
+
+    // 1. Quote from Agent B (signature checked if present)
+    let pricing = breakers
+        .agent_b
+        .call(get_ticket_price(http, endpoints.agent_b_url, input, endpoints.agent_b_did))
+        .await?;
+
+    // 2. zkVM input bytes for the same request, bound to the quote
+    let mut zk_request = serde_json::to_value(input)?;
+    zk_request["quote_id"] = serde_json::json!(pricing.quote_id);
+    zk_request["currency"] = serde_json::json!(pricing.currency);
+    zk_request["purchase_date"] = serde_json::json!(pricing.purchase_date);
+    let zk_input = breakers.agent_b.call(format_zk_input(
+        http,
+        endpoints.agent_b_url,
+        "price",
+        &zk_request,
+    ))
+    .await?;
+
+    // 3. Proof from the attester
+    let attestation = breakers.attester.call(request_attestation(
+        attester_http,
+        endpoints.attester_url,
+        &pricing.program_id,
+        zk_input.input_array,
+        Some(serde_json::json!({ "price_cents": pricing.price_cents, "currency": pricing.currency })),
+        true,
+    ))
+    .await?;
+
+    // 4. The proof must commit the price and route the user was shown, whatever the attester says
+    let values = PublicValues::abi_decode(&hex_to_bytes(&attestation.public_values)?)?;
+    let mismatches = pricing_mismatches(&PricingClaim::abi_decode(&values.claim)?, input, &pricing);
+    if !mismatches.is_empty() {
+        let mismatch = PriceMismatch {
+            mismatches,
+            proof_bundle: ProofBundle::from_attestation(
+                &attestation,
+                &pricing.program_id,
+                &pricing.elf_hash,
+                proof_mode_of(&attestation),
+            )?,
+        };
+        tracing::warn!("⚠ {}", mismatch);
+        return Err(mismatch.into());
+    }
+
+    // 5. Optional on-chain check
+    let verified = if wants_onchain(verify_onchain, &attestation) {
+        let result = breakers.rpc.call(verify_on_chain(
+            http,
+            endpoints.zeroproof_addr,
+            endpoints.rpc,
+            &attestation.proof,
+            &attestation.public_values,
+            &attestation.vk_hash,
+            &ClaimBuilder::new(),
+        ))
+        .await?;
+        Some(result.verified)
+    } else {
+        None
     };
 
+    Ok(AttestPriceResult {
+        price_cents: pricing.price_cents,
+        currency: pricing.currency,
+        quote_id: pricing.quote_id,
+        expires_at: pricing.expires_at,
+        program_id: pricing.program_id,
+        elf_hash: pricing.elf_hash,
+        proof_mode: proof_mode_of(&attestation),
+        proof: attestation.proof,
+        public_values: attestation.public_values,
+        vk_hash: attestation.vk_hash,
+        verified,
+    })
+}
+
+/// Price several options in one Agent B call; signature handling matches [`get_ticket_price`]
+pub async fn get_flight_options(
+    client: &reqwest::Client,
+    agent_b_url: &str,
+    input: &FlightOptionsInput,
+    expected_signer: Option<&str>,
+) -> Result<FlightOptionsResponse> {
+    tracing::info!("→ Comparing {} options at Agent B {}", input.options.len(), agent_b_url);
+
+    let url = format!("{}/price/batch", agent_b_url);
     let response = client
-        .post(&format!("{}/attest", attester_url))
-        .json(&request)
+        .post(&url)
+        .with_request_id()
+        .tap_json("POST", &url, input)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(anyhow::anyhow!("Agent B /price/batch returned {}: {}", status, response.text().await?));
+    }
+    let response_json = response.json::<serde_json::Value>().await?;
+
+    let signer = check_agent_b_signature(&response_json, expected_signer)?;
+    let mut options: FlightOptionsResponse = serde_json::from_value(response_json)
+        .map_err(|e| anyhow::anyhow!("Unexpected /price/batch response: {}", e))?;
+    options.signer = signer;
+
+    if let Some(cheapest) = options.cheapest_option() {
+        tracing::info!(
+            "✓ Agent B comparison: cheapest {} → {} at {}",
+            cheapest.from,
+            cheapest.to,
+            format_price(cheapest.price_cents.unwrap_or_default(), &options.currency)
+        );
+    }
+    Ok(options)
+}
+
+/// Compare options at Agent B and prove the whole comparison in one execution:
+/// Agent B /price/batch → Agent B /zk-input → attester /attest → (optional) on-chain verification
+///
+/// The proof commits every option's row and the cheapest, so "cheapest" can't be picked
+/// from a shortened list.
+pub async fn attest_cheapest_option(
+    http: &reqwest::Client,
+    attester_http: &reqwest::Client,
+    endpoints: &PipelineEndpoints<'_>,
+    input: &FlightOptionsInput,
+    verify_onchain: bool,
+) -> Result<AttestCheapestResult> {
+    let breakers = endpoints.breakers;
+
+    // 1. Comparison from Agent B (signature checked if present)
+    let comparison = breakers
+        .agent_b
+        .call(get_flight_options(http, endpoints.agent_b_url, input, endpoints.agent_b_did))
+        .await?;
+    let cheapest = comparison
+        .cheapest_option()
+        .and_then(|option| option.price_cents)
+        .ok_or_else(|| anyhow::anyhow!("Agent B comparison has no priced cheapest option"))?;
+
+    // 2. zkVM input bytes for exactly the requests Agent B priced
+    let zk_input = breakers.agent_b.call(format_zk_input(
+        http,
+        endpoints.agent_b_url,
+        "price_batch",
+        &comparison.zk_input["input"],
+    ))
+    .await?;
+
+    // 3. Proof from the attester
+    let attestation = breakers.attester.call(request_attestation(
+        attester_http,
+        endpoints.attester_url,
+        &comparison.program_id,
+        zk_input.input_array,
+        Some(serde_json::json!({
+            "cheapest": comparison.cheapest,
+            "price_cents": cheapest,
+            "currency": comparison.currency
+        })),
+        true,
+    ))
+    .await?;
+
+    // 4. Optional on-chain check
+    let verified = if wants_onchain(verify_onchain, &attestation) {
+        let result = breakers.rpc.call(verify_on_chain(
+            http,
+            endpoints.zeroproof_addr,
+            endpoints.rpc,
+            &attestation.proof,
+            &attestation.public_values,
+            &attestation.vk_hash,
+            &ClaimBuilder::new(),
+        ))
+        .await?;
+        Some(result.verified)
+    } else {
+        None
+    };
+
+    let proof_bundle = ProofBundle::from_attestation(
+        &attestation,
+        &comparison.program_id,
+        &comparison.elf_hash,
+        proof_mode_of(&attestation),
+    )?;
+
+    Ok(AttestCheapestResult {
+        options: comparison.options,
+        currency: comparison.currency,
+        cheapest: comparison.cheapest,
+        proof_bundle,
+        verified,
+    })
+}
+
+/// Book a flight through Agent B and prove the confirmation
+///
+/// Same trail as [`attest_price`]: the booking is re-executed in the zkVM from the
+/// `/zk-input` bytes (`endpoint: "book"`) and the claimed output is the confirmation.
+pub async fn attest_booking(
+    http: &reqwest::Client,
+    attester_http: &reqwest::Client,
+    endpoints: &PipelineEndpoints<'_>,
+    input: &BookingInput,
+    verify_onchain: bool,
+) -> Result<AttestBookingResult> {
+    let breakers = endpoints.breakers;
+
+    // 1. Booking from Agent B (signature checked if present)
+    let booking = breakers
+        .agent_b
+        .call(book_flight(http, endpoints.agent_b_url, input, endpoints.agent_b_did))
+        .await?;
+
+    // 2. zkVM input bytes for the same request, with the salt Agent B committed under
+    let (Some(pii_salt), Some(passenger_commitment)) = (booking.pii_salt.clone(), booking.passenger_commitment.clone())
+    else {
+        return Err(anyhow::anyhow!("Agent B returned no passenger commitment, so booking {} can't be proven", booking.booking_id));
+    };
+    let zk_input = breakers.agent_b.call(format_zk_input(
+        http,
+        endpoints.agent_b_url,
+        "book",
+        &serde_json::to_value(BookingInput { pii_salt: Some(pii_salt.clone()), ..input.clone() })?,
+    ))
+    .await?;
+
+    // 3. Proof from the attester
+    let attestation = breakers.attester.call(request_attestation(
+        attester_http,
+        endpoints.attester_url,
+        &booking.program_id,
+        zk_input.input_array,
+        Some(serde_json::json!({
+            "booking_id": booking.booking_id,
+            "status": booking.status,
+            "confirmation_code": booking.confirmation_code,
+            "passenger_commitment": passenger_commitment
+        })),
+        true,
+    ))
+    .await?;
+
+    // The proven claim must carry the commitment the passenger can open
+    let values = PublicValues::abi_decode(&hex_to_bytes(&attestation.public_values)?)?;
+    if PiiCommitment(BookingClaim::abi_decode(&values.claim)?.passenger_commitment).to_hex() != passenger_commitment {
+        return Err(anyhow::anyhow!("Proof of booking {} commits a different passenger", booking.booking_id));
+    }
+
+    // 4. Optional on-chain check
+    let verified = if wants_onchain(verify_onchain, &attestation) {
+        let result = breakers.rpc.call(verify_on_chain(
+            http,
+            endpoints.zeroproof_addr,
+            endpoints.rpc,
+            &attestation.proof,
+            &attestation.public_values,
+            &attestation.vk_hash,
+            &ClaimBuilder::new().claim_type(ClaimType::Booking),
+        ))
+        .await?;
+        Some(result.verified)
+    } else {
+        None
+    };
+
+    let proof_bundle = ProofBundle::from_attestation(
+        &attestation,
+        &booking.program_id,
+        &booking.elf_hash,
+        proof_mode_of(&attestation),
+    )?;
+
+    Ok(AttestBookingResult {
+        booking_id: booking.booking_id,
+        status: booking.status,
+        confirmation_code: booking.confirmation_code,
+        pii_salt,
+        passenger_commitment,
+        proof_bundle,
+        verified,
+    })
+}
+
+/// Cancel a booking through Agent B and prove the refund
+///
+/// The fee calculation is re-executed in the zkVM from the `zk_input` Agent B returned
+/// with the cancellation (`endpoint: "cancel"`). The proven claim must carry the booking,
+/// fee, and refund Agent B reported, so the amount refunded is the amount proven.
+pub async fn attest_refund(
+    http: &reqwest::Client,
+    attester_http: &reqwest::Client,
+    endpoints: &PipelineEndpoints<'_>,
+    booking_id: &str,
+    verify_onchain: bool,
+) -> Result<AttestRefundResult> {
+    let breakers = endpoints.breakers;
+
+    // 1. Cancellation from Agent B (signature checked if present)
+    let cancellation = breakers
+        .agent_b
+        .call(cancel_booking(http, endpoints.agent_b_url, booking_id, endpoints.agent_b_did))
+        .await?;
+
+    // 2. zkVM input bytes for the same fee calculation
+    let zk_input = breakers.agent_b.call(format_zk_input(
+        http,
+        endpoints.agent_b_url,
+        "cancel",
+        &cancellation.zk_input["input"],
+    ))
+    .await?;
+
+    // 3. Proof from the attester
+    let attestation = breakers.attester.call(request_attestation(
+        attester_http,
+        endpoints.attester_url,
+        &cancellation.program_id,
+        zk_input.input_array,
+        Some(serde_json::json!({
+            "booking_id": cancellation.booking_id,
+            "fee_cents": cancellation.fee_cents,
+            "refund_cents": cancellation.refund_cents,
+            "currency": cancellation.currency
+        })),
+        true,
+    ))
+    .await?;
+
+    // 4. The proof must commit the refund Agent B reported
+    let values = PublicValues::abi_decode(&hex_to_bytes(&attestation.public_values)?)?;
+    let claim = CancellationClaim::abi_decode(&values.claim)?;
+    if claim.booking_id != cancellation.booking_id
+        || claim.fee_cents != cancellation.fee_cents
+        || claim.refund_cents != cancellation.refund_cents
+        || !claim.currency.eq_ignore_ascii_case(&cancellation.currency)
+    {
+        return Err(anyhow::anyhow!(
+            "Proof of cancelling {} commits a refund of {}, Agent B reported {}",
+            cancellation.booking_id,
+            format_price(claim.refund_cents, &claim.currency),
+            format_price(cancellation.refund_cents, &cancellation.currency)
+        ));
+    }
+
+    // 5. Optional on-chain check
+    let verified = if wants_onchain(verify_onchain, &attestation) {
+        let result = breakers.rpc.call(verify_on_chain(
+            http,
+            endpoints.zeroproof_addr,
+            endpoints.rpc,
+            &attestation.proof,
+            &attestation.public_values,
+            &attestation.vk_hash,
+            &ClaimBuilder::new().claim_type(ClaimType::Payment),
+        ))
+        .await?;
+        Some(result.verified)
+    } else {
+        None
+    };
+
+    let proof_bundle = ProofBundle::from_attestation(
+        &attestation,
+        &cancellation.program_id,
+        &cancellation.elf_hash,
+        proof_mode_of(&attestation),
+    )?;
+
+    Ok(AttestRefundResult {
+        booking_id: cancellation.booking_id,
+        fare_cents: claim.fare_cents,
+        fee_cents: claim.fee_cents,
+        refund_cents: claim.refund_cents,
+        currency: cancellation.currency,
+        cancelled_on: cancellation.cancelled_on,
+        proof_bundle,
+        verified,
+    })
+}
+
+/// Fetch the chain ID (eth_chainId) from the RPC pool
+pub async fn fetch_chain_id(client: &reqwest::Client, rpc: &RpcPool) -> Result<u64> {
+    let RpcReply { response, .. } = rpc.call(client, "eth_chainId", serde_json::json!([])).await?;
+
+    let hex_id = response
+        .get("result")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Unexpected eth_chainId response: {}", response))?;
+    Ok(u64::from_str_radix(hex_id.trim_start_matches("0x"), 16)?)
+}
+
+/// Submit an attestation as an async job; returns immediately with the job id
+pub async fn submit_attestation_job(
+    client: &reqwest::Client,
+    attester_url: &str,
+    request: AttestRequest,
+) -> Result<AttestJobId> {
+    let envelope = client
+        .post(format!("{}/attest/jobs", attester_url))
+        .with_request_id()
+        .json(&Envelope::new(request))
         .send()
         .await?
-        .json::<AttestResponse>()
+        .error_for_status()?
+        .json::<Envelope<AttestJobAccepted>>()
         .await?;
 
-    tracing::info!("✓ Attestation response: verified_output={}", response.verified_output);
+    let (accepted, _) = envelope.migrate_legacy().open()?;
+    tracing::info!("✓ Attestation job queued: {}", accepted.job_id);
+    Ok(accepted.job_id)
+}
 
-    Ok(response)
+/// Current status of an attestation job
+pub async fn fetch_attestation_job(
+    client: &reqwest::Client,
+    attester_url: &str,
+    job_id: &AttestJobId,
+) -> Result<AttestJobStatus> {
+    let envelope = client
+        .get(format!("{}/attest/jobs/{}", attester_url, job_id))
+        .with_request_id()
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Envelope<AttestJobStatus>>()
+        .await?;
+
+    let (status, _) = envelope.migrate_legacy().open()?;
+    Ok(status)
+}
+
+/// Error of [`poll_attestation_job`] when the job isn't finished within its timeout
+#[derive(Debug, Clone)]
+pub struct JobTimedOut {
+    pub job_id: AttestJobId,
+    pub timeout: std::time::Duration,
+}
+
+impl std::fmt::Display for JobTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Attestation job {} not finished after {:?}", self.job_id, self.timeout)
+    }
+}
+
+impl std::error::Error for JobTimedOut {}
+
+/// Poll an attestation job every `interval` until it reaches a terminal state, giving up
+/// with [`JobTimedOut`] after `timeout`
+pub async fn poll_attestation_job(
+    client: &reqwest::Client,
+    attester_url: &str,
+    job_id: &AttestJobId,
+    interval: std::time::Duration,
+    timeout: std::time::Duration,
+) -> Result<AttestResponse> {
+    let poll = async {
+        loop {
+            match fetch_attestation_job(client, attester_url, job_id).await? {
+                AttestJobStatus::Done(response) => {
+                    tracing::info!("✓ Attestation job {} done", job_id);
+                    return Ok(response);
+                }
+                AttestJobStatus::Failed { code, message } => {
+                    return Err(anyhow::anyhow!("Attestation job {} failed ({}): {}", job_id, code, message));
+                }
+                other => {
+                    tracing::debug!("… Attestation job {}: {:?}", job_id, other);
+                    tokio::time::sleep(interval).await;
+                }
+            }
+        }
+    };
+    tokio::time::timeout(timeout, poll)
+        .await
+        .map_err(|_| JobTimedOut { job_id: job_id.clone(), timeout })?
 }
 
 #[cfg(test)]
@@ -292,12 +2367,220 @@ mod tests {
 
     #[test]
     fn test_pricing_input_schema() {
-        let input = PricingInput {
+        let _input = PricingInput {
             from: "NYC".to_string(),
             to: "LON".to_string(),
             vip: true,
+            departure_date: Some("2026-11-02".to_string()),
+            return_date: None,
+            currency: None,
+            cabin: None,
+            fare_family: None,
+            promo_code: None,
         };
         let schema = schemars::schema_for!(PricingInput);
         assert!(schema.schema.object.is_some());
     }
+
+    #[test]
+    fn test_aggregate3_encoding() {
+        use ethers::abi::Token;
+
+        let target: ethers::types::Address = "0x9C33252D29B41Fe2706704a8Ca99E8731B58af41".parse().unwrap();
+        let call_data = encode_aggregate3(target, [&[1u8, 2][..], &[3u8][..]].into_iter(), true);
+        assert_eq!(hex::encode(&call_data[..4]), "82ad56cb");
+
+        let returned = ethers::abi::encode(&[Token::Array(vec![
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(vec![])]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![0x09, 0xbd, 0xe3, 0x39])]),
+        ])]);
+        let outcomes = decode_aggregate3(&returned).unwrap();
+        assert_eq!(outcomes, vec![(true, vec![]), (false, vec![0x09, 0xbd, 0xe3, 0x39])]);
+    }
+
+    #[test]
+    fn test_pricing_mismatches() {
+        let input: PricingInput = serde_json::from_value(serde_json::json!({
+            "from": "NYC", "to": "LON", "vip": false, "departure_date": "2026-11-02"
+        }))
+        .unwrap();
+        let quote: PricingResponse = serde_json::from_value(serde_json::json!({
+            "price_cents": 67990, "currency": "USD", "quote_id": "q1", "expires_at": null,
+            "program_id": "p", "elf_hash": "e", "signer": null
+        }))
+        .unwrap();
+        let mut claim = PricingClaim {
+            from: "nyc".into(),
+            to: "LON".into(),
+            price_cents: 67990,
+            vip: false,
+            quote_id: "q1".into(),
+            currency: "USD".into(),
+            promo_code: String::new(),
+            departure_date: "2026-11-02".into(),
+            return_date: String::new(),
+        };
+        assert!(pricing_mismatches(&claim, &input, &quote).is_empty());
+
+        claim.price_cents = 12000;
+        claim.to = "PAR".into();
+        let fields: Vec<_> = pricing_mismatches(&claim, &input, &quote).into_iter().map(|m| m.field).collect();
+        assert_eq!(fields, ["to", "price_cents"]);
+    }
+
+    #[test]
+    fn test_price_bundle_keeps_the_proof_mode() {
+        let attestation = AttestResponse {
+            proof: format!("0x{}00", hex::encode(zk_protocol::PLONK_SELECTOR)),
+            public_values: "0x01".into(),
+            vk_hash: "0x00".into(),
+            verified_output: serde_json::Value::Null,
+            mode: Default::default(),
+        };
+        let result = AttestPriceResult {
+            price_cents: 67990,
+            currency: "USD".into(),
+            quote_id: None,
+            expires_at: None,
+            program_id: "p".into(),
+            elf_hash: "e".into(),
+            proof_mode: proof_mode_of(&attestation),
+            proof: attestation.proof,
+            public_values: attestation.public_values,
+            vk_hash: attestation.vk_hash,
+            verified: None,
+        };
+        assert_eq!(result.proof_bundle().unwrap().proof_mode, ProofMode::Plonk);
+    }
+
+    /// Revert data for the Solidity error `signature` with `words` zero arguments, with the
+    /// selector computed independently of the abigen bindings
+    fn revert_data(signature: &str, words: usize) -> String {
+        let selector = &ethers::core::utils::keccak256(signature.as_bytes())[..4];
+        format!("0x{}{}", hex::encode(selector), "00".repeat(32 * words))
+    }
+
+    #[test]
+    fn test_revert_reason_selectors() {
+        // Arguments don't have to be complete to classify a revert
+        let data = revert_data("WrongVerifierSelector(bytes4,bytes4)", 0);
+        assert_eq!(RevertReason::from_revert_data(&format!("{}deadbeef", data)), RevertReason::WrongVerifierSelector);
+        assert_eq!(RevertReason::from_revert_data("0x09BDE339"), RevertReason::InvalidProof);
+
+        // Error(string) with message "bad"
+        let data = format!("0x08c379a0{:064x}{:064x}{:0<64}", 32, 3, hex::encode("bad"));
+        assert_eq!(RevertReason::from_revert_data(&data), RevertReason::Custom("bad".into()));
+
+        assert_eq!(
+            RevertReason::from_revert_data("0xabcdef01"),
+            RevertReason::Custom("unrecognized selector 0xabcdef01".into())
+        );
+    }
+
+    #[test]
+    fn test_revert_reason_unsupported_proof_type() {
+        let data = revert_data("UnsupportedProofType(bytes32)", 1);
+        assert_eq!(RevertReason::from_revert_data(&data), RevertReason::UnsupportedProofType);
+    }
+
+    #[test]
+    fn test_revert_reason_proof_already_verified() {
+        let data = revert_data("ProofAlreadyVerified(bytes32)", 1);
+        assert_eq!(RevertReason::from_revert_data(&data), RevertReason::ProofAlreadyVerified);
+    }
+
+    #[test]
+    fn test_revert_reason_verification_failed() {
+        let data = revert_data("VerificationFailed()", 0);
+        assert_eq!(RevertReason::from_revert_data(&data), RevertReason::VerificationFailed);
+    }
+
+    #[test]
+    fn test_revert_reason_invalid_verifier_address() {
+        let data = revert_data("InvalidVerifierAddress()", 0);
+        assert_eq!(RevertReason::from_revert_data(&data), RevertReason::InvalidVerifierAddress);
+    }
+
+    #[test]
+    fn test_revert_reason_claim_already_recorded() {
+        let data = revert_data("ClaimAlreadyRecorded(bytes32)", 1);
+        assert_eq!(RevertReason::from_revert_data(&data), RevertReason::ClaimAlreadyRecorded);
+    }
+
+    #[test]
+    fn test_revert_reason_claim_not_found() {
+        let data = revert_data("ClaimNotFound(bytes32)", 1);
+        assert_eq!(RevertReason::from_revert_data(&data), RevertReason::ClaimNotFound);
+    }
+
+    #[test]
+    fn test_revert_reason_not_claim_agent() {
+        let data = revert_data("NotClaimAgent(address,address)", 2);
+        assert_eq!(RevertReason::from_revert_data(&data), RevertReason::NotClaimAgent);
+    }
+
+    #[tokio::test]
+    async fn test_poll_attestation_job_times_out() {
+        // An attester whose job never leaves the queue
+        let app = axum::Router::new().route(
+            "/attest/jobs/:id",
+            axum::routing::get(|| async { axum::Json(Envelope::new(AttestJobStatus::Queued)) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let job_id = AttestJobId("job-1".to_string());
+        let interval = std::time::Duration::from_millis(20);
+        let timeout = std::time::Duration::from_millis(200);
+        let error = poll_attestation_job(&reqwest::Client::new(), &url, &job_id, interval, timeout)
+            .await
+            .unwrap_err();
+        let timed_out = error.downcast_ref::<JobTimedOut>().expect("a JobTimedOut error");
+        assert_eq!(timed_out.job_id, job_id);
+        assert_eq!(timed_out.timeout, timeout);
+    }
+
+    #[tokio::test]
+    async fn test_submit_on_chain_reports_estimate_revert() {
+        // A node on which verifyProof reverts with InvalidProof()
+        let invalid_proof = revert_data("InvalidProof()", 0);
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move |axum::Json(request): axum::Json<serde_json::Value>| async move {
+                assert_eq!(request["method"], "eth_estimateGas");
+                axum::Json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": { "code": 3, "message": "execution reverted", "data": invalid_proof },
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let rpc = RpcPool::new(vec![url.clone()]).unwrap();
+        let key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+        let queue = tx_queue::TxQueue::new(&url, key).unwrap();
+        let result = submit_on_chain(
+            &reqwest::Client::new(),
+            "0x9C33252D29B41Fe2706704a8Ca99E8731B58af41",
+            &rpc,
+            &queue,
+            None,
+            &fees::FeeStrategy::default(),
+            "0x11b6a09d00",
+            "0x00",
+            &format!("0x{}", "00".repeat(32)),
+            &ClaimBuilder::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!result.verified);
+        assert_eq!(result.details, Some(RevertReason::InvalidProof.to_string()));
+        assert!(result.tx_hash.is_none());
+        assert!(queue.transactions(None).is_empty());
+    }
 }
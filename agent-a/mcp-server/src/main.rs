@@ -1,60 +1,1149 @@
-/// Agent A MCP Server - JSON-RPC (stdio) + HTTP API
-///
-/// Dual-protocol server:
-/// 1. JSON-RPC over stdin/stdout (for direct MCP protocol)
-/// 2. HTTP endpoints (for remote/network access)
-///
-/// Run with HTTP: AGENT_A_MODE=http ./agent-a-mcp
-/// Run with MCP:  ./agent-a-mcp (default)
+//! Agent A MCP Server - JSON-RPC (stdio) + HTTP API
+//!
+//! Dual-protocol server:
+//! 1. JSON-RPC over stdin/stdout (for direct MCP protocol)
+//! 2. HTTP endpoints (for remote/network access)
+//!
+//! Run with HTTP: AGENT_A_MODE=http ./agent-a-mcp
+//! Run with MCP:  ./agent-a-mcp (default)
 
 use anyhow::{Result, anyhow};
 use axum::{
-    extract::Json,
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Extension, Json, Path, Query, State},
+    http::{header, StatusCode},
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse,
+    },
     routing::{get, post},
     Router,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use futures::Stream;
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::io::{self, BufRead};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
 use tower_http::cors::CorsLayer;
+use tower_http::trace::TraceLayer;
+
+use agent_config::{ConfigError, Validate};
+use agent_tls::TlsConfig;
+use openapi_spec::{swagger_ui_html, SpecBuilder};
+use telemetry::correlation;
+use tool_envelope::ToolResponse;
+
+mod auth;
+use auth::{AuthConfig, Principal};
 
 use agent_a_mcp::{
-    PricingInput,
-    verify_on_chain, get_ticket_price, format_zk_input, request_attestation,
+    AttestBookingResult, AttestCheapestResult, AttestRefundResult, AttestPriceResult, BookingInput, BookingResponse, ClaimArgs,
+    ClaimBuilder, PriceMismatch, FlightOptionsInput, FlightOptionsResponse, PipelineEndpoints, PricingInput, PricingResponse, ProofBundle, RpcPool,
+    ClaimRecord, RecordClaimResult, VerificationResult, verify_on_chain, submit_on_chain, record_claim, get_claim,
+    BatchProof, BatchVerification, SessionVerification, verify_batch_on_chain, submit_batch_on_chain,
+    verify_offchain, verify_offchain_hex,
+    get_ticket_price, format_zk_input, encode_verify_proof_call,
+    request_attestation, fetch_chain_id, attest_price, attest_booking, attest_refund, book_flight, anchor_on_chain,
+    get_flight_options, attest_cheapest_option,
+    new_attest_request, proof_mode_of, MOCK_PROOF_ERROR, submit_attestation_job, fetch_attestation_job,
+    activity::{ActivityEvent, ActivityFeed},
+    audit::{self, AuditEvent, AuditKind, AuditStore},
+    booking_state::{self, BookingState, BookingStateStore},
+    approval::{Approvals, PendingAction},
+    proof_chain::{self, SessionAnchor},
+    proof_store::{
+        self, ChainVerification, ProofFilter, ProofStore, RetentionPolicy, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE,
+    },
+    session_bundle::{ChainMetadata, DeniedAction, SessionBundle, SessionProof},
+    session_guard::{RateLimited, SessionGuard},
+    breaker::{BreakerState, Breakers},
+    chain_fixtures::{self, LocalChain},
+    chain_indexer::{self, ClaimFilter, ClaimIndex},
+    fees::{self, FeeSpeed, FeeStrategy},
+    metrics::Metrics,
+    tx_queue::TxQueue,
+    tap::{self, TapKeyStore},
+    crypto_payment::{self, CryptoPayment, CryptoPaymentStatus, PaymentLedger, PaymentTerms},
+    AgentIdentity, AttestJobId, AttestJobStatus, ClaimType,
 };
 
-/// Agent A Server - holds tool implementations
-#[derive(Clone)]
-struct AgentAMcp {
-    agent_b_url: Arc<String>,
-    attester_url: Arc<String>,
-    zeroproof_addr: Arc<String>,
-    rpc_url: Arc<String>,
-}
-
-impl AgentAMcp {
-    fn new() -> Self {
-        Self {
-            agent_b_url: Arc::new(
-                std::env::var("AGENT_B_URL")
-                    .unwrap_or_else(|_| "http://localhost:8001".to_string()),
-            ),
-            attester_url: Arc::new(
-                std::env::var("ATTESTER_URL")
-                    .unwrap_or_else(|_| "http://localhost:8000".to_string()),
+/// Parsed configuration (read once at startup)
+///
+/// Loaded from AGENT_A_CONFIG (default `agent-a.toml`, optional) with env overrides,
+/// e.g. `agent_b_url = "..."` in the file or AGENT_B_URL in the environment.
+#[derive(Debug, Deserialize)]
+struct Config {
+    #[serde(default = "default_agent_b_url")]
+    agent_b_url: String,
+    #[serde(default = "default_attester_url")]
+    attester_url: String,
+    #[serde(rename = "zeroproof_address", default = "default_zeroproof_address")]
+    zeroproof_addr: String,
+    /// Multicall3 contract batches of verifyProof calls go through
+    #[serde(default = "default_multicall3_address")]
+    multicall3_address: String,
+    /// "sepolia", or "local" to deploy the contracts on Anvil at startup
+    #[serde(default)]
+    chain_mode: ChainMode,
+    /// Anvil node for local mode (unset = spawn `anvil`)
+    anvil_url: Option<String>,
+    /// Foundry artifact ZeroProof is deployed from in local mode
+    #[serde(default = "default_zeroproof_artifact")]
+    zeroproof_artifact: String,
+    /// Comma-separated RPC endpoints (takes precedence over rpc_url)
+    rpc_urls: Option<String>,
+    rpc_url: Option<String>,
+    agent_b_did: Option<String>,
+    /// Key used to sign verifyProof transactions (submit mode)
+    signer_private_key: Option<String>,
+    /// Tip percentile for transactions: slow, normal, or fast
+    #[serde(default)]
+    agent_a_fee_speed: FeeSpeed,
+    /// Cap on maxFeePerGas in gwei (unset = uncapped)
+    agent_a_max_fee_gwei: Option<u64>,
+    /// 32-byte hex Ed25519 seeds for TAP signatures on calls to Agent B, comma-separated;
+    /// all are published, the last one signs (unset = unsigned)
+    agent_a_signing_key: Option<String>,
+    /// Generate a new TAP signing key this often (unset = no rotation)
+    agent_a_tap_rotation_secs: Option<u64>,
+    /// How long a rotated-out TAP key stays in the JWKS
+    #[serde(default = "default_tap_key_grace_secs")]
+    agent_a_tap_key_grace_secs: u64,
+    /// "http" or "jsonrpc"
+    #[serde(default = "default_mode")]
+    agent_a_mode: String,
+    #[serde(default = "default_port")]
+    agent_a_server_port: u16,
+    /// Bearer tokens as `principal:token,...` (unset disables auth)
+    agent_a_api_tokens: Option<String>,
+    /// TLS_CERT / TLS_KEY / TLS_CLIENT_CA for the HTTP listener; TLS_CA /
+    /// TLS_CLIENT_CERT / TLS_CLIENT_KEY for calls to Agent B and the attester
+    #[serde(flatten)]
+    tls: TlsConfig,
+    #[serde(default = "default_breaker_threshold")]
+    breaker_threshold: u32,
+    #[serde(default = "default_breaker_cooldown_secs")]
+    breaker_cooldown_secs: u64,
+    /// Hold book_flight / attest_booking / attest_refund for confirmation via POST /chat/approve
+    #[serde(default)]
+    agent_a_require_approval: bool,
+    #[serde(default = "default_approval_ttl_secs")]
+    agent_a_approval_ttl_secs: u64,
+    /// Calls per minute per session_id (0 = unlimited)
+    #[serde(default = "default_session_rate_limit")]
+    agent_a_session_rate_limit: u32,
+    /// Attest/book/request_attestation calls per minute across all callers (0 = unlimited)
+    #[serde(default = "default_global_rate_limit")]
+    agent_a_global_rate_limit: u32,
+    /// SQLite file for session proofs and booking states (unset keeps them in memory)
+    agent_a_proof_db: Option<String>,
+    /// Seconds between background on-chain checks of unverified proofs (0 = off)
+    #[serde(default = "default_verify_interval_secs")]
+    agent_a_verify_interval_secs: u64,
+    /// Proofs checked per background pass
+    #[serde(default = "default_verify_batch")]
+    agent_a_verify_batch: usize,
+    /// Retention: drop proofs older than this many seconds
+    agent_a_proof_max_age_secs: Option<u64>,
+    /// Retention: keep only the newest N proofs per session
+    agent_a_proof_max_per_session: Option<usize>,
+    /// Retention: cap on the serialized size of all stored proofs
+    agent_a_proof_max_bytes: Option<u64>,
+    /// Seconds between retention sweeps
+    #[serde(default = "default_retention_sweep_secs")]
+    agent_a_retention_sweep_secs: u64,
+    /// WebSocket RPC to follow ZeroProof events on for GET /claims (unset = indexer off)
+    agent_a_index_ws_url: Option<String>,
+    /// SQLite file for indexed events (unset keeps them in memory)
+    agent_a_index_db: Option<String>,
+    /// Block to backfill from on an empty index (the contract's deployment block)
+    #[serde(default)]
+    agent_a_index_from_block: u64,
+    /// Address crypto payments are made to (unset = create_crypto_payment off)
+    agent_a_crypto_pay_to: Option<String>,
+    /// ERC-20 stablecoin crypto payments are made in
+    agent_a_crypto_token: Option<String>,
+    #[serde(default = "default_crypto_token_symbol")]
+    agent_a_crypto_token_symbol: String,
+    #[serde(default = "default_crypto_token_decimals")]
+    agent_a_crypto_token_decimals: u32,
+    /// How long a crypto payment request stays payable
+    #[serde(default = "default_crypto_payment_ttl_secs")]
+    agent_a_crypto_payment_ttl_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ChainMode {
+    #[default]
+    Sepolia,
+    Local,
+}
+
+fn default_zeroproof_artifact() -> String {
+    chain_fixtures::DEFAULT_ARTIFACT.to_string()
+}
+
+fn default_agent_b_url() -> String {
+    "http://localhost:8001".to_string()
+}
+
+fn default_attester_url() -> String {
+    "http://localhost:8000".to_string()
+}
+
+fn default_zeroproof_address() -> String {
+    "0x9C33252D29B41Fe2706704a8Ca99E8731B58af41".to_string()
+}
+
+fn default_multicall3_address() -> String {
+    agent_a_mcp::MULTICALL3_ADDRESS.to_string()
+}
+
+fn default_mode() -> String {
+    "http".to_string()
+}
+
+fn default_port() -> u16 {
+    3001
+}
+
+fn default_breaker_threshold() -> u32 {
+    5
+}
+
+fn default_breaker_cooldown_secs() -> u64 {
+    30
+}
+
+fn default_approval_ttl_secs() -> u64 {
+    300
+}
+
+fn default_session_rate_limit() -> u32 {
+    30
+}
+
+fn default_global_rate_limit() -> u32 {
+    300
+}
+
+fn default_verify_interval_secs() -> u64 {
+    60
+}
+
+fn default_verify_batch() -> usize {
+    10
+}
+
+fn default_retention_sweep_secs() -> u64 {
+    300
+}
+
+fn default_tap_key_grace_secs() -> u64 {
+    // Longer than a TAP signature can live
+    600
+}
+
+fn default_crypto_token_symbol() -> String {
+    "USDC".to_string()
+}
+
+fn default_crypto_token_decimals() -> u32 {
+    6
+}
+
+fn default_crypto_payment_ttl_secs() -> u64 {
+    900
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Config {
+    fn load() -> Result<Self> {
+        Ok(agent_config::load("AGENT_A_CONFIG", "agent-a.toml")?)
+    }
+
+    /// Point RPC, contract, and signer at a local chain
+    fn use_local_chain(&mut self, chain: &LocalChain) {
+        self.rpc_urls = Some(chain.rpc_url.clone());
+        self.zeroproof_addr = chain.zeroproof_addr.clone();
+        self.signer_private_key = Some(chain.private_key.clone());
+    }
+
+    fn retention(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            max_age_secs: self.agent_a_proof_max_age_secs,
+            max_per_session: self.agent_a_proof_max_per_session,
+            max_total_bytes: self.agent_a_proof_max_bytes,
+        }
+    }
+
+    fn signing_seeds(&self) -> impl Iterator<Item = &str> {
+        self.agent_a_signing_key
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|seed| !seed.is_empty())
+    }
+
+    /// TAP key store from the configured seeds; `None` when signing is off
+    fn tap_keys(&self) -> Result<Option<TapKeyStore>> {
+        let identities = self
+            .signing_seeds()
+            .map(AgentIdentity::from_seed_hex)
+            .collect::<Result<Vec<_>, _>>()?;
+        if identities.is_empty() {
+            return Ok(None);
+        }
+        let store = TapKeyStore::new(identities, unix_now());
+        Ok(Some(match self.agent_a_tap_rotation_secs {
+            Some(every) => store.with_rotation(
+                std::time::Duration::from_secs(every),
+                std::time::Duration::from_secs(self.agent_a_tap_key_grace_secs),
             ),
-            zeroproof_addr: Arc::new(
-                std::env::var("ZEROPROOF_ADDRESS")
-                    .unwrap_or_else(|_| "0x9C33252D29B41Fe2706704a8Ca99E8731B58af41".to_string()),
+            None => store,
+        }))
+    }
+
+    fn fee_strategy(&self) -> FeeStrategy {
+        FeeStrategy { speed: self.agent_a_fee_speed, max_fee_gwei: self.agent_a_max_fee_gwei }
+    }
+
+    /// Token and recipient of crypto payments on `chain_id`; `None` when not configured
+    fn payment_terms(&self, chain_id: u64) -> Result<Option<PaymentTerms>> {
+        let (Some(recipient), Some(token)) = (&self.agent_a_crypto_pay_to, &self.agent_a_crypto_token) else {
+            return Ok(None);
+        };
+        Ok(Some(PaymentTerms {
+            chain_id,
+            token: token.parse()?,
+            symbol: self.agent_a_crypto_token_symbol.clone(),
+            decimals: self.agent_a_crypto_token_decimals,
+            recipient: recipient.parse()?,
+        }))
+    }
+
+    /// RPC endpoint list (rpc_urls, else rpc_url)
+    fn rpc_list(&self) -> &str {
+        self.rpc_urls
+            .as_deref()
+            .or(self.rpc_url.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self) -> Result<(), ConfigError> {
+        agent_config::check_url("agent_b_url", &self.agent_b_url)?;
+        agent_config::check_url("attester_url", &self.attester_url)?;
+        self.tls.validate().map_err(|e| agent_config::invalid("tls", e.to_string()))?;
+        agent_config::check_hex("zeroproof_address", &self.zeroproof_addr, 20)?;
+        agent_config::check_hex("multicall3_address", &self.multicall3_address, 20)?;
+
+        // Local mode fills these in once Anvil is up
+        if self.chain_mode == ChainMode::Sepolia {
+            agent_config::check_present("rpc_urls (or rpc_url)", Some(self.rpc_list()))?;
+        }
+        if let Some(url) = &self.anvil_url {
+            agent_config::check_url("anvil_url", url)?;
+        }
+        for url in self.rpc_list().split(',').map(str::trim).filter(|u| !u.is_empty()) {
+            agent_config::check_url("rpc_urls", url)?;
+        }
+
+        if let Some(key) = &self.signer_private_key {
+            agent_config::check_hex("signer_private_key", key, 32)?;
+        }
+        for seed in self.signing_seeds() {
+            agent_config::check_hex("agent_a_signing_key", seed, 32)?;
+        }
+        if self.agent_a_tap_rotation_secs == Some(0) {
+            return Err(agent_config::invalid("agent_a_tap_rotation_secs", "must be at least 1"));
+        }
+        if let Some(url) = &self.agent_a_index_ws_url {
+            if !url.starts_with("ws://") && !url.starts_with("wss://") {
+                return Err(agent_config::invalid("agent_a_index_ws_url", "must be a ws:// or wss:// URL"));
+            }
+        }
+        if let Some(address) = &self.agent_a_crypto_pay_to {
+            agent_config::check_hex("agent_a_crypto_pay_to", address, 20)?;
+        }
+        if let Some(address) = &self.agent_a_crypto_token {
+            agent_config::check_hex("agent_a_crypto_token", address, 20)?;
+        }
+        if self.agent_a_crypto_pay_to.is_some() != self.agent_a_crypto_token.is_some() {
+            return Err(agent_config::invalid(
+                "agent_a_crypto_token",
+                "set together with agent_a_crypto_pay_to",
+            ));
+        }
+        if self.agent_a_crypto_token_decimals < 2 {
+            return Err(agent_config::invalid("agent_a_crypto_token_decimals", "must be at least 2"));
+        }
+        if !matches!(self.agent_a_mode.as_str(), "http" | "jsonrpc") {
+            return Err(agent_config::invalid("agent_a_mode", "must be \"http\" or \"jsonrpc\""));
+        }
+        Ok(())
+    }
+}
+
+/// Agent A Server state - shared by every handler via `Arc`
+struct AppState {
+    config: Config,
+    /// Pooled client for Agent B and RPC calls
+    http: reqwest::Client,
+    /// Pooled client with a long timeout for proof generation
+    attester_http: reqwest::Client,
+    /// RPC endpoints with failover
+    rpc: RpcPool,
+    /// Chain ID of RPC_URL, fetched on first use
+    chain_id: tokio::sync::OnceCell<u64>,
+    /// Attestation jobs submitted over HTTP
+    attestation_jobs: RwLock<HashMap<AttestJobId, PendingAttestation>>,
+    /// Circuit breakers for Agent B, the attester, and RPC
+    breakers: Breakers,
+    /// Bookings held for confirmation (agent_a_require_approval)
+    approvals: Approvals<HeldAction>,
+    /// Proofs recorded per `session_id`, exported by GET /sessions/{id}/proof-bundle
+    /// and queried by GET /proofs
+    proofs: Box<dyn ProofStore>,
+    /// Proofs deleted by retention sweeps since startup
+    proofs_swept: AtomicU64,
+    /// ZeroProof events indexed by `chain_indexer` (empty while it's off)
+    claims: Arc<ClaimIndex>,
+    /// Sends every transaction of SIGNER_PRIVATE_KEY's wallet (unset = read-only)
+    tx_queue: Option<TxQueue>,
+    /// Live events for GET /sessions/{id}/events
+    activity: ActivityFeed,
+    /// Rate limits, and one call at a time per session
+    guard: SessionGuard,
+    /// Crypto payment requests (create_crypto_payment)
+    payments: PaymentLedger,
+    /// Payments refused by the client's spending policy, per session; exported with the
+    /// proof bundle
+    denied_actions: RwLock<HashMap<String, Vec<DeniedAction>>>,
+    /// Latest booking progress per chat session, for GET /session/{id}
+    booking_states: Box<dyn BookingStateStore>,
+    /// Decisions, tool calls, and activity per session, for GET /sessions/{id}/audit
+    audit: Box<dyn AuditStore>,
+    /// Chat, tool, proof, and funnel counters for GET /metrics
+    metrics: Metrics,
+}
+
+/// A side-effecting HTTP call waiting in `approvals`
+enum HeldAction {
+    BookFlight(BookFlightRequest),
+    AttestBooking(AttestBookingRequest),
+    AttestRefund(AttestRefundRequest),
+}
+
+/// What's needed to turn a finished job into a proof bundle
+struct PendingAttestation {
+    program_id: String,
+    elf_hash: String,
+    session_id: Option<String>,
+    /// Caller that queued the job; the proof is attributed to them, not to whoever polls
+    principal: Option<String>,
+    /// Correlation ID of the request that queued it
+    request_id: Option<String>,
+}
+
+/// How often job status is re-read from the attester for SSE streams
+const JOB_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+impl AppState {
+    fn new(config: Config) -> Result<Self> {
+        let rpc = RpcPool::from_list(config.rpc_list())?;
+        let http = config.tls.client(reqwest::Client::builder())?.timeout(std::time::Duration::from_secs(60)).build()?;
+        let attester_http =
+            config.tls.client(reqwest::Client::builder())?.timeout(std::time::Duration::from_secs(7200)).build()?;
+        Ok(Self {
+            tx_queue: config
+                .signer_private_key
+                .as_deref()
+                .map(|key| TxQueue::new(rpc.next_url(), key))
+                .transpose()?,
+            rpc,
+            breakers: Breakers::new(
+                config.breaker_threshold,
+                std::time::Duration::from_secs(config.breaker_cooldown_secs),
             ),
-            rpc_url: Arc::new(
-                std::env::var("RPC_URL")
-                    .unwrap_or_else(|_| "https://sepolia.infura.io/v3/abc123".to_string()),
+            approvals: Approvals::new(std::time::Duration::from_secs(config.agent_a_approval_ttl_secs)),
+            guard: SessionGuard::new(config.agent_a_session_rate_limit, config.agent_a_global_rate_limit),
+            proofs: proof_store::open(config.agent_a_proof_db.as_deref())?,
+            booking_states: booking_state::open(config.agent_a_proof_db.as_deref())?,
+            audit: audit::open(config.agent_a_proof_db.as_deref())?,
+            metrics: Metrics::new()?,
+            proofs_swept: AtomicU64::new(0),
+            claims: Arc::new(ClaimIndex::open(config.agent_a_index_db.as_deref())?),
+            config,
+            http,
+            attester_http,
+            chain_id: tokio::sync::OnceCell::new(),
+            attestation_jobs: RwLock::new(HashMap::new()),
+            activity: ActivityFeed::new(),
+            payments: PaymentLedger::new(),
+            denied_actions: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Execute a booking call, directly or once its pending action is approved
+    async fn run_held_action(&self, action: HeldAction) -> Result<Value> {
+        let (tool, session_id) = match &action {
+            HeldAction::BookFlight(req) => ("book_flight", req.session_id.clone()),
+            HeldAction::AttestBooking(req) => ("attest_booking", req.session_id.clone()),
+            HeldAction::AttestRefund(req) => ("attest_refund", req.session_id.clone()),
+        };
+        let session_id = session_id.as_deref();
+        let _turn = self.guard.turn(session_id).await;
+        self.publish(session_id, ActivityEvent::ToolStarted { tool: tool.to_string() });
+
+        let result = match action {
+            HeldAction::BookFlight(req) => {
+                let call = book_flight(&self.http, &self.config.agent_b_url, &req.input, self.config.agent_b_did.as_deref());
+                self.breakers.agent_b.call(call).await.and_then(|booking| {
+                    self.publish(session_id, ActivityEvent::BookingConfirmed {
+                        booking_id: booking.booking_id.clone(),
+                        confirmation_code: booking.confirmation_code.clone(),
+                    });
+                    Ok(serde_json::to_value(booking)?)
+                })
+            }
+            HeldAction::AttestBooking(req) => {
+                match attest_booking(&self.http, &self.attester_http, &self.endpoints(), &req.input, req.verify_on_chain).await {
+                    Ok(result) => {
+                        self.record_proof(session_id, SessionProof {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            tool: "attest_booking".to_string(),
+                            kind: "booking".to_string(),
+                            claim: json!({
+                                "booking_id": result.booking_id,
+                                "status": result.status,
+                                "confirmation_code": result.confirmation_code
+                            }),
+                            bundle: result.proof_bundle.clone(),
+                            verified: result.verified,
+                            prev_hash: None,
+                            principal: None,
+                            request_id: None,
+                        });
+                        self.publish(session_id, ActivityEvent::BookingConfirmed {
+                            booking_id: result.booking_id.clone(),
+                            confirmation_code: result.confirmation_code.clone(),
+                        });
+                        serde_json::to_value(result).map_err(Into::into)
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            HeldAction::AttestRefund(req) => {
+                match attest_refund(&self.http, &self.attester_http, &self.endpoints(), &req.booking_id, req.verify_on_chain).await {
+                    Ok(result) => {
+                        self.record_proof(session_id, SessionProof {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            tool: "attest_refund".to_string(),
+                            kind: "refund".to_string(),
+                            claim: json!({
+                                "booking_id": result.booking_id,
+                                "fee_cents": result.fee_cents,
+                                "refund_cents": result.refund_cents,
+                                "currency": result.currency
+                            }),
+                            bundle: result.proof_bundle.clone(),
+                            verified: result.verified,
+                            prev_hash: None,
+                            principal: None,
+                            request_id: None,
+                        });
+                        serde_json::to_value(result).map_err(Into::into)
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+        };
+
+        self.publish_tool_result(session_id, tool, &result);
+        result
+    }
+
+    /// Announce `event` to live subscribers and add it to the session's audit log
+    fn publish(&self, session_id: Option<&str>, event: ActivityEvent) {
+        self.metrics.observe_activity(&event);
+        if let Some(session_id) = session_id {
+            let event = serde_json::to_value(&event).unwrap_or_default();
+            self.record_audit(session_id, AuditEvent::new("agent_a", AuditKind::Activity { event }));
+        }
+        self.activity.publish(session_id, event);
+    }
+
+    /// Append to the session's audit log; a failure is logged, never surfaced to the caller
+    fn record_audit(&self, session_id: &str, mut event: AuditEvent) -> Option<AuditEvent> {
+        event.at = unix_now();
+        self.metrics.observe_audit(&event.kind);
+        match self.audit.append(session_id, event) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                tracing::error!("Failed to append to the audit log of session {}: {}", session_id, e);
+                None
+            }
+        }
+    }
+
+    /// Payments denied in the session so far, oldest first
+    fn denied_actions_for(&self, session_id: &str) -> Vec<DeniedAction> {
+        self.denied_actions.read().unwrap().get(session_id).cloned().unwrap_or_default()
+    }
+
+    /// Add a proof to the session's export and announce it to live subscribers
+    ///
+    /// Unless already set, the proof is attributed to the authenticated caller of the
+    /// current request and tagged with its correlation ID.
+    fn record_proof(&self, session_id: Option<&str>, mut proof: SessionProof) {
+        let Some(session_id) = session_id else {
+            return;
+        };
+        if proof.principal.is_none() {
+            proof.principal = auth::current();
+        }
+        if proof.request_id.is_none() {
+            proof.request_id = correlation::current();
+        }
+        let event = ActivityEvent::ProofCollected {
+            kind: proof.kind.clone(),
+            vk_hash: proof.bundle.vk_hash.clone(),
+            verified: proof.verified,
+        };
+        match self.proofs.record(session_id, proof) {
+            Ok(true) => self.publish(Some(session_id), event),
+            Ok(false) => {}
+            Err(e) => tracing::error!("Failed to store proof for session {}: {}", session_id, e),
+        }
+    }
+
+    /// Keep a proof that contradicted the quote as a "mismatch" entry and alert subscribers;
+    /// other errors are ignored
+    fn record_mismatch(&self, session_id: Option<&str>, tool: &str, error: &anyhow::Error) {
+        let Some(mismatch) = error.downcast_ref::<PriceMismatch>() else {
+            return;
+        };
+        self.publish(session_id, ActivityEvent::ClaimMismatch {
+            tool: tool.to_string(),
+            mismatches: mismatch.mismatches.clone(),
+        });
+        self.record_proof(session_id, SessionProof {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool: tool.to_string(),
+            kind: "mismatch".to_string(),
+            claim: json!({ "mismatches": mismatch.mismatches }),
+            bundle: mismatch.proof_bundle.clone(),
+            verified: Some(false),
+            prev_hash: None,
+            principal: None,
+            request_id: None,
+        });
+    }
+
+    fn publish_tool_result<T>(&self, session_id: Option<&str>, tool: &str, result: &Result<T>) {
+        self.publish(session_id, ActivityEvent::ToolResult {
+            tool: tool.to_string(),
+            success: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+    }
+
+    /// Probe subsystems whose breaker is open; a healthy probe closes the breaker
+    async fn probe_open_breakers(&self) {
+        for breaker in self.breakers.all() {
+            if breaker.state() == BreakerState::Closed {
+                continue;
+            }
+            let probe = match breaker.name() {
+                "agent_b" => self.probe_health(&self.config.agent_b_url).await,
+                "attester" => self.probe_health(&self.config.attester_url).await,
+                _ => fetch_chain_id(&self.http, &self.rpc).await.map(|_| ()),
+            };
+            match probe {
+                Ok(()) => breaker.record_success(),
+                Err(e) => breaker.record_failure(&format!("health probe failed: {}", e)),
+            }
+        }
+    }
+
+    async fn probe_health(&self, base_url: &str) -> Result<()> {
+        self.http
+            .get(format!("{}/health", base_url))
+            .timeout(std::time::Duration::from_secs(5))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Attester job status (with the proof bundle once done), and whether it's terminal
+    async fn attestation_job(&self, job_id: &AttestJobId) -> Result<(bool, Value)> {
+        let status = self
+            .breakers
+            .attester
+            .call(fetch_attestation_job(&self.attester_http, &self.config.attester_url, job_id))
+            .await?;
+
+        let bundle = match &status {
+            AttestJobStatus::Done(response) => {
+                let jobs = self.attestation_jobs.read().unwrap();
+                let job = jobs.get(job_id);
+                let (program_id, elf_hash) = job
+                    .map(|job| (job.program_id.as_str(), job.elf_hash.as_str()))
+                    .unwrap_or_default();
+                let bundle = ProofBundle::from_attestation(response, program_id, elf_hash, proof_mode_of(response)).ok();
+                // Recorded on every poll, deduped by job id
+                if let Some(bundle) = &bundle {
+                    self.record_proof(job.and_then(|job| job.session_id.as_deref()), SessionProof {
+                        id: job_id.to_string(),
+                        tool: "request_attestation".to_string(),
+                        kind: "attestation".to_string(),
+                        claim: json!({ "verified_output": response.verified_output }),
+                        bundle: bundle.clone(),
+                        verified: None,
+                        prev_hash: None,
+                        principal: job.and_then(|job| job.principal.clone()),
+                        request_id: job.and_then(|job| job.request_id.clone()),
+                    });
+                }
+                bundle
+            }
+            _ => None,
+        };
+
+        let terminal = status.is_terminal();
+        let mut view = serde_json::to_value(&status)?;
+        view["job_id"] = json!(job_id);
+        if let Some(bundle) = bundle {
+            view["proof_bundle"] = serde_json::to_value(bundle)?;
+        }
+        Ok((terminal, view))
+    }
+
+    fn endpoints(&self) -> PipelineEndpoints<'_> {
+        PipelineEndpoints {
+            agent_b_url: &self.config.agent_b_url,
+            attester_url: &self.config.attester_url,
+            zeroproof_addr: &self.config.zeroproof_addr,
+            rpc: &self.rpc,
+            agent_b_did: self.config.agent_b_did.as_deref(),
+            breakers: &self.breakers,
+        }
+    }
+
+    /// Verify a proof via eth_call, or record it on-chain with a signed transaction if `submit`
+    ///
+    /// An eth_call that verifies also estimates what `submit` would cost, so the user can
+    /// approve the fee before anything is sent.
+    async fn verify_proof(
+        &self,
+        proof: &str,
+        public_values: &str,
+        vk_hash: &str,
+        submit: bool,
+        speed: Option<FeeSpeed>,
+        claim: &ClaimBuilder,
+    ) -> Result<Value> {
+        let fees = self.config.fee_strategy().with_speed(speed);
+        if submit {
+            // Don't pay gas for a proof the verifier would reject
+            let (p, pv, vk) = (proof.to_string(), public_values.to_string(), vk_hash.to_string());
+            let check = tokio::task::spawn_blocking(move || verify_offchain_hex(&p, &pv, &vk)).await??;
+            if !check.verified {
+                return Err(anyhow!(
+                    "Proof failed off-chain verification, no transaction sent: {}",
+                    check.error.unwrap_or_default()
+                ));
+            }
+        }
+        let mut result = self.check_on_chain(proof, public_values, vk_hash, submit, None, &fees, claim).await?;
+        if !submit && result.verified {
+            let call_data = encode_verify_proof_call(proof, public_values, vk_hash, claim)?;
+            let from = self.tx_queue.as_ref().map(|queue| format!("{:#x}", queue.address()));
+            match fees::estimate(&self.http, &self.rpc, from.as_deref(), &self.config.zeroproof_addr, &call_data, &fees)
+                .await
+            {
+                Ok(cost) => result.cost_estimate = Some(cost),
+                Err(e) => tracing::warn!("Could not estimate verifyProof cost: {}", e),
+            }
+        }
+
+        let mut value = serde_json::to_value(&result)?;
+        value["message"] = json!(match (&result.cost_estimate, result.verified, submit) {
+            (_, false, _) => "✗ Proof verification failed".to_string(),
+            (Some(cost), true, false) => format!(
+                "✓ Proof verified on-chain; recording it (submit=true) would cost about {} ETH, at most {} ETH",
+                cost.expected_cost_eth, cost.max_cost_eth
             ),
+            _ => "✓ Proof verified on-chain".to_string(),
+        });
+        Ok(value)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn check_on_chain(
+        &self,
+        proof: &str,
+        public_values: &str,
+        vk_hash: &str,
+        submit: bool,
+        label: Option<&str>,
+        fees: &FeeStrategy,
+        claim: &ClaimBuilder,
+    ) -> Result<VerificationResult> {
+        Ok(if submit {
+            let queue = self
+                .tx_queue
+                .as_ref()
+                .ok_or_else(|| anyhow!("submit=true requires SIGNER_PRIVATE_KEY"))?;
+            self.breakers
+                .rpc
+                .call(submit_on_chain(
+                    &self.http,
+                    &self.config.zeroproof_addr,
+                    &self.rpc,
+                    queue,
+                    label,
+                    fees,
+                    proof,
+                    public_values,
+                    vk_hash,
+                    claim,
+                ))
+                .await?
+        } else {
+            self.breakers
+                .rpc
+                .call(verify_on_chain(
+                    &self.http,
+                    &self.config.zeroproof_addr,
+                    &self.rpc,
+                    proof,
+                    public_values,
+                    vk_hash,
+                    claim,
+                ))
+                .await?
+        })
+    }
+
+    /// Record a proven claim in the ZeroProof registry (needs SIGNER_PRIVATE_KEY)
+    async fn record_claim(
+        &self,
+        proof: &str,
+        public_values: &str,
+        vk_hash: &str,
+        speed: Option<FeeSpeed>,
+        claim: &ClaimBuilder,
+    ) -> Result<RecordClaimResult> {
+        let queue = self
+            .tx_queue
+            .as_ref()
+            .ok_or_else(|| anyhow!("record_claim requires SIGNER_PRIVATE_KEY"))?;
+        self
+            .breakers
+            .rpc
+            .call(record_claim(
+                &self.http,
+                &self.config.zeroproof_addr,
+                &self.rpc,
+                queue,
+                &self.config.fee_strategy().with_speed(speed),
+                proof,
+                public_values,
+                vk_hash,
+                claim,
+            ))
+            .await
+    }
+
+    async fn get_claim(&self, claim_id: &str) -> Result<Option<ClaimRecord>> {
+        self
+            .breakers
+            .rpc
+            .call(get_claim(&self.http, &self.config.zeroproof_addr, &self.rpc, claim_id))
+            .await
+    }
+
+    /// Verify every on-chain-compatible proof of a session in one Multicall3 batch
+    ///
+    /// With `submit`, the proofs that pass are recorded in one transaction. Results are
+    /// stored on the proofs, as the background verifier does.
+    async fn verify_session_on_chain(
+        &self,
+        session_id: &str,
+        submit: bool,
+        speed: Option<FeeSpeed>,
+    ) -> Result<SessionVerification> {
+        let proofs = self
+            .proofs
+            .session(session_id)?
+            .ok_or_else(|| anyhow!("No proofs recorded for session {}", session_id))?;
+        let (checkable, mocks): (Vec<_>, Vec<_>) = proofs.into_iter().partition(SessionProof::onchain_compatible);
+        let batch: Vec<BatchProof> = checkable
+            .iter()
+            .map(|proof| BatchProof {
+                id: proof.id.clone(),
+                proof_hex: format!("0x{}", hex::encode(&proof.bundle.proof)),
+                public_values_hex: format!("0x{}", hex::encode(&proof.bundle.public_values)),
+                vk_hash: proof.bundle.vk_hash.clone(),
+                claim: session_claim(proof),
+            })
+            .collect();
+
+        let results = if batch.is_empty() {
+            Vec::new()
+        } else if submit {
+            let queue = self
+                .tx_queue
+                .as_ref()
+                .ok_or_else(|| anyhow!("submit=true requires SIGNER_PRIVATE_KEY"))?;
+            self.breakers
+                .rpc
+                .call(submit_batch_on_chain(
+                    &self.http,
+                    &self.config.zeroproof_addr,
+                    &self.config.multicall3_address,
+                    &self.rpc,
+                    queue,
+                    Some(session_id),
+                    &self.config.fee_strategy().with_speed(speed),
+                    &batch,
+                ))
+                .await?
+        } else {
+            self.breakers
+                .rpc
+                .call(verify_batch_on_chain(
+                    &self.http,
+                    &self.config.zeroproof_addr,
+                    &self.config.multicall3_address,
+                    &self.rpc,
+                    &batch,
+                ))
+                .await?
+        };
+
+        let checked_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        for BatchVerification { id, result } in &results {
+            let verification = ChainVerification {
+                verified: result.verified,
+                tx_hash: result.tx_hash.clone(),
+                block_number: result.block_number,
+                error: result.error.clone(),
+                checked_at,
+            };
+            if let Err(e) = self.proofs.set_verification(session_id, id, &verification) {
+                tracing::error!("Failed to store verification of proof {}: {}", id, e);
+                continue;
+            }
+            self.publish(Some(session_id), ActivityEvent::ProofVerified {
+                proof_id: id.clone(),
+                verified: result.verified,
+                tx_hash: result.tx_hash.clone(),
+            });
+        }
+
+        let verified = results.iter().filter(|r| r.result.verified).count();
+        Ok(SessionVerification {
+            session_id: session_id.to_string(),
+            verified,
+            failed: results.len() - verified,
+            skipped: mocks.into_iter().map(|proof| proof.id).collect(),
+            results,
+        })
+    }
+
+    /// Apply the retention policy to the proof store
+    fn sweep_proofs(&self) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        match self.proofs.sweep(&self.config.retention(), now) {
+            Ok(0) => {}
+            Ok(removed) => {
+                self.proofs_swept.fetch_add(removed as u64, Ordering::Relaxed);
+                tracing::info!("Retention sweep removed {} proofs", removed);
+            }
+            Err(e) => tracing::error!("Retention sweep failed: {}", e),
+        }
+    }
+
+    /// Check a batch of recorded proofs that have no on-chain status yet
+    ///
+    /// Sends transactions when SIGNER_PRIVATE_KEY is set, else uses eth_call. Proofs whose
+    /// check errors (RPC down, breaker open) stay pending and are retried next pass.
+    async fn verify_pending_proofs(&self) {
+        let pending = match self.proofs.pending_verification(self.config.agent_a_verify_batch) {
+            Ok(pending) => pending,
+            Err(e) => {
+                tracing::error!("Failed to read pending proofs: {}", e);
+                return;
+            }
+        };
+        let submit = self.tx_queue.is_some();
+
+        for stored in pending {
+            let proof = &stored.proof;
+            if proof.bundle.is_mock() {
+                // Never verifiable; record that once rather than retrying every round
+                let verification = ChainVerification {
+                    verified: false,
+                    tx_hash: None,
+                    block_number: None,
+                    error: Some(MOCK_PROOF_ERROR.to_string()),
+                    checked_at: std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or_default(),
+                };
+                if let Err(e) = self.proofs.set_verification(&stored.session_id, &proof.id, &verification) {
+                    tracing::error!("Failed to store verification of proof {}: {}", proof.id, e);
+                }
+                continue;
+            }
+            let claim = session_claim(proof);
+            let result = self
+                .check_on_chain(
+                    &format!("0x{}", hex::encode(&proof.bundle.proof)),
+                    &format!("0x{}", hex::encode(&proof.bundle.public_values)),
+                    &proof.bundle.vk_hash,
+                    submit,
+                    Some(&proof.id),
+                    &self.config.fee_strategy(),
+                    &claim,
+                )
+                .await;
+            let result = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    tracing::warn!("Background verification of proof {} deferred: {}", proof.id, e);
+                    continue;
+                }
+            };
+
+            let verification = ChainVerification {
+                verified: result.verified,
+                tx_hash: result.tx_hash.clone(),
+                block_number: result.block_number,
+                error: result.error,
+                checked_at: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+            };
+            if let Err(e) = self.proofs.set_verification(&stored.session_id, &proof.id, &verification) {
+                tracing::error!("Failed to store verification of proof {}: {}", proof.id, e);
+                continue;
+            }
+            self.publish(Some(&stored.session_id), ActivityEvent::ProofVerified {
+                proof_id: proof.id.clone(),
+                verified: result.verified,
+                tx_hash: result.tx_hash,
+            });
+        }
+    }
+
+    /// Cached chain ID (None if the RPC endpoint is unreachable)
+    async fn chain_id(&self) -> Option<u64> {
+        self.chain_id
+            .get_or_try_init(|| self.breakers.rpc.call(fetch_chain_id(&self.http, &self.rpc)))
+            .await
+            .ok()
+            .copied()
+    }
+
+    /// Request a crypto payment of `amount_cents` USD, payable from the current block
+    async fn create_crypto_payment(
+        &self,
+        amount_cents: u64,
+        currency: Option<&str>,
+        session_id: Option<&str>,
+    ) -> Result<CryptoPayment> {
+        if let Some(currency) = currency.filter(|c| !c.eq_ignore_ascii_case("USD")) {
+            return Err(anyhow!("Crypto payments are priced in USD, not {}", currency));
         }
+        if amount_cents == 0 {
+            return Err(anyhow!("amount_cents must be positive"));
+        }
+        let chain_id = self
+            .chain_id()
+            .await
+            .ok_or_else(|| anyhow!("RPC endpoint unreachable; can't create a crypto payment"))?;
+        let terms = self
+            .config
+            .payment_terms(chain_id)?
+            .ok_or_else(|| anyhow!("Crypto payments require AGENT_A_CRYPTO_PAY_TO and AGENT_A_CRYPTO_TOKEN"))?;
+        let from_block = self
+            .breakers
+            .rpc
+            .call(crypto_payment::latest_block(&self.http, &self.rpc))
+            .await?;
+
+        let payment = self.payments.create(
+            &terms,
+            amount_cents,
+            session_id,
+            from_block,
+            unix_now(),
+            self.config.agent_a_crypto_payment_ttl_secs,
+        )?;
+        self.publish(session_id, ActivityEvent::PaymentStep {
+            step: "crypto_payment_requested".to_string(),
+            detail: json!({"payment_id": payment.id, "amount": payment.amount, "uri": payment.uri}),
+        });
+        Ok(payment)
+    }
+
+    /// Current state of a crypto payment, checking the chain while it's pending
+    async fn get_crypto_payment(&self, payment_id: &str) -> Result<CryptoPayment> {
+        let payment = self
+            .payments
+            .get(payment_id)
+            .ok_or_else(|| anyhow!("No crypto payment {}", payment_id))?;
+        if payment.status != CryptoPaymentStatus::Pending {
+            return Ok(payment);
+        }
+
+        // Past the deadline, only a transfer mined by then still pays
+        let now = unix_now();
+        let mined_by = (now >= payment.expires_at).then_some(payment.expires_at);
+        let exclude = self.payments.settled_txs();
+        let found = self
+            .breakers
+            .rpc
+            .call(crypto_payment::find_transfer(&self.http, &self.rpc, &payment, &exclude, mined_by))
+            .await?;
+        if let Some(paid) = found.and_then(|transfer| self.payments.settle(payment_id, &transfer)) {
+            tracing::info!("✓ Crypto payment {} settled by {:?}", paid.id, paid.tx_hash);
+            self.publish(paid.session_id.as_deref(), ActivityEvent::PaymentStep {
+                step: "crypto_payment_settled".to_string(),
+                detail: json!({"payment_id": paid.id, "tx_hash": paid.tx_hash, "payer": paid.payer}),
+            });
+            return Ok(paid);
+        }
+        self.payments
+            .expire_if_due(payment_id, now)
+            .ok_or_else(|| anyhow!("No crypto payment {}", payment_id))
     }
 
     /// List all available tools
@@ -69,7 +1158,39 @@ impl AgentAMcp {
                         "properties": {
                             "from": {"type": "string"},
                             "to": {"type": "string"},
-                            "vip": {"type": "boolean"}
+                            "vip": {"type": "boolean"},
+                            "departure_date": {"type": "string", "description": "YYYY-MM-DD"},
+                            "return_date": {"type": "string", "description": "YYYY-MM-DD; prices the round trip in one quote"},
+                            "currency": {"type": "string", "description": "USD, EUR, GBP, or JPY (default USD)"},
+                            "cabin": {"type": "string", "enum": ["economy", "premium", "business", "first"]},
+                            "fare_family": {"type": "string", "enum": ["non_refundable", "refundable"]},
+                            "promo_code": {"type": "string"}
+                        }
+                    }
+                },
+                {
+                    "name": "get_flight_options",
+                    "description": "Compare routes and dates at Agent B in one call and get the cheapest option",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "options": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "from": {"type": "string"},
+                                        "to": {"type": "string"},
+                                        "vip": {"type": "boolean"},
+                                        "departure_date": {"type": "string", "description": "YYYY-MM-DD"},
+                                        "return_date": {"type": "string", "description": "YYYY-MM-DD"},
+                                        "currency": {"type": "string", "description": "USD, EUR, GBP, or JPY; the same for every option"},
+                                        "cabin": {"type": "string", "enum": ["economy", "premium", "business", "first"]},
+                                        "fare_family": {"type": "string", "enum": ["non_refundable", "refundable"]},
+                                        "promo_code": {"type": "string"}
+                                    }
+                                }
+                            }
                         }
                     }
                 },
@@ -92,54 +1213,298 @@ impl AgentAMcp {
                         "properties": {
                             "program_id": {"type": "string"},
                             "input_hex": {"type": "string"},
-                            "claimed_output": {"type": "string"}
+                            "claimed_output": {"type": "string"},
+                            "elf_hash": {"type": "string"}
                         }
                     }
                 },
                 {
-                    "name": "verify_on_chain",
-                    "description": "Verify ZK proof on Sepolia blockchain",
+                    "name": "attest_price",
+                    "description": "Get a price from Agent B and prove it: fetches zkVM input, requests attestation, and optionally verifies on-chain (takes 11-27 minutes)",
                     "inputSchema": {
                         "type": "object",
                         "properties": {
-                            "proof": {"type": "string"},
-                            "public_values": {"type": "string"},
-                            "vk_hash": {"type": "string"}
+                            "from": {"type": "string"},
+                            "to": {"type": "string"},
+                            "vip": {"type": "boolean"},
+                            "departure_date": {"type": "string", "description": "YYYY-MM-DD"},
+                            "return_date": {"type": "string", "description": "YYYY-MM-DD; prices the round trip in one quote"},
+                            "currency": {"type": "string", "description": "USD, EUR, GBP, or JPY (default USD)"},
+                            "cabin": {"type": "string", "enum": ["economy", "premium", "business", "first"]},
+                            "fare_family": {"type": "string", "enum": ["non_refundable", "refundable"]},
+                            "promo_code": {"type": "string"},
+                            "verify_on_chain": {"type": "boolean"}
                         }
                     }
-                }
-            ]
-        })
-    }
-
-    /// Call a tool and return result
-    async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value> {
-        match name {
-            "get_ticket_price" => {
-                let from = arguments
-                    .get("from")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("NYC");
-                let to = arguments
-                    .get("to")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("LON");
-                let vip = arguments
-                    .get("vip")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false);
-
-                let input = PricingInput {
-                    from: from.to_string(),
-                    to: to.to_string(),
-                    vip,
-                };
-
-                match get_ticket_price(&self.agent_b_url, &input).await {
+                },
+                {
+                    "name": "attest_cheapest_option",
+                    "description": "Compare routes and dates at Agent B and prove the comparison, cheapest included, in one zkVM execution (takes 11-27 minutes)",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "options": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "from": {"type": "string"},
+                                        "to": {"type": "string"},
+                                        "vip": {"type": "boolean"},
+                                        "departure_date": {"type": "string", "description": "YYYY-MM-DD"},
+                                        "return_date": {"type": "string", "description": "YYYY-MM-DD"},
+                                        "currency": {"type": "string", "description": "USD, EUR, GBP, or JPY; the same for every option"},
+                                        "cabin": {"type": "string", "enum": ["economy", "premium", "business", "first"]},
+                                        "fare_family": {"type": "string", "enum": ["non_refundable", "refundable"]},
+                                        "promo_code": {"type": "string"}
+                                    }
+                                }
+                            },
+                            "verify_on_chain": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "book_flight",
+                    "description": "Book a flight through Agent B",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "from": {"type": "string"},
+                            "to": {"type": "string"},
+                            "passenger_name": {"type": "string"},
+                            "passenger_email": {"type": "string"},
+                            "cabin": {"type": "string", "enum": ["economy", "premium", "business", "first"]},
+                            "fare_family": {"type": "string", "enum": ["non_refundable", "refundable"]}
+                        }
+                    }
+                },
+                {
+                    "name": "attest_booking",
+                    "description": "Book a flight through Agent B and prove it: fetches zkVM input, requests attestation, and returns the proof bundle (takes 11-27 minutes)",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "from": {"type": "string"},
+                            "to": {"type": "string"},
+                            "passenger_name": {"type": "string"},
+                            "passenger_email": {"type": "string"},
+                            "cabin": {"type": "string", "enum": ["economy", "premium", "business", "first"]},
+                            "fare_family": {"type": "string", "enum": ["non_refundable", "refundable"]},
+                            "verify_on_chain": {"type": "boolean"}
+                        }
+                    }
+                },
+                {
+                    "name": "attest_refund",
+                    "description": "Cancel a booking through Agent B and prove the refund: the cancellation fee is re-executed in the zkVM and the proven refund must match Agent B's (takes 11-27 minutes)",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "booking_id": {"type": "string"},
+                            "verify_on_chain": {"type": "boolean"}
+                        },
+                        "required": ["booking_id"]
+                    }
+                },
+                {
+                    "name": "verify_on_chain",
+                    "description": "Verify ZK proof on Sepolia blockchain. Without submit, a valid proof comes back with cost_estimate for recording it; tell the user the cost and get their approval before calling again with submit=true",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "proof": {"type": "string"},
+                            "public_values": {"type": "string"},
+                            "vk_hash": {"type": "string"},
+                            "submit": {
+                                "type": "boolean",
+                                "description": "Send a signed transaction instead of eth_call (records the proof on-chain)"
+                            },
+                            "fee_speed": {
+                                "type": "string",
+                                "enum": ["slow", "normal", "fast"],
+                                "description": "Priority fee level for the transaction (default: AGENT_A_FEE_SPEED)"
+                            },
+                            "agent_address": {
+                                "type": "string",
+                                "description": "Claim agent address (default: zero address)"
+                            },
+                            "claim_type": {
+                                "type": "string",
+                                "description": "pricing, booking, payment, or a 0x bytes32 (default: pricing)"
+                            },
+                            "public_data": {
+                                "type": "string",
+                                "description": "Hex claim public data (default: public_values)"
+                            },
+                            "data_hash": {
+                                "type": "string",
+                                "description": "Hex bytes32 data hash (default: keccak256(public_data))"
+                            }
+                        }
+                    }
+                },
+                {
+                    "name": "record_claim",
+                    "description": "Verify a ZK proof and record its claim in the ZeroProof registry (sends a transaction)",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "proof": {"type": "string"},
+                            "public_values": {"type": "string"},
+                            "vk_hash": {"type": "string"},
+                            "fee_speed": {
+                                "type": "string",
+                                "enum": ["slow", "normal", "fast"],
+                                "description": "Priority fee level for the transaction (default: AGENT_A_FEE_SPEED)"
+                            },
+                            "agent_address": {
+                                "type": "string",
+                                "description": "Claim agent address; only the signer can record (default: signer address)"
+                            },
+                            "claim_type": {
+                                "type": "string",
+                                "description": "pricing, booking, payment, or a 0x bytes32 (default: pricing)"
+                            },
+                            "public_data": {
+                                "type": "string",
+                                "description": "Hex claim public data (default: public_values)"
+                            },
+                            "data_hash": {
+                                "type": "string",
+                                "description": "Hex bytes32 data hash (default: keccak256(public_data))"
+                            }
+                        },
+                        "required": ["proof", "public_values", "vk_hash"]
+                    }
+                },
+                {
+                    "name": "get_claim",
+                    "description": "Look up a claim recorded in the ZeroProof registry",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "claim_id": {
+                                "type": "string",
+                                "description": "0x bytes32 claim id returned by record_claim"
+                            }
+                        },
+                        "required": ["claim_id"]
+                    }
+                },
+                {
+                    "name": "verify_offchain",
+                    "description": "Check a proof with the Rust SP1 verifier, no RPC needed. Pass proof_bundle, an exported session_bundle, or proof/public_values/vk_hash",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "proof": {"type": "string"},
+                            "public_values": {"type": "string"},
+                            "vk_hash": {"type": "string"},
+                            "proof_bundle": {
+                                "type": "object",
+                                "description": "A proof_bundle as returned by the attest tools"
+                            },
+                            "session_bundle": {
+                                "type": "object",
+                                "description": "A bundle from GET /sessions/{id}/proof-bundle; every proof, the manifest, and the Merkle root are checked"
+                            }
+                        }
+                    }
+                },
+                {
+                    "name": "create_crypto_payment",
+                    "description": "Request payment of a USD amount in the configured stablecoin; returns an EIP-681 URI and a payment_id to poll with get_crypto_payment before booking",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "amount_cents": {"type": "integer", "description": "Price to pay, in cents"},
+                            "currency": {"type": "string", "description": "Must be USD (default)"},
+                            "session_id": {
+                                "type": "string",
+                                "description": "Session whose proof bundle lists the payment once settled"
+                            }
+                        },
+                        "required": ["amount_cents"]
+                    }
+                },
+                {
+                    "name": "get_crypto_payment",
+                    "description": "Status of a crypto payment (pending, paid, expired); checks the chain for the transfer while pending",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "payment_id": {"type": "string"}
+                        },
+                        "required": ["payment_id"]
+                    }
+                },
+                {
+                    "name": "verify_session_on_chain",
+                    "description": "Verify every proof recorded for a session on-chain in one Multicall3 batch, with a result per proof",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "session_id": {"type": "string"},
+                            "submit": {
+                                "type": "boolean",
+                                "description": "Record the valid proofs in one signed transaction instead of eth_call"
+                            },
+                            "fee_speed": {
+                                "type": "string",
+                                "enum": ["slow", "normal", "fast"],
+                                "description": "Priority fee level for the transaction (default: AGENT_A_FEE_SPEED)"
+                            }
+                        },
+                        "required": ["session_id"]
+                    }
+                }
+            ]
+        })
+    }
+
+    /// Call a tool and return result
+    async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        match name {
+            "get_ticket_price" => {
+                let from = arguments
+                    .get("from")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("NYC");
+                let to = arguments
+                    .get("to")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("LON");
+                let vip = arguments
+                    .get("vip")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                let input = PricingInput {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    vip,
+                    departure_date: arguments.get("departure_date").and_then(|v| v.as_str()).map(str::to_string),
+                    return_date: arguments.get("return_date").and_then(|v| v.as_str()).map(str::to_string),
+                    currency: arguments.get("currency").and_then(|v| v.as_str()).map(str::to_string),
+                    cabin: arguments.get("cabin").and_then(|v| v.as_str()).map(str::to_string),
+                    fare_family: arguments.get("fare_family").and_then(|v| v.as_str()).map(str::to_string),
+                    promo_code: arguments.get("promo_code").and_then(|v| v.as_str()).map(str::to_string),
+                };
+
+                let call = get_ticket_price(&self.http, &self.config.agent_b_url, &input, self.config.agent_b_did.as_deref());
+                match self.breakers.agent_b.call(call).await {
                     Ok(response) => Ok(json!({
-                        "price": response.price,
+                        "price_cents": response.price_cents,
+                        "currency": response.currency,
+                        "promo_code": response.promo_code,
+                        "discount_cents": response.discount_cents,
+                        "purchase_date": response.purchase_date,
+                        "quote_id": response.quote_id,
+                        "expires_at": response.expires_at,
                         "program_id": response.program_id,
-                        "elf_hash": response.elf_hash
+                        "elf_hash": response.elf_hash,
+                        "signer": response.signer
                     })),
                     Err(e) => Err(anyhow!("Agent B call failed: {}", e)),
                 }
@@ -152,7 +1517,8 @@ impl AgentAMcp {
                     .unwrap_or("default");
                 let input = arguments.get("input").cloned().unwrap_or(json!({}));
 
-                match format_zk_input(&self.agent_b_url, endpoint, &input).await {
+                let call = format_zk_input(&self.http, &self.config.agent_b_url, endpoint, &input);
+                match self.breakers.agent_b.call(call).await {
                     Ok(result) => Ok(json!({
                         "input_hex": result.input_bytes,
                         "length": result.input_array.len()
@@ -175,19 +1541,28 @@ impl AgentAMcp {
                     .map_err(|e| anyhow!("Invalid hex: {}", e))?;
                 let claimed_output = arguments.get("claimed_output").cloned();
 
-                match request_attestation(
-                    &self.attester_url,
+                let elf_hash = arguments
+                    .get("elf_hash")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+
+                let call = request_attestation(
+                    &self.attester_http,
+                    &self.config.attester_url,
                     program_id,
                     input_bytes,
                     claimed_output,
                     true,
-                )
-                .await
-                {
-                    Ok(response) => Ok(json!({
-                        "verified_output": response.verified_output,
-                        "vk_hash": response.vk_hash
-                    })),
+                );
+                match self.breakers.attester.call(call).await {
+                    Ok(response) => {
+                        let bundle = ProofBundle::from_attestation(&response, program_id, elf_hash, proof_mode_of(&response))?;
+                        Ok(json!({
+                            "verified_output": response.verified_output,
+                            "vk_hash": response.vk_hash,
+                            "proof_bundle": bundle
+                        }))
+                    }
                     Err(e) => Err(anyhow!("Attestation request failed: {}", e)),
                 }
             }
@@ -206,384 +1581,1893 @@ impl AgentAMcp {
                     .and_then(|v| v.as_str())
                     .unwrap_or("0x");
 
-                match verify_on_chain(&self.zeroproof_addr, &self.rpc_url, proof, public_values, vk_hash).await {
-                    Ok(verified) => Ok(json!({
-                        "verified": verified,
-                        "message": if verified {
-                            "✓ Proof verified on-chain"
-                        } else {
-                            "✗ Proof verification failed"
-                        }
-                    })),
-                    Err(e) => Err(anyhow!("On-chain verification error: {}", e)),
+                let submit = arguments
+                    .get("submit")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+
+                let speed = arguments
+                    .get("fee_speed")
+                    .map(|v| serde_json::from_value::<FeeSpeed>(v.clone()))
+                    .transpose()?;
+
+                let claim = serde_json::from_value::<ClaimArgs>(arguments.clone())?.into_builder()?;
+
+                self.verify_proof(proof, public_values, vk_hash, submit, speed, &claim)
+                    .await
+                    .map_err(|e| anyhow!("On-chain verification error: {}", e))
+            }
+
+            "record_claim" => {
+                let arg = |key: &str| arguments.get(key).and_then(|v| v.as_str()).unwrap_or("0x");
+                let speed = arguments
+                    .get("fee_speed")
+                    .map(|v| serde_json::from_value::<FeeSpeed>(v.clone()))
+                    .transpose()?;
+                let claim = serde_json::from_value::<ClaimArgs>(arguments.clone())?.into_builder()?;
+
+                let result = self
+                    .record_claim(arg("proof"), arg("public_values"), arg("vk_hash"), speed, &claim)
+                    .await
+                    .map_err(|e| anyhow!("Recording claim failed: {}", e))?;
+                Ok(serde_json::to_value(result)?)
+            }
+
+            "get_claim" => {
+                let claim_id = arguments
+                    .get("claim_id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing claim_id"))?;
+
+                match self.get_claim(claim_id).await? {
+                    Some(record) => Ok(serde_json::to_value(record)?),
+                    None => Err(anyhow!("No claim recorded under {}", claim_id)),
+                }
+            }
+
+            "verify_offchain" => {
+                let req = serde_json::from_value::<VerifyOffchainRequest>(arguments.clone())?;
+                verify_offchain_request(req).await
+            }
+
+            "create_crypto_payment" => {
+                let req = serde_json::from_value::<CreateCryptoPaymentRequest>(arguments.clone())?;
+                let payment = self
+                    .create_crypto_payment(req.amount_cents, req.currency.as_deref(), req.session_id.as_deref())
+                    .await?;
+                Ok(serde_json::to_value(payment)?)
+            }
+
+            "get_crypto_payment" => {
+                let payment_id = arguments
+                    .get("payment_id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing payment_id"))?;
+                Ok(serde_json::to_value(self.get_crypto_payment(payment_id).await?)?)
+            }
+
+            "verify_session_on_chain" => {
+                let session_id = arguments
+                    .get("session_id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing session_id"))?;
+                let submit = arguments.get("submit").and_then(|v| v.as_bool()).unwrap_or(false);
+                let speed = arguments
+                    .get("fee_speed")
+                    .map(|v| serde_json::from_value::<FeeSpeed>(v.clone()))
+                    .transpose()?;
+
+                let result = self
+                    .verify_session_on_chain(session_id, submit, speed)
+                    .await
+                    .map_err(|e| anyhow!("Session verification error: {}", e))?;
+                Ok(serde_json::to_value(result)?)
+            }
+
+            "attest_price" => {
+                let input = PricingInput {
+                    from: arguments.get("from").and_then(|v| v.as_str()).unwrap_or("NYC").to_string(),
+                    to: arguments.get("to").and_then(|v| v.as_str()).unwrap_or("LON").to_string(),
+                    vip: arguments.get("vip").and_then(|v| v.as_bool()).unwrap_or(false),
+                    departure_date: arguments.get("departure_date").and_then(|v| v.as_str()).map(str::to_string),
+                    return_date: arguments.get("return_date").and_then(|v| v.as_str()).map(str::to_string),
+                    currency: arguments.get("currency").and_then(|v| v.as_str()).map(str::to_string),
+                    cabin: arguments.get("cabin").and_then(|v| v.as_str()).map(str::to_string),
+                    fare_family: arguments.get("fare_family").and_then(|v| v.as_str()).map(str::to_string),
+                    promo_code: arguments.get("promo_code").and_then(|v| v.as_str()).map(str::to_string),
+                };
+                let verify_onchain = arguments
+                    .get("verify_on_chain")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+
+                match attest_price(&self.http, &self.attester_http, &self.endpoints(), &input, verify_onchain).await {
+                    Ok(result) => Ok(serde_json::to_value(result)?),
+                    Err(e) => Err(anyhow!("attest_price failed: {}", e)),
+                }
+            }
+
+            "get_flight_options" => {
+                let input: FlightOptionsInput = serde_json::from_value(arguments)
+                    .map_err(|e| anyhow!("Invalid flight options input: {}", e))?;
+
+                let call = get_flight_options(&self.http, &self.config.agent_b_url, &input, self.config.agent_b_did.as_deref());
+                match self.breakers.agent_b.call(call).await {
+                    Ok(options) => Ok(serde_json::to_value(options)?),
+                    Err(e) => Err(anyhow!("Agent B call failed: {}", e)),
+                }
+            }
+
+            "attest_cheapest_option" => {
+                let verify_onchain = arguments
+                    .get("verify_on_chain")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                let input: FlightOptionsInput = serde_json::from_value(arguments)
+                    .map_err(|e| anyhow!("Invalid flight options input: {}", e))?;
+
+                match attest_cheapest_option(&self.http, &self.attester_http, &self.endpoints(), &input, verify_onchain).await {
+                    Ok(result) => Ok(serde_json::to_value(result)?),
+                    Err(e) => Err(anyhow!("attest_cheapest_option failed: {}", e)),
+                }
+            }
+
+            "book_flight" => {
+                let input: BookingInput = serde_json::from_value(arguments)
+                    .map_err(|e| anyhow!("Invalid booking input: {}", e))?;
+
+                let call = book_flight(&self.http, &self.config.agent_b_url, &input, self.config.agent_b_did.as_deref());
+                match self.breakers.agent_b.call(call).await {
+                    Ok(booking) => Ok(serde_json::to_value(booking)?),
+                    Err(e) => Err(anyhow!("Failed to book via Agent B: {}", e)),
+                }
+            }
+
+            "attest_booking" => {
+                let verify_onchain = arguments
+                    .get("verify_on_chain")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let input: BookingInput = serde_json::from_value(arguments)
+                    .map_err(|e| anyhow!("Invalid booking input: {}", e))?;
+
+                match attest_booking(&self.http, &self.attester_http, &self.endpoints(), &input, verify_onchain).await {
+                    Ok(result) => Ok(serde_json::to_value(result)?),
+                    Err(e) => Err(anyhow!("attest_booking failed: {}", e)),
+                }
+            }
+
+            "attest_refund" => {
+                let verify_onchain = arguments
+                    .get("verify_on_chain")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let booking_id = arguments
+                    .get("booking_id")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| anyhow!("Missing booking_id"))?;
+
+                match attest_refund(&self.http, &self.attester_http, &self.endpoints(), booking_id, verify_onchain).await {
+                    Ok(result) => Ok(serde_json::to_value(result)?),
+                    Err(e) => Err(anyhow!("attest_refund failed: {}", e)),
+                }
+            }
+
+            _ => Err(anyhow!("Unknown tool: {}", name)),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Logs go to stderr so they never interleave with JSON-RPC on stdout
+    let _telemetry = telemetry::init("agent-a", true);
+
+    let mut config = Config::load()?;
+
+    // Kept alive for the life of the process; a spawned Anvil stops when it drops
+    let _local_chain = match config.chain_mode {
+        ChainMode::Local => {
+            let chain = chain_fixtures::start(config.anvil_url.as_deref(), &config.zeroproof_artifact).await?;
+            config.use_local_chain(&chain);
+            Some(chain)
+        }
+        ChainMode::Sepolia => None,
+    };
+
+    if let Some(keys) = config.tap_keys()? {
+        tracing::info!("✓ Signing Agent B calls (TAP) with {}", keys.active_kids().join(", "));
+        tap::install(keys);
+        // Rotation check; a no-op unless agent_a_tap_rotation_secs is set
+        tokio::spawn(async {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+            loop {
+                interval.tick().await;
+                if let Some(keys) = tap::keys() {
+                    keys.rotate_if_due(unix_now());
                 }
             }
+        });
+    }
+
+    // Check if running in HTTP mode or JSON-RPC mode
+    match config.agent_a_mode.as_str() {
+        "jsonrpc" => run_jsonrpc_server(config).await,
+        _ => start_http_server(config).await,
+    }
+}
+
+async fn run_jsonrpc_server(config: Config) -> Result<()> {
+    let server = AppState::new(config)?;
+    let stdin = io::stdin();
+    let mut reader = stdin.lock().lines();
+
+    // Read JSON-RPC messages from stdin
+    while let Some(Ok(line)) = reader.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Parse JSON-RPC request
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle_jsonrpc(&server, request).await,
+            Err(e) => Some(jsonrpc_error(json!(null), -32700, format!("Parse error: {}", e))),
+        };
+
+        // Notifications get no response
+        if let Some(response) = response {
+            println!("{}", response);
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle one MCP JSON-RPC message; `None` for notifications
+async fn handle_jsonrpc(server: &AppState, request: Value) -> Option<Value> {
+    let method = request.get("method").and_then(|v| v.as_str()).unwrap_or_default();
+    let id = match request.get("id") {
+        Some(id) => id.clone(),
+        None => {
+            if method != "notifications/initialized" {
+                tracing::debug!("Ignoring notification {}", method);
+            }
+            return None;
+        }
+    };
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {"tools": {}},
+            "serverInfo": {
+                "name": "Agent A",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        }),
+
+        "ping" => json!({}),
+
+        "tools/list" => server.list_tools(),
+
+        "tools/call" => {
+            let params = request.get("params").cloned().unwrap_or(json!({}));
+            let Some(tool_name) = params.get("name").and_then(|v| v.as_str()) else {
+                return Some(jsonrpc_error(id, -32602, "tools/call requires params.name".to_string()));
+            };
+            let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+            // Tool failures are results with isError, not protocol errors
+            match server.call_tool(tool_name, arguments).await {
+                Ok(result) => tool_result(serde_json::to_string_pretty(&result).unwrap_or_default(), false),
+                Err(e) => tool_result(e.to_string(), true),
+            }
+        }
+
+        _ => return Some(jsonrpc_error(id, -32601, format!("Method not found: {}", method))),
+    };
+
+    Some(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result
+    }))
+}
+
+/// MCP revision implemented by the stdio mode
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({
+        "content": [{
+            "type": "text",
+            "text": text
+        }],
+        "isError": is_error
+    })
+}
+
+fn jsonrpc_error(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message
+        }
+    })
+}
+
+/// HTTP request types
+#[derive(Debug, Deserialize, JsonSchema)]
+struct CallAgentBRequest {
+    from: String,
+    to: String,
+    vip: bool,
+    /// YYYY-MM-DD
+    #[serde(default)]
+    departure_date: Option<String>,
+    /// YYYY-MM-DD; prices the round trip in one quote
+    #[serde(default)]
+    return_date: Option<String>,
+    /// ISO 4217 code to quote in (USD if unset)
+    #[serde(default)]
+    currency: Option<String>,
+    #[serde(default)]
+    cabin: Option<String>,
+    #[serde(default)]
+    fare_family: Option<String>,
+    #[serde(default)]
+    promo_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct FormatZkInputRequest {
+    endpoint: String,
+    input: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct RequestAttestationRequest {
+    program_id: String,
+    input_hex: String,
+    #[serde(default)]
+    claimed_output: Option<String>,
+    #[serde(default)]
+    elf_hash: Option<String>,
+    /// Record the finished proof under this session (GET /sessions/{id}/proof-bundle)
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AttestPriceRequest {
+    from: String,
+    to: String,
+    #[serde(default)]
+    vip: bool,
+    /// YYYY-MM-DD
+    #[serde(default)]
+    departure_date: Option<String>,
+    /// YYYY-MM-DD; prices the round trip in one quote
+    #[serde(default)]
+    return_date: Option<String>,
+    /// ISO 4217 code to quote in (USD if unset)
+    #[serde(default)]
+    currency: Option<String>,
+    #[serde(default)]
+    cabin: Option<String>,
+    #[serde(default)]
+    fare_family: Option<String>,
+    #[serde(default)]
+    promo_code: Option<String>,
+    #[serde(default = "default_true")]
+    verify_on_chain: bool,
+    /// Record the proof under this session (GET /sessions/{id}/proof-bundle)
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+/// Body of a 202 for a call held by `agent_a_require_approval`
+#[derive(Debug, Serialize, JsonSchema)]
+struct ApprovalRequired {
+    pending_action: PendingAction,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+struct AnchorRequest {
+    /// Also send the root on-chain (requires SIGNER_PRIVATE_KEY)
+    #[serde(default)]
+    on_chain: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ApproveRequest {
+    /// `pending_action.id` from the held response
+    id: String,
+    /// true to confirm (the action runs now), false to deny
+    approve: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct BookFlightRequest {
+    #[serde(flatten)]
+    input: BookingInput,
+    /// Publish progress to GET /sessions/{id}/events
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AttestBookingRequest {
+    #[serde(flatten)]
+    input: BookingInput,
+    #[serde(default)]
+    verify_on_chain: bool,
+    /// Record the proof under this session (GET /sessions/{id}/proof-bundle)
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AttestRefundRequest {
+    /// Agent B booking to cancel
+    booking_id: String,
+    #[serde(default)]
+    verify_on_chain: bool,
+    /// Record the refund proof under this session (GET /sessions/{id}/proof-bundle)
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct FlightOptionsRequest {
+    #[serde(flatten)]
+    input: FlightOptionsInput,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AttestCheapestRequest {
+    #[serde(flatten)]
+    input: FlightOptionsInput,
+    #[serde(default = "default_true")]
+    verify_on_chain: bool,
+    /// Record the proof under this session (GET /sessions/{id}/proof-bundle)
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct VerifyOnChainRequest {
+    proof: String,
+    public_values: String,
+    vk_hash: String,
+    #[serde(default)]
+    submit: bool,
+    /// Overrides AGENT_A_FEE_SPEED
+    fee_speed: Option<FeeSpeed>,
+    #[serde(flatten)]
+    claim: ClaimArgs,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct RecordClaimRequest {
+    proof: String,
+    public_values: String,
+    vk_hash: String,
+    /// Overrides AGENT_A_FEE_SPEED
+    fee_speed: Option<FeeSpeed>,
+    #[serde(flatten)]
+    claim: ClaimArgs,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetClaimRequest {
+    claim_id: String,
+}
+
+/// One of: `session_bundle`, `proof_bundle`, or `proof` + `public_values` + `vk_hash`
+#[derive(Debug, Deserialize, JsonSchema)]
+struct VerifyOffchainRequest {
+    proof: Option<String>,
+    public_values: Option<String>,
+    vk_hash: Option<String>,
+    /// ProofBundle JSON
+    proof_bundle: Option<Value>,
+    /// Exported session proof bundle JSON
+    session_bundle: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct VerifySessionRequest {
+    session_id: String,
+    /// Record the valid proofs in one transaction instead of eth_call
+    #[serde(default)]
+    submit: bool,
+    /// Overrides AGENT_A_FEE_SPEED
+    fee_speed: Option<FeeSpeed>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct CreateCryptoPaymentRequest {
+    amount_cents: u64,
+    /// Must be USD
+    currency: Option<String>,
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetCryptoPaymentRequest {
+    payment_id: String,
+}
+
+/// Run `verify_offchain` on whichever form the request carries, off the async runtime
+async fn verify_offchain_request(req: VerifyOffchainRequest) -> Result<Value> {
+    tokio::task::spawn_blocking(move || {
+        if let Some(bundle) = req.session_bundle {
+            let bundle: SessionBundle = serde_json::from_value(bundle)?;
+            return Ok(serde_json::to_value(bundle.audit())?);
+        }
+        if let Some(bundle) = req.proof_bundle {
+            let bundle: ProofBundle = serde_json::from_value(bundle)?;
+            return Ok(serde_json::to_value(verify_offchain(&bundle))?);
+        }
+        match (req.proof, req.public_values, req.vk_hash) {
+            (Some(proof), Some(public_values), Some(vk_hash)) => {
+                Ok(serde_json::to_value(verify_offchain_hex(&proof, &public_values, &vk_hash)?)?)
+            }
+            _ => Err(anyhow!("Pass session_bundle, proof_bundle, or proof with public_values and vk_hash")),
+        }
+    })
+    .await?
+}
+
+/// Claim a recorded proof is verified under: booking proofs as bookings, the rest as pricing
+fn session_claim(proof: &SessionProof) -> ClaimBuilder {
+    match proof.kind.as_str() {
+        "booking" => ClaimBuilder::new().claim_type(ClaimType::Booking),
+        _ => ClaimBuilder::new(),
+    }
+}
+
+// HTTP Handlers
+async fn health(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    Json(json!({
+        "status": "ok",
+        "service": "Agent A MCP Server",
+        "protocols": ["http", "jsonrpc-stdio"],
+        "version": "0.1.0",
+        "chain_id": state.chain_id().await,
+        "subsystems": state.breakers.status(),
+        "proofs": {
+            "stored": state.proofs.stats().ok(),
+            "swept": state.proofs_swept.load(Ordering::Relaxed)
+        }
+    }))
+}
+
+async fn list_tools_http(
+    State(state): State<Arc<AppState>>,
+) -> Json<serde_json::Value> {
+    Json(state.list_tools())
+}
+
+async fn http_get_ticket_price(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CallAgentBRequest>,
+) -> impl IntoResponse {
+    let input = PricingInput {
+        from: req.from,
+        to: req.to,
+        vip: req.vip,
+        departure_date: req.departure_date,
+        return_date: req.return_date,
+        currency: req.currency,
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        promo_code: req.promo_code,
+    };
+
+    let call = get_ticket_price(&state.http, &state.config.agent_b_url, &input, state.config.agent_b_did.as_deref());
+    match state.breakers.agent_b.call(call).await {
+        Ok(response) => {
+            (
+                StatusCode::OK,
+                Json(ToolResponse::ok(json!({
+                    "price_cents": response.price_cents,
+                    "currency": response.currency,
+                    "promo_code": response.promo_code,
+                    "discount_cents": response.discount_cents,
+                    "purchase_date": response.purchase_date,
+                    "quote_id": response.quote_id,
+                    "expires_at": response.expires_at,
+                    "program_id": response.program_id,
+                    "elf_hash": response.elf_hash,
+                    "signer": response.signer
+                }))),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            let error_response: ToolResponse<Value> = ToolResponse::err(e.to_string());
+            (
+                StatusCode::BAD_REQUEST,
+                Json(error_response),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn http_get_flight_options(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<FlightOptionsRequest>,
+) -> impl IntoResponse {
+    let call = get_flight_options(&state.http, &state.config.agent_b_url, &req.input, state.config.agent_b_did.as_deref());
+    match state.breakers.agent_b.call(call).await {
+        Ok(options) => (StatusCode::OK, Json(ToolResponse::ok(options))).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(ToolResponse::<()>::err(e.to_string()))).into_response(),
+    }
+}
+
+async fn http_format_zk_input(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<FormatZkInputRequest>,
+) -> impl IntoResponse {
+    let call = format_zk_input(&state.http, &state.config.agent_b_url, &req.endpoint, &req.input);
+    match state.breakers.agent_b.call(call).await {
+        Ok(result) => {
+            (
+                StatusCode::OK,
+                Json(ToolResponse::ok(json!({
+                    "input_hex": result.input_bytes,
+                    "length": result.input_array.len()
+                }))),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ToolResponse::<()>::err(e.to_string())),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Queue a proof on the attester and return 202 with the job id
+///
+/// Progress is available from `GET /tools/request_attestation/:job_id` or as SSE from
+/// `GET /tools/request_attestation/:job_id/events`; the proof bundle arrives with the `done` status.
+async fn http_request_attestation(
+    State(state): State<Arc<AppState>>,
+    principal: Option<Extension<Principal>>,
+    Json(req): Json<RequestAttestationRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = state.guard.admit(req.session_id.as_deref()) {
+        return rate_limited(e);
+    }
+    let principal = principal.map(|Extension(Principal(principal))| principal);
+    if let Some(principal) = &principal {
+        tracing::info!("Attestation for {} requested by {}", req.program_id, principal);
+    }
+
+    let input_bytes = match hex::decode(req.input_hex.strip_prefix("0x").unwrap_or(&req.input_hex))
+    {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ToolResponse::<()>::err(format!("Invalid hex: {}", e))),
+            )
+                .into_response();
+        }
+    };
+
+    let request = new_attest_request(
+        &req.program_id,
+        input_bytes,
+        req.claimed_output.as_deref().map(|s| serde_json::json!(s)),
+        true,
+    );
+
+    let call = submit_attestation_job(&state.attester_http, &state.config.attester_url, request);
+    match state.breakers.attester.call(call).await {
+        Ok(job_id) => {
+            state.attestation_jobs.write().unwrap().insert(
+                job_id.clone(),
+                PendingAttestation {
+                    program_id: req.program_id,
+                    elf_hash: req.elf_hash.unwrap_or_default(),
+                    session_id: req.session_id,
+                    principal,
+                    request_id: correlation::current(),
+                },
+            );
+            (
+                StatusCode::ACCEPTED,
+                Json(ToolResponse::ok(json!({
+                    "job_id": job_id,
+                    "status": "queued",
+                    "status_url": format!("/tools/request_attestation/{}", job_id),
+                    "events_url": format!("/tools/request_attestation/{}/events", job_id)
+                }))),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ToolResponse::<()>::err(e.to_string())),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn http_attestation_status(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    match state.attestation_job(&AttestJobId(job_id)).await {
+        Ok((_, view)) => (StatusCode::OK, Json(ToolResponse::ok(view))).into_response(),
+        Err(e) => {
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(ToolResponse::<()>::err(e.to_string())),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// SSE stream of job status changes; ends after the terminal status (or an `error` event)
+async fn http_attestation_events(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let initial = (state, AttestJobId(job_id), None::<String>, false);
+
+    let stream = futures::stream::unfold(initial, |(state, job_id, last, finished)| async move {
+        if finished {
+            return None;
+        }
+        loop {
+            match state.attestation_job(&job_id).await {
+                Ok((terminal, view)) => {
+                    let data = view.to_string();
+                    if terminal || last.as_deref() != Some(data.as_str()) {
+                        let event = Event::default().event("status").data(data.clone());
+                        return Some((Ok(event), (state, job_id, Some(data), terminal)));
+                    }
+                }
+                Err(e) => {
+                    let event = Event::default().event("error").data(e.to_string());
+                    return Some((Ok(event), (state, job_id, last, true)));
+                }
+            }
+            tokio::time::sleep(JOB_POLL_INTERVAL).await;
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn http_verify_on_chain(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<VerifyOnChainRequest>,
+) -> impl IntoResponse {
+    let claim = match req.claim.into_builder() {
+        Ok(claim) => claim,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ToolResponse::<()>::err(e.to_string())),
+            )
+                .into_response()
+        }
+    };
+
+    match state
+        .verify_proof(&req.proof, &req.public_values, &req.vk_hash, req.submit, req.fee_speed, &claim)
+        .await
+    {
+        Ok(result) => (StatusCode::OK, Json(ToolResponse::ok(result))).into_response(),
+        Err(e) => {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ToolResponse::<()>::err(e.to_string())),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn http_record_claim(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RecordClaimRequest>,
+) -> impl IntoResponse {
+    let claim = match req.claim.into_builder() {
+        Ok(claim) => claim,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ToolResponse::<()>::err(e.to_string())),
+            )
+                .into_response()
+        }
+    };
+
+    match state.record_claim(&req.proof, &req.public_values, &req.vk_hash, req.fee_speed, &claim).await {
+        Ok(result) => (StatusCode::OK, Json(ToolResponse::ok(result))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+async fn http_verify_offchain(Json(req): Json<VerifyOffchainRequest>) -> impl IntoResponse {
+    match verify_offchain_request(req).await {
+        Ok(result) => (StatusCode::OK, Json(ToolResponse::ok(result))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+async fn http_verify_session_on_chain(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<VerifySessionRequest>,
+) -> impl IntoResponse {
+    match state.verify_session_on_chain(&req.session_id, req.submit, req.fee_speed).await {
+        Ok(result) => (StatusCode::OK, Json(ToolResponse::ok(result))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+async fn http_create_crypto_payment(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateCryptoPaymentRequest>,
+) -> impl IntoResponse {
+    match state
+        .create_crypto_payment(req.amount_cents, req.currency.as_deref(), req.session_id.as_deref())
+        .await
+    {
+        Ok(payment) => (StatusCode::OK, Json(ToolResponse::ok(payment))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+async fn http_get_crypto_payment(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<GetCryptoPaymentRequest>,
+) -> impl IntoResponse {
+    match state.get_crypto_payment(&req.payment_id).await {
+        Ok(payment) => (StatusCode::OK, Json(ToolResponse::ok(payment))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+async fn http_get_claim(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<GetClaimRequest>,
+) -> impl IntoResponse {
+    match state.get_claim(&req.claim_id).await {
+        Ok(Some(record)) => (StatusCode::OK, Json(ToolResponse::ok(record))).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ToolResponse::<()>::err(format!("No claim recorded under {}", req.claim_id))),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::BAD_GATEWAY,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
+    }
+}
+
+async fn http_attest_price(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AttestPriceRequest>,
+) -> impl IntoResponse {
+    let input = PricingInput {
+        from: req.from,
+        to: req.to,
+        vip: req.vip,
+        departure_date: req.departure_date,
+        return_date: req.return_date,
+        currency: req.currency,
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        promo_code: req.promo_code,
+    };
+
+    let session_id = req.session_id.as_deref();
+    if let Err(e) = state.guard.admit(session_id) {
+        return rate_limited(e);
+    }
+    let _turn = state.guard.turn(session_id).await;
+    state.publish(session_id, ActivityEvent::ToolStarted { tool: "attest_price".to_string() });
+
+    let result = attest_price(&state.http, &state.attester_http, &state.endpoints(), &input, req.verify_on_chain).await;
+    state.publish_tool_result(session_id, "attest_price", &result);
+    match result {
+        Ok(result) => {
+            if let Ok(bundle) = result.proof_bundle() {
+                state.record_proof(session_id, SessionProof {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    tool: "attest_price".to_string(),
+                    kind: "price".to_string(),
+                    claim: json!({
+                        "from": input.from,
+                        "to": input.to,
+                        "vip": input.vip,
+                        "departure_date": input.departure_date,
+                        "return_date": input.return_date,
+                        "price_cents": result.price_cents,
+                        "currency": result.currency,
+                        "cabin": input.cabin,
+                        "fare_family": input.fare_family,
+                        "promo_code": input.promo_code,
+                        "quote_id": result.quote_id
+                    }),
+                    bundle,
+                    verified: result.verified,
+                    prev_hash: None,
+                    principal: None,
+                    request_id: None,
+                });
+            }
+            (StatusCode::OK, Json(ToolResponse::ok(result))).into_response()
+        }
+        Err(e) => {
+            state.record_mismatch(session_id, "attest_price", &e);
+            let status = if e.is::<PriceMismatch>() { StatusCode::CONFLICT } else { StatusCode::BAD_REQUEST };
+            (status, Json(ToolResponse::<()>::err(e.to_string()))).into_response()
+        }
+    }
+}
+
+async fn http_attest_cheapest_option(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AttestCheapestRequest>,
+) -> impl IntoResponse {
+    let session_id = req.session_id.as_deref();
+    if let Err(e) = state.guard.admit(session_id) {
+        return rate_limited(e);
+    }
+    let _turn = state.guard.turn(session_id).await;
+    state.publish(session_id, ActivityEvent::ToolStarted { tool: "attest_cheapest_option".to_string() });
 
-            _ => Err(anyhow!("Unknown tool: {}", name)),
+    let result = attest_cheapest_option(&state.http, &state.attester_http, &state.endpoints(), &req.input, req.verify_on_chain).await;
+    state.publish_tool_result(session_id, "attest_cheapest_option", &result);
+    match result {
+        Ok(result) => {
+            state.record_proof(session_id, SessionProof {
+                id: uuid::Uuid::new_v4().to_string(),
+                tool: "attest_cheapest_option".to_string(),
+                kind: "price".to_string(),
+                claim: json!({
+                    "options": result.options,
+                    "cheapest": result.cheapest,
+                    "currency": result.currency
+                }),
+                bundle: result.proof_bundle.clone(),
+                verified: result.verified,
+                prev_hash: None,
+                principal: None,
+                request_id: None,
+            });
+            (StatusCode::OK, Json(ToolResponse::ok(result))).into_response()
+        }
+        Err(e) => {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ToolResponse::<()>::err(e.to_string())),
+            )
+                .into_response()
         }
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Check if running in HTTP mode or JSON-RPC mode
-    let mode = std::env::var("AGENT_A_MODE").unwrap_or_else(|_| "http".to_string());
-    
-    match mode.as_str() {
-        "jsonrpc" => run_jsonrpc_server().await,
-        "http" | _ => start_http_server().await,
+async fn http_book_flight(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BookFlightRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = state.guard.admit(req.session_id.as_deref()) {
+        return rate_limited(e);
     }
+    if state.config.agent_a_require_approval {
+        let description = format!(
+            "Book {} → {} for {} <{}>",
+            req.input.from, req.input.to, req.input.passenger_name, req.input.passenger_email
+        );
+        return hold_for_approval(&state, description, HeldAction::BookFlight(req));
+    }
+    held_action_response(state.run_held_action(HeldAction::BookFlight(req)).await)
 }
 
-async fn run_jsonrpc_server() -> Result<()> {
-    let server = AgentAMcp::new();
-    let stdin = io::stdin();
-    let mut reader = stdin.lock().lines();
+async fn http_attest_booking(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AttestBookingRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = state.guard.admit(req.session_id.as_deref()) {
+        return rate_limited(e);
+    }
+    if state.config.agent_a_require_approval {
+        let description = format!(
+            "Book and attest {} → {} for {} <{}>",
+            req.input.from, req.input.to, req.input.passenger_name, req.input.passenger_email
+        );
+        return hold_for_approval(&state, description, HeldAction::AttestBooking(req));
+    }
+    held_action_response(state.run_held_action(HeldAction::AttestBooking(req)).await)
+}
 
-    // Read JSON-RPC messages from stdin
-    while let Some(Ok(line)) = reader.next() {
-        if line.trim().is_empty() {
-            continue;
-        }
+async fn http_attest_refund(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AttestRefundRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = state.guard.admit(req.session_id.as_deref()) {
+        return rate_limited(e);
+    }
+    if state.config.agent_a_require_approval {
+        let description = format!("Cancel booking {} and prove the refund", req.booking_id);
+        return hold_for_approval(&state, description, HeldAction::AttestRefund(req));
+    }
+    held_action_response(state.run_held_action(HeldAction::AttestRefund(req)).await)
+}
 
-        // Parse JSON-RPC request
-        let request: Value = match serde_json::from_str(&line) {
-            Ok(v) => v,
-            Err(e) => {
-                eprintln!("Parse error: {}", e);
-                continue;
-            }
-        };
+/// 429 with the limit that was hit and a Retry-After header
+fn rate_limited(e: RateLimited) -> axum::response::Response {
+    tracing::warn!("⚠ {}", e);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, e.retry_after.as_secs().max(1).to_string())],
+        Json(ToolResponse::<()>::err(e)),
+    )
+        .into_response()
+}
 
-        let id = request.get("id").cloned().unwrap_or(json!(null));
-        let method = match request.get("method").and_then(|v| v.as_str()) {
-            Some(m) => m,
-            None => continue,
-        };
+/// 202 with the pending action the caller must resolve via POST /chat/approve
+fn hold_for_approval(state: &AppState, description: String, action: HeldAction) -> axum::response::Response {
+    let pending_action = state.approvals.propose(description, action);
+    tracing::info!("⏸ Holding {} for approval: {}", pending_action.id, pending_action.description);
+    (
+        StatusCode::ACCEPTED,
+        Json(ToolResponse::ok(ApprovalRequired { pending_action })),
+    )
+        .into_response()
+}
 
-        let response = match method {
-            "initialize" => {
-                json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": {
-                        "protocolVersion": "2024-11",
-                        "capabilities": {"tools": {}},
-                        "serverInfo": {
-                            "name": "Agent A",
-                            "version": "0.1.0"
-                        }
-                    }
-                })
-            }
+fn held_action_response(result: Result<Value>) -> axum::response::Response {
+    match result {
+        Ok(data) => (StatusCode::OK, Json(ToolResponse::ok(data))).into_response(),
+        Err(e) => {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ToolResponse::<()>::err(e.to_string())),
+            )
+                .into_response()
+        }
+    }
+}
 
-            "tools/list" => {
-                let tools = server.list_tools();
-                json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": tools
-                })
-            }
+async fn http_approve(
+    State(state): State<Arc<AppState>>,
+    principal: Option<Extension<Principal>>,
+    Json(req): Json<ApproveRequest>,
+) -> impl IntoResponse {
+    let action = match state.approvals.take(&req.id) {
+        Ok(action) => action,
+        Err(e) => {
+            return (StatusCode::NOT_FOUND, Json(ToolResponse::<()>::err(e.to_string()))).into_response();
+        }
+    };
+    let who = principal.map(|Extension(p)| p.0).unwrap_or_else(|| "anonymous".to_string());
 
-            "tools/call" => {
-                let params = request.get("params").cloned().unwrap_or(json!({}));
-                let tool_name = params
-                    .get("name")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-                let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+    if !req.approve {
+        tracing::info!("✗ Pending action {} denied by {}", req.id, who);
+        return (
+            StatusCode::OK,
+            Json(ToolResponse::ok(json!({ "id": req.id, "status": "denied" }))),
+        )
+            .into_response();
+    }
+    tracing::info!("✓ Pending action {} approved by {}", req.id, who);
+    held_action_response(state.run_held_action(action).await)
+}
 
-                match server.call_tool(tool_name, arguments).await {
-                    Ok(result) => {
-                        json!({
-                            "jsonrpc": "2.0",
-                            "id": id,
-                            "result": {
-                                "content": [{
-                                    "type": "text",
-                                    "text": result.to_string()
-                                }]
-                            }
-                        })
-                    }
-                    Err(e) => {
-                        json!({
-                            "jsonrpc": "2.0",
-                            "id": id,
-                            "error": {
-                                "code": -32603,
-                                "message": e.to_string()
-                            }
-                        })
-                    }
-                }
-            }
+/// SSE stream of a session's activity events, named by type (`tool_started`, ...)
+async fn http_session_events(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = state.activity.subscribe(&session_id);
 
-            _ => {
-                json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "error": {
-                        "code": -32601,
-                        "message": format!("Method not found: {}", method)
-                    }
-                })
+    let stream = futures::stream::unfold((events, session_id), |(mut events, session_id)| async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    let sse = Event::default()
+                        .event(event.name())
+                        .data(serde_json::to_string(&event).unwrap_or_default());
+                    return Some((Ok(sse), (events, session_id)));
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Session {} subscriber lagged, skipped {} events", session_id, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
             }
-        };
-
-        // Send response
-        println!("{}", response.to_string());
-    }
+        }
+    });
 
-    Ok(())
+    Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
-/// HTTP Response wrapper
-#[derive(Debug, Serialize)]
-struct HttpResponse<T> {
-    success: bool,
-    data: Option<T>,
-    error: Option<String>,
+/// Every proof recorded for a session, with chain metadata and a manifest hash
+async fn http_session_proof_bundle(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> impl IntoResponse {
+    let chain = ChainMetadata {
+        chain_id: state.chain_id().await,
+        zeroproof_address: state.config.zeroproof_addr.clone(),
+    };
+    let denied_actions = state.denied_actions_for(&session_id);
+    let proofs = match state.proofs.session(&session_id) {
+        // A session whose only trace is a refused payment still has something to export
+        Ok(None) if !denied_actions.is_empty() => Ok(Some(Vec::new())),
+        other => other,
+    };
+    match proofs {
+        Ok(Some(proofs)) => (
+            StatusCode::OK,
+            [(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"proof-bundle-{}.json\"", session_id),
+            )],
+            Json(ToolResponse::ok(
+                SessionBundle::new(&session_id, chain, proofs)
+                    .with_payments(state.payments.settled_for_session(&session_id))
+                    .with_denied_actions(denied_actions),
+            )),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ToolResponse::<()>::err(format!("No proofs recorded for session {}", session_id))),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
+    }
 }
 
-impl<T> HttpResponse<T> {
-    fn ok(data: T) -> Self {
-        Self {
-            success: true,
-            data: Some(data),
-            error: None,
+/// A session's booking progress, with the summary shown when the user resumes it
+async fn http_get_booking_state(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> impl IntoResponse {
+    match state.booking_states.get(&session_id) {
+        Ok(Some(booking)) => {
+            let mut view = serde_json::to_value(&booking).unwrap_or_default();
+            view["summary"] = json!(booking.summary());
+            view["resumable"] = json!(booking.is_resumable());
+            (StatusCode::OK, Json(ToolResponse::ok(view))).into_response()
         }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ToolResponse::<()>::err(format!("No booking in progress for session {}", session_id))),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
     }
+}
 
-    fn err(error: impl std::fmt::Display) -> Self {
-        Self {
-            success: false,
-            data: None,
-            error: Some(error.to_string()),
+/// Record a booking milestone reported by the client
+async fn http_save_booking_state(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(mut booking): Json<BookingState>,
+) -> impl IntoResponse {
+    booking.session_id = session_id;
+    booking.updated_at = unix_now();
+    let previous = state.booking_states.get(&booking.session_id).ok().flatten().map(|previous| previous.stage);
+    match state.booking_states.put(&booking) {
+        Ok(()) => {
+            if previous != Some(booking.stage) {
+                let stage = |stage: booking_state::BookingStage| serde_json::to_value(stage).ok().and_then(|v| v.as_str().map(String::from));
+                let transition = AuditKind::StateTransition {
+                    from: previous.and_then(stage),
+                    to: stage(booking.stage).unwrap_or_default(),
+                };
+                state.record_audit(&booking.session_id, AuditEvent::new("agent_a", transition));
+            }
+            (StatusCode::OK, Json(ToolResponse::ok(booking))).into_response()
         }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
     }
 }
 
-/// HTTP request types
-#[derive(Debug, Deserialize)]
-struct CallAgentBRequest {
-    from: String,
-    to: String,
-    vip: bool,
-}
-
-#[derive(Debug, Deserialize)]
-struct FormatZkInputRequest {
-    endpoint: String,
-    input: serde_json::Value,
-}
-
-#[derive(Debug, Deserialize)]
-struct RequestAttestationRequest {
-    program_id: String,
-    input_hex: String,
-    #[serde(default)]
-    claimed_output: Option<String>,
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+struct AuditQuery {
+    /// "jsonl" to download one event per line instead of a JSON array
+    format: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct VerifyOnChainRequest {
-    proof: String,
-    public_values: String,
-    vk_hash: String,
+/// The session's audit log, as JSON or as a JSONL download
+async fn http_session_audit(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(query): Query<AuditQuery>,
+) -> impl IntoResponse {
+    let events = match state.audit.session(&session_id) {
+        Ok(events) => events,
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ToolResponse::<()>::err(e.to_string()))).into_response()
+        }
+    };
+    if events.is_empty() {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ToolResponse::<()>::err(format!("No audit events recorded for session {}", session_id))),
+        )
+            .into_response();
+    }
+    if query.format.as_deref() != Some("jsonl") {
+        return (StatusCode::OK, Json(ToolResponse::ok(events))).into_response();
+    }
+    match audit::to_jsonl(&events) {
+        Ok(jsonl) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"audit-{}.jsonl\"", session_id),
+                ),
+            ],
+            jsonl,
+        )
+            .into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ToolResponse::<()>::err(e.to_string()))).into_response(),
+    }
 }
 
-// HTTP Handlers
-async fn health() -> Json<serde_json::Value> {
-    Json(json!({
-        "status": "ok",
-        "service": "Agent A MCP Server",
-        "protocols": ["http", "jsonrpc-stdio"],
-        "version": "0.1.0"
-    }))
+/// Append an event the client reported (LLM decision, tool call, tool result)
+async fn http_append_audit(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(mut event): Json<AuditEvent>,
+) -> impl IntoResponse {
+    event.actor = "client".to_string();
+    match state.record_audit(&session_id, event) {
+        Some(event) => (StatusCode::OK, Json(ToolResponse::ok(event))).into_response(),
+        None => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ToolResponse::<()>::err(format!("Could not append to the audit log of session {}", session_id))),
+        )
+            .into_response(),
+    }
 }
 
-async fn list_tools_http(
-) -> Json<serde_json::Value> {
-    let server = AgentAMcp::new();
-    Json(server.list_tools())
+/// Log a payment the client's spending policy refused to the session's trace
+async fn http_record_denied_action(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(mut action): Json<DeniedAction>,
+) -> impl IntoResponse {
+    action.denied_at = unix_now();
+    tracing::warn!("⛔ Session {}: {} denied by policy ({}): {}", session_id, action.tool, action.rule, action.message);
+    state.publish(Some(&session_id), ActivityEvent::PaymentDenied {
+        tool: action.tool.clone(),
+        rule: action.rule.clone(),
+        message: action.message.clone(),
+    });
+    state
+        .denied_actions
+        .write()
+        .unwrap()
+        .entry(session_id)
+        .or_default()
+        .push(action.clone());
+    (StatusCode::OK, Json(ToolResponse::ok(action)))
 }
 
-async fn http_get_ticket_price(
-    Json(req): Json<CallAgentBRequest>,
+/// Export a session's proof bundle, then delete its proofs from the store
+async fn http_archive_session(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
 ) -> impl IntoResponse {
-    let server = AgentAMcp::new();
-    let input = PricingInput {
-        from: req.from,
-        to: req.to,
-        vip: req.vip,
+    // Holding the session's turn keeps new proofs from landing between export and delete
+    let _turn = state.guard.turn(Some(&session_id)).await;
+    let chain = ChainMetadata {
+        chain_id: state.chain_id().await,
+        zeroproof_address: state.config.zeroproof_addr.clone(),
     };
-
-    match get_ticket_price(&server.agent_b_url, &input).await {
-        Ok(response) => {
-            (
-                StatusCode::OK,
-                Json(HttpResponse::ok(json!({
-                    "price": response.price,
-                    "program_id": response.program_id,
-                    "elf_hash": response.elf_hash
-                }))),
+    let denied_actions = state.denied_actions_for(&session_id);
+    let proofs = match state.proofs.session(&session_id) {
+        Ok(None) if !denied_actions.is_empty() => Ok(Some(Vec::new())),
+        other => other,
+    };
+    let bundle = match proofs {
+        Ok(Some(proofs)) => SessionBundle::new(&session_id, chain, proofs)
+            .with_payments(state.payments.settled_for_session(&session_id))
+            .with_denied_actions(denied_actions),
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ToolResponse::<()>::err(format!("No proofs recorded for session {}", session_id))),
             )
                 .into_response()
         }
         Err(e) => {
-            let error_response: HttpResponse<Value> = HttpResponse::err(e.to_string());
-            (
-                StatusCode::BAD_REQUEST,
-                Json(error_response),
-            )
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ToolResponse::<()>::err(e.to_string())))
                 .into_response()
         }
+    };
+    if let Err(e) = state.proofs.remove_session(&session_id) {
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(ToolResponse::<()>::err(e.to_string()))).into_response();
     }
+    state.denied_actions.write().unwrap().remove(&session_id);
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"proof-archive-{}.json\"", session_id),
+        )],
+        Json(ToolResponse::ok(bundle)),
+    )
+        .into_response()
 }
 
-async fn http_format_zk_input(
-    Json(req): Json<FormatZkInputRequest>,
+/// Merkle root of a session's proof trail, optionally written on-chain
+async fn http_anchor_session(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    body: Option<Json<AnchorRequest>>,
 ) -> impl IntoResponse {
-    let server = AgentAMcp::new();
-
-    match format_zk_input(&server.agent_b_url, &req.endpoint, &req.input).await {
-        Ok(result) => {
-            (
-                StatusCode::OK,
-                Json(HttpResponse::ok(json!({
-                    "input_hex": result.input_bytes,
-                    "length": result.input_array.len()
-                }))),
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let proofs = match state.proofs.session(&session_id) {
+        Ok(Some(proofs)) => proofs,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ToolResponse::<()>::err(format!("No proofs recorded for session {}", session_id))),
             )
                 .into_response()
         }
         Err(e) => {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(HttpResponse::<()>::err(e.to_string())),
-            )
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ToolResponse::<()>::err(e.to_string())))
                 .into_response()
         }
-    }
-}
+    };
+    let Some(root) = proof_chain::merkle_root(&proofs) else {
+        return (StatusCode::NOT_FOUND, Json(ToolResponse::<()>::err("Session has no proofs"))).into_response();
+    };
 
-async fn http_request_attestation(
-    Json(req): Json<RequestAttestationRequest>,
-) -> impl IntoResponse {
-    let server = AgentAMcp::new();
-    
-    let input_bytes = match hex::decode(req.input_hex.strip_prefix("0x").unwrap_or(&req.input_hex))
-    {
-        Ok(bytes) => bytes,
-        Err(e) => {
+    let mut anchor = SessionAnchor {
+        session_id,
+        merkle_root: proof_chain::to_hex(&root),
+        proof_count: proofs.len(),
+        chain_intact: proof_chain::first_broken_link(&proofs).is_none(),
+        anchored_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        tx_hash: None,
+        block_number: None,
+    };
+    if req.on_chain {
+        let Some(queue) = state.tx_queue.as_ref() else {
             return (
                 StatusCode::BAD_REQUEST,
-                Json(HttpResponse::<()>::err(format!("Invalid hex: {}", e))),
+                Json(ToolResponse::<()>::err("on_chain=true requires SIGNER_PRIVATE_KEY")),
             )
                 .into_response();
+        };
+        match state.breakers.rpc.call(anchor_on_chain(queue, Some(&anchor.session_id), &root)).await {
+            Ok((tx_hash, block_number)) => {
+                anchor.tx_hash = Some(tx_hash);
+                anchor.block_number = block_number;
+            }
+            Err(e) => {
+                return (StatusCode::BAD_GATEWAY, Json(ToolResponse::<()>::err(e.to_string()))).into_response()
+            }
         }
-    };
+    }
+    (StatusCode::OK, Json(ToolResponse::ok(anchor))).into_response()
+}
 
-    match request_attestation(
-        &server.attester_url,
-        &req.program_id,
-        input_bytes,
-        req.claimed_output.as_deref().map(|s| serde_json::json!(s)),
-        true,
-    )
-    .await
-    {
-        Ok(response) => {
-            (
-                StatusCode::OK,
-                Json(HttpResponse::ok(json!({
-                    "verified_output": response.verified_output,
-                    "vk_hash": response.vk_hash
-                }))),
+/// A session's proofs with their background verification status
+async fn http_session_proofs(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> impl IntoResponse {
+    let records = match state.proofs.records(&session_id) {
+        Ok(records) if records.is_empty() => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ToolResponse::<()>::err(format!("No proofs recorded for session {}", session_id))),
             )
                 .into_response()
         }
+        Ok(records) => records,
         Err(e) => {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(HttpResponse::<()>::err(e.to_string())),
-            )
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ToolResponse::<()>::err(e.to_string())))
                 .into_response()
         }
+    };
+
+    let count = |status: Option<bool>| records.iter().filter(|p| p.verified() == status).count();
+    let (verified, failed, pending) = (count(Some(true)), count(Some(false)), count(None));
+    let progress = json!({
+        "session_id": session_id,
+        "total": records.len(),
+        "verified": verified,
+        "failed": failed,
+        "pending": pending,
+        "fully_verified": verified == records.len(),
+        "proofs": records,
+    });
+    (StatusCode::OK, Json(ToolResponse::ok(progress))).into_response()
+}
+
+/// GET /proofs query: filters plus cursor pagination
+///
+/// Spelled out rather than flattening `ProofFilter`: serde_urlencoded can't parse
+/// numbers or booleans through `#[serde(flatten)]`.
+#[derive(Debug, Deserialize)]
+struct ProofsQuery {
+    session_id: Option<String>,
+    tool: Option<String>,
+    kind: Option<String>,
+    verified: Option<bool>,
+    principal: Option<String>,
+    request_id: Option<String>,
+    since: Option<u64>,
+    until: Option<u64>,
+    cursor: Option<u64>,
+    limit: Option<usize>,
+}
+
+/// Recorded proofs across sessions, filtered by session, tool, stage, verified flag, principal, request, and time
+async fn http_list_proofs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ProofsQuery>,
+) -> impl IntoResponse {
+    let filter = ProofFilter {
+        session_id: query.session_id,
+        tool: query.tool,
+        kind: query.kind,
+        verified: query.verified,
+        principal: query.principal,
+        request_id: query.request_id,
+        since: query.since,
+        until: query.until,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    match state.proofs.query(&filter, query.cursor, limit) {
+        Ok(page) => (StatusCode::OK, Json(ToolResponse::ok(page))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
     }
 }
 
-async fn http_verify_on_chain(
-    Json(req): Json<VerifyOnChainRequest>,
+/// Query string of GET /claims
+#[derive(Debug, Deserialize)]
+struct ClaimsQuery {
+    agent: Option<String>,
+    /// pricing, booking, payment, or a 0x bytes32
+    #[serde(rename = "claimType")]
+    claim_type: Option<String>,
+    /// Only events from transactions this session's proofs were verified in
+    session_id: Option<String>,
+    cursor: Option<u64>,
+    limit: Option<usize>,
+}
+
+/// On-chain ProofVerified / ClaimRecorded events from the local index
+async fn http_list_claims(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ClaimsQuery>,
 ) -> impl IntoResponse {
-    let server = AgentAMcp::new();
-
-    match verify_on_chain(
-        &server.zeroproof_addr,
-        &server.rpc_url,
-        &req.proof,
-        &req.public_values,
-        &req.vk_hash,
-    )
-    .await
-    {
-        Ok(verified) => {
-            (
-                StatusCode::OK,
-                Json(HttpResponse::ok(json!({
-                    "verified": verified,
-                    "message": if verified {
-                        "✓ Proof verified on-chain"
-                    } else {
-                        "✗ Proof verification failed"
-                    }
-                }))),
-            )
-                .into_response()
+    let claim_type = match query.claim_type.as_deref().map(chain_indexer::parse_claim_type).transpose() {
+        Ok(claim_type) => claim_type,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ToolResponse::<()>::err(e.to_string()))).into_response()
         }
+    };
+    let tx_hashes = match query.session_id.as_deref().map(|id| state.proofs.records(id)).transpose() {
+        Ok(records) => records.map(|records| {
+            records
+                .iter()
+                .filter_map(|p| p.verification.as_ref()?.tx_hash.clone())
+                .collect()
+        }),
         Err(e) => {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(HttpResponse::<()>::err(e.to_string())),
-            )
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ToolResponse::<()>::err(e.to_string())))
                 .into_response()
         }
+    };
+
+    let filter = ClaimFilter { agent: query.agent, claim_type, tx_hashes };
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    match state.claims.query(&filter, query.cursor, limit) {
+        Ok(page) => (StatusCode::OK, Json(ToolResponse::ok(page))).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ToolResponse::<()>::err(e.to_string())),
+        )
+            .into_response(),
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct TransactionsQuery {
+    /// Proof id, claim id, or session id the transaction was sent for
+    label: Option<String>,
+}
+
+/// Transactions sent from the signing wallet, newest first, with pending/confirmed status
+async fn http_transactions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TransactionsQuery>,
+) -> impl IntoResponse {
+    let Some(queue) = state.tx_queue.as_ref() else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ToolResponse::<()>::err("No signing wallet (SIGNER_PRIVATE_KEY unset)")),
+        )
+            .into_response();
+    };
+    let transactions = queue.transactions(query.label.as_deref());
+    (
+        StatusCode::OK,
+        Json(ToolResponse::ok(json!({
+            "address": format!("{:#x}", queue.address()),
+            "transactions": transactions,
+        }))),
+    )
+        .into_response()
+}
+
+/// OpenAPI spec for the routes registered in `start_http_server`
+fn openapi_spec() -> Value {
+    SpecBuilder::new()
+        .get::<Value>("/health", "Service health and chain ID")
+        .get::<String>("/metrics", "Prometheus metrics: chat turns, LLM latency and tokens, tool calls, proofs, booking funnel")
+        .get::<Value>("/tools", "MCP tool definitions")
+        .get::<Value>("/.well-known/tap-jwks.json", "Ed25519 JWKS of the keys Agent A signs TAP requests with")
+        .post::<CallAgentBRequest, ToolResponse<PricingResponse>>(
+            "/tools/get_ticket_price",
+            "Get flight ticket pricing from Agent B",
+        )
+        .post::<FlightOptionsRequest, ToolResponse<FlightOptionsResponse>>(
+            "/tools/get_flight_options",
+            "Compare routes and dates at Agent B",
+        )
+        .post::<FormatZkInputRequest, ToolResponse<Value>>(
+            "/tools/format_zk_input",
+            "Format input for zkVM computation",
+        )
+        .post::<RequestAttestationRequest, ToolResponse<Value>>(
+            "/tools/request_attestation",
+            "Queue a ZK proof on the attester (202 with job id)",
+        )
+        .get::<ToolResponse<Value>>(
+            "/tools/request_attestation/{job_id}",
+            "Attestation job status (SSE stream at .../events)",
+        )
+        .post::<VerifyOnChainRequest, ToolResponse<VerificationResult>>(
+            "/tools/verify_on_chain",
+            "Verify ZK proof on Sepolia blockchain",
+        )
+        .post::<RecordClaimRequest, ToolResponse<RecordClaimResult>>(
+            "/tools/record_claim",
+            "Verify a proof and record its claim in the ZeroProof registry",
+        )
+        .post::<GetClaimRequest, ToolResponse<ClaimRecord>>(
+            "/tools/get_claim",
+            "Look up a recorded claim by id",
+        )
+        .post::<VerifyOffchainRequest, ToolResponse<Value>>(
+            "/tools/verify_offchain",
+            "Check a proof, proof bundle, or session bundle with the Rust SP1 verifier (no RPC)",
+        )
+        .post::<CreateCryptoPaymentRequest, ToolResponse<CryptoPayment>>(
+            "/tools/create_crypto_payment",
+            "Request a stablecoin payment (EIP-681 URI) to settle before booking",
+        )
+        .post::<GetCryptoPaymentRequest, ToolResponse<CryptoPayment>>(
+            "/tools/get_crypto_payment",
+            "Status of a crypto payment; checks the chain while pending",
+        )
+        .post::<VerifySessionRequest, ToolResponse<SessionVerification>>(
+            "/tools/verify_session_on_chain",
+            "Verify every proof of a session in one Multicall3 batch",
+        )
+        .post::<AttestPriceRequest, ToolResponse<AttestPriceResult>>(
+            "/tools/attest_price",
+            "Price, prove, and optionally verify on-chain",
+        )
+        .post::<AttestCheapestRequest, ToolResponse<AttestCheapestResult>>(
+            "/tools/attest_cheapest_option",
+            "Compare options and prove the cheapest, optionally verifying on-chain",
+        )
+        .post::<BookFlightRequest, ToolResponse<BookingResponse>>(
+            "/tools/book_flight",
+            "Book a flight through Agent B",
+        )
+        .post::<AttestBookingRequest, ToolResponse<AttestBookingResult>>(
+            "/tools/attest_booking",
+            "Book, prove, and optionally verify on-chain",
+        )
+        .post::<AttestRefundRequest, ToolResponse<AttestRefundResult>>(
+            "/tools/attest_refund",
+            "Cancel a booking, prove the refund, and optionally verify on-chain",
+        )
+        .post::<ApproveRequest, ToolResponse<Value>>(
+            "/chat/approve",
+            "Confirm or deny a pending_action returned (202) by a held booking call",
+        )
+        .get::<Value>(
+            "/sessions/{id}/events",
+            "SSE stream of tool_started, tool_result, proof_collected, payment_step, booking_confirmed, payment_denied events",
+        )
+        .get::<ToolResponse<Value>>(
+            "/sessions/{id}/proof-bundle",
+            "Download every proof recorded for a session, for offline or on-chain verification",
+        )
+        .get::<ToolResponse<Vec<AuditEvent>>>(
+            "/sessions/{id}/audit",
+            "Every decision, tool call, state transition, and activity event of a session; ?format=jsonl downloads it one event per line",
+        )
+        .post::<AuditEvent, ToolResponse<AuditEvent>>(
+            "/sessions/{id}/audit",
+            "Append a client-side event (llm_decision, tool_call, tool_result) to the session's audit log",
+        )
+        .post::<DeniedAction, ToolResponse<DeniedAction>>(
+            "/sessions/{id}/denied-actions",
+            "Record a payment refused by the client's spending policy; exported with the proof bundle",
+        )
+        .post::<AnchorRequest, ToolResponse<SessionAnchor>>(
+            "/sessions/{id}/anchor",
+            "Merkle root of the session's hash-linked proofs; on_chain=true also writes it in a transaction",
+        )
+        .get::<ToolResponse<Value>>(
+            "/session/{id}",
+            "Where the session's booking stands (BookingState) plus a one-line resume summary",
+        )
+        .post::<BookingState, ToolResponse<BookingState>>(
+            "/session/{id}",
+            "Save the session's booking progress, replacing the previous state",
+        )
+        .get::<ToolResponse<Value>>(
+            "/proofs",
+            "List recorded proofs; filter by session_id, tool, kind, verified, principal, request_id, since, until; page with cursor and limit",
+        )
+        .get::<ToolResponse<Value>>(
+            "/proofs/{session_id}",
+            "A session's proofs with on-chain verification progress (verified, failed, pending)",
+        )
+        .post::<(), ToolResponse<Value>>(
+            "/proofs/{session_id}/archive",
+            "Download the session's proof bundle and delete its proofs from the store",
+        )
+        .get::<ToolResponse<Value>>(
+            "/transactions",
+            "Transactions sent from the signing wallet with pending/confirmed status; filter by label (proof id)",
+        )
+        .get::<ToolResponse<Value>>(
+            "/claims",
+            "Indexed on-chain ProofVerified/ClaimRecorded events; filter by agent, claimType, session_id; page with cursor and limit",
+        )
+        .build("Agent A MCP Server", env!("CARGO_PKG_VERSION"))
+}
+
+/// Public keys Agent A signs TAP requests with (empty when signing is off)
+async fn tap_jwks() -> Json<Value> {
+    let jwks = tap::keys().map(TapKeyStore::jwks).unwrap_or_default();
+    Json(serde_json::to_value(jwks).unwrap_or_default())
+}
+
+/// Prometheus scrape endpoint
+async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], state.metrics.render())
+}
+
+async fn openapi_json() -> Json<Value> {
+    Json(openapi_spec())
+}
+
+async fn swagger_ui() -> Html<String> {
+    Html(swagger_ui_html("Agent A MCP Server", "/openapi.json"))
+}
+
 /// Start HTTP server
-async fn start_http_server() -> Result<()> {
-    let port = std::env::var("AGENT_A_SERVER_PORT")
-        .unwrap_or_else(|_| "3001".to_string())
-        .parse::<u16>()
-        .unwrap_or(3001);
+async fn start_http_server(config: Config) -> Result<()> {
+    let port = config.agent_a_server_port;
+    let tls = config.tls.clone();
+    let auth = Arc::new(AuthConfig::parse(config.agent_a_api_tokens.as_deref().unwrap_or_default()));
+    let state = Arc::new(AppState::new(config)?);
+
+    // Background health probes for open breakers
+    let probe_state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+            probe_state.probe_open_breakers().await;
+        }
+    });
+
+    // Retention sweeps of the proof store
+    let sweep_interval = state.config.agent_a_retention_sweep_secs.max(1);
+    let sweep_state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(sweep_interval));
+        loop {
+            interval.tick().await;
+            sweep_state.sweep_proofs();
+        }
+    });
+
+    // Background on-chain verification of recorded proofs
+    let verify_interval = state.config.agent_a_verify_interval_secs;
+    if verify_interval > 0 {
+        let verify_state = state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(verify_interval));
+            loop {
+                interval.tick().await;
+                verify_state.verify_pending_proofs().await;
+            }
+        });
+    }
+
+    // ZeroProof event indexing for GET /claims
+    if let Some(ws_url) = state.config.agent_a_index_ws_url.clone() {
+        tokio::spawn(chain_indexer::run(
+            ws_url,
+            state.config.zeroproof_addr.clone(),
+            state.claims.clone(),
+            state.config.agent_a_index_from_block,
+        ));
+    }
 
     let app = Router::new()
         .route("/health", get(health))
+        .route("/metrics", get(metrics))
         .route("/tools", get(list_tools_http))
         .route("/tools/get_ticket_price", post(http_get_ticket_price))
+        .route("/tools/get_flight_options", post(http_get_flight_options))
         .route("/tools/format_zk_input", post(http_format_zk_input))
         .route("/tools/request_attestation", post(http_request_attestation))
+        .route("/tools/request_attestation/:job_id", get(http_attestation_status))
+        .route("/tools/request_attestation/:job_id/events", get(http_attestation_events))
         .route("/tools/verify_on_chain", post(http_verify_on_chain))
-        .layer(CorsLayer::permissive());
-
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
+        .route("/tools/record_claim", post(http_record_claim))
+        .route("/tools/get_claim", post(http_get_claim))
+        .route("/tools/verify_offchain", post(http_verify_offchain))
+        .route("/tools/verify_session_on_chain", post(http_verify_session_on_chain))
+        .route("/tools/create_crypto_payment", post(http_create_crypto_payment))
+        .route("/tools/get_crypto_payment", post(http_get_crypto_payment))
+        .route("/tools/attest_price", post(http_attest_price))
+        .route("/tools/attest_cheapest_option", post(http_attest_cheapest_option))
+        .route("/tools/book_flight", post(http_book_flight))
+        .route("/tools/attest_booking", post(http_attest_booking))
+        .route("/tools/attest_refund", post(http_attest_refund))
+        .route("/chat/approve", post(http_approve))
+        .route("/sessions/:id/events", get(http_session_events))
+        .route("/sessions/:id/proof-bundle", get(http_session_proof_bundle))
+        .route("/sessions/:id/audit", get(http_session_audit).post(http_append_audit))
+        .route("/sessions/:id/denied-actions", post(http_record_denied_action))
+        .route("/sessions/:id/anchor", post(http_anchor_session))
+        .route("/session/:id", get(http_get_booking_state).post(http_save_booking_state))
+        .route("/proofs", get(http_list_proofs))
+        .route("/proofs/:session_id", get(http_session_proofs))
+        .route("/proofs/:session_id/archive", post(http_archive_session))
+        .route("/claims", get(http_list_claims))
+        .route("/transactions", get(http_transactions))
+        .route("/.well-known/tap-jwks.json", get(tap_jwks))
+        .route("/openapi.json", get(openapi_json))
+        .route("/docs", get(swagger_ui))
+        .layer(middleware::from_fn_with_state(auth.clone(), auth::require_bearer))
+        .layer(TraceLayer::new_for_http().make_span_with(correlation::make_span))
+        .layer(middleware::from_fn(correlation::propagate))
+        .layer(CorsLayer::permissive())
+        .with_state(state);
 
     println!("\n╔════════════════════════════════════════════════════════════╗");
     println!("║           Agent A - HTTP Server                            ║");
     println!("╚════════════════════════════════════════════════════════════╝\n");
-    println!("✓ Server listening on http://0.0.0.0:{}\n", port);
+    println!("✓ Server listening on {}://0.0.0.0:{}", tls.scheme(), port);
+    if tls.requires_client_cert() {
+        println!("✓ Client certificates required (TLS_CLIENT_CA)");
+    }
+    if auth.is_enabled() {
+        println!("✓ Bearer-token auth enabled (AGENT_A_API_TOKENS)\n");
+    } else {
+        println!("⚠ AGENT_A_API_TOKENS not set, HTTP endpoints are unauthenticated\n");
+    }
     println!("Endpoints:");
     println!("  GET    http://localhost:{}/health", port);
+    println!("  GET    http://localhost:{}/metrics", port);
     println!("  GET    http://localhost:{}/tools", port);
+    println!("  GET    http://localhost:{}/openapi.json", port);
+    println!("  GET    http://localhost:{}/.well-known/tap-jwks.json", port);
+    println!("  GET    http://localhost:{}/docs", port);
     println!("  POST   http://localhost:{}/tools/get_ticket_price", port);
+    println!("  POST   http://localhost:{}/tools/get_flight_options", port);
     println!("  POST   http://localhost:{}/tools/format_zk_input", port);
     println!("  POST   http://localhost:{}/tools/request_attestation", port);
-    println!("  POST   http://localhost:{}/tools/verify_on_chain\n", port);
+    println!("  GET    http://localhost:{}/tools/request_attestation/:job_id[/events]", port);
+    println!("  POST   http://localhost:{}/tools/verify_on_chain", port);
+    println!("  POST   http://localhost:{}/tools/record_claim", port);
+    println!("  POST   http://localhost:{}/tools/get_claim", port);
+    println!("  POST   http://localhost:{}/tools/verify_offchain", port);
+    println!("  POST   http://localhost:{}/tools/verify_session_on_chain", port);
+    println!("  POST   http://localhost:{}/tools/create_crypto_payment", port);
+    println!("  POST   http://localhost:{}/tools/get_crypto_payment", port);
+    println!("  POST   http://localhost:{}/tools/attest_price", port);
+    println!("  POST   http://localhost:{}/tools/attest_cheapest_option", port);
+    println!("  POST   http://localhost:{}/tools/book_flight", port);
+    println!("  POST   http://localhost:{}/tools/attest_booking", port);
+    println!("  POST   http://localhost:{}/tools/attest_refund", port);
+    println!("  POST   http://localhost:{}/chat/approve", port);
+    println!("  GET    http://localhost:{}/sessions/:id/events", port);
+    println!("  GET    http://localhost:{}/sessions/:id/proof-bundle", port);
+    println!("  GET    http://localhost:{}/sessions/:id/audit", port);
+    println!("  POST   http://localhost:{}/sessions/:id/audit", port);
+    println!("  POST   http://localhost:{}/sessions/:id/denied-actions", port);
+    println!("  POST   http://localhost:{}/sessions/:id/anchor", port);
+    println!("  GET    http://localhost:{}/session/:id", port);
+    println!("  POST   http://localhost:{}/session/:id", port);
+    println!("  GET    http://localhost:{}/proofs", port);
+    println!("  GET    http://localhost:{}/proofs/:session_id", port);
+    println!("  POST   http://localhost:{}/proofs/:session_id/archive", port);
+    println!("  GET    http://localhost:{}/claims", port);
+    println!("  GET    http://localhost:{}/transactions\n", port);
 
-    axum::serve(listener, app).await?;
+    agent_tls::serve(([0, 0, 0, 0], port).into(), app, &tls).await?;
 
     Ok(())
 }
@@ -0,0 +1,150 @@
+//! Prometheus metrics for the chat and tool pipeline (`GET /metrics`)
+//!
+//! Agent A doesn't call the LLM itself, so chat figures come from what the chat client
+//! reports to the audit log: one `llm_decision` per chat turn, with the LLM call's
+//! latency and token usage. Tool calls are counted twice over, by `source`: "agent_a" for
+//! tools this server ran and "client" for every tool call the chat client made (Agent B's
+//! and the payment agent's included). The booking funnel counts sessions entering each
+//! stage (`POST /session/{id}`), so conversion is e.g. `booked / quoted`.
+
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+
+use crate::activity::ActivityEvent;
+use crate::audit::AuditKind;
+
+/// LLM latency buckets, in seconds
+const LATENCY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0, 32.0];
+
+pub struct Metrics {
+    registry: Registry,
+    chat_turns: IntCounterVec,
+    llm_latency: HistogramVec,
+    llm_tokens: IntCounterVec,
+    tool_calls: IntCounterVec,
+    proofs: IntCounterVec,
+    funnel: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("agent_a".to_string()), None)?;
+        let chat_turns = IntCounterVec::new(Opts::new("chat_turns_total", "Chat turns routed by the LLM"), &["provider"])?;
+        let llm_latency = HistogramVec::new(
+            HistogramOpts::new("llm_request_duration_seconds", "LLM API call latency").buckets(LATENCY_BUCKETS.to_vec()),
+            &["provider"],
+        )?;
+        let llm_tokens =
+            IntCounterVec::new(Opts::new("llm_tokens_total", "LLM tokens used"), &["provider", "direction"])?;
+        let tool_calls = IntCounterVec::new(
+            Opts::new("tool_calls_total", "Tool calls by tool and outcome"),
+            &["tool", "outcome", "source"],
+        )?;
+        let proofs = IntCounterVec::new(Opts::new("proofs_collected_total", "Proofs added to sessions"), &["kind"])?;
+        let funnel = IntCounterVec::new(
+            Opts::new("booking_funnel_total", "Bookings entering each stage"),
+            &["stage"],
+        )?;
+        for collector in [&chat_turns, &llm_tokens, &tool_calls, &proofs, &funnel] {
+            registry.register(Box::new(collector.clone()))?;
+        }
+        registry.register(Box::new(llm_latency.clone()))?;
+        Ok(Self { registry, chat_turns, llm_latency, llm_tokens, tool_calls, proofs, funnel })
+    }
+
+    /// Count an activity event Agent A published
+    pub fn observe_activity(&self, event: &ActivityEvent) {
+        match event {
+            ActivityEvent::ToolResult { tool, success, .. } => {
+                self.tool_calls.with_label_values(&[tool, outcome(*success), "agent_a"]).inc()
+            }
+            ActivityEvent::ProofCollected { kind, .. } => self.proofs.with_label_values(&[kind]).inc(),
+            _ => {}
+        }
+    }
+
+    /// Count an audit event; activity events are counted by [`Self::observe_activity`]
+    pub fn observe_audit(&self, kind: &AuditKind) {
+        match kind {
+            AuditKind::LlmDecision { provider, latency_ms, input_tokens, output_tokens, .. } => {
+                let provider = provider.as_deref().unwrap_or("unknown");
+                self.chat_turns.with_label_values(&[provider]).inc();
+                if let Some(latency_ms) = latency_ms {
+                    self.llm_latency.with_label_values(&[provider]).observe(*latency_ms as f64 / 1000.0);
+                }
+                if let Some(tokens) = input_tokens {
+                    self.llm_tokens.with_label_values(&[provider, "input"]).inc_by(*tokens);
+                }
+                if let Some(tokens) = output_tokens {
+                    self.llm_tokens.with_label_values(&[provider, "output"]).inc_by(*tokens);
+                }
+            }
+            AuditKind::ToolResult { tool, success, .. } => {
+                self.tool_calls.with_label_values(&[tool, outcome(*success), "client"]).inc()
+            }
+            AuditKind::StateTransition { to, .. } => self.funnel.with_label_values(&[to]).inc(),
+            AuditKind::ToolCall { .. } | AuditKind::Activity { .. } => {}
+        }
+    }
+
+    /// Text exposition format
+    pub fn render(&self) -> String {
+        let mut out = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut out) {
+            tracing::error!("Encoding metrics: {}", e);
+        }
+        String::from_utf8(out).unwrap_or_default()
+    }
+}
+
+fn outcome(success: bool) -> &'static str {
+    if success {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_counts_turns_tools_and_funnel() {
+        let metrics = Metrics::new().unwrap();
+        let decision: AuditKind = serde_json::from_value(json!({
+            "type": "llm_decision",
+            "prompt_hash": "0xabc",
+            "provider": "anthropic",
+            "tools": ["get-ticket-price"],
+            "text": "Pricing NYC → LON",
+            "latency_ms": 850,
+            "input_tokens": 1200,
+            "output_tokens": 40,
+        }))
+        .unwrap();
+        metrics.observe_audit(&decision);
+        metrics.observe_audit(&AuditKind::ToolResult {
+            tool: "get-ticket-price".into(),
+            success: false,
+            result: None,
+            error: Some("timeout".into()),
+        });
+        metrics.observe_audit(&AuditKind::StateTransition { from: None, to: "quoted".into() });
+        metrics.observe_activity(&ActivityEvent::ToolResult { tool: "attest_price".into(), success: true, error: None });
+        metrics.observe_activity(&ActivityEvent::ProofCollected {
+            kind: "price".into(),
+            vk_hash: "0x01".into(),
+            verified: None,
+        });
+
+        let text = metrics.render();
+        assert!(text.contains(r#"agent_a_chat_turns_total{provider="anthropic"} 1"#));
+        assert!(text.contains(r#"agent_a_llm_tokens_total{direction="input",provider="anthropic"} 1200"#));
+        assert!(text.contains(r#"agent_a_llm_request_duration_seconds_count{provider="anthropic"} 1"#));
+        assert!(text.contains(r#"agent_a_tool_calls_total{outcome="failure",source="client",tool="get-ticket-price"} 1"#));
+        assert!(text.contains(r#"agent_a_tool_calls_total{outcome="success",source="agent_a",tool="attest_price"} 1"#));
+        assert!(text.contains(r#"agent_a_proofs_collected_total{kind="price"} 1"#));
+        assert!(text.contains(r#"agent_a_booking_funnel_total{stage="quoted"} 1"#));
+    }
+}
@@ -0,0 +1,149 @@
+//! Hash-linked proof chains and per-session Merkle roots
+//!
+//! Each [`SessionProof`] stores the hash of the proof recorded before it in the same
+//! session (`prev_hash`), so removing or inserting an entry breaks the link after it.
+//! The Merkle root over the proof hashes is a single 32-byte commitment to the whole
+//! trail that can be anchored on-chain (`POST /sessions/{id}/anchor`) and compared
+//! against any later export.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::session_bundle::SessionProof;
+
+/// Root of an anchored session trail
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionAnchor {
+    pub session_id: String,
+    /// 0x-prefixed Merkle root over the session's proof hashes
+    pub merkle_root: String,
+    pub proof_count: usize,
+    /// Whether every `prev_hash` matches the proof before it
+    pub chain_intact: bool,
+    /// Unix seconds when the root was computed
+    pub anchored_at: u64,
+    /// Transaction carrying the root as calldata, if anchored on-chain
+    pub tx_hash: Option<String>,
+    pub block_number: Option<u64>,
+}
+
+/// SHA-256 of a proof entry, `prev_hash` included
+pub fn proof_hash(proof: &SessionProof) -> [u8; 32] {
+    let bytes = serde_json::to_vec(proof).unwrap_or_default();
+    Sha256::digest(bytes).into()
+}
+
+pub fn to_hex(hash: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(hash))
+}
+
+/// Index of the first proof whose `prev_hash` doesn't match its predecessor
+pub fn first_broken_link(proofs: &[SessionProof]) -> Option<usize> {
+    let mut prev: Option<String> = None;
+    for (i, proof) in proofs.iter().enumerate() {
+        let linked = match (&prev, &proof.prev_hash) {
+            (None, None) => true,
+            (Some(expected), Some(actual)) => expected.eq_ignore_ascii_case(actual),
+            _ => false,
+        };
+        if !linked {
+            return Some(i);
+        }
+        prev = Some(to_hex(&proof_hash(proof)));
+    }
+    None
+}
+
+/// Merkle root over the proof hashes in recording order; `None` for no proofs
+///
+/// Nodes are SHA-256(left || right). An odd node is carried up unchanged rather than
+/// paired with itself, so repeating the last proof changes the root.
+pub fn merkle_root(proofs: &[SessionProof]) -> Option<[u8; 32]> {
+    let mut level: Vec<[u8; 32]> = proofs.iter().map(proof_hash).collect();
+    if level.is_empty() {
+        return None;
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut hasher = Sha256::new();
+                    hasher.update(left);
+                    hasher.update(right);
+                    hasher.finalize().into()
+                }
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    Some(level[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use zk_protocol::{ProofBundle, ProofMode};
+
+    fn proof(id: &str) -> SessionProof {
+        SessionProof {
+            id: id.into(),
+            tool: "attest_price".into(),
+            kind: "price".into(),
+            claim: json!({}),
+            bundle: ProofBundle {
+                proof: vec![1],
+                public_values: vec![2],
+                vk_hash: "0x00".into(),
+                program_id: "prog".into(),
+                elf_hash: "0x01".into(),
+                proof_mode: ProofMode::Groth16,
+                created_at: 0,
+            },
+            verified: None,
+            prev_hash: None,
+            principal: None,
+            request_id: None,
+        }
+    }
+
+    fn linked(ids: &[&str]) -> Vec<SessionProof> {
+        let mut proofs: Vec<SessionProof> = Vec::new();
+        for id in ids {
+            let mut next = proof(id);
+            next.prev_hash = proofs.last().map(|p| to_hex(&proof_hash(p)));
+            proofs.push(next);
+        }
+        proofs
+    }
+
+    #[test]
+    fn test_removal_and_insertion_are_detected() {
+        let proofs = linked(&["a", "b", "c"]);
+        assert_eq!(first_broken_link(&proofs), None);
+
+        let mut removed = proofs.clone();
+        removed.remove(1);
+        assert_eq!(first_broken_link(&removed), Some(1));
+
+        let mut inserted = proofs.clone();
+        inserted.insert(2, proof("x"));
+        assert_eq!(first_broken_link(&inserted), Some(2));
+    }
+
+    #[test]
+    fn test_merkle_root_commits_to_every_proof() {
+        assert!(merkle_root(&[]).is_none());
+        let proofs = linked(&["a", "b", "c"]);
+        let root = merkle_root(&proofs).unwrap();
+        assert_eq!(merkle_root(&proofs), Some(root));
+        assert_ne!(merkle_root(&proofs[..2]), Some(root));
+
+        let mut altered = proofs.clone();
+        altered[2].claim = json!({ "price_cents": 1 });
+        assert_ne!(merkle_root(&altered), Some(root));
+    }
+}
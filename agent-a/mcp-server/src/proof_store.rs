@@ -0,0 +1,672 @@
+//! Storage for session proofs: in-memory, or SQLite so proofs survive restarts
+//!
+//! Both backends keep proofs in recording order, link each to the hash of the previous
+//! proof in its session (see [`crate::proof_chain`]), and answer the same queries: a whole
+//! session (for export), or a filtered page (`GET /proofs`). Pages are cursor-based;
+//! the cursor is the position of the last proof returned, so concurrent inserts never
+//! shift a page.
+//!
+//! On-chain checks made after recording (Agent A's background verifier) are stored next
+//! to a proof as a [`ChainVerification`] rather than edited into it, so they don't break
+//! the hash links.
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::proof_chain::{proof_hash, to_hex};
+use crate::session_bundle::SessionProof;
+
+/// Default and maximum page size
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Query filters; every field is optional and they combine with AND
+#[derive(Debug, Clone, Default)]
+pub struct ProofFilter {
+    pub session_id: Option<String>,
+    /// Tool that produced the proof
+    pub tool: Option<String>,
+    /// Workflow stage ("price", "booking", "attestation")
+    pub kind: Option<String>,
+    pub verified: Option<bool>,
+    /// Authenticated caller that produced the proof
+    pub principal: Option<String>,
+    /// Correlation ID of the request that produced the proof
+    pub request_id: Option<String>,
+    /// Proof created at or after (unix seconds)
+    pub since: Option<u64>,
+    /// Proof created before (unix seconds)
+    pub until: Option<u64>,
+}
+
+impl ProofFilter {
+    fn matches(&self, proof: &StoredProof) -> bool {
+        let created_at = proof.proof.bundle.created_at;
+        self.session_id.as_deref().is_none_or(|s| s == proof.session_id)
+            && self.tool.as_deref().is_none_or(|t| t == proof.proof.tool)
+            && self.kind.as_deref().is_none_or(|k| k == proof.proof.kind)
+            && self.verified.is_none_or(|v| proof.verified() == Some(v))
+            && self.principal.as_deref().is_none_or(|p| proof.proof.principal.as_deref() == Some(p))
+            && self.request_id.as_deref().is_none_or(|r| proof.proof.request_id.as_deref() == Some(r))
+            && self.since.is_none_or(|since| created_at >= since)
+            && self.until.is_none_or(|until| created_at < until)
+    }
+}
+
+/// Limits enforced by [`ProofStore::sweep`]; unset limits don't apply
+///
+/// Oldest proofs go first. A session trimmed from the front no longer passes
+/// [`first_broken_link`](crate::proof_chain::first_broken_link), so archive sessions
+/// whose trail must stay provable before the limits reach them.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    /// Drop proofs created more than this many seconds ago
+    pub max_age_secs: Option<u64>,
+    /// Keep only the newest N proofs of each session
+    pub max_per_session: Option<usize>,
+    /// Keep the newest proofs whose serialized size fits in this many bytes
+    pub max_total_bytes: Option<u64>,
+}
+
+/// What the store currently holds
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StoreStats {
+    pub sessions: usize,
+    pub proofs: usize,
+    /// Serialized size of the stored proofs
+    pub bytes: u64,
+}
+
+/// Result of verifying a recorded proof on-chain after the fact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainVerification {
+    pub verified: bool,
+    /// Set when the check was a signed transaction rather than an eth_call
+    pub tx_hash: Option<String>,
+    pub block_number: Option<u64>,
+    pub error: Option<String>,
+    /// Unix seconds
+    pub checked_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredProof {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub proof: SessionProof,
+    pub verification: Option<ChainVerification>,
+}
+
+impl StoredProof {
+    /// Latest known on-chain status: the background check if done, else the one at recording
+    pub fn verified(&self) -> Option<bool> {
+        self.verification.as_ref().map(|v| v.verified).or(self.proof.verified)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofPage {
+    pub proofs: Vec<StoredProof>,
+    /// Pass as `cursor` to get the next page; `None` on the last page
+    pub next_cursor: Option<u64>,
+}
+
+pub trait ProofStore: Send + Sync {
+    /// Add `proof` to `session_id`; false if its id was already recorded
+    fn record(&self, session_id: &str, proof: SessionProof) -> Result<bool>;
+
+    /// Every proof of a session in recording order, with any later verification
+    fn records(&self, session_id: &str) -> Result<Vec<StoredProof>>;
+
+    /// Up to `limit` matching proofs recorded after `cursor`
+    fn query(&self, filter: &ProofFilter, cursor: Option<u64>, limit: usize) -> Result<ProofPage>;
+
+    /// Oldest proofs with no on-chain status yet that the verifier contract can check
+    fn pending_verification(&self, limit: usize) -> Result<Vec<StoredProof>>;
+
+    /// Attach an on-chain check to a recorded proof
+    fn set_verification(&self, session_id: &str, id: &str, verification: &ChainVerification) -> Result<()>;
+
+    /// Delete every proof of a session; returns how many were removed
+    fn remove_session(&self, session_id: &str) -> Result<usize>;
+
+    /// Delete proofs beyond `policy` as of `now` (unix seconds); returns how many were removed
+    fn sweep(&self, policy: &RetentionPolicy, now: u64) -> Result<usize>;
+
+    fn stats(&self) -> Result<StoreStats>;
+
+    /// Every proof of a session in recording order; `None` for unknown sessions
+    fn session(&self, session_id: &str) -> Result<Option<Vec<SessionProof>>> {
+        let proofs: Vec<SessionProof> = self.records(session_id)?.into_iter().map(|p| p.proof).collect();
+        Ok((!proofs.is_empty()).then_some(proofs))
+    }
+}
+
+/// Proofs held in memory; lost on restart
+#[derive(Default)]
+pub struct MemoryProofStore {
+    proofs: Mutex<Vec<StoredProof>>,
+}
+
+impl MemoryProofStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProofStore for MemoryProofStore {
+    fn record(&self, session_id: &str, mut proof: SessionProof) -> Result<bool> {
+        let mut proofs = self.proofs.lock().unwrap();
+        if proofs.iter().any(|p| p.session_id == session_id && p.proof.id == proof.id) {
+            return Ok(false);
+        }
+        proof.prev_hash = proofs
+            .iter()
+            .rev()
+            .find(|p| p.session_id == session_id)
+            .map(|p| to_hex(&proof_hash(&p.proof)));
+        proofs.push(StoredProof { session_id: session_id.to_string(), proof, verification: None });
+        Ok(true)
+    }
+
+    fn records(&self, session_id: &str) -> Result<Vec<StoredProof>> {
+        Ok(self
+            .proofs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.session_id == session_id)
+            .cloned()
+            .collect())
+    }
+
+    fn query(&self, filter: &ProofFilter, cursor: Option<u64>, limit: usize) -> Result<ProofPage> {
+        let proofs = self.proofs.lock().unwrap();
+        // Cursor = 1-based position of the last proof returned
+        let mut matching = proofs
+            .iter()
+            .enumerate()
+            .skip(cursor.unwrap_or(0) as usize)
+            .filter(|(_, p)| filter.matches(p));
+
+        let page: Vec<(usize, &StoredProof)> = matching.by_ref().take(limit).collect();
+        let next_cursor = match (page.last(), matching.next()) {
+            (Some((i, _)), Some(_)) => Some(*i as u64 + 1),
+            _ => None,
+        };
+        Ok(ProofPage {
+            proofs: page.into_iter().map(|(_, p)| p.clone()).collect(),
+            next_cursor,
+        })
+    }
+
+    fn pending_verification(&self, limit: usize) -> Result<Vec<StoredProof>> {
+        Ok(self
+            .proofs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.verified().is_none() && p.proof.onchain_compatible())
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn set_verification(&self, session_id: &str, id: &str, verification: &ChainVerification) -> Result<()> {
+        let mut proofs = self.proofs.lock().unwrap();
+        let proof = proofs
+            .iter_mut()
+            .find(|p| p.session_id == session_id && p.proof.id == id)
+            .ok_or_else(|| anyhow::anyhow!("No proof {} in session {}", id, session_id))?;
+        proof.verification = Some(verification.clone());
+        Ok(())
+    }
+
+    fn remove_session(&self, session_id: &str) -> Result<usize> {
+        let mut proofs = self.proofs.lock().unwrap();
+        let before = proofs.len();
+        proofs.retain(|p| p.session_id != session_id);
+        Ok(before - proofs.len())
+    }
+
+    fn sweep(&self, policy: &RetentionPolicy, now: u64) -> Result<usize> {
+        let mut proofs = self.proofs.lock().unwrap();
+        let before = proofs.len();
+
+        if let Some(max_age) = policy.max_age_secs {
+            let cutoff = now.saturating_sub(max_age);
+            proofs.retain(|p| p.proof.bundle.created_at >= cutoff);
+        }
+        if let Some(max) = policy.max_per_session {
+            // Count from the newest end so each session keeps its latest `max`
+            let mut kept: HashMap<&str, usize> = HashMap::new();
+            let mut keep = vec![false; proofs.len()];
+            for (i, p) in proofs.iter().enumerate().rev() {
+                let n = kept.entry(p.session_id.as_str()).or_default();
+                *n += 1;
+                keep[i] = *n <= max;
+            }
+            let mut keep = keep.into_iter();
+            proofs.retain(|_| keep.next().unwrap_or(true));
+        }
+        if let Some(max_bytes) = policy.max_total_bytes {
+            let mut total: u64 = proofs.iter().map(|p| stored_size(&p.proof)).sum();
+            let mut drop = 0;
+            while total > max_bytes && drop < proofs.len() {
+                total -= stored_size(&proofs[drop].proof);
+                drop += 1;
+            }
+            proofs.drain(..drop);
+        }
+        Ok(before - proofs.len())
+    }
+
+    fn stats(&self) -> Result<StoreStats> {
+        let proofs = self.proofs.lock().unwrap();
+        let mut sessions: Vec<&str> = proofs.iter().map(|p| p.session_id.as_str()).collect();
+        sessions.sort_unstable();
+        sessions.dedup();
+        Ok(StoreStats {
+            sessions: sessions.len(),
+            proofs: proofs.len(),
+            bytes: proofs.iter().map(|p| stored_size(&p.proof)).sum(),
+        })
+    }
+}
+
+/// Size a proof takes in storage (its JSON, as kept by the SQLite backend)
+fn stored_size(proof: &SessionProof) -> u64 {
+    serde_json::to_vec(proof).map(|json| json.len() as u64).unwrap_or_default()
+}
+
+/// Proofs persisted in a SQLite database
+pub struct SqliteProofStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteProofStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        // Lets sweeps hand freed pages back to the filesystem (new databases only)
+        conn.execute_batch("PRAGMA auto_vacuum = INCREMENTAL")?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS proofs (
+                seq        INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                id         TEXT NOT NULL,
+                tool       TEXT NOT NULL,
+                kind       TEXT NOT NULL,
+                verified   INTEGER,
+                created_at INTEGER NOT NULL,
+                proof      TEXT NOT NULL,
+                UNIQUE (session_id, id)
+            );
+            CREATE INDEX IF NOT EXISTS proofs_by_session ON proofs (session_id, seq);",
+        )?;
+        // Databases created before background verification lack the column
+        if conn.prepare("SELECT verification FROM proofs LIMIT 0").is_err() {
+            conn.execute_batch("ALTER TABLE proofs ADD COLUMN verification TEXT")?;
+        }
+        // ... and before proofs were attributed to a principal and request
+        if conn.prepare("SELECT principal FROM proofs LIMIT 0").is_err() {
+            conn.execute_batch("ALTER TABLE proofs ADD COLUMN principal TEXT")?;
+        }
+        if conn.prepare("SELECT request_id FROM proofs LIMIT 0").is_err() {
+            conn.execute_batch("ALTER TABLE proofs ADD COLUMN request_id TEXT")?;
+        }
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+}
+
+impl ProofStore for SqliteProofStore {
+    fn record(&self, session_id: &str, mut proof: SessionProof) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM proofs WHERE session_id = ?1 AND id = ?2)",
+            [session_id, &proof.id],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Ok(false);
+        }
+        let prev: Option<String> = conn
+            .query_row(
+                "SELECT proof FROM proofs WHERE session_id = ?1 ORDER BY seq DESC LIMIT 1",
+                [session_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        proof.prev_hash = match prev {
+            Some(json) => Some(to_hex(&proof_hash(&serde_json::from_str(&json)?))),
+            None => None,
+        };
+
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO proofs (session_id, id, tool, kind, verified, created_at, proof, principal, request_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                session_id,
+                proof.id,
+                proof.tool,
+                proof.kind,
+                proof.verified,
+                proof.bundle.created_at as i64,
+                serde_json::to_string(&proof)?,
+                proof.principal,
+                proof.request_id,
+            ],
+        )?;
+        Ok(inserted == 1)
+    }
+
+    fn records(&self, session_id: &str) -> Result<Vec<StoredProof>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT seq, session_id, proof, verification FROM proofs WHERE session_id = ?1 ORDER BY seq",
+        )?;
+        let rows = stmt.query_map([session_id], row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.into_iter().map(|(_, proof)| proof.decode()).collect()
+    }
+
+    fn query(&self, filter: &ProofFilter, cursor: Option<u64>, limit: usize) -> Result<ProofPage> {
+        let mut sql = String::from("SELECT seq, session_id, proof, verification FROM proofs WHERE seq > ?");
+        let mut args: Vec<Box<dyn ToSql>> = vec![Box::new(cursor.unwrap_or(0) as i64)];
+        let mut and = |clause: &str, arg: Box<dyn ToSql>| {
+            sql.push_str(" AND ");
+            sql.push_str(clause);
+            args.push(arg);
+        };
+        if let Some(session_id) = &filter.session_id {
+            and("session_id = ?", Box::new(session_id.clone()));
+        }
+        if let Some(tool) = &filter.tool {
+            and("tool = ?", Box::new(tool.clone()));
+        }
+        if let Some(kind) = &filter.kind {
+            and("kind = ?", Box::new(kind.clone()));
+        }
+        if let Some(verified) = filter.verified {
+            and("verified = ?", Box::new(verified));
+        }
+        if let Some(principal) = &filter.principal {
+            and("principal = ?", Box::new(principal.clone()));
+        }
+        if let Some(request_id) = &filter.request_id {
+            and("request_id = ?", Box::new(request_id.clone()));
+        }
+        if let Some(since) = filter.since {
+            and("created_at >= ?", Box::new(since as i64));
+        }
+        if let Some(until) = filter.until {
+            and("created_at < ?", Box::new(until as i64));
+        }
+        // One extra row tells us whether there is a next page
+        sql.push_str(" ORDER BY seq LIMIT ?");
+        args.push(Box::new(limit as i64 + 1));
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(args.iter()), row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let has_more = rows.len() > limit;
+        let mut proofs = Vec::with_capacity(limit);
+        let mut last_seq = None;
+        for (seq, row) in rows.into_iter().take(limit) {
+            proofs.push(row.decode()?);
+            last_seq = Some(seq as u64);
+        }
+        Ok(ProofPage {
+            proofs,
+            next_cursor: if has_more { last_seq } else { None },
+        })
+    }
+
+    fn pending_verification(&self, limit: usize) -> Result<Vec<StoredProof>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT seq, session_id, proof, verification FROM proofs WHERE verified IS NULL ORDER BY seq",
+        )?;
+        let rows = stmt.query_map([], row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut pending = Vec::with_capacity(limit);
+        for (_, row) in rows {
+            let proof = row.decode()?;
+            if proof.proof.onchain_compatible() {
+                pending.push(proof);
+                if pending.len() == limit {
+                    break;
+                }
+            }
+        }
+        Ok(pending)
+    }
+
+    fn set_verification(&self, session_id: &str, id: &str, verification: &ChainVerification) -> Result<()> {
+        let updated = self.conn.lock().unwrap().execute(
+            "UPDATE proofs SET verified = ?1, verification = ?2 WHERE session_id = ?3 AND id = ?4",
+            params![verification.verified, serde_json::to_string(verification)?, session_id, id],
+        )?;
+        if updated == 0 {
+            anyhow::bail!("No proof {} in session {}", id, session_id);
+        }
+        Ok(())
+    }
+
+    fn remove_session(&self, session_id: &str) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute("DELETE FROM proofs WHERE session_id = ?1", [session_id])?;
+        conn.execute_batch("PRAGMA incremental_vacuum")?;
+        Ok(removed)
+    }
+
+    fn sweep(&self, policy: &RetentionPolicy, now: u64) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let mut removed = 0;
+        if let Some(max_age) = policy.max_age_secs {
+            removed += conn.execute(
+                "DELETE FROM proofs WHERE created_at < ?1",
+                [now.saturating_sub(max_age) as i64],
+            )?;
+        }
+        if let Some(max) = policy.max_per_session {
+            removed += conn.execute(
+                "DELETE FROM proofs WHERE seq IN (
+                    SELECT seq FROM (
+                        SELECT seq, ROW_NUMBER() OVER (PARTITION BY session_id ORDER BY seq DESC) AS n
+                        FROM proofs
+                    ) WHERE n > ?1
+                )",
+                [max as i64],
+            )?;
+        }
+        if let Some(max_bytes) = policy.max_total_bytes {
+            // Newest-first running total; everything from the first row over the limit goes
+            removed += conn.execute(
+                "DELETE FROM proofs WHERE seq <= (
+                    SELECT MAX(seq) FROM (
+                        SELECT seq, SUM(LENGTH(proof)) OVER (ORDER BY seq DESC) AS running
+                        FROM proofs
+                    ) WHERE running > ?1
+                )",
+                [max_bytes as i64],
+            )?;
+        }
+        if removed > 0 {
+            conn.execute_batch("PRAGMA incremental_vacuum")?;
+        }
+        Ok(removed)
+    }
+
+    fn stats(&self) -> Result<StoreStats> {
+        let conn = self.conn.lock().unwrap();
+        let (sessions, proofs, bytes) = conn.query_row(
+            "SELECT COUNT(DISTINCT session_id), COUNT(*), COALESCE(SUM(LENGTH(proof)), 0) FROM proofs",
+            [],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)),
+        )?;
+        Ok(StoreStats { sessions: sessions as usize, proofs: proofs as usize, bytes: bytes as u64 })
+    }
+}
+
+/// Undecoded `seq, session_id, proof, verification` row
+struct Row {
+    session_id: String,
+    proof: String,
+    verification: Option<String>,
+}
+
+impl Row {
+    fn decode(self) -> Result<StoredProof> {
+        Ok(StoredProof {
+            session_id: self.session_id,
+            proof: serde_json::from_str(&self.proof)?,
+            verification: self.verification.as_deref().map(serde_json::from_str).transpose()?,
+        })
+    }
+}
+
+fn row(row: &rusqlite::Row) -> rusqlite::Result<(i64, Row)> {
+    Ok((
+        row.get(0)?,
+        Row { session_id: row.get(1)?, proof: row.get(2)?, verification: row.get(3)? },
+    ))
+}
+
+/// SQLite at `path` if set, else memory
+pub fn open(path: Option<&str>) -> Result<Box<dyn ProofStore>> {
+    Ok(match path {
+        Some(path) => Box::new(SqliteProofStore::open(path)?),
+        None => Box::new(MemoryProofStore::new()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use zk_protocol::{ProofBundle, ProofMode};
+
+    fn proof(id: &str, kind: &str, verified: Option<bool>, created_at: u64) -> SessionProof {
+        SessionProof {
+            id: id.into(),
+            tool: format!("attest_{}", kind),
+            kind: kind.into(),
+            claim: json!({}),
+            bundle: ProofBundle {
+                proof: vec![1],
+                public_values: vec![2],
+                vk_hash: "0x00".into(),
+                program_id: "prog".into(),
+                elf_hash: "0x01".into(),
+                proof_mode: ProofMode::Groth16,
+                created_at,
+            },
+            verified,
+            prev_hash: None,
+            principal: None,
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_backends_filter_and_paginate_alike() {
+        let stores: [Box<dyn ProofStore>; 2] =
+            [Box::new(MemoryProofStore::new()), Box::new(SqliteProofStore::open_in_memory().unwrap())];
+        for store in stores {
+            assert!(store.record("s1", proof("a", "price", Some(true), 100)).unwrap());
+            assert!(!store.record("s1", proof("a", "price", Some(true), 100)).unwrap());
+            store.record("s1", proof("b", "booking", Some(true), 200)).unwrap();
+            store.record("s2", proof("c", "price", None, 300)).unwrap();
+            store.record("s2", proof("d", "price", Some(true), 400)).unwrap();
+
+            let s1 = store.session("s1").unwrap().unwrap();
+            assert_eq!(s1.len(), 2);
+            assert!(s1[0].prev_hash.is_none());
+            assert_eq!(crate::proof_chain::first_broken_link(&s1), None);
+            assert!(store.session("s3").unwrap().is_none());
+
+            let filter = ProofFilter { kind: Some("price".into()), verified: Some(true), ..Default::default() };
+            let first = store.query(&filter, None, 1).unwrap();
+            assert_eq!(first.proofs[0].proof.id, "a");
+            let second = store.query(&filter, first.next_cursor, 1).unwrap();
+            assert_eq!(second.proofs[0].proof.id, "d");
+            assert!(second.next_cursor.is_none());
+
+            let pending: Vec<_> = store.pending_verification(10).unwrap().into_iter().map(|p| p.proof.id).collect();
+            assert_eq!(pending, ["c"]);
+            let check = ChainVerification {
+                verified: true,
+                tx_hash: Some("0xabc".into()),
+                block_number: Some(1),
+                error: None,
+                checked_at: 500,
+            };
+            store.set_verification("s2", "c", &check).unwrap();
+            assert!(store.pending_verification(10).unwrap().is_empty());
+            assert_eq!(store.records("s2").unwrap()[0].verified(), Some(true));
+            assert!(store.set_verification("s2", "missing", &check).is_err());
+
+            let stats = store.stats().unwrap();
+            assert_eq!((stats.sessions, stats.proofs), (2, 4));
+
+            let mut attributed = proof("e", "price", None, 500);
+            attributed.principal = Some("ops".into());
+            attributed.request_id = Some("req-1".into());
+            store.record("s2", attributed).unwrap();
+            let by_ops = ProofFilter { principal: Some("ops".into()), ..Default::default() };
+            let ids: Vec<_> = store.query(&by_ops, None, 10).unwrap().proofs.into_iter().map(|p| p.proof.id).collect();
+            assert_eq!(ids, ["e"]);
+            let by_request = ProofFilter { request_id: Some("req-1".into()), ..Default::default() };
+            assert_eq!(store.query(&by_request, None, 10).unwrap().proofs.len(), 1);
+            let recorded = &store.records("s2").unwrap()[2].proof;
+            assert_eq!(recorded.principal.as_deref(), Some("ops"));
+            assert_eq!(recorded.request_id.as_deref(), Some("req-1"));
+
+            let window = ProofFilter { since: Some(200), until: Some(400), ..Default::default() };
+            let ids: Vec<_> = store.query(&window, None, 10).unwrap().proofs.into_iter().map(|p| p.proof.id).collect();
+            assert_eq!(ids, ["b", "c"]);
+        }
+    }
+
+    #[test]
+    fn test_backends_sweep_alike() {
+        let stores: [Box<dyn ProofStore>; 2] =
+            [Box::new(MemoryProofStore::new()), Box::new(SqliteProofStore::open_in_memory().unwrap())];
+        for store in stores {
+            for (session, id, created_at) in [("s1", "a", 100), ("s1", "b", 200), ("s1", "c", 300), ("s2", "d", 400)] {
+                store.record(session, proof(id, "price", None, created_at)).unwrap();
+            }
+            let ids = |session: &str| -> Vec<String> {
+                store.records(session).unwrap().into_iter().map(|p| p.proof.id).collect()
+            };
+
+            let by_age = RetentionPolicy { max_age_secs: Some(250), ..Default::default() };
+            assert_eq!(store.sweep(&by_age, 400).unwrap(), 1);
+            assert_eq!(ids("s1"), ["b", "c"]);
+
+            let per_session = RetentionPolicy { max_per_session: Some(1), ..Default::default() };
+            assert_eq!(store.sweep(&per_session, 400).unwrap(), 1);
+            assert_eq!(ids("s1"), ["c"]);
+
+            let size = store.stats().unwrap().bytes;
+            let by_size = RetentionPolicy { max_total_bytes: Some(size - 1), ..Default::default() };
+            assert_eq!(store.sweep(&by_size, 400).unwrap(), 1);
+            assert!(ids("s1").is_empty());
+
+            assert_eq!(store.remove_session("s2").unwrap(), 1);
+            assert_eq!(store.stats().unwrap().proofs, 0);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Per-session proof export (`GET /sessions/{id}/proof-bundle`)
+//!
+//! Proofs produced for requests tagged with a `session_id` are kept in a
+//! [`ProofStore`](crate::proof_store::ProofStore). The exported [`SessionBundle`]
+//! carries everything needed to re-check them without Agent A: each [`ProofBundle`]
+//! (proof, public values, VK hash), the chain and contract to verify against, a
+//! manifest hash over the entries, and the Merkle root of the hash-linked trail.
+//! Crypto payments settled in the session are listed with their transaction hashes, and
+//! payments the client's spending policy refused as denied actions.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use zk_protocol::ProofBundle;
+
+use crate::crypto_payment::CryptoPayment;
+use crate::proof_chain;
+
+/// One proven step of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionProof {
+    /// Attester job id, or a fresh id for pipeline proofs
+    pub id: String,
+    /// Tool that produced the proof (attest_price, attest_booking, request_attestation)
+    pub tool: String,
+    /// Workflow stage: "price", "booking", "refund", or "attestation"; "mismatch" for a proof whose
+    /// claim contradicted the quote shown (recorded with `verified: Some(false)` so it is
+    /// never submitted on-chain)
+    pub kind: String,
+    /// What the proof claims (price and quote, booking confirmation, claimed output)
+    pub claim: Value,
+    pub bundle: ProofBundle,
+    /// On-chain verification result at the time, if checked
+    pub verified: Option<bool>,
+    /// Hash of the previous proof in the session; set by the store when recorded
+    #[serde(default)]
+    pub prev_hash: Option<String>,
+    /// Authenticated caller whose request produced the proof; `None` with auth off.
+    /// Omitted when unset so proofs recorded before the field existed hash the same.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub principal: Option<String>,
+    /// Correlation ID (`x-request-id`) of that request, to find it in the logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl SessionProof {
+    /// Whether the ZeroProof contract can check it (mock proofs carry no proof bytes)
+    pub fn onchain_compatible(&self) -> bool {
+        !self.bundle.proof.is_empty()
+    }
+}
+
+/// A payment the client's spending policy refused before any payment tool ran
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeniedAction {
+    /// Payment tool that would have run, e.g. "initiate-purchase-instruction"
+    pub tool: String,
+    /// Rule that refused it: "max_per_transaction", "max_per_day", "merchant", or "route"
+    pub rule: String,
+    /// What the user was told
+    pub message: String,
+    pub amount_cents: Option<u64>,
+    pub currency: Option<String>,
+    /// Unix seconds; set by the server
+    #[serde(default)]
+    pub denied_at: u64,
+}
+
+/// Where the proofs can be verified on-chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainMetadata {
+    pub chain_id: Option<u64>,
+    /// ZeroProof verifier contract
+    pub zeroproof_address: String,
+}
+
+/// Self-contained export of every proof recorded for a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionBundle {
+    pub session_id: String,
+    pub chain: ChainMetadata,
+    pub proofs: Vec<SessionProof>,
+    /// 0x-prefixed SHA-256 of the JSON-serialized `proofs`
+    pub manifest_hash: String,
+    /// Root to compare against an anchored one (see [`crate::proof_chain`])
+    pub merkle_root: Option<String>,
+    /// Unix seconds when the bundle was exported
+    pub exported_at: u64,
+    /// Crypto payments settled for the session (see [`crate::crypto_payment`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub payments: Vec<CryptoPayment>,
+    /// Payments refused by the client's spending policy, in the order they were denied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_actions: Vec<DeniedAction>,
+}
+
+impl SessionBundle {
+    /// Bundle `proofs` (in recording order) with a fresh manifest hash
+    pub fn new(session_id: &str, chain: ChainMetadata, proofs: Vec<SessionProof>) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            chain,
+            manifest_hash: manifest_hash(&proofs),
+            merkle_root: proof_chain::merkle_root(&proofs).map(|root| proof_chain::to_hex(&root)),
+            proofs,
+            exported_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            payments: Vec::new(),
+            denied_actions: Vec::new(),
+        }
+    }
+
+    /// Attach the session's settled payments; they aren't covered by the manifest hash
+    pub fn with_payments(self, payments: Vec<CryptoPayment>) -> Self {
+        Self { payments, ..self }
+    }
+
+    /// Attach the session's denied payments; like payments, outside the manifest hash
+    pub fn with_denied_actions(self, denied_actions: Vec<DeniedAction>) -> Self {
+        Self { denied_actions, ..self }
+    }
+
+    /// Recompute the manifest hash; false if any proof entry was altered
+    pub fn verify_manifest(&self) -> bool {
+        manifest_hash(&self.proofs).eq_ignore_ascii_case(&self.manifest_hash)
+    }
+
+    /// Re-check the manifest, the Merkle root, and every proof offline
+    /// (see [`crate::verify_offchain`]); mock proofs are listed as skipped
+    pub fn audit(&self) -> BundleAudit {
+        let mut audit = BundleAudit {
+            session_id: self.session_id.clone(),
+            manifest_valid: self.verify_manifest(),
+            merkle_root_valid: proof_chain::merkle_root(&self.proofs).map(|root| proof_chain::to_hex(&root))
+                == self.merkle_root,
+            verified: 0,
+            failed: 0,
+            skipped: Vec::new(),
+            results: Vec::new(),
+        };
+        for proof in &self.proofs {
+            if !proof.onchain_compatible() {
+                audit.skipped.push(proof.id.clone());
+                continue;
+            }
+            let result = crate::verify_offchain(&proof.bundle);
+            if result.verified {
+                audit.verified += 1;
+            } else {
+                audit.failed += 1;
+            }
+            audit.results.push(ProofAudit { id: proof.id.clone(), result });
+        }
+        audit
+    }
+}
+
+/// Offline check of an exported [`SessionBundle`]
+#[derive(Debug, Serialize)]
+pub struct BundleAudit {
+    pub session_id: String,
+    /// The proofs match the manifest hash
+    pub manifest_valid: bool,
+    /// The proofs' hash chain matches the bundle's Merkle root
+    pub merkle_root_valid: bool,
+    pub verified: usize,
+    pub failed: usize,
+    /// Ids of mock proofs, which carry no proof bytes
+    pub skipped: Vec<String>,
+    pub results: Vec<ProofAudit>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProofAudit {
+    pub id: String,
+    #[serde(flatten)]
+    pub result: crate::OffchainVerification,
+}
+
+fn manifest_hash(proofs: &[SessionProof]) -> String {
+    let bytes = serde_json::to_vec(proofs).unwrap_or_default();
+    format!("0x{}", hex::encode(Sha256::digest(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use zk_protocol::ProofMode;
+
+    #[test]
+    fn test_manifest_detects_tampering() {
+        let proof = SessionProof {
+            id: "job-1".into(),
+            tool: "attest_price".into(),
+            kind: "price".into(),
+            claim: json!({ "price_cents": 68000, "quote_id": "q_1" }),
+            bundle: ProofBundle {
+                proof: vec![1, 2, 3],
+                public_values: vec![4, 5],
+                vk_hash: "0x00".into(),
+                program_id: "prog".into(),
+                elf_hash: "0x01".into(),
+                proof_mode: ProofMode::Groth16,
+                created_at: 0,
+            },
+            verified: Some(true),
+            prev_hash: None,
+            principal: None,
+            request_id: None,
+        };
+        let chain = ChainMetadata { chain_id: Some(11155111), zeroproof_address: "0xabc".into() };
+        let mut mock = proof.clone();
+        mock.id = "mock-1".into();
+        mock.bundle.proof.clear();
+        let mut bundle = SessionBundle::new("s1", chain, vec![proof, mock]);
+        assert!(bundle.verify_manifest());
+
+        // Garbage proof bytes fail the pairing check; the mock is skipped
+        let audit = bundle.audit();
+        assert!(audit.manifest_valid && audit.merkle_root_valid);
+        assert_eq!((audit.verified, audit.failed), (0, 1));
+        assert_eq!(audit.skipped, ["mock-1"]);
+
+        bundle.proofs[0].claim = json!({ "price_cents": 100, "quote_id": "q_1" });
+        assert!(!bundle.verify_manifest());
+        assert!(!bundle.audit().merkle_root_valid);
+    }
+}
@@ -0,0 +1,133 @@
+//! Per-session serialization and rate limits for session-tagged HTTP calls
+//!
+//! Calls carrying the same `session_id` run one at a time, in arrival order, so two
+//! overlapping requests can't interleave their Agent B / attester steps. Admission is
+//! limited per session and globally over a sliding one-minute window; a rejected call
+//! gets a [`RateLimited`] explaining which limit was hit and when to retry.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OwnedMutexGuard;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimited {
+    /// "session <id>" or "global"
+    pub scope: String,
+    pub limit_per_minute: u32,
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Rate limit exceeded ({}: {} requests per minute); retry in {}s",
+            self.scope,
+            self.limit_per_minute,
+            self.retry_after.as_secs().max(1)
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Admission times within the last minute
+#[derive(Default)]
+struct Window(VecDeque<Instant>);
+
+impl Window {
+    /// Admit at `now` unless `limit` calls were already admitted this window (0 = unlimited)
+    fn check(&mut self, limit: u32, now: Instant) -> Result<(), Duration> {
+        while self.0.front().is_some_and(|t| now.duration_since(*t) >= WINDOW) {
+            self.0.pop_front();
+        }
+        if limit > 0 && self.0.len() >= limit as usize {
+            let oldest = self.0.front().copied().unwrap_or(now);
+            return Err(WINDOW.saturating_sub(now.duration_since(oldest)));
+        }
+        Ok(())
+    }
+}
+
+pub struct SessionGuard {
+    session_limit: u32,
+    global_limit: u32,
+    windows: Mutex<(Window, HashMap<String, Window>)>,
+    turns: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl SessionGuard {
+    /// Limits are calls per minute; 0 disables that limit
+    pub fn new(session_limit: u32, global_limit: u32) -> Self {
+        Self {
+            session_limit,
+            global_limit,
+            windows: Mutex::new((Window::default(), HashMap::new())),
+            turns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a call against the global and (if tagged) session limits
+    pub fn admit(&self, session_id: Option<&str>) -> Result<(), RateLimited> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+        let (global, sessions) = &mut *windows;
+        sessions.retain(|_, w| w.0.back().is_some_and(|t| now.duration_since(*t) < WINDOW));
+
+        global.check(self.global_limit, now).map_err(|retry_after| RateLimited {
+            scope: "global".to_string(),
+            limit_per_minute: self.global_limit,
+            retry_after,
+        })?;
+        if let Some(session_id) = session_id {
+            let session = sessions.entry(session_id.to_string()).or_default();
+            session.check(self.session_limit, now).map_err(|retry_after| RateLimited {
+                scope: format!("session {}", session_id),
+                limit_per_minute: self.session_limit,
+                retry_after,
+            })?;
+            session.0.push_back(now);
+        }
+        global.0.push_back(now);
+        Ok(())
+    }
+
+    /// Wait for the session's previous call to finish; hold the guard for the whole call
+    pub async fn turn(&self, session_id: Option<&str>) -> Option<OwnedMutexGuard<()>> {
+        let lock = {
+            let mut turns = self.turns.lock().unwrap();
+            // Locks nobody holds or waits on can go
+            turns.retain(|_, lock| Arc::strong_count(lock) > 1);
+            turns.entry(session_id?.to_string()).or_default().clone()
+        };
+        Some(lock.lock_owned().await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limits_and_serializes_sessions() {
+        let guard = SessionGuard::new(2, 3);
+        assert!(guard.admit(Some("a")).is_ok());
+        assert!(guard.admit(Some("a")).is_ok());
+        let err = guard.admit(Some("a")).unwrap_err();
+        assert_eq!(err.scope, "session a");
+
+        assert!(guard.admit(Some("b")).is_ok());
+        assert_eq!(guard.admit(None).unwrap_err().scope, "global");
+
+        let first = guard.turn(Some("a")).await;
+        assert!(first.is_some());
+        let second = tokio::time::timeout(Duration::from_millis(50), guard.turn(Some("a"))).await;
+        assert!(second.is_err(), "second turn must wait for the first");
+        assert!(guard.turn(Some("b")).await.is_some());
+        drop(first);
+        assert!(guard.turn(Some("a")).await.is_some());
+    }
+}
@@ -0,0 +1,188 @@
+//! TAP signatures on Agent A's outbound calls
+//!
+//! With `agent_a_signing_key` (AGENT_A_SIGNING_KEY) set, requests to Agent B carry
+//! `Signature-Input` / `Signature` headers from [`zk_protocol::tap`], so Agent B can
+//! require that calls come from this agent. Keys live in a [`TapKeyStore`] installed once
+//! at startup and picked up by [`WithTapSignature::tap_json`], like correlation IDs.
+//!
+//! The store may hold several keys. Each key's `kid` is its did:key; requests are signed
+//! with the newest active key unless a kid is asked for. With a rotation interval, a fresh
+//! key takes over on schedule and the previous ones stay published in the JWKS
+//! (`GET /.well-known/tap-jwks.json`) for a grace period so in-flight signatures still
+//! verify. Rotated keys are generated in memory and don't survive a restart.
+
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use zk_protocol::tap::{
+    Jwk, Jwks, TapError, TapHeaders, TapHttpRequestBuilder, CONTENT_DIGEST_HEADER, SIGNATURE_HEADER,
+    SIGNATURE_INPUT_HEADER,
+};
+use zk_protocol::AgentIdentity;
+
+static KEYS: OnceLock<TapKeyStore> = OnceLock::new();
+
+struct TapKey {
+    identity: AgentIdentity,
+    created_at: u64,
+    /// Set once rotated out; the key stays published until then
+    retires_at: Option<u64>,
+}
+
+pub struct TapKeyStore {
+    keys: RwLock<Vec<TapKey>>,
+    rotation: Option<Rotation>,
+}
+
+struct Rotation {
+    every: Duration,
+    grace: Duration,
+}
+
+impl TapKeyStore {
+    /// Store with `identities` all active, the last one signing by default
+    pub fn new(identities: Vec<AgentIdentity>, now: u64) -> Self {
+        let keys = identities
+            .into_iter()
+            .map(|identity| TapKey { identity, created_at: now, retires_at: None })
+            .collect();
+        Self { keys: RwLock::new(keys), rotation: None }
+    }
+
+    /// Replace the signing key every `every`, keeping old keys published for `grace`
+    pub fn with_rotation(mut self, every: Duration, grace: Duration) -> Self {
+        self.rotation = Some(Rotation { every, grace });
+        self
+    }
+
+    /// did:keys of the active (not rotated-out) keys, newest last
+    pub fn active_kids(&self) -> Vec<String> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|key| key.retires_at.is_none())
+            .map(|key| key.identity.did())
+            .collect()
+    }
+
+    /// Sign a JSON request with `kid`, or the newest active key
+    pub fn sign(&self, kid: Option<&str>, method: &str, url: &str, body: &[u8]) -> Result<TapHeaders, TapError> {
+        let keys = self.keys.read().unwrap();
+        let mut active = keys.iter().filter(|key| key.retires_at.is_none());
+        let key = match kid {
+            Some(kid) => active.find(|key| key.identity.did() == kid),
+            None => active.next_back(),
+        }
+        .ok_or_else(|| TapError::InvalidKey(format!("no active TAP key {}", kid.unwrap_or_default())))?;
+
+        TapHttpRequestBuilder::new(&key.identity, method, url)
+            .components(&["@method"])
+            .body(body)
+            .sign()
+    }
+
+    /// Rotate if the newest active key is older than the interval, and drop keys past
+    /// their grace period; true if a new key was generated
+    pub fn rotate_if_due(&self, now: u64) -> bool {
+        let Some(rotation) = &self.rotation else {
+            return false;
+        };
+        let mut keys = self.keys.write().unwrap();
+        keys.retain(|key| key.retires_at.is_none_or(|at| at > now));
+
+        let newest = keys.iter().filter(|key| key.retires_at.is_none()).map(|key| key.created_at).max();
+        if newest.is_some_and(|created_at| created_at + rotation.every.as_secs() > now) {
+            return false;
+        }
+        let retires_at = now + rotation.grace.as_secs();
+        for key in keys.iter_mut().filter(|key| key.retires_at.is_none()) {
+            key.retires_at = Some(retires_at);
+        }
+        let identity = AgentIdentity::generate();
+        tracing::info!("✓ Rotated TAP signing key, now {}", identity.did());
+        keys.push(TapKey { identity, created_at: now, retires_at: None });
+        true
+    }
+
+    /// Public keys for `/.well-known/tap-jwks.json`, rotated-out ones with their `exp`
+    pub fn jwks(&self) -> Jwks {
+        Jwks {
+            keys: self
+                .keys
+                .read()
+                .unwrap()
+                .iter()
+                .map(|key| Jwk::from_identity(&key.identity, key.retires_at))
+                .collect(),
+        }
+    }
+}
+
+/// Sign outbound requests from `store` from now on; false if one was already set
+pub fn install(store: TapKeyStore) -> bool {
+    KEYS.set(store).is_ok()
+}
+
+/// The installed key store, if signing is on
+pub fn keys() -> Option<&'static TapKeyStore> {
+    KEYS.get()
+}
+
+/// Send a JSON body with TAP signature headers
+pub trait WithTapSignature {
+    /// Like `.json(body)`; when signing, the signature covers `@method` and the body's
+    /// `Content-Digest` as well as `@authority` and `@path`
+    fn tap_json<T: Serialize + ?Sized>(self, method: &str, url: &str, body: &T) -> Self;
+}
+
+impl WithTapSignature for reqwest::RequestBuilder {
+    fn tap_json<T: Serialize + ?Sized>(self, method: &str, url: &str, body: &T) -> Self {
+        let Some(keys) = KEYS.get() else {
+            return self.json(body);
+        };
+        let Ok(bytes) = serde_json::to_vec(body) else {
+            // Let reqwest report the serialization error on send
+            return self.json(body);
+        };
+        let request = self.header(CONTENT_TYPE, "application/json");
+        match keys.sign(None, method, url, &bytes) {
+            Ok(headers) => request
+                .header(SIGNATURE_INPUT_HEADER, headers.signature_input)
+                .header(SIGNATURE_HEADER, headers.signature)
+                .header(CONTENT_DIGEST_HEADER, headers.content_digest.unwrap_or_default())
+                .body(bytes),
+            Err(e) => {
+                tracing::warn!("Sending {} unsigned, TAP signing failed: {}", url, e);
+                request.body(bytes)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_old_key_published_through_grace() {
+        let store = TapKeyStore::new(vec![AgentIdentity::generate()], 0)
+            .with_rotation(Duration::from_secs(100), Duration::from_secs(50));
+        let first = store.active_kids()[0].clone();
+
+        assert!(!store.rotate_if_due(99));
+        assert!(store.rotate_if_due(100));
+        let second = store.active_kids();
+        assert_eq!(second.len(), 1);
+        assert_ne!(second[0], first);
+
+        let jwks = store.jwks();
+        assert!(jwks.public_key(&first, 120).is_some());
+        assert!(store.sign(Some(&first), "POST", "http://b/price", b"{}").is_err());
+        assert!(store.sign(None, "POST", "http://b/price", b"{}").is_ok());
+
+        store.rotate_if_due(151);
+        assert!(store.jwks().keys.iter().all(|key| key.kid != first));
+    }
+}
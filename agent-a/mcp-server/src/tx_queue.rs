@@ -0,0 +1,276 @@
+//! Sends every transaction of the signing wallet, one at a time
+//!
+//! Sessions verifying proofs concurrently would otherwise each read the same pending
+//! nonce and knock each other's transactions out of the mempool. [`TxQueue`] owns the
+//! nonce: it is read from the chain once, handed out under a lock while a transaction is
+//! broadcast, and re-read after anything that leaves it uncertain (a failed broadcast, a
+//! dropped transaction, "nonce too low"). Waiting for receipts happens outside the lock,
+//! so later submissions don't queue behind block times.
+//!
+//! A broadcast rejected as "replacement transaction underpriced" (or "already known")
+//! means a transaction with this nonce is already pending; the fees are raised by
+//! [`FEE_BUMP_PERCENT`] and it is resent, up to [`MAX_FEE_BUMPS`] times.
+//!
+//! Each transaction is tracked as a [`TxRecord`] under an optional label (Agent A uses
+//! the proof id), for `GET /transactions`.
+
+use anyhow::{anyhow, Result};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, PendingTransaction, Provider};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, BlockNumber, TransactionReceipt, H256, U256};
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Raise fees by this much per resend (nodes require at least 10%)
+pub const FEE_BUMP_PERCENT: u64 = 15;
+
+pub const MAX_FEE_BUMPS: u32 = 3;
+
+/// Transactions kept for `GET /transactions`
+const MAX_RECORDS: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStatus {
+    Pending,
+    Confirmed,
+    Reverted,
+    /// Left the mempool without being mined
+    Dropped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TxRecord {
+    /// What the transaction is for (a proof id), if the caller said
+    pub label: Option<String>,
+    pub tx_hash: String,
+    pub nonce: u64,
+    pub status: TxStatus,
+    /// Times the fees were raised before the node accepted it
+    pub fee_bumps: u32,
+    pub block_number: Option<u64>,
+    /// Unix seconds
+    pub submitted_at: u64,
+}
+
+type Client = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+pub struct TxQueue {
+    rpc_url: String,
+    wallet: LocalWallet,
+    client: tokio::sync::OnceCell<Client>,
+    /// Next nonce to use; `None` until read from the chain
+    nonce: tokio::sync::Mutex<Option<U256>>,
+    records: std::sync::Mutex<VecDeque<TxRecord>>,
+}
+
+impl TxQueue {
+    /// Queue for the wallet of `private_key`, sending through `rpc_url` only (a resend
+    /// through another endpoint could race the first)
+    pub fn new(rpc_url: &str, private_key: &str) -> Result<Self> {
+        Ok(Self {
+            rpc_url: rpc_url.to_string(),
+            wallet: private_key.strip_prefix("0x").unwrap_or(private_key).parse()?,
+            client: tokio::sync::OnceCell::new(),
+            nonce: tokio::sync::Mutex::new(None),
+            records: std::sync::Mutex::new(VecDeque::new()),
+        })
+    }
+
+    pub fn address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    async fn client(&self) -> Result<&Client> {
+        self.client
+            .get_or_try_init(|| async {
+                let provider = Provider::<Http>::try_from(self.rpc_url.as_str())?;
+                let chain_id = provider.get_chainid().await?.as_u64();
+                Ok::<_, anyhow::Error>(SignerMiddleware::new(provider, self.wallet.clone().with_chain_id(chain_id)))
+            })
+            .await
+    }
+
+    async fn chain_nonce(&self, client: &Client) -> Result<U256> {
+        Ok(client
+            .get_transaction_count(self.address(), Some(BlockNumber::Pending.into()))
+            .await?)
+    }
+
+    /// Send `tx` with the next nonce and wait for its receipt
+    ///
+    /// A reverted transaction still returns its receipt (status 0); a dropped one is an error.
+    pub async fn send(&self, label: Option<&str>, tx: impl Into<TypedTransaction>) -> Result<TransactionReceipt> {
+        let client = self.client().await?;
+        let mut tx: TypedTransaction = tx.into();
+        tx.set_from(self.address());
+
+        let (tx_hash, nonce, fee_bumps) = {
+            let mut next = self.nonce.lock().await;
+            let mut nonce = match *next {
+                Some(nonce) => nonce,
+                None => self.chain_nonce(client).await?,
+            };
+            tx.set_nonce(nonce);
+            if let Err(e) = client.fill_transaction(&mut tx, None).await {
+                return Err(anyhow!("Preparing transaction: {}", e));
+            }
+
+            let mut fee_bumps = 0;
+            let mut nonce_refreshed = false;
+            let tx_hash = loop {
+                match client.send_transaction(tx.clone(), None).await {
+                    Ok(pending) => break pending.tx_hash(),
+                    Err(e) => {
+                        let message = e.to_string().to_lowercase();
+                        if (message.contains("underpriced") || message.contains("already known"))
+                            && fee_bumps < MAX_FEE_BUMPS
+                        {
+                            fee_bumps += 1;
+                            bump_fees(&mut tx);
+                            tracing::warn!("Nonce {} taken by a pending transaction; resending with fees +{}%", nonce, FEE_BUMP_PERCENT);
+                        } else if message.contains("nonce too low") && !nonce_refreshed {
+                            nonce_refreshed = true;
+                            nonce = self.chain_nonce(client).await?;
+                            tx.set_nonce(nonce);
+                        } else {
+                            *next = None;
+                            return Err(anyhow!("Sending transaction: {}", e));
+                        }
+                    }
+                }
+            };
+            *next = Some(nonce + 1);
+            (tx_hash, nonce.as_u64(), fee_bumps)
+        };
+
+        let hash = format!("{:#x}", tx_hash);
+        tracing::info!("  Transaction sent: {} (nonce {})", hash, nonce);
+        self.push(TxRecord {
+            label: label.map(str::to_string),
+            tx_hash: hash.clone(),
+            nonce,
+            status: TxStatus::Pending,
+            fee_bumps,
+            block_number: None,
+            submitted_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        });
+
+        match PendingTransaction::new(tx_hash, client.provider()).await {
+            Ok(Some(receipt)) => {
+                let status = if receipt.status.map(|s| s.as_u64()) == Some(1) {
+                    TxStatus::Confirmed
+                } else {
+                    TxStatus::Reverted
+                };
+                self.update(tx_hash, status, receipt.block_number.map(|b| b.as_u64()));
+                Ok(receipt)
+            }
+            Ok(None) => {
+                // The nonce it held is free again; read it back before the next send
+                self.update(tx_hash, TxStatus::Dropped, None);
+                *self.nonce.lock().await = None;
+                Err(anyhow!("Transaction {} dropped from mempool", hash))
+            }
+            Err(e) => Err(anyhow!("Waiting for transaction {}: {}", hash, e)),
+        }
+    }
+
+    /// Tracked transactions, newest first; only those under `label` if given
+    pub fn transactions(&self, label: Option<&str>) -> Vec<TxRecord> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|r| label.is_none_or(|label| r.label.as_deref() == Some(label)))
+            .cloned()
+            .collect()
+    }
+
+    fn push(&self, record: TxRecord) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() == MAX_RECORDS {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    fn update(&self, tx_hash: H256, status: TxStatus, block_number: Option<u64>) {
+        let hash = format!("{:#x}", tx_hash);
+        if let Some(record) = self.records.lock().unwrap().iter_mut().rev().find(|r| r.tx_hash == hash) {
+            record.status = status;
+            record.block_number = block_number;
+        }
+    }
+}
+
+/// Raise every fee field set on `tx` by FEE_BUMP_PERCENT
+fn bump_fees(tx: &mut TypedTransaction) {
+    let bump = |fee: U256| fee + fee * FEE_BUMP_PERCENT / 100 + 1;
+    match tx {
+        TypedTransaction::Eip1559(inner) => {
+            inner.max_fee_per_gas = inner.max_fee_per_gas.map(bump);
+            inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.map(bump);
+        }
+        other => {
+            if let Some(price) = other.gas_price() {
+                other.set_gas_price(bump(price));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    #[test]
+    fn test_fee_bump_covers_both_transaction_kinds() {
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(1_000u64)
+            .max_priority_fee_per_gas(100u64)
+            .into();
+        bump_fees(&mut tx);
+        let TypedTransaction::Eip1559(inner) = &tx else { unreachable!() };
+        assert_eq!(inner.max_fee_per_gas, Some(U256::from(1_151)));
+        assert_eq!(inner.max_priority_fee_per_gas, Some(U256::from(116)));
+
+        let mut legacy: TypedTransaction = TransactionRequest::new().gas_price(200u64).into();
+        bump_fees(&mut legacy);
+        assert_eq!(legacy.gas_price(), Some(U256::from(231)));
+    }
+
+    #[test]
+    fn test_records_filter_by_label_newest_first() {
+        let queue = TxQueue::new("http://localhost:8545", "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+            .unwrap();
+        for (i, label) in ["p1", "p2", "p1"].into_iter().enumerate() {
+            queue.push(TxRecord {
+                label: Some(label.into()),
+                tx_hash: format!("{:#x}", H256::from_low_u64_be(i as u64)),
+                nonce: i as u64,
+                status: TxStatus::Pending,
+                fee_bumps: 0,
+                block_number: None,
+                submitted_at: 0,
+            });
+        }
+        queue.update(H256::from_low_u64_be(2), TxStatus::Confirmed, Some(7));
+
+        let p1 = queue.transactions(Some("p1"));
+        assert_eq!(p1.iter().map(|r| r.nonce).collect::<Vec<_>>(), [2, 0]);
+        assert_eq!(p1[0].status, TxStatus::Confirmed);
+        assert_eq!(queue.transactions(None).len(), 3);
+    }
+}
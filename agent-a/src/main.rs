@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use hex;
-use zk_protocol::{AttestRequest, AttestResponse, AgentResponse};
+use zk_protocol::{AttestRequest, AttestResponse, AgentResponse, Codec, ProofMode};
 
 // Agent-specific input type (Agent A only needs to know its own format)
 #[derive(Serialize, Deserialize)]
@@ -184,8 +184,12 @@ async fn main() -> anyhow::Result<()> {
     let attest_req = AttestRequest {
         program_id: price_resp.program_id.clone(),
         input_bytes,
+        codec: Codec::Bincode,
         claimed_output: Some(price_resp.data.clone()),
         verify_locally: true,
+        nonce: None,
+        expires_at: None,
+        proof_mode: ProofMode::Groth16,
     };
 
     let attest_resp = client
@@ -1,242 +1,989 @@
-/// Agent B MCP Server - Pricing & Booking Service
-///
-/// Exposes pricing and booking operations as MCP tools over HTTP API
-/// - POST /tools/get-ticket-price
-/// - POST /tools/book-flight
-/// - GET /tools - List all tools
+//! Agent B MCP Server - Pricing & Booking Service
+//!
+//! Exposes pricing and booking operations as MCP tools over HTTP API
+//! (default) or JSON-RPC over stdin/stdout (AGENT_B_MODE=jsonrpc)
+//! - POST /tools/get-ticket-price
+//! - POST /tools/book-flight
+//! - POST /tools/validate-promo
+//! - POST /tools/get-flight-options
+//! - POST /tools/get-seats
+//! - POST /tools/select-seat
+//! - POST /tools/get-booking
+//! - POST /tools/cancel-booking
+//! - POST /tools/change-booking
+//! - POST /tools/get-zk-input
+//! - GET /tools - List all tools (the catalogue shared with agent-b/server, see agent-b-tools)
+//! - GET /openapi.json - OpenAPI spec (Swagger UI at /docs)
+//!
+//! Successful tool responses carry a `zk` block naming the guest program that proves them
+//! (see zk.rs).
 
 use anyhow::Result;
+use async_trait::async_trait;
 use axum::{
-    extract::Json,
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::Html,
     routing::{get, post},
     Router,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::sync::Arc;
-use tower_http::cors::CorsLayer;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use agent_tls::TlsConfig;
+use agent_b_tools::{ToolBackend, ToolError, ToolsResponse};
+use openapi_spec::{swagger_ui_html, SpecBuilder};
+use tool_envelope::ToolResponse as Envelope;
 
-use pricing_core::pricing;
+use pricing_core::fare::{Cabin, FareFamily};
+use pricing_core::validate::ValidationError;
+use pricing_core::{booking, cancellation, pricing, seats, RpcCall};
+use zk_protocol::PiiCommitment;
+
+/// How long a price quote stays valid
+const QUOTE_TTL_SECS: u64 = 900;
+
+/// Bookings made by book-flight in this process, for the lookup, change, and cancel tools
+/// (agent-b/server keeps its bookings in SQLite instead)
+static BOOKINGS: LazyLock<Mutex<HashMap<String, BookResponse>>> = LazyLock::new(Default::default);
+
+/// Idempotency key → (booking_id, `booking::duplicate_key`) of the booking it created, so a
+/// retried book-flight returns the original booking
+static BOOKING_KEYS: LazyLock<Mutex<HashMap<String, (String, String)>>> = LazyLock::new(Default::default);
+
+mod zk;
+use zk::{AppState, ZkInfo};
 
 /// Pricing Tool Request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct PriceRequest {
     from: String,
     to: String,
     vip: Option<bool>,
+    /// Departure day, YYYY-MM-DD; weekends, peak season, and late booking cost more
+    departure_date: Option<String>,
+    /// Return day, YYYY-MM-DD; quotes the round trip
+    return_date: Option<String>,
+    /// ISO 4217 code (USD, EUR, GBP, JPY); USD if unset
+    currency: Option<String>,
+    /// economy (default), premium, business, or first
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    cabin: Cabin,
+    /// non_refundable (default) or refundable
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    fare_family: FareFamily,
+    /// Promo code to apply; quoting fails if it doesn't apply (check with validate-promo)
+    promo_code: Option<String>,
 }
 
 /// Pricing Tool Response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 struct PriceResponse {
-    price: f64,
+    /// In `currency`'s minor units (cents; whole yen for JPY)
+    price_cents: u64,
     from: String,
     to: String,
     vip: bool,
     currency: String,
+    #[schemars(with = "String")]
+    cabin: Cabin,
+    #[schemars(with = "String")]
+    fare_family: FareFamily,
+    /// Promo code applied, as published
+    promo_code: Option<String>,
+    /// Amount the promo took off `price_cents`
+    discount_cents: u64,
+    departure_date: Option<String>,
+    return_date: Option<String>,
+    /// Day priced on, YYYY-MM-DD; with `departure_date`, sets the advance-purchase tier
+    purchase_date: String,
+    /// Quote to prove against; re-price after `expires_at`
+    quote_id: String,
+    /// Unix seconds after which the quote is stale
+    expires_at: u64,
+}
+
+/// Flight Options Tool Request
+#[derive(Debug, Deserialize, JsonSchema)]
+struct FlightOptionsRequest {
+    /// Routes/dates to compare, each as for get-ticket-price, all in the same currency
+    options: Vec<PriceRequest>,
+}
+
+/// Flight Options Tool Response
+#[derive(Debug, Serialize, JsonSchema)]
+struct FlightOptionsResponse {
+    /// In request order: `{from, to, departure_date, return_date, price_cents, promo_code, error}`
+    #[schemars(with = "Vec<Value>")]
+    options: Vec<pricing::BatchOption>,
+    currency: String,
+    /// Index into `options` of the lowest price
+    cheapest: usize,
+    /// Day priced on, YYYY-MM-DD
+    purchase_date: String,
+}
+
+/// Promo Validation Response
+#[derive(Debug, Serialize, JsonSchema)]
+struct PromoResponse {
+    promo_code: String,
+    valid: bool,
+    /// Why the code doesn't apply
+    reason: Option<String>,
+    /// Fare without the promo
+    price_cents: u64,
+    /// Fare with the promo, if valid
+    discounted_price_cents: Option<u64>,
+    discount_cents: u64,
+    currency: String,
 }
 
 /// Booking Tool Request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 struct BookRequest {
     from: String,
     to: String,
     passenger_name: String,
     passenger_email: String,
+    /// economy (default), premium, business, or first
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    cabin: Cabin,
+    /// non_refundable (default) or refundable
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    fare_family: FareFamily,
+    /// Departure day, YYYY-MM-DD; the flight `seat` is checked against
+    departure_date: Option<String>,
+    /// Seat from get-seats, e.g. "12C"
+    seat: Option<String>,
+    /// Fare paid, from get-ticket-price; the list fare is recorded if unset
+    price_cents: Option<u64>,
+    currency: Option<String>,
+    /// Retries with the same key return the original booking (over HTTP, the Idempotency-Key
+    /// header works too); without one, a repeat for the same passenger, route, and day does
+    #[serde(default)]
+    idempotency_key: Option<String>,
+    /// Salt for the passenger commitment (32 bytes of hex); a fresh one if unset
+    #[serde(default)]
+    pii_salt: Option<String>,
 }
 
 /// Booking Tool Response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 struct BookResponse {
     booking_id: String,
+    /// "confirmed" or "cancelled"
     status: String,
     confirmation_code: String,
     from: String,
     to: String,
     passenger_name: String,
+    #[serde(skip)]
+    passenger_email: String,
+    #[schemars(with = "String")]
+    cabin: Cabin,
+    #[schemars(with = "String")]
+    fare_family: FareFamily,
+    departure_date: Option<String>,
+    seat: Option<String>,
+    /// Opens `passenger_commitment` with the passenger's name and email; keep it private
+    pii_salt: Option<String>,
+    /// All a booking proof reveals about the passenger
+    passenger_commitment: Option<String>,
+    /// Fare paid, in `currency`'s minor units; change and cancellation fees are shares of it
+    price_cents: u64,
+    currency: String,
+    /// Total charged for changes so far
+    change_fees_cents: u64,
+    /// Set once cancelled
+    cancellation: Option<CancellationInfo>,
+    /// A retry answered with the original booking
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    duplicate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+struct CancellationInfo {
+    fee_cents: u64,
+    refund_cents: u64,
+    /// YYYY-MM-DD
+    cancelled_on: String,
+}
+
+/// Booking Lookup / Cancellation Tool Request
+#[derive(Debug, Deserialize, JsonSchema)]
+struct BookingIdRequest {
+    booking_id: String,
 }
 
-/// Tool Definition
-#[derive(Debug, Serialize)]
-struct ToolDefinition {
-    name: String,
-    description: String,
-    inputSchema: serde_json::Value,
+/// Booking Change Tool Request; unset fields keep their current value
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ChangeBookingRequest {
+    booking_id: String,
+    /// New departure day, YYYY-MM-DD
+    departure_date: Option<String>,
+    /// New seat, e.g. "14A"
+    seat: Option<String>,
+}
+
+/// Booking Change Tool Response
+#[derive(Debug, Serialize, JsonSchema)]
+struct ChangeBookingResponse {
+    #[serde(flatten)]
+    booking: BookResponse,
+    /// Charged for this change
+    change_fee_cents: u64,
 }
 
-/// Tools List Response
-#[derive(Debug, Serialize)]
-struct ToolsResponse {
-    tools: Vec<ToolDefinition>,
+/// Seat Map Tool Request
+#[derive(Debug, Deserialize, JsonSchema)]
+struct SeatsRequest {
+    from: String,
+    to: String,
+    /// Departure day, YYYY-MM-DD
+    departure_date: Option<String>,
 }
 
-/// Standard Tool Response
-#[derive(Debug, Serialize)]
+/// Seat Map Tool Response
+#[derive(Debug, Serialize, JsonSchema)]
+struct SeatsResponse {
+    from: String,
+    to: String,
+    departure_date: Option<String>,
+    /// Free seats on the flight
+    available: usize,
+    /// Every seat, front to back: `{seat, cabin, available}`
+    #[schemars(with = "Vec<Value>")]
+    seats: Vec<seats::Seat>,
+}
+
+/// Seat Selection Tool Request
+#[derive(Debug, Deserialize, JsonSchema)]
+struct SelectSeatRequest {
+    from: String,
+    to: String,
+    /// Departure day, YYYY-MM-DD
+    departure_date: Option<String>,
+    /// Cabin being booked; the seat must be in it
+    #[serde(default)]
+    #[schemars(with = "Option<String>")]
+    cabin: Cabin,
+    seat: String,
+}
+
+/// Seat Selection Tool Response
+#[derive(Debug, Serialize, JsonSchema)]
+struct SelectSeatResponse {
+    /// Seat as it appears on the map; pass it to book-flight
+    seat: String,
+    from: String,
+    to: String,
+    departure_date: Option<String>,
+    #[schemars(with = "String")]
+    cabin: Cabin,
+}
+
+/// ZK Input Tool Request
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ZkInputRequest {
+    /// "price", "price_batch", "book", or "cancel"; the `zk.endpoint` of the result to prove
+    endpoint: String,
+    /// As for agent-b/server's POST /zk-input
+    input: Value,
+}
+
+/// ZK Input Tool Response
+#[derive(Debug, Serialize, JsonSchema)]
+struct ZkInputResponse {
+    /// bincode-encoded RpcCall, the guest's stdin
+    input_bytes: Vec<u8>,
+    program: &'static str,
+    program_id: String,
+    elf_hash: String,
+}
+
+/// The shared tool envelope (tool-envelope), plus the guest program behind a result
+#[derive(Debug, Serialize, JsonSchema)]
 struct ToolResponse<T: Serialize> {
-    success: bool,
-    data: Option<T>,
-    error: Option<String>,
+    #[serde(flatten)]
+    envelope: Envelope<T>,
+    /// Guest program behind the result; set on success
+    #[serde(skip_serializing_if = "Option::is_none")]
+    zk: Option<ZkInfo>,
 }
 
 impl<T: Serialize> ToolResponse<T> {
-    fn ok(data: T) -> Self {
+    fn ok(data: T, zk: ZkInfo) -> Self {
         Self {
-            success: true,
-            data: Some(data),
-            error: None,
+            envelope: Envelope::ok(data),
+            zk: Some(zk),
         }
     }
 }
 
 fn tool_error(error: String) -> ToolResponse<()> {
     ToolResponse {
-        success: false,
-        data: None,
-        error: Some(error),
+        envelope: Envelope::err(error),
+        zk: None,
     }
 }
 
-/// List all available tools
-async fn list_tools() -> Json<ToolsResponse> {
-    tracing::info!("[LIST TOOLS] Received request to list available tools");
-    Json(ToolsResponse {
-        tools: vec![
-            ToolDefinition {
-                name: "get-ticket-price".to_string(),
-                description: "Get flight ticket pricing based on route and passenger tier".to_string(),
-                inputSchema: json!({
-                    "type": "object",
-                    "properties": {
-                        "from": {
-                            "type": "string",
-                            "description": "Departure city code (e.g., NYC)"
-                        },
-                        "to": {
-                            "type": "string",
-                            "description": "Destination city code (e.g., LON)"
-                        },
-                        "vip": {
-                            "type": "boolean",
-                            "description": "Whether passenger is VIP (optional, default false)"
-                        }
-                    },
-                    "required": ["from", "to"]
-                }),
-            },
-            ToolDefinition {
-                name: "book-flight".to_string(),
-                description: "Book a flight and generate confirmation".to_string(),
-                inputSchema: json!({
-                    "type": "object",
-                    "properties": {
-                        "from": {
-                            "type": "string",
-                            "description": "Departure city code"
-                        },
-                        "to": {
-                            "type": "string",
-                            "description": "Destination city code"
-                        },
-                        "passenger_name": {
-                            "type": "string",
-                            "description": "Full name of passenger"
-                        },
-                        "passenger_email": {
-                            "type": "string",
-                            "description": "Email address of passenger"
-                        }
-                    },
-                    "required": ["from", "to", "passenger_name", "passenger_email"]
-                }),
-            },
-        ],
-    })
+/// 422 for a request pricing-core's validation rejects
+fn rejected(e: ValidationError) -> (StatusCode, Json<ToolResponse<()>>) {
+    tracing::warn!("Rejected invalid input: {}", e);
+    let response = ToolResponse {
+        envelope: Envelope::err(&e).with_code(e.code.as_str()),
+        zk: None,
+    };
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(response))
 }
 
 /// Get ticket pricing
 async fn get_ticket_price(
+    State(state): State<Arc<AppState>>,
     Json(req): Json<PriceRequest>,
 ) -> Result<Json<ToolResponse<PriceResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
-    tracing::info!("[GET-TICKET-PRICE] Tool call received: from={}, to={}, vip={:?}", req.from, req.to, req.vip);
-    
-    // Validate input
-    if req.from.is_empty() || req.to.is_empty() {
-        tracing::warn!("[GET-TICKET-PRICE] Validation failed: missing required fields");
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(tool_error(
-                "from and to fields are required".to_string(),
-            )),
-        ));
-    }
+    core_request(&req).validate().map_err(rejected)?;
+    price(req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("get-ticket-price"))))
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(tool_error(e))))
+}
 
-    // Use pricing-core to calculate price
-    let core_req = pricing::Request {
-        from: req.from.clone(),
-        to: req.to.clone(),
-        vip: req.vip.unwrap_or(false),
-    };
+/// Compare prices across routes and dates
+async fn get_flight_options(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<FlightOptionsRequest>,
+) -> Result<Json<ToolResponse<FlightOptionsResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
+    RpcCall::GetPriceBatch(req.options.iter().map(core_request).collect())
+        .validate()
+        .map_err(rejected)?;
+    flight_options(req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("get-flight-options"))))
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(tool_error(e))))
+}
 
-    let core_resp = pricing::handle(core_req);
-    
-    tracing::info!("[GET-TICKET-PRICE] Successfully calculated price: ${} (vip={})", core_resp.price, req.vip.unwrap_or(false));
+/// Check a promo code against a fare
+async fn validate_promo_tool(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<PriceRequest>,
+) -> Result<Json<ToolResponse<PromoResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
+    core_request(&req).validate().map_err(rejected)?;
+    validate_promo(req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("validate-promo"))))
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(tool_error(e))))
+}
 
-    Ok(Json(ToolResponse::ok(PriceResponse {
-        price: core_resp.price,
-        from: req.from,
-        to: req.to,
-        vip: req.vip.unwrap_or(false),
-        currency: "USD".to_string(),
-    })))
+/// Get a flight's seat map
+async fn get_seats(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SeatsRequest>,
+) -> Json<ToolResponse<SeatsResponse>> {
+    Json(ToolResponse::ok(seat_map(req), state.zk("get-seats")))
+}
+
+/// Check a seat before booking it
+async fn select_seat_tool(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SelectSeatRequest>,
+) -> Result<Json<ToolResponse<SelectSeatResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
+    select_seat(req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("select-seat"))))
+        .map_err(|e| (StatusCode::CONFLICT, Json(tool_error(e))))
+}
+
+/// Look up a booking
+async fn get_booking_tool(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BookingIdRequest>,
+) -> Result<Json<ToolResponse<BookResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
+    get_booking(req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("get-booking"))))
+        .map_err(|e| (StatusCode::NOT_FOUND, Json(tool_error(e))))
+}
+
+/// Cancel a booking
+async fn cancel_booking_tool(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<BookingIdRequest>,
+) -> Result<Json<ToolResponse<BookResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
+    cancel_booking(req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("cancel-booking"))))
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(tool_error(e))))
+}
+
+/// Change a booking's date or seat
+async fn change_booking_tool(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ChangeBookingRequest>,
+) -> Result<Json<ToolResponse<ChangeBookingResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
+    change_booking(req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("change-booking"))))
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(tool_error(e))))
 }
 
 /// Book a flight
 async fn book_flight(
-    Json(req): Json<BookRequest>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(mut req): Json<BookRequest>,
 ) -> Result<Json<ToolResponse<BookResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
-    tracing::info!("[BOOK-FLIGHT] Tool call received: from={}, to={}, passenger={}, email={}", req.from, req.to, req.passenger_name, req.passenger_email);
-    
-    // Validate input
-    if req.from.is_empty() || req.to.is_empty() || req.passenger_name.is_empty() {
-        tracing::warn!("[BOOK-FLIGHT] Validation failed: missing required fields");
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(tool_error(
-                "from, to, and passenger_name are required".to_string(),
-            )),
-        ));
+    core_booking_request(&req).validate().map_err(rejected)?;
+    if req.idempotency_key.is_none() {
+        req.idempotency_key = headers
+            .get("idempotency-key")
+            .map(|v| v.to_str().map(str::to_string).unwrap_or_default());
     }
+    book(req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("book-flight"))))
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(tool_error(e))))
+}
 
-    // Use pricing-core to generate booking
-    let core_req = pricing_core::booking::Request {
+/// Input bytes to prove a tool result with
+async fn get_zk_input_tool(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ZkInputRequest>,
+) -> Result<Json<ToolResponse<ZkInputResponse>>, (StatusCode, Json<ToolResponse<()>>)> {
+    zk_input(&state, req)
+        .map(|resp| Json(ToolResponse::ok(resp, state.zk("get-zk-input"))))
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, Json(tool_error(e))))
+}
+
+fn price(req: PriceRequest) -> Result<PriceResponse, String> {
+    tracing::info!("[GET-TICKET-PRICE] Tool call received: from={}, to={}, vip={:?}", req.from, req.to, req.vip);
+    
+    // Use pricing-core to calculate price
+    let core_resp = pricing::handle(core_request(&req)).map_err(|e| {
+        tracing::warn!("[GET-TICKET-PRICE] Pricing failed: {}", e);
+        e
+    })?;
+    
+    tracing::info!("[GET-TICKET-PRICE] Successfully calculated price: {} {} minor units (vip={})", core_resp.price_cents, core_resp.currency, req.vip.unwrap_or(false));
+
+    let issued_at = unix_now();
+    let purchase_date = pricing_core::date::from_unix_secs(issued_at);
+
+    Ok(PriceResponse {
+        price_cents: core_resp.price_cents,
+        from: req.from,
+        to: req.to,
+        vip: req.vip.unwrap_or(false),
+        currency: core_resp.currency,
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        promo_code: core_resp.promo_code,
+        discount_cents: core_resp.discount_cents,
+        departure_date: req.departure_date,
+        return_date: req.return_date,
+        purchase_date,
+        quote_id: format!("q_{}", uuid::Uuid::new_v4().simple()),
+        expires_at: issued_at + QUOTE_TTL_SECS,
+    })
+}
+
+fn core_request(req: &PriceRequest) -> pricing::Request {
+    pricing::Request {
         from: req.from.clone(),
         to: req.to.clone(),
-        passenger_name: req.passenger_name.clone(),
-        passenger_email: req.passenger_email.clone(),
+        vip: req.vip.unwrap_or(false),
+        quote_id: None,
+        departure_date: req.departure_date.clone(),
+        return_date: req.return_date.clone(),
+        purchase_date: Some(pricing_core::date::from_unix_secs(unix_now())),
+        currency: req.currency.clone(),
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        promo_code: req.promo_code.clone(),
+    }
+}
+
+/// Every option priced as of today, with the cheapest marked
+fn flight_options(req: FlightOptionsRequest) -> Result<FlightOptionsResponse, String> {
+    tracing::info!("[GET-FLIGHT-OPTIONS] Tool call received: {} options", req.options.len());
+
+    let purchase_date = pricing_core::date::from_unix_secs(unix_now());
+    let batch = pricing::handle_batch(
+        req.options
+            .iter()
+            .map(|option| pricing::Request {
+                purchase_date: Some(purchase_date.clone()),
+                ..core_request(option)
+            })
+            .collect(),
+    )?;
+
+    Ok(FlightOptionsResponse {
+        options: batch.options,
+        currency: batch.currency,
+        cheapest: batch.cheapest,
+        purchase_date,
+    })
+}
+
+/// Price the fare with and without `promo_code`; an inapplicable code is a result, not an error
+fn validate_promo(req: PriceRequest) -> Result<PromoResponse, String> {
+    let code = req.promo_code.clone().ok_or("promo_code is required")?;
+    tracing::info!("[VALIDATE-PROMO] Tool call received: code={}, from={}, to={}", code, req.from, req.to);
+
+    let mut core_req = core_request(&req);
+    core_req.promo_code = None;
+    let base = pricing::handle(core_req.clone())?;
+    core_req.promo_code = Some(code.clone());
+
+    Ok(match pricing::handle(core_req) {
+        Ok(discounted) => PromoResponse {
+            promo_code: discounted.promo_code.unwrap_or(code),
+            valid: true,
+            reason: None,
+            price_cents: base.price_cents,
+            discounted_price_cents: Some(discounted.price_cents),
+            discount_cents: discounted.discount_cents,
+            currency: discounted.currency,
+        },
+        Err(reason) => PromoResponse {
+            promo_code: code,
+            valid: false,
+            reason: Some(reason),
+            price_cents: base.price_cents,
+            discounted_price_cents: None,
+            discount_cents: 0,
+            currency: base.currency,
+        },
+    })
+}
+
+fn book(mut req: BookRequest) -> Result<BookResponse, String> {
+    tracing::info!("[BOOK-FLIGHT] Tool call received: from={}, to={}, passenger={}, email={}", req.from, req.to, req.passenger_name, req.passenger_email);
+    req.pii_salt.get_or_insert_with(|| hex::encode(rand::random::<[u8; 32]>()));
+
+    // Replay by idempotency key if given, else while the same passenger's booking on this
+    // route and day is still confirmed (after a cancellation, booking again is intended)
+    let idempotency_key = match req.idempotency_key.as_deref().map(str::trim) {
+        Some(key) if key.is_empty() || key.len() > 255 => {
+            return Err("idempotency_key must be 1-255 visible ASCII characters".to_string())
+        }
+        key => key.map(|key| format!("client:{}", key)),
+    };
+    let duplicate_key = booking::duplicate_key(&core_booking_request(&req));
+    let passenger_key = format!("passenger:{}", duplicate_key);
+    let known = BOOKING_KEYS
+        .lock()
+        .unwrap()
+        .get(idempotency_key.as_ref().unwrap_or(&passenger_key))
+        .cloned();
+    if let Some((booking_id, original_duplicate_key)) = known {
+        if original_duplicate_key != duplicate_key {
+            return Err(format!(
+                "idempotency_key was already used for booking {}, for another passenger, route, or day",
+                booking_id
+            ));
+        }
+        let original = get_booking(BookingIdRequest { booking_id })?;
+        if idempotency_key.is_some() || original.status == "confirmed" {
+            tracing::info!("[BOOK-FLIGHT] Duplicate, returning booking {}", original.booking_id);
+            return Ok(BookResponse { duplicate: true, ..original });
+        }
+    }
+    
+    // Fees on later changes and cancellations are shares of this
+    let (price_cents, currency) = match req.price_cents {
+        Some(price_cents) => (price_cents, req.currency.clone().unwrap_or_else(|| "USD".to_string())),
+        None => {
+            let list = pricing::handle(pricing::Request {
+                from: req.from.clone(),
+                to: req.to.clone(),
+                vip: false,
+                quote_id: None,
+                departure_date: req.departure_date.clone(),
+                return_date: None,
+                purchase_date: Some(pricing_core::date::from_unix_secs(unix_now())),
+                currency: req.currency.clone(),
+                cabin: req.cabin,
+                fare_family: req.fare_family,
+                promo_code: None,
+            })?;
+            (list.price_cents, list.currency)
+        }
     };
 
-    let core_resp = pricing_core::booking::handle(core_req);
+    // Use pricing-core to generate booking
+    let core_resp = booking::handle(core_booking_request(&req)).map_err(|e| {
+        tracing::warn!("[BOOK-FLIGHT] Booking failed: {}", e);
+        e
+    })?;
     
     tracing::info!("[BOOK-FLIGHT] Successfully booked flight: booking_id={}, confirmation_code={}, status={}", core_resp.booking_id, core_resp.confirmation_code, core_resp.status);
 
-    Ok(Json(ToolResponse::ok(BookResponse {
+    let commitment = passenger_commitment(req.pii_salt.as_deref(), &req.passenger_name, &req.passenger_email);
+    let record = BookResponse {
         booking_id: core_resp.booking_id,
         status: core_resp.status,
         confirmation_code: core_resp.confirmation_code,
         from: req.from,
         to: req.to,
         passenger_name: req.passenger_name,
-    })))
+        passenger_email: req.passenger_email,
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        departure_date: req.departure_date,
+        seat: core_resp.seat,
+        passenger_commitment: commitment,
+        pii_salt: req.pii_salt,
+        price_cents,
+        currency,
+        change_fees_cents: 0,
+        cancellation: None,
+        duplicate: false,
+    };
+    BOOKINGS.lock().unwrap().insert(record.booking_id.clone(), record.clone());
+    let mut keys = BOOKING_KEYS.lock().unwrap();
+    for key in std::iter::once(passenger_key).chain(idempotency_key) {
+        keys.insert(key, (record.booking_id.clone(), duplicate_key.clone()));
+    }
+    Ok(record)
+}
+
+fn core_booking_request(req: &BookRequest) -> booking::Request {
+    booking::Request {
+        from: req.from.clone(),
+        to: req.to.clone(),
+        passenger_name: req.passenger_name.clone(),
+        passenger_email: req.passenger_email.clone(),
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        departure_date: req.departure_date.clone(),
+        seat: req.seat.clone(),
+        pii_salt: req.pii_salt.clone(),
+    }
+}
+
+/// Commitment a booking proof carries in place of the passenger's name and email
+fn passenger_commitment(salt: Option<&str>, passenger_name: &str, passenger_email: &str) -> Option<String> {
+    let salt = PiiCommitment::parse_salt(salt?).ok()?;
+    Some(PiiCommitment::new(&salt, passenger_name, passenger_email).to_hex())
+}
+
+fn get_booking(req: BookingIdRequest) -> Result<BookResponse, String> {
+    BOOKINGS
+        .lock()
+        .unwrap()
+        .get(&req.booking_id)
+        .cloned()
+        .ok_or_else(|| format!("No booking {}", req.booking_id))
+}
+
+fn cancel_booking(req: BookingIdRequest) -> Result<BookResponse, String> {
+    tracing::info!("[CANCEL-BOOKING] Tool call received: booking_id={}", req.booking_id);
+    let mut record = get_booking(req)?;
+    if record.cancellation.is_some() {
+        return Err(format!("Booking {} is already cancelled", record.booking_id));
+    }
+
+    let cancelled_on = pricing_core::date::from_unix_secs(unix_now());
+    let core_resp = cancellation::handle(cancellation::Request {
+        booking_id: record.booking_id.clone(),
+        fare_cents: record.price_cents,
+        currency: record.currency.clone(),
+        fare_family: record.fare_family,
+        departure_date: record.departure_date.clone(),
+        cancel_date: cancelled_on.clone(),
+    })?;
+
+    record.status = "cancelled".to_string();
+    record.cancellation = Some(CancellationInfo {
+        fee_cents: core_resp.fee_cents,
+        refund_cents: core_resp.refund_cents,
+        cancelled_on,
+    });
+    BOOKINGS.lock().unwrap().insert(record.booking_id.clone(), record.clone());
+    Ok(record)
+}
+
+fn change_booking(req: ChangeBookingRequest) -> Result<ChangeBookingResponse, String> {
+    tracing::info!("[CHANGE-BOOKING] Tool call received: booking_id={}", req.booking_id);
+    let mut record = get_booking(BookingIdRequest { booking_id: req.booking_id })?;
+    if record.cancellation.is_some() {
+        return Err(format!("Booking {} is cancelled", record.booking_id));
+    }
+
+    let departure_date = req.departure_date.or_else(|| record.departure_date.clone());
+    // Re-run the booking logic so the seat is checked against the new flight
+    let core_resp = booking::handle(booking::Request {
+        from: record.from.clone(),
+        to: record.to.clone(),
+        passenger_name: record.passenger_name.clone(),
+        passenger_email: record.passenger_email.clone(),
+        cabin: record.cabin,
+        fare_family: record.fare_family,
+        departure_date: departure_date.clone(),
+        seat: req.seat.or_else(|| record.seat.clone()),
+        pii_salt: record.pii_salt.clone(),
+    })?;
+
+    let change_fee_cents = cancellation::change_fee_cents(record.fare_family, record.price_cents);
+    // The booking keeps its id; the confirmation code is reissued
+    record.confirmation_code = core_resp.confirmation_code;
+    record.departure_date = departure_date;
+    record.seat = core_resp.seat;
+    record.change_fees_cents += change_fee_cents;
+    BOOKINGS.lock().unwrap().insert(record.booking_id.clone(), record.clone());
+    Ok(ChangeBookingResponse { booking: record, change_fee_cents })
+}
+
+fn zk_input(state: &AppState, req: ZkInputRequest) -> Result<ZkInputResponse, String> {
+    tracing::info!("[GET-ZK-INPUT] Tool call received: endpoint={}", req.endpoint);
+    let input_bytes = zk::input_bytes(&req.endpoint, req.input)?;
+    let program = state
+        .program()
+        .ok_or("Program not registered with the attester yet; retry shortly")?;
+    Ok(ZkInputResponse {
+        input_bytes,
+        program: zk::PROGRAM,
+        program_id: program.program_id,
+        elf_hash: program.elf_hash,
+    })
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn seat_map(req: SeatsRequest) -> SeatsResponse {
+    tracing::info!("[GET-SEATS] Tool call received: from={}, to={}, date={:?}", req.from, req.to, req.departure_date);
+    let map = seats::availability(&req.from, &req.to, req.departure_date.as_deref());
+    SeatsResponse {
+        available: map.seats.iter().filter(|s| s.available).count(),
+        from: map.from,
+        to: map.to,
+        departure_date: map.departure_date,
+        seats: map.seats,
+    }
+}
+
+fn select_seat(req: SelectSeatRequest) -> Result<SelectSeatResponse, String> {
+    tracing::info!("[SELECT-SEAT] Tool call received: from={}, to={}, seat={}", req.from, req.to, req.seat);
+    let seat = seats::check(&req.from, &req.to, req.departure_date.as_deref(), &req.seat, req.cabin)?;
+    Ok(SelectSeatResponse {
+        seat,
+        from: req.from,
+        to: req.to,
+        departure_date: req.departure_date,
+        cabin: req.cabin,
+    })
+}
+
+/// Dispatch an MCP tool call by name; the result carries the `zk` block as a field
+fn call_tool(state: &AppState, name: &str, arguments: Value) -> Result<Value, String> {
+    fn parse<T: serde::de::DeserializeOwned>(arguments: Value) -> Result<T, String> {
+        serde_json::from_value(arguments).map_err(|e| format!("Invalid arguments: {}", e))
+    }
+
+    let mut result = match name {
+        "get-ticket-price" => price(parse(arguments)?).and_then(to_value),
+        "book-flight" => book(parse(arguments)?).and_then(to_value),
+        "validate-promo" => validate_promo(parse(arguments)?).and_then(to_value),
+        "get-flight-options" => flight_options(parse(arguments)?).and_then(to_value),
+        "get-seats" => to_value(seat_map(parse(arguments)?)),
+        "select-seat" => select_seat(parse(arguments)?).and_then(to_value),
+        "get-booking" => get_booking(parse(arguments)?).and_then(to_value),
+        "cancel-booking" => cancel_booking(parse(arguments)?).and_then(to_value),
+        "change-booking" => change_booking(parse(arguments)?).and_then(to_value),
+        "get-zk-input" => zk_input(state, parse(arguments)?).and_then(to_value),
+        _ => Err(format!("Unknown tool: {}", name)),
+    }?;
+    result["zk"] = to_value(state.zk(name))?;
+    Ok(result)
+}
+
+/// GET /tools and POST /tools/:name via agent-b-tools; the typed routes answer every known
+/// tool first, so the `:name` dispatch only sees names outside the catalogue
+#[async_trait]
+impl ToolBackend for AppState {
+    fn offers(&self, name: &str) -> bool {
+        agent_b_tools::definitions().tools.iter().any(|tool| tool.name == name)
+    }
+
+    async fn call(self: Arc<Self>, name: &str, arguments: Value, _headers: HeaderMap) -> Result<Value, ToolError> {
+        call_tool(&self, name, arguments).map_err(|e| ToolError::new(StatusCode::BAD_REQUEST, e))
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+/// MCP revision implemented by the stdio mode
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Serve MCP over stdin/stdout (one JSON-RPC message per line)
+fn run_jsonrpc_server(state: &AppState) -> Result<()> {
+    use std::io::BufRead;
+
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle_jsonrpc(state, request),
+            Err(e) => Some(jsonrpc_error(json!(null), -32700, format!("Parse error: {}", e))),
+        };
+
+        // Notifications get no response
+        if let Some(response) = response {
+            println!("{}", response);
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle one MCP JSON-RPC message; `None` for notifications
+fn handle_jsonrpc(state: &AppState, request: Value) -> Option<Value> {
+    let id = request.get("id")?.clone();
+    let method = request.get("method").and_then(|v| v.as_str()).unwrap_or_default();
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": {"tools": {}},
+            "serverInfo": {
+                "name": "Agent B",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        }),
+
+        "ping" => json!({}),
+
+        "tools/list" => to_value(agent_b_tools::offered(state)).unwrap_or_default(),
+
+        "tools/call" => {
+            let params = request.get("params").cloned().unwrap_or(json!({}));
+            let Some(name) = params.get("name").and_then(|v| v.as_str()) else {
+                return Some(jsonrpc_error(id, -32602, "tools/call requires params.name".to_string()));
+            };
+            let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+            // Tool failures are results with isError, not protocol errors
+            let (text, is_error) = match call_tool(state, name, arguments) {
+                Ok(result) => (serde_json::to_string_pretty(&result).unwrap_or_default(), false),
+                Err(e) => (e, true),
+            };
+            json!({
+                "content": [{"type": "text", "text": text}],
+                "isError": is_error
+            })
+        }
+
+        _ => return Some(jsonrpc_error(id, -32601, format!("Method not found: {}", method))),
+    };
+
+    Some(json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "result": result
+    }))
+}
+
+fn jsonrpc_error(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": code,
+            "message": message
+        }
+    })
+}
+
+/// OpenAPI spec for the tool routes
+async fn openapi_json() -> Json<Value> {
+    Json(
+        SpecBuilder::new()
+            .get::<ToolsResponse>("/tools", "List all tools")
+            .post::<PriceRequest, ToolResponse<PriceResponse>>(
+                "/tools/get-ticket-price",
+                "Get flight pricing",
+            )
+            .post::<BookRequest, ToolResponse<BookResponse>>(
+                "/tools/book-flight",
+                "Book a flight",
+            )
+            .post::<FlightOptionsRequest, ToolResponse<FlightOptionsResponse>>(
+                "/tools/get-flight-options",
+                "Compare prices across routes and dates",
+            )
+            .post::<PriceRequest, ToolResponse<PromoResponse>>(
+                "/tools/validate-promo",
+                "Check a promo code against a fare",
+            )
+            .post::<SeatsRequest, ToolResponse<SeatsResponse>>(
+                "/tools/get-seats",
+                "Get a flight's seat map",
+            )
+            .post::<SelectSeatRequest, ToolResponse<SelectSeatResponse>>(
+                "/tools/select-seat",
+                "Check a seat before booking it",
+            )
+            .post::<BookingIdRequest, ToolResponse<BookResponse>>(
+                "/tools/get-booking",
+                "Look up a booking",
+            )
+            .post::<BookingIdRequest, ToolResponse<BookResponse>>(
+                "/tools/cancel-booking",
+                "Cancel a booking",
+            )
+            .post::<ChangeBookingRequest, ToolResponse<ChangeBookingResponse>>(
+                "/tools/change-booking",
+                "Change a booking's date or seat",
+            )
+            .post::<ZkInputRequest, ToolResponse<ZkInputResponse>>(
+                "/tools/get-zk-input",
+                "Input bytes to prove a tool result with",
+            )
+            .build("Agent B MCP Server", env!("CARGO_PKG_VERSION")),
+    )
+}
+
+async fn swagger_ui() -> Html<String> {
+    Html(swagger_ui_html("Agent B MCP Server", "/openapi.json"))
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
+    // Initialize logging (stderr, so stdio mode keeps stdout for JSON-RPC)
+    let _telemetry = telemetry::init("agent-b-mcp", true);
+
+    let attester_url = std::env::var("ATTESTER_URL").unwrap_or_else(|_| "http://localhost:8000".to_string());
+    let elf_path = std::env::var("AGENT_B_ELF").map(PathBuf::from).unwrap_or_else(|_| {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/agent-b-program")
+    });
+    let tls = TlsConfig::from_env();
+    tls.validate()?;
+    // Presents TLS_CLIENT_CERT to the attester, if set
+    let client = tls.client(reqwest::Client::builder())?.build()?;
+    let state = Arc::new(AppState::new(attester_url, elf_path, client));
+    // Tools answer meanwhile, with a null zk.program_id
+    tokio::spawn({
+        let state = state.clone();
+        async move { state.register().await }
+    });
+
+    if std::env::var("AGENT_B_MODE").as_deref() == Ok("jsonrpc") {
+        // Blocks on stdin, so off the runtime's worker threads
+        return tokio::task::spawn_blocking(move || run_jsonrpc_server(&state)).await?;
+    }
 
     println!("\n╔════════════════════════════════════════════════════════════╗");
     println!("║          Agent B - MCP Server (Pricing & Booking)          ║");
@@ -244,21 +991,46 @@ async fn main() -> Result<()> {
 
     // Build router
     let app = Router::new()
-        .route("/tools", get(list_tools))
         .route("/tools/get-ticket-price", post(get_ticket_price))
         .route("/tools/book-flight", post(book_flight))
-        .layer(CorsLayer::permissive());
-
-    // Bind and serve
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:8001")
-        .await?;
+        .route("/tools/validate-promo", post(validate_promo_tool))
+        .route("/tools/get-flight-options", post(get_flight_options))
+        .route("/tools/get-seats", post(get_seats))
+        .route("/tools/select-seat", post(select_seat_tool))
+        .route("/tools/get-booking", post(get_booking_tool))
+        .route("/tools/cancel-booking", post(cancel_booking_tool))
+        .route("/tools/change-booking", post(change_booking_tool))
+        .route("/tools/get-zk-input", post(get_zk_input_tool))
+        .route("/openapi.json", get(openapi_json))
+        .route("/docs", get(swagger_ui))
+        .merge(agent_b_tools::router(state.clone()))
+        .layer(TraceLayer::new_for_http().make_span_with(telemetry::http_span))
+        .layer(CorsLayer::permissive())
+        .with_state(state.clone());
 
-    println!("✓ Agent B MCP Server running on http://0.0.0.0:8001");
+    // AGENT_B_MCP_PORT lets it run next to agent-b-server, which also defaults to 8001
+    let port: u16 = std::env::var("AGENT_B_MCP_PORT").ok().and_then(|p| p.parse().ok()).unwrap_or(8001);
+    println!("✓ Agent B MCP Server running on {}://0.0.0.0:{}", tls.scheme(), port);
+    if tls.requires_client_cert() {
+        println!("  ✓ Client certificates required (TLS_CLIENT_CA)");
+    }
+    println!("  attester_url: {}", state.attester_url);
+    println!("  elf_path: {:?}", state.elf_path);
     println!("  GET  /tools                     — List all tools");
     println!("  POST /tools/get-ticket-price    — Get flight pricing");
-    println!("  POST /tools/book-flight         — Book a flight\n");
+    println!("  POST /tools/book-flight         — Book a flight");
+    println!("  POST /tools/validate-promo      — Check a promo code");
+    println!("  POST /tools/get-flight-options  — Compare routes and dates");
+    println!("  POST /tools/get-seats           — Get a flight's seat map");
+    println!("  POST /tools/select-seat         — Check a seat before booking");
+    println!("  POST /tools/get-booking         — Look up a booking");
+    println!("  POST /tools/cancel-booking      — Cancel a booking");
+    println!("  POST /tools/change-booking      — Change a booking's date or seat");
+    println!("  POST /tools/get-zk-input        — Input bytes to prove a result");
+    println!("  GET  /openapi.json              — OpenAPI spec");
+    println!("  GET  /docs                      — Swagger UI\n");
 
-    axum::serve(listener, app).await?;
+    agent_tls::serve(([0, 0, 0, 0], port).into(), app, &tls).await?;
 
     Ok(())
 }
@@ -0,0 +1,157 @@
+//! ZK metadata for tool responses
+//!
+//! The MCP server registers the full guest program (agent-b-program, which proves every call)
+//! with the attester at ATTESTER_URL, in the background and retried until it succeeds, as
+//! agent-b/server does. Every successful tool response then carries a `zk` block with its
+//! program_id and elf_hash, and, for tools whose result the guest can prove, the
+//! `get-zk-input` endpoint to use. Until registration succeeds program_id and elf_hash are null.
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use pricing_core::{booking, cancellation, pricing, RpcCall};
+
+/// Name agent-b/server's GET /program gives the same binary
+pub const PROGRAM: &str = "full";
+
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+const MAX_RETRY: Duration = Duration::from_secs(60);
+
+/// The guest program as registered with the attester
+#[derive(Clone)]
+pub struct Program {
+    pub program_id: String,
+    /// SHA-256 of the ELF, 0x-prefixed hex
+    pub elf_hash: String,
+}
+
+/// `zk` block of a tool response
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ZkInfo {
+    pub program: &'static str,
+    /// Null until the ELF is registered with the attester
+    pub program_id: Option<String>,
+    pub elf_hash: Option<String>,
+    pub attester_url: String,
+    /// get-zk-input endpoint that proves this result; null if the guest doesn't prove it
+    pub endpoint: Option<&'static str>,
+}
+
+/// State shared by the tool handlers
+pub struct AppState {
+    pub attester_url: String,
+    pub elf_path: PathBuf,
+    program: RwLock<Option<Program>>,
+    client: reqwest::Client,
+}
+
+impl AppState {
+    pub fn new(attester_url: String, elf_path: PathBuf, client: reqwest::Client) -> Self {
+        Self {
+            attester_url,
+            elf_path,
+            program: RwLock::new(None),
+            client,
+        }
+    }
+
+    pub fn program(&self) -> Option<Program> {
+        self.program.read().unwrap().clone()
+    }
+
+    /// `zk` block for a response of `tool`
+    pub fn zk(&self, tool: &str) -> ZkInfo {
+        let program = self.program();
+        ZkInfo {
+            program: PROGRAM,
+            program_id: program.as_ref().map(|p| p.program_id.clone()),
+            elf_hash: program.map(|p| p.elf_hash),
+            attester_url: self.attester_url.clone(),
+            endpoint: endpoint_for(tool),
+        }
+    }
+
+    /// Register the ELF, retrying with exponential backoff (capped at a minute) until it succeeds
+    pub async fn register(&self) {
+        let mut delay = FIRST_RETRY;
+        loop {
+            match self.attempt().await {
+                Ok(program) => {
+                    tracing::info!("✓ ELF registered with attester, program_id {}", program.program_id);
+                    *self.program.write().unwrap() = Some(program);
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!("⚠ ELF registration failed (retrying in {}s): {}", delay.as_secs(), e);
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(MAX_RETRY);
+                }
+            }
+        }
+    }
+
+    async fn attempt(&self) -> Result<Program, String> {
+        let elf = std::fs::read(&self.elf_path).map_err(|e| {
+            format!("Failed to read {:?} ({}). Run 'cd program && cargo prove build' first.", self.elf_path, e)
+        })?;
+        let elf_hash = format!("0x{}", hex::encode(Sha256::digest(&elf)));
+
+        let part = reqwest::multipart::Part::bytes(elf)
+            .file_name("agent-b-program.elf")
+            .mime_str("application/octet-stream")
+            .map_err(|e| format!("Failed to create multipart: {}", e))?;
+        let body: Value = self
+            .client
+            .post(format!("{}/register-elf", self.attester_url))
+            .headers(telemetry::trace_headers())
+            .multipart(reqwest::multipart::Form::new().part("elf", part))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to register ELF: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse attester response: {}", e))?;
+        let program_id = body["program_id"].as_str().ok_or("No program_id in response")?.to_string();
+        Ok(Program { program_id, elf_hash })
+    }
+}
+
+/// get-zk-input endpoint whose guest call reproduces `tool`'s result
+fn endpoint_for(tool: &str) -> Option<&'static str> {
+    match tool {
+        "get-ticket-price" => Some("price"),
+        "get-flight-options" => Some("price_batch"),
+        "book-flight" => Some("book"),
+        "cancel-booking" => Some("cancel"),
+        _ => None,
+    }
+}
+
+/// Guest input for `endpoint`, bincode-encoded as the zkVM reads it. `input` is as for
+/// agent-b/server's POST /zk-input: the tool's arguments plus the quote_id and purchase_date
+/// get-ticket-price returned, the pii_salt book-flight returned for "book", or the booking's
+/// fare for "cancel".
+pub fn input_bytes(endpoint: &str, input: Value) -> Result<Vec<u8>, String> {
+    fn parse<T: serde::de::DeserializeOwned>(endpoint: &str, input: Value) -> Result<T, String> {
+        serde_json::from_value(input).map_err(|e| format!("Invalid {} input: {}", endpoint, e))
+    }
+
+    let call = match endpoint {
+        "price" => RpcCall::GetPrice(parse::<pricing::Request>(endpoint, input)?),
+        "price_batch" => RpcCall::GetPriceBatch(parse(endpoint, input)?),
+        "book" => RpcCall::BookFlight(parse::<booking::Request>(endpoint, input)?),
+        "cancel" => RpcCall::CancelBooking(parse::<cancellation::Request>(endpoint, input)?),
+        _ => return Err(format!("Unknown endpoint: {} (price, price_batch, book, or cancel)", endpoint)),
+    };
+    // Proving a call the guest would reject only wastes prover time
+    call.validate().map_err(|e| e.to_string())?;
+    if matches!(&call, RpcCall::BookFlight(req) if req.pii_salt.is_none()) {
+        return Err("book input needs the pii_salt book-flight returned".to_string());
+    }
+    bincode::serialize(&call).map_err(|e| format!("Failed to serialize RpcCall: {}", e))
+}
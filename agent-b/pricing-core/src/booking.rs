@@ -1,12 +1,30 @@
 use alloc::string::String;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+use crate::fare::{Cabin, FareFamily};
+use crate::seats;
+use crate::validate::{self, ValidationError};
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Request {
     pub from: String,
     pub to: String,
     pub passenger_name: String,
     pub passenger_email: String,
+    #[serde(default)]
+    pub cabin: Cabin,
+    #[serde(default)]
+    pub fare_family: FareFamily,
+    /// Departure day, `YYYY-MM-DD`; picks the flight whose seat map `seat` is checked against
+    #[serde(default)]
+    pub departure_date: Option<String>,
+    /// Seat from [`seats::availability`], e.g. "12C"; must be free and in `cabin`
+    #[serde(default)]
+    pub seat: Option<String>,
+    /// Salt of the passenger commitment a booking proof commits instead of name and email
+    /// (see `zk_protocol::PiiCommitment`); required to prove a booking, not to make one
+    #[serde(default)]
+    pub pii_salt: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -14,30 +32,100 @@ pub struct Response {
     pub booking_id: String,
     pub status: String,
     pub confirmation_code: String,
+    /// Seat booked, as it appears on the seat map
+    pub seat: Option<String>,
+}
+
+impl Request {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate::route(&self.from, &self.to)?;
+        validate::name("passenger_name", &self.passenger_name)?;
+        validate::email("passenger_email", &self.passenger_email)?;
+        match &self.pii_salt {
+            Some(salt) => validate::pii_salt("pii_salt", salt),
+            None => Ok(()),
+        }
+    }
 }
 
 /// Booking logic that runs both on server and inside SP1
 /// NOTE: Inside SP1, external HTTP calls are not possible, so this will
 /// return a deterministic result based on input. The server implementation
 /// can override this to make real HTTP calls.
-pub fn handle(req: Request) -> Response {
+pub fn handle(req: Request) -> Result<Response, String> {
     // Deterministic booking logic for ZK proof
     // In SP1: generates deterministic booking based on inputs
     // On server: this can be overridden to call real booking API
-    
+    req.validate().map_err(|e| alloc::format!("{}", e))?;
+    if let Some(date) = req.departure_date.as_deref().filter(|d| crate::pricing::is_blackout(d)) {
+        return Err(alloc::format!("no flights on {}", date));
+    }
+
+    let seat = req
+        .seat
+        .as_deref()
+        .map(|seat| seats::check(&req.from, &req.to, req.departure_date.as_deref(), seat, req.cabin))
+        .transpose()?;
+
     // Generate deterministic booking ID from request data
     let booking_data = alloc::format!(
-        "{}-{}-{}-{}",
-        req.from, req.to, req.passenger_name, req.passenger_email
+        "{}-{}-{}-{}-{}-{}-{}",
+        req.from,
+        req.to,
+        req.passenger_name,
+        req.passenger_email,
+        req.cabin.as_str(),
+        req.fare_family.as_str(),
+        seat.as_deref().unwrap_or_default()
     );
     
-    // Simple hash-like transformation (deterministic)
-    let booking_id = alloc::format!("BK{:08X}", booking_data.len() * 12345);
-    let confirmation_code = alloc::format!("CONF{:06X}", booking_data.len() * 67890);
+    // Deterministic, and distinct for different passengers on the same flight
+    let hash = crate::fnv1a(&[&booking_data]);
+    let booking_id = alloc::format!("BK{:08X}", hash >> 32);
+    let confirmation_code = alloc::format!("CONF{:06X}", hash & 0xFF_FFFF);
 
-    Response {
+    Ok(Response {
         booking_id,
         status: String::from("confirmed"),
         confirmation_code,
+        seat,
+    })
+}
+
+/// Key under which repeats of the same booking are detected: one passenger (name ignoring
+/// case and spacing, email ignoring case) on one route and day. Cabin, fare family, and seat
+/// are left out, so a retry that changed them still finds the original.
+pub fn duplicate_key(req: &Request) -> String {
+    let name = req.passenger_name.split_whitespace().collect::<alloc::vec::Vec<_>>().join(" ").to_lowercase();
+    let email = req.passenger_email.trim().to_lowercase();
+    let hash = crate::fnv1a(&[&req.from, &req.to, req.departure_date.as_deref().unwrap_or_default(), &name, &email]);
+    alloc::format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(name: &str, email: &str, date: Option<&str>, seat: Option<&str>) -> Request {
+        Request {
+            from: "NYC".into(),
+            to: "LON".into(),
+            passenger_name: name.into(),
+            passenger_email: email.into(),
+            cabin: Cabin::Economy,
+            fare_family: FareFamily::NonRefundable,
+            departure_date: date.map(Into::into),
+            seat: seat.map(Into::into),
+            pii_salt: None,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_key_ignores_case_spacing_and_seat() {
+        let key = duplicate_key(&request("Jane Doe", "jane@example.com", Some("2026-11-02"), None));
+        assert_eq!(key, duplicate_key(&request(" jane  DOE ", "Jane@Example.com", Some("2026-11-02"), Some("12C"))));
+        assert_ne!(key, duplicate_key(&request("Jane Doe", "jane@example.com", Some("2026-11-03"), None)));
+        assert_ne!(key, duplicate_key(&request("Jane Doe", "jane@example.com", None, None)));
+        assert_ne!(key, duplicate_key(&request("John Doe", "jane@example.com", Some("2026-11-02"), None)));
     }
 }
@@ -0,0 +1,116 @@
+//! Cancellation and change fees
+//!
+//! Fees are basis points of the fare paid, set by fare family and how many days before
+//! departure the booking is cancelled. This runs in the guest like pricing does, so a
+//! refund can be proven against the same rules the server applied.
+
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+
+use crate::date;
+use crate::fare::FareFamily;
+
+/// Changing a non-refundable booking costs this share of the fare; refundable changes are free
+pub(crate) const NON_REFUNDABLE_CHANGE_BPS: u64 = 1_500;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Request {
+    pub booking_id: String,
+    /// Fare paid, in `currency`'s minor units
+    pub fare_cents: u64,
+    pub currency: String,
+    #[serde(default)]
+    pub fare_family: FareFamily,
+    /// `YYYY-MM-DD`; bookings without one are charged the last-minute fee
+    #[serde(default)]
+    pub departure_date: Option<String>,
+    /// Day of cancellation, `YYYY-MM-DD`
+    pub cancel_date: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Response {
+    pub booking_id: String,
+    pub fare_cents: u64,
+    pub fee_cents: u64,
+    /// `fare_cents - fee_cents`
+    pub refund_cents: u64,
+    pub currency: String,
+}
+
+pub fn handle(req: Request) -> Result<Response, String> {
+    let days_out = match &req.departure_date {
+        Some(departure) => date::days_between(&req.cancel_date, departure)
+            .ok_or_else(|| alloc::format!("invalid dates {} / {}, expected YYYY-MM-DD", req.cancel_date, departure))?,
+        None => {
+            date::parse(&req.cancel_date)
+                .ok_or_else(|| alloc::format!("invalid cancel_date {}, expected YYYY-MM-DD", req.cancel_date))?;
+            0
+        }
+    };
+    if days_out < 0 {
+        return Err(alloc::format!("booking {} has already departed", req.booking_id));
+    }
+
+    let fee_cents = req.fare_cents * fee_bps(req.fare_family, days_out) / 10_000;
+    Ok(Response {
+        booking_id: req.booking_id,
+        fare_cents: req.fare_cents,
+        fee_cents,
+        refund_cents: req.fare_cents - fee_cents,
+        currency: req.currency,
+    })
+}
+
+/// Refundable: free a week out, 10% until the day before, 25% on the day.
+/// Non-refundable: half back a month out, nothing after.
+fn fee_bps(fare_family: FareFamily, days_out: i64) -> u64 {
+    match (fare_family, days_out) {
+        (FareFamily::Refundable, 7..) => 0,
+        (FareFamily::Refundable, 1..) => 1_000,
+        (FareFamily::Refundable, _) => 2_500,
+        (FareFamily::NonRefundable, 30..) => 5_000,
+        (FareFamily::NonRefundable, _) => 10_000,
+    }
+}
+
+/// Fee for moving a booking to another date or seat, in the fare's minor units
+pub fn change_fee_cents(fare_family: FareFamily, fare_cents: u64) -> u64 {
+    match fare_family {
+        FareFamily::Refundable => 0,
+        FareFamily::NonRefundable => fare_cents * NON_REFUNDABLE_CHANGE_BPS / 10_000,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cancel(fare_family: FareFamily, departure: Option<&str>, on: &str) -> Result<(u64, u64), String> {
+        handle(Request {
+            booking_id: "BK1".into(),
+            fare_cents: 60_000,
+            currency: "USD".into(),
+            fare_family,
+            departure_date: departure.map(Into::into),
+            cancel_date: on.into(),
+        })
+        .map(|resp| (resp.fee_cents, resp.refund_cents))
+    }
+
+    #[test]
+    fn test_cancellation_fee_by_notice() {
+        let departure = Some("2026-11-30");
+        assert_eq!(cancel(FareFamily::Refundable, departure, "2026-11-23"), Ok((0, 60_000)));
+        assert_eq!(cancel(FareFamily::Refundable, departure, "2026-11-29"), Ok((6_000, 54_000)));
+        assert_eq!(cancel(FareFamily::Refundable, departure, "2026-11-30"), Ok((15_000, 45_000)));
+        assert_eq!(cancel(FareFamily::NonRefundable, departure, "2026-10-31"), Ok((30_000, 30_000)));
+        assert_eq!(cancel(FareFamily::NonRefundable, departure, "2026-11-01"), Ok((60_000, 0)));
+        assert_eq!(cancel(FareFamily::Refundable, None, "2026-11-01"), Ok((15_000, 45_000)));
+        assert!(cancel(FareFamily::Refundable, departure, "2026-12-01").is_err());
+        assert!(cancel(FareFamily::Refundable, None, "soon").is_err());
+
+        assert_eq!(change_fee_cents(FareFamily::NonRefundable, 60_000), 9_000);
+        assert_eq!(change_fee_cents(FareFamily::Refundable, 60_000), 0);
+    }
+}
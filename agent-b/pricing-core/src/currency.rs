@@ -0,0 +1,35 @@
+//! Fixed conversion table for quoting outside USD
+//!
+//! Rates are integer micro-units of the currency per 1 USD, so the guest and the
+//! server convert identically without floats. They are part of the ELF: changing a
+//! rate changes the program's vk.
+
+pub struct Currency {
+    pub code: &'static str,
+    /// Digits after the decimal point (2 for cents, 0 for JPY)
+    pub minor_digits: u32,
+    /// Units of this currency per 1 USD, times 1_000_000
+    pub per_usd_micros: u64,
+}
+
+pub const USD: &str = "USD";
+
+pub const CURRENCIES: &[Currency] = &[
+    Currency { code: "USD", minor_digits: 2, per_usd_micros: 1_000_000 },
+    Currency { code: "EUR", minor_digits: 2, per_usd_micros: 920_000 },
+    Currency { code: "GBP", minor_digits: 2, per_usd_micros: 790_000 },
+    Currency { code: "JPY", minor_digits: 0, per_usd_micros: 149_500_000 },
+];
+
+/// Table entry for an ISO 4217 code (case-insensitive)
+pub fn find(code: &str) -> Option<&'static Currency> {
+    CURRENCIES.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
+
+impl Currency {
+    /// USD cents in this currency's minor units, rounded down
+    pub fn from_usd_cents(&self, usd_cents: u64) -> u64 {
+        let scale = 10u128.pow(self.minor_digits);
+        (usd_cents as u128 * self.per_usd_micros as u128 * scale / 100_000_000) as u64
+    }
+}
@@ -0,0 +1,72 @@
+//! Calendar arithmetic on `YYYY-MM-DD` strings
+//!
+//! Days are counted from 1970-01-01 with Howard Hinnant's civil-date algorithms, so the
+//! guest needs no date crate.
+
+use alloc::string::String;
+
+/// (year, month, day) if `date` is a real calendar day
+pub fn parse(date: &str) -> Option<(i64, u32, u32)> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    (date.len() == 10 && (1..=days_in_month).contains(&day)).then_some((year, month, day))
+}
+
+/// Days since 1970-01-01 (a Thursday)
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// UTC day of `unix_secs` as `YYYY-MM-DD`
+pub fn from_unix_secs(unix_secs: u64) -> String {
+    let z = (unix_secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    alloc::format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Whole days from `from` to `to`; negative if `to` is earlier
+pub fn days_between(from: &str, to: &str) -> Option<i64> {
+    let (fy, fm, fd) = parse(from)?;
+    let (ty, tm, td) = parse(to)?;
+    Some(days_from_civil(ty, tm, td) - days_from_civil(fy, fm, fd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_day_roundtrip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(from_unix_secs(0), "1970-01-01");
+        // 2026-10-15T12:00:00Z
+        assert_eq!(from_unix_secs(1_792_065_600), "2026-10-15");
+        assert_eq!(days_between("2026-02-27", "2026-03-01"), Some(2));
+        assert_eq!(days_between("2028-02-27", "2028-03-01"), Some(3));
+        assert_eq!(days_between("2026-03-01", "2026-02-27"), Some(-2));
+        assert_eq!(days_between("2026-02-30", "2026-03-01"), None);
+    }
+}
@@ -0,0 +1,62 @@
+//! Cabin classes and fare families
+//!
+//! Both default to the cheapest option, so requests that don't name them price and
+//! book as before.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cabin {
+    #[default]
+    Economy,
+    Premium,
+    Business,
+    First,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FareFamily {
+    #[default]
+    NonRefundable,
+    Refundable,
+}
+
+impl Cabin {
+    /// Fare relative to economy, in basis points
+    pub fn bps(self) -> u64 {
+        match self {
+            Cabin::Economy => 10_000,
+            Cabin::Premium => 16_000,
+            Cabin::Business => 30_000,
+            Cabin::First => 50_000,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Cabin::Economy => "economy",
+            Cabin::Premium => "premium",
+            Cabin::Business => "business",
+            Cabin::First => "first",
+        }
+    }
+}
+
+impl FareFamily {
+    /// Fare relative to non-refundable, in basis points
+    pub fn bps(self) -> u64 {
+        match self {
+            FareFamily::NonRefundable => 10_000,
+            FareFamily::Refundable => 12_500,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FareFamily::NonRefundable => "non_refundable",
+            FareFamily::Refundable => "refundable",
+        }
+    }
+}
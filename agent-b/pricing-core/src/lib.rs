@@ -6,26 +6,97 @@ use serde::{Deserialize, Serialize};
 
 pub mod pricing;
 pub mod booking;
+pub mod cancellation;
+pub mod currency;
+pub mod date;
+pub mod fare;
+pub mod promo;
+pub mod routes;
+pub mod ruleset;
+pub mod seats;
+pub mod validate;
 
 /// Single enum — one input type for the entire backend
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub enum RpcCall {
     GetPrice(pricing::Request),
+    /// Several options priced and compared in one execution
+    GetPriceBatch(alloc::vec::Vec<pricing::Request>),
     BookFlight(booking::Request),
+    CancelBooking(cancellation::Request),
 }
 
 /// Single enum — one output type
 #[derive(Serialize, Deserialize)]
 pub enum RpcResult {
     Price(pricing::Response),
+    PriceBatch(pricing::BatchResponse),
     Booking(booking::Response),
+    Cancellation(cancellation::Response),
+    /// Request rejected by [`validate`] before any logic ran
+    Invalid(validate::ValidationError),
     Error(String),
 }
 
+impl RpcCall {
+    /// Name of the call as used by Agent B's `/zk-input` and program selection
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            RpcCall::GetPrice(_) => "price",
+            RpcCall::GetPriceBatch(_) => "price_batch",
+            RpcCall::BookFlight(_) => "book",
+            RpcCall::CancelBooking(_) => "cancel",
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), validate::ValidationError> {
+        match self {
+            RpcCall::GetPrice(req) => req.validate(),
+            RpcCall::GetPriceBatch(reqs) => reqs.iter().enumerate().try_for_each(|(i, req)| {
+                req.validate().map_err(|mut e| {
+                    e.field = alloc::format!("options[{}].{}", i, e.field);
+                    e
+                })
+            }),
+            RpcCall::BookFlight(req) => req.validate(),
+            RpcCall::CancelBooking(_) => Ok(()),
+        }
+    }
+}
+
 /// Main dispatcher — runs both on server and inside SP1
 pub fn handle_call(call: RpcCall) -> RpcResult {
+    if let Err(e) = call.validate() {
+        return RpcResult::Invalid(e);
+    }
     match call {
-        RpcCall::GetPrice(req)   => RpcResult::Price(pricing::handle(req)),
-        RpcCall::BookFlight(req) => RpcResult::Booking(booking::handle(req)),
+        RpcCall::GetPrice(req)   => match pricing::handle(req) {
+            Ok(resp) => RpcResult::Price(resp),
+            Err(e) => RpcResult::Error(e),
+        },
+        RpcCall::GetPriceBatch(reqs) => match pricing::handle_batch(reqs) {
+            Ok(resp) => RpcResult::PriceBatch(resp),
+            Err(e) => RpcResult::Error(e),
+        },
+        RpcCall::BookFlight(req) => match booking::handle(req) {
+            Ok(resp) => RpcResult::Booking(resp),
+            Err(e) => RpcResult::Error(e),
+        },
+        RpcCall::CancelBooking(req) => match cancellation::handle(req) {
+            Ok(resp) => RpcResult::Cancellation(resp),
+            Err(e) => RpcResult::Error(e),
+        },
+    }
+}
+
+/// FNV-1a over `parts`, '|'-separated; stable across host and guest, for ids and seat maps
+pub(crate) fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain(core::iter::once(b'|')) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
     }
+    hash
 }
\ No newline at end of file
@@ -1,38 +1,428 @@
 use alloc::string::String;
+use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+use crate::fare::{Cabin, FareFamily};
+use crate::validate::{self, ValidationError};
+use crate::{currency, date, promo, routes};
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Request {
     pub from: String,
     pub to: String,
     pub vip: bool,
+    /// Quote being proven; committed as-is so the proof binds to that quote
+    #[serde(default)]
+    pub quote_id: Option<String>,
+    /// Departure day, `YYYY-MM-DD`; enables weekend and seasonal adjustments
+    #[serde(default)]
+    pub departure_date: Option<String>,
+    /// Return day, `YYYY-MM-DD`; prices the `to`→`from` leg too and quotes the round trip
+    #[serde(default)]
+    pub return_date: Option<String>,
+    /// Day the fare is bought, `YYYY-MM-DD`; with `departure_date`, sets the advance-purchase
+    /// tier. The server fills it from its clock so the guest can reproduce the quote later.
+    #[serde(default)]
+    pub purchase_date: Option<String>,
+    /// ISO 4217 code from the conversion table; USD if unset
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub cabin: Cabin,
+    #[serde(default)]
+    pub fare_family: FareFamily,
+    /// Code from [`promo::PROMOS`]; an unknown or inapplicable code fails the quote
+    #[serde(default)]
+    pub promo_code: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Response {
-    pub price: f64,
+    /// In `currency`'s minor units (cents; whole yen for JPY), after any discount
+    pub price_cents: u64,
+    pub currency: String,
+    /// Promo code applied, as published
+    pub promo_code: Option<String>,
+    /// Amount the promo took off, in `currency`'s minor units
+    pub discount_cents: u64,
+}
+
+impl Request {
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate::route(&self.from, &self.to)
+    }
 }
 
+/// Most options one batch may compare; each is priced inside the guest
+pub const MAX_BATCH_OPTIONS: usize = 20;
+
+/// One row of a batch comparison
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BatchOption {
+    pub from: String,
+    pub to: String,
+    pub departure_date: Option<String>,
+    pub return_date: Option<String>,
+    /// `None` if the option couldn't be priced (blackout day, inapplicable promo, ...)
+    pub price_cents: Option<u64>,
+    pub promo_code: Option<String>,
+    /// Why the option couldn't be priced
+    pub error: Option<String>,
+}
+
+impl BatchOption {
+    /// `NYC-LON 2026-05-13/2026-05-20 135980`, with `-` for a missing date or price
+    pub fn row(&self) -> String {
+        let mut row = alloc::format!("{}-{} {}", self.from, self.to, self.departure_date.as_deref().unwrap_or("-"));
+        if let Some(return_date) = &self.return_date {
+            row.push('/');
+            row.push_str(return_date);
+        }
+        match self.price_cents {
+            Some(price_cents) => row.push_str(&alloc::format!(" {}", price_cents)),
+            None => row.push_str(" -"),
+        }
+        row
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BatchResponse {
+    /// In request order
+    pub options: Vec<BatchOption>,
+    /// Currency of every price in `options`
+    pub currency: String,
+    /// Index into `options` of the lowest price; the earliest wins a tie
+    pub cheapest: usize,
+}
+
+impl BatchResponse {
+    /// Every option's [`BatchOption::row`], `;`-separated, as committed by the guest
+    pub fn table(&self) -> String {
+        self.options.iter().map(BatchOption::row).collect::<Vec<_>>().join(";")
+    }
+}
+
+/// Flat fare for routes missing from the route table
+pub(crate) const FALLBACK_FARE_CENTS: u64 = 45_000;
+
+/// Per-km rate by distance band: (band upper bound in km, cents per km)
+pub(crate) const DISTANCE_BANDS: &[(u64, u64)] = &[(1_500, 12), (4_000, 9), (u64::MAX, 7)];
+
+/// Adjustments in basis points of the running fare
+pub(crate) const VIP_BPS: u64 = 8_500;
+pub(crate) const WEEKEND_BPS: u64 = 11_000;
+pub(crate) const PEAK_SEASON_BPS: u64 = 12_000;
+pub(crate) const OFF_PEAK_SEASON_BPS: u64 = 9_000;
+
+/// Advance-purchase tiers: (minimum days between purchase and departure, bps), longest first
+pub(crate) const ADVANCE_PURCHASE_TIERS: &[(i64, u64)] = &[(60, 9_000), (21, 10_000), (7, 11_500), (0, 13_000)];
+
+/// (month, day) with no departures
+pub(crate) const BLACKOUT_DAYS: &[(u32, u32)] = &[(12, 25), (1, 1)];
+
 /// This function runs both on your server and inside SP1
 /// → Zero duplication, 100% guaranteed correctness
-pub fn handle(req: Request) -> Response {
-    // ←←← YOUR REAL SECRET PRICING LOGIC (edit only here!) ←←←
-    let base = if req.from == "NYC" && req.to == "LON" {
-        680.0
-    } else if req.from == "LON" && req.to == "NYC" {
-        675.0
-    } else {
-        450.0
+///
+/// Fares are integer cents throughout, so the guest and the server agree bit for bit:
+/// route base fare + distance at its band's rate, then cabin, fare family, season, and
+/// weekend adjustments per leg, then advance-purchase and VIP adjustments on the total,
+/// each rounded down, then any promo discount. The USD fare is then converted at the
+/// fixed rate in [`currency::CURRENCIES`].
+pub fn handle(req: Request) -> Result<Response, String> {
+    req.validate().map_err(|e| alloc::format!("{}", e))?;
+    let code = req.currency.as_deref().unwrap_or(currency::USD);
+    let currency = currency::find(code).ok_or_else(|| alloc::format!("unsupported currency {}", code))?;
+
+    let mut cents = leg_cents(&req, &req.from, &req.to, req.departure_date.as_deref())?;
+    if let Some(return_date) = &req.return_date {
+        let departure = req
+            .departure_date
+            .as_deref()
+            .ok_or_else(|| String::from("return_date needs a departure_date"))?;
+        if date::days_between(departure, return_date).is_some_and(|days| days < 0) {
+            return Err(alloc::format!("return_date {} is before departure_date {}", return_date, departure));
+        }
+        cents += leg_cents(&req, &req.to, &req.from, Some(return_date))?;
+    }
+
+    if let (Some(purchase), Some(departure)) = (&req.purchase_date, &req.departure_date) {
+        let days_ahead = date::days_between(purchase, departure)
+            .ok_or_else(|| alloc::format!("invalid purchase_date {}, expected YYYY-MM-DD", purchase))?;
+        if days_ahead < 0 {
+            return Err(alloc::format!("departure_date {} has passed", departure));
+        }
+        cents = apply_bps(cents, advance_purchase_bps(days_ahead));
+    }
+
+    if req.vip {
+        cents = apply_bps(cents, VIP_BPS);
+    }
+
+    let mut applied = None;
+    let mut discount = 0;
+    if let Some(code) = &req.promo_code {
+        let promo = promo::check(code, cents, req.departure_date.as_deref())?;
+        discount = promo.discount_cents(cents);
+        cents -= discount;
+        applied = Some(String::from(promo.code));
+    }
+
+    Ok(Response {
+        price_cents: currency.from_usd_cents(cents),
+        currency: String::from(currency.code),
+        promo_code: applied,
+        discount_cents: currency.from_usd_cents(discount),
+    })
+}
+
+/// USD fare of one `from`→`to` leg departing on `date`, before advance-purchase and VIP
+fn leg_cents(req: &Request, from: &str, to: &str, date: Option<&str>) -> Result<u64, String> {
+    let mut cents = match routes::find(from, to) {
+        Some(route) => route.base_fare_cents + route.distance_km * per_km_cents(route.distance_km),
+        None => FALLBACK_FARE_CENTS,
     };
+    cents = apply_bps(cents, req.cabin.bps());
+    cents = apply_bps(cents, req.fare_family.bps());
+
+    if let Some(date) = date {
+        let (year, month, day) =
+            date::parse(date).ok_or_else(|| alloc::format!("invalid date {}, expected YYYY-MM-DD", date))?;
+        if BLACKOUT_DAYS.contains(&(month, day)) {
+            return Err(alloc::format!("no flights on {}", date));
+        }
+        if let Some(bps) = season_bps(month, day) {
+            cents = apply_bps(cents, bps);
+        }
+        if is_weekend(year, month, day) {
+            cents = apply_bps(cents, WEEKEND_BPS);
+        }
+    }
+    Ok(cents)
+}
+
+/// Whether `date` (`YYYY-MM-DD`) is a day without departures
+pub fn is_blackout(date: &str) -> bool {
+    date::parse(date).is_some_and(|(_, month, day)| BLACKOUT_DAYS.contains(&(month, day)))
+}
 
-    let price = if req.vip {
-        base * 0.85
-    } else {
-        base
+/// Price every option the same way [`handle`] would and pick the cheapest
+///
+/// Options that can't be priced stay in the table with their error; the batch fails only
+/// if it's empty, too large, mixes currencies, or nothing in it could be priced.
+pub fn handle_batch(reqs: Vec<Request>) -> Result<BatchResponse, String> {
+    if reqs.is_empty() || reqs.len() > MAX_BATCH_OPTIONS {
+        return Err(alloc::format!("a batch compares 1–{} options, not {}", MAX_BATCH_OPTIONS, reqs.len()));
+    }
+    let code = |req: &Request| {
+        let code = req.currency.as_deref().unwrap_or(currency::USD);
+        currency::find(code).map(|c| c.code).ok_or_else(|| alloc::format!("unsupported currency {}", code))
     };
+    let currency = code(&reqs[0])?;
+    for req in &reqs[1..] {
+        if code(req)? != currency {
+            return Err(String::from("every option must be quoted in the same currency"));
+        }
+    }
+
+    let options: Vec<BatchOption> = reqs
+        .into_iter()
+        .map(|req| {
+            let mut option = BatchOption {
+                from: req.from.clone(),
+                to: req.to.clone(),
+                departure_date: req.departure_date.clone(),
+                return_date: req.return_date.clone(),
+                price_cents: None,
+                promo_code: None,
+                error: None,
+            };
+            match handle(req) {
+                Ok(resp) => {
+                    option.price_cents = Some(resp.price_cents);
+                    option.promo_code = resp.promo_code;
+                }
+                Err(e) => option.error = Some(e),
+            }
+            option
+        })
+        .collect();
+
+    let cheapest = options
+        .iter()
+        .enumerate()
+        .filter_map(|(i, option)| option.price_cents.map(|price| (i, price)))
+        .min_by_key(|&(_, price)| price)
+        .map(|(i, _)| i)
+        .ok_or_else(|| String::from("none of the options could be priced"))?;
+
+    Ok(BatchResponse { options, currency: String::from(currency), cheapest })
+}
+
+fn per_km_cents(distance_km: u64) -> u64 {
+    DISTANCE_BANDS
+        .iter()
+        .find(|(upper, _)| distance_km < *upper)
+        .map(|(_, rate)| *rate)
+        .unwrap_or_default()
+}
+
+fn apply_bps(cents: u64, bps: u64) -> u64 {
+    cents * bps / 10_000
+}
+
+/// Peak: Jun–Aug and Dec 15–Jan 5; off-peak: the rest of Jan through Mar
+fn season_bps(month: u32, day: u32) -> Option<u64> {
+    match (month, day) {
+        (6..=8, _) | (12, 15..) | (1, ..=5) => Some(PEAK_SEASON_BPS),
+        (1..=3, _) => Some(OFF_PEAK_SEASON_BPS),
+        _ => None,
+    }
+}
+
+/// Cheaper the further ahead the fare is bought: ≥60 days −10%, 21–59 days list,
+/// 7–20 days +15%, under a week +30%
+fn advance_purchase_bps(days_ahead: i64) -> u64 {
+    ADVANCE_PURCHASE_TIERS
+        .iter()
+        .find(|(min_days, _)| days_ahead >= *min_days)
+        .map(|(_, bps)| *bps)
+        .unwrap_or(10_000)
+}
+
+/// Saturday or Sunday
+fn is_weekend(year: i64, month: u32, day: u32) -> bool {
+    // 0 = Thursday
+    matches!(date::days_from_civil(year, month, day).rem_euclid(7), 2 | 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(from: &str, to: &str, vip: bool, date: Option<&str>) -> Request {
+        Request {
+            from: from.into(),
+            to: to.into(),
+            vip,
+            quote_id: None,
+            departure_date: date.map(Into::into),
+            return_date: None,
+            purchase_date: None,
+            currency: None,
+            cabin: Cabin::default(),
+            fare_family: FareFamily::default(),
+            promo_code: None,
+        }
+    }
+
+    fn quote(from: &str, to: &str, vip: bool, date: Option<&str>) -> Result<u64, String> {
+        handle(request(from, to, vip, date)).map(|resp| resp.price_cents)
+    }
+
+    #[test]
+    fn test_route_distance_and_calendar_pricing() {
+        // 290.00 base + 5570 km at the long-haul 0.07/km
+        assert_eq!(quote("NYC", "LON", false, None), Ok(67_990));
+        assert_eq!(quote("LON", "NYC", false, None), Ok(67_990));
+        assert_eq!(quote("NYC", "LON", true, None), Ok(57_791));
+        assert_eq!(quote("AAA", "BBB", false, None), Ok(45_000));
+
+        // Wednesday in May: no adjustment; Saturday in May: weekend
+        assert_eq!(quote("NYC", "LON", false, Some("2026-05-13")), Ok(67_990));
+        assert_eq!(quote("NYC", "LON", false, Some("2026-05-16")), Ok(74_789));
+        // Wednesday in July: peak; Wednesday in February: off-peak
+        assert_eq!(quote("NYC", "LON", false, Some("2026-07-15")), Ok(81_588));
+        assert_eq!(quote("NYC", "LON", false, Some("2026-02-11")), Ok(61_191));
+
+        assert!(quote("NYC", "LON", false, Some("2026-02-30")).is_err());
+        assert!(quote("NYC", "NYC", false, None).is_err());
+
+        let mut business = request("NYC", "LON", false, None);
+        business.cabin = Cabin::Business;
+        business.fare_family = FareFamily::Refundable;
+        assert_eq!(handle(business).map(|resp| resp.price_cents), Ok(254_962));
+    }
+
+    #[test]
+    fn test_round_trip_advance_purchase_and_blackouts() {
+        let priced = |departure: Option<&str>, ret: Option<&str>, bought: Option<&str>| {
+            let mut req = request("NYC", "LON", false, departure);
+            req.return_date = ret.map(Into::into);
+            req.purchase_date = bought.map(Into::into);
+            handle(req).map(|resp| resp.price_cents)
+        };
+        // Two Wednesdays in May, both legs 679.90
+        assert_eq!(priced(Some("2026-05-13"), Some("2026-05-20"), None), Ok(135_980));
+        assert!(priced(Some("2026-05-13"), Some("2026-05-12"), None).is_err());
+        assert!(priced(None, Some("2026-05-20"), None).is_err());
+
+        assert_eq!(priced(Some("2026-05-13"), None, Some("2026-03-01")), Ok(61_191));
+        assert_eq!(priced(Some("2026-05-13"), None, Some("2026-04-13")), Ok(67_990));
+        assert_eq!(priced(Some("2026-05-13"), None, Some("2026-05-01")), Ok(78_188));
+        assert_eq!(priced(Some("2026-05-13"), None, Some("2026-05-13")), Ok(88_387));
+        assert!(priced(Some("2026-05-13"), None, Some("2026-05-14")).is_err());
+
+        assert!(priced(Some("2026-12-25"), None, None).is_err());
+        assert!(priced(Some("2026-12-20"), Some("2027-01-01"), None).is_err());
+        assert!(is_blackout("2027-01-01"));
+        assert!(!is_blackout("2027-01-02"));
+    }
+
+    #[test]
+    fn test_batch_picks_cheapest_option() {
+        let batch = handle_batch(alloc::vec![
+            request("NYC", "LON", false, Some("2026-05-16")),
+            request("NYC", "LON", false, Some("2026-12-25")),
+            request("NYC", "LON", false, Some("2026-05-13")),
+            request("NYC", "LON", false, Some("2026-05-20")),
+        ])
+        .unwrap();
+        assert_eq!(batch.cheapest, 2);
+        assert_eq!(batch.options[0].price_cents, Some(74_789));
+        assert!(batch.options[1].error.is_some());
+        assert_eq!(
+            batch.table(),
+            "NYC-LON 2026-05-16 74789;NYC-LON 2026-12-25 -;NYC-LON 2026-05-13 67990;NYC-LON 2026-05-20 67990"
+        );
+
+        assert!(handle_batch(Vec::new()).is_err());
+        assert!(handle_batch(alloc::vec![request("NYC", "LON", false, Some("2027-01-01"))]).is_err());
+        let mut in_euros = request("NYC", "LON", false, None);
+        in_euros.currency = Some("EUR".into());
+        assert!(handle_batch(alloc::vec![request("NYC", "LON", false, None), in_euros]).is_err());
+    }
 
-    // You can add arbitrage checks, signature verification, etc.
-    // As long as it uses only no_std-compatible code
+    #[test]
+    fn test_converts_with_fixed_rates() {
+        let in_currency = |code: &str| {
+            let mut req = request("NYC", "LON", false, None);
+            req.currency = Some(code.into());
+            handle(req).map(|resp| (resp.price_cents, resp.currency))
+        };
+        assert_eq!(in_currency("usd"), Ok((67_990, "USD".into())));
+        assert_eq!(in_currency("EUR"), Ok((62_550, "EUR".into())));
+        assert_eq!(in_currency("GBP"), Ok((53_712, "GBP".into())));
+        assert_eq!(in_currency("JPY"), Ok((101_645, "JPY".into())));
+        assert!(in_currency("XYZ").is_err());
+    }
 
-    Response { price }
+    #[test]
+    fn test_promo_codes_follow_published_rules() {
+        let with_promo = |code: &str, date: Option<&str>| {
+            let mut req = request("NYC", "LON", false, date);
+            req.promo_code = Some(code.into());
+            handle(req).map(|resp| (resp.price_cents, resp.discount_cents, resp.promo_code))
+        };
+        assert_eq!(with_promo("welcome10", None), Ok((61_191, 6_799, Some("WELCOME10".into()))));
+        assert_eq!(with_promo("SUMMER50", Some("2026-05-13")), Ok((62_990, 5_000, Some("SUMMER50".into()))));
+        assert!(with_promo("SUMMER50", Some("2026-09-01")).is_err());
+        assert!(with_promo("SUMMER50", None).is_err());
+        // 679.90 is above LONGHAUL25's 600.00 floor; the fallback 450.00 isn't
+        assert!(with_promo("LONGHAUL25", None).is_ok());
+        let mut short = request("AAA", "BBB", false, None);
+        short.promo_code = Some("LONGHAUL25".into());
+        assert!(handle(short).is_err());
+        assert!(with_promo("NOPE", None).is_err());
+    }
 }
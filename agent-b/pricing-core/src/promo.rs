@@ -0,0 +1,65 @@
+//! Published promo codes
+//!
+//! The table is compiled into the ELF, so a proven price shows the discount was
+//! applied by these rules and no others. Discounts apply to the USD fare after every
+//! other adjustment, before currency conversion.
+
+pub enum Discount {
+    /// Basis points off the fare
+    PercentBps(u64),
+    /// USD cents off the fare
+    FixedCents(u64),
+}
+
+pub struct Promo {
+    pub code: &'static str,
+    pub discount: Discount,
+    /// Smallest USD fare (in cents) the code applies to
+    pub min_fare_cents: u64,
+    /// Last departure day (YYYY-MM-DD) the code is valid for; requires a departure_date
+    pub departs_by: Option<&'static str>,
+}
+
+pub const PROMOS: &[Promo] = &[
+    Promo { code: "WELCOME10", discount: Discount::PercentBps(1_000), min_fare_cents: 0, departs_by: None },
+    Promo { code: "LONGHAUL25", discount: Discount::PercentBps(2_500), min_fare_cents: 60_000, departs_by: None },
+    Promo {
+        code: "SUMMER50",
+        discount: Discount::FixedCents(5_000),
+        min_fare_cents: 30_000,
+        departs_by: Some("2026-08-31"),
+    },
+];
+
+impl Promo {
+    /// USD cents off `fare_cents`, never more than the fare
+    pub fn discount_cents(&self, fare_cents: u64) -> u64 {
+        match self.discount {
+            Discount::PercentBps(bps) => fare_cents * bps / 10_000,
+            Discount::FixedCents(cents) => cents.min(fare_cents),
+        }
+    }
+}
+
+/// The promo for `code` (case-insensitive) if it applies to this fare and departure
+pub fn check(code: &str, fare_cents: u64, departure_date: Option<&str>) -> Result<&'static Promo, alloc::string::String> {
+    let promo = PROMOS
+        .iter()
+        .find(|p| p.code.eq_ignore_ascii_case(code))
+        .ok_or_else(|| alloc::format!("unknown promo code {}", code))?;
+    if fare_cents < promo.min_fare_cents {
+        return Err(alloc::format!(
+            "{} needs a fare of at least {} USD cents",
+            promo.code, promo.min_fare_cents
+        ));
+    }
+    if let Some(last_day) = promo.departs_by {
+        // YYYY-MM-DD compares correctly as a string
+        match departure_date {
+            Some(date) if date <= last_day => {}
+            Some(_) => return Err(alloc::format!("{} is only valid for departures by {}", promo.code, last_day)),
+            None => return Err(alloc::format!("{} needs a departure_date", promo.code)),
+        }
+    }
+    Ok(promo)
+}
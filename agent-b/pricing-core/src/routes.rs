@@ -0,0 +1,44 @@
+//! Route table compiled into the binary (and the SP1 guest)
+//!
+//! Routes are undirected: NYC→LON and LON→NYC share one entry. Codes are IATA city
+//! codes, matching what Agent A sends.
+
+pub struct Route {
+    pub a: &'static str,
+    pub b: &'static str,
+    /// Great-circle distance, rounded
+    pub distance_km: u64,
+    /// Fixed part of the fare (taxes, carrier fees), in cents
+    pub base_fare_cents: u64,
+}
+
+const fn route(a: &'static str, b: &'static str, distance_km: u64, base_fare_cents: u64) -> Route {
+    Route { a, b, distance_km, base_fare_cents }
+}
+
+pub const ROUTES: &[Route] = &[
+    route("NYC", "LON", 5_570, 29_000),
+    route("NYC", "PAR", 5_840, 29_000),
+    route("NYC", "LAX", 3_940, 12_000),
+    route("NYC", "SFO", 4_150, 12_000),
+    route("NYC", "CHI", 1_150, 6_000),
+    route("NYC", "MIA", 1_760, 7_000),
+    route("LON", "PAR", 340, 5_000),
+    route("LON", "FRA", 650, 5_500),
+    route("LON", "DXB", 5_500, 26_000),
+    route("LON", "SIN", 10_880, 38_000),
+    route("LON", "TYO", 9_560, 36_000),
+    route("PAR", "FRA", 480, 5_000),
+    route("LAX", "SFO", 540, 4_500),
+    route("LAX", "TYO", 8_800, 31_000),
+    route("SFO", "TYO", 8_270, 31_000),
+    route("DXB", "SIN", 5_840, 21_000),
+    route("SIN", "TYO", 5_320, 21_000),
+];
+
+/// Table entry for `from`–`to` in either direction
+pub fn find(from: &str, to: &str) -> Option<&'static Route> {
+    ROUTES
+        .iter()
+        .find(|r| (r.a == from && r.b == to) || (r.a == to && r.b == from))
+}
@@ -0,0 +1,85 @@
+//! Version and fingerprint of the pricing rules
+//!
+//! The guest commits both next to every claim, so a verifier can tell which rules produced
+//! a quote and a contract can pin the rule versions it accepts. [`hash`] covers every table
+//! the rules read; [`VERSION`] is bumped by hand whenever a table or the logic around it
+//! changes (the test below fails until it is).
+
+use alloc::string::String;
+use core::fmt::Write;
+use sha2::{Digest, Sha256};
+
+use crate::cancellation::NON_REFUNDABLE_CHANGE_BPS;
+use crate::currency::CURRENCIES;
+use crate::fare::{Cabin, FareFamily};
+use crate::pricing::{
+    ADVANCE_PURCHASE_TIERS, BLACKOUT_DAYS, DISTANCE_BANDS, FALLBACK_FARE_CENTS, OFF_PEAK_SEASON_BPS,
+    PEAK_SEASON_BPS, VIP_BPS, WEEKEND_BPS,
+};
+use crate::promo::{Discount, PROMOS};
+use crate::routes::ROUTES;
+
+pub const VERSION: u64 = 1;
+
+/// Every table, one `name values...` line per entry
+pub fn canonical() -> String {
+    let mut out = String::new();
+    // Writing to a String can't fail
+    let mut line = |args: core::fmt::Arguments| {
+        let _ = out.write_fmt(args);
+        out.push('\n');
+    };
+    for r in ROUTES {
+        line(format_args!("route {} {} {} {}", r.a, r.b, r.distance_km, r.base_fare_cents));
+    }
+    line(format_args!("fallback {}", FALLBACK_FARE_CENTS));
+    for (up_to_km, cents_per_km) in DISTANCE_BANDS {
+        line(format_args!("band {} {}", up_to_km, cents_per_km));
+    }
+    line(format_args!("vip {}", VIP_BPS));
+    line(format_args!("weekend {}", WEEKEND_BPS));
+    line(format_args!("peak {}", PEAK_SEASON_BPS));
+    line(format_args!("off_peak {}", OFF_PEAK_SEASON_BPS));
+    for (days, bps) in ADVANCE_PURCHASE_TIERS {
+        line(format_args!("advance {} {}", days, bps));
+    }
+    for (month, day) in BLACKOUT_DAYS {
+        line(format_args!("blackout {} {}", month, day));
+    }
+    for cabin in [Cabin::Economy, Cabin::Premium, Cabin::Business, Cabin::First] {
+        line(format_args!("cabin {} {}", cabin.as_str(), cabin.bps()));
+    }
+    for family in [FareFamily::NonRefundable, FareFamily::Refundable] {
+        line(format_args!("fare_family {} {}", family.as_str(), family.bps()));
+    }
+    for c in CURRENCIES {
+        line(format_args!("currency {} {} {}", c.code, c.minor_digits, c.per_usd_micros));
+    }
+    for p in PROMOS {
+        let (kind, amount) = match p.discount {
+            Discount::PercentBps(bps) => ("percent", bps),
+            Discount::FixedCents(cents) => ("fixed", cents),
+        };
+        line(format_args!("promo {} {} {} {} {}", p.code, kind, amount, p.min_fare_cents, p.departs_by.unwrap_or("-")));
+    }
+    line(format_args!("change {}", NON_REFUNDABLE_CHANGE_BPS));
+    out
+}
+
+/// SHA-256 of [`canonical`]
+pub fn hash() -> [u8; 32] {
+    Sha256::digest(canonical().as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ruleset_hash_is_pinned_to_version() {
+        // Changed a table? Bump VERSION and pin the new hash.
+        assert_eq!(VERSION, 1);
+        let hex: String = hash().iter().map(|b| alloc::format!("{:02x}", b)).collect();
+        assert_eq!(hex, "6a724270d3656d2412af00cb9fe923483861b82fbe0ef50d702b9b06aa760632");
+    }
+}
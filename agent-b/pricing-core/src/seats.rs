@@ -0,0 +1,104 @@
+//! Seat maps generated per flight
+//!
+//! Which seats are taken is a hash of route, departure day, and seat, so the server and
+//! the guest see the same map for the same flight without any stored state. Nothing is
+//! held between calls: the seat is checked again when the booking is made.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+use crate::fare::Cabin;
+
+/// Cabin layout, front to back: (cabin, first row, last row, seat letters)
+const LAYOUT: &[(Cabin, u32, u32, &str)] = &[
+    (Cabin::First, 1, 2, "AD"),
+    (Cabin::Business, 3, 6, "ACDF"),
+    (Cabin::Premium, 7, 10, "ABCDEF"),
+    (Cabin::Economy, 11, 30, "ABCDEF"),
+];
+
+/// Share of seats already taken, in percent
+const OCCUPIED_PERCENT: u64 = 40;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Seat {
+    /// Row and letter, e.g. "12C"
+    pub seat: String,
+    pub cabin: Cabin,
+    pub available: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SeatMap {
+    pub from: String,
+    pub to: String,
+    pub departure_date: Option<String>,
+    pub seats: Vec<Seat>,
+}
+
+/// Seat map of the `from`→`to` flight departing on `date`; nothing is free on blackout days
+pub fn availability(from: &str, to: &str, date: Option<&str>) -> SeatMap {
+    let blackout = date.is_some_and(crate::pricing::is_blackout);
+    let mut seats = Vec::new();
+    for &(cabin, first_row, last_row, letters) in LAYOUT {
+        for row in first_row..=last_row {
+            for letter in letters.chars() {
+                let seat = alloc::format!("{}{}", row, letter);
+                let available = !blackout && !is_taken(from, to, date, &seat);
+                seats.push(Seat { seat, cabin, available });
+            }
+        }
+    }
+    SeatMap {
+        from: String::from(from),
+        to: String::from(to),
+        departure_date: date.map(String::from),
+        seats,
+    }
+}
+
+/// The seat (normalized, e.g. "12c" → "12C") if it's on the aircraft, in `cabin`, and free
+pub fn check(from: &str, to: &str, date: Option<&str>, seat: &str, cabin: Cabin) -> Result<String, String> {
+    let wanted = seat.trim().to_ascii_uppercase();
+    let found = availability(from, to, date)
+        .seats
+        .into_iter()
+        .find(|s| s.seat == wanted)
+        .ok_or_else(|| alloc::format!("no seat {} on this aircraft", wanted))?;
+    if found.cabin != cabin {
+        return Err(alloc::format!("seat {} is in {}, not {}", found.seat, found.cabin.as_str(), cabin.as_str()));
+    }
+    if !found.available {
+        return Err(alloc::format!("seat {} is taken", found.seat));
+    }
+    Ok(found.seat)
+}
+
+fn is_taken(from: &str, to: &str, date: Option<&str>, seat: &str) -> bool {
+    crate::fnv1a(&[from, to, date.unwrap_or_default(), seat]) % 100 < OCCUPIED_PERCENT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seat_map_is_deterministic_per_flight() {
+        let map = availability("NYC", "LON", Some("2026-11-02"));
+        assert_eq!(map.seats.len(), 2 * 2 + 4 * 4 + 4 * 6 + 20 * 6);
+        assert_eq!(map.seats[0].seat, "1A");
+        assert_eq!(map.seats[0].cabin, Cabin::First);
+        assert_eq!(map.seats, availability("NYC", "LON", Some("2026-11-02")).seats);
+        assert_ne!(map.seats, availability("NYC", "LON", Some("2026-11-03")).seats);
+
+        let free = map.seats.iter().find(|s| s.available && s.cabin == Cabin::Economy).unwrap();
+        let taken = map.seats.iter().find(|s| !s.available && s.cabin == Cabin::Economy).unwrap();
+        let lower = free.seat.to_ascii_lowercase();
+        assert_eq!(check("NYC", "LON", Some("2026-11-02"), &lower, Cabin::Economy), Ok(free.seat.clone()));
+        assert!(check("NYC", "LON", Some("2026-11-02"), &taken.seat, Cabin::Economy).is_err());
+        assert!(check("NYC", "LON", Some("2026-11-02"), &free.seat, Cabin::Business).is_err());
+        assert!(check("NYC", "LON", Some("2026-11-02"), "31A", Cabin::Economy).is_err());
+        assert!(availability("NYC", "LON", Some("2026-12-25")).seats.iter().all(|s| !s.available));
+    }
+}
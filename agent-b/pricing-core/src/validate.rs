@@ -0,0 +1,185 @@
+//! Input checks run before pricing and booking
+//!
+//! Malformed requests fail with a machine-readable [`ErrorCode`] instead of being priced,
+//! booked, and proven. The checks are structural (no regexes, no lookups), so they cost
+//! little inside the guest.
+
+use alloc::string::String;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+const MAX_NAME_CHARS: usize = 100;
+const MAX_EMAIL_LEN: usize = 254;
+const MAX_EMAIL_LOCAL_LEN: usize = 64;
+const MAX_DOMAIN_LABEL_LEN: usize = 63;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Not three uppercase letters
+    InvalidAirportCode,
+    SameOriginAndDestination,
+    InvalidEmail,
+    InvalidName,
+    /// Not 32 bytes of hex
+    InvalidPiiSalt,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidAirportCode => "invalid_airport_code",
+            ErrorCode::SameOriginAndDestination => "same_origin_and_destination",
+            ErrorCode::InvalidEmail => "invalid_email",
+            ErrorCode::InvalidName => "invalid_name",
+            ErrorCode::InvalidPiiSalt => "invalid_pii_salt",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    pub code: ErrorCode,
+    /// Request field at fault
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+fn invalid(code: ErrorCode, field: &str, message: String) -> ValidationError {
+    ValidationError { code, field: String::from(field), message }
+}
+
+/// IATA-style city or airport code: exactly three uppercase ASCII letters
+pub fn airport_code(field: &str, code: &str) -> Result<(), ValidationError> {
+    if code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase()) {
+        Ok(())
+    } else {
+        Err(invalid(
+            ErrorCode::InvalidAirportCode,
+            field,
+            alloc::format!("{:?} is not a three-letter code like NYC", code),
+        ))
+    }
+}
+
+/// Both codes valid, and different
+pub fn route(from: &str, to: &str) -> Result<(), ValidationError> {
+    airport_code("from", from)?;
+    airport_code("to", to)?;
+    if from == to {
+        return Err(invalid(
+            ErrorCode::SameOriginAndDestination,
+            "to",
+            alloc::format!("from and to are both {}", from),
+        ));
+    }
+    Ok(())
+}
+
+/// `local@domain.tld` with the structural limits of RFC 5321 (no quoted local parts)
+pub fn email(field: &str, email: &str) -> Result<(), ValidationError> {
+    let reject = |why: &str| Err(invalid(ErrorCode::InvalidEmail, field, alloc::format!("{:?} {}", email, why)));
+    if email.len() > MAX_EMAIL_LEN {
+        return reject("is too long");
+    }
+    let Some((local, domain)) = email.split_once('@') else {
+        return reject("has no @");
+    };
+
+    let local_ok = !local.is_empty()
+        && local.len() <= MAX_EMAIL_LOCAL_LEN
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && local.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-/=?^_`{|}~.".contains(&b));
+    if !local_ok {
+        return reject("has an invalid part before the @");
+    }
+
+    let mut labels = domain.split('.').peekable();
+    let mut count = 0;
+    while let Some(label) = labels.next() {
+        count += 1;
+        let label_ok = !label.is_empty()
+            && label.len() <= MAX_DOMAIN_LABEL_LEN
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+        // Top-level domain: letters only, at least two
+        let is_tld = labels.peek().is_none();
+        if !label_ok || (is_tld && (label.len() < 2 || !label.bytes().all(|b| b.is_ascii_alphabetic()))) {
+            return reject("has an invalid domain");
+        }
+    }
+    if count < 2 {
+        return reject("has no top-level domain");
+    }
+    Ok(())
+}
+
+/// Passenger name: 1–100 characters of letters, spaces, hyphens, apostrophes, and periods,
+/// with at least one letter
+pub fn name(field: &str, name: &str) -> Result<(), ValidationError> {
+    let trimmed = name.trim();
+    let chars = trimmed.chars().count();
+    let charset_ok = trimmed
+        .chars()
+        .all(|c| c.is_alphabetic() || matches!(c, ' ' | '-' | '\'' | '.' | '’'));
+    if chars == 0 || chars > MAX_NAME_CHARS || !charset_ok || !trimmed.chars().any(char::is_alphabetic) {
+        return Err(invalid(
+            ErrorCode::InvalidName,
+            field,
+            alloc::format!("must be 1–{} letters, spaces, hyphens, apostrophes, or periods", MAX_NAME_CHARS),
+        ));
+    }
+    Ok(())
+}
+
+/// Salt of a booking's passenger commitment: 64 hex digits, optionally 0x-prefixed
+pub fn pii_salt(field: &str, salt: &str) -> Result<(), ValidationError> {
+    let digits = salt.strip_prefix("0x").unwrap_or(salt);
+    if digits.len() == 64 && digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(invalid(ErrorCode::InvalidPiiSalt, field, String::from("must be 32 bytes of hex")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_malformed_booking_fields() {
+        assert!(route("NYC", "LON").is_ok());
+        assert_eq!(route("nyc", "LON").unwrap_err().code, ErrorCode::InvalidAirportCode);
+        assert_eq!(route("NYC", "London").unwrap_err().field, "to");
+        assert_eq!(route("NYC", "NYC").unwrap_err().code, ErrorCode::SameOriginAndDestination);
+
+        for ok in ["jane@example.com", "jane.doe+trip@mail.example.co.uk"] {
+            assert!(email("passenger_email", ok).is_ok(), "{}", ok);
+        }
+        for bad in ["", "jane", "jane@", "@example.com", "jane@example", "jane@@example.com", "ja..ne@example.com", "jane@-example.com", "jane@example.c0m"] {
+            assert_eq!(email("passenger_email", bad).unwrap_err().code, ErrorCode::InvalidEmail, "{}", bad);
+        }
+
+        for ok in ["Jane Doe", "Seán O'Brien", "Anne-Marie St. John", "李小龍"] {
+            assert!(name("passenger_name", ok).is_ok(), "{}", ok);
+        }
+        for bad in ["", "   ", "---", "Robert'); DROP TABLE", "Jane\u{0}Doe"] {
+            assert_eq!(name("passenger_name", bad).unwrap_err().code, ErrorCode::InvalidName, "{}", bad);
+        }
+
+        assert!(pii_salt("pii_salt", &"ab".repeat(32)).is_ok());
+        assert!(pii_salt("pii_salt", &alloc::format!("0x{}", "AB".repeat(32))).is_ok());
+        for bad in ["", "0x", "abcd", &"zz".repeat(32)] {
+            assert_eq!(pii_salt("pii_salt", bad).unwrap_err().code, ErrorCode::InvalidPiiSalt, "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Proves bookings and cancellations only (`BookFlight`, `CancelBooking`)
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    agent_b_program::run(&["book", "cancel"]);
+}
@@ -0,0 +1,7 @@
+//! Proves single quotes only (`GetPrice`)
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    agent_b_program::run(&["price"]);
+}
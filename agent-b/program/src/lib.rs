@@ -1,10 +1,81 @@
-#![no_main]
-sp1_zkvm::entrypoint!(main);
+//! Agent B guest programs
+//!
+//! Every binary runs [`run`] with the operations it proves: `agent-b-program` takes all of
+//! them, and the binaries in `src/bin/` one family each. Each is registered with the
+//! attester under its own program_id, so a proof also says which family it came from.
+//!
+//! The claim is committed inside [`PublicValues`], next to the SHA-256 of the input bytes,
+//! so a proof of one request (say, a cheaper route) can't be presented as a proof of another,
+//! and the version and hash of the pricing rules ([`pricing_core::ruleset`]) it ran.
+//!
+//! Booking claims carry no passenger details, only a salted [`PiiCommitment`] of them.
 
-use pricing_core::{handle_call, RpcCall, RpcResult};
+use pricing_core::{handle_call, ruleset, RpcCall, RpcResult};
+use zk_protocol::abi::{BookingClaim, CancellationClaim, PriceComparisonClaim, PricingClaim, PublicValues};
+use zk_protocol::PiiCommitment;
 
-pub fn main() {
-    let call: RpcCall = sp1_zkvm::io::read();
-    let result: RpcResult = handle_call(call);
-    sp1_zkvm::io::commit(&result);
-}
\ No newline at end of file
+/// Read the call, refuse it unless its [`RpcCall::endpoint`] is in `endpoints`, and commit
+/// the ABI-encoded claim with the hash of the input it came from and the ruleset it ran
+pub fn run(endpoints: &[&str]) {
+    // Read raw so the hash covers exactly the bytes the attester was given
+    let input = sp1_zkvm::io::read_vec();
+    let call: RpcCall = bincode::deserialize(&input).expect("input is not a bincode-encoded RpcCall");
+    assert!(
+        endpoints.contains(&call.endpoint()),
+        "this program doesn't prove {} calls",
+        call.endpoint()
+    );
+    let result: RpcResult = handle_call(call.clone());
+    let claim = claim(call, result);
+    sp1_zkvm::io::commit_slice(&PublicValues::new(&input, ruleset::VERSION, ruleset::hash(), claim).abi_encode());
+}
+
+/// ABI-encoded claim for the on-chain contract to decode; panics if the call failed, so
+/// nothing is proven for it
+fn claim(call: RpcCall, result: RpcResult) -> Vec<u8> {
+    match (call, result) {
+        (RpcCall::GetPrice(req), RpcResult::Price(resp)) => PricingClaim {
+            from: req.from,
+            to: req.to,
+            price_cents: resp.price_cents,
+            vip: req.vip,
+            quote_id: req.quote_id.unwrap_or_default(),
+            currency: resp.currency,
+            promo_code: resp.promo_code.unwrap_or_default(),
+            departure_date: req.departure_date.unwrap_or_default(),
+            return_date: req.return_date.unwrap_or_default(),
+        }
+        .abi_encode(),
+        (RpcCall::GetPriceBatch(_), RpcResult::PriceBatch(resp)) => PriceComparisonClaim {
+            option_count: resp.options.len() as u64,
+            cheapest_index: resp.cheapest as u64,
+            cheapest_price_cents: resp.options[resp.cheapest].price_cents.unwrap_or_default(),
+            currency: resp.currency.clone(),
+            options: resp.table(),
+        }
+        .abi_encode(),
+        (RpcCall::BookFlight(req), RpcResult::Booking(resp)) => {
+            let salt = req.pii_salt.as_deref().expect("booking proofs need a pii_salt");
+            let salt = PiiCommitment::parse_salt(salt).expect("pii_salt is not 32 bytes of hex");
+            BookingClaim {
+                booking_id: resp.booking_id,
+                status: resp.status,
+                confirmation_code: resp.confirmation_code,
+                seat: resp.seat.unwrap_or_default(),
+                passenger_commitment: PiiCommitment::new(&salt, &req.passenger_name, &req.passenger_email).0,
+            }
+            .abi_encode()
+        }
+        (RpcCall::CancelBooking(_), RpcResult::Cancellation(resp)) => CancellationClaim {
+            booking_id: resp.booking_id,
+            fare_cents: resp.fare_cents,
+            fee_cents: resp.fee_cents,
+            refund_cents: resp.refund_cents,
+            currency: resp.currency,
+        }
+        .abi_encode(),
+        (_, RpcResult::Invalid(e)) => panic!("{}", e),
+        (_, RpcResult::Error(msg)) => panic!("{}", msg),
+        _ => panic!("result does not match call"),
+    }
+}
@@ -0,0 +1,6 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+pub fn main() {
+    agent_b_program::run(&["price", "price_batch", "book", "cancel"]);
+}
@@ -0,0 +1,281 @@
+//! Booking providers for /book
+//!
+//! `booking_provider` selects who fulfils a booking: `deterministic` (pricing-core's booking
+//! engine, the logic the guest proves), `rest` (an Amadeus/Duffel-style REST API at
+//! `booking_api_url`), or `mock` (canned confirmations, no network). It defaults to `rest`
+//! when `booking_api_url` is set and `deterministic` otherwise.
+//!
+//! Any other provider runs behind a circuit breaker. When it fails, times out, or its circuit
+//! is open, the deterministic engine books instead, and the response says so.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use pricing_core::booking;
+
+use telemetry::correlation::WithRequestId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Deterministic,
+    Rest,
+    Mock,
+}
+
+#[async_trait]
+pub trait BookingProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Book `req`, which has already passed pricing-core's validation and seat check
+    async fn book(&self, req: &booking::Request) -> Result<booking::Response, String>;
+}
+
+/// pricing-core's engine; the same booking the guest would prove
+pub struct DeterministicProvider;
+
+#[async_trait]
+impl BookingProvider for DeterministicProvider {
+    fn name(&self) -> &'static str {
+        "deterministic"
+    }
+
+    async fn book(&self, req: &booking::Request) -> Result<booking::Response, String> {
+        booking::handle(req.clone())
+    }
+}
+
+/// Confirms everything with `MOCK` references; for running against no real API
+pub struct MockProvider;
+
+#[async_trait]
+impl BookingProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn book(&self, req: &booking::Request) -> Result<booking::Response, String> {
+        let resp = booking::handle(req.clone())?;
+        Ok(booking::Response {
+            booking_id: format!("MK{}", resp.booking_id.trim_start_matches("BK")),
+            confirmation_code: format!("MOCK{}", resp.confirmation_code.trim_start_matches("CONF")),
+            ..resp
+        })
+    }
+}
+
+/// REST booking API: POSTs the booking as JSON and maps the reply with [`map_response`]
+pub struct RestProvider {
+    client: reqwest::Client,
+    url: String,
+    /// Sent as `Authorization: Bearer`
+    api_key: Option<String>,
+    /// Further attempts after a transport error or 5xx
+    retries: u32,
+}
+
+#[derive(Serialize)]
+struct ApiRequest<'a> {
+    from: &'a str,
+    to: &'a str,
+    passenger_name: &'a str,
+    passenger_email: &'a str,
+    cabin: pricing_core::fare::Cabin,
+    fare_family: pricing_core::fare::FareFamily,
+    departure_date: Option<&'a str>,
+    seat: Option<String>,
+}
+
+impl RestProvider {
+    pub fn new(url: String, api_key: Option<String>, timeout: Duration, retries: u32) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to build booking API client: {}", e))?;
+        Ok(Self { client, url, api_key, retries })
+    }
+
+    /// One attempt; `Err((retryable, message))`
+    async fn attempt(&self, body: &ApiRequest<'_>) -> Result<Value, (bool, String)> {
+        let mut request = self.client.post(&self.url).with_request_id().json(body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| (true, format!("HTTP request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err((status.is_server_error(), format!("Booking API returned {}: {}", status, text)));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| (false, format!("Failed to parse API response: {}", e)))
+    }
+}
+
+#[async_trait]
+impl BookingProvider for RestProvider {
+    fn name(&self) -> &'static str {
+        "rest"
+    }
+
+    async fn book(&self, req: &booking::Request) -> Result<booking::Response, String> {
+        let seat = req.seat.as_deref().map(|s| s.trim().to_ascii_uppercase());
+        let body = ApiRequest {
+            from: &req.from,
+            to: &req.to,
+            passenger_name: &req.passenger_name,
+            passenger_email: &req.passenger_email,
+            cabin: req.cabin,
+            fare_family: req.fare_family,
+            departure_date: req.departure_date.as_deref(),
+            seat: seat.clone(),
+        };
+
+        let mut delay = Duration::from_millis(200);
+        let mut attempt = 0;
+        let reply = loop {
+            match self.attempt(&body).await {
+                Ok(reply) => break reply,
+                Err((true, e)) if attempt < self.retries => {
+                    tracing::warn!("⚠ Booking API attempt {} failed, retrying in {}ms: {}", attempt + 1, delay.as_millis(), e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err((_, e)) => return Err(e),
+            }
+        };
+        let mut resp = map_response(&reply)?;
+        resp.seat = seat;
+        Ok(resp)
+    }
+}
+
+/// Booking out of a provider's reply. Accepts the flat `{booking_id, status,
+/// confirmation_code}`, Duffel's `{data: {id, booking_reference}}`, and Amadeus's
+/// `{data: {id, associatedRecords: [{reference}]}}`; status defaults to "confirmed".
+pub fn map_response(reply: &Value) -> Result<booking::Response, String> {
+    let body = reply.get("data").filter(|d| d.is_object()).unwrap_or(reply);
+    let text = |value: &Value| value.as_str().map(String::from);
+    let booking_id = body
+        .get("booking_id")
+        .or_else(|| body.get("id"))
+        .and_then(text)
+        .ok_or("Booking API reply has no booking_id or id")?;
+    let confirmation_code = body
+        .get("confirmation_code")
+        .or_else(|| body.get("booking_reference"))
+        .or_else(|| body.pointer("/associatedRecords/0/reference"))
+        .and_then(text)
+        .ok_or("Booking API reply has no confirmation_code, booking_reference, or associatedRecords reference")?;
+    let status = body.get("status").and_then(text).unwrap_or_else(|| "confirmed".to_string());
+    Ok(booking::Response { booking_id, status, confirmation_code, seat: None })
+}
+
+/// Opens after `threshold` consecutive failures; after `cooldown` calls go through again,
+/// and the first failure re-opens it while a success closes it
+struct Breaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<(u32, Option<Instant>)>,
+}
+
+impl Breaker {
+    fn is_open(&self) -> bool {
+        let (_, opened_at) = *self.state.lock().unwrap();
+        opened_at.is_some_and(|at| at.elapsed() < self.cooldown)
+    }
+
+    fn record(&self, ok: bool) {
+        let mut state = self.state.lock().unwrap();
+        if ok {
+            *state = (0, None);
+            return;
+        }
+        state.0 += 1;
+        if state.0 >= self.threshold {
+            state.1 = Some(Instant::now());
+        }
+    }
+}
+
+/// Who fulfilled a booking
+pub struct Fulfilment {
+    pub response: booking::Response,
+    pub provider: &'static str,
+    /// Why the configured provider didn't book it, when the deterministic engine did instead
+    pub fallback_reason: Option<String>,
+}
+
+/// The configured provider, with the deterministic engine behind it
+pub struct Booker {
+    provider: Box<dyn BookingProvider>,
+    breaker: Breaker,
+}
+
+impl Booker {
+    pub fn new(provider: Box<dyn BookingProvider>, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            provider,
+            breaker: Breaker { threshold: threshold.max(1), cooldown, state: Mutex::new((0, None)) },
+        }
+    }
+
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    pub async fn book(&self, req: &booking::Request) -> Result<Fulfilment, String> {
+        if self.provider.name() == DeterministicProvider.name() {
+            let response = DeterministicProvider.book(req).await?;
+            return Ok(Fulfilment { response, provider: DeterministicProvider.name(), fallback_reason: None });
+        }
+
+        let reason = if self.breaker.is_open() {
+            format!("{} circuit open", self.provider.name())
+        } else {
+            match self.provider.book(req).await {
+                Ok(response) => {
+                    self.breaker.record(true);
+                    return Ok(Fulfilment { response, provider: self.provider.name(), fallback_reason: None });
+                }
+                Err(e) => {
+                    self.breaker.record(false);
+                    e
+                }
+            }
+        };
+        tracing::warn!("⚠ {} booking failed ({}), using deterministic engine", self.provider.name(), reason);
+        let response = DeterministicProvider.book(req).await?;
+        Ok(Fulfilment { response, provider: DeterministicProvider.name(), fallback_reason: Some(reason) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_maps_flat_duffel_and_amadeus_replies() {
+        let flat = map_response(&json!({"booking_id": "B1", "status": "pending", "confirmation_code": "C1"})).unwrap();
+        assert_eq!((flat.booking_id.as_str(), flat.status.as_str(), flat.confirmation_code.as_str()), ("B1", "pending", "C1"));
+
+        let duffel = map_response(&json!({"data": {"id": "ord_1", "booking_reference": "RZPVZE"}})).unwrap();
+        assert_eq!((duffel.booking_id.as_str(), duffel.status.as_str(), duffel.confirmation_code.as_str()), ("ord_1", "confirmed", "RZPVZE"));
+
+        let amadeus = map_response(&json!({"data": {"id": "eJzTd9f3", "associatedRecords": [{"reference": "QVWRHT"}]}})).unwrap();
+        assert_eq!(amadeus.confirmation_code, "QVWRHT");
+
+        assert!(map_response(&json!({"data": {"id": "ord_1"}})).is_err());
+        assert!(map_response(&json!({"errors": []})).is_err());
+    }
+}
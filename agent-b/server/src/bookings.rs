@@ -0,0 +1,137 @@
+//! Bookings made through /book, kept so they can be looked up, changed, and cancelled
+//!
+//! Stored in SQLite at `agent_b_booking_db`, or an in-memory database if unset. Each
+//! booking is kept as JSON next to its id and status; lookups are by id only.
+//!
+//! `booking_keys` maps idempotency keys (a client's `Idempotency-Key`, or the passenger,
+//! route, and day a booking was made for) to the booking they created, so a retried /book
+//! returns the original confirmation instead of booking again.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use pricing_core::fare::{Cabin, FareFamily};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Booking {
+    pub booking_id: String,
+    /// "confirmed" or "cancelled"
+    pub status: String,
+    pub confirmation_code: String,
+    pub from: String,
+    pub to: String,
+    pub passenger_name: String,
+    pub passenger_email: String,
+    pub cabin: Cabin,
+    pub fare_family: FareFamily,
+    pub departure_date: Option<String>,
+    pub seat: Option<String>,
+    /// Booking provider that fulfilled it; unset on bookings made before providers were tracked
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Salt of the passenger commitment booking proofs carry; unset on bookings made before
+    /// proofs hid passenger details
+    #[serde(default)]
+    pub pii_salt: Option<String>,
+    /// Fare paid, in `currency`'s minor units; cancellation and change fees are shares of it
+    pub price_cents: u64,
+    pub currency: String,
+    /// Total charged for changes so far, in `currency`'s minor units
+    pub change_fees_cents: u64,
+    /// Set once cancelled
+    pub cancellation: Option<Cancellation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cancellation {
+    pub fee_cents: u64,
+    pub refund_cents: u64,
+    /// YYYY-MM-DD
+    pub cancelled_on: String,
+}
+
+pub struct BookingStore {
+    conn: Mutex<Connection>,
+}
+
+impl BookingStore {
+    pub fn open(path: Option<&str>) -> Result<Self, String> {
+        let conn = match path {
+            Some(path) => Connection::open(path),
+            None => Connection::open_in_memory(),
+        }
+        .map_err(|e| format!("Failed to open booking database: {}", e))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS bookings (
+                booking_id TEXT PRIMARY KEY,
+                status     TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                booking    TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS booking_keys (
+                key           TEXT PRIMARY KEY,
+                booking_id    TEXT NOT NULL,
+                duplicate_key TEXT NOT NULL,
+                created_at    INTEGER NOT NULL
+            );",
+        )
+        .map_err(|e| format!("Failed to create bookings table: {}", e))?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Insert or overwrite `booking`; booking ids are deterministic, so rebooking the same
+    /// passenger on the same flight replaces the earlier record
+    pub fn save(&self, booking: &Booking, now: u64) -> Result<(), String> {
+        let json = serde_json::to_string(booking).map_err(|e| e.to_string())?;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO bookings (booking_id, status, updated_at, booking) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (booking_id) DO UPDATE SET status = ?2, updated_at = ?3, booking = ?4",
+                params![booking.booking_id, booking.status, now as i64, json],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to save booking {}: {}", booking.booking_id, e))
+    }
+
+    pub fn get(&self, booking_id: &str) -> Result<Option<Booking>, String> {
+        let json: Option<String> = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT booking FROM bookings WHERE booking_id = ?1", [booking_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| format!("Failed to read booking {}: {}", booking_id, e))?;
+        json.map(|json| serde_json::from_str(&json).map_err(|e| e.to_string())).transpose()
+    }
+
+    /// Booking created under `key`, with the `booking::duplicate_key` of the request that made it
+    pub fn find_key(&self, key: &str) -> Result<Option<(String, String)>, String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT booking_id, duplicate_key FROM booking_keys WHERE key = ?1",
+                [key],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read booking key: {}", e))
+    }
+
+    /// Point `key` at `booking_id`, replacing whatever it pointed at
+    pub fn save_key(&self, key: &str, booking_id: &str, duplicate_key: &str, now: u64) -> Result<(), String> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO booking_keys (key, booking_id, duplicate_key, created_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (key) DO UPDATE SET booking_id = ?2, duplicate_key = ?3, created_at = ?4",
+                params![key, booking_id, duplicate_key, now as i64],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to save booking key: {}", e))
+    }
+}
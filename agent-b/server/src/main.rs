@@ -1,40 +1,65 @@
 use axum::{
-    extract::State,
-    routing::post,
+    extract::{Path, Query, State},
+    body::Body,
+    http::{header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE}, HeaderMap, HeaderName, StatusCode},
+    response::IntoResponse,
+    middleware,
+    routing::{get, post},
     Router, Json,
 };
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use pricing_core::{pricing, booking};
+use std::time::Duration;
+use tower_http::trace::TraceLayer;
+use pricing_core::{pricing, booking, cancellation, seats, RpcCall};
+use pricing_core::fare::{Cabin, FareFamily};
+use pricing_core::validate::ValidationError;
+use zk_protocol::{AgentIdentity, AgentResponse, PiiCommitment, SignedAgentResponse};
+use agent_config::Validate;
+use agent_tls::TlsConfig;
+use telemetry::correlation;
 
+mod booking_provider;
+mod bookings;
+mod registration;
+mod tap;
+mod tools;
 mod zk_adapter;
 
+use booking_provider::{Booker, BookingProvider, DeterministicProvider, MockProvider, ProviderKind, RestProvider};
+use bookings::{Booking, BookingStore};
+use registration::{Program, Registration, PROGRAMS};
+
 #[derive(Deserialize)]
 struct PriceRequest {
     from: String,
     to: String,
+    /// Optional for the get-ticket-price tool's sake
+    #[serde(default)]
     vip: bool,
+    /// YYYY-MM-DD; enables weekend, seasonal, and advance-purchase pricing
+    #[serde(default)]
+    departure_date: Option<String>,
+    /// YYYY-MM-DD; quotes the round trip
+    #[serde(default)]
+    return_date: Option<String>,
+    /// ISO 4217 code (USD, EUR, GBP, JPY); USD if unset
+    #[serde(default)]
+    currency: Option<String>,
+    #[serde(default)]
+    cabin: Cabin,
+    #[serde(default)]
+    fare_family: FareFamily,
+    #[serde(default)]
+    promo_code: Option<String>,
 }
 
-#[derive(Serialize)]
-struct PriceResponse {
-    // Agent-specific data
-    price: f64,
-    // ZK verification metadata
-    program_id: String,
-    elf_hash: String,
-}
-
-#[derive(Serialize)]
-struct BookResponse {
-    // Agent-specific data
-    booking_id: String,
-    status: String,
-    confirmation_code: String,
-    // ZK verification metadata
-    program_id: String,
-    elf_hash: String,
+/// Options to compare, each priced as by /price
+#[derive(Deserialize)]
+struct PriceBatchRequest {
+    /// At most `pricing::MAX_BATCH_OPTIONS`, all in the same currency
+    options: Vec<PriceRequest>,
 }
 
 #[derive(Deserialize)]
@@ -43,234 +68,1012 @@ struct BookRequest {
     to: String,
     passenger_name: String,
     passenger_email: String,
+    #[serde(default)]
+    cabin: Cabin,
+    #[serde(default)]
+    fare_family: FareFamily,
+    /// YYYY-MM-DD; the flight `seat` is checked against
+    #[serde(default)]
+    departure_date: Option<String>,
+    /// Seat from GET /seats, e.g. "12C"
+    #[serde(default)]
+    seat: Option<String>,
+    /// Fare paid, from /price; the list fare is recorded if unset
+    #[serde(default)]
+    price_cents: Option<u64>,
+    #[serde(default)]
+    currency: Option<String>,
+    /// Salt for the passenger commitment (32 bytes of hex); a fresh one if unset
+    #[serde(default)]
+    pii_salt: Option<String>,
 }
 
-#[derive(Clone)]
-struct AppState {
-    program_id: String,
-    elf_hash: String,
+/// Fields of a booking that can be changed; unset fields keep their current value
+#[derive(Deserialize)]
+struct ChangeRequest {
+    #[serde(default)]
+    departure_date: Option<String>,
+    #[serde(default)]
+    seat: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SeatsQuery {
+    from: String,
+    to: String,
+    #[serde(default)]
+    departure_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SelectSeatRequest {
+    from: String,
+    to: String,
+    #[serde(default)]
+    departure_date: Option<String>,
+    #[serde(default)]
+    cabin: Cabin,
+    seat: String,
+}
+
+/// Startup configuration: AGENT_B_CONFIG (default `agent-b.toml`, optional) + env overrides
+#[derive(Deserialize)]
+struct Config {
+    #[serde(default = "default_attester_url")]
+    attester_url: String,
+    /// Who fulfils bookings (see booking_provider.rs); `rest` if booking_api_url is set,
+    /// else `deterministic`
+    booking_provider: Option<ProviderKind>,
+    /// External booking API, for the `rest` provider
     booking_api_url: Option<String>,
+    /// Bearer token for the booking API
+    booking_api_key: Option<String>,
+    #[serde(default = "default_booking_api_timeout_secs")]
+    booking_api_timeout_secs: u64,
+    /// Further attempts after a transport error or 5xx
+    #[serde(default = "default_booking_api_retries")]
+    booking_api_retries: u32,
+    /// Consecutive failed bookings before the provider is skipped for the cooldown
+    #[serde(default = "default_booking_api_breaker_threshold")]
+    booking_api_breaker_threshold: u32,
+    #[serde(default = "default_booking_api_breaker_cooldown_secs")]
+    booking_api_breaker_cooldown_secs: u64,
+    /// 32-byte hex Ed25519 seed (ephemeral key if unset)
+    agent_b_signing_key: Option<String>,
+    /// How long a price quote stays valid
+    #[serde(default = "default_quote_ttl_secs")]
+    quote_ttl_secs: u64,
+    /// did:keys whose TAP signatures are accepted, comma-separated (unset = not required)
+    agent_b_tap_trusted_keys: Option<String>,
+    /// JWKS of further trusted TAP keys, e.g. Agent A's /.well-known/tap-jwks.json
+    agent_b_tap_jwks_url: Option<String>,
+    /// How often to re-fetch the TAP JWKS
+    #[serde(default = "default_tap_jwks_refresh_secs")]
+    agent_b_tap_jwks_refresh_secs: u64,
+    /// SQLite file for bookings (unset keeps them in memory)
+    agent_b_booking_db: Option<String>,
+    /// Bearer token for /admin routes (unset disables them)
+    agent_b_admin_token: Option<String>,
+    /// How often to check that the attester still has each program registered
+    #[serde(default = "default_attester_check_secs")]
+    attester_check_secs: u64,
+    /// TLS_CERT / TLS_KEY / TLS_CLIENT_CA for the listener; TLS_CA / TLS_CLIENT_CERT /
+    /// TLS_CLIENT_KEY for calls to the attester and Agent A's JWKS
+    #[serde(flatten)]
+    tls: TlsConfig,
+}
+
+fn default_attester_url() -> String {
+    "http://localhost:8000".to_string()
+}
+
+fn default_quote_ttl_secs() -> u64 {
+    900
+}
+
+fn default_booking_api_timeout_secs() -> u64 {
+    10
+}
+
+fn default_booking_api_retries() -> u32 {
+    2
+}
+
+fn default_booking_api_breaker_threshold() -> u32 {
+    3
+}
+
+fn default_booking_api_breaker_cooldown_secs() -> u64 {
+    30
+}
+
+fn default_attester_check_secs() -> u64 {
+    30
+}
+
+fn default_tap_jwks_refresh_secs() -> u64 {
+    // Well inside Agent A's default key grace period
+    300
+}
+
+impl Config {
+    fn load() -> Result<Self, agent_config::ConfigError> {
+        agent_config::load("AGENT_B_CONFIG", "agent-b.toml")
+    }
+
+    fn booking_provider(&self) -> ProviderKind {
+        self.booking_provider.unwrap_or(match self.booking_api_url {
+            Some(_) => ProviderKind::Rest,
+            None => ProviderKind::Deterministic,
+        })
+    }
+
+    fn booker(&self) -> Result<Booker, String> {
+        let provider: Box<dyn BookingProvider> = match self.booking_provider() {
+            ProviderKind::Deterministic => Box::new(DeterministicProvider),
+            ProviderKind::Mock => Box::new(MockProvider),
+            ProviderKind::Rest => Box::new(RestProvider::new(
+                self.booking_api_url.clone().unwrap_or_default(),
+                self.booking_api_key.clone(),
+                Duration::from_secs(self.booking_api_timeout_secs),
+                self.booking_api_retries,
+            )?),
+        };
+        Ok(Booker::new(
+            provider,
+            self.booking_api_breaker_threshold,
+            Duration::from_secs(self.booking_api_breaker_cooldown_secs),
+        ))
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self) -> Result<(), agent_config::ConfigError> {
+        agent_config::check_url("attester_url", &self.attester_url)?;
+        self.tls.validate().map_err(|e| agent_config::invalid("tls", e.to_string()))?;
+        if let Some(url) = &self.booking_api_url {
+            agent_config::check_url("booking_api_url", url)?;
+        }
+        if self.booking_provider() == ProviderKind::Rest && self.booking_api_url.is_none() {
+            return Err(agent_config::invalid("booking_api_url", "required by booking_provider = \"rest\""));
+        }
+        if self.booking_api_timeout_secs == 0 {
+            return Err(agent_config::invalid("booking_api_timeout_secs", "must be at least 1"));
+        }
+        if let Some(seed) = &self.agent_b_signing_key {
+            agent_config::check_hex("agent_b_signing_key", seed, 32)?;
+        }
+        if let Some(keys) = &self.agent_b_tap_trusted_keys {
+            tap::TapConfig::parse(keys)
+                .map_err(|e| agent_config::invalid("agent_b_tap_trusted_keys", e.to_string()))?;
+        }
+        if let Some(url) = &self.agent_b_tap_jwks_url {
+            agent_config::check_url("agent_b_tap_jwks_url", url)?;
+        }
+        if self.agent_b_tap_jwks_refresh_secs == 0 {
+            return Err(agent_config::invalid("agent_b_tap_jwks_refresh_secs", "must be at least 1"));
+        }
+        if self.attester_check_secs == 0 {
+            return Err(agent_config::invalid("attester_check_secs", "must be at least 1"));
+        }
+        if self.agent_b_admin_token.as_deref() == Some("") {
+            return Err(agent_config::invalid("agent_b_admin_token", "must not be empty"));
+        }
+        if self.quote_ttl_secs == 0 {
+            return Err(agent_config::invalid("quote_ttl_secs", "must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+struct AppState {
+    // Guest programs by name, each with its program_id once the attester has the ELF
+    programs: BTreeMap<&'static str, Arc<Registration>>,
+    // Fulfils /book, falling back to pricing-core's engine
+    booker: Booker,
+    quote_ttl_secs: u64,
+    bookings: BookingStore,
+    // Signs every response so Agent A can detect a swapped program_id
+    identity: AgentIdentity,
+    admin_token: Option<String>,
+}
+
+impl AppState {
+    /// Registered program that proves `endpoint`: `requested` if set, else the most
+    /// specialised one (first in `PROGRAMS`)
+    fn program_for(
+        &self,
+        endpoint: &str,
+        requested: Option<&str>,
+    ) -> Result<(&'static str, Program), (StatusCode, String)> {
+        if let Some(name) = requested {
+            let registration = self
+                .programs
+                .get(name)
+                .ok_or_else(|| (StatusCode::UNPROCESSABLE_ENTITY, format!("Unknown program {}; see GET /program", name)))?;
+            if !registration.spec.endpoints.contains(&endpoint) {
+                return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("Program {} doesn't prove {} calls", name, endpoint)));
+            }
+            let program = registration.program().ok_or_else(|| {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("Program {} not registered with the attester yet; see GET /ready", name))
+            })?;
+            return Ok((registration.spec.name, program));
+        }
+        PROGRAMS
+            .iter()
+            .filter(|spec| spec.endpoints.contains(&endpoint))
+            .find_map(|spec| Some((spec.name, self.programs.get(spec.name)?.program()?)))
+            .ok_or_else(|| {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("No program for {} registered with the attester yet; see GET /ready", endpoint))
+            })
+    }
+
+    /// Wrap agent-specific data with ZK metadata and sign it; the program_id is the one that
+    /// proves `endpoint`, named in the data as `program`
+    fn signed_response(
+        &self,
+        endpoint: &str,
+        mut data: serde_json::Value,
+    ) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+        // Without a program_id the response couldn't be proven against, so don't serve it
+        let (name, program) = self.program_for(endpoint, None)?;
+        if let Some(fields) = data.as_object_mut() {
+            fields.insert("program".to_string(), serde_json::json!(name));
+        }
+        let response = AgentResponse {
+            data,
+            program_id: program.program_id,
+            elf_hash: program.elf_hash,
+        };
+        self.identity
+            .sign_response(response)
+            .map(Json)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to sign response: {}", e)))
+    }
 }
 
 async fn price_handler(
     State(state): State<Arc<AppState>>,
     Json(req): Json<PriceRequest>,
-) -> Json<PriceResponse> {
+) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+    // Every price is a quote: callers re-price after expires_at and prove against quote_id
+    let issued_at = unix_now();
+    // Returned with the quote so /zk-input can reproduce the advance-purchase tier
+    let purchase_date = pricing_core::date::from_unix_secs(issued_at);
+
     // Use pricing-core logic
-    let core_req = pricing::Request {
-        from: req.from,
-        to: req.to,
+    let core_req = core_price_request(&req, &purchase_date);
+    core_req.validate().map_err(unprocessable)?;
+
+    let core_resp = pricing::handle(core_req).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    state.signed_response("price", serde_json::json!({
+        "price_cents": core_resp.price_cents,
+        "currency": core_resp.currency,
+        "cabin": req.cabin,
+        "fare_family": req.fare_family,
+        "promo_code": core_resp.promo_code,
+        "discount_cents": core_resp.discount_cents,
+        "departure_date": req.departure_date,
+        "return_date": req.return_date,
+        "purchase_date": purchase_date,
+        "quote_id": format!("q_{}", uuid::Uuid::new_v4().simple()),
+        "expires_at": issued_at + state.quote_ttl_secs,
+    }))
+}
+
+// POST /price/batch - Price several routes/dates and pick the cheapest; provable in one execution
+async fn price_batch_handler(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<PriceBatchRequest>,
+) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+    let purchase_date = pricing_core::date::from_unix_secs(unix_now());
+    let core_reqs: Vec<pricing::Request> =
+        req.options.iter().map(|option| core_price_request(option, &purchase_date)).collect();
+    RpcCall::GetPriceBatch(core_reqs.clone()).validate().map_err(unprocessable)?;
+
+    // Returned as-is so the caller can prove the comparison via /zk-input (endpoint "price_batch")
+    let zk_input = serde_json::to_value(&core_reqs).unwrap_or_default();
+    let batch = pricing::handle_batch(core_reqs).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    state.signed_response("price_batch", serde_json::json!({
+        "options": batch.options,
+        "currency": batch.currency,
+        "cheapest": batch.cheapest,
+        "purchase_date": purchase_date,
+        "zk_input": { "endpoint": "price_batch", "input": zk_input },
+    }))
+}
+
+/// `req` as pricing-core sees it, priced as bought on `purchase_date`
+fn core_price_request(req: &PriceRequest, purchase_date: &str) -> pricing::Request {
+    pricing::Request {
+        from: req.from.clone(),
+        to: req.to.clone(),
         vip: req.vip,
-    };
-    
-    let core_resp = pricing::handle(core_req);
+        quote_id: None,
+        departure_date: req.departure_date.clone(),
+        return_date: req.return_date.clone(),
+        purchase_date: Some(purchase_date.to_string()),
+        currency: req.currency.clone(),
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        promo_code: req.promo_code.clone(),
+    }
+}
 
-    Json(PriceResponse {
-        price: core_resp.price,
-        program_id: state.program_id.clone(),
-        elf_hash: state.elf_hash.clone(),
-    })
+/// Header a client sets to make retries of one /book safe
+const IDEMPOTENCY_KEY: &str = "idempotency-key";
+
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, (StatusCode, String)> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|key| !key.is_empty() && key.len() <= 255)
+        .map(|key| Some(key.to_string()))
+        .ok_or((StatusCode::BAD_REQUEST, "Idempotency-Key must be 1-255 visible ASCII characters".to_string()))
 }
 
+// POST /book - Book a flight; a retry returns the original confirmation (see bookings.rs)
 async fn book_handler(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(req): Json<BookRequest>,
-) -> Json<BookResponse> {
-    // If BOOKING_API_URL is set, call the real API
-    let core_resp = if let Some(api_url) = &state.booking_api_url {
-        match call_booking_api(api_url, &req).await {
-            Ok(resp) => resp,
-            Err(e) => {
-                eprintln!("⚠ Booking API call failed: {}, using fallback", e);
-                // Fallback to deterministic logic
-                let core_req = booking::Request {
-                    from: req.from.clone(),
-                    to: req.to.clone(),
-                    passenger_name: req.passenger_name.clone(),
-                    passenger_email: req.passenger_email.clone(),
-                };
-                booking::handle(core_req)
-            }
+) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+    let core_req = booking::Request {
+        from: req.from.clone(),
+        to: req.to.clone(),
+        passenger_name: req.passenger_name.clone(),
+        passenger_email: req.passenger_email.clone(),
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        departure_date: req.departure_date.clone(),
+        seat: req.seat.clone(),
+        pii_salt: Some(req.pii_salt.clone().unwrap_or_else(new_pii_salt)),
+    };
+    core_req.validate().map_err(unprocessable)?;
+
+    // Replay by Idempotency-Key if sent, else while the same passenger's booking on this
+    // route and day is still confirmed (after a cancellation, booking again is intended)
+    let duplicate_key = booking::duplicate_key(&core_req);
+    let idempotency_key = idempotency_key(&headers)?;
+    let passenger_key = format!("passenger:{}", duplicate_key);
+    let key = idempotency_key.as_ref().map(|k| format!("header:{}", k)).unwrap_or_else(|| passenger_key.clone());
+    let internal = |e| (StatusCode::INTERNAL_SERVER_ERROR, e);
+    if let Some((booking_id, original_duplicate_key)) = state.bookings.find_key(&key).map_err(internal)? {
+        if original_duplicate_key != duplicate_key {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Idempotency-Key was already used for booking {}, for another passenger, route, or day", booking_id),
+            ));
+        }
+        let original = find_booking(&state, &booking_id)?;
+        if idempotency_key.is_some() || original.status == "confirmed" {
+            tracing::info!("Duplicate /book, returning booking {}", original.booking_id);
+            return state.signed_response("book", confirmation(&original, true));
+        }
+    }
+
+    // The seat map is ours, so check the seat even when an external API does the booking
+    if let Some(seat) = &req.seat {
+        seats::check(&req.from, &req.to, req.departure_date.as_deref(), seat, req.cabin)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    }
+
+    // Fees on later changes and cancellations are shares of this
+    let (price_cents, currency) = match req.price_cents {
+        Some(price_cents) => (price_cents, req.currency.clone().unwrap_or_else(|| "USD".to_string())),
+        None => {
+            let list = pricing::handle(pricing::Request {
+                from: req.from.clone(),
+                to: req.to.clone(),
+                vip: false,
+                quote_id: None,
+                departure_date: req.departure_date.clone(),
+                return_date: None,
+                purchase_date: Some(pricing_core::date::from_unix_secs(unix_now())),
+                currency: req.currency.clone(),
+                cabin: req.cabin,
+                fare_family: req.fare_family,
+                promo_code: None,
+            })
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            (list.price_cents, list.currency)
         }
-    } else {
-        // Use deterministic booking logic from pricing-core
-        let core_req = booking::Request {
-            from: req.from,
-            to: req.to,
-            passenger_name: req.passenger_name,
-            passenger_email: req.passenger_email,
-        };
-        booking::handle(core_req)
     };
 
-    Json(BookResponse {
+    let fulfilment = state.booker.book(&core_req).await.map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let core_resp = fulfilment.response;
+
+    let record = Booking {
         booking_id: core_resp.booking_id,
         status: core_resp.status,
         confirmation_code: core_resp.confirmation_code,
-        program_id: state.program_id.clone(),
-        elf_hash: state.elf_hash.clone(),
+        from: req.from,
+        to: req.to,
+        passenger_name: req.passenger_name,
+        passenger_email: req.passenger_email,
+        cabin: req.cabin,
+        fare_family: req.fare_family,
+        departure_date: req.departure_date,
+        seat: core_resp.seat,
+        provider: Some(fulfilment.provider.to_string()),
+        pii_salt: core_req.pii_salt,
+        price_cents,
+        currency,
+        change_fees_cents: 0,
+        cancellation: None,
+    };
+    let now = unix_now();
+    state.bookings.save(&record, now).map_err(internal)?;
+    state.bookings.save_key(&passenger_key, &record.booking_id, &duplicate_key, now).map_err(internal)?;
+    if let Some(k) = &idempotency_key {
+        state.bookings.save_key(&format!("header:{}", k), &record.booking_id, &duplicate_key, now).map_err(internal)?;
+    }
+
+    let mut value = confirmation(&record, false);
+    if let Some(reason) = fulfilment.fallback_reason {
+        value["fallback_reason"] = serde_json::json!(reason);
+    }
+    state.signed_response("book", value)
+}
+
+/// Fresh salt for a booking's passenger commitment
+fn new_pii_salt() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+/// Commitment a booking proof carries in place of the passenger's name and email
+fn passenger_commitment(record: &Booking) -> Option<String> {
+    let salt = PiiCommitment::parse_salt(record.pii_salt.as_deref()?).ok()?;
+    Some(PiiCommitment::new(&salt, &record.passenger_name, &record.passenger_email).to_hex())
+}
+
+/// /book response; `duplicate` marks a retry answered with the original booking. `pii_salt`
+/// is for the caller alone: with it the passenger can open `passenger_commitment`, which is
+/// all a booking proof reveals about them.
+fn confirmation(record: &Booking, duplicate: bool) -> serde_json::Value {
+    serde_json::json!({
+        "booking_id": record.booking_id,
+        "status": record.status,
+        "confirmation_code": record.confirmation_code,
+        "cabin": record.cabin,
+        "fare_family": record.fare_family,
+        "departure_date": record.departure_date,
+        "seat": record.seat,
+        "provider": record.provider,
+        "pii_salt": record.pii_salt,
+        "passenger_commitment": passenger_commitment(record),
+        "price_cents": record.price_cents,
+        "currency": record.currency,
+        "duplicate": duplicate,
     })
 }
 
-async fn call_booking_api(
-    api_url: &str,
-    req: &BookRequest,
-) -> Result<booking::Response, String> {
-    let client = reqwest::Client::new();
-    
-    #[derive(Serialize)]
-    struct ApiRequest {
-        from: String,
-        to: String,
-        passenger_name: String,
-        passenger_email: String,
+/// 422 with the validation error as JSON: `{"code", "field", "message"}`
+fn unprocessable(e: ValidationError) -> (StatusCode, String) {
+    let body = serde_json::to_string(&e).unwrap_or_else(|_| e.to_string());
+    (StatusCode::UNPROCESSABLE_ENTITY, body)
+}
+
+fn find_booking(state: &AppState, booking_id: &str) -> Result<Booking, (StatusCode, String)> {
+    state
+        .bookings
+        .get(booking_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No booking {}", booking_id)))
+}
+
+fn booking_value(record: &Booking) -> Result<serde_json::Value, (StatusCode, String)> {
+    serde_json::to_value(record)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to encode booking: {}", e)))
+}
+
+// GET /booking/:id - A booking made through /book
+async fn get_booking_handler(
+    State(state): State<Arc<AppState>>,
+    Path(booking_id): Path<String>,
+) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+    let record = find_booking(&state, &booking_id)?;
+    state.signed_response("book", booking_value(&record)?)
+}
+
+// POST /booking/:id/cancel - Cancel as of today (UTC); the fee follows pricing-core's cancellation rules
+async fn cancel_booking_handler(
+    State(state): State<Arc<AppState>>,
+    Path(booking_id): Path<String>,
+) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+    let mut record = find_booking(&state, &booking_id)?;
+    if record.cancellation.is_some() {
+        return Err((StatusCode::CONFLICT, format!("Booking {} is already cancelled", booking_id)));
     }
-    
-    let api_req = ApiRequest {
-        from: req.from.clone(),
-        to: req.to.clone(),
-        passenger_name: req.passenger_name.clone(),
-        passenger_email: req.passenger_email.clone(),
+
+    let core_req = cancellation::Request {
+        booking_id: record.booking_id.clone(),
+        fare_cents: record.price_cents,
+        currency: record.currency.clone(),
+        fare_family: record.fare_family,
+        departure_date: record.departure_date.clone(),
+        cancel_date: pricing_core::date::from_unix_secs(unix_now()),
     };
-    
-    let response = client
-        .post(api_url)
-        .json(&api_req)
-        .send()
-        .await
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
-    
-    #[derive(Deserialize)]
-    struct ApiResponse {
-        booking_id: String,
-        status: String,
-        confirmation_code: String,
+    // Returned as-is so the caller can prove the refund via /zk-input (endpoint "cancel")
+    let zk_input = serde_json::to_value(&core_req).unwrap_or_default();
+    let cancel_date = core_req.cancel_date.clone();
+    let core_resp = cancellation::handle(core_req).map_err(|e| (StatusCode::CONFLICT, e))?;
+
+    record.status = "cancelled".to_string();
+    record.cancellation = Some(bookings::Cancellation {
+        fee_cents: core_resp.fee_cents,
+        refund_cents: core_resp.refund_cents,
+        cancelled_on: cancel_date,
+    });
+    state.bookings.save(&record, unix_now()).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    let mut value = booking_value(&record)?;
+    value["zk_input"] = serde_json::json!({ "endpoint": "cancel", "input": zk_input });
+    state.signed_response("cancel", value)
+}
+
+// POST /booking/:id/change - Move to another date and/or seat, for pricing-core's change fee
+async fn change_booking_handler(
+    State(state): State<Arc<AppState>>,
+    Path(booking_id): Path<String>,
+    Json(req): Json<ChangeRequest>,
+) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+    let mut record = find_booking(&state, &booking_id)?;
+    if record.cancellation.is_some() {
+        return Err((StatusCode::CONFLICT, format!("Booking {} is cancelled", booking_id)));
     }
-    
-    let api_resp: ApiResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse API response: {}", e))?;
-    
-    Ok(booking::Response {
-        booking_id: api_resp.booking_id,
-        status: api_resp.status,
-        confirmation_code: api_resp.confirmation_code,
+
+    let departure_date = req.departure_date.or_else(|| record.departure_date.clone());
+    let seat = req.seat.or_else(|| record.seat.clone());
+    // Re-run the booking logic so the new seat is checked against the new flight
+    let core_resp = booking::handle(booking::Request {
+        from: record.from.clone(),
+        to: record.to.clone(),
+        passenger_name: record.passenger_name.clone(),
+        passenger_email: record.passenger_email.clone(),
+        cabin: record.cabin,
+        fare_family: record.fare_family,
+        departure_date: departure_date.clone(),
+        seat,
+        pii_salt: record.pii_salt.clone(),
     })
+    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let change_fee_cents = cancellation::change_fee_cents(record.fare_family, record.price_cents);
+    // The booking keeps its id; the confirmation code is reissued
+    record.confirmation_code = core_resp.confirmation_code;
+    record.departure_date = departure_date;
+    record.seat = core_resp.seat;
+    record.change_fees_cents += change_fee_cents;
+    state.bookings.save(&record, unix_now()).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    let mut value = booking_value(&record)?;
+    value["change_fee_cents"] = change_fee_cents.into();
+    state.signed_response("book", value)
 }
 
-async fn register_elf_with_attester(
-    elf_bytes: Vec<u8>,
-    attester_url: &str,
-) -> Result<String, String> {
-    let part = reqwest::multipart::Part::bytes(elf_bytes)
-        .file_name("agent-b-program.elf")
-        .mime_str("application/octet-stream")
-        .map_err(|e| format!("Failed to create multipart: {}", e))?;
-    
-    let form = reqwest::multipart::Form::new()
-        .part("elf", part);
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&format!("{}/register-elf", attester_url))
-        .multipart(form)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to register ELF: {}", e))?;
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
 
-    let body: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse attester response: {}", e))?;
+// GET /seats - Seat map of one flight
+async fn seats_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SeatsQuery>,
+) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+    let map = seats::availability(&query.from, &query.to, query.departure_date.as_deref());
+    let value = serde_json::to_value(map)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to encode seat map: {}", e)))?;
+    state.signed_response("book", value)
+}
+
+// POST /select-seat - Check a seat before booking it (nothing is held)
+async fn select_seat_handler(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SelectSeatRequest>,
+) -> Result<Json<SignedAgentResponse>, (StatusCode, String)> {
+    let seat = seats::check(&req.from, &req.to, req.departure_date.as_deref(), &req.seat, req.cabin)
+        .map_err(|e| (StatusCode::CONFLICT, e))?;
+    state.signed_response("book", serde_json::json!({
+        "from": req.from,
+        "to": req.to,
+        "departure_date": req.departure_date,
+        "cabin": req.cabin,
+        "seat": seat,
+    }))
+}
+
+// GET /health - Liveness probe (used by Agent A's circuit breaker)
+async fn health(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let program_ids: BTreeMap<_, _> = state
+        .programs
+        .iter()
+        .map(|(name, registration)| (*name, registration.program().map(|p| p.program_id)))
+        .collect();
+    Json(serde_json::json!({
+        "status": "ok",
+        "programs": program_ids,
+    }))
+}
+
+// GET /ready - Readiness probe: 503 until every endpoint has a program registered with the attester
+async fn ready(State(state): State<Arc<AppState>>) -> (StatusCode, Json<serde_json::Value>) {
+    let unproven: Vec<&str> = zk_adapter::ENDPOINTS
+        .iter()
+        .map(|e| e.endpoint)
+        .filter(|endpoint| state.program_for(endpoint, None).is_err())
+        .collect();
+    let programs: BTreeMap<_, _> = state
+        .programs
+        .iter()
+        .map(|(name, registration)| {
+            let status = registration.status();
+            (*name, serde_json::json!({
+                "registered": status.program.is_some(),
+                "program_id": status.program.map(|p| p.program_id),
+                "attempts": status.attempts,
+                "last_error": status.last_error,
+            }))
+        })
+        .collect();
+    let code = if unproven.is_empty() { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (code, Json(serde_json::json!({
+        "ready": unproven.is_empty(),
+        "unproven_endpoints": unproven,
+        "programs": programs,
+    })))
+}
+
+// GET /program - The guest programs behind this server's responses, and how they were built
+async fn program_info(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let programs: Vec<serde_json::Value> = PROGRAMS
+        .iter()
+        .filter_map(|spec| state.programs.get(spec.name))
+        .map(|registration| {
+            let program = registration.program();
+            serde_json::json!({
+                "name": registration.spec.name,
+                "endpoints": registration.spec.endpoints,
+                "program_id": program.as_ref().map(|p| &p.program_id),
+                "elf_hash": program.as_ref().map(|p| &p.elf_hash),
+                "registered_at": program.as_ref().map(|p| p.registered_at),
+                "elf_path": registration.elf_path,
+            })
+        })
+        .collect();
+    let attester_url = state.programs.values().next().map(|r| r.attester_url.clone());
+    Json(serde_json::json!({
+        "programs": programs,
+        "attester_url": attester_url,
+        "signer": state.identity.did(),
+        "build": build_info(),
+    }))
+}
+
+fn build_info() -> serde_json::Value {
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        // Set at build time, e.g. AGENT_B_GIT_COMMIT=$(git rev-parse HEAD) cargo build
+        "git_commit": option_env!("AGENT_B_GIT_COMMIT"),
+        "profile": if cfg!(debug_assertions) { "debug" } else { "release" },
+        "protocol_version": zk_protocol::ProtocolVersion::CURRENT.to_string(),
+        // Committed by the guest next to every claim
+        "ruleset_version": pricing_core::ruleset::VERSION,
+        "ruleset_hash": format!("0x{}", hex::encode(pricing_core::ruleset::hash())),
+    })
+}
+
+#[derive(Deserialize)]
+struct ElfQuery {
+    /// Program name from GET /program; "full" if unset
+    #[serde(default)]
+    program: Option<String>,
+}
 
-    body["program_id"]
-        .as_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| "No program_id in response".to_string())
+fn registered_program(state: &AppState, name: Option<&str>) -> Result<(&'static str, Program), (StatusCode, String)> {
+    let name = name.unwrap_or("full");
+    let registration = state
+        .programs
+        .get(name)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unknown program {}; see GET /program", name)))?;
+    let program = registration.program().ok_or_else(|| {
+        (StatusCode::SERVICE_UNAVAILABLE, format!("Program {} not registered with the attester yet; see GET /ready", name))
+    })?;
+    Ok((registration.spec.name, program))
+}
+
+// GET /elf - The ELF exactly as registered with the attester, to recompute elf_hash and vk_hash from
+async fn elf_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ElfQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let (name, program) = registered_program(&state, query.program.as_deref())?;
+    let headers = [
+        (CONTENT_TYPE, "application/octet-stream".to_string()),
+        (CONTENT_DISPOSITION, format!("attachment; filename=\"agent-b-{}.elf\"", name)),
+        (HeaderName::from_static("x-program-id"), program.program_id),
+        (HeaderName::from_static("x-elf-hash"), program.elf_hash),
+    ];
+    Ok((headers, Body::from(program.elf.to_vec())))
+}
+
+// GET /verification-info - What an auditor needs to check a proof was made by the advertised program
+//
+// Download the ELF from GET /elf, check its SHA-256 against elf_hash, run SP1's setup on it,
+// and compare the verifying key's bytes32() with vk_hash and with the proof's vk_hash.
+async fn verification_info_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ElfQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let (name, program) = registered_program(&state, query.program.as_deref())?;
+    Ok(Json(serde_json::json!({
+        "program": name,
+        "program_id": program.program_id,
+        "elf_hash": program.elf_hash,
+        "elf_size": program.elf.len(),
+        "elf_url": format!("/elf?program={}", name),
+        // As reported by the attester; null until its first proof for this program
+        "vk_hash": program.vk_hash,
+        "registered_at": program.registered_at,
+        "build": build_info(),
+    })))
 }
 
 // POST /zk-input - Helper endpoint for external agents
-// Returns properly formatted zkVM input bytes
+// Returns properly formatted zkVM input bytes, and the program to prove them with
 #[derive(Deserialize)]
 struct ZkInputRequest {
-    endpoint: String,  // "price" or "book"
+    endpoint: String,  // one of zk_adapter::ENDPOINTS, see GET /zk-input/schema
     input: serde_json::Value,
+    /// Program name from GET /program; the most specialised registered one if unset
+    #[serde(default)]
+    program: Option<String>,
 }
 
 #[derive(Serialize)]
 struct ZkInputResponse {
     input_bytes: Vec<u8>,
+    program: &'static str,
+    program_id: String,
+    elf_hash: String,
 }
 
 async fn zk_input_handler(
+    State(state): State<Arc<AppState>>,
     Json(req): Json<ZkInputRequest>,
-) -> Json<ZkInputResponse> {
-    let rpc_call = zk_adapter::json_to_rpc_call(&req.endpoint, &req.input)
-        .expect("Failed to convert to RpcCall");
-    
-    let input_bytes = zk_adapter::rpc_call_to_bytes(&rpc_call);
-    
-    Json(ZkInputResponse { input_bytes })
+) -> Result<Json<ZkInputResponse>, (StatusCode, String)> {
+    let rpc_call = zk_adapter::json_to_rpc_call(&req.endpoint, &req.input).map_err(|e| {
+        let body = serde_json::to_string(&e).unwrap_or(e.message);
+        (StatusCode::UNPROCESSABLE_ENTITY, body)
+    })?;
+    // Proving a call the guest would reject only wastes prover time
+    rpc_call.validate().map_err(unprocessable)?;
+    let (program, registered) = state.program_for(&req.endpoint, req.program.as_deref())?;
+
+    let input_bytes = zk_adapter::rpc_call_to_bytes(&rpc_call)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+    Ok(Json(ZkInputResponse {
+        input_bytes,
+        program,
+        program_id: registered.program_id,
+        elf_hash: registered.elf_hash,
+    }))
+}
+
+// GET /zk-input/schema - The JSON each /zk-input endpoint expects
+async fn zk_input_schema_handler() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "endpoints": zk_adapter::ENDPOINTS }))
+}
+
+// POST /admin/reload-elf - Re-read ELFs from disk and re-register them, swapping program_ids
+#[derive(Deserialize, Default)]
+struct ReloadRequest {
+    /// Program name from GET /program; every program if unset
+    #[serde(default)]
+    program: Option<String>,
+}
+
+async fn reload_elf_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<ReloadRequest>>,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
+    let expected = state
+        .admin_token
+        .as_deref()
+        .ok_or((StatusCode::FORBIDDEN, "Admin routes are disabled; set AGENT_B_ADMIN_TOKEN".to_string()))?;
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+    if !token.is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes())) {
+        return Err((StatusCode::UNAUTHORIZED, "Missing or invalid bearer token".to_string()));
+    }
+
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    let registrations: Vec<&Arc<Registration>> = match &req.program {
+        Some(name) => vec![state
+            .programs
+            .get(name.as_str())
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unknown program {}; see GET /program", name)))?],
+        None => state.programs.values().collect(),
+    };
+
+    let mut results = Vec::new();
+    let mut failed = false;
+    for registration in registrations {
+        let result = match registration.reload().await {
+            Ok((previous, program)) => serde_json::json!({
+                "program": registration.spec.name,
+                "program_id": program.program_id,
+                "elf_hash": program.elf_hash,
+                "previous_program_id": previous.map(|p| p.program_id),
+            }),
+            Err(e) => {
+                failed = true;
+                serde_json::json!({ "program": registration.spec.name, "error": e })
+            }
+        };
+        results.push(result);
+    }
+    // Failed programs keep their previous program_id
+    let code = if failed { StatusCode::BAD_GATEWAY } else { StatusCode::OK };
+    Ok((code, Json(serde_json::json!({ "reloaded": results }))))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
 #[tokio::main]
 async fn main() {
-    let attester_url = std::env::var("ATTESTER_URL")
-        .unwrap_or_else(|_| "http://localhost:8000".to_string());
-
-    // Read the proper ELF binary (not .a archive)
-    let elf_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/agent-b-program");
-    
-    println!("Loading ELF from: {:?}", elf_path);
-    let elf_bytes = std::fs::read(&elf_path)
-        .expect(&format!("Failed to read {:?}. Run 'cd program && cargo prove build' first.", elf_path));
-
-    // Compute ELF hash
-    let mut hasher = Sha256::new();
-    hasher.update(&elf_bytes);
-    let elf_hash = format!("0x{}", hex::encode(hasher.finalize()));
-
-    // Register with attester
-    let program_id = register_elf_with_attester(elf_bytes, &attester_url)
-        .await
-        .expect("Failed to register ELF with attester");
+    let _telemetry = telemetry::init("agent-b-server", false);
+
+    let config = Config::load().unwrap_or_else(|e| panic!("{}", e));
+    let attester_url = config.attester_url.clone();
+
+    // Read the proper ELF binaries (not .a archive); `cargo prove build` builds them all
+    let elf_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../target/elf-compilation/riscv32im-succinct-zkvm-elf/release");
 
-    println!("✓ ELF registered with attester");
-    println!("  program_id: {}", program_id);
-    println!("  elf_hash: {}", elf_hash);
+    println!("Loading ELFs from: {:?}", elf_dir);
     println!("  attester_url: {}", attester_url);
 
-    // Optional: External booking API URL
-    let booking_api_url = std::env::var("BOOKING_API_URL").ok();
-    if let Some(ref url) = booking_api_url {
-        println!("  booking_api_url: {}", url);
-    } else {
-        println!("  booking_api_url: (not set, using deterministic logic)");
+    // Presents TLS_CLIENT_CERT to the attester and Agent A, if set
+    let client = config
+        .tls
+        .client(reqwest::Client::builder())
+        .and_then(|builder| Ok(builder.build()?))
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    // Register in the background so a missing ELF or attester doesn't stop the server;
+    // /ready reports progress
+    let check_every = Duration::from_secs(config.attester_check_secs);
+    let mut programs = BTreeMap::new();
+    for spec in PROGRAMS {
+        let registration = Arc::new(Registration::new(spec, &elf_dir, attester_url.clone(), client.clone()));
+        programs.insert(spec.name, registration.clone());
+        tokio::spawn(async move { registration.run(check_every).await });
+    }
+    if config.agent_b_admin_token.is_some() {
+        println!("  ✓ POST /admin/reload-elf enabled (AGENT_B_ADMIN_TOKEN)");
+    }
+
+    let booker = config.booker().unwrap_or_else(|e| panic!("{}", e));
+    match &config.booking_api_url {
+        Some(url) if booker.provider_name() == "rest" => println!("  booking_provider: rest ({})", url),
+        _ => println!("  booking_provider: {}", booker.provider_name()),
+    }
+
+    // Signing identity: AGENT_B_SIGNING_KEY (32-byte hex seed), else ephemeral
+    let identity = match &config.agent_b_signing_key {
+        Some(seed) => AgentIdentity::from_seed_hex(seed)
+            .expect("AGENT_B_SIGNING_KEY must be a 32-byte hex seed"),
+        None => {
+            println!("  ⚠ AGENT_B_SIGNING_KEY not set, using an ephemeral signing key");
+            AgentIdentity::generate()
+        }
+    };
+    println!("  signer: {}", identity.did());
+
+    let tap = Arc::new(
+        tap::TapConfig::parse(config.agent_b_tap_trusted_keys.as_deref().unwrap_or_default())
+            .expect("AGENT_B_TAP_TRUSTED_KEYS was validated at startup")
+            .with_jwks_url(config.agent_b_tap_jwks_url.clone()),
+    );
+    if tap.is_enabled() {
+        println!("  ✓ TAP signatures required (AGENT_B_TAP_TRUSTED_KEYS / AGENT_B_TAP_JWKS_URL)");
+    }
+    if let Some(url) = &config.agent_b_tap_jwks_url {
+        let tap = tap.clone();
+        let client = client.clone();
+        let every = Duration::from_secs(config.agent_b_tap_jwks_refresh_secs);
+        println!("  tap_jwks_url: {}", url);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                match tap.refresh_jwks(&client).await {
+                    Ok(count) => tracing::debug!("TAP JWKS refreshed, {} keys", count),
+                    Err(e) => tracing::warn!("TAP JWKS refresh failed, keeping previous keys: {}", e),
+                }
+            }
+        });
+    }
+
+    let bookings = BookingStore::open(config.agent_b_booking_db.as_deref()).unwrap_or_else(|e| panic!("{}", e));
+    match &config.agent_b_booking_db {
+        Some(path) => println!("  booking_db: {}", path),
+        None => println!("  booking_db: (not set, bookings are kept in memory)"),
     }
 
     let state = Arc::new(AppState {
-        program_id,
-        elf_hash,
-        booking_api_url,
+        programs,
+        booker,
+        quote_ttl_secs: config.quote_ttl_secs,
+        bookings,
+        identity,
+        admin_token: config.agent_b_admin_token.clone(),
     });
 
     let app = Router::new()
         .route("/price", post(price_handler))
+        .route("/price/batch", post(price_batch_handler))
         .route("/book", post(book_handler))
+        .route("/seats", get(seats_handler))
+        .route("/select-seat", post(select_seat_handler))
+        .route("/booking/:id", get(get_booking_handler))
+        .route("/booking/:id/cancel", post(cancel_booking_handler))
+        .route("/booking/:id/change", post(change_booking_handler))
         .route("/zk-input", post(zk_input_handler))
+        .route("/zk-input/schema", get(zk_input_schema_handler))
+        .route("/admin/reload-elf", post(reload_elf_handler))
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/program", get(program_info))
+        .route("/elf", get(elf_handler))
+        .route("/verification-info", get(verification_info_handler))
+        .merge(agent_b_tools::router(state.clone()))
+        .layer(middleware::from_fn_with_state(tap, tap::require_tap))
+        .layer(TraceLayer::new_for_http().make_span_with(correlation::make_span))
+        .layer(middleware::from_fn(correlation::propagate))
         .with_state(state);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:8001")
-        .await
-        .expect("Failed to bind to 0.0.0.0:8001");
-
-    println!("✓ Agent B running on http://0.0.0.0:8001");
+    println!("✓ Agent B running on {}://0.0.0.0:8001", config.tls.scheme());
+    if config.tls.requires_client_cert() {
+        println!("  ✓ Client certificates required (TLS_CLIENT_CA)");
+    }
     println!("  POST /price  — Get flight pricing");
+    println!("  POST /price/batch — Compare several routes/dates");
     println!("  POST /book   — Book a flight");
+    println!("  GET  /seats  — Seat map of a flight");
+    println!("  POST /select-seat — Check a seat before booking");
+    println!("  GET  /booking/:id — Look up a booking");
+    println!("  POST /booking/:id/cancel — Cancel a booking");
+    println!("  POST /booking/:id/change — Change a booking's date or seat");
+    println!("  POST /zk-input — zkVM input bytes for a call");
+    println!("  GET  /zk-input/schema — JSON each /zk-input endpoint expects");
+    println!("  POST /admin/reload-elf — Re-register rebuilt ELFs (bearer token)");
+    println!("  GET  /health — Liveness probe");
+    println!("  GET  /ready  — Readiness (a program registered for every endpoint)");
+    println!("  GET  /program — Guest programs, their program_ids, and build info");
+    println!("  GET  /elf    — Registered ELF (?program=name)");
+    println!("  GET  /verification-info — elf_hash, vk_hash, and build info of a program");
+    println!("  GET  /tools  — MCP tool listing ({} tools, as agent-b-mcp-server)", tools::TOOLS.len());
+    println!("  POST /tools/:name — Call a tool (same handlers as the routes above)");
 
-    axum::serve(listener, app)
+    agent_tls::serve(([0, 0, 0, 0], 8001).into(), app, &config.tls)
         .await
         .expect("Server error");
 }
\ No newline at end of file
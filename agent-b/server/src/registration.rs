@@ -0,0 +1,212 @@
+//! Registration of the guest ELFs with the attester
+//!
+//! Each binary of agent-b/program in [`PROGRAMS`] is registered separately and gets its own
+//! program_id. Startup doesn't wait on it: registration runs in the background and is
+//! retried with backoff until it succeeds, re-reading the ELF each time so a `cargo prove
+//! build` that lands after startup is picked up. Until every endpoint has a registered
+//! program `/ready` is 503, and a response no registered program can prove is refused.
+//!
+//! Afterwards the attester is polled for each program_id, and a program it has forgotten is
+//! registered again. `POST /admin/reload-elf` does the same on demand after a rebuild.
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// A guest binary and the `/zk-input` endpoints it proves
+pub struct ProgramSpec {
+    pub name: &'static str,
+    /// Binary name under target/elf-compilation
+    pub bin: &'static str,
+    pub endpoints: &'static [&'static str],
+}
+
+/// Specialised programs first: for an endpoint, the first registered one that proves it is used
+pub const PROGRAMS: &[ProgramSpec] = &[
+    ProgramSpec { name: "pricing", bin: "agent-b-pricing", endpoints: &["price"] },
+    ProgramSpec { name: "batch", bin: "agent-b-batch", endpoints: &["price_batch"] },
+    ProgramSpec { name: "booking", bin: "agent-b-booking", endpoints: &["book", "cancel"] },
+    ProgramSpec { name: "full", bin: "agent-b-program", endpoints: &["price", "price_batch", "book", "cancel"] },
+];
+
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+const MAX_RETRY: Duration = Duration::from_secs(60);
+
+/// The guest program as registered with the attester
+#[derive(Clone)]
+pub struct Program {
+    pub program_id: String,
+    /// The ELF as sent to the attester, served by GET /elf
+    pub elf: Arc<[u8]>,
+    /// SHA-256 of the ELF, 0x-prefixed hex
+    pub elf_hash: String,
+    /// Verifying key hash as reported by the attester, once it has computed the keys
+    pub vk_hash: Option<String>,
+    /// Unix seconds
+    pub registered_at: u64,
+}
+
+#[derive(Clone, Default)]
+pub struct Status {
+    /// Set once registration succeeds
+    pub program: Option<Program>,
+    pub attempts: u32,
+    /// Why the latest attempt failed
+    pub last_error: Option<String>,
+}
+
+pub struct Registration {
+    pub spec: &'static ProgramSpec,
+    pub elf_path: PathBuf,
+    pub attester_url: String,
+    status: RwLock<Status>,
+    client: reqwest::Client,
+}
+
+impl Registration {
+    /// `spec`'s ELF, looked up in `elf_dir`, registered through `client`
+    pub fn new(spec: &'static ProgramSpec, elf_dir: &Path, attester_url: String, client: reqwest::Client) -> Self {
+        Self {
+            spec,
+            elf_path: elf_dir.join(spec.bin),
+            attester_url,
+            status: RwLock::new(Status::default()),
+            client,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        self.status.read().unwrap().clone()
+    }
+
+    pub fn program(&self) -> Option<Program> {
+        self.status.read().unwrap().program.clone()
+    }
+
+    /// Register, retrying with exponential backoff (capped at a minute) until it succeeds, then
+    /// check every `check_every` that the attester still has the program and register again
+    /// if it doesn't (e.g. after an attester restart, which forgets every ELF)
+    pub async fn run(&self, check_every: Duration) {
+        loop {
+            let mut delay = FIRST_RETRY;
+            while let Err(e) = self.reload().await {
+                tracing::warn!(
+                    "⚠ {} ELF registration failed (attempt {}, retrying in {}s): {}",
+                    self.spec.name, self.status().attempts, delay.as_secs(), e
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY);
+            }
+            self.wait_until_forgotten(check_every).await;
+        }
+    }
+
+    /// Re-read the ELF and register it now, swapping in the new program_id. Returns the
+    /// previous program, which keeps serving if this fails.
+    pub async fn reload(&self) -> Result<(Option<Program>, Program), String> {
+        let result = self.attempt().await;
+        let mut status = self.status.write().unwrap();
+        status.attempts += 1;
+        match result {
+            Ok(program) => {
+                println!("✓ {} ELF registered with attester", self.spec.name);
+                println!("  program_id: {}", program.program_id);
+                println!("  elf_hash: {}", program.elf_hash);
+                status.last_error = None;
+                Ok((status.program.replace(program.clone()), program))
+            }
+            Err(e) => {
+                status.last_error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Return once the attester answers 404 for the current program_id, recording its vk_hash
+    /// meanwhile. An unreachable attester isn't taken as a restart: it may come back with the
+    /// program still loaded.
+    async fn wait_until_forgotten(&self, check_every: Duration) {
+        loop {
+            tokio::time::sleep(check_every).await;
+            let Some(program) = self.program() else { return };
+            let url = format!("{}/programs/{}", self.attester_url, program.program_id);
+            match self.client.get(&url).send().await {
+                Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => {
+                    tracing::warn!(
+                        "⚠ Attester no longer knows {} program {}, re-registering",
+                        self.spec.name, program.program_id
+                    );
+                    return;
+                }
+                Ok(response) => {
+                    let vk_hash = response
+                        .json::<serde_json::Value>()
+                        .await
+                        .ok()
+                        .and_then(|body| body["vk_hash"].as_str().map(String::from));
+                    if let Some(vk_hash) = vk_hash {
+                        let mut status = self.status.write().unwrap();
+                        // Unless a reload swapped the program in the meantime
+                        if let Some(current) = status.program.as_mut().filter(|p| p.program_id == program.program_id) {
+                            current.vk_hash = Some(vk_hash);
+                        }
+                    }
+                }
+                Err(e) => tracing::debug!("Attester check for {} failed: {}", self.spec.name, e),
+            }
+        }
+    }
+
+    async fn attempt(&self) -> Result<Program, String> {
+        let elf_bytes = std::fs::read(&self.elf_path).map_err(|e| {
+            format!("Failed to read {:?} ({}). Run 'cd program && cargo prove build' first.", self.elf_path, e)
+        })?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(&elf_bytes);
+        let elf_hash = format!("0x{}", hex::encode(hasher.finalize()));
+
+        let elf: Arc<[u8]> = elf_bytes.into();
+        let program_id = register_elf_with_attester(&self.client, elf.to_vec(), self.spec.bin, &self.attester_url).await?;
+        Ok(Program {
+            program_id,
+            elf,
+            elf_hash,
+            vk_hash: None,
+            registered_at: crate::unix_now(),
+        })
+    }
+}
+
+async fn register_elf_with_attester(
+    client: &reqwest::Client,
+    elf_bytes: Vec<u8>,
+    bin: &str,
+    attester_url: &str,
+) -> Result<String, String> {
+    let part = reqwest::multipart::Part::bytes(elf_bytes)
+        .file_name(format!("{}.elf", bin))
+        .mime_str("application/octet-stream")
+        .map_err(|e| format!("Failed to create multipart: {}", e))?;
+
+    let form = reqwest::multipart::Form::new()
+        .part("elf", part);
+
+    let response = client
+        .post(format!("{}/register-elf", attester_url))
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to register ELF: {}", e))?;
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse attester response: {}", e))?;
+
+    body["program_id"]
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "No program_id in response".to_string())
+}
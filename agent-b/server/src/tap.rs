@@ -0,0 +1,156 @@
+//! TAP signature check for inbound requests
+//!
+//! Trusted signers come from `agent_b_tap_trusted_keys` (AGENT_B_TAP_TRUSTED_KEYS) as
+//! comma-separated did:keys, e.g. Agent A's. With `agent_b_tap_jwks_url`
+//! (AGENT_B_TAP_JWKS_URL), keys published at that JWKS (Agent A's
+//! `/.well-known/tap-jwks.json`) are trusted too and refreshed periodically, so Agent A can
+//! rotate keys without a restart here. With neither configured, requests are not checked.
+//! A nonce is accepted once while its signature is valid, so a captured request can't be
+//! replayed.
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header::HOST, request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::Instrument;
+use zk_protocol::tap::{self, Jwks, TapError, CONTENT_DIGEST_HEADER};
+
+/// Routes reachable without a signature
+const PUBLIC_PATHS: &[&str] = &["/health", "/ready", "/program", "/elf", "/verification-info", "/tools"];
+
+/// Largest body buffered for digest checking
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct TapConfig {
+    /// (did:key, hex public key)
+    trusted: Vec<(String, String)>,
+    /// Where to refresh `jwks` from
+    jwks_url: Option<String>,
+    jwks: RwLock<Jwks>,
+    /// Nonce -> expiry of the signature that used it
+    seen_nonces: Mutex<HashMap<String, u64>>,
+}
+
+impl TapConfig {
+    pub fn parse(list: &str) -> Result<Self, TapError> {
+        let trusted = list
+            .split(',')
+            .map(str::trim)
+            .filter(|did| !did.is_empty())
+            .map(|did| Ok((did.to_string(), tap::public_key_from_did(did)?)))
+            .collect::<Result<_, TapError>>()?;
+        Ok(Self { trusted, ..Default::default() })
+    }
+
+    /// Also trust keys published at `url`
+    pub fn with_jwks_url(mut self, url: Option<String>) -> Self {
+        self.jwks_url = url;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.trusted.is_empty() || self.jwks_url.is_some()
+    }
+
+    /// Re-fetch the JWKS; the previous key set is kept if the fetch fails
+    pub async fn refresh_jwks(&self, client: &reqwest::Client) -> Result<usize, reqwest::Error> {
+        let Some(url) = &self.jwks_url else {
+            return Ok(0);
+        };
+        let jwks: Jwks = client.get(url).send().await?.error_for_status()?.json().await?;
+        let count = jwks.keys.len();
+        *self.jwks.write().unwrap() = jwks;
+        Ok(count)
+    }
+
+    fn public_key(&self, did: &str, now: u64) -> Option<String> {
+        self.trusted
+            .iter()
+            .find(|(trusted, _)| trusted == did)
+            .map(|(_, key)| key.clone())
+            .or_else(|| self.jwks.read().unwrap().public_key(did, now))
+    }
+
+    /// Record `nonce`; false if it was already used by an unexpired signature
+    fn first_use(&self, nonce: &str, expires: u64, now: u64) -> bool {
+        let mut seen = self.seen_nonces.lock().unwrap();
+        seen.retain(|_, expiry| *expiry > now);
+        seen.insert(nonce.to_string(), expires).is_none()
+    }
+}
+
+/// Reject requests without a valid TAP signature from a trusted agent (outside `PUBLIC_PATHS`)
+///
+/// Requests with a body must also cover `content-digest`, and the digest must match the
+/// body received. The rest of the request runs in a span tagged with the signer's did:key.
+pub async fn require_tap(State(tap): State<Arc<TapConfig>>, req: Request, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    if !tap.is_enabled() || PUBLIC_PATHS.contains(&path.as_str()) {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let body = match axum::body::to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => return reject(&path, format!("unreadable body: {}", e)),
+    };
+    match check(&tap, &parts, &body) {
+        Ok(signer) => {
+            let span = tracing::info_span!("tap", signer = %signer);
+            next.run(Request::from_parts(parts, Body::from(body))).instrument(span).await
+        }
+        Err(reason) => reject(&path, reason),
+    }
+}
+
+fn reject(path: &str, reason: String) -> Response {
+    tracing::warn!("✗ Rejected TAP signature on {}: {}", path, reason);
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(json!({ "error": format!("TAP signature rejected: {}", reason) })),
+    )
+        .into_response()
+}
+
+fn check(tap: &TapConfig, parts: &Parts, body: &[u8]) -> Result<String, String> {
+    let headers = &parts.headers;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let key_id = tap::tap_key_id(headers).map_err(|e| e.to_string())?;
+    let public_key = tap
+        .public_key(&key_id, now)
+        .ok_or_else(|| format!("untrusted signer {}", key_id))?;
+
+    // Agent B sits behind plain HTTP; the signed authority is the Host the caller used
+    let host = headers
+        .get(HOST)
+        .and_then(|v| v.to_str().ok())
+        .ok_or("missing Host header")?;
+    let path_and_query = parts.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    let url = format!("http://{}{}", host, path_and_query);
+
+    let verified = tap::verify_tap_signature(headers, parts.method.as_str(), &url, &public_key)
+        .map_err(|e| e.to_string())?;
+    if !body.is_empty() {
+        if !verified.components.iter().any(|c| c == CONTENT_DIGEST_HEADER) {
+            return Err("signature does not cover content-digest".to_string());
+        }
+        tap::verify_content_digest(headers, body).map_err(|e| e.to_string())?;
+    }
+
+    let nonce = verified.nonce.ok_or("missing nonce")?;
+    if !tap.first_use(&nonce, verified.expires, now) {
+        return Err("nonce already used".to_string());
+    }
+    Ok(verified.key_id)
+}
@@ -0,0 +1,112 @@
+//! Agent B's MCP tool routes on the plain server
+//!
+//! GET /tools and POST /tools/{name} come from agent-b-tools, the same router the MCP server
+//! uses, so Agent A can discover and call tools here too. Each tool runs the handler behind
+//! the matching REST route, and its `data` is that route's signed response. validate-promo
+//! has no route here, so it isn't offered.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    Json,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+use agent_b_tools::{ToolBackend, ToolError};
+use tool_envelope::codes;
+use pricing_core::validate::ValidationError;
+
+use crate::{
+    book_handler, cancel_booking_handler, change_booking_handler, get_booking_handler, price_batch_handler,
+    price_handler, seats_handler, select_seat_handler, zk_input_handler, AppState, IDEMPOTENCY_KEY,
+};
+
+/// Tools this server implements, in the catalogue's terms
+pub const TOOLS: [&str; 9] = [
+    "get-ticket-price",
+    "get-flight-options",
+    "book-flight",
+    "get-seats",
+    "select-seat",
+    "get-booking",
+    "cancel-booking",
+    "change-booking",
+    "get-zk-input",
+];
+
+/// Arguments of the tools that act on one booking
+#[derive(Deserialize)]
+struct BookingId {
+    booking_id: String,
+}
+
+#[async_trait]
+impl ToolBackend for AppState {
+    fn offers(&self, name: &str) -> bool {
+        TOOLS.contains(&name)
+    }
+
+    async fn call(self: Arc<Self>, name: &str, arguments: Value, mut headers: HeaderMap) -> Result<Value, ToolError> {
+        let state = State(self);
+        let response = match name {
+            "get-ticket-price" => price_handler(state, parse(arguments)?).await,
+            "get-flight-options" => price_batch_handler(state, parse(arguments)?).await,
+            "book-flight" => {
+                // The MCP server takes the key as an argument too
+                if let Some(key) = arguments.get("idempotency_key").and_then(Value::as_str) {
+                    if !headers.contains_key(IDEMPOTENCY_KEY) {
+                        let key = HeaderValue::from_str(key)
+                            .map_err(|_| ToolError::new(StatusCode::BAD_REQUEST, "Invalid idempotency_key"))?;
+                        headers.insert(IDEMPOTENCY_KEY, key);
+                    }
+                }
+                book_handler(state, headers, parse(arguments)?).await
+            }
+            "get-seats" => {
+                let Json(query) = parse(arguments)?;
+                seats_handler(state, Query(query)).await
+            }
+            "select-seat" => select_seat_handler(state, parse(arguments)?).await,
+            "get-booking" => get_booking_handler(state, booking_id(&arguments)?).await,
+            "cancel-booking" => cancel_booking_handler(state, booking_id(&arguments)?).await,
+            "change-booking" => {
+                let id = booking_id(&arguments)?;
+                change_booking_handler(state, id, parse(arguments)?).await
+            }
+            "get-zk-input" => {
+                return zk_input_handler(state, parse(arguments)?).await.map_err(tool_error).and_then(to_value)
+            }
+            _ => return Err(ToolError::new(StatusCode::NOT_FOUND, format!("Unknown tool: {}", name))),
+        };
+        response.map_err(tool_error).and_then(to_value)
+    }
+}
+
+fn parse<T: DeserializeOwned>(arguments: Value) -> Result<Json<T>, ToolError> {
+    serde_json::from_value(arguments)
+        .map(Json)
+        .map_err(|e| {
+            ToolError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Invalid arguments: {}", e))
+                .with_code(codes::INVALID_ARGUMENTS)
+        })
+}
+
+fn booking_id(arguments: &Value) -> Result<Path<String>, ToolError> {
+    let Json(BookingId { booking_id }) = parse(arguments.clone())?;
+    Ok(Path(booking_id))
+}
+
+fn to_value<T: Serialize>(Json(response): Json<T>) -> Result<Value, ToolError> {
+    serde_json::to_value(response).map_err(|e| ToolError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// A handler's error as a tool error; 422s carry pricing-core's `ValidationError` as JSON
+fn tool_error((status, body): (StatusCode, String)) -> ToolError {
+    match serde_json::from_str::<ValidationError>(&body) {
+        Ok(e) => ToolError::new(status, e.to_string()).with_code(e.code.as_str()),
+        Err(_) => ToolError::new(status, body),
+    }
+}
@@ -1,32 +1,238 @@
-/// ZK Input Adapter for Agent B
-/// 
-/// This module provides utilities to convert HTTP request formats
-/// to zkVM input formats. This keeps Agent B's internal zkVM structure
-/// private while allowing external agents to interact via simple JSON.
+//! ZK Input Adapter for Agent B
+//!
+//! This module provides utilities to convert HTTP request formats
+//! to zkVM input formats. This keeps Agent B's internal zkVM structure
+//! private while allowing external agents to interact via simple JSON.
+//!
+//! The accepted JSON per endpoint is described by [`ENDPOINTS`], served at
+//! GET /zk-input/schema; input that doesn't match it is rejected with an [`InputError`].
 
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::Value;
-use pricing_core::{pricing, booking, RpcCall};
+use pricing_core::{pricing, booking, cancellation, RpcCall};
+
+/// One field of an endpoint's `input`
+#[derive(Serialize)]
+pub struct FieldSchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct EndpointSchema {
+    pub endpoint: &'static str,
+    /// Guest call the input runs as
+    pub rpc_call: &'static str,
+    pub description: &'static str,
+    /// "object", or "array" of objects with these fields
+    pub input: &'static str,
+    pub fields: &'static [FieldSchema],
+    /// Most elements an "array" input may have
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+}
+
+const fn field(name: &'static str, ty: &'static str, required: bool, description: &'static str) -> FieldSchema {
+    FieldSchema { name, ty, required, description }
+}
+
+const PRICE_FIELDS: &[FieldSchema] = &[
+    field("from", "string", true, "Three-letter city code, e.g. NYC"),
+    field("to", "string", true, "Three-letter city code, different from `from`"),
+    field("vip", "boolean", true, "Applies the VIP discount"),
+    field("quote_id", "string", false, "Quote being proven, from /price"),
+    field("departure_date", "string", false, "YYYY-MM-DD"),
+    field("return_date", "string", false, "YYYY-MM-DD; prices the round trip"),
+    field("purchase_date", "string", false, "YYYY-MM-DD, from /price; sets the advance-purchase tier"),
+    field("currency", "string", false, "USD, EUR, GBP, or JPY; USD if unset"),
+    field("cabin", "string", false, "economy (default), premium, business, or first"),
+    field("fare_family", "string", false, "non_refundable (default) or refundable"),
+    field("promo_code", "string", false, "Promo code; an unknown or inapplicable code fails the call"),
+];
+
+const BOOK_FIELDS: &[FieldSchema] = &[
+    field("from", "string", true, "Three-letter city code, e.g. NYC"),
+    field("to", "string", true, "Three-letter city code, different from `from`"),
+    field("passenger_name", "string", true, "1-100 letters, spaces, hyphens, apostrophes, or periods"),
+    field("passenger_email", "string", true, "local@domain.tld"),
+    field("cabin", "string", false, "economy (default), premium, business, or first"),
+    field("fare_family", "string", false, "non_refundable (default) or refundable"),
+    field("departure_date", "string", false, "YYYY-MM-DD; the flight `seat` is checked against"),
+    field("seat", "string", false, "Seat from GET /seats, e.g. 12C"),
+    field("pii_salt", "string", true, "32-byte hex salt of the passenger commitment, from /book"),
+];
+
+const CANCEL_FIELDS: &[FieldSchema] = &[
+    field("booking_id", "string", true, "Booking being cancelled"),
+    field("fare_cents", "integer", true, "Fare paid, in `currency`'s minor units"),
+    field("currency", "string", true, "Currency of `fare_cents`"),
+    field("fare_family", "string", false, "non_refundable (default) or refundable"),
+    field("departure_date", "string", false, "YYYY-MM-DD; without it the last-minute fee applies"),
+    field("cancel_date", "string", true, "YYYY-MM-DD"),
+];
+
+/// Every endpoint `/zk-input` accepts
+pub const ENDPOINTS: &[EndpointSchema] = &[
+    EndpointSchema {
+        endpoint: "price",
+        rpc_call: "GetPrice",
+        description: "Price one itinerary; the /price body plus its quote_id and purchase_date",
+        input: "object",
+        fields: PRICE_FIELDS,
+        max_items: None,
+    },
+    EndpointSchema {
+        endpoint: "price_batch",
+        rpc_call: "GetPriceBatch",
+        description: "Compare options and pick the cheapest; the `zk_input.input` returned by /price/batch",
+        input: "array",
+        fields: PRICE_FIELDS,
+        max_items: Some(pricing::MAX_BATCH_OPTIONS),
+    },
+    EndpointSchema {
+        endpoint: "book",
+        rpc_call: "BookFlight",
+        description: "Book a flight; the body sent to /book, without price_cents and currency",
+        input: "object",
+        fields: BOOK_FIELDS,
+        max_items: None,
+    },
+    EndpointSchema {
+        endpoint: "cancel",
+        rpc_call: "CancelBooking",
+        description: "Cancellation fee and refund; the `zk_input.input` returned by /booking/:id/cancel",
+        input: "object",
+        fields: CANCEL_FIELDS,
+        max_items: None,
+    },
+];
+
+/// Why `/zk-input` couldn't convert a request, returned as the 422 body
+#[derive(Serialize, Debug)]
+pub struct InputError {
+    /// "unknown_endpoint" or "invalid_input"
+    pub code: &'static str,
+    pub endpoint: String,
+    pub message: String,
+    /// Required fields absent from `input`; `[i].field` for array inputs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_fields: Vec<String>,
+    /// Set on unknown_endpoint
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub supported_endpoints: Vec<&'static str>,
+}
+
+impl InputError {
+    fn invalid(endpoint: &str, message: String) -> Self {
+        Self {
+            code: "invalid_input",
+            endpoint: endpoint.to_string(),
+            message,
+            missing_fields: Vec::new(),
+            supported_endpoints: Vec::new(),
+        }
+    }
+}
+
+fn schema(endpoint: &str) -> Result<&'static EndpointSchema, InputError> {
+    ENDPOINTS.iter().find(|s| s.endpoint == endpoint).ok_or_else(|| InputError {
+        code: "unknown_endpoint",
+        endpoint: endpoint.to_string(),
+        message: format!("Unknown endpoint: {}", endpoint),
+        missing_fields: Vec::new(),
+        supported_endpoints: ENDPOINTS.iter().map(|s| s.endpoint).collect(),
+    })
+}
+
+/// Required fields of `schema` that `input` lacks, or why its shape is wrong
+fn missing_fields(schema: &EndpointSchema, input: &Value) -> Result<Vec<String>, String> {
+    let missing_in = |object: &serde_json::Map<String, Value>, prefix: &str| -> Vec<String> {
+        schema
+            .fields
+            .iter()
+            .filter(|f| f.required && !matches!(object.get(f.name), Some(v) if !v.is_null()))
+            .map(|f| format!("{}{}", prefix, f.name))
+            .collect()
+    };
+    match (schema.input, input) {
+        ("object", Value::Object(object)) => Ok(missing_in(object, "")),
+        ("array", Value::Array(items)) => {
+            if let Some(max) = schema.max_items.filter(|max| items.len() > *max) {
+                return Err(format!("input has {} items, at most {} allowed", items.len(), max));
+            }
+            let mut missing = Vec::new();
+            for (i, item) in items.iter().enumerate() {
+                let object = item.as_object().ok_or_else(|| format!("input[{}] must be a JSON object", i))?;
+                missing.extend(missing_in(object, &format!("[{}].", i)));
+            }
+            Ok(missing)
+        }
+        ("array", _) => Err("input must be a JSON array".to_string()),
+        _ => Err("input must be a JSON object".to_string()),
+    }
+}
 
 /// Convert generic JSON input to Agent B's internal RpcCall format
 /// This allows Agent A to send simple JSON without knowing RpcCall structure
-pub fn json_to_rpc_call(endpoint: &str, input: &Value) -> Result<RpcCall, String> {
+pub fn json_to_rpc_call(endpoint: &str, input: &Value) -> Result<RpcCall, InputError> {
+    let schema = schema(endpoint)?;
+    let missing = missing_fields(schema, input).map_err(|e| InputError::invalid(endpoint, e))?;
+    if !missing.is_empty() {
+        return Err(InputError {
+            missing_fields: missing,
+            ..InputError::invalid(endpoint, format!("Missing required fields for {}", endpoint))
+        });
+    }
+
+    let parse_error = |e: serde_json::Error| InputError::invalid(endpoint, format!("Invalid {} input: {}", endpoint, e));
     match endpoint {
-        "price" => {
-            let req: pricing::Request = serde_json::from_value(input.clone())
-                .map_err(|e| format!("Invalid pricing input: {}", e))?;
-            Ok(RpcCall::GetPrice(req))
-        }
-        "book" => {
-            let req: booking::Request = serde_json::from_value(input.clone())
-                .map_err(|e| format!("Invalid booking input: {}", e))?;
-            Ok(RpcCall::BookFlight(req))
-        }
-        _ => Err(format!("Unknown endpoint: {}", endpoint))
+        "price" => serde_json::from_value::<pricing::Request>(input.clone())
+            .map(RpcCall::GetPrice)
+            .map_err(parse_error),
+        "price_batch" => serde_json::from_value::<Vec<pricing::Request>>(input.clone())
+            .map(RpcCall::GetPriceBatch)
+            .map_err(parse_error),
+        "book" => serde_json::from_value::<booking::Request>(input.clone())
+            .map(RpcCall::BookFlight)
+            .map_err(parse_error),
+        "cancel" => serde_json::from_value::<cancellation::Request>(input.clone())
+            .map(RpcCall::CancelBooking)
+            .map_err(parse_error),
+        _ => unreachable!("every endpoint in ENDPOINTS is handled"),
     }
 }
 
 /// Helper to serialize RpcCall to bincode bytes for zkVM
-pub fn rpc_call_to_bytes(call: &RpcCall) -> Vec<u8> {
-    bincode::serialize(call).expect("Failed to serialize RpcCall")
+pub fn rpc_call_to_bytes(call: &RpcCall) -> Result<Vec<u8>, String> {
+    bincode::serialize(call).map_err(|e| format!("Failed to serialize RpcCall: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rejects_unknown_endpoints_and_missing_fields() {
+        let err = json_to_rpc_call("refund", &json!({})).unwrap_err();
+        assert_eq!(err.code, "unknown_endpoint");
+        assert_eq!(err.supported_endpoints, ["price", "price_batch", "book", "cancel"]);
+
+        let err = json_to_rpc_call("book", &json!({"from": "NYC", "to": "LON", "passenger_name": null})).unwrap_err();
+        assert_eq!(err.code, "invalid_input");
+        assert_eq!(err.missing_fields, ["passenger_name", "passenger_email"]);
+
+        let err = json_to_rpc_call("price_batch", &json!([{"from": "NYC", "to": "LON", "vip": false}, {"from": "NYC"}])).unwrap_err();
+        assert_eq!(err.missing_fields, ["[1].to", "[1].vip"]);
+        assert!(json_to_rpc_call("price_batch", &json!({"from": "NYC"})).is_err());
+        assert!(json_to_rpc_call("price", &json!({"from": "NYC", "to": "LON", "vip": "yes"})).is_err());
+
+        let book = json!({"from": "NYC", "to": "LON", "passenger_name": "Jane Doe", "passenger_email": "jane@example.com", "seat": "12C", "pii_salt": "ab".repeat(32)});
+        assert!(matches!(json_to_rpc_call("book", &book), Ok(RpcCall::BookFlight(_))));
+        let cancel = json!({"booking_id": "BK1", "fare_cents": 60000, "currency": "USD", "cancel_date": "2026-11-01"});
+        assert!(matches!(json_to_rpc_call("cancel", &cancel), Ok(RpcCall::CancelBooking(_))));
+    }
 }
@@ -0,0 +1,329 @@
+//! The tool catalogue both Agent B binaries advertise at GET /tools (and the MCP server at
+//! `tools/list`): each tool's name, description for the LLM, and JSON Schema of its arguments
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::json;
+
+use pricing_core::pricing;
+
+/// Tool Definition
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "inputSchema")]
+    pub input_schema: serde_json::Value,
+}
+
+/// Tools List Response
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ToolsResponse {
+    pub tools: Vec<ToolDefinition>,
+}
+
+/// Every tool an Agent B server may offer; a server lists the ones it implements
+pub fn definitions() -> ToolsResponse {
+    let cabin = json!({
+        "type": "string",
+        "enum": ["economy", "premium", "business", "first"],
+        "description": "Cabin class (optional, default economy)"
+    });
+    let fare_family = json!({
+        "type": "string",
+        "enum": ["non_refundable", "refundable"],
+        "description": "Fare family (optional, default non_refundable); refundable fares cost more"
+    });
+
+    ToolsResponse {
+        tools: vec![
+            ToolDefinition {
+                name: "get-ticket-price".to_string(),
+                description: "Get flight ticket pricing based on route and passenger tier; returns a quote_id valid until expires_at".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "Departure city code (e.g., NYC)"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Destination city code (e.g., LON)"
+                        },
+                        "vip": {
+                            "type": "boolean",
+                            "description": "Whether passenger is VIP (optional, default false)"
+                        },
+                        "departure_date": {
+                            "type": "string",
+                            "description": "Departure day as YYYY-MM-DD (optional); weekends, peak season, and booking under three weeks ahead cost more, 60+ days ahead costs less. No flights on Dec 25 or Jan 1"
+                        },
+                        "return_date": {
+                            "type": "string",
+                            "description": "Return day as YYYY-MM-DD (optional, needs departure_date); quotes the round trip"
+                        },
+                        "currency": {
+                            "type": "string",
+                            "enum": ["USD", "EUR", "GBP", "JPY"],
+                            "description": "Currency to quote in (optional, default USD)"
+                        },
+                        "cabin": cabin.clone(),
+                        "fare_family": fare_family.clone(),
+                        "promo_code": {
+                            "type": "string",
+                            "description": "Promo code to apply (optional); check it with validate-promo first"
+                        }
+                    },
+                    "required": ["from", "to"]
+                }),
+            },
+            ToolDefinition {
+                name: "get-flight-options".to_string(),
+                description: format!(
+                    "Price up to {} routes/dates side by side and point out the cheapest; options that can't be priced are listed with the reason. Not a quote: price the chosen option with get-ticket-price before booking",
+                    pricing::MAX_BATCH_OPTIONS
+                ),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "options": {
+                            "type": "array",
+                            "description": "Options to compare, in the order to list them",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "from": {"type": "string", "description": "Departure city code (e.g., NYC)"},
+                                    "to": {"type": "string", "description": "Destination city code (e.g., LON)"},
+                                    "vip": {"type": "boolean"},
+                                    "departure_date": {"type": "string", "description": "YYYY-MM-DD (optional)"},
+                                    "return_date": {"type": "string", "description": "YYYY-MM-DD (optional); compares the round trip"},
+                                    "currency": {
+                                        "type": "string",
+                                        "enum": ["USD", "EUR", "GBP", "JPY"],
+                                        "description": "Same for every option (optional, default USD)"
+                                    },
+                                    "cabin": cabin.clone(),
+                                    "fare_family": fare_family.clone(),
+                                    "promo_code": {"type": "string"}
+                                },
+                                "required": ["from", "to"]
+                            }
+                        }
+                    },
+                    "required": ["options"]
+                }),
+            },
+            ToolDefinition {
+                name: "validate-promo".to_string(),
+                description: "Check whether a promo code applies to a fare and what it takes off, without issuing a quote".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "promo_code": {
+                            "type": "string",
+                            "description": "Promo code the traveller gave"
+                        },
+                        "from": {
+                            "type": "string",
+                            "description": "Departure city code (e.g., NYC)"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Destination city code (e.g., LON)"
+                        },
+                        "vip": {
+                            "type": "boolean",
+                            "description": "Whether passenger is VIP (optional, default false)"
+                        },
+                        "departure_date": {
+                            "type": "string",
+                            "description": "Departure day as YYYY-MM-DD (required by date-limited codes)"
+                        },
+                        "return_date": {
+                            "type": "string",
+                            "description": "Return day as YYYY-MM-DD (optional); checks the code against the round-trip fare"
+                        },
+                        "currency": {
+                            "type": "string",
+                            "enum": ["USD", "EUR", "GBP", "JPY"],
+                            "description": "Currency to quote in (optional, default USD)"
+                        },
+                        "cabin": cabin.clone(),
+                        "fare_family": fare_family.clone()
+                    },
+                    "required": ["promo_code", "from", "to"]
+                }),
+            },
+            ToolDefinition {
+                name: "book-flight".to_string(),
+                description: "Book a flight and generate confirmation".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "Departure city code"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Destination city code"
+                        },
+                        "passenger_name": {
+                            "type": "string",
+                            "description": "Full name of passenger"
+                        },
+                        "passenger_email": {
+                            "type": "string",
+                            "description": "Email address of passenger"
+                        },
+                        "cabin": cabin.clone(),
+                        "fare_family": fare_family,
+                        "departure_date": {
+                            "type": "string",
+                            "description": "Departure day as YYYY-MM-DD (optional); the flight the seat is on"
+                        },
+                        "seat": {
+                            "type": "string",
+                            "description": "Seat from get-seats, e.g. 12C (optional); must be free and in the booked cabin"
+                        },
+                        "price_cents": {
+                            "type": "integer",
+                            "description": "Fare paid, from get-ticket-price (optional); change and cancellation fees are shares of it"
+                        },
+                        "currency": {
+                            "type": "string",
+                            "description": "Currency of price_cents (optional, default USD)"
+                        },
+                        "idempotency_key": {
+                            "type": "string",
+                            "description": "Client-chosen key (optional); retries with the same key return the original booking instead of booking again"
+                        },
+                        "pii_salt": {
+                            "type": "string",
+                            "description": "32-byte hex salt for the passenger commitment (optional, fresh if unset); booking proofs reveal only the commitment"
+                        }
+                    },
+                    "required": ["from", "to", "passenger_name", "passenger_email"]
+                }),
+            },
+            ToolDefinition {
+                name: "get-seats".to_string(),
+                description: "Seat map of a flight: every seat with its cabin and whether it's free".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "Departure city code"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Destination city code"
+                        },
+                        "departure_date": {
+                            "type": "string",
+                            "description": "Departure day as YYYY-MM-DD (optional)"
+                        }
+                    },
+                    "required": ["from", "to"]
+                }),
+            },
+            ToolDefinition {
+                name: "select-seat".to_string(),
+                description: "Check that a seat is free and in the booked cabin; nothing is held, so pass it to book-flight".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "string",
+                            "description": "Departure city code"
+                        },
+                        "to": {
+                            "type": "string",
+                            "description": "Destination city code"
+                        },
+                        "departure_date": {
+                            "type": "string",
+                            "description": "Departure day as YYYY-MM-DD (optional)"
+                        },
+                        "cabin": cabin,
+                        "seat": {
+                            "type": "string",
+                            "description": "Seat, e.g. 12C"
+                        }
+                    },
+                    "required": ["from", "to", "seat"]
+                }),
+            },
+            ToolDefinition {
+                name: "get-booking".to_string(),
+                description: "Look up a booking made with book-flight".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "booking_id": {
+                            "type": "string",
+                            "description": "Booking id from book-flight"
+                        }
+                    },
+                    "required": ["booking_id"]
+                }),
+            },
+            ToolDefinition {
+                name: "cancel-booking".to_string(),
+                description: "Cancel a booking as of today; refundable fares are free to cancel a week out, non-refundable ones refund half a month out and nothing after".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "booking_id": {
+                            "type": "string",
+                            "description": "Booking id from book-flight"
+                        }
+                    },
+                    "required": ["booking_id"]
+                }),
+            },
+            ToolDefinition {
+                name: "change-booking".to_string(),
+                description: "Move a booking to another date and/or seat; non-refundable fares pay a change fee of 15%".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "booking_id": {
+                            "type": "string",
+                            "description": "Booking id from book-flight"
+                        },
+                        "departure_date": {
+                            "type": "string",
+                            "description": "New departure day as YYYY-MM-DD (optional)"
+                        },
+                        "seat": {
+                            "type": "string",
+                            "description": "New seat from get-seats (optional)"
+                        }
+                    },
+                    "required": ["booking_id"]
+                }),
+            },
+            ToolDefinition {
+                name: "get-zk-input".to_string(),
+                description: "zkVM input bytes to prove a result with, and the program_id to send them to the attester with; use the result's zk.endpoint".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "endpoint": {
+                            "type": "string",
+                            "enum": ["price", "price_batch", "book", "cancel"],
+                            "description": "zk.endpoint of the tool result being proven"
+                        },
+                        "input": {
+                            "description": "price: get-ticket-price's arguments plus the quote_id and purchase_date it returned; price_batch: get-flight-options' options with that purchase_date; book: book-flight's arguments; cancel: {booking_id, fare_cents, currency, fare_family, departure_date, cancel_date} of the cancelled booking"
+                        }
+                    },
+                    "required": ["endpoint", "input"]
+                }),
+            },
+        ],
+    }
+}
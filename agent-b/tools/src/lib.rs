@@ -0,0 +1,174 @@
+//! Agent B's tool discovery contract, shared by agent-b-server and agent-b-mcp-server
+//!
+//! Agent A discovers tools with GET `{server}/tools` and calls one with POST
+//! `{server}/tools/{name}`, getting back tool-envelope's `ToolResponse`. [`router`]
+//! serves both routes for any [`ToolBackend`]: the listing is the catalogue in
+//! [`definitions`] narrowed to the tools the backend implements, and calls are dispatched
+//! to it by name, so either binary can stand in for the other behind AGENT_B_MCP_URL.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Json, Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use tool_envelope::{codes, ToolResponse};
+
+mod definitions;
+pub use definitions::{definitions, ToolDefinition, ToolsResponse};
+
+/// A server's implementations of the tools in the catalogue
+#[async_trait]
+pub trait ToolBackend: Send + Sync + 'static {
+    /// Whether this server implements `name`; GET /tools lists only these
+    fn offers(&self, name: &str) -> bool;
+
+    /// Run tool `name` (one it offers); `headers` are the HTTP request's, e.g. Idempotency-Key.
+    /// The result becomes the envelope's `data`.
+    async fn call(self: Arc<Self>, name: &str, arguments: Value, headers: HeaderMap) -> Result<Value, ToolError>;
+}
+
+/// A failed tool call: the HTTP status and the envelope's `error` (and `code`)
+#[derive(Debug)]
+pub struct ToolError {
+    pub status: StatusCode,
+    pub message: String,
+    /// Machine-readable reason, from `tool_envelope::codes` or e.g. "invalid_email"
+    pub code: Option<String>,
+}
+
+impl ToolError {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self { status, message: message.into(), code: None }
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+}
+
+/// The tools `backend` offers, in catalogue order
+pub fn offered<B: ToolBackend>(backend: &B) -> ToolsResponse {
+    ToolsResponse {
+        tools: definitions().tools.into_iter().filter(|tool| backend.offers(&tool.name)).collect(),
+    }
+}
+
+/// GET /tools and POST /tools/:name for `backend`, to merge into a server's router.
+/// Routes the server registers itself (e.g. typed `/tools/get-ticket-price`) take precedence
+/// over the `:name` dispatch.
+pub fn router<S, B>(backend: Arc<B>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    B: ToolBackend,
+{
+    Router::new()
+        .route("/tools", get(list_tools::<B>))
+        .route("/tools/:name", post(call_tool::<B>))
+        .with_state(backend)
+}
+
+async fn list_tools<B: ToolBackend>(State(backend): State<Arc<B>>) -> Json<ToolsResponse> {
+    tracing::info!("[LIST TOOLS] Received request to list available tools");
+    Json(offered(backend.as_ref()))
+}
+
+async fn call_tool<B: ToolBackend>(
+    State(backend): State<Arc<B>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(arguments): Json<Value>,
+) -> (StatusCode, Json<ToolResponse<Value>>) {
+    let result = if backend.offers(&name) {
+        backend.call(&name, arguments, headers).await
+    } else {
+        Err(ToolError::new(StatusCode::NOT_FOUND, format!("Unknown tool: {}", name)).with_code(codes::UNKNOWN_TOOL))
+    };
+    match result {
+        Ok(data) => (StatusCode::OK, Json(ToolResponse::ok(data))),
+        Err(e) => {
+            tracing::warn!("[TOOL {}] {}", name, e.message);
+            let response = ToolResponse::err(e.message);
+            (e.status, Json(ToolResponse { code: e.code, ..response }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    /// Offers only get-seats, echoing its arguments
+    struct Seats;
+
+    #[async_trait]
+    impl ToolBackend for Seats {
+        fn offers(&self, name: &str) -> bool {
+            name == "get-seats"
+        }
+
+        async fn call(self: Arc<Self>, _name: &str, arguments: Value, _headers: HeaderMap) -> Result<Value, ToolError> {
+            match arguments.get("from") {
+                Some(_) => Ok(arguments),
+                None => Err(ToolError::new(StatusCode::UNPROCESSABLE_ENTITY, "from is required").with_code("invalid_airport")),
+            }
+        }
+    }
+
+    async fn send(request: Request<Body>) -> (StatusCode, Value) {
+        let response = router::<(), _>(Arc::new(Seats)).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn post(tool: &str, arguments: Value) -> Request<Body> {
+        Request::post(format!("/tools/{}", tool))
+            .header("content-type", "application/json")
+            .body(Body::from(arguments.to_string()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_catalogue_names_are_unique() {
+        let tools = definitions().tools;
+        let mut names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), tools.len());
+    }
+
+    #[tokio::test]
+    async fn test_lists_only_offered_tools() {
+        let (status, body) = send(Request::get("/tools").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tools"].as_array().unwrap().len(), 1);
+        assert_eq!(body["tools"][0]["name"], "get-seats");
+        assert!(body["tools"][0]["inputSchema"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_dispatches_by_name_in_the_envelope() {
+        let (status, body) = send(post("get-seats", json!({ "from": "NYC", "to": "LON" }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "success": true, "data": { "from": "NYC", "to": "LON" }, "error": null }));
+
+        let (status, body) = send(post("get-seats", json!({}))).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["code"], "invalid_airport");
+
+        let (status, body) = send(post("book-flight", json!({}))).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Unknown tool: book-flight");
+        assert_eq!(body["code"], codes::UNKNOWN_TOOL);
+    }
+}
@@ -0,0 +1,74 @@
+//! Typed service configuration shared by Agent A (server + client) and Agent B
+//!
+//! Each service defines its own `Deserialize` config struct and loads it with [`load`]:
+//! values come from an optional TOML file, then environment variables override them.
+//! Env keys are the existing variable names lower-cased (`AGENT_B_URL` → `agent_b_url`),
+//! so deployments that only set env vars keep working.
+
+use figment::{
+    providers::{Env, Format, Toml},
+    Figment,
+};
+use serde::de::DeserializeOwned;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to load config: {0}")]
+    Load(#[from] Box<figment::Error>),
+
+    #[error("invalid config `{field}`: {reason}")]
+    Invalid { field: &'static str, reason: String },
+}
+
+/// Startup checks run after loading
+pub trait Validate {
+    fn validate(&self) -> Result<(), ConfigError>;
+}
+
+/// Load `T` from the TOML file named by `$path_env` (falling back to `default_path`,
+/// which may be absent) plus environment overrides, then validate it
+pub fn load<T: DeserializeOwned + Validate>(
+    path_env: &str,
+    default_path: &str,
+) -> Result<T, ConfigError> {
+    let path = std::env::var(path_env).unwrap_or_else(|_| default_path.to_string());
+
+    let config: T = Figment::new()
+        .merge(Toml::file(&path))
+        .merge(Env::raw().lowercase(true))
+        .extract()
+        .map_err(Box::new)?;
+
+    config.validate()?;
+    Ok(config)
+}
+
+pub fn invalid(field: &'static str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid { field, reason: reason.into() }
+}
+
+/// Require an http(s) URL
+pub fn check_url(field: &'static str, value: &str) -> Result<(), ConfigError> {
+    let url = url::Url::parse(value).map_err(|e| invalid(field, format!("{} ({})", e, value)))?;
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        other => Err(invalid(field, format!("unsupported scheme `{}`", other))),
+    }
+}
+
+/// Require 0x-prefixed hex of exactly `len` bytes
+pub fn check_hex(field: &'static str, value: &str, len: usize) -> Result<(), ConfigError> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() != len * 2 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid(field, format!("expected {} hex bytes", len)));
+    }
+    Ok(())
+}
+
+/// Require a non-empty value
+pub fn check_present(field: &'static str, value: Option<&str>) -> Result<(), ConfigError> {
+    match value {
+        Some(v) if !v.trim().is_empty() => Ok(()),
+        _ => Err(invalid(field, "is required")),
+    }
+}
@@ -0,0 +1,270 @@
+//! Optional TLS and mTLS for the services' HTTP listeners and the calls between them
+//!
+//! Everything is off unless configured, and then the same variables work for every
+//! service (each reads them from its own environment):
+//!
+//! - `TLS_CERT` + `TLS_KEY` (PEM files) serve HTTPS instead of plain HTTP.
+//! - `TLS_CLIENT_CA` additionally requires callers to present a certificate signed by that
+//!   CA (mTLS). The verified certificate's common name reaches handlers as a
+//!   [`PeerIdentity`] request extension; the attester uses it to tell Agent B (ELF
+//!   registration) from Agent A (attestation).
+//! - `TLS_CA` is an extra trusted root for outbound `https://` calls (a private CA), and
+//!   `TLS_CLIENT_CERT` + `TLS_CLIENT_KEY` are the certificate presented to servers that ask.
+//!
+//! Agent A and Agent B read these through their figment config (`#[serde(flatten)]` a
+//! [`TlsConfig`]); the Agent B MCP server and the attester use [`TlsConfig::from_env`].
+
+use axum::Router;
+use axum_server::accept::Accept;
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use futures::future::BoxFuture;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use serde::Deserialize;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tower_http::add_extension::AddExtension;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("{0} and {1} must be set together")]
+    Pair(&'static str, &'static str),
+
+    #[error("tls_client_ca needs tls_cert and tls_key: client certificates are only checked over TLS")]
+    ClientCaWithoutCert,
+
+    #[error("failed to read {path}: {source}")]
+    Read { path: String, source: io::Error },
+
+    #[error("no {what} found in {path}")]
+    Missing { what: &'static str, path: String },
+
+    #[error("invalid TLS setup: {0}")]
+    Rustls(#[from] rustls::Error),
+
+    #[error("invalid client CA: {0}")]
+    ClientCa(#[from] rustls::server::VerifierBuilderError),
+
+    #[error("invalid client TLS setup: {0}")]
+    Client(#[from] reqwest::Error),
+}
+
+/// PEM file paths; all optional
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TlsConfig {
+    /// Server certificate chain
+    pub tls_cert: Option<String>,
+    /// Server private key
+    pub tls_key: Option<String>,
+    /// CA client certificates must chain to (enables mTLS)
+    pub tls_client_ca: Option<String>,
+    /// Extra root trusted for outbound calls
+    pub tls_ca: Option<String>,
+    /// Certificate presented on outbound calls
+    pub tls_client_cert: Option<String>,
+    pub tls_client_key: Option<String>,
+}
+
+impl TlsConfig {
+    /// From `TLS_CERT`, `TLS_KEY`, `TLS_CLIENT_CA`, `TLS_CA`, `TLS_CLIENT_CERT`, and
+    /// `TLS_CLIENT_KEY`
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+        Self {
+            tls_cert: var("TLS_CERT"),
+            tls_key: var("TLS_KEY"),
+            tls_client_ca: var("TLS_CLIENT_CA"),
+            tls_ca: var("TLS_CA"),
+            tls_client_cert: var("TLS_CLIENT_CERT"),
+            tls_client_key: var("TLS_CLIENT_KEY"),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), TlsError> {
+        if self.tls_cert.is_some() != self.tls_key.is_some() {
+            return Err(TlsError::Pair("tls_cert", "tls_key"));
+        }
+        if self.tls_client_cert.is_some() != self.tls_client_key.is_some() {
+            return Err(TlsError::Pair("tls_client_cert", "tls_client_key"));
+        }
+        if self.tls_client_ca.is_some() && self.tls_cert.is_none() {
+            return Err(TlsError::ClientCaWithoutCert);
+        }
+        Ok(())
+    }
+
+    /// Whether the listener serves HTTPS
+    pub fn is_enabled(&self) -> bool {
+        self.tls_cert.is_some()
+    }
+
+    /// Whether callers must present a client certificate
+    pub fn requires_client_cert(&self) -> bool {
+        self.tls_client_ca.is_some()
+    }
+
+    /// "https" or "http", for startup banners
+    pub fn scheme(&self) -> &'static str {
+        if self.is_enabled() {
+            "https"
+        } else {
+            "http"
+        }
+    }
+
+    /// rustls server setup, or `None` to serve plain HTTP
+    pub fn server_config(&self) -> Result<Option<ServerConfig>, TlsError> {
+        let (Some(cert), Some(key)) = (&self.tls_cert, &self.tls_key) else {
+            return Ok(None);
+        };
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ServerConfig::builder_with_provider(provider.clone()).with_safe_default_protocol_versions()?;
+        let builder = match &self.tls_client_ca {
+            Some(ca) => {
+                let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(root_store(ca)?), provider).build()?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+        let mut config = builder.with_single_cert(read_certs(cert)?, read_key(key)?)?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(Some(config))
+    }
+
+    /// `builder` with the extra root and client certificate, if configured
+    pub fn client(&self, builder: reqwest::ClientBuilder) -> Result<reqwest::ClientBuilder, TlsError> {
+        let mut builder = builder.use_rustls_tls();
+        if let Some(ca) = &self.tls_ca {
+            for cert in reqwest::Certificate::from_pem_bundle(&read(ca)?)? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if let (Some(cert), Some(key)) = (&self.tls_client_cert, &self.tls_client_key) {
+            let mut pem = read(cert)?;
+            pem.extend(read(key)?);
+            builder = builder.identity(reqwest::Identity::from_pem(&pem)?);
+        }
+        Ok(builder)
+    }
+}
+
+/// Serve `app` on `addr`, over TLS when `tls` has a certificate
+pub async fn serve(addr: SocketAddr, app: Router, tls: &TlsConfig) -> io::Result<()> {
+    let config = tls.server_config().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    match config {
+        Some(config) => {
+            let acceptor = PeerAcceptor(RustlsAcceptor::new(RustlsConfig::from_config(Arc::new(config))));
+            axum_server::bind(addr).acceptor(acceptor).serve(app.into_make_service()).await
+        }
+        None => axum::serve(tokio::net::TcpListener::bind(addr).await?, app).await,
+    }
+}
+
+/// The caller's verified client certificate, set on every request of an mTLS connection
+#[derive(Debug, Clone, Default)]
+pub struct PeerIdentity {
+    /// Subject common name, e.g. "agent-b"
+    pub common_name: Option<String>,
+}
+
+impl PeerIdentity {
+    fn from_certs(certs: Option<&[CertificateDer<'_>]>) -> Self {
+        let common_name = certs.and_then(|certs| certs.first()).and_then(|leaf| {
+            let (_, cert) = x509_parser::parse_x509_certificate(leaf).ok()?;
+            let name = cert.subject().iter_common_name().next()?.as_str().ok()?.to_string();
+            Some(name)
+        });
+        Self { common_name }
+    }
+
+    /// Whether the caller is one of `allowed` (compared by common name); an empty list
+    /// allows anyone
+    pub fn is_allowed(&self, allowed: &[String]) -> bool {
+        allowed.is_empty() || self.common_name.as_ref().is_some_and(|name| allowed.contains(name))
+    }
+}
+
+/// TLS handshake, then tag the connection's requests with the client's [`PeerIdentity`]
+#[derive(Clone)]
+struct PeerAcceptor(RustlsAcceptor);
+
+impl<I, S> Accept<I, S> for PeerAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = <RustlsAcceptor as Accept<I, S>>::Stream;
+    type Service = AddExtension<S, PeerIdentity>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let inner = self.0.clone();
+        Box::pin(async move {
+            let (stream, service) = inner.accept(stream, service).await?;
+            let peer = PeerIdentity::from_certs(stream.get_ref().1.peer_certificates());
+            Ok((stream, AddExtension::new(service, peer)))
+        })
+    }
+}
+
+fn read(path: &str) -> Result<Vec<u8>, TlsError> {
+    std::fs::read(path).map_err(|source| TlsError::Read { path: path.to_string(), source })
+}
+
+fn read_certs(path: &str) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let certs = rustls_pemfile::certs(&mut read(path)?.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|source| TlsError::Read { path: path.to_string(), source })?;
+    if certs.is_empty() {
+        return Err(TlsError::Missing { what: "certificate", path: path.to_string() });
+    }
+    Ok(certs)
+}
+
+fn read_key(path: &str) -> Result<PrivateKeyDer<'static>, TlsError> {
+    rustls_pemfile::private_key(&mut read(path)?.as_slice())
+        .map_err(|source| TlsError::Read { path: path.to_string(), source })?
+        .ok_or_else(|| TlsError::Missing { what: "private key", path: path.to_string() })
+}
+
+fn root_store(path: &str) -> Result<RootCertStore, TlsError> {
+    let mut roots = RootCertStore::empty();
+    for cert in read_certs(path)? {
+        roots.add(cert)?;
+    }
+    Ok(roots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_requires_pairs() {
+        assert!(TlsConfig::default().validate().is_ok());
+        assert!(!TlsConfig::default().is_enabled());
+
+        let half = TlsConfig { tls_cert: Some("cert.pem".into()), ..Default::default() };
+        assert!(matches!(half.validate(), Err(TlsError::Pair("tls_cert", "tls_key"))));
+
+        let mtls_only = TlsConfig { tls_client_ca: Some("ca.pem".into()), ..Default::default() };
+        assert!(matches!(mtls_only.validate(), Err(TlsError::ClientCaWithoutCert)));
+
+        let client = TlsConfig { tls_client_key: Some("key.pem".into()), ..Default::default() };
+        assert!(matches!(client.validate(), Err(TlsError::Pair("tls_client_cert", "tls_client_key"))));
+    }
+
+    #[test]
+    fn test_peer_allow_list() {
+        let agent_b = PeerIdentity { common_name: Some("agent-b".into()) };
+        let anonymous = PeerIdentity::default();
+        let allowed = vec!["agent-b".to_string()];
+        assert!(agent_b.is_allowed(&allowed));
+        assert!(!anonymous.is_allowed(&allowed));
+        assert!(anonymous.is_allowed(&[]));
+        assert!(!PeerIdentity { common_name: Some("agent-a".into()) }.is_allowed(&allowed));
+    }
+}
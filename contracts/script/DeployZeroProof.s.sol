@@ -20,13 +20,20 @@ contract DeployZeroProof is Script {
             address(0x0000000000000000000000000000000000000000) // TODO: Deploy Reclaim verifier first
         );
 
+        // Optional: SP1 PLONK verifier, registered under SP1_PLONK after deployment
+        address sp1PlonkVerifier = vm.envOr("SP1_PLONK_VERIFIER_ADDRESS", address(0));
+
         console.log("Deploying ZeroProof with:");
         console.log("  SP1 Verifier:", sp1Verifier);
         console.log("  Reclaim Verifier:", reclaimVerifier);
+        console.log("  SP1 PLONK Verifier:", sp1PlonkVerifier);
 
         vm.startBroadcast(deployerPrivateKey);
 
         ZeroProof zeroProof = new ZeroProof(sp1Verifier, reclaimVerifier);
+        if (sp1PlonkVerifier != address(0)) {
+            zeroProof.registerVerifier(zeroProof.SP1_PLONK(), sp1PlonkVerifier);
+        }
 
         vm.stopBroadcast();
 
@@ -19,6 +19,15 @@ contract ZeroProof is Ownable {
         bytes32 dataHash;       // Hash of the claimed data for verification
     }
 
+    /// @notice A verified claim kept on-chain as a durable audit anchor
+    struct ClaimRecord {
+        address agent;
+        bytes32 claimType;
+        bytes32 dataHash;
+        bytes32 vkHash;         // SP1 program verifying key; zero for other proof types
+        uint64 timestamp;       // Block time it was recorded at
+    }
+
     // ============ Storage ============
     
     /// @notice Registry mapping proof type ID to verifier contract address
@@ -28,9 +37,15 @@ contract ZeroProof is Ownable {
     /// @notice Track verified proofs to prevent replay attacks
     mapping(bytes32 => bool) public verifiedProofs;
 
+    /// @notice Claims recorded with recordClaim, by claimIdOf(agent, claimType, dataHash)
+    mapping(bytes32 => ClaimRecord) public claimRecords;
+
     // ============ Constants ============
     
     bytes32 public constant SP1_ZKVM = keccak256("sp1-zkvm");
+    /// @notice SP1 proofs wrapped in PLONK; registered separately since the SP1 PLONK
+    ///         verifier is a different contract from the Groth16 one
+    bytes32 public constant SP1_PLONK = keccak256("sp1-plonk");
     bytes32 public constant RECLAIM_ZKTLS = keccak256("reclaim-zktls");
 
     // ============ Events ============
@@ -43,6 +58,15 @@ contract ZeroProof is Ownable {
         uint256 timestamp
     );
 
+    event ClaimRecorded(
+        bytes32 indexed claimId,
+        address indexed agent,
+        bytes32 indexed claimType,
+        bytes32 dataHash,
+        bytes32 vkHash,
+        uint256 timestamp
+    );
+
     event VerifierRegistered(
         bytes32 indexed proofType,
         address indexed verifier,
@@ -60,6 +84,9 @@ contract ZeroProof is Ownable {
     error ProofAlreadyVerified(bytes32 proofHash);
     error VerificationFailed();
     error InvalidVerifierAddress();
+    error ClaimAlreadyRecorded(bytes32 claimId);
+    error ClaimNotFound(bytes32 claimId);
+    error NotClaimAgent(address agent, address sender);
 
     // ============ Constructor ============
     
@@ -84,7 +111,7 @@ contract ZeroProof is Ownable {
     // ============ External Functions ============
     
     /// @notice Verify a proof using the appropriate verifier
-    /// @param proofType Type of proof (SP1_ZKVM or RECLAIM_ZKTLS)
+    /// @param proofType Type of proof (SP1_ZKVM, SP1_PLONK or RECLAIM_ZKTLS)
     /// @param proof Encoded proof data (format depends on proofType)
     /// @param claim Standardized claim structure
     /// @return verified True if proof is valid
@@ -93,47 +120,62 @@ contract ZeroProof is Ownable {
         bytes calldata proof,
         Claim calldata claim
     ) external returns (bool verified) {
-        // Check if verifier exists
-        address verifier = verifiers[proofType];
-        if (verifier == address(0)) {
-            revert UnsupportedProofType(proofType);
-        }
+        _verify(proofType, proof, claim);
+        return true;
+    }
 
-        // Calculate proof hash to prevent replay
-        bytes32 proofHash = keccak256(abi.encode(proofType, proof, claim));
-        if (verifiedProofs[proofHash]) {
-            revert ProofAlreadyVerified(proofHash);
+    /// @notice Record a verified claim as (agent, claimType, dataHash, vkHash, timestamp)
+    /// @dev Verifies the proof first unless verifyProof already accepted it. Only the claim's
+    ///      agent can record it, and each (agent, claimType, dataHash) can be recorded once,
+    ///      so nobody can take another agent's claim id by recording it first
+    /// @param proofType Type of proof (SP1_ZKVM, SP1_PLONK or RECLAIM_ZKTLS)
+    /// @param proof Encoded proof data (format depends on proofType)
+    /// @param claim Standardized claim structure
+    /// @return claimId Key of the record in claimRecords
+    function recordClaim(
+        bytes32 proofType,
+        bytes calldata proof,
+        Claim calldata claim
+    ) external returns (bytes32 claimId) {
+        if (claim.agent != msg.sender) {
+            revert NotClaimAgent(claim.agent, msg.sender);
         }
-
-        // Route to appropriate verifier
-        if (proofType == SP1_ZKVM) {
-            verified = _verifySP1Proof(verifier, proof, claim);
-        } else if (proofType == RECLAIM_ZKTLS) {
-            verified = _verifyReclaimProof(verifier, proof);
-        } else {
-            // For future proof types, attempt generic call
-            verified = _verifyGenericProof(verifier, proof);
+        claimId = claimIdOf(claim.agent, claim.claimType, claim.dataHash);
+        if (claimRecords[claimId].timestamp != 0) {
+            revert ClaimAlreadyRecorded(claimId);
         }
 
-        if (!verified) {
-            revert VerificationFailed();
+        if (!verifiedProofs[keccak256(abi.encode(proofType, proof, claim))]) {
+            _verify(proofType, proof, claim);
         }
 
-        // Mark as verified
-        verifiedProofs[proofHash] = true;
+        bytes32 vkHash;
+        if (proofType == SP1_ZKVM || proofType == SP1_PLONK) {
+            (vkHash, , ) = abi.decode(proof, (bytes32, bytes, bytes));
+        }
 
-        // Calculate claim hash for event
-        bytes32 claimHash = keccak256(abi.encode(claim));
+        claimRecords[claimId] = ClaimRecord({
+            agent: claim.agent,
+            claimType: claim.claimType,
+            dataHash: claim.dataHash,
+            vkHash: vkHash,
+            timestamp: uint64(block.timestamp)
+        });
+        emit ClaimRecorded(claimId, claim.agent, claim.claimType, claim.dataHash, vkHash, block.timestamp);
+    }
 
-        emit ProofVerified(
-            proofType,
-            claim.claimType,
-            claim.agent,
-            claimHash,
-            block.timestamp
-        );
+    /// @notice A claim recorded with recordClaim
+    /// @param claimId claimIdOf(agent, claimType, dataHash)
+    function getClaim(bytes32 claimId) external view returns (ClaimRecord memory record) {
+        record = claimRecords[claimId];
+        if (record.timestamp == 0) {
+            revert ClaimNotFound(claimId);
+        }
+    }
 
-        return true;
+    /// @notice Key of a claim in claimRecords
+    function claimIdOf(address agent, bytes32 claimType, bytes32 dataHash) public pure returns (bytes32) {
+        return keccak256(abi.encode(agent, claimType, dataHash));
     }
 
     /// @notice Register a new proof type verifier
@@ -173,9 +215,60 @@ contract ZeroProof is Ownable {
     }
 
     // ============ Internal Functions ============
+
+    /// @notice Verify a proof, mark it verified, and emit ProofVerified; reverts otherwise
+    function _verify(
+        bytes32 proofType,
+        bytes calldata proof,
+        Claim calldata claim
+    ) internal {
+        // Check if verifier exists
+        address verifier = verifiers[proofType];
+        if (verifier == address(0)) {
+            revert UnsupportedProofType(proofType);
+        }
+
+        // Calculate proof hash to prevent replay
+        bytes32 proofHash = keccak256(abi.encode(proofType, proof, claim));
+        if (verifiedProofs[proofHash]) {
+            revert ProofAlreadyVerified(proofHash);
+        }
+
+        // Route to appropriate verifier
+        bool ok;
+        if (proofType == SP1_ZKVM || proofType == SP1_PLONK) {
+            ok = _verifySP1Proof(verifier, proof, claim);
+        } else if (proofType == RECLAIM_ZKTLS) {
+            ok = _verifyReclaimProof(verifier, proof);
+        } else {
+            // For future proof types, attempt generic call
+            ok = _verifyGenericProof(verifier, proof);
+        }
+
+        if (!ok) {
+            revert VerificationFailed();
+        }
+
+        // Mark as verified
+        verifiedProofs[proofHash] = true;
+
+        // Calculate claim hash for event
+        bytes32 claimHash = keccak256(abi.encode(claim));
+
+        emit ProofVerified(
+            proofType,
+            claim.claimType,
+            claim.agent,
+            claimHash,
+            block.timestamp
+        );
+    }
     
     /// @notice Verify SP1 zkVM proof
     /// @dev Expects proof format: abi.encode(programVKey, publicValues, proofBytes)
+    /// @dev Agent B's publicValues are abi.encode(bytes32 inputHash, uint256 rulesetVersion,
+    ///      bytes32 rulesetHash, bytes claim), so dataHash also binds the claim to the sha256 of
+    ///      the input it was proven for and to the pricing rules it ran
     function _verifySP1Proof(
         address verifier,
         bytes calldata proof,
@@ -71,6 +71,33 @@ contract ZeroProofTest is Test {
         assertTrue(verified);
     }
 
+    function testVerifySP1PlonkProof() public {
+        bytes32 vkey = keccak256("test-program");
+        bytes memory publicValues = abi.encode(uint256(578));
+        bytes memory encodedProof = abi.encode(vkey, publicValues, new bytes(256));
+
+        ZeroProof.Claim memory claim = ZeroProof.Claim({
+            agent: bob,
+            claimType: keccak256("pricing"),
+            publicData: "",
+            dataHash: keccak256(publicValues)
+        });
+
+        // Not registered by the constructor
+        vm.expectRevert(abi.encodeWithSelector(ZeroProof.UnsupportedProofType.selector, zeroProof.SP1_PLONK()));
+        zeroProof.verifyProof(zeroProof.SP1_PLONK(), encodedProof, claim);
+
+        MockSP1Verifier plonkVerifier = new MockSP1Verifier();
+        zeroProof.registerVerifier(zeroProof.SP1_PLONK(), address(plonkVerifier));
+        assertTrue(zeroProof.verifyProof(zeroProof.SP1_PLONK(), encodedProof, claim));
+
+        vm.startPrank(bob);
+        bytes32 claimId = zeroProof.recordClaim(zeroProof.SP1_PLONK(), encodedProof, claim);
+        vm.stopPrank();
+        (, , , bytes32 vkHash, ) = zeroProof.claimRecords(claimId);
+        assertEq(vkHash, vkey);
+    }
+
     function testVerifyReclaimProof() public {
         // Create mock Reclaim proof
         IReclaimVerifier.Proof memory proof = IReclaimVerifier.Proof({
@@ -162,4 +189,86 @@ contract ZeroProofTest is Test {
         vm.expectRevert();
         zeroProof.registerVerifier(noirType, noirVerifier);
     }
+
+    function testRecordClaim() public {
+        bytes32 vkey = keccak256("test-program");
+        bytes memory publicValues = abi.encode(uint256(578));
+        bytes memory encodedProof = abi.encode(vkey, publicValues, new bytes(256));
+
+        ZeroProof.Claim memory claim = ZeroProof.Claim({
+            agent: bob,
+            claimType: keccak256("booking"),
+            publicData: publicValues,
+            dataHash: keccak256(publicValues)
+        });
+
+        // Recording after verifyProof doesn't verify again
+        zeroProof.verifyProof(zeroProof.SP1_ZKVM(), encodedProof, claim);
+        vm.warp(1_700_000_000);
+        vm.startPrank(bob);
+        bytes32 claimId = zeroProof.recordClaim(zeroProof.SP1_ZKVM(), encodedProof, claim);
+
+        assertEq(claimId, zeroProof.claimIdOf(bob, claim.claimType, claim.dataHash));
+        ZeroProof.ClaimRecord memory record = zeroProof.getClaim(claimId);
+        assertEq(record.agent, bob);
+        assertEq(record.claimType, keccak256("booking"));
+        assertEq(record.dataHash, keccak256(publicValues));
+        assertEq(record.vkHash, vkey);
+        assertEq(record.timestamp, 1_700_000_000);
+
+        vm.expectRevert(abi.encodeWithSelector(ZeroProof.ClaimAlreadyRecorded.selector, claimId));
+        zeroProof.recordClaim(zeroProof.SP1_ZKVM(), encodedProof, claim);
+        vm.stopPrank();
+
+        vm.expectRevert(abi.encodeWithSelector(ZeroProof.ClaimNotFound.selector, bytes32(0)));
+        zeroProof.getClaim(bytes32(0));
+    }
+
+    function testRecordClaimRejectsInvalidProof() public {
+        bytes memory publicValues = abi.encode(uint256(578));
+        bytes memory encodedProof = abi.encode(keccak256("test-program"), publicValues, new bytes(256));
+
+        ZeroProof.Claim memory claim = ZeroProof.Claim({
+            agent: bob,
+            claimType: keccak256("booking"),
+            publicData: publicValues,
+            dataHash: keccak256("something else")
+        });
+
+        vm.startPrank(bob);
+        vm.expectRevert(ZeroProof.VerificationFailed.selector);
+        zeroProof.recordClaim(zeroProof.SP1_ZKVM(), encodedProof, claim);
+        vm.stopPrank();
+    }
+
+    function testRecordClaimCannotBeSquatted() public {
+        bytes memory publicValues = abi.encode(uint256(578));
+        bytes memory encodedProof = abi.encode(keccak256("test-program"), publicValues, new bytes(256));
+
+        ZeroProof.Claim memory claim = ZeroProof.Claim({
+            agent: bob,
+            claimType: keccak256("booking"),
+            publicData: publicValues,
+            dataHash: keccak256(publicValues)
+        });
+        bytes32 bobId = zeroProof.claimIdOf(bob, claim.claimType, claim.dataHash);
+
+        // Alice front-runs bob's transaction: she can't record his claim...
+        vm.startPrank(alice);
+        vm.expectRevert(abi.encodeWithSelector(ZeroProof.NotClaimAgent.selector, bob, alice));
+        zeroProof.recordClaim(zeroProof.SP1_ZKVM(), encodedProof, claim);
+
+        // ...and recording the same proof as her own claim doesn't take his claim id
+        claim.agent = alice;
+        bytes32 aliceId = zeroProof.recordClaim(zeroProof.SP1_ZKVM(), encodedProof, claim);
+        vm.stopPrank();
+        assertTrue(aliceId != bobId);
+
+        claim.agent = bob;
+        vm.startPrank(bob);
+        assertEq(zeroProof.recordClaim(zeroProof.SP1_ZKVM(), encodedProof, claim), bobId);
+        vm.stopPrank();
+        assertEq(zeroProof.getClaim(bobId).agent, bob);
+        assertEq(zeroProof.getClaim(aliceId).agent, alice);
+    }
 }
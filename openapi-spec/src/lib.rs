@@ -0,0 +1,139 @@
+//! OpenAPI 3 specs for the tool servers' HTTP APIs
+//!
+//! Agent A's and Agent B's MCP servers describe their routes with [`SpecBuilder`], serve the
+//! result at /openapi.json, and point [`swagger_ui_html`] at it from /docs. Schemas come from
+//! the same `JsonSchema` derives used for the tool types, so a spec can't drift from the
+//! structs the handlers actually (de)serialize.
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+/// Collects operations and their component schemas
+pub struct SpecBuilder {
+    gen: SchemaGenerator,
+    paths: Map<String, Value>,
+}
+
+impl Default for SpecBuilder {
+    fn default() -> Self {
+        Self {
+            gen: SchemaSettings::openapi3().into_generator(),
+            paths: Map::new(),
+        }
+    }
+}
+
+impl SpecBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// GET operation returning `Res`
+    pub fn get<Res: JsonSchema>(mut self, path: &str, summary: &str) -> Self {
+        let response = self.gen.subschema_for::<Res>();
+        self.operation(
+            path,
+            "get",
+            json!({
+                "summary": summary,
+                "responses": { "200": json_content("OK", response) }
+            }),
+        );
+        self
+    }
+
+    /// POST operation taking `Req` and returning `Res` (errors use the same envelope)
+    pub fn post<Req: JsonSchema, Res: JsonSchema>(mut self, path: &str, summary: &str) -> Self {
+        let request = self.gen.subschema_for::<Req>();
+        let response = self.gen.subschema_for::<Res>();
+        self.operation(
+            path,
+            "post",
+            json!({
+                "summary": summary,
+                "requestBody": {
+                    "required": true,
+                    "content": { "application/json": { "schema": request } }
+                },
+                "responses": {
+                    "200": json_content("OK", response.clone()),
+                    "400": json_content("Request failed (success=false, error set)", response)
+                }
+            }),
+        );
+        self
+    }
+
+    /// Add `method` to the path item, next to any other methods already on it
+    fn operation(&mut self, path: &str, method: &str, operation: Value) {
+        if let Some(item) = self.paths.entry(path).or_insert_with(|| json!({})).as_object_mut() {
+            item.insert(method.to_string(), operation);
+        }
+    }
+
+    pub fn build(mut self, title: &str, version: &str) -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "info": { "title": title, "version": version },
+            "paths": self.paths,
+            "components": { "schemas": self.gen.take_definitions() }
+        })
+    }
+}
+
+fn json_content(description: &str, schema: impl serde::Serialize) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } }
+    })
+}
+
+/// Swagger UI page (assets from unpkg) pointed at `spec_url`
+pub fn swagger_ui_html(title: &str, spec_url: &str) -> String {
+    format!(
+        r##"<!DOCTYPE html>
+<html>
+<head>
+  <title>{title}</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>SwaggerUIBundle({{ url: "{spec_url}", dom_id: "#swagger-ui" }});</script>
+</body>
+</html>"##
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Booking {
+        booking_id: String,
+    }
+
+    #[test]
+    fn test_methods_share_a_path_item() {
+        let spec = SpecBuilder::new()
+            .get::<Booking>("/bookings", "List bookings")
+            .post::<Booking, Booking>("/bookings", "Create a booking")
+            .build("Test", "1.0");
+        let item = &spec["paths"]["/bookings"];
+        assert_eq!(item["get"]["summary"], "List bookings");
+        assert_eq!(item["post"]["summary"], "Create a booking");
+        assert_eq!(item["post"]["requestBody"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/Booking");
+        assert!(spec["components"]["schemas"]["Booking"].is_object());
+    }
+
+    #[test]
+    fn test_swagger_ui_html() {
+        let html = swagger_ui_html("Agent B", "/openapi.json");
+        assert!(html.contains("<title>Agent B</title>"));
+        assert!(html.contains(r##"SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });"##));
+    }
+}
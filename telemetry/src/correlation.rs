@@ -0,0 +1,78 @@
+//! Correlation IDs across Agent A, Agent B, and the attester
+//!
+//! Each inbound HTTP request gets an `x-request-id` (reused if the caller sent one).
+//! It is held in a task-local for the lifetime of the request so outbound `reqwest`
+//! calls can forward it with [`WithRequestId::with_request_id`].
+
+use axum::{
+    extract::Request,
+    http::{HeaderValue, Request as HttpRequest},
+    middleware::Next,
+    response::Response,
+};
+use std::future::Future;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Correlation ID of the request being handled, if any
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Run `f` with `id` as the current correlation ID
+pub async fn scope<F: Future>(id: String, f: F) -> F::Output {
+    REQUEST_ID.scope(id, f).await
+}
+
+/// Forward the current correlation ID and trace context on an outbound request
+pub trait WithRequestId {
+    fn with_request_id(self) -> Self;
+}
+
+impl WithRequestId for reqwest::RequestBuilder {
+    fn with_request_id(self) -> Self {
+        let builder = self.headers(crate::trace_headers());
+        match current() {
+            Some(id) => builder.header(REQUEST_ID_HEADER, id),
+            None => builder,
+        }
+    }
+}
+
+/// Axum middleware: accept or mint `x-request-id`, scope it, and echo it on the response
+pub async fn propagate(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        req.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    let mut response = scope(id.clone(), next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Span for `tower_http::trace::TraceLayer::make_span_with`, tagged with the correlation ID
+/// and joined to the caller's trace
+pub fn make_span<B>(req: &HttpRequest<B>) -> tracing::Span {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-");
+    let span = tracing::info_span!("request", method = %req.method(), uri = %req.uri(), request_id = %id);
+    crate::set_parent(&span, req.headers());
+    span
+}
@@ -0,0 +1,100 @@
+//! Logging and distributed tracing shared by Agent A, Agent B, and the attester
+//!
+//! Every service calls [`init`] once at startup instead of building its own
+//! `tracing_subscriber`. Logs work as before (`RUST_LOG`, default `info`). When
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` is set (e.g. `http://localhost:4317` for Jaeger or Tempo),
+//! spans are also exported over OTLP/gRPC under the service's name (`OTEL_SERVICE_NAME`
+//! overrides it).
+//!
+//! Traces cross services through the W3C `traceparent` header: outbound calls add
+//! [`trace_headers`] and inbound request spans adopt the caller's with [`set_parent`], so
+//! one booking shows up as a single trace from Agent A through Agent B to the attester.
+//! [`correlation`] adds an `x-request-id` alongside it for log lines.
+
+pub mod correlation;
+
+use opentelemetry::{global, trace::TracerProvider as _, KeyValue};
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use opentelemetry_sdk::{propagation::TraceContextPropagator, runtime, trace::TracerProvider, Resource};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+pub const ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Keeps the span exporter running; flushes pending spans when dropped
+pub struct Telemetry {
+    provider: Option<TracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Flushing trace spans: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the global subscriber; logs go to stderr with `stderr` set (for services that
+/// speak JSON-RPC on stdout). Must run inside the Tokio runtime.
+pub fn init(service: &str, stderr: bool) -> Telemetry {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let writer = if stderr { BoxMakeWriter::new(std::io::stderr) } else { BoxMakeWriter::new(std::io::stdout) };
+    let provider = std::env::var(ENDPOINT_ENV).ok().filter(|e| !e.is_empty()).and_then(|_| {
+        tracer_provider(service)
+            .map_err(|e| eprintln!("Tracing export disabled ({}): {}", ENDPOINT_ENV, e))
+            .ok()
+    });
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(service.to_string()))
+    });
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(writer))
+        .with(otel)
+        .init();
+
+    if let Some(provider) = &provider {
+        global::set_tracer_provider(provider.clone());
+    }
+    Telemetry { provider }
+}
+
+fn tracer_provider(service: &str) -> Result<TracerProvider, opentelemetry::trace::TraceError> {
+    // The exporter reads OTEL_EXPORTER_OTLP_ENDPOINT itself
+    let exporter = opentelemetry_otlp::SpanExporter::builder().with_tonic().build()?;
+    let name = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| service.to_string());
+    Ok(TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![KeyValue::new("service.name", name)]))
+        .build())
+}
+
+/// `traceparent` (and `tracestate`) for the current span, to send on an outbound request;
+/// empty when tracing export is off or there is no current span
+pub fn trace_headers() -> http::HeaderMap {
+    let mut headers = http::HeaderMap::new();
+    let context = tracing::Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(&mut headers))
+    });
+    headers
+}
+
+/// Make `span` a child of the caller's span from an inbound `traceparent`, if any
+pub fn set_parent(span: &tracing::Span, headers: &http::HeaderMap) {
+    let parent = global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    span.set_parent(parent);
+}
+
+/// Span for `tower_http::trace::TraceLayer::make_span_with` in services without
+/// correlation IDs, joined to the caller's trace
+pub fn http_span<B>(req: &http::Request<B>) -> tracing::Span {
+    let span = tracing::info_span!("request", method = %req.method(), uri = %req.uri());
+    set_parent(&span, req.headers());
+    span
+}
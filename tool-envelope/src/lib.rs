@@ -0,0 +1,134 @@
+//! The `{success, data, error, code}` envelope every tool server answers with
+//!
+//! Agent A's server, both Agent B servers, and the payment agent wrap tool results in
+//! [`ToolResponse`]; Agent A's client unwraps them with [`parse_tool_response`] and reads
+//! tool listings with [`tool_list`]. `code` is optional and machine-readable: one of
+//! [`codes`], or a server's own (Agent B passes pricing-core's validation codes through).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// `code` values shared across servers
+pub mod codes {
+    /// The arguments didn't deserialize into the tool's request
+    pub const INVALID_ARGUMENTS: &str = "invalid_arguments";
+    /// No tool by that name on this server
+    pub const UNKNOWN_TOOL: &str = "unknown_tool";
+    /// Missing or rejected credentials
+    pub const UNAUTHORIZED: &str = "unauthorized";
+}
+
+/// Standard Tool Response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ToolResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub error: Option<String>,
+    /// Machine-readable reason for a failure, e.g. "invalid_email"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl<T> ToolResponse<T> {
+    pub fn ok(data: T) -> Self {
+        Self {
+            success: true,
+            data: Some(data),
+            error: None,
+            code: None,
+        }
+    }
+
+    pub fn err(error: impl std::fmt::Display) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(error.to_string()),
+            code: None,
+        }
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+}
+
+/// Why a response carried no data
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EnvelopeError {
+    /// The tool ran and failed
+    #[error("Tool error: {message}")]
+    Tool { message: String, code: Option<String> },
+
+    #[error("Invalid server response: {0}")]
+    Malformed(&'static str),
+}
+
+impl EnvelopeError {
+    /// `code` of a tool failure
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            EnvelopeError::Tool { code, .. } => code.as_deref(),
+            EnvelopeError::Malformed(_) => None,
+        }
+    }
+}
+
+/// The `data` of a tool response, or its error
+///
+/// A non-null `error` (a string, or any JSON the payment agent sends) or `success: false`
+/// is a failure; otherwise `data` must be present, though it may be null.
+pub fn parse_tool_response(body: &Value) -> Result<&Value, EnvelopeError> {
+    let fields = body.as_object().ok_or(EnvelopeError::Malformed("not a JSON object"))?;
+    let code = fields.get("code").and_then(Value::as_str).map(str::to_string);
+    match fields.get("error") {
+        Some(Value::Null) | None => {}
+        Some(Value::String(message)) => return Err(EnvelopeError::Tool { message: message.clone(), code }),
+        Some(error) => return Err(EnvelopeError::Tool { message: error.to_string(), code }),
+    }
+    if fields.get("success").and_then(Value::as_bool) == Some(false) {
+        return Err(EnvelopeError::Tool { message: "failed without an error message".to_string(), code });
+    }
+    fields.get("data").ok_or(EnvelopeError::Malformed("neither data nor error"))
+}
+
+/// Tool definitions of a GET /tools response: `{tools}` from Agent A and Agent B, or
+/// enveloped as `{data: {tools}}` by the payment agent
+pub fn tool_list(body: &Value) -> Option<&Vec<Value>> {
+    body.get("tools").or_else(|| body.pointer("/data/tools")).and_then(Value::as_array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_tool_response() {
+        let ok = serde_json::to_value(ToolResponse::ok(json!({ "price_cents": 68_250 }))).unwrap();
+        assert_eq!(parse_tool_response(&ok).unwrap()["price_cents"], 68_250);
+        assert!(ok.get("code").is_none());
+
+        let failed = serde_json::to_value(ToolResponse::<()>::err("bad email").with_code("invalid_email")).unwrap();
+        let e = parse_tool_response(&failed).unwrap_err();
+        assert_eq!(e.to_string(), "Tool error: bad email");
+        assert_eq!(e.code(), Some("invalid_email"));
+
+        // The payment agent's shapes: structured errors, and no error field at all
+        let structured = json!({ "success": false, "error": { "reason": "DECLINED" } });
+        assert!(matches!(parse_tool_response(&structured), Err(EnvelopeError::Tool { message, .. }) if message.contains("DECLINED")));
+        assert_eq!(parse_tool_response(&json!({ "data": { "tokenId": "tok_1" } })).unwrap()["tokenId"], "tok_1");
+        assert!(parse_tool_response(&json!({ "success": false })).is_err());
+        assert_eq!(parse_tool_response(&json!({ "ok": true })), Err(EnvelopeError::Malformed("neither data nor error")));
+    }
+
+    #[test]
+    fn test_tool_list_reads_both_shapes() {
+        let tools = json!([{ "name": "get-ticket-price" }]);
+        assert_eq!(tool_list(&json!({ "tools": tools })), tools.as_array());
+        assert_eq!(tool_list(&json!({ "success": true, "data": { "tools": tools } })), tools.as_array());
+        assert!(tool_list(&json!({ "data": null })).is_none());
+    }
+}
@@ -0,0 +1,242 @@
+//! zeroproof-dev: the whole demo from one command
+//!
+//! Builds and starts the attester (SP1 mock prover), Agent B's booking server and MCP
+//! server, and Agent A's HTTP server with a generated local configuration, waits until
+//! each one answers, then opens the chat client in this terminal. Service logs go to
+//! `target/zeroproof-dev/<service>.log`; leaving the chat (or Ctrl-C) stops everything.
+//!
+//! The services are binaries in their own workspaces, so they run as child processes of
+//! this one. Agent A runs with CHAIN_MODE=local, which needs Foundry (`anvil` on PATH and
+//! `forge build` in contracts/). Agent B's guest ELFs (`cargo prove build` in
+//! agent-b/program) are only needed for proofs; pricing and booking work without them.
+//!
+//! Variables already set in the environment win over the generated ones, e.g.
+//! `ANTHROPIC_API_KEY=... cargo run -p zeroproof-dev` chats with Claude instead of the
+//! mock LLM.
+//!
+//!     cargo run -p zeroproof-dev              # services + chat
+//!     cargo run -p zeroproof-dev -- --no-chat # services only, until Ctrl-C
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+
+const ATTESTER_PORT: u16 = 8000;
+const AGENT_B_PORT: u16 = 8001;
+const AGENT_B_MCP_PORT: u16 = 8002;
+const AGENT_A_PORT: u16 = 3001;
+
+/// How long a service gets to answer its readiness URL
+const READY_TIMEOUT: Duration = Duration::from_secs(180);
+const READY_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Parser)]
+#[command(name = "zeroproof-dev", about = "Run the attester, Agent B, and Agent A locally, then chat")]
+struct Args {
+    /// Start the services and wait for Ctrl-C instead of opening the chat
+    #[arg(long)]
+    no_chat: bool,
+    /// Build and run release binaries
+    #[arg(long)]
+    release: bool,
+}
+
+struct Service {
+    name: &'static str,
+    /// Cargo workspace the binary is built in, relative to the repo root
+    workspace: &'static str,
+    bin: &'static str,
+    /// Answers 200 once the service is up
+    ready_url: String,
+    env: Vec<(&'static str, String)>,
+}
+
+fn services(root: &Path) -> Vec<Service> {
+    let attester_url = format!("http://localhost:{}", ATTESTER_PORT);
+    let artifact = root.join("contracts/out/ZeroProof.sol/ZeroProof.json");
+    vec![
+        Service {
+            name: "attester",
+            workspace: "zk-attestation-service",
+            bin: "attester",
+            ready_url: format!("{}/health", attester_url),
+            env: vec![("PROVER_MODE", "mock".to_string())],
+        },
+        Service {
+            name: "agent-b",
+            workspace: "agent-b",
+            bin: "agent-b-server",
+            ready_url: format!("http://localhost:{}/health", AGENT_B_PORT),
+            env: vec![("ATTESTER_URL", attester_url.clone())],
+        },
+        Service {
+            name: "agent-b-mcp",
+            workspace: "agent-b",
+            bin: "agent-b-mcp-server",
+            ready_url: format!("http://localhost:{}/tools", AGENT_B_MCP_PORT),
+            env: vec![
+                ("ATTESTER_URL", attester_url.clone()),
+                ("AGENT_B_MCP_PORT", AGENT_B_MCP_PORT.to_string()),
+            ],
+        },
+        Service {
+            name: "agent-a",
+            workspace: "agent-a",
+            bin: "agent-a-mcp",
+            ready_url: format!("http://localhost:{}/health", AGENT_A_PORT),
+            env: vec![
+                ("AGENT_A_MODE", "http".to_string()),
+                ("AGENT_A_SERVER_PORT", AGENT_A_PORT.to_string()),
+                ("AGENT_B_URL", format!("http://localhost:{}", AGENT_B_PORT)),
+                ("ATTESTER_URL", attester_url),
+                ("CHAIN_MODE", "local".to_string()),
+                ("ZEROPROOF_ARTIFACT", artifact.display().to_string()),
+            ],
+        },
+    ]
+}
+
+/// Settings for the chat client
+fn chat_env() -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("AGENT_A_SERVER_URL", format!("http://localhost:{}", AGENT_A_PORT)),
+        ("AGENT_B_MCP_URL", format!("http://localhost:{}", AGENT_B_MCP_PORT)),
+        ("PAYMENT_AGENT_ENABLED", "false".to_string()),
+    ];
+    if std::env::var_os("ANTHROPIC_API_KEY").is_none() {
+        env.push(("LLM_PROVIDER", "mock".to_string()));
+    }
+    env
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").canonicalize()?;
+    let logs = root.join("target/zeroproof-dev");
+    tokio::fs::create_dir_all(&logs).await?;
+    let profile = if args.release { "release" } else { "debug" };
+
+    let services = services(&root);
+    let mut bins: Vec<(&str, &str)> = services.iter().map(|s| (s.workspace, s.bin)).collect();
+    if !args.no_chat {
+        bins.push(("agent-a", "mcp-client"));
+    }
+    for (workspace, bin) in &bins {
+        build(&root.join(workspace), bin, args.release).await?;
+    }
+
+    // Killed when dropped, so an early return stops whatever already started
+    let mut running: Vec<(&str, Child)> = Vec::new();
+    for service in &services {
+        println!("▶ {} (log: {})", service.name, logs.join(format!("{}.log", service.name)).display());
+        for (key, value) in &service.env {
+            println!("    {}={}", key, value);
+        }
+        let mut child = start(&root, service, profile, &logs)?;
+        wait_ready(service, &mut child).await?;
+        running.push((service.name, child));
+    }
+
+    println!("\n✓ attester    http://localhost:{} (mock prover)", ATTESTER_PORT);
+    println!("✓ Agent B     http://localhost:{} (MCP tools on :{})", AGENT_B_PORT, AGENT_B_MCP_PORT);
+    println!("✓ Agent A     http://localhost:{} (docs at /docs)\n", AGENT_A_PORT);
+
+    if args.no_chat {
+        println!("Ctrl-C to stop");
+        tokio::signal::ctrl_c().await?;
+    } else {
+        let mut chat = Command::new(binary(&root.join("agent-a"), profile, "mcp-client"));
+        chat.arg("chat").current_dir(root.join("agent-a"));
+        set_env(&mut chat, &chat_env());
+        let mut chat = chat.kill_on_drop(true).spawn().context("Starting the chat client")?;
+        tokio::select! {
+            status = chat.wait() => {
+                if let Ok(status) = status {
+                    if !status.success() {
+                        eprintln!("Chat client exited with {}", status);
+                    }
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+
+    println!("\nStopping services…");
+    for (name, mut child) in running.into_iter().rev() {
+        if let Err(e) = child.kill().await {
+            eprintln!("Stopping {}: {}", name, e);
+        }
+    }
+    Ok(())
+}
+
+async fn build(workspace: &Path, bin: &str, release: bool) -> Result<()> {
+    println!("🔨 cargo build --bin {} ({})", bin, workspace.display());
+    let mut cargo = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+    cargo.args(["build", "--bin", bin]).current_dir(workspace);
+    if release {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().await.with_context(|| format!("Running cargo for {}", bin))?;
+    if !status.success() {
+        bail!("Building {} failed", bin);
+    }
+    Ok(())
+}
+
+/// Where cargo put `bin` (CARGO_TARGET_DIR if set, else the workspace's target/)
+fn binary(workspace: &Path, profile: &str, bin: &str) -> PathBuf {
+    let target = std::env::var_os("CARGO_TARGET_DIR").map(PathBuf::from).unwrap_or_else(|| workspace.join("target"));
+    target.join(profile).join(bin)
+}
+
+fn start(root: &Path, service: &Service, profile: &str, logs: &Path) -> Result<Child> {
+    let workspace = root.join(service.workspace);
+    let log = std::fs::File::create(logs.join(format!("{}.log", service.name)))?;
+    let mut command = Command::new(binary(&workspace, profile, service.bin));
+    command
+        .current_dir(&workspace)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .kill_on_drop(true);
+    set_env(&mut command, &service.env);
+    command.spawn().with_context(|| format!("Starting {}", service.name))
+}
+
+/// Generated settings, except where the caller's environment already has one
+fn set_env(command: &mut Command, env: &[(&'static str, String)]) {
+    for (key, value) in env {
+        if std::env::var_os(key).is_none() {
+            command.env(key, value);
+        }
+    }
+}
+
+async fn wait_ready(service: &Service, child: &mut Child) -> Result<()> {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(2)).build()?;
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Err(anyhow!(
+                "{} exited with {} before it was ready; see target/zeroproof-dev/{}.log",
+                service.name,
+                status,
+                service.name
+            ));
+        }
+        if let Ok(response) = client.get(&service.ready_url).send().await {
+            if response.status().is_success() {
+                return Ok(());
+            }
+        }
+        if started.elapsed() > READY_TIMEOUT {
+            bail!("{} did not answer {} within {}s", service.name, service.ready_url, READY_TIMEOUT.as_secs());
+        }
+        tokio::time::sleep(READY_POLL).await;
+    }
+}
@@ -0,0 +1,265 @@
+//! zeroproof-wasm: Agent B's pricing rules and proof decoding, compiled for the browser
+//!
+//! A web UI can re-run the deterministic pricing from `pricing-core`, decode the public
+//! values of a proof bundle with `zk_protocol::abi`, and compare the proven price with the
+//! one it was quoted, without trusting Agent A's server to have checked. Every function
+//! takes and returns JSON strings; errors are thrown as JS `Error`s.
+//!
+//! This doesn't verify the proof itself (that needs the SP1 verifier, on-chain or through
+//! Agent A's `verify_offchain`); it checks that what was proven is what the user was quoted.
+//! Recomputing a quote needs the same `purchase_date` Agent B priced it with.
+//!
+//! ```sh
+//! wasm-pack build zeroproof-wasm --target web
+//! cargo build -p zeroproof-wasm --target wasm32-unknown-unknown
+//! ```
+
+use pricing_core::{pricing, ruleset};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use zk_protocol::abi::{format_price, PricingClaim, PublicValues};
+use zk_protocol::ProofBundle;
+
+/// [`PricingClaim`] as the browser sees it
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct Claim {
+    pub from: String,
+    pub to: String,
+    pub price_cents: u64,
+    /// `price_cents` for display, e.g. `682.50 USD`
+    pub price: String,
+    pub vip: bool,
+    pub quote_id: String,
+    pub currency: String,
+    pub promo_code: String,
+    pub departure_date: String,
+    pub return_date: String,
+}
+
+impl From<PricingClaim> for Claim {
+    fn from(claim: PricingClaim) -> Self {
+        Self {
+            price: format_price(claim.price_cents, &claim.currency),
+            from: claim.from,
+            to: claim.to,
+            price_cents: claim.price_cents,
+            vip: claim.vip,
+            quote_id: claim.quote_id,
+            currency: claim.currency,
+            promo_code: claim.promo_code,
+            departure_date: claim.departure_date,
+            return_date: claim.return_date,
+        }
+    }
+}
+
+/// What a pricing proof bundle commits to
+#[derive(Serialize, Debug)]
+pub struct DecodedBundle {
+    pub program_id: String,
+    pub vk_hash: String,
+    /// Produced by an attester in `PROVER_MODE=mock`: the values are real but nothing
+    /// can verify the (empty) proof
+    pub mock: bool,
+    /// 0x-prefixed SHA-256 of the guest's input
+    pub input_hash: String,
+    pub ruleset_version: u64,
+    pub ruleset_hash: String,
+    pub claim: Claim,
+}
+
+/// Outcome of [`check_quote`]; `matches` only if `mismatches` is empty
+#[derive(Serialize, Debug)]
+pub struct QuoteCheck {
+    pub matches: bool,
+    pub mismatches: Vec<String>,
+    pub quoted_cents: u64,
+    /// Price this build's rules give for the request
+    pub recomputed_cents: u64,
+    pub bundle: DecodedBundle,
+}
+
+/// Price a `pricing::Request` (JSON) with Agent B's rules; returns a `pricing::Response`
+#[wasm_bindgen]
+pub fn price(request_json: &str) -> Result<String, JsError> {
+    price_json(request_json).map_err(|e| JsError::new(&e))
+}
+
+/// Decode a bundle exported by `ProofBundle::to_hex_json` into a [`DecodedBundle`]
+#[wasm_bindgen(js_name = decodePublicValues)]
+pub fn decode_public_values(bundle_json: &str) -> Result<String, JsError> {
+    decode_bundle(bundle_json).and_then(to_json).map_err(|e| JsError::new(&e))
+}
+
+/// Compare a pricing proof with the request it should prove and the price that was quoted;
+/// returns a [`QuoteCheck`]
+#[wasm_bindgen(js_name = checkQuote)]
+pub fn check_quote(bundle_json: &str, request_json: &str, quoted_cents: u64) -> Result<String, JsError> {
+    check(bundle_json, request_json, quoted_cents)
+        .and_then(to_json)
+        .map_err(|e| JsError::new(&e))
+}
+
+/// Version and 0x-prefixed hash of the pricing rules compiled into this module
+#[wasm_bindgen(js_name = rulesetInfo)]
+pub fn ruleset_info() -> String {
+    serde_json::json!({
+        "version": ruleset::VERSION,
+        "hash": format!("0x{}", hex::encode(ruleset::hash())),
+    })
+    .to_string()
+}
+
+fn to_json<T: Serialize>(value: T) -> Result<String, String> {
+    serde_json::to_string(&value).map_err(|e| e.to_string())
+}
+
+fn parse_request(request_json: &str) -> Result<pricing::Request, String> {
+    serde_json::from_str(request_json).map_err(|e| format!("invalid pricing request: {}", e))
+}
+
+fn price_json(request_json: &str) -> Result<String, String> {
+    pricing::handle(parse_request(request_json)?).and_then(to_json)
+}
+
+fn decode_bundle(bundle_json: &str) -> Result<DecodedBundle, String> {
+    let bundle = ProofBundle::from_hex_json(bundle_json).map_err(|e| format!("invalid proof bundle: {}", e))?;
+    let values = PublicValues::abi_decode(&bundle.public_values).map_err(|e| e.to_string())?;
+    let claim = PricingClaim::abi_decode(&values.claim).map_err(|e| format!("not a pricing proof: {}", e))?;
+    Ok(DecodedBundle {
+        mock: bundle.is_mock(),
+        program_id: bundle.program_id,
+        vk_hash: bundle.vk_hash,
+        input_hash: format!("0x{}", hex::encode(values.input_hash)),
+        ruleset_version: values.ruleset_version,
+        ruleset_hash: format!("0x{}", hex::encode(values.ruleset_hash)),
+        claim: claim.into(),
+    })
+}
+
+fn check(bundle_json: &str, request_json: &str, quoted_cents: u64) -> Result<QuoteCheck, String> {
+    let bundle = decode_bundle(bundle_json)?;
+    let request = parse_request(request_json)?;
+    let recomputed = pricing::handle(request.clone())?;
+    let claim = &bundle.claim;
+
+    let mut mismatches = Vec::new();
+    let local_hash = format!("0x{}", hex::encode(ruleset::hash()));
+    if bundle.ruleset_hash != local_hash {
+        mismatches.push(format!(
+            "proven with ruleset v{} ({}), this module has v{} ({})",
+            bundle.ruleset_version,
+            bundle.ruleset_hash,
+            ruleset::VERSION,
+            local_hash
+        ));
+    }
+    let mut field = |name: &str, proven: &str, expected: &str| {
+        if proven != expected {
+            mismatches.push(format!("{} is {:?} in the proof, {:?} in the request", name, proven, expected));
+        }
+    };
+    field("from", &claim.from, &request.from);
+    field("to", &claim.to, &request.to);
+    field("departure_date", &claim.departure_date, request.departure_date.as_deref().unwrap_or_default());
+    field("return_date", &claim.return_date, request.return_date.as_deref().unwrap_or_default());
+    field("currency", &claim.currency, &recomputed.currency);
+    if let Some(quote_id) = &request.quote_id {
+        field("quote_id", &claim.quote_id, quote_id);
+    }
+    if claim.vip != request.vip {
+        mismatches.push(format!("vip is {} in the proof, {} in the request", claim.vip, request.vip));
+    }
+    if claim.price_cents != recomputed.price_cents {
+        mismatches.push(format!(
+            "proof claims {}, the rules give {}",
+            claim.price,
+            format_price(recomputed.price_cents, &recomputed.currency)
+        ));
+    }
+    if claim.price_cents != quoted_cents {
+        mismatches.push(format!(
+            "proof claims {}, the quote was {}",
+            claim.price,
+            format_price(quoted_cents, &claim.currency)
+        ));
+    }
+
+    Ok(QuoteCheck {
+        matches: mismatches.is_empty(),
+        mismatches,
+        quoted_cents,
+        recomputed_cents: recomputed.price_cents,
+        bundle,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zk_protocol::ProofMode;
+
+    const REQUEST: &str = r#"{"from":"NYC","to":"LON","vip":false,"departure_date":"2026-12-01"}"#;
+
+    fn bundle(price_cents: u64, ruleset_hash: [u8; 32]) -> String {
+        let claim = PricingClaim {
+            from: "NYC".into(),
+            to: "LON".into(),
+            price_cents,
+            vip: false,
+            quote_id: String::new(),
+            currency: "USD".into(),
+            promo_code: String::new(),
+            departure_date: "2026-12-01".into(),
+            return_date: String::new(),
+        };
+        let bundle = ProofBundle {
+            proof: Vec::new(),
+            public_values: PublicValues::new(REQUEST.as_bytes(), ruleset::VERSION, ruleset_hash, claim.abi_encode())
+                .abi_encode(),
+            vk_hash: "0x00c0".into(),
+            program_id: "pricing-v1".into(),
+            elf_hash: "0xe1f".into(),
+            proof_mode: ProofMode::Groth16,
+            created_at: 0,
+        };
+        bundle.to_hex_json().unwrap()
+    }
+
+    fn recomputed() -> u64 {
+        pricing::handle(parse_request(REQUEST).unwrap()).unwrap().price_cents
+    }
+
+    #[test]
+    fn test_decode_pricing_bundle() {
+        let decoded = decode_bundle(&bundle(68_250, ruleset::hash())).unwrap();
+        assert!(decoded.mock);
+        assert_eq!(decoded.program_id, "pricing-v1");
+        assert_eq!(decoded.ruleset_version, ruleset::VERSION);
+        assert_eq!(decoded.claim.price_cents, 68_250);
+        assert_eq!(decoded.claim.price, "682.50 USD");
+        assert!(decode_bundle("{}").is_err());
+    }
+
+    #[test]
+    fn test_check_quote_matches_recomputed_price() {
+        let cents = recomputed();
+        let check = check(&bundle(cents, ruleset::hash()), REQUEST, cents).unwrap();
+        assert!(check.matches, "{:?}", check.mismatches);
+        assert_eq!(check.recomputed_cents, cents);
+    }
+
+    #[test]
+    fn test_check_quote_reports_mismatches() {
+        let cents = recomputed();
+        let check = check(&bundle(cents + 100, [0; 32]), REQUEST, cents).unwrap();
+        assert!(!check.matches);
+        assert_eq!(check.mismatches.len(), 3);
+        assert!(check.mismatches[0].starts_with("proven with ruleset"));
+        assert!(check.mismatches[1].starts_with("proof claims"));
+
+        let other_route = REQUEST.replace("LON", "PAR");
+        let check = super::check(&bundle(cents, ruleset::hash()), &other_route, cents).unwrap();
+        assert!(check.mismatches.iter().any(|m| m.starts_with("to is")));
+    }
+}
@@ -0,0 +1,63 @@
+//! `cargo zeroproof new-program <spec.json> [--out <dir>]`
+//!
+//! Writes the guest crate and `/zk-input` adapter [`scaffold::generate`] makes from a spec
+//! (`{"name", "input", "output"}`, the last two JSON Schemas) into `<dir>/<name>`.
+//! Install with `cargo install --path attester --bin cargo-zeroproof`.
+
+mod scaffold;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: cargo zeroproof new-program <spec.json> [--out <dir>]";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("✗ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    // Cargo runs `cargo-zeroproof zeroproof <args>`
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("zeroproof") {
+        args.next();
+    }
+    if args.next().as_deref() != Some("new-program") {
+        return Err(USAGE.to_string());
+    }
+    let spec_path = args.next().ok_or(USAGE)?;
+    let out = match (args.next().as_deref(), args.next()) {
+        (None, _) => PathBuf::from("."),
+        (Some("--out"), Some(dir)) => PathBuf::from(dir),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let spec = std::fs::read_to_string(&spec_path).map_err(|e| format!("Failed to read {}: {}", spec_path, e))?;
+    let spec: scaffold::Spec = serde_json::from_str(&spec).map_err(|e| format!("Invalid spec {}: {}", spec_path, e))?;
+    let crate_dir = out.join(&spec.name);
+    if crate_dir.exists() {
+        return Err(format!("{} already exists", crate_dir.display()));
+    }
+
+    for file in scaffold::generate(&spec)? {
+        let path = out.join(&file.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, file.contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        println!("✓ {}", path.display());
+    }
+
+    println!();
+    println!("Next:");
+    println!("  1. Put your logic in {}/src/main.rs (fn run)", crate_dir.display());
+    println!("  2. cd {} && cargo prove build", crate_dir.display());
+    println!("  3. Register the ELF: curl -F elf=@<elf> http://localhost:8000/register-elf");
+    println!("  4. Serve /zk-input with {}/adapter/zk_input.rs", crate_dir.display());
+    Ok(())
+}
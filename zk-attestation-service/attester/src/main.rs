@@ -1,8 +1,9 @@
 use axum::{
-    extract::{Multipart, DefaultBodyLimit},
-    routing::post,
+    extract::{Multipart, DefaultBodyLimit, Path, Request},
+    middleware::{self, Next},
+    routing::{get, post},
     Json, Router,
-    http::StatusCode,
+    http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
 };
 use once_cell::sync::Lazy;
@@ -12,14 +13,104 @@ use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
 };
+use tower_http::trace::TraceLayer;
 use uuid::Uuid;
-use zk_protocol::{AttestRequest, AttestResponse};
+use agent_tls::{PeerIdentity, TlsConfig};
+use zk_protocol::abi::{PublicValues, ValuesLayout};
+use zk_protocol::{
+    AttestJobAccepted, AttestJobId, AttestJobStatus, AttestRequest, AttestResponse, Envelope,
+    OutputDescriptor, ProofMode, ProverMode, VersionCheck,
+};
+
+mod scaffold;
 
 type ElfStore = HashMap<String, Vec<u8>>; // program_id → ELF bytes
 type KeyCache = HashMap<String, (SP1ProvingKey, SP1VerifyingKey)>; // program_id → (pk, vk)
+type JobStore = HashMap<AttestJobId, (AttestJobStatus, Option<u64>)>; // job_id → (latest status, finished at)
+type NonceCache = HashMap<String, u64>; // nonce → unix time after which it may be forgotten
+type OutputStore = HashMap<String, OutputDescriptor>; // program_id → layout of its public values
+type LayoutStore = HashMap<String, ValuesLayout>; // program_id → whether it binds its input
 
 static STORE: Lazy<Arc<RwLock<ElfStore>>> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
 static KEY_CACHE: Lazy<Arc<RwLock<KeyCache>>> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static JOBS: Lazy<Arc<RwLock<JobStore>>> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static SEEN_NONCES: Lazy<Arc<RwLock<NonceCache>>> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static OUTPUTS: Lazy<Arc<RwLock<OutputStore>>> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static LAYOUTS: Lazy<Arc<RwLock<LayoutStore>>> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
+/// How long a nonce is remembered when the request carries no expires_at, and the furthest
+/// ahead an expires_at may be (seconds). Override with NONCE_WINDOW_SECS
+static NONCE_WINDOW_SECS: Lazy<u64> = Lazy::new(|| {
+    std::env::var("NONCE_WINDOW_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(600)
+});
+
+/// How long a finished or failed job stays readable (seconds). Override with JOB_TTL_SECS
+static JOB_TTL_SECS: Lazy<u64> = Lazy::new(|| {
+    std::env::var("JOB_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3600)
+});
+
+/// Client-certificate common names allowed to register ELFs (ATTESTER_REGISTER_PEERS,
+/// comma-separated, e.g. "agent-b"); empty = any caller. Needs TLS_CLIENT_CA.
+static REGISTER_PEERS: Lazy<Vec<String>> = Lazy::new(|| peer_list("ATTESTER_REGISTER_PEERS"));
+
+/// Client-certificate common names allowed to request proofs (ATTESTER_ATTEST_PEERS,
+/// e.g. "agent-a"); empty = any caller. Needs TLS_CLIENT_CA.
+static ATTEST_PEERS: Lazy<Vec<String>> = Lazy::new(|| peer_list("ATTESTER_ATTEST_PEERS"));
+
+fn peer_list(var: &str) -> Vec<String> {
+    std::env::var(var)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// PROVER_MODE=mock: execute the program and return SP1 mock proofs in seconds instead of
+/// Groth16/PLONK proofs that take minutes, for local development. Mock proofs are empty and
+/// verify nowhere; responses say `mode: "mock"` so Agent A never sends them on-chain.
+/// SP1_PROVER=mock has the same effect.
+static MOCK_PROVER: Lazy<bool> = Lazy::new(|| {
+    std::env::var("PROVER_MODE").is_ok_and(|mode| mode.eq_ignore_ascii_case("mock"))
+        || std::env::var("SP1_PROVER").is_ok_and(|prover| prover == "mock")
+});
+
+/// Reject expired requests, expiries beyond the replay window, and nonces already seen
+/// inside it
+fn check_replay(request: &AttestRequest) -> Result<(), AppError> {
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let window_end = now + *NONCE_WINDOW_SECS;
+
+    if request.is_expired(now) {
+        return Err(AppError("Attestation request expired".to_string()));
+    }
+    // A far-off expires_at would keep the nonce (and the request) alive indefinitely
+    if request.expires_at.is_some_and(|t| t > window_end) {
+        return Err(AppError(format!("expires_at is more than {}s ahead", *NONCE_WINDOW_SECS)));
+    }
+
+    let Some(nonce) = &request.nonce else {
+        return Ok(());
+    };
+
+    let mut seen = SEEN_NONCES.write().unwrap();
+    seen.retain(|_, forget_at| *forget_at > now);
+
+    if seen.contains_key(nonce) {
+        return Err(AppError(format!("Replayed nonce: {}", nonce)));
+    }
+
+    let forget_at = request.expires_at.unwrap_or(window_end);
+    seen.insert(nonce.clone(), forget_at);
+    Ok(())
+}
 
 // Simple error wrapper for better error responses
 struct AppError(String);
@@ -40,11 +131,19 @@ impl From<String> for AppError {
 struct RegisterResponse {
     program_id: String,
     registered_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<OutputDescriptor>,
+    values_layout: ValuesLayout,
 }
 
 // POST /register-elf  ← called by Agent B on startup
+// Optional `output` field: JSON OutputDescriptor; proofs are then decoded into verified_output
+// Optional `values_layout` field: "claim_only" for legacy programs that commit a bare claim;
+// by default proofs must commit PublicValues bound to the request's input
 async fn register_elf(mut multipart: Multipart) -> Result<Json<RegisterResponse>, AppError> {
     let mut elf_bytes: Option<Vec<u8>> = None;
+    let mut output: Option<OutputDescriptor> = None;
+    let mut values_layout = ValuesLayout::default();
 
     // Read all multipart fields
     while let Some(field) = multipart.next_field().await.map_err(|e| {
@@ -65,7 +164,18 @@ async fn register_elf(mut multipart: Multipart) -> Result<Json<RegisterResponse>
             
             println!("✓ Read ELF file: {} bytes", bytes.len());
             elf_bytes = Some(bytes.to_vec());
-            break; // Got what we need, stop reading
+        } else if field_name.as_deref() == Some("output") {
+            let text = field.text().await.map_err(|e| AppError(format!("Failed to read output descriptor: {}", e)))?;
+            let descriptor: OutputDescriptor = serde_json::from_str(&text)
+                .map_err(|e| AppError(format!("Invalid output descriptor: {}", e)))?;
+            descriptor.validate().map_err(|e| AppError(format!("Invalid output descriptor: {}", e)))?;
+            println!("✓ Output descriptor: {:?}", descriptor);
+            output = Some(descriptor);
+        } else if field_name.as_deref() == Some("values_layout") {
+            let text = field.text().await.map_err(|e| AppError(format!("Failed to read values layout: {}", e)))?;
+            values_layout = serde_json::from_value(serde_json::Value::String(text.trim().to_string()))
+                .map_err(|e| AppError(format!("Invalid values_layout: {}", e)))?;
+            println!("✓ Values layout: {:?}", values_layout);
         }
     }
 
@@ -80,19 +190,46 @@ async fn register_elf(mut multipart: Multipart) -> Result<Json<RegisterResponse>
         let mut store = STORE.write().unwrap();
         store.insert(program_id.clone(), elf);
     }
+    if let Some(descriptor) = &output {
+        OUTPUTS.write().unwrap().insert(program_id.clone(), descriptor.clone());
+    }
+    LAYOUTS.write().unwrap().insert(program_id.clone(), values_layout);
 
     println!("✓ ELF registered with program_id: {}", program_id);
 
     Ok(Json(RegisterResponse {
         program_id: program_id.clone(),
         registered_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        output,
+        values_layout,
     }))
 }
 
-// POST /attest  ← called by Agent A
-async fn attest(
-    Json(payload): Json<AttestRequest>,
-) -> Json<AttestResponse> {
+/// Unwrap the versioned envelope and run replay checks
+fn open_request(envelope: Envelope<AttestRequest>) -> Result<AttestRequest, AppError> {
+    // Negotiate protocol version (reject unknown major, warn on minor skew)
+    let (payload, check) = envelope
+        .migrate_legacy()
+        .open()
+        .map_err(|e| AppError(e.to_string()))?;
+    if let VersionCheck::MinorSkew { local, peer } = check {
+        println!("⚠ Protocol minor version skew: attester={}, client={}", local, peer);
+    }
+
+    check_replay(&payload)?;
+    println!("📨 Attestation request {}", payload.canonical_hash_hex());
+
+    Ok(payload)
+}
+
+/// Generate (and optionally verify) a proof - blocking, takes minutes
+/// `progress` is called at each stage so async jobs can report status
+fn run_attestation(
+    payload: AttestRequest,
+    progress: impl Fn(AttestJobStatus),
+) -> Result<AttestResponse, AppError> {
+    progress(AttestJobStatus::Executing);
+
     let prover = ProverClient::from_env();
     let program_id = &payload.program_id;
 
@@ -100,7 +237,7 @@ async fn attest(
     let elf = {
         let store = STORE.read().unwrap();
         store.get(program_id)
-            .expect("Unknown program_id")
+            .ok_or_else(|| AppError(format!("Unknown program_id: {}", program_id)))?
             .clone()
     };
 
@@ -128,67 +265,317 @@ async fn attest(
     let vk_hash_str = vk_hash.to_string();
 
     println!("✓ Verifying Key Hash: {}", vk_hash_str);
-    println!("  (Pass this to SP1VerifierGroth16.verifyProof() on-chain)");
+    println!("  (Pass this to the SP1 verifier's verifyProof() on-chain)");
 
     // 4. Create stdin with the input
-    // Input is already bincode-serialized by the agent
+    // Input is already serialized by the agent (codec tells the guest how to read it)
+    println!("  Input codec: {:?} ({} bytes)", payload.codec, payload.input_bytes.len());
     let mut stdin = SP1Stdin::new();
     stdin.write_vec(payload.input_bytes.clone());
 
-    // 5. Generate Groth16 proof (SNARK-wrapped for on-chain compatibility)
-    // Groth16: (~100k gas on-chain, uses GPU acceleration if available)
-    // Alternative: .plonk() (~300k gas, const-size proof)
-    let proof = prover
-        .prove(&pk, &stdin)
-        .groth16()  // Wraps STARK in Groth16 for on-chain verification
+    // 5. Generate the SNARK-wrapped proof for on-chain compatibility
+    // Groth16 (default): ~100k gas on-chain, uses GPU acceleration if available
+    // PLONK: ~300k gas, no trusted setup; checked by the SP1 PLONK verifier
+    println!("  Proof mode: {:?}", payload.proof_mode);
+    progress(AttestJobStatus::Proving { pct: 0 });
+    let request = prover.prove(&pk, &stdin);
+    let request = match payload.proof_mode {
+        ProofMode::Groth16 => request.groth16(),
+        ProofMode::Plonk => request.plonk(),
+    };
+    let proof = request
         .run()
-        .expect("Proving failed");
+        .map_err(|e| AppError(format!("Proving failed: {}", e)))?;
+    progress(AttestJobStatus::Proving { pct: 90 });
 
     // 6. Optional: Verify proof locally before returning
     // - If verify_locally=true (default): Verify proof in attester (safe, adds 2-3s)
     // - If verify_locally=false: Skip verification (fast, Agent A verifies on-chain)
-    if payload.verify_locally {
+    // Mock proofs have nothing to verify
+    if *MOCK_PROVER {
+        println!("⊘ Mock proof (PROVER_MODE=mock), not verifiable");
+    } else if payload.verify_locally {
         println!("⚙ Verifying proof locally in attester...");
         prover.verify(&proof, &vk)
-            .expect("Verification failed");
+            .map_err(|e| AppError(format!("Verification failed: {}", e)))?;
         println!("✓ Local verification passed");
     } else {
         println!("⊘ Skipping local verification (Agent A will verify on-chain)");
     }
 
     // 7. Extract public values and proof bytes
-    let actual_output = payload.claimed_output.unwrap_or_else(|| serde_json::json!({}));
     let public_values_bytes = proof.public_values.as_slice();
 
+    // With a registered layout, verified_output is what the proof committed, and any claimed
+    // field it also has must agree; without one, the claim is passed through
+    let descriptor = OUTPUTS.read().unwrap().get(program_id).cloned();
+    let mut actual_output = match descriptor {
+        Some(descriptor) => {
+            let decoded = descriptor
+                .decode(public_values_bytes)
+                .map_err(|e| AppError(format!("Public values don't match the registered output layout: {}", e)))?;
+            let claimed = payload.claimed_output.as_ref().and_then(|c| c.as_object());
+            for (name, claimed_value) in claimed.into_iter().flatten() {
+                if let Some(proven) = decoded.get(name).filter(|proven| *proven != claimed_value) {
+                    return Err(AppError(format!("Claimed {} = {}, but the proof committed {}", name, claimed_value, proven)));
+                }
+            }
+            println!("✓ Decoded public values with the registered output layout");
+            decoded
+        }
+        None => payload.claimed_output.unwrap_or_else(|| serde_json::json!({})),
+    };
+
+    // The claim must be for the input we were sent, not some other request
+    let layout = LAYOUTS.read().unwrap().get(program_id).copied().unwrap_or_default();
+    match PublicValues::check_input(layout, public_values_bytes, &payload.input_bytes)
+        .map_err(|e| AppError(format!("Proof doesn't match the request: {}", e)))?
+    {
+        Some(values) => {
+            let input_hash = format!("0x{}", hex::encode(values.input_hash));
+            println!("✓ Public values bound to input {}", input_hash);
+            if let Some(fields) = actual_output.as_object_mut() {
+                fields.insert("input_hash".to_string(), serde_json::json!(input_hash));
+                fields.insert("ruleset_version".to_string(), serde_json::json!(values.ruleset_version));
+                fields.insert(
+                    "ruleset_hash".to_string(),
+                    serde_json::json!(format!("0x{}", hex::encode(values.ruleset_hash))),
+                );
+            }
+        }
+        None => println!("⚠ Program is registered claim-only; the proof isn't bound to this request"),
+    }
+
     // proof.bytes() returns [vkey_hash[..4], proof_bytes]
     // The contract expects proofBytes to START with the first 4 bytes of the verifier hash
     // So we use proof.bytes() as-is (it already has the correct format)
     let proof_bytes = proof.bytes();
 
-    Json(AttestResponse {
+    Ok(AttestResponse {
         proof: hex::encode(proof_bytes),
         public_values: hex::encode(public_values_bytes),
         vk_hash: vk_hash_str,  // Include VK hash for on-chain verification
         verified_output: actual_output,
+        mode: if *MOCK_PROVER { ProverMode::Mock } else { ProverMode::Sp1 },
     })
 }
 
-#[tokio::main]
-async fn main() {
-    let app = Router::new()
+// POST /attest  ← called by Agent A (blocks until the proof is ready)
+async fn attest(
+    Json(envelope): Json<Envelope<AttestRequest>>,
+) -> Result<Json<Envelope<AttestResponse>>, AppError> {
+    let payload = open_request(envelope)?;
+    let response = run_attestation(payload, |_| {})?;
+    Ok(Json(Envelope::new(response)))
+}
+
+// POST /attest/jobs  ← async variant: returns a job id immediately
+async fn submit_attest_job(
+    headers: HeaderMap,
+    Json(envelope): Json<Envelope<AttestRequest>>,
+) -> Result<Json<Envelope<AttestJobAccepted>>, AppError> {
+    let payload = open_request(envelope)?;
+    let job_id = AttestJobId(Uuid::new_v4().to_string());
+
+    set_job_status(&job_id, AttestJobStatus::Queued);
+    println!(
+        "📥 Queued attestation job {} (request {})",
+        job_id,
+        headers.get(REQUEST_ID_HEADER).and_then(|v| v.to_str().ok()).unwrap_or("-")
+    );
+
+    let worker_job_id = job_id.clone();
+    // Keep the request span (and its request_id) on the proving thread
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || {
+        let _entered = span.enter();
+        let result = run_attestation(payload, |status| set_job_status(&worker_job_id, status));
+        let final_status = match result {
+            Ok(response) => AttestJobStatus::Done(response),
+            Err(AppError(message)) => AttestJobStatus::Failed {
+                code: "attestation_failed".to_string(),
+                message,
+            },
+        };
+        println!("✓ Attestation job {} finished", worker_job_id);
+        set_job_status(&worker_job_id, final_status);
+    });
+
+    Ok(Json(Envelope::new(AttestJobAccepted {
+        job_id,
+        status: AttestJobStatus::Queued,
+    })))
+}
+
+// GET /attest/jobs/:job_id  ← Agent A polls this
+async fn get_attest_job(
+    Path(job_id): Path<String>,
+) -> Result<Json<Envelope<AttestJobStatus>>, AppError> {
+    let jobs = JOBS.read().unwrap();
+    jobs.get(&AttestJobId(job_id.clone()))
+        .map(|(status, _)| Json(Envelope::new(status.clone())))
+        .ok_or_else(|| AppError(format!("Unknown job_id: {}", job_id)))
+}
+
+/// Record a job's status; jobs that finished more than JOB_TTL_SECS ago are dropped
+fn set_job_status(job_id: &AttestJobId, status: AttestJobStatus) {
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let finished_at = status.is_terminal().then_some(now);
+    let mut jobs = JOBS.write().unwrap();
+    jobs.retain(|_, (_, done)| done.is_none_or(|at| now < at + *JOB_TTL_SECS));
+    jobs.insert(job_id.clone(), (status, finished_at));
+}
+
+// GET /programs/:program_id  ← Agent B polls this to notice a restart (404: re-register)
+// vk_hash is set once the keys have been computed (first attestation)
+async fn get_program(Path(program_id): Path<String>) -> Response {
+    match STORE.read().unwrap().get(&program_id) {
+        Some(elf) => Json(serde_json::json!({
+            "program_id": program_id,
+            "elf_size": elf.len(),
+            "vk_hash": KEY_CACHE.read().unwrap().get(&program_id).map(|(_, vk)| vk.bytes32().to_string()),
+            "output": OUTPUTS.read().unwrap().get(&program_id),
+            "values_layout": LAYOUTS.read().unwrap().get(&program_id).copied().unwrap_or_default(),
+        }))
+        .into_response(),
+        None => (StatusCode::NOT_FOUND, format!("Unknown program_id: {}", program_id)).into_response(),
+    }
+}
+
+// POST /programs/scaffold  ← guest crate + /zk-input adapter from input/output JSON Schemas
+async fn scaffold_program(Json(spec): Json<scaffold::Spec>) -> Result<Json<serde_json::Value>, AppError> {
+    let files = scaffold::generate(&spec)?;
+    println!("✓ Scaffolded program {} ({} files)", spec.name, files.len());
+    Ok(Json(serde_json::json!({ "name": spec.name, "files": files })))
+}
+
+// GET /health  ← liveness probe (Agent A's circuit breaker)
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "programs": STORE.read().unwrap().len(),
+        "jobs": JOBS.read().unwrap().len(),
+        "prover_mode": if *MOCK_PROVER { ProverMode::Mock } else { ProverMode::Sp1 },
+    }))
+}
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Reuse the caller's x-request-id (or mint one) and echo it on the response
+async fn propagate_request_id(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let value = HeaderValue::from_str(&id).ok();
+    if let Some(value) = value.clone() {
+        req.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    let mut response = next.run(req).await;
+    if let Some(value) = value {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// 403 unless the caller's client certificate is one of `allowed`
+async fn require_peer(allowed: &[String], req: Request, next: Next) -> Response {
+    let peer = req.extensions().get::<PeerIdentity>().cloned().unwrap_or_default();
+    if !peer.is_allowed(allowed) {
+        let name = peer.common_name.as_deref().unwrap_or("(none)");
+        tracing::warn!("Refused {} from client certificate CN={}", req.uri().path(), name);
+        return (StatusCode::FORBIDDEN, format!("Client certificate CN={} may not call {}", name, req.uri().path()))
+            .into_response();
+    }
+    next.run(req).await
+}
+
+fn peers_label(peers: &[String]) -> String {
+    if peers.is_empty() { "any verified certificate".to_string() } else { peers.join(", ") }
+}
+
+async fn register_peers_only(req: Request, next: Next) -> Response {
+    require_peer(&REGISTER_PEERS, req, next).await
+}
+
+async fn attest_peers_only(req: Request, next: Next) -> Response {
+    require_peer(&ATTEST_PEERS, req, next).await
+}
+
+fn request_span<B>(req: &axum::http::Request<B>) -> tracing::Span {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-");
+    let span = tracing::info_span!("request", method = %req.method(), uri = %req.uri(), request_id = %id);
+    telemetry::set_parent(&span, req.headers());
+    span
+}
+
+fn main() {
+    if *MOCK_PROVER {
+        // ProverClient::from_env reads SP1_PROVER. Set before the runtime is built, while
+        // this is the only thread, so nothing can read the environment concurrently.
+        unsafe { std::env::set_var("SP1_PROVER", "mock") };
+    }
+    tokio::runtime::Runtime::new()
+        .expect("Failed to start the Tokio runtime")
+        .block_on(serve());
+}
+
+async fn serve() {
+    let _telemetry = telemetry::init("zk-attester", false);
+
+    let tls = TlsConfig::from_env();
+    tls.validate().expect("Invalid TLS settings");
+    if !tls.requires_client_cert() && (!REGISTER_PEERS.is_empty() || !ATTEST_PEERS.is_empty()) {
+        panic!("ATTESTER_REGISTER_PEERS / ATTESTER_ATTEST_PEERS need TLS_CLIENT_CA (mTLS)");
+    }
+
+    // Agent B registers ELFs, Agent A requests proofs; each is checked by its certificate
+    let registration = Router::new()
         .route("/register-elf", post(register_elf))
+        .route_layer(middleware::from_fn(register_peers_only));
+    let attestation = Router::new()
         .route("/attest", post(attest))
-        .layer(DefaultBodyLimit::max(20 * 1024 * 1024)); // 20MB limit for ELF files
+        .route("/attest/jobs", post(submit_attest_job))
+        .route("/attest/jobs/:job_id", get(get_attest_job))
+        .route_layer(middleware::from_fn(attest_peers_only));
+
+    let app = Router::new()
+        .route("/health", get(health))
+        .route("/programs/scaffold", post(scaffold_program))
+        .route("/programs/:program_id", get(get_program))
+        .merge(registration)
+        .merge(attestation)
+        .layer(DefaultBodyLimit::max(20 * 1024 * 1024)) // 20MB limit for ELF files
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(middleware::from_fn(propagate_request_id));
 
-    println!("ZK Attester running → http://0.0.0.0:8000");
+    println!("ZK Attester running → {}://0.0.0.0:8000", tls.scheme());
+    if *MOCK_PROVER {
+        println!("   ⚠ PROVER_MODE=mock: proofs are SP1 mock proofs and can't be verified on-chain");
+    }
+    if tls.requires_client_cert() {
+        println!("   Client certificates required (TLS_CLIENT_CA)");
+        println!("   register-elf peers: {}", peers_label(&REGISTER_PEERS));
+        println!("   attest peers:       {}", peers_label(&ATTEST_PEERS));
+    }
+    println!("   GET  /health");
     println!("   POST /register-elf   ← Agent B calls this once");
+    println!("   GET  /programs/:program_id");
+    println!("   POST /programs/scaffold ← new guest crate from JSON Schemas");
     println!("   POST /attest        ← Agent A calls this");
+    println!("   POST /attest/jobs   ← async variant (returns job_id)");
+    println!("   GET  /attest/jobs/:job_id");
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:8000")
-        .await
-        .expect("Failed to bind to 0.0.0.0:8000");
-
-    axum::serve(listener, app)
+    agent_tls::serve(([0, 0, 0, 0], 8000).into(), app, &tls)
         .await
         .expect("Server error");
 }
\ No newline at end of file
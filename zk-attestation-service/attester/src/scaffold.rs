@@ -0,0 +1,230 @@
+//! Guest program scaffolding for new agents
+//!
+//! [`generate`] turns a [`Spec`] (a crate name plus JSON Schemas of the request a program
+//! reads and the output it commits) into an SP1 guest crate and a matching `/zk-input`
+//! adapter. Both share the same generated types, so the bytes the adapter encodes are the
+//! bytes the guest reads. The agent pastes its existing logic into the guest's `run`.
+//!
+//! Supported schema types: `string`, `boolean`, `integer` (`u64` with `"minimum": 0`, else
+//! `i64`), `number` (`f64`), `array` (of any of these), and `object` (a nested struct).
+//! Properties missing from `required` become `Option`s. `enum` values are read as strings.
+//!
+//! Served by `cargo zeroproof new-program` and `POST /programs/scaffold`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Matches the SP1 version the attester proves with
+const SP1_VERSION: &str = "5.0.8";
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "yield",
+];
+
+#[derive(Deserialize)]
+pub struct Spec {
+    /// Crate name, e.g. "acme-pricing"
+    pub name: String,
+    /// JSON Schema of the request the guest reads
+    pub input: Value,
+    /// JSON Schema of what it commits
+    pub output: Value,
+}
+
+#[derive(Serialize)]
+pub struct File {
+    /// Relative to where the crate is created, starting with the crate name
+    pub path: String,
+    pub contents: String,
+}
+
+pub fn generate(spec: &Spec) -> Result<Vec<File>, String> {
+    let name = &spec.name;
+    let name_ok = name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !name_ok {
+        return Err(format!("{:?} is not a crate name (lowercase letters, digits, - and _)", name));
+    }
+
+    let mut types = Types::default();
+    types.object("Input", &spec.input)?;
+    types.object("Output", &spec.output)?;
+    let definitions = types.structs.join("\n");
+    let required: Vec<String> = spec.input["required"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|field| format!("{:?}", field))
+        .collect();
+
+    Ok(vec![
+        File { path: format!("{}/Cargo.toml", name), contents: cargo_toml(name) },
+        File { path: format!("{}/src/main.rs", name), contents: guest(name, &definitions) },
+        File {
+            path: format!("{}/adapter/zk_input.rs", name),
+            contents: adapter(name, &definitions, &required.join(", ")),
+        },
+    ])
+}
+
+/// Struct definitions collected while walking a schema, nested ones first
+#[derive(Default)]
+struct Types {
+    structs: Vec<String>,
+}
+
+impl Types {
+    fn object(&mut self, struct_name: &str, schema: &Value) -> Result<(), String> {
+        if schema["type"] != "object" {
+            return Err(format!("{} schema must be {{\"type\": \"object\", ...}}", struct_name));
+        }
+        let required: Vec<&str> = schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+        let properties = schema["properties"].as_object().cloned().unwrap_or_default();
+
+        let mut fields = String::new();
+        for (property, property_schema) in &properties {
+            let ident = identifier(property)?;
+            let mut ty = self.field_type(struct_name, property, property_schema)?;
+            if !required.contains(&property.as_str()) {
+                ty = format!("Option<{}>", ty);
+                fields.push_str("    #[serde(default)]\n");
+            }
+            if ident.trim_start_matches("r#") != property {
+                fields.push_str(&format!("    #[serde(rename = {:?})]\n", property));
+            }
+            if let Some(description) = property_schema["description"].as_str() {
+                fields.push_str(&format!("    /// {}\n", description));
+            }
+            fields.push_str(&format!("    pub {}: {},\n", ident, ty));
+        }
+
+        self.structs.push(format!(
+            "#[derive(Serialize, Deserialize, Debug, Clone)]\npub struct {} {{\n{}}}\n",
+            struct_name, fields
+        ));
+        Ok(())
+    }
+
+    fn field_type(&mut self, parent: &str, property: &str, schema: &Value) -> Result<String, String> {
+        if schema.get("enum").is_some() {
+            return Ok("String".to_string());
+        }
+        let ty = match schema["type"].as_str() {
+            Some("string") => "String".to_string(),
+            Some("boolean") => "bool".to_string(),
+            Some("integer") if schema["minimum"].as_f64().is_some_and(|min| min >= 0.0) => "u64".to_string(),
+            Some("integer") => "i64".to_string(),
+            Some("number") => "f64".to_string(),
+            Some("array") => format!("Vec<{}>", self.field_type(parent, property, &schema["items"])?),
+            Some("object") => {
+                let nested = format!("{}{}", parent, camel_case(property));
+                self.object(&nested, schema)?;
+                nested
+            }
+            other => return Err(format!("{}.{}: unsupported type {:?}", parent, property, other)),
+        };
+        Ok(ty)
+    }
+}
+
+/// Rust field name for a schema property; keywords become raw identifiers
+fn identifier(property: &str) -> Result<String, String> {
+    let ident: String = property
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_lowercase() || c == '_') || ident.chars().all(|c| c == '_') {
+        return Err(format!("Property {:?} can't be a Rust field name", property));
+    }
+    Ok(match ident.as_str() {
+        // Can't be raw identifiers
+        "self" | "super" | "crate" => format!("{}_", ident),
+        keyword if RUST_KEYWORDS.contains(&keyword) => format!("r#{}", ident),
+        _ => ident,
+    })
+}
+
+fn camel_case(property: &str) -> String {
+    property
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|c| c.to_ascii_uppercase()).into_iter().chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+fn cargo_toml(name: &str) -> String {
+    format!(
+        r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+serde = {{ version = "1.0", features = ["derive"] }}
+sp1-zkvm = "{SP1_VERSION}"
+
+# Built on its own with `cargo prove build`
+[workspace]
+"#
+    )
+}
+
+fn guest(name: &str, definitions: &str) -> String {
+    format!(
+        r#"//! {name}: SP1 guest generated by `cargo zeroproof new-program`
+//!
+//! Reads one `Input`, runs `run`, and commits its `Output`. Register the built ELF with the
+//! attester (`POST /register-elf`) and encode requests with adapter/zk_input.rs.
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use serde::{{Deserialize, Serialize}};
+
+{definitions}
+pub fn main() {{
+    let input: Input = sp1_zkvm::io::read();
+    let output = run(input);
+    sp1_zkvm::io::commit(&output);
+}}
+
+/// Your existing logic goes here; it runs in the zkVM, so no network or clock
+fn run(input: Input) -> Output {{
+    let _ = input;
+    todo!("compute the Output from the Input")
+}}
+"#
+    )
+}
+
+fn adapter(name: &str, definitions: &str, required: &str) -> String {
+    format!(
+        r#"//! `/zk-input` adapter for {name}, generated by `cargo zeroproof new-program`
+//!
+//! Copy into the agent that serves requests (needs serde, serde_json, and bincode).
+//! `input_bytes` turns a request into the attester's `input_bytes`; `decode_output` reads
+//! the proof's public values back.
+
+use serde::{{Deserialize, Serialize}};
+
+{definitions}
+/// Input fields a request must set
+pub const REQUIRED_FIELDS: &[&str] = &[{required}];
+
+pub fn input_bytes(input: &serde_json::Value) -> Result<Vec<u8>, String> {{
+    let input: Input = serde_json::from_value(input.clone()).map_err(|e| format!("Invalid input: {{}}", e))?;
+    bincode::serialize(&input).map_err(|e| format!("Failed to serialize input: {{}}", e))
+}}
+
+pub fn decode_output(public_values: &[u8]) -> Result<Output, String> {{
+    bincode::deserialize(public_values).map_err(|e| format!("Public values aren't an Output: {{}}", e))
+}}
+"#
+    )
+}
@@ -1,3 +1,5 @@
+// Hand-edited example; `cargo zeroproof new-program` (attester/src/scaffold.rs) generates
+// a guest like this, plus its /zk-input adapter, from JSON Schemas of the input and output.
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
@@ -0,0 +1,537 @@
+//! Solidity ABI encoding for zkVM public values
+//!
+//! Guest programs commit `abi.encode(...)`-compatible bytes instead of bincode,
+//! so contracts can `abi.decode(publicValues, (string, string, uint256, bool))`
+//! and read the claim directly. Only the static/dynamic types our claims need
+//! are supported; `uint256` values are limited to u128 on the Rust side.
+//!
+//! Agent B's guest wraps its claim in [`PublicValues`] together with the hash of its input,
+//! so a proof can't be passed off as one for another request.
+
+use alloc::{format, string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ZkProtocolError;
+
+/// A single ABI value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiValue {
+    Uint(u128),
+    Bool(bool),
+    Address([u8; 20]),
+    FixedBytes32([u8; 32]),
+    Bytes(Vec<u8>),
+    String(String),
+}
+
+/// Expected type when decoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbiType {
+    Uint,
+    Bool,
+    Address,
+    FixedBytes32,
+    Bytes,
+    String,
+}
+
+/// `abi.encode(v1, v2, ...)` - head/tail encoding of a tuple
+pub fn encode(values: &[AbiValue]) -> Vec<u8> {
+    let head_len = values.len() * 32;
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+
+    for value in values {
+        match value {
+            AbiValue::Uint(n) => head.extend_from_slice(&uint_word(*n)),
+            AbiValue::Bool(b) => head.extend_from_slice(&uint_word(*b as u128)),
+            AbiValue::Address(addr) => {
+                let mut word = [0u8; 32];
+                word[12..].copy_from_slice(addr);
+                head.extend_from_slice(&word);
+            }
+            AbiValue::FixedBytes32(bytes) => head.extend_from_slice(bytes),
+            AbiValue::Bytes(bytes) => {
+                head.extend_from_slice(&uint_word((head_len + tail.len()) as u128));
+                encode_dynamic(&mut tail, bytes);
+            }
+            AbiValue::String(s) => {
+                head.extend_from_slice(&uint_word((head_len + tail.len()) as u128));
+                encode_dynamic(&mut tail, s.as_bytes());
+            }
+        }
+    }
+
+    head.extend(tail);
+    head
+}
+
+/// Inverse of `encode` for a known tuple layout
+pub fn decode(data: &[u8], types: &[AbiType]) -> Result<Vec<AbiValue>, ZkProtocolError> {
+    let mut values = Vec::with_capacity(types.len());
+
+    for (i, ty) in types.iter().enumerate() {
+        let word = read_word(data, i * 32)?;
+        let value = match ty {
+            AbiType::Uint => AbiValue::Uint(word_to_u128(&word)?),
+            AbiType::Bool => match word_to_u128(&word)? {
+                0 => AbiValue::Bool(false),
+                1 => AbiValue::Bool(true),
+                n => return Err(ZkProtocolError::Abi(format!("invalid bool value {}", n))),
+            },
+            AbiType::Address => {
+                let mut addr = [0u8; 20];
+                addr.copy_from_slice(&word[12..]);
+                AbiValue::Address(addr)
+            }
+            AbiType::FixedBytes32 => AbiValue::FixedBytes32(word),
+            AbiType::Bytes | AbiType::String => {
+                let offset = word_to_usize(&word)?;
+                let len = word_to_usize(&read_word(data, offset)?)?;
+                let out_of_bounds = || ZkProtocolError::Abi(format!("dynamic value {} out of bounds", i));
+                let start = offset.checked_add(32).ok_or_else(out_of_bounds)?;
+                let end = start.checked_add(len).ok_or_else(out_of_bounds)?;
+                let bytes = data.get(start..end).ok_or_else(out_of_bounds)?.to_vec();
+                if *ty == AbiType::String {
+                    AbiValue::String(
+                        String::from_utf8(bytes)
+                            .map_err(|_| ZkProtocolError::Abi(format!("value {} is not UTF-8", i)))?,
+                    )
+                } else {
+                    AbiValue::Bytes(bytes)
+                }
+            }
+        };
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
+/// SHA-256 of a guest's stdin, as committed in [`PublicValues::input_hash`]
+pub fn input_hash(input_bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(input_bytes).into()
+}
+
+/// How a program commits its public values
+///
+/// Agent B's guests commit [`PublicValues`]; programs written before input binding commit a
+/// bare claim. A verifier takes the layout from what it knows about the program, never from
+/// whether the bytes happen to decode as `PublicValues`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValuesLayout {
+    /// [`PublicValues`]: the claim, bound to the input it was computed from
+    #[default]
+    Bound,
+    /// Legacy: a bare claim, with no input hash to check
+    ClaimOnly,
+}
+
+/// Public values committed by Agent B's guest:
+/// `(bytes32 inputHash, uint256 rulesetVersion, bytes32 rulesetHash, bytes claim)`
+///
+/// `inputHash` is [`input_hash`] of the `input_bytes` the attester was sent. `rulesetVersion`
+/// and `rulesetHash` identify the pricing rules the claim was computed with
+/// (`pricing_core::ruleset`), so a contract can pin the versions it accepts. `claim` is one of
+/// the claims below, ABI-encoded. Contracts
+/// `abi.decode(publicValues, (bytes32, uint256, bytes32, bytes))` and then decode `claim`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicValues {
+    pub input_hash: [u8; 32],
+    pub ruleset_version: u64,
+    pub ruleset_hash: [u8; 32],
+    pub claim: Vec<u8>,
+}
+
+impl PublicValues {
+    pub const TYPES: [AbiType; 4] = [AbiType::FixedBytes32, AbiType::Uint, AbiType::FixedBytes32, AbiType::Bytes];
+
+    /// `claim`, bound to the input it was computed from and the rules it was computed with
+    pub fn new(input_bytes: &[u8], ruleset_version: u64, ruleset_hash: [u8; 32], claim: Vec<u8>) -> Self {
+        Self { input_hash: input_hash(input_bytes), ruleset_version, ruleset_hash, claim }
+    }
+
+    pub fn abi_encode(&self) -> Vec<u8> {
+        encode(&[
+            AbiValue::FixedBytes32(self.input_hash),
+            AbiValue::Uint(self.ruleset_version as u128),
+            AbiValue::FixedBytes32(self.ruleset_hash),
+            AbiValue::Bytes(self.claim.clone()),
+        ])
+    }
+
+    pub fn abi_decode(data: &[u8]) -> Result<Self, ZkProtocolError> {
+        match decode(data, &Self::TYPES)?.as_slice() {
+            [AbiValue::FixedBytes32(input_hash), AbiValue::Uint(version), AbiValue::FixedBytes32(ruleset_hash), AbiValue::Bytes(claim)] => Ok(Self {
+                input_hash: *input_hash,
+                ruleset_version: u64::try_from(*version)
+                    .map_err(|_| ZkProtocolError::Abi("rulesetVersion exceeds u64".into()))?,
+                ruleset_hash: *ruleset_hash,
+                claim: claim.clone(),
+            }),
+            _ => Err(ZkProtocolError::Abi("unexpected public values layout".into())),
+        }
+    }
+
+    /// Check `public_values` were committed for `input_bytes`. With [`ValuesLayout::Bound`] they
+    /// must decode as `PublicValues` and commit the input's hash, or it's an error;
+    /// `ClaimOnly` programs commit no input hash, so there's nothing to check and it's `Ok(None)`.
+    pub fn check_input(
+        layout: ValuesLayout,
+        public_values: &[u8],
+        input_bytes: &[u8],
+    ) -> Result<Option<Self>, ZkProtocolError> {
+        if layout == ValuesLayout::ClaimOnly {
+            return Ok(None);
+        }
+        let values = Self::abi_decode(public_values)
+            .map_err(|e| ZkProtocolError::Verification(format!("public values aren't PublicValues: {}", e)))?;
+        let expected = input_hash(input_bytes);
+        if values.input_hash != expected {
+            return Err(ZkProtocolError::Verification(format!(
+                "public values commit input hash 0x{}, but the input hashes to 0x{}",
+                hex::encode(values.input_hash),
+                hex::encode(expected)
+            )));
+        }
+        Ok(Some(values))
+    }
+}
+
+/// Pricing claim committed by Agent B's guest:
+/// `(string from, string to, uint256 priceCents, bool vip, string quoteId, string currency, string promoCode,
+/// string departureDate, string returnDate)`
+///
+/// `quoteId` is empty for proofs of an ad-hoc price rather than an issued quote.
+/// `priceCents` is in `currency`'s minor units (whole yen for JPY), after the discount of
+/// `promoCode` (empty when none was applied). The dates are `YYYY-MM-DD`, empty when the
+/// quote didn't give one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PricingClaim {
+    pub from: String,
+    pub to: String,
+    pub price_cents: u64,
+    pub vip: bool,
+    pub quote_id: String,
+    /// ISO 4217 code
+    pub currency: String,
+    pub promo_code: String,
+    pub departure_date: String,
+    pub return_date: String,
+}
+
+impl PricingClaim {
+    pub const TYPES: [AbiType; 9] = [
+        AbiType::String,
+        AbiType::String,
+        AbiType::Uint,
+        AbiType::Bool,
+        AbiType::String,
+        AbiType::String,
+        AbiType::String,
+        AbiType::String,
+        AbiType::String,
+    ];
+
+    pub fn abi_encode(&self) -> Vec<u8> {
+        encode(&[
+            AbiValue::String(self.from.clone()),
+            AbiValue::String(self.to.clone()),
+            AbiValue::Uint(self.price_cents as u128),
+            AbiValue::Bool(self.vip),
+            AbiValue::String(self.quote_id.clone()),
+            AbiValue::String(self.currency.clone()),
+            AbiValue::String(self.promo_code.clone()),
+            AbiValue::String(self.departure_date.clone()),
+            AbiValue::String(self.return_date.clone()),
+        ])
+    }
+
+    pub fn abi_decode(data: &[u8]) -> Result<Self, ZkProtocolError> {
+        match decode(data, &Self::TYPES)?.as_slice() {
+            [AbiValue::String(from), AbiValue::String(to), AbiValue::Uint(cents), AbiValue::Bool(vip), AbiValue::String(quote_id), AbiValue::String(currency), AbiValue::String(promo_code), AbiValue::String(departure_date), AbiValue::String(return_date)] => Ok(Self {
+                from: from.clone(),
+                to: to.clone(),
+                price_cents: u64::try_from(*cents)
+                    .map_err(|_| ZkProtocolError::Abi("priceCents exceeds u64".into()))?,
+                vip: *vip,
+                quote_id: quote_id.clone(),
+                currency: currency.clone(),
+                promo_code: promo_code.clone(),
+                departure_date: departure_date.clone(),
+                return_date: return_date.clone(),
+            }),
+            _ => Err(ZkProtocolError::Abi("unexpected pricing claim layout".into())),
+        }
+    }
+}
+
+/// Comparison claim committed by Agent B's guest for a batch of pricing options:
+/// `(uint256 optionCount, uint256 cheapestIndex, uint256 cheapestPriceCents, string currency, string options)`
+///
+/// `options` is the whole comparison table, one `FROM-TO DEPART[/RETURN] PRICE` row per
+/// option in request order, `;`-separated, with `-` for a missing date or an option that
+/// couldn't be priced. The cheapest fields let a contract read the winner without parsing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceComparisonClaim {
+    pub option_count: u64,
+    pub cheapest_index: u64,
+    pub cheapest_price_cents: u64,
+    pub currency: String,
+    pub options: String,
+}
+
+impl PriceComparisonClaim {
+    pub const TYPES: [AbiType; 5] = [AbiType::Uint, AbiType::Uint, AbiType::Uint, AbiType::String, AbiType::String];
+
+    pub fn abi_encode(&self) -> Vec<u8> {
+        encode(&[
+            AbiValue::Uint(self.option_count as u128),
+            AbiValue::Uint(self.cheapest_index as u128),
+            AbiValue::Uint(self.cheapest_price_cents as u128),
+            AbiValue::String(self.currency.clone()),
+            AbiValue::String(self.options.clone()),
+        ])
+    }
+
+    pub fn abi_decode(data: &[u8]) -> Result<Self, ZkProtocolError> {
+        let int = |n: &u128| u64::try_from(*n).map_err(|_| ZkProtocolError::Abi("value exceeds u64".into()));
+        match decode(data, &Self::TYPES)?.as_slice() {
+            [AbiValue::Uint(count), AbiValue::Uint(index), AbiValue::Uint(cents), AbiValue::String(currency), AbiValue::String(options)] => Ok(Self {
+                option_count: int(count)?,
+                cheapest_index: int(index)?,
+                cheapest_price_cents: int(cents)?,
+                currency: currency.clone(),
+                options: options.clone(),
+            }),
+            _ => Err(ZkProtocolError::Abi("unexpected price comparison claim layout".into())),
+        }
+    }
+}
+
+/// Booking claim committed by Agent B's guest:
+/// `(string bookingId, string status, string confirmationCode, string seat, bytes32 passengerCommitment)`
+///
+/// `seat` is empty when the booking didn't choose one. The passenger appears only as a
+/// salted [`crate::PiiCommitment`] of their name and email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookingClaim {
+    pub booking_id: String,
+    pub status: String,
+    pub confirmation_code: String,
+    pub seat: String,
+    pub passenger_commitment: [u8; 32],
+}
+
+impl BookingClaim {
+    pub const TYPES: [AbiType; 5] =
+        [AbiType::String, AbiType::String, AbiType::String, AbiType::String, AbiType::FixedBytes32];
+
+    pub fn abi_encode(&self) -> Vec<u8> {
+        encode(&[
+            AbiValue::String(self.booking_id.clone()),
+            AbiValue::String(self.status.clone()),
+            AbiValue::String(self.confirmation_code.clone()),
+            AbiValue::String(self.seat.clone()),
+            AbiValue::FixedBytes32(self.passenger_commitment),
+        ])
+    }
+
+    pub fn abi_decode(data: &[u8]) -> Result<Self, ZkProtocolError> {
+        match decode(data, &Self::TYPES)?.as_slice() {
+            [AbiValue::String(booking_id), AbiValue::String(status), AbiValue::String(code), AbiValue::String(seat), AbiValue::FixedBytes32(commitment)] => Ok(Self {
+                booking_id: booking_id.clone(),
+                status: status.clone(),
+                confirmation_code: code.clone(),
+                seat: seat.clone(),
+                passenger_commitment: *commitment,
+            }),
+            _ => Err(ZkProtocolError::Abi("unexpected booking claim layout".into())),
+        }
+    }
+}
+
+/// Cancellation claim committed by Agent B's guest:
+/// `(string bookingId, uint256 fareCents, uint256 feeCents, uint256 refundCents, string currency)`
+///
+/// Amounts are in `currency`'s minor units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancellationClaim {
+    pub booking_id: String,
+    pub fare_cents: u64,
+    pub fee_cents: u64,
+    pub refund_cents: u64,
+    pub currency: String,
+}
+
+impl CancellationClaim {
+    pub const TYPES: [AbiType; 5] = [AbiType::String, AbiType::Uint, AbiType::Uint, AbiType::Uint, AbiType::String];
+
+    pub fn abi_encode(&self) -> Vec<u8> {
+        encode(&[
+            AbiValue::String(self.booking_id.clone()),
+            AbiValue::Uint(self.fare_cents as u128),
+            AbiValue::Uint(self.fee_cents as u128),
+            AbiValue::Uint(self.refund_cents as u128),
+            AbiValue::String(self.currency.clone()),
+        ])
+    }
+
+    pub fn abi_decode(data: &[u8]) -> Result<Self, ZkProtocolError> {
+        let cents = |n: &u128| u64::try_from(*n).map_err(|_| ZkProtocolError::Abi("amount exceeds u64".into()));
+        match decode(data, &Self::TYPES)?.as_slice() {
+            [AbiValue::String(booking_id), AbiValue::Uint(fare), AbiValue::Uint(fee), AbiValue::Uint(refund), AbiValue::String(currency)] => Ok(Self {
+                booking_id: booking_id.clone(),
+                fare_cents: cents(fare)?,
+                fee_cents: cents(fee)?,
+                refund_cents: cents(refund)?,
+                currency: currency.clone(),
+            }),
+            _ => Err(ZkProtocolError::Abi("unexpected cancellation claim layout".into())),
+        }
+    }
+}
+
+/// ISO 4217 currencies without a minor unit
+const ZERO_DECIMAL_CURRENCIES: [&str; 5] = ["JPY", "KRW", "VND", "CLP", "ISK"];
+
+/// Decimal digits of `currency`'s minor unit: 0 for JPY and the like, else 2
+pub fn minor_digits(currency: &str) -> u32 {
+    if ZERO_DECIMAL_CURRENCIES.iter().any(|c| c.eq_ignore_ascii_case(currency)) {
+        0
+    } else {
+        2
+    }
+}
+
+/// Display form of a price in minor units: `(67990, "USD")` → "679.90 USD"
+pub fn format_price(price_cents: u64, currency: &str) -> String {
+    let digits = minor_digits(currency);
+    if digits == 0 {
+        return format!("{} {}", price_cents, currency);
+    }
+    let scale = 10u64.pow(digits);
+    format!(
+        "{}.{:0width$} {}",
+        price_cents / scale,
+        price_cents % scale,
+        currency,
+        width = digits as usize
+    )
+}
+
+fn uint_word(n: u128) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&n.to_be_bytes());
+    word
+}
+
+fn encode_dynamic(tail: &mut Vec<u8>, bytes: &[u8]) {
+    tail.extend_from_slice(&uint_word(bytes.len() as u128));
+    tail.extend_from_slice(bytes);
+    let padding = (32 - bytes.len() % 32) % 32;
+    tail.extend(core::iter::repeat_n(0u8, padding));
+}
+
+fn read_word(data: &[u8], offset: usize) -> Result<[u8; 32], ZkProtocolError> {
+    let truncated = || ZkProtocolError::Abi(format!("truncated data at offset {}", offset));
+    let end = offset.checked_add(32).ok_or_else(truncated)?;
+    let slice = data.get(offset..end).ok_or_else(truncated)?;
+    let mut word = [0u8; 32];
+    word.copy_from_slice(slice);
+    Ok(word)
+}
+
+fn word_to_u128(word: &[u8; 32]) -> Result<u128, ZkProtocolError> {
+    if word[..16].iter().any(|b| *b != 0) {
+        return Err(ZkProtocolError::Abi("uint256 value exceeds u128".into()));
+    }
+    let mut low = [0u8; 16];
+    low.copy_from_slice(&word[16..]);
+    Ok(u128::from_be_bytes(low))
+}
+
+fn word_to_usize(word: &[u8; 32]) -> Result<usize, ZkProtocolError> {
+    usize::try_from(word_to_u128(word)?).map_err(|_| ZkProtocolError::Abi("offset too large".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_claim_roundtrip() {
+        let claim = PricingClaim {
+            from: "NYC".into(),
+            to: "LON".into(),
+            price_cents: 57800,
+            vip: true,
+            quote_id: "q_1".into(),
+            currency: "USD".into(),
+            promo_code: String::new(),
+            departure_date: "2026-11-02".into(),
+            return_date: String::new(),
+        };
+        let encoded = claim.abi_encode();
+        // 9 head words + 5 × (length word + 1 padded data word) + 2 length words for ""
+        assert_eq!(encoded.len(), 21 * 32);
+        assert_eq!(PricingClaim::abi_decode(&encoded).unwrap(), claim);
+        assert_eq!(format_price(claim.price_cents, &claim.currency), "578.00 USD");
+        assert_eq!(format_price(101_645, "JPY"), "101645 JPY");
+    }
+
+    #[test]
+    fn test_price_comparison_claim_roundtrip() {
+        let claim = PriceComparisonClaim {
+            option_count: 2,
+            cheapest_index: 1,
+            cheapest_price_cents: 67990,
+            currency: "USD".into(),
+            options: "NYC-LON 2026-05-16 74789;NYC-LON 2026-05-13 67990".into(),
+        };
+        let encoded = claim.abi_encode();
+        // 5 head words + "USD" (length word + 1 data word) + 49-byte table (length word + 2 data words)
+        assert_eq!(encoded.len(), 10 * 32);
+        assert_eq!(PriceComparisonClaim::abi_decode(&encoded).unwrap(), claim);
+    }
+
+    #[test]
+    fn test_public_values_bind_the_input() {
+        let claim = BookingClaim {
+            booking_id: "BK1".into(),
+            status: "confirmed".into(),
+            confirmation_code: "CONF1".into(),
+            seat: String::new(),
+            passenger_commitment: [3; 32],
+        }
+        .abi_encode();
+        let encoded = PublicValues::new(b"input", 1, [7; 32], claim.clone()).abi_encode();
+
+        let decoded = PublicValues::check_input(ValuesLayout::Bound, &encoded, b"input").unwrap().unwrap();
+        assert_eq!((decoded.ruleset_version, decoded.ruleset_hash), (1, [7; 32]));
+        assert_eq!(decoded.claim, claim);
+        let booking = BookingClaim::abi_decode(&decoded.claim).unwrap();
+        assert_eq!((booking.booking_id.as_str(), booking.passenger_commitment), ("BK1", [3; 32]));
+        assert!(PublicValues::check_input(ValuesLayout::Bound, &encoded, b"other input").is_err());
+
+        // A bare claim or garbage can't skip the check; only a program declared claim-only does
+        assert!(PublicValues::check_input(ValuesLayout::Bound, &claim, b"input").is_err());
+        assert!(PublicValues::check_input(ValuesLayout::Bound, &[1, 2, 3], b"input").is_err());
+        assert!(PublicValues::check_input(ValuesLayout::ClaimOnly, &claim, b"input").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_decode_rejects_overflowing_offset_and_length() {
+        let huge = uint_word(usize::MAX as u128);
+
+        // Offset word pointing at the very end of the address space
+        assert!(matches!(decode(&huge, &[AbiType::Bytes]), Err(ZkProtocolError::Abi(_))));
+
+        // Valid offset, length that wraps `start + len`
+        let mut data = uint_word(32).to_vec();
+        data.extend_from_slice(&huge);
+        assert!(matches!(decode(&data, &[AbiType::String]), Err(ZkProtocolError::Abi(_))));
+    }
+}
@@ -0,0 +1,187 @@
+//! Self-contained proof bundle
+//!
+//! Everything a third party needs to re-check an attestation offline:
+//! the proof, the committed public values, the VK hash, and which program produced it.
+//! Bytes are stored raw and serialized as 0x-prefixed hex.
+
+#[cfg(any(feature = "std", feature = "verify"))]
+use alloc::string::ToString;
+use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::{ProofSystem, ZkProtocolError};
+#[cfg(feature = "std")]
+use crate::{AttestResponse, hex_to_bytes};
+
+/// First 4 bytes of the SP1 v5 Groth16 verifier's VERIFIER_HASH, prefixed to its proofs
+pub const GROTH16_SELECTOR: [u8; 4] = [0xa4, 0x59, 0x4c, 0x59];
+
+/// First 4 bytes of the SP1 v5 PLONK verifier's VERIFIER_HASH, prefixed to its proofs
+pub const PLONK_SELECTOR: [u8; 4] = [0xd4, 0xe8, 0xec, 0xd2];
+
+/// SNARK wrapper used for the proof
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofMode {
+    #[default]
+    Groth16,
+    Plonk,
+}
+
+impl ProofMode {
+    /// Mode of an SP1 proof, read from its verifier-hash prefix; `None` if unrecognized
+    pub fn from_proof(proof: &[u8]) -> Option<Self> {
+        match proof.get(..4)? {
+            prefix if prefix == GROTH16_SELECTOR => Some(ProofMode::Groth16),
+            prefix if prefix == PLONK_SELECTOR => Some(ProofMode::Plonk),
+            _ => None,
+        }
+    }
+
+    /// `proofType` the ZeroProof contract routes this mode's proofs by
+    pub fn proof_system(self) -> ProofSystem {
+        match self {
+            ProofMode::Groth16 => ProofSystem::Sp1Zkvm,
+            ProofMode::Plonk => ProofSystem::Sp1Plonk,
+        }
+    }
+}
+
+/// How the attester produced a proof (`AttestResponse::mode`)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverMode {
+    /// A real SP1 proof, verifiable on-chain
+    #[default]
+    Sp1,
+    /// SP1 mock proof from an attester in `PROVER_MODE=mock`: the program ran and its public
+    /// values are real, but the proof bytes are empty and no verifier accepts them
+    Mock,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofBundle {
+    /// Proof bytes as returned by the attester (verifier selector prefix included)
+    #[serde(with = "hex_bytes")]
+    pub proof: Vec<u8>,
+    /// Public values committed by the zkVM program
+    #[serde(with = "hex_bytes")]
+    pub public_values: Vec<u8>,
+    /// bytes32 VK hash of the program
+    pub vk_hash: String,
+    /// Attester program ID the proof was generated for
+    pub program_id: String,
+    /// SHA-256 of the guest ELF, as advertised by the agent
+    pub elf_hash: String,
+    pub proof_mode: ProofMode,
+    /// Unix timestamp (seconds) when the bundle was created
+    pub created_at: u64,
+}
+
+/// Key material accepted by `ProofBundle::verify_locally`
+pub enum ElfOrVk<'a> {
+    /// Guest ELF - the VK is re-derived from it (requires the `sdk` feature)
+    Elf(&'a [u8]),
+    /// bytes32 VK hash, checked against the bundle's own `vk_hash`
+    VkHash(&'a str),
+}
+
+impl ProofBundle {
+    /// Build a bundle from an attester response, stamped with the current time
+    #[cfg(feature = "std")]
+    pub fn from_attestation(
+        response: &AttestResponse,
+        program_id: &str,
+        elf_hash: &str,
+        proof_mode: ProofMode,
+    ) -> Result<Self, ZkProtocolError> {
+        Ok(Self {
+            proof: hex_to_bytes(&response.proof)?,
+            public_values: hex_to_bytes(&response.public_values)?,
+            vk_hash: response.vk_hash.clone(),
+            program_id: program_id.to_string(),
+            elf_hash: elf_hash.to_string(),
+            proof_mode,
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        })
+    }
+
+    /// SP1 mock proofs carry no proof bytes; they can't be verified on-chain or offline
+    pub fn is_mock(&self) -> bool {
+        self.proof.is_empty()
+    }
+
+    /// Export as pretty-printed JSON with hex-encoded byte fields
+    pub fn to_hex_json(&self) -> Result<String, ZkProtocolError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Import a bundle previously produced by `to_hex_json`
+    pub fn from_hex_json(json: &str) -> Result<Self, ZkProtocolError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Re-verify the proof without contacting any running service
+    #[cfg(feature = "verify")]
+    pub fn verify_locally(&self, key: ElfOrVk<'_>) -> Result<(), ZkProtocolError> {
+        let vk_hash = match key {
+            ElfOrVk::VkHash(vk_hash) => vk_hash.to_string(),
+            #[cfg(feature = "sdk")]
+            ElfOrVk::Elf(elf) => {
+                use sp1_sdk::HashableKey;
+                let (_, vk) = sp1_sdk::ProverClient::from_env().setup(elf);
+                vk.bytes32()
+            }
+            #[cfg(not(feature = "sdk"))]
+            ElfOrVk::Elf(_) => {
+                return Err(ZkProtocolError::Verification(
+                    "verifying against an ELF requires the `sdk` feature".to_string(),
+                ));
+            }
+        };
+
+        if !vk_hash.eq_ignore_ascii_case(&self.vk_hash) {
+            return Err(ZkProtocolError::Verification(alloc::format!(
+                "VK hash mismatch: bundle has {}, expected {}",
+                self.vk_hash, vk_hash
+            )));
+        }
+
+        let result = match self.proof_mode {
+            ProofMode::Groth16 => sp1_verifier::Groth16Verifier::verify(
+                &self.proof,
+                &self.public_values,
+                &vk_hash,
+                *sp1_verifier::GROTH16_VK_BYTES,
+            )
+            .map_err(|e| e.to_string()),
+            ProofMode::Plonk => sp1_verifier::PlonkVerifier::verify(
+                &self.proof,
+                &self.public_values,
+                &vk_hash,
+                *sp1_verifier::PLONK_VK_BYTES,
+            )
+            .map_err(|e| e.to_string()),
+        };
+
+        result.map_err(ZkProtocolError::Verification)
+    }
+}
+
+/// Serde adapter: Vec<u8> <-> "0x..." hex string
+mod hex_bytes {
+    use alloc::{format, string::String, vec::Vec};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        crate::hex_to_bytes(&s).map_err(serde::de::Error::custom)
+    }
+}
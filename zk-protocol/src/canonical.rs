@@ -0,0 +1,147 @@
+//! Deterministic byte encoding used for hashing
+//!
+//! Produces exactly what bincode 1.x (default options) would for the same
+//! sequence of fields, but without needing bincode, so it also works in no_std guests.
+//! JSON values get their own tagged encoding with object keys sorted, so the bytes don't
+//! depend on serde_json's map type (`preserve_order` keeps insertion order).
+
+use alloc::vec::Vec;
+use serde_json::Value;
+
+#[derive(Default)]
+pub(crate) struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn bool(&mut self, v: bool) {
+        self.buf.push(v as u8);
+    }
+
+    /// Enum discriminant (bincode encodes unit variants as u32)
+    pub(crate) fn variant(&mut self, idx: u32) {
+        self.buf.extend_from_slice(&idx.to_le_bytes());
+    }
+
+    /// Length-prefixed byte string
+    pub(crate) fn bytes(&mut self, v: &[u8]) {
+        self.u64(v.len() as u64);
+        self.buf.extend_from_slice(v);
+    }
+
+    pub(crate) fn str(&mut self, v: &str) {
+        self.bytes(v.as_bytes());
+    }
+
+    pub(crate) fn opt_str(&mut self, v: Option<&str>) {
+        match v {
+            Some(s) => {
+                self.buf.push(1);
+                self.str(s);
+            }
+            None => self.buf.push(0),
+        }
+    }
+
+    pub(crate) fn opt_u64(&mut self, v: Option<u64>) {
+        match v {
+            Some(n) => {
+                self.buf.push(1);
+                self.u64(n);
+            }
+            None => self.buf.push(0),
+        }
+    }
+
+    /// A JSON value: a tag byte, then its contents; object entries in key order
+    pub(crate) fn json(&mut self, v: &Value) {
+        match v {
+            Value::Null => self.buf.push(0),
+            Value::Bool(b) => {
+                self.buf.push(1);
+                self.bool(*b);
+            }
+            Value::Number(n) => {
+                if let Some(u) = n.as_u64() {
+                    self.buf.push(2);
+                    self.u64(u);
+                } else if let Some(i) = n.as_i64() {
+                    self.buf.push(3);
+                    self.u64(i as u64);
+                } else {
+                    self.buf.push(4);
+                    self.u64(n.as_f64().unwrap_or_default().to_bits());
+                }
+            }
+            Value::String(s) => {
+                self.buf.push(5);
+                self.str(s);
+            }
+            Value::Array(items) => {
+                self.buf.push(6);
+                self.u64(items.len() as u64);
+                items.iter().for_each(|item| self.json(item));
+            }
+            Value::Object(map) => {
+                self.buf.push(7);
+                self.u64(map.len() as u64);
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                for (key, value) in entries {
+                    self.str(key);
+                    self.json(value);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn opt_json(&mut self, v: Option<&Value>) {
+        match v {
+            Some(value) => {
+                self.buf.push(1);
+                self.json(value);
+            }
+            None => self.buf.push(0),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn encode(v: &Value) -> Vec<u8> {
+        let mut enc = Encoder::default();
+        enc.json(v);
+        enc.finish()
+    }
+
+    #[test]
+    fn test_json_ignores_key_order() {
+        let mut forward = serde_json::Map::new();
+        forward.insert("a".into(), json!(1));
+        forward.insert("b".into(), json!([true, null]));
+        let mut reverse = serde_json::Map::new();
+        reverse.insert("b".into(), json!([true, null]));
+        reverse.insert("a".into(), json!(1));
+        assert_eq!(encode(&Value::Object(forward)), encode(&Value::Object(reverse)));
+    }
+
+    #[test]
+    fn test_json_distinguishes_types() {
+        assert_ne!(encode(&json!(1)), encode(&json!("1")));
+        assert_ne!(encode(&json!(1)), encode(&json!(-1)));
+        assert_ne!(encode(&json!(1)), encode(&json!(1.0)));
+        assert_ne!(encode(&json!([])), encode(&json!({})));
+        assert_eq!(encode(&json!({ "a": 1 })), [7, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, b'a', 2, 1, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,75 @@
+//! Claim and proof-system identifiers shared with the ZeroProof contract
+//!
+//! On-chain these are `keccak256(label)`; keeping the labels here means
+//! Agent A, Agent B, the attester, and the ABI encoder can't drift apart.
+
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// What an agent is attesting to (`Claim.claimType` on-chain)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaimType {
+    Pricing,
+    Booking,
+    Payment,
+}
+
+impl ClaimType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClaimType::Pricing => "pricing",
+            ClaimType::Booking => "booking",
+            ClaimType::Payment => "payment",
+        }
+    }
+
+    /// keccak256(label), as stored in `Claim.claimType`
+    pub fn as_bytes32(&self) -> [u8; 32] {
+        keccak256(self.label().as_bytes())
+    }
+}
+
+/// Proof system used to back a claim (`proofType` argument of `verifyProof`)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProofSystem {
+    #[serde(rename = "sp1-zkvm")]
+    Sp1Zkvm,
+    /// SP1 proof wrapped in PLONK, checked by the SP1 PLONK verifier
+    #[serde(rename = "sp1-plonk")]
+    Sp1Plonk,
+    #[serde(rename = "reclaim-zktls")]
+    ReclaimZktls,
+}
+
+impl ProofSystem {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProofSystem::Sp1Zkvm => "sp1-zkvm",
+            ProofSystem::Sp1Plonk => "sp1-plonk",
+            ProofSystem::ReclaimZktls => "reclaim-zktls",
+        }
+    }
+
+    /// keccak256(label), matching `ZeroProof.SP1_ZKVM` / `SP1_PLONK` / `RECLAIM_ZKTLS`
+    pub fn as_bytes32(&self) -> [u8; 32] {
+        keccak256(self.label().as_bytes())
+    }
+}
+
+impl fmt::Display for ClaimType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl fmt::Display for ProofSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
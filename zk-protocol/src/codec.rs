@@ -0,0 +1,44 @@
+//! Input/output codecs for zkVM programs
+//!
+//! bincode is the default (it's what `sp1_zkvm::io::read` uses).
+//! CBOR and Borsh are feature-gated for guest programs written against other ecosystems.
+
+#[cfg(any(feature = "cbor", feature = "borsh"))]
+use alloc::{string::ToString, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "cbor", feature = "borsh"))]
+use crate::ZkProtocolError;
+
+/// Encoding of `AttestRequest::input_bytes`, so the attester and guest agree on the format
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Codec {
+    #[default]
+    Bincode,
+    Cbor,
+    Borsh,
+}
+
+#[cfg(feature = "cbor")]
+pub fn serialize_input_cbor<T: Serialize>(input: &T) -> Result<Vec<u8>, ZkProtocolError> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(input, &mut bytes)
+        .map_err(|e| ZkProtocolError::Serialization(e.to_string()))?;
+    Ok(bytes)
+}
+
+#[cfg(feature = "cbor")]
+pub fn deserialize_output_cbor<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, ZkProtocolError> {
+    ciborium::from_reader(bytes).map_err(|e| ZkProtocolError::Serialization(e.to_string()))
+}
+
+#[cfg(feature = "borsh")]
+pub fn serialize_input_borsh<T: borsh::BorshSerialize>(input: &T) -> Result<Vec<u8>, ZkProtocolError> {
+    borsh::to_vec(input).map_err(|e| ZkProtocolError::Serialization(e.to_string()))
+}
+
+#[cfg(feature = "borsh")]
+pub fn deserialize_output_borsh<T: borsh::BorshDeserialize>(bytes: &[u8]) -> Result<T, ZkProtocolError> {
+    borsh::from_slice(bytes).map_err(|e| ZkProtocolError::Serialization(e.to_string()))
+}
@@ -0,0 +1,102 @@
+//! Versioned wire envelope for messages exchanged between agents and the attester
+//!
+//! Every message is sent as `Envelope<T>`: the payload fields stay at the top level
+//! (so the JSON shape is unchanged) and a `protocol_version` field is added next to them.
+//! Payloads produced before the envelope existed carry no version and decode as
+//! `ProtocolVersion::LEGACY`; `migrate_legacy()` upgrades them explicitly.
+
+use alloc::string::ToString;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+use crate::ZkProtocolError;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl ProtocolVersion {
+    /// Version spoken by this build of zk-protocol
+    pub const CURRENT: ProtocolVersion = ProtocolVersion { major: 1, minor: 0 };
+    /// Implied version of payloads sent before versioning was introduced
+    pub const LEGACY: ProtocolVersion = ProtocolVersion { major: 0, minor: 0 };
+
+    /// Negotiation rules:
+    /// - different major → reject (payload layout may differ)
+    /// - different minor → accept, but caller should warn (additive changes only)
+    pub fn negotiate(&self, peer: &ProtocolVersion) -> Result<VersionCheck, ZkProtocolError> {
+        if self.major != peer.major {
+            return Err(ZkProtocolError::VersionMismatch {
+                expected: self.to_string(),
+                actual: peer.to_string(),
+            });
+        }
+        if self.minor != peer.minor {
+            Ok(VersionCheck::MinorSkew { local: *self, peer: *peer })
+        } else {
+            Ok(VersionCheck::Exact)
+        }
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> Self {
+        ProtocolVersion::LEGACY
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Outcome of a successful version negotiation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionCheck {
+    Exact,
+    /// Compatible but not identical - log a warning
+    MinorSkew { local: ProtocolVersion, peer: ProtocolVersion },
+}
+
+/// Versioned wrapper around a protocol message
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Envelope<T> {
+    #[serde(default)]
+    pub protocol_version: ProtocolVersion,
+    #[serde(flatten)]
+    pub body: T,
+}
+
+impl<T> Envelope<T> {
+    /// Wrap a message with the current protocol version
+    pub fn new(body: T) -> Self {
+        Self {
+            protocol_version: ProtocolVersion::CURRENT,
+            body,
+        }
+    }
+
+    /// Check the sender's version against ours
+    pub fn check(&self) -> Result<VersionCheck, ZkProtocolError> {
+        ProtocolVersion::CURRENT.negotiate(&self.protocol_version)
+    }
+
+    /// Unwrap the message, rejecting incompatible major versions
+    pub fn open(self) -> Result<(T, VersionCheck), ZkProtocolError> {
+        let check = self.check()?;
+        Ok((self.body, check))
+    }
+
+    /// Upgrade an unversioned (pre-envelope) payload to the current version.
+    /// The 0.x and 1.0 layouts are identical, so only the version is rewritten.
+    /// Payloads that already carry a version are left untouched.
+    pub fn migrate_legacy(mut self) -> Self {
+        if self.protocol_version == ProtocolVersion::LEGACY {
+            self.protocol_version = ProtocolVersion { major: ProtocolVersion::CURRENT.major, minor: 0 };
+        }
+        self
+    }
+}
@@ -0,0 +1,51 @@
+//! Error type shared by all zk-protocol helpers
+//!
+//! Agents match on these variants instead of on raw bincode/hex/serde errors,
+//! so every service reports protocol failures the same way.
+
+use alloc::string::{String, ToString};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ZkProtocolError {
+    /// Input/output could not be (de)serialized for the zkVM
+    #[error("serialization error: {0}")]
+    Serialization(String),
+    /// A hex string (proof, public values, vk hash, input bytes) was malformed
+    #[error("invalid hex: {0}")]
+    InvalidHex(String),
+    /// A JSON value was expected to be an array of bytes (0-255)
+    #[error("invalid JSON byte array: {0}")]
+    InvalidJsonArray(String),
+    /// Peer speaks an incompatible protocol version
+    #[error("protocol version mismatch: expected {expected}, got {actual}")]
+    VersionMismatch { expected: String, actual: String },
+    /// Proof did not verify against the given key material
+    #[error("verification failed: {0}")]
+    Verification(String),
+    /// Agent response signature or signer identity is invalid
+    #[error("signature error: {0}")]
+    Signature(String),
+    /// ABI-encoded public values could not be decoded
+    #[error("ABI decode error: {0}")]
+    Abi(String),
+}
+
+#[cfg(feature = "std")]
+impl From<bincode::Error> for ZkProtocolError {
+    fn from(err: bincode::Error) -> Self {
+        ZkProtocolError::Serialization(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for ZkProtocolError {
+    fn from(err: serde_json::Error) -> Self {
+        ZkProtocolError::Serialization(err.to_string())
+    }
+}
+
+impl From<hex::FromHexError> for ZkProtocolError {
+    fn from(err: hex::FromHexError) -> Self {
+        ZkProtocolError::InvalidHex(err.to_string())
+    }
+}
@@ -0,0 +1,132 @@
+//! Agent identity and response signing
+//!
+//! Agent B signs every `AgentResponse` (price, program_id, elf_hash) with an Ed25519 key.
+//! Agent A checks the signature - and optionally the expected signer DID - before it
+//! spends prover time on an attestation, so a MITM cannot swap in a different program_id.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{AgentResponse, ZkProtocolError, hex_to_bytes};
+
+/// Domain separator so a response signature can't be replayed as any other message
+const RESPONSE_DOMAIN: &[u8] = b"zeroproof/agent-response/v1";
+
+/// Multicodec prefix for Ed25519 public keys (did:key)
+pub(crate) const ED25519_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+/// Ed25519 keypair with a did:key identifier
+pub struct AgentIdentity {
+    signing_key: SigningKey,
+}
+
+impl AgentIdentity {
+    /// Fresh random identity (ephemeral, e.g. for local development)
+    pub fn generate() -> Self {
+        Self {
+            signing_key: SigningKey::generate(&mut rand::rngs::OsRng),
+        }
+    }
+
+    /// Load from a 32-byte hex seed (with or without 0x)
+    pub fn from_seed_hex(seed_hex: &str) -> Result<Self, ZkProtocolError> {
+        let seed: [u8; 32] = hex_to_bytes(seed_hex)?
+            .try_into()
+            .map_err(|_| ZkProtocolError::InvalidHex("signing seed must be 32 bytes".to_string()))?;
+        Ok(Self {
+            signing_key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.signing_key.verifying_key().to_bytes()))
+    }
+
+    /// did:key identifier derived from the public key
+    pub fn did(&self) -> String {
+        did_from_public_key(&self.signing_key.verifying_key())
+    }
+
+    pub(crate) fn signing_key(&self) -> &SigningKey {
+        &self.signing_key
+    }
+
+    /// Sign an agent response
+    pub fn sign_response(&self, response: AgentResponse) -> Result<SignedAgentResponse, ZkProtocolError> {
+        let digest = response_digest(&response)?;
+        let signature = self.signing_key.sign(&digest);
+        Ok(SignedAgentResponse {
+            response,
+            signer: self.did(),
+            public_key: self.public_key_hex(),
+            signature: format!("0x{}", hex::encode(signature.to_bytes())),
+        })
+    }
+}
+
+/// `AgentResponse` plus the signer's identity and signature.
+/// Serializes flat: the response fields followed by `signer`, `public_key`, `signature`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignedAgentResponse {
+    #[serde(flatten)]
+    pub response: AgentResponse,
+    /// did:key of the signing agent
+    pub signer: String,
+    /// Hex-encoded Ed25519 public key
+    pub public_key: String,
+    /// Hex-encoded Ed25519 signature over the response digest
+    pub signature: String,
+}
+
+impl SignedAgentResponse {
+    /// Check the signature, and that the signer matches `expected_signer` if given.
+    /// Returns the inner response only if everything checks out.
+    pub fn verify(&self, expected_signer: Option<&str>) -> Result<&AgentResponse, ZkProtocolError> {
+        let key_bytes: [u8; 32] = hex_to_bytes(&self.public_key)?
+            .try_into()
+            .map_err(|_| ZkProtocolError::InvalidHex("public key must be 32 bytes".to_string()))?;
+        let verifying_key = VerifyingKey::from_bytes(&key_bytes)
+            .map_err(|e| ZkProtocolError::Signature(e.to_string()))?;
+
+        if did_from_public_key(&verifying_key) != self.signer {
+            return Err(ZkProtocolError::Signature("signer DID does not match public key".to_string()));
+        }
+        if let Some(expected) = expected_signer {
+            if expected != self.signer {
+                return Err(ZkProtocolError::Signature(format!(
+                    "unexpected signer: expected {}, got {}",
+                    expected, self.signer
+                )));
+            }
+        }
+
+        let sig_bytes: [u8; 64] = hex_to_bytes(&self.signature)?
+            .try_into()
+            .map_err(|_| ZkProtocolError::InvalidHex("signature must be 64 bytes".to_string()))?;
+        let signature = Signature::from_bytes(&sig_bytes);
+
+        let digest = response_digest(&self.response)?;
+        verifying_key
+            .verify(&digest, &signature)
+            .map_err(|e| ZkProtocolError::Signature(e.to_string()))?;
+
+        Ok(&self.response)
+    }
+}
+
+/// SHA-256(domain || bincode(program_id, elf_hash, compact JSON of data))
+fn response_digest(response: &AgentResponse) -> Result<[u8; 32], ZkProtocolError> {
+    let data = serde_json::to_string(&response.data)?;
+    let encoded = bincode::serialize(&(&response.program_id, &response.elf_hash, &data))?;
+    let mut hasher = Sha256::new();
+    hasher.update(RESPONSE_DOMAIN);
+    hasher.update(&encoded);
+    Ok(hasher.finalize().into())
+}
+
+fn did_from_public_key(key: &VerifyingKey) -> String {
+    let mut bytes = ED25519_MULTICODEC.to_vec();
+    bytes.extend_from_slice(key.as_bytes());
+    format!("did:key:z{}", bs58::encode(bytes).into_string())
+}
@@ -0,0 +1,52 @@
+//! Async attestation job types
+//!
+//! Proving takes minutes, so the attester can accept a request, hand back an
+//! `AttestJobId`, and let Agent A poll `AttestJobStatus` until it is terminal.
+
+use alloc::string::String;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+use crate::AttestResponse;
+
+/// Opaque job handle returned by the attester
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct AttestJobId(pub String);
+
+impl fmt::Display for AttestJobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Lifecycle of an attestation job
+/// Serialized with a `status` tag, e.g. `{"status": "proving", "pct": 40}`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AttestJobStatus {
+    /// Accepted, waiting for a prover
+    Queued,
+    /// Loading ELF / computing keys
+    Executing,
+    /// Proof generation in progress (0-100)
+    Proving { pct: u8 },
+    /// Finished successfully
+    Done(AttestResponse),
+    /// Finished with an error
+    Failed { code: String, message: String },
+}
+
+impl AttestJobStatus {
+    /// True once the job will not change state again
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, AttestJobStatus::Done(_) | AttestJobStatus::Failed { .. })
+    }
+}
+
+/// Response to a job submission
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AttestJobAccepted {
+    pub job_id: AttestJobId,
+    pub status: AttestJobStatus,
+}
@@ -1,32 +1,113 @@
-/// General protocol for ZK attestation between agents
-/// This library provides common types and serialization helpers
-/// that any agent can use without depending on other agents' code.
+//! General protocol for ZK attestation between agents
+//! This library provides common types and serialization helpers
+//! that any agent can use without depending on other agents' code.
+//!
+//! Builds `no_std` + `alloc` with `default-features = false`, so the same types
+//! and hashing helpers can be used inside SP1 guest programs.
+//! The default `std` feature adds bincode helpers and wall-clock timestamps.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{format, string::String, vec::Vec};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+pub mod abi;
+mod bundle;
+mod canonical;
+mod claims;
+mod codec;
+mod envelope;
+mod error;
+#[cfg(feature = "identity")]
+mod identity;
+#[cfg(feature = "identity")]
+pub mod tap;
+mod jobs;
+mod output;
+mod pii;
+
+pub use bundle::{ElfOrVk, ProofBundle, ProofMode, ProverMode, GROTH16_SELECTOR, PLONK_SELECTOR};
+pub use claims::{ClaimType, ProofSystem, keccak256};
+pub use codec::Codec;
+#[cfg(feature = "cbor")]
+pub use codec::{deserialize_output_cbor, serialize_input_cbor};
+#[cfg(feature = "borsh")]
+pub use codec::{deserialize_output_borsh, serialize_input_borsh};
+pub use envelope::{Envelope, ProtocolVersion, VersionCheck};
+pub use error::ZkProtocolError;
+#[cfg(feature = "identity")]
+pub use identity::{AgentIdentity, SignedAgentResponse};
+pub use jobs::{AttestJobAccepted, AttestJobId, AttestJobStatus};
+pub use output::{OutputDescriptor, OutputField};
+pub use pii::PiiCommitment;
 
 /// Request to the attester service to generate a ZK proof
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AttestRequest {
     pub program_id: String,
-    /// Input data as raw bytes (bincode-serialized)
+    /// Input data as raw bytes (encoded with `codec`, bincode by default)
     /// Will be passed to the zkVM program via stdin
     pub input_bytes: Vec<u8>,
+    /// Encoding of `input_bytes`
+    #[serde(default)]
+    pub codec: Codec,
     /// Expected output for verification (optional, format defined by agent)
     pub claimed_output: Option<Value>,
     /// Whether to verify the proof locally before returning
     #[serde(default = "default_verify")]
     pub verify_locally: bool,
+    /// Client-chosen unique value; the attester rejects repeats within its replay window
+    #[serde(default)]
+    pub nonce: Option<String>,
+    /// Unix timestamp (seconds) after which the attester must refuse the request
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// SNARK wrapper for the proof (Groth16 unless asked for PLONK)
+    #[serde(default)]
+    pub proof_mode: ProofMode,
 }
 
 fn default_verify() -> bool {
     true
 }
 
+impl AttestRequest {
+    /// SHA-256 over a deterministic encoding of the request.
+    /// `claimed_output` is encoded field by field with object keys sorted (not as JSON
+    /// text, whose key order depends on serde_json's features), so two semantically equal
+    /// requests always hash the same.
+    pub fn canonical_hash(&self) -> [u8; 32] {
+        let mut enc = canonical::Encoder::default();
+        enc.str(&self.program_id);
+        enc.bytes(&self.input_bytes);
+        enc.variant(self.codec as u32);
+        enc.opt_json(self.claimed_output.as_ref());
+        enc.bool(self.verify_locally);
+        enc.opt_str(self.nonce.as_deref());
+        enc.opt_u64(self.expires_at);
+        enc.variant(self.proof_mode as u32);
+        Sha256::digest(enc.finish()).into()
+    }
+
+    /// Hex form of `canonical_hash()` (0x-prefixed)
+    pub fn canonical_hash_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.canonical_hash()))
+    }
+
+    /// True if `expires_at` is set and lies at or before `now` (unix seconds)
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.map(|t| t <= now).unwrap_or(false)
+    }
+}
+
 /// Response from the attester service
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AttestResponse {
-    /// Hex-encoded Groth16 proof for on-chain verification
+    /// Hex-encoded Groth16 or PLONK proof for on-chain verification (see `ProofMode::from_proof`)
     pub proof: String,
     /// Public values committed by the zkVM program (hex-encoded)
     pub public_values: String,
@@ -34,10 +115,14 @@ pub struct AttestResponse {
     pub vk_hash: String,
     /// Output from the zkVM program
     pub verified_output: Value,
+    /// `mock` when the attester runs with PROVER_MODE=mock; such proofs are empty and must
+    /// not be sent on-chain
+    #[serde(default)]
+    pub mode: ProverMode,
 }
 
 /// Response from an agent's pricing/booking endpoint
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AgentResponse {
     /// Agent-specific response data (price, booking ID, etc.)
     #[serde(flatten)]
@@ -49,13 +134,15 @@ pub struct AgentResponse {
 }
 
 /// Helper to serialize any serde-compatible type to bincode bytes
-pub fn serialize_input<T: Serialize>(input: &T) -> Result<Vec<u8>, bincode::Error> {
-    bincode::serialize(input)
+#[cfg(feature = "std")]
+pub fn serialize_input<T: Serialize>(input: &T) -> Result<Vec<u8>, ZkProtocolError> {
+    Ok(bincode::serialize(input)?)
 }
 
 /// Helper to deserialize bincode bytes to any serde-compatible type
-pub fn deserialize_output<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, bincode::Error> {
-    bincode::deserialize(bytes)
+#[cfg(feature = "std")]
+pub fn deserialize_output<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T, ZkProtocolError> {
+    Ok(bincode::deserialize(bytes)?)
 }
 
 /// Convert bincode bytes to JSON array format for HTTP transport
@@ -64,10 +151,24 @@ pub fn bytes_to_json_array(bytes: &[u8]) -> Value {
 }
 
 /// Extract bytes from JSON array format
-pub fn json_array_to_bytes(value: &Value) -> Option<Vec<u8>> {
-    if let Value::Array(arr) = value {
-        Some(arr.iter().filter_map(|v| v.as_u64().map(|n| n as u8)).collect())
-    } else {
-        None
-    }
+/// Every element must be an integer in 0..=255
+pub fn json_array_to_bytes(value: &Value) -> Result<Vec<u8>, ZkProtocolError> {
+    let arr = value
+        .as_array()
+        .ok_or_else(|| ZkProtocolError::InvalidJsonArray(format!("expected array, got {}", value)))?;
+
+    arr.iter()
+        .enumerate()
+        .map(|(i, v)| {
+            v.as_u64()
+                .filter(|n| *n <= u8::MAX as u64)
+                .map(|n| n as u8)
+                .ok_or_else(|| ZkProtocolError::InvalidJsonArray(format!("element {} is not a byte: {}", i, v)))
+        })
+        .collect()
+}
+
+/// Decode a hex string, with or without "0x" prefix
+pub fn hex_to_bytes(hex_str: &str) -> Result<Vec<u8>, ZkProtocolError> {
+    Ok(hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str))?)
 }
@@ -0,0 +1,248 @@
+//! Layout of a program's committed public values
+//!
+//! A program registers an [`OutputDescriptor`] with its ELF, and the attester decodes each
+//! proof's public values with it into `verified_output`, instead of echoing what the caller
+//! claimed. Programs without one keep the old behaviour.
+//!
+//! ```json
+//! {"format": "abi", "fields": [{"name": "from", "type": "string"}, {"name": "priceCents", "type": "uint256"}]}
+//! {"format": "bincode", "fields": [{"name": "price", "type": "f64"}, {"name": "route", "type": "string"}]}
+//! {"format": "raw"}
+//! ```
+//!
+//! `bincode` is SP1's `io::commit` of a struct (little-endian, u64 length prefixes), fields
+//! in declaration order. Integers that don't fit a JSON number, bytes, and addresses come
+//! out as strings (decimal or 0x hex).
+
+use alloc::{format, string::String, string::ToString, vec::Vec};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::abi::{self, AbiType, AbiValue};
+use crate::ZkProtocolError;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum OutputDescriptor {
+    /// `abi.encode` of a tuple; types are `uint256`, `bool`, `address`, `bytes32`, `bytes`, `string`
+    Abi { fields: Vec<OutputField> },
+    /// A bincode struct; types are `bool`, `u8`–`u64`, `i8`–`i64`, `f32`, `f64`, `string`, `bytes`
+    Bincode { fields: Vec<OutputField> },
+    /// Opaque; decoded as `{"public_values": "0x..."}`
+    Raw,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OutputField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+impl OutputDescriptor {
+    /// Reject unknown types up front, at registration
+    pub fn validate(&self) -> Result<(), ZkProtocolError> {
+        match self {
+            OutputDescriptor::Abi { fields } => fields.iter().try_for_each(|f| abi_type(&f.ty).map(|_| ())),
+            OutputDescriptor::Bincode { fields } => {
+                fields.iter().try_for_each(|f| BincodeType::parse(&f.ty).map(|_| ()))
+            }
+            OutputDescriptor::Raw => Ok(()),
+        }
+    }
+
+    /// Public values as a JSON object keyed by field name
+    pub fn decode(&self, public_values: &[u8]) -> Result<Value, ZkProtocolError> {
+        let mut out = Map::new();
+        match self {
+            OutputDescriptor::Abi { fields } => {
+                let types = fields.iter().map(|f| abi_type(&f.ty)).collect::<Result<Vec<_>, _>>()?;
+                for (field, value) in fields.iter().zip(abi::decode(public_values, &types)?) {
+                    out.insert(field.name.clone(), abi_json(value));
+                }
+            }
+            OutputDescriptor::Bincode { fields } => {
+                let mut reader = Reader { data: public_values, offset: 0 };
+                for field in fields {
+                    let value = reader.read(BincodeType::parse(&field.ty)?)?;
+                    out.insert(field.name.clone(), value);
+                }
+                if reader.offset != public_values.len() {
+                    return Err(ZkProtocolError::Serialization(format!(
+                        "{} bytes of public values left after the last field",
+                        public_values.len() - reader.offset
+                    )));
+                }
+            }
+            OutputDescriptor::Raw => {
+                out.insert("public_values".to_string(), Value::String(hex_string(public_values)));
+            }
+        }
+        Ok(Value::Object(out))
+    }
+}
+
+fn abi_type(ty: &str) -> Result<AbiType, ZkProtocolError> {
+    Ok(match ty {
+        "uint256" => AbiType::Uint,
+        "bool" => AbiType::Bool,
+        "address" => AbiType::Address,
+        "bytes32" => AbiType::FixedBytes32,
+        "bytes" => AbiType::Bytes,
+        "string" => AbiType::String,
+        _ => return Err(ZkProtocolError::Abi(format!("unsupported ABI type {:?}", ty))),
+    })
+}
+
+fn abi_json(value: AbiValue) -> Value {
+    match value {
+        AbiValue::Uint(n) => uint_json(n),
+        AbiValue::Bool(b) => Value::Bool(b),
+        AbiValue::Address(addr) => Value::String(hex_string(&addr)),
+        AbiValue::FixedBytes32(bytes) => Value::String(hex_string(&bytes)),
+        AbiValue::Bytes(bytes) => Value::String(hex_string(&bytes)),
+        AbiValue::String(s) => Value::String(s),
+    }
+}
+
+fn uint_json(n: u128) -> Value {
+    u64::try_from(n).map(Value::from).unwrap_or_else(|_| Value::String(n.to_string()))
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[derive(Clone, Copy)]
+enum BincodeType {
+    Bool,
+    /// Unsigned, width in bytes
+    Uint(usize),
+    /// Signed, width in bytes
+    Int(usize),
+    F32,
+    F64,
+    String,
+    Bytes,
+}
+
+impl BincodeType {
+    fn parse(ty: &str) -> Result<Self, ZkProtocolError> {
+        Ok(match ty {
+            "bool" => BincodeType::Bool,
+            "u8" => BincodeType::Uint(1),
+            "u16" => BincodeType::Uint(2),
+            "u32" => BincodeType::Uint(4),
+            "u64" => BincodeType::Uint(8),
+            "i8" => BincodeType::Int(1),
+            "i16" => BincodeType::Int(2),
+            "i32" => BincodeType::Int(4),
+            "i64" => BincodeType::Int(8),
+            "f32" => BincodeType::F32,
+            "f64" => BincodeType::F64,
+            "string" => BincodeType::String,
+            "bytes" => BincodeType::Bytes,
+            _ => return Err(ZkProtocolError::Serialization(format!("unsupported bincode type {:?}", ty))),
+        })
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], ZkProtocolError> {
+        let bytes = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.offset..end))
+            .ok_or_else(|| ZkProtocolError::Serialization(format!("public values end at offset {}", self.offset)))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// Little-endian, zero-extended to 8 bytes
+    fn word(&mut self, width: usize) -> Result<[u8; 8], ZkProtocolError> {
+        let mut word = [0u8; 8];
+        word[..width].copy_from_slice(self.take(width)?);
+        Ok(word)
+    }
+
+    fn read(&mut self, ty: BincodeType) -> Result<Value, ZkProtocolError> {
+        Ok(match ty {
+            BincodeType::Bool => match self.take(1)?[0] {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                b => return Err(ZkProtocolError::Serialization(format!("invalid bool byte {}", b))),
+            },
+            BincodeType::Uint(width) => Value::from(u64::from_le_bytes(self.word(width)?)),
+            BincodeType::Int(width) => {
+                // Sign-extend from the field's width
+                let shift = 64 - 8 * width as u32;
+                Value::from((i64::from_le_bytes(self.word(width)?) << shift) >> shift)
+            }
+            BincodeType::F32 => float_json(f32::from_le_bytes(self.word(4)?[..4].try_into().unwrap()) as f64),
+            BincodeType::F64 => float_json(f64::from_le_bytes(self.word(8)?)),
+            BincodeType::String => {
+                let bytes = self.length_prefixed()?;
+                let s = core::str::from_utf8(bytes)
+                    .map_err(|e| ZkProtocolError::Serialization(format!("invalid UTF-8 string: {}", e)))?;
+                Value::String(s.to_string())
+            }
+            BincodeType::Bytes => Value::String(hex_string(self.length_prefixed()?)),
+        })
+    }
+
+    fn length_prefixed(&mut self) -> Result<&[u8], ZkProtocolError> {
+        let len = u64::from_le_bytes(self.word(8)?);
+        let len = usize::try_from(len).map_err(|_| ZkProtocolError::Serialization("length too large".into()))?;
+        self.take(len)
+    }
+}
+
+/// NaN and infinities have no JSON form
+fn float_json(f: f64) -> Value {
+    serde_json::Number::from_f64(f).map(Value::Number).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(spec: &[(&str, &str)]) -> Vec<OutputField> {
+        spec.iter().map(|(name, ty)| OutputField { name: (*name).into(), ty: (*ty).into() }).collect()
+    }
+
+    #[test]
+    fn test_decodes_abi_bincode_and_raw_public_values() {
+        let abi_values = abi::encode(&[AbiValue::String("NYC".into()), AbiValue::Uint(57800), AbiValue::Bool(true)]);
+        let descriptor = OutputDescriptor::Abi { fields: fields(&[("from", "string"), ("priceCents", "uint256"), ("vip", "bool")]) };
+        assert_eq!(
+            descriptor.decode(&abi_values).unwrap(),
+            serde_json::json!({"from": "NYC", "priceCents": 57800, "vip": true})
+        );
+
+        // struct { price: f64, seats: i16, route: String }
+        let mut bincode_values = Vec::new();
+        bincode_values.extend_from_slice(&682.5f64.to_le_bytes());
+        bincode_values.extend_from_slice(&(-2i16).to_le_bytes());
+        bincode_values.extend_from_slice(&7u64.to_le_bytes());
+        bincode_values.extend_from_slice(b"NYC-LON");
+        let descriptor = OutputDescriptor::Bincode { fields: fields(&[("price", "f64"), ("seats", "i16"), ("route", "string")]) };
+        assert_eq!(
+            descriptor.decode(&bincode_values).unwrap(),
+            serde_json::json!({"price": 682.5, "seats": -2, "route": "NYC-LON"})
+        );
+        assert!(descriptor.decode(&bincode_values[..12]).is_err());
+        let mut trailing = bincode_values.clone();
+        trailing.push(0);
+        assert!(descriptor.decode(&trailing).is_err());
+
+        assert_eq!(OutputDescriptor::Raw.decode(&[0xab]).unwrap(), serde_json::json!({"public_values": "0xab"}));
+
+        let parsed: OutputDescriptor = serde_json::from_str(r#"{"format": "bincode", "fields": [{"name": "n", "type": "u128"}]}"#).unwrap();
+        assert!(parsed.validate().is_err());
+    }
+}
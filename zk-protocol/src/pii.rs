@@ -0,0 +1,87 @@
+//! Salted commitments to passenger details
+//!
+//! Booking proofs commit a [`PiiCommitment`] instead of the passenger's name and email, so
+//! public values posted on-chain reveal nothing about who travels. The salt is random per
+//! booking and only goes to the booking agent (in the /book response), which hands it to the
+//! passenger. With it, the passenger can later show a booking was theirs by revealing name,
+//! email, and salt to whoever they choose, who recomputes the commitment.
+//!
+//! Names are compared ignoring case and spacing, emails ignoring case, as Agent B does when
+//! detecting duplicate bookings.
+
+use alloc::{format, string::String, vec::Vec};
+use sha2::{Digest, Sha256};
+
+use crate::ZkProtocolError;
+
+/// Separates these hashes from every other SHA-256 in the protocol
+const DOMAIN: &[u8] = b"zeroproof-pii-v1";
+
+/// `sha256(DOMAIN || salt || len(name) || name || len(email) || email)`, lengths as
+/// big-endian u32
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PiiCommitment(pub [u8; 32]);
+
+impl PiiCommitment {
+    pub const SALT_LEN: usize = 32;
+
+    pub fn new(salt: &[u8; 32], passenger_name: &str, passenger_email: &str) -> Self {
+        let name = passenger_name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+        let email = passenger_email.trim().to_lowercase();
+        let mut hasher = Sha256::new();
+        hasher.update(DOMAIN);
+        hasher.update(salt);
+        for field in [name.as_bytes(), email.as_bytes()] {
+            hasher.update((field.len() as u32).to_be_bytes());
+            hasher.update(field);
+        }
+        Self(hasher.finalize().into())
+    }
+
+    /// Whether `salt`, name, and email open this commitment
+    pub fn opens_to(&self, salt: &[u8; 32], passenger_name: &str, passenger_email: &str) -> bool {
+        *self == Self::new(salt, passenger_name, passenger_email)
+    }
+
+    /// 0x-prefixed hex, as in /book responses and verified output
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.0))
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self, ZkProtocolError> {
+        Ok(Self(bytes32(s, "commitment")?))
+    }
+
+    /// Salt from its 0x-optional hex form
+    pub fn parse_salt(s: &str) -> Result<[u8; 32], ZkProtocolError> {
+        bytes32(s, "pii_salt")
+    }
+}
+
+fn bytes32(s: &str, what: &str) -> Result<[u8; 32], ZkProtocolError> {
+    let bytes = hex::decode(s.trim_start_matches("0x"))?;
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| ZkProtocolError::InvalidHex(format!("{} must be 32 bytes, got {}", what, b.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_opens_only_with_its_salt_and_passenger() {
+        let salt = [9u8; 32];
+        let commitment = PiiCommitment::new(&salt, "Jane Doe", "jane@example.com");
+
+        assert!(commitment.opens_to(&salt, " jane  DOE ", "Jane@Example.com"));
+        assert!(!commitment.opens_to(&[8u8; 32], "Jane Doe", "jane@example.com"));
+        assert!(!commitment.opens_to(&salt, "John Doe", "jane@example.com"));
+        // Length prefixes keep the split between name and email unambiguous
+        assert_ne!(PiiCommitment::new(&salt, "a", "bc"), PiiCommitment::new(&salt, "ab", "c"));
+
+        assert_eq!(PiiCommitment::from_hex(&commitment.to_hex()).unwrap(), commitment);
+        assert_eq!(PiiCommitment::parse_salt(&hex::encode(salt)).unwrap(), salt);
+        assert!(PiiCommitment::parse_salt("0xabcd").is_err());
+    }
+}
@@ -0,0 +1,641 @@
+//! Trusted Agent Protocol (TAP) request signatures
+//!
+//! TAP profiles RFC 9421 HTTP Message Signatures: the calling agent signs selected
+//! components of each request with its Ed25519 key and sends them as `Signature-Input`
+//! and `Signature` headers. The receiving server rebuilds the same signature base from
+//! the request it got and checks it against the agent's public key, so a request can't
+//! be forged or redirected to another host or path. With a body, the signature also
+//! covers its RFC 9530 `Content-Digest`, so the payload can't be swapped either.
+
+use base64::{
+    engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD as BASE64_URL},
+    Engine,
+};
+use ed25519_dalek::{Signature, Signer, VerifyingKey, Verifier};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::hex_to_bytes;
+use crate::identity::{AgentIdentity, ED25519_MULTICODEC};
+
+pub const SIGNATURE_INPUT_HEADER: &str = "signature-input";
+pub const SIGNATURE_HEADER: &str = "signature";
+pub const CONTENT_DIGEST_HEADER: &str = "content-digest";
+
+/// Dictionary label for the signature we create
+const LABEL: &str = "sig1";
+/// TAP caps a signature's lifetime at 8 minutes
+pub const MAX_VALIDITY_SECS: u64 = 480;
+/// Tolerated clock difference for `created`
+const CLOCK_SKEW_SECS: u64 = 60;
+/// TAP tag for browsing/booking requests (payment calls use "agent-payer-auth")
+pub const TAG_BROWSER_AUTH: &str = "agent-browser-auth";
+/// Covered when nothing else is configured
+pub const DEFAULT_COMPONENTS: &[&str] = &["@authority", "@path"];
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TapError {
+    #[error("missing {0} header")]
+    MissingHeader(&'static str),
+    #[error("malformed signature: {0}")]
+    Malformed(String),
+    #[error("signature expired")]
+    Expired,
+    #[error("signature created in the future")]
+    NotYetValid,
+    #[error("unsupported signature algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    #[error("invalid public key: {0}")]
+    InvalidKey(String),
+    #[error("signature does not match request")]
+    BadSignature,
+    #[error("Content-Digest does not match body")]
+    DigestMismatch,
+}
+
+/// Headers to add to a signed request
+#[derive(Debug, Clone)]
+pub struct TapHeaders {
+    pub signature_input: String,
+    pub signature: String,
+    /// Set when a body was signed; must be sent as `Content-Digest`
+    pub content_digest: Option<String>,
+}
+
+/// What a valid signature vouched for
+#[derive(Debug, Clone, PartialEq)]
+pub struct TapVerified {
+    /// `keyid` parameter (the signer's did:key)
+    pub key_id: String,
+    pub created: u64,
+    pub expires: u64,
+    pub nonce: Option<String>,
+    pub tag: Option<String>,
+    /// Covered components, in signing order
+    pub components: Vec<String>,
+}
+
+/// Signs one request; see [`create_tap_signature`] for the defaults
+pub struct TapHttpRequestBuilder<'a> {
+    identity: &'a AgentIdentity,
+    method: String,
+    url: String,
+    tag: String,
+    nonce: Option<String>,
+    created: Option<u64>,
+    validity_secs: u64,
+    components: Vec<String>,
+    /// Values of covered header fields
+    fields: Vec<(String, String)>,
+}
+
+impl<'a> TapHttpRequestBuilder<'a> {
+    pub fn new(identity: &'a AgentIdentity, method: &str, url: &str) -> Self {
+        Self {
+            identity,
+            method: method.to_uppercase(),
+            url: url.to_string(),
+            tag: TAG_BROWSER_AUTH.to_string(),
+            nonce: None,
+            created: None,
+            validity_secs: MAX_VALIDITY_SECS,
+            components: DEFAULT_COMPONENTS.iter().map(|c| c.to_string()).collect(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Replace the covered components (derived `@...` names or header fields)
+    ///
+    /// `@authority` and `@path` are always kept, since verifiers require them, as are
+    /// fields already given through [`header`](Self::header) or [`body`](Self::body).
+    pub fn components(mut self, components: &[&str]) -> Self {
+        let mut covered: Vec<String> = DEFAULT_COMPONENTS.iter().map(|c| c.to_string()).collect();
+        let fields = self.fields.iter().map(|(name, _)| name.clone());
+        for component in components.iter().map(|c| c.to_lowercase()).chain(fields) {
+            if !covered.contains(&component) {
+                covered.push(component);
+            }
+        }
+        self.components = covered;
+        self
+    }
+
+    /// Value of a header field the signature covers (added to the components)
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = name.to_lowercase();
+        if !self.components.contains(&name) {
+            self.components.push(name.clone());
+        }
+        self.fields.retain(|(field, _)| *field != name);
+        self.fields.push((name, value.trim().to_string()));
+        self
+    }
+
+    /// Bind the request body: covers its `Content-Digest`
+    pub fn body(self, body: &[u8]) -> Self {
+        self.header(CONTENT_DIGEST_HEADER, &content_digest(body))
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tag = tag.to_string();
+        self
+    }
+
+    /// Fixed nonce (a random one is generated otherwise)
+    pub fn nonce(mut self, nonce: &str) -> Self {
+        self.nonce = Some(nonce.to_string());
+        self
+    }
+
+    /// Signing time in unix seconds (now otherwise)
+    pub fn created(mut self, created: u64) -> Self {
+        self.created = Some(created);
+        self
+    }
+
+    /// Signature lifetime, capped at [`MAX_VALIDITY_SECS`]
+    pub fn validity_secs(mut self, secs: u64) -> Self {
+        self.validity_secs = secs.min(MAX_VALIDITY_SECS);
+        self
+    }
+
+    pub fn sign(self) -> Result<TapHeaders, TapError> {
+        let target = Target::parse(&self.method, &self.url)?;
+        let created = self.created.unwrap_or_else(unix_now);
+        let nonce = self.nonce.unwrap_or_else(|| {
+            let bytes: [u8; 16] = rand::random();
+            BASE64.encode(bytes)
+        });
+
+        let list = self.components.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(" ");
+        let params = format!(
+            "({});created={};expires={};keyid=\"{}\";alg=\"ed25519\";nonce=\"{}\";tag=\"{}\"",
+            list,
+            created,
+            created + self.validity_secs,
+            self.identity.did(),
+            nonce,
+            self.tag
+        );
+
+        let mut base = String::new();
+        for component in &self.components {
+            let value = if component.starts_with('@') {
+                target.derived(component)
+            } else {
+                self.fields.iter().find(|(name, _)| name == component).map(|(_, value)| value.clone())
+            }
+            .ok_or_else(|| TapError::Malformed(format!("no value for covered component {}", component)))?;
+            base.push_str(&format!("\"{}\": {}\n", component, value));
+        }
+        base.push_str(&format!("\"@signature-params\": {}", params));
+
+        let signature = self.identity.signing_key().sign(base.as_bytes());
+        Ok(TapHeaders {
+            signature_input: format!("{}={}", LABEL, params),
+            signature: format!("{}=:{}:", LABEL, BASE64.encode(signature.to_bytes())),
+            content_digest: self
+                .fields
+                .into_iter()
+                .find(|(name, _)| name == CONTENT_DIGEST_HEADER)
+                .map(|(_, value)| value),
+        })
+    }
+}
+
+/// Sign `method url` as the agent, covering `@authority` and `@path`
+pub fn create_tap_signature(identity: &AgentIdentity, method: &str, url: &str) -> Result<TapHeaders, TapError> {
+    TapHttpRequestBuilder::new(identity, method, url).sign()
+}
+
+/// RFC 9530 `Content-Digest` value for `body`: `sha-256=:<base64>:`
+pub fn content_digest(body: &[u8]) -> String {
+    format!("sha-256=:{}:", BASE64.encode(Sha256::digest(body)))
+}
+
+/// Check the request's `Content-Digest` against the body it arrived with
+///
+/// Only the signature binds the digest to the request; call this after
+/// [`verify_tap_signature`] reported `content-digest` among the covered components.
+pub fn verify_content_digest(headers: &http::HeaderMap, body: &[u8]) -> Result<(), TapError> {
+    let header = headers
+        .get(CONTENT_DIGEST_HEADER)
+        .ok_or(TapError::MissingHeader("Content-Digest"))?
+        .to_str()
+        .map_err(|_| TapError::Malformed("Content-Digest is not ASCII".to_string()))?;
+    let sha256 = dictionary_members(header)
+        .into_iter()
+        .find(|(algorithm, _)| algorithm == "sha-256")
+        .ok_or_else(|| TapError::Malformed("Content-Digest has no sha-256 member".to_string()))?;
+    if format!("sha-256={}", sha256.1) == content_digest(body) {
+        Ok(())
+    } else {
+        Err(TapError::DigestMismatch)
+    }
+}
+
+/// `keyid` of the request's signature, so the caller can pick the key to verify with
+pub fn tap_key_id(headers: &http::HeaderMap) -> Result<String, TapError> {
+    let (_, input) = signature_input(headers)?;
+    input.param("keyid").ok_or_else(|| TapError::Malformed("missing keyid".to_string()))
+}
+
+/// Check the TAP signature on a request against `public_key` (hex Ed25519)
+///
+/// `url` must be the URL the request was sent to (scheme, host, path, query). Rejects
+/// signatures that are expired, not yet valid, longer-lived than TAP allows, or that
+/// don't cover `@authority` and `@path`.
+pub fn verify_tap_signature(
+    headers: &http::HeaderMap,
+    method: &str,
+    url: &str,
+    public_key: &str,
+) -> Result<TapVerified, TapError> {
+    let (label, input) = signature_input(headers)?;
+    let signature = signature_bytes(headers, &label)?;
+
+    if let Some(alg) = input.param("alg") {
+        if alg != "ed25519" {
+            return Err(TapError::UnsupportedAlgorithm(alg));
+        }
+    }
+    let number = |name: &str| -> Result<u64, TapError> {
+        input
+            .param(name)
+            .ok_or_else(|| TapError::Malformed(format!("missing {}", name)))?
+            .parse()
+            .map_err(|_| TapError::Malformed(format!("{} is not a timestamp", name)))
+    };
+    let (created, expires) = (number("created")?, number("expires")?);
+    if expires <= created || expires - created > MAX_VALIDITY_SECS {
+        return Err(TapError::Malformed("expires must be within 8 minutes after created".to_string()));
+    }
+    let now = unix_now();
+    if created > now + CLOCK_SKEW_SECS {
+        return Err(TapError::NotYetValid);
+    }
+    if now >= expires {
+        return Err(TapError::Expired);
+    }
+    for required in ["@authority", "@path"] {
+        if !input.components.iter().any(|c| c == required) {
+            return Err(TapError::Malformed(format!("{} is not covered", required)));
+        }
+    }
+
+    let target = Target::parse(method, url)?;
+    let mut base = String::new();
+    for component in &input.components {
+        let value = if component.starts_with('@') {
+            target.derived(component)
+        } else {
+            headers.get(component.as_str()).and_then(|v| v.to_str().ok()).map(|v| v.trim().to_string())
+        }
+        .ok_or_else(|| TapError::Malformed(format!("covered component {} not in request", component)))?;
+        base.push_str(&format!("\"{}\": {}\n", component, value));
+    }
+    base.push_str(&format!("\"@signature-params\": {}", input.raw));
+
+    let key_bytes: [u8; 32] = hex_to_bytes(public_key)
+        .map_err(|e| TapError::InvalidKey(e.to_string()))?
+        .try_into()
+        .map_err(|_| TapError::InvalidKey("public key must be 32 bytes".to_string()))?;
+    let key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| TapError::InvalidKey(e.to_string()))?;
+    key.verify(base.as_bytes(), &signature).map_err(|_| TapError::BadSignature)?;
+
+    Ok(TapVerified {
+        key_id: input.param("keyid").unwrap_or_default(),
+        created,
+        expires,
+        nonce: input.param("nonce"),
+        tag: input.param("tag"),
+        components: input.components,
+    })
+}
+
+/// Hex Ed25519 public key of a `did:key`
+pub fn public_key_from_did(did: &str) -> Result<String, TapError> {
+    let encoded = did
+        .strip_prefix("did:key:z")
+        .ok_or_else(|| TapError::InvalidKey(format!("not a did:key: {}", did)))?;
+    let bytes = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| TapError::InvalidKey(e.to_string()))?;
+    match bytes.strip_prefix(&ED25519_MULTICODEC[..]) {
+        Some(key) if key.len() == 32 => Ok(format!("0x{}", hex::encode(key))),
+        _ => Err(TapError::InvalidKey(format!("not an Ed25519 did:key: {}", did))),
+    }
+}
+
+/// Published form of a TAP key: an RFC 8037 Ed25519 JWK whose `kid` is the key's did:key
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Jwk {
+    /// "OKP"
+    pub kty: String,
+    /// "Ed25519"
+    pub crv: String,
+    /// Base64url public key
+    pub x: String,
+    pub kid: String,
+    #[serde(rename = "use", default)]
+    pub key_use: Option<String>,
+    #[serde(default)]
+    pub alg: Option<String>,
+    /// Unix seconds after which the key is withdrawn (rotated-out keys)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+}
+
+impl Jwk {
+    pub fn from_identity(identity: &AgentIdentity, exp: Option<u64>) -> Self {
+        Self {
+            kty: "OKP".to_string(),
+            crv: "Ed25519".to_string(),
+            x: BASE64_URL.encode(identity.signing_key().verifying_key().as_bytes()),
+            kid: identity.did(),
+            key_use: Some("sig".to_string()),
+            alg: Some("EdDSA".to_string()),
+            exp,
+        }
+    }
+
+    /// Hex public key, for [`verify_tap_signature`]
+    pub fn public_key_hex(&self) -> Result<String, TapError> {
+        if self.kty != "OKP" || self.crv != "Ed25519" {
+            return Err(TapError::InvalidKey(format!("{} is not an Ed25519 key", self.kid)));
+        }
+        let key = BASE64_URL
+            .decode(&self.x)
+            .map_err(|e| TapError::InvalidKey(e.to_string()))?;
+        if key.len() != 32 {
+            return Err(TapError::InvalidKey("public key must be 32 bytes".to_string()));
+        }
+        Ok(format!("0x{}", hex::encode(key)))
+    }
+}
+
+/// Key set served at `/.well-known/tap-jwks.json`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+impl Jwks {
+    /// Hex public key for `kid`, skipping keys past their `exp` at `now`
+    pub fn public_key(&self, kid: &str, now: u64) -> Option<String> {
+        self.keys
+            .iter()
+            .filter(|key| key.exp.is_none_or(|exp| exp > now))
+            .find(|key| key.kid == kid)
+            .and_then(|key| key.public_key_hex().ok())
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Request target split into the parts RFC 9421 derived components use
+struct Target {
+    method: String,
+    scheme: String,
+    authority: String,
+    path: String,
+    query: Option<String>,
+}
+
+impl Target {
+    fn parse(method: &str, url: &str) -> Result<Self, TapError> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| TapError::Malformed(format!("not an absolute URL: {}", url)))?;
+        let rest = rest.split('#').next().unwrap_or_default();
+        let (authority, path_and_query) = match rest.find(['/', '?']) {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+        let (path, query) = match path_and_query.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (path_and_query, None),
+        };
+        Ok(Self {
+            method: method.to_uppercase(),
+            scheme: scheme.to_lowercase(),
+            authority: authority.to_lowercase(),
+            path: if path.is_empty() { "/".to_string() } else { path.to_string() },
+            query,
+        })
+    }
+
+    fn derived(&self, component: &str) -> Option<String> {
+        let query = || format!("?{}", self.query.as_deref().unwrap_or_default());
+        Some(match component {
+            "@method" => self.method.clone(),
+            "@authority" => self.authority.clone(),
+            "@scheme" => self.scheme.clone(),
+            "@path" => self.path.clone(),
+            "@query" => query(),
+            "@request-target" => match &self.query {
+                Some(_) => format!("{}{}", self.path, query()),
+                None => self.path.clone(),
+            },
+            "@target-uri" => match &self.query {
+                Some(_) => format!("{}://{}{}{}", self.scheme, self.authority, self.path, query()),
+                None => format!("{}://{}{}", self.scheme, self.authority, self.path),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// Parsed `Signature-Input` member
+struct SignatureInput {
+    components: Vec<String>,
+    /// `name=value` parameters with quotes removed
+    params: Vec<(String, String)>,
+    /// The member value exactly as sent, used as `@signature-params`
+    raw: String,
+}
+
+impl SignatureInput {
+    fn param(&self, name: &str) -> Option<String> {
+        self.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+    }
+}
+
+/// First member of `Signature-Input` (the one labelled `sig1` if several)
+fn signature_input(headers: &http::HeaderMap) -> Result<(String, SignatureInput), TapError> {
+    let header = headers
+        .get(SIGNATURE_INPUT_HEADER)
+        .ok_or(TapError::MissingHeader("Signature-Input"))?
+        .to_str()
+        .map_err(|_| TapError::Malformed("Signature-Input is not ASCII".to_string()))?;
+    let members = dictionary_members(header);
+    let (label, raw) = members
+        .iter()
+        .find(|(label, _)| label == LABEL)
+        .or(members.first())
+        .cloned()
+        .ok_or_else(|| TapError::Malformed("empty Signature-Input".to_string()))?;
+
+    let malformed = || TapError::Malformed("Signature-Input must be (components);params".to_string());
+    let inner = raw.strip_prefix('(').ok_or_else(malformed)?;
+    let (list, params) = inner.split_once(')').ok_or_else(malformed)?;
+    let components = list
+        .split_whitespace()
+        .map(|c| c.strip_prefix('"').and_then(|c| c.strip_suffix('"')).map(str::to_string).ok_or_else(malformed))
+        .collect::<Result<Vec<_>, _>>()?;
+    let params = params
+        .split(';')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').ok_or_else(malformed)?;
+            Ok((k.trim().to_string(), v.trim().trim_matches('"').to_string()))
+        })
+        .collect::<Result<Vec<_>, TapError>>()?;
+
+    Ok((label, SignatureInput { components, params, raw }))
+}
+
+fn signature_bytes(headers: &http::HeaderMap, label: &str) -> Result<Signature, TapError> {
+    let header = headers
+        .get(SIGNATURE_HEADER)
+        .ok_or(TapError::MissingHeader("Signature"))?
+        .to_str()
+        .map_err(|_| TapError::Malformed("Signature is not ASCII".to_string()))?;
+    let value = dictionary_members(header)
+        .into_iter()
+        .find(|(l, _)| l == label)
+        .map(|(_, v)| v)
+        .ok_or_else(|| TapError::Malformed(format!("no Signature for {}", label)))?;
+    let encoded = value
+        .strip_prefix(':')
+        .and_then(|v| v.strip_suffix(':'))
+        .ok_or_else(|| TapError::Malformed("Signature must be :base64:".to_string()))?;
+    let bytes: [u8; 64] = BASE64
+        .decode(encoded)
+        .map_err(|e| TapError::Malformed(e.to_string()))?
+        .try_into()
+        .map_err(|_| TapError::Malformed("Ed25519 signature must be 64 bytes".to_string()))?;
+    Ok(Signature::from_bytes(&bytes))
+}
+
+/// Split a structured-field dictionary into `(label, value)` at top-level commas
+fn dictionary_members(header: &str) -> Vec<(String, String)> {
+    let mut members = Vec::new();
+    let (mut depth, mut quoted, mut start) = (0, false, 0);
+    let bytes = header.as_bytes();
+    for i in 0..=bytes.len() {
+        let c = bytes.get(i).copied();
+        match c {
+            Some(b'"') => quoted = !quoted,
+            Some(b'(') if !quoted => depth += 1,
+            Some(b')') if !quoted => depth -= 1,
+            Some(b',') | None if !quoted && depth == 0 => {
+                if let Some((label, value)) = header[start..i].split_once('=') {
+                    members.push((label.trim().to_string(), value.trim().to_string()));
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::{HeaderMap, HeaderValue};
+
+    fn signed(identity: &AgentIdentity, created: u64) -> HeaderMap {
+        let tap = TapHttpRequestBuilder::new(identity, "POST", "https://agent-b.example/price?x=1")
+            .created(created)
+            .nonce("n-1")
+            .sign()
+            .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(SIGNATURE_INPUT_HEADER, HeaderValue::from_str(&tap.signature_input).unwrap());
+        headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(&tap.signature).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_jwks_resolves_keys_by_kid() {
+        let identity = AgentIdentity::generate();
+        let retired = AgentIdentity::generate();
+        let jwks = Jwks {
+            keys: vec![Jwk::from_identity(&identity, None), Jwk::from_identity(&retired, Some(100))],
+        };
+        let json = serde_json::to_string(&jwks).unwrap();
+        let parsed: Jwks = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.public_key(&identity.did(), 200), Some(identity.public_key_hex()));
+        assert_eq!(parsed.public_key(&retired.did(), 50), Some(retired.public_key_hex()));
+        assert_eq!(parsed.public_key(&retired.did(), 200), None);
+    }
+
+    #[test]
+    fn test_body_is_bound_through_content_digest() {
+        let identity = AgentIdentity::generate();
+        let url = "https://agent-b.example/book";
+        let body = br#"{"from":"NYC","to":"LON"}"#;
+        let tap = TapHttpRequestBuilder::new(&identity, "POST", url)
+            .components(&["@method"])
+            .body(body)
+            .sign()
+            .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(SIGNATURE_INPUT_HEADER, HeaderValue::from_str(&tap.signature_input).unwrap());
+        headers.insert(SIGNATURE_HEADER, HeaderValue::from_str(&tap.signature).unwrap());
+        headers.insert(CONTENT_DIGEST_HEADER, HeaderValue::from_str(&tap.content_digest.unwrap()).unwrap());
+
+        let verified = verify_tap_signature(&headers, "POST", url, &identity.public_key_hex()).unwrap();
+        assert_eq!(verified.components, ["@authority", "@path", "@method", "content-digest"]);
+        assert!(verify_content_digest(&headers, body).is_ok());
+        assert_eq!(verify_content_digest(&headers, b"{}"), Err(TapError::DigestMismatch));
+
+        // Swapping the digest header breaks the signature
+        headers.insert(CONTENT_DIGEST_HEADER, HeaderValue::from_str(&content_digest(b"{}")).unwrap());
+        assert_eq!(
+            verify_tap_signature(&headers, "POST", url, &identity.public_key_hex()),
+            Err(TapError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_signature_roundtrip_and_rejections() {
+        let identity = AgentIdentity::generate();
+        let key = public_key_from_did(&identity.did()).unwrap();
+        assert_eq!(key, identity.public_key_hex());
+
+        let headers = signed(&identity, unix_now());
+        let url = "https://agent-b.example/price?x=1";
+        let verified = verify_tap_signature(&headers, "POST", url, &key).unwrap();
+        assert_eq!(verified.key_id, identity.did());
+        assert_eq!(verified.nonce.as_deref(), Some("n-1"));
+        assert_eq!(tap_key_id(&headers).unwrap(), identity.did());
+
+        assert_eq!(
+            verify_tap_signature(&headers, "POST", "https://agent-b.example/book", &key),
+            Err(TapError::BadSignature)
+        );
+        let other = AgentIdentity::generate().public_key_hex();
+        assert_eq!(verify_tap_signature(&headers, "POST", url, &other), Err(TapError::BadSignature));
+
+        let stale = signed(&identity, unix_now() - MAX_VALIDITY_SECS - 1);
+        assert_eq!(verify_tap_signature(&stale, "POST", url, &key), Err(TapError::Expired));
+
+        let mut broken = headers.clone();
+        broken.insert(SIGNATURE_INPUT_HEADER, HeaderValue::from_static("sig1=\"@path\";created=1"));
+        assert!(matches!(verify_tap_signature(&broken, "POST", url, &key), Err(TapError::Malformed(_))));
+        assert_eq!(
+            verify_tap_signature(&HeaderMap::new(), "POST", url, &key),
+            Err(TapError::MissingHeader("Signature-Input"))
+        );
+    }
+}